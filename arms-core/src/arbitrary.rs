@@ -0,0 +1,33 @@
+//! proptest `Arbitrary` for `Point`
+//!
+//! Points take their dimensionality as the strategy parameter
+//! (`any_with::<Point>(Some(128))`); `None` picks one in
+//! `1..=MAX_DIMENSIONALITY`. Coordinates are finite and never all zero.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::Point;
+
+/// Largest dimensionality picked when none is given
+pub const MAX_DIMENSIONALITY: usize = 32;
+
+/// Coordinates of one non-zero point
+pub fn coordinates(dimensionality: Option<usize>) -> BoxedStrategy<Vec<f32>> {
+    let len = match dimensionality {
+        Some(d) => d..=d,
+        None => 1..=MAX_DIMENSIONALITY,
+    };
+    len.prop_flat_map(|d| vec(-1.0f32..1.0, d))
+        .prop_filter("zero vector", |dims| dims.iter().any(|&x| x != 0.0))
+        .boxed()
+}
+
+impl Arbitrary for Point {
+    type Parameters = Option<usize>;
+    type Strategy = BoxedStrategy<Point>;
+
+    fn arbitrary_with(dimensionality: Self::Parameters) -> Self::Strategy {
+        coordinates(dimensionality).prop_map(Point::new).boxed()
+    }
+}
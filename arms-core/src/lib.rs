@@ -0,0 +1,36 @@
+//! # ARMS Core
+//!
+//! The pure math of ARMS, split out so it compiles under `no_std + alloc`:
+//! - `Point` - A position in dimensional space
+//! - `Proximity` - Trait for measuring relatedness
+//! - `Merge` - Trait for composing points
+//!
+//! Embedded and edge inference devices can score and merge embeddings with
+//! exactly the functions the full engine (`arms-hat`) uses, without its
+//! storage, indexes, clocks or threads.
+//!
+//! ```text
+//! arms-core = { version = "0.1", default-features = false, features = ["libm"] }
+//! ```
+//!
+//! Float math comes from `std` by default, or from `libm` without it.
+//! `arms-hat` re-exports everything here under `arms_hat::core`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("arms-core needs either the `std` or the `libm` feature for float math");
+
+mod math;
+mod point;
+pub mod proximity;
+pub mod merge;
+
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+
+pub use point::Point;
+pub use proximity::{Proximity, ScoreOrder};
+pub use merge::Merge;
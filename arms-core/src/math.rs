@@ -0,0 +1,39 @@
+//! # Float Math
+//!
+//! The few `f32` functions core needs, from `std` or, without it, `libm`.
+//!
+//! `f32::sqrt` and friends live in `std`, not `core`, so `no_std` builds
+//! (feature `libm` instead of `std`) route them through libm's software
+//! implementations. Results may differ from std's in the last bit.
+
+#[cfg(feature = "std")]
+mod imp {
+    pub fn sqrt(x: f32) -> f32 {
+        x.sqrt()
+    }
+
+    pub fn exp(x: f32) -> f32 {
+        x.exp()
+    }
+
+    pub fn powi(x: f32, n: i32) -> f32 {
+        x.powi(n)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod imp {
+    pub fn sqrt(x: f32) -> f32 {
+        libm::sqrtf(x)
+    }
+
+    pub fn exp(x: f32) -> f32 {
+        libm::expf(x)
+    }
+
+    pub fn powi(x: f32, n: i32) -> f32 {
+        libm::powf(x, n as f32)
+    }
+}
+
+pub(crate) use imp::{exp, powi, sqrt};
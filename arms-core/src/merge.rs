@@ -0,0 +1,768 @@
+//! # Merge
+//!
+//! Trait and implementations for composing multiple points into one.
+//!
+//! This is one of the five primitives of ARMS:
+//! `Merge: fn(points) -> point` - Compose together
+//!
+//! Merge is used for hierarchical composition:
+//! - Chunks → Document
+//! - Documents → Session
+//! - Sessions → Domain
+//!
+//! Merge functions are pluggable - use whichever fits your use case.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::math;
+
+use crate::proximity::{Cosine, Proximity, ScoreOrder};
+use crate::Point;
+
+/// Trait for merging multiple points into one
+///
+/// Used for hierarchical composition and aggregation.
+pub trait Merge: Send + Sync {
+    /// Merge multiple points into a single point
+    ///
+    /// All points must have the same dimensionality.
+    /// The slice must not be empty.
+    fn merge(&self, points: &[Point]) -> Point;
+
+    /// Name of this merge function (for debugging/config)
+    fn name(&self) -> &'static str;
+
+    /// Descriptor that `parse` turns back into this function
+    ///
+    /// Defaults to `name`; parameterized functions include their settings.
+    fn describe(&self) -> String {
+        self.name().to_string()
+    }
+}
+
+// ============================================================================
+// IMPLEMENTATIONS
+// ============================================================================
+
+/// Mean (average) of all points
+///
+/// The centroid of the input points.
+/// Good default for most hierarchical composition.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Mean;
+
+impl Merge for Mean {
+    fn merge(&self, points: &[Point]) -> Point {
+        assert!(!points.is_empty(), "Cannot merge empty slice");
+
+        let dims = points[0].dimensionality();
+        let n = points.len() as f32;
+
+        let mut result = vec![0.0; dims];
+        for p in points {
+            assert_eq!(
+                p.dimensionality(),
+                dims,
+                "All points must have same dimensionality"
+            );
+            for (r, d) in result.iter_mut().zip(p.dims()) {
+                *r += d / n;
+            }
+        }
+
+        Point::new(result)
+    }
+
+    fn name(&self) -> &'static str {
+        "mean"
+    }
+}
+
+/// Weighted mean of points
+///
+/// Each point contributes proportionally to its weight.
+/// Useful for recency weighting, importance weighting, etc.
+#[derive(Clone, Debug)]
+pub struct WeightedMean {
+    weights: Vec<f32>,
+}
+
+impl WeightedMean {
+    /// Create a new weighted mean with given weights
+    ///
+    /// Weights will be normalized (divided by sum) during merge.
+    pub fn new(weights: Vec<f32>) -> Self {
+        Self { weights }
+    }
+
+    /// Create with uniform weights (equivalent to Mean)
+    pub fn uniform(n: usize) -> Self {
+        Self {
+            weights: vec![1.0; n],
+        }
+    }
+
+    /// Create with recency weighting (more recent = higher weight)
+    ///
+    /// `decay` should be in (0, 1). Smaller = faster decay.
+    /// First point is oldest, last is most recent.
+    pub fn recency(n: usize, decay: f32) -> Self {
+        let weights: Vec<f32> = (0..n).map(|i| math::powi(decay, (n - 1 - i) as i32)).collect();
+        Self { weights }
+    }
+}
+
+impl Merge for WeightedMean {
+    fn merge(&self, points: &[Point]) -> Point {
+        assert!(!points.is_empty(), "Cannot merge empty slice");
+        assert_eq!(
+            points.len(),
+            self.weights.len(),
+            "Number of points must match number of weights"
+        );
+
+        let dims = points[0].dimensionality();
+        let total_weight: f32 = self.weights.iter().sum();
+
+        let mut result = vec![0.0; dims];
+        for (p, &w) in points.iter().zip(&self.weights) {
+            assert_eq!(
+                p.dimensionality(),
+                dims,
+                "All points must have same dimensionality"
+            );
+            let normalized_w = w / total_weight;
+            for (r, d) in result.iter_mut().zip(p.dims()) {
+                *r += d * normalized_w;
+            }
+        }
+
+        Point::new(result)
+    }
+
+    fn name(&self) -> &'static str {
+        "weighted_mean"
+    }
+}
+
+/// Max pooling across points
+///
+/// Takes the maximum value of each dimension across all points.
+/// Preserves the strongest activations.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaxPool;
+
+impl Merge for MaxPool {
+    fn merge(&self, points: &[Point]) -> Point {
+        assert!(!points.is_empty(), "Cannot merge empty slice");
+
+        let dims = points[0].dimensionality();
+        let mut result = points[0].dims().to_vec();
+
+        for p in &points[1..] {
+            assert_eq!(
+                p.dimensionality(),
+                dims,
+                "All points must have same dimensionality"
+            );
+            for (r, d) in result.iter_mut().zip(p.dims()) {
+                *r = r.max(*d);
+            }
+        }
+
+        Point::new(result)
+    }
+
+    fn name(&self) -> &'static str {
+        "max_pool"
+    }
+}
+
+/// Min pooling across points
+///
+/// Takes the minimum value of each dimension across all points.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinPool;
+
+impl Merge for MinPool {
+    fn merge(&self, points: &[Point]) -> Point {
+        assert!(!points.is_empty(), "Cannot merge empty slice");
+
+        let dims = points[0].dimensionality();
+        let mut result = points[0].dims().to_vec();
+
+        for p in &points[1..] {
+            assert_eq!(
+                p.dimensionality(),
+                dims,
+                "All points must have same dimensionality"
+            );
+            for (r, d) in result.iter_mut().zip(p.dims()) {
+                *r = r.min(*d);
+            }
+        }
+
+        Point::new(result)
+    }
+
+    fn name(&self) -> &'static str {
+        "min_pool"
+    }
+}
+
+/// Sum of all points (no averaging)
+///
+/// Simple additive composition.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sum;
+
+impl Merge for Sum {
+    fn merge(&self, points: &[Point]) -> Point {
+        assert!(!points.is_empty(), "Cannot merge empty slice");
+
+        let dims = points[0].dimensionality();
+        let mut result = vec![0.0; dims];
+
+        for p in points {
+            assert_eq!(
+                p.dimensionality(),
+                dims,
+                "All points must have same dimensionality"
+            );
+            for (r, d) in result.iter_mut().zip(p.dims()) {
+                *r += d;
+            }
+        }
+
+        Point::new(result)
+    }
+
+    fn name(&self) -> &'static str {
+        "sum"
+    }
+}
+
+/// Attention-weighted mean of points
+///
+/// Each point is weighted by the softmax of its proximity to an anchor
+/// (typically a query), so points related to the anchor dominate the result.
+/// Useful for query-conditioned summaries of a session.
+///
+/// Distances are negated before the softmax, so the closest point always
+/// gets the largest weight. Lower `temperature` sharpens the weighting
+/// toward the single most related point; higher flattens it toward `Mean`.
+#[derive(Clone)]
+pub struct AttentionMerge {
+    anchor: Point,
+    temperature: f32,
+    proximity: Arc<dyn Proximity>,
+}
+
+impl AttentionMerge {
+    /// Create with cosine proximity and temperature 1.0
+    pub fn new(anchor: Point) -> Self {
+        Self {
+            anchor,
+            temperature: 1.0,
+            proximity: Arc::new(Cosine),
+        }
+    }
+
+    /// Set the softmax temperature
+    ///
+    /// # Panics
+    /// If `temperature` is not positive.
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        assert!(temperature > 0.0, "Temperature must be positive");
+        self.temperature = temperature;
+        self
+    }
+
+    /// Set the proximity function used to score points against the anchor
+    pub fn with_proximity(mut self, proximity: Arc<dyn Proximity>) -> Self {
+        self.proximity = proximity;
+        self
+    }
+
+    /// The anchor points are weighted against
+    pub fn anchor(&self) -> &Point {
+        &self.anchor
+    }
+
+    /// Softmax weights the merge would give each point (sum to 1)
+    pub fn weights(&self, points: &[Point]) -> Vec<f32> {
+        let order = self.proximity.order();
+        let logits: Vec<f32> = points
+            .iter()
+            .map(|p| {
+                assert_eq!(
+                    p.dimensionality(),
+                    self.anchor.dimensionality(),
+                    "All points must have same dimensionality"
+                );
+                let score = self.proximity.proximity(&self.anchor, p);
+                let logit = match order {
+                    ScoreOrder::HigherIsBetter => score,
+                    ScoreOrder::LowerIsBetter => -score,
+                };
+                logit / self.temperature
+            })
+            .collect();
+
+        // Subtract the max logit so exp() can't overflow
+        let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let exps: Vec<f32> = logits.iter().map(|l| math::exp(l - max)).collect();
+        let total: f32 = exps.iter().sum();
+        exps.into_iter().map(|e| e / total).collect()
+    }
+}
+
+impl fmt::Debug for AttentionMerge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttentionMerge")
+            .field("anchor", &self.anchor)
+            .field("temperature", &self.temperature)
+            .field("proximity", &self.proximity.name())
+            .finish()
+    }
+}
+
+impl Merge for AttentionMerge {
+    fn merge(&self, points: &[Point]) -> Point {
+        assert!(!points.is_empty(), "Cannot merge empty slice");
+
+        let weights = self.weights(points);
+        let mut result = vec![0.0; self.anchor.dimensionality()];
+        for (p, w) in points.iter().zip(weights) {
+            for (r, d) in result.iter_mut().zip(p.dims()) {
+                *r += d * w;
+            }
+        }
+
+        Point::new(result)
+    }
+
+    fn name(&self) -> &'static str {
+        "attention"
+    }
+}
+
+/// Geometric median of points
+///
+/// The point minimizing the sum of Euclidean distances to all inputs,
+/// found by Weiszfeld iteration starting from the mean. Unlike the mean,
+/// a few outliers (e.g., a pasted stack trace among prose chunks) can't
+/// drag it arbitrarily far.
+#[derive(Clone, Copy, Debug)]
+pub struct GeometricMedian {
+    /// Maximum Weiszfeld iterations
+    pub max_iterations: usize,
+
+    /// Stop once an iteration moves the estimate less than this (L2)
+    pub tolerance: f32,
+}
+
+impl Default for GeometricMedian {
+    fn default() -> Self {
+        Self {
+            max_iterations: 64,
+            tolerance: 1e-5,
+        }
+    }
+}
+
+impl GeometricMedian {
+    pub fn new(max_iterations: usize, tolerance: f32) -> Self {
+        Self {
+            max_iterations,
+            tolerance,
+        }
+    }
+}
+
+impl Merge for GeometricMedian {
+    fn merge(&self, points: &[Point]) -> Point {
+        let mut estimate = Mean.merge(points).dims().to_vec();
+
+        for _ in 0..self.max_iterations {
+            let mut numerator = vec![0.0; estimate.len()];
+            let mut denominator = 0.0;
+            for p in points {
+                let distance = math::sqrt(
+                    p.dims()
+                        .iter()
+                        .zip(&estimate)
+                        .map(|(x, y)| (x - y) * (x - y))
+                        .sum::<f32>(),
+                );
+                // An input at the estimate would divide by zero; floor its weight
+                let w = 1.0 / distance.max(1e-6);
+                for (n, d) in numerator.iter_mut().zip(p.dims()) {
+                    *n += d * w;
+                }
+                denominator += w;
+            }
+
+            let mut moved = 0.0;
+            for (e, n) in estimate.iter_mut().zip(&numerator) {
+                let next = n / denominator;
+                moved += (next - *e) * (next - *e);
+                *e = next;
+            }
+            if math::sqrt(moved) < self.tolerance {
+                break;
+            }
+        }
+
+        Point::new(estimate)
+    }
+
+    fn name(&self) -> &'static str {
+        "geometric_median"
+    }
+
+    fn describe(&self) -> String {
+        format!("geometric_median({},{})", self.max_iterations, self.tolerance)
+    }
+}
+
+/// Running mean maintained one point at a time
+///
+/// Welford-style update: each `push` moves the mean by `(x - mean) / n`,
+/// so a centroid stays current in O(d) per point without re-merging (or
+/// even keeping) the points, and without the precision loss of a growing
+/// sum. Produces the same result as `Mean` over all pushed points.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OnlineMerge {
+    mean: Vec<f32>,
+    count: usize,
+}
+
+impl OnlineMerge {
+    /// Create an empty running mean
+    pub fn new(dimensionality: usize) -> Self {
+        Self {
+            mean: vec![0.0; dimensionality],
+            count: 0,
+        }
+    }
+
+    /// Start from a set of points
+    ///
+    /// # Panics
+    /// If `points` is empty.
+    pub fn from_points(points: &[Point]) -> Self {
+        assert!(!points.is_empty(), "Cannot merge empty slice");
+        let mut online = Self::new(points[0].dimensionality());
+        for p in points {
+            online.push(p);
+        }
+        online
+    }
+
+    /// Resume from the sum of `count` points
+    pub fn from_sum(sum: &[f32], count: usize) -> Self {
+        let n = count.max(1) as f32;
+        Self {
+            mean: sum.iter().map(|s| s / n).collect(),
+            count,
+        }
+    }
+
+    /// Add one point to the mean
+    pub fn push(&mut self, point: &Point) {
+        assert_eq!(
+            point.dimensionality(),
+            self.mean.len(),
+            "All points must have same dimensionality"
+        );
+        self.count += 1;
+        let n = self.count as f32;
+        for (m, d) in self.mean.iter_mut().zip(point.dims()) {
+            *m += (d - *m) / n;
+        }
+    }
+
+    /// Fold in another running mean, as if its points had been pushed here
+    pub fn combine(&mut self, other: &OnlineMerge) {
+        assert_eq!(
+            other.mean.len(),
+            self.mean.len(),
+            "All points must have same dimensionality"
+        );
+        if other.count == 0 {
+            return;
+        }
+        self.count += other.count;
+        let w = other.count as f32 / self.count as f32;
+        for (m, o) in self.mean.iter_mut().zip(&other.mean) {
+            *m += (o - *m) * w;
+        }
+    }
+
+    /// Number of points pushed
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Check if no points have been pushed
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Current mean
+    pub fn mean(&self) -> &[f32] {
+        &self.mean
+    }
+
+    /// Current mean as a point
+    pub fn centroid(&self) -> Point {
+        Point::new(self.mean.clone())
+    }
+
+    /// Sum of all pushed points
+    pub fn sum(&self) -> Vec<f32> {
+        let n = self.count as f32;
+        self.mean.iter().map(|m| m * n).collect()
+    }
+}
+
+/// Build a merge function from a `describe` descriptor
+///
+/// Knows the built-ins that need no per-point data; returns `None` for
+/// anything else (weighted, attention or foreign functions).
+pub fn parse(descriptor: &str) -> Option<Arc<dyn Merge>> {
+    match descriptor.trim() {
+        "mean" => Some(Arc::new(Mean)),
+        "max_pool" => Some(Arc::new(MaxPool)),
+        "min_pool" => Some(Arc::new(MinPool)),
+        "sum" => Some(Arc::new(Sum)),
+        other => {
+            let args = other.strip_prefix("geometric_median(")?.strip_suffix(')')?;
+            let (iterations, tolerance) = args.split_once(',')?;
+            Some(Arc::new(GeometricMedian::new(
+                iterations.trim().parse().ok()?,
+                tolerance.trim().parse().ok()?,
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mean_single() {
+        let points = vec![Point::new(vec![1.0, 2.0, 3.0])];
+        let merged = Mean.merge(&points);
+        assert_eq!(merged.dims(), &[1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_mean_multiple() {
+        let points = vec![
+            Point::new(vec![1.0, 2.0]),
+            Point::new(vec![3.0, 4.0]),
+        ];
+        let merged = Mean.merge(&points);
+        assert_eq!(merged.dims(), &[2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_weighted_mean() {
+        let points = vec![
+            Point::new(vec![0.0, 0.0]),
+            Point::new(vec![10.0, 10.0]),
+        ];
+        // Weight second point 3x more than first
+        let merger = WeightedMean::new(vec![1.0, 3.0]);
+        let merged = merger.merge(&points);
+        // (0*0.25 + 10*0.75, 0*0.25 + 10*0.75) = (7.5, 7.5)
+        assert!((merged.dims()[0] - 7.5).abs() < 0.0001);
+        assert!((merged.dims()[1] - 7.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_weighted_mean_recency() {
+        let merger = WeightedMean::recency(3, 0.5);
+        // decay = 0.5, n = 3
+        // weights: [0.5^2, 0.5^1, 0.5^0] = [0.25, 0.5, 1.0]
+        assert_eq!(merger.weights.len(), 3);
+        assert!((merger.weights[0] - 0.25).abs() < 0.0001);
+        assert!((merger.weights[1] - 0.5).abs() < 0.0001);
+        assert!((merger.weights[2] - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_max_pool() {
+        let points = vec![
+            Point::new(vec![1.0, 5.0, 2.0]),
+            Point::new(vec![3.0, 2.0, 4.0]),
+            Point::new(vec![2.0, 3.0, 1.0]),
+        ];
+        let merged = MaxPool.merge(&points);
+        assert_eq!(merged.dims(), &[3.0, 5.0, 4.0]);
+    }
+
+    #[test]
+    fn test_min_pool() {
+        let points = vec![
+            Point::new(vec![1.0, 5.0, 2.0]),
+            Point::new(vec![3.0, 2.0, 4.0]),
+            Point::new(vec![2.0, 3.0, 1.0]),
+        ];
+        let merged = MinPool.merge(&points);
+        assert_eq!(merged.dims(), &[1.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn test_sum() {
+        let points = vec![
+            Point::new(vec![1.0, 2.0]),
+            Point::new(vec![3.0, 4.0]),
+        ];
+        let merged = Sum.merge(&points);
+        assert_eq!(merged.dims(), &[4.0, 6.0]);
+    }
+
+    #[test]
+    fn test_merge_names() {
+        assert_eq!(Mean.name(), "mean");
+        assert_eq!(MaxPool.name(), "max_pool");
+        assert_eq!(MinPool.name(), "min_pool");
+        assert_eq!(Sum.name(), "sum");
+    }
+
+    #[test]
+    fn test_attention_merge_favors_anchor() {
+        let points = vec![
+            Point::new(vec![1.0, 0.0]),
+            Point::new(vec![0.0, 1.0]),
+        ];
+        let merger = AttentionMerge::new(Point::new(vec![1.0, 0.0])).with_temperature(0.1);
+        let weights = merger.weights(&points);
+        assert!((weights.iter().sum::<f32>() - 1.0).abs() < 0.0001);
+        assert!(weights[0] > 0.99);
+
+        let merged = merger.merge(&points);
+        assert!(merged.dims()[0] > 0.99);
+        assert_eq!(merger.name(), "attention");
+    }
+
+    #[test]
+    fn test_attention_merge_distance_and_temperature() {
+        use crate::proximity::Euclidean;
+
+        let points = vec![
+            Point::new(vec![0.0, 0.0]),
+            Point::new(vec![10.0, 0.0]),
+        ];
+        // Distances are negated: the point at the anchor gets more weight
+        let merger = AttentionMerge::new(Point::new(vec![0.0, 0.0]))
+            .with_proximity(Arc::new(Euclidean));
+        let weights = merger.weights(&points);
+        assert!(weights[0] > weights[1]);
+
+        // A very high temperature approaches the plain mean
+        let flat = merger.clone().with_temperature(1e6).merge(&points);
+        assert!((flat.dims()[0] - 5.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_geometric_median_resists_outlier() {
+        let points = vec![
+            Point::new(vec![0.0, 0.0]),
+            Point::new(vec![1.0, 0.0]),
+            Point::new(vec![0.0, 1.0]),
+            Point::new(vec![1.0, 1.0]),
+            Point::new(vec![100.0, 100.0]),
+        ];
+        let mean = Mean.merge(&points);
+        let median = GeometricMedian::default().merge(&points);
+
+        // The outlier pulls the mean to ~20; the median stays near the cluster
+        assert!(mean.dims()[0] > 20.0);
+        assert!(median.dims()[0] < 1.5 && median.dims()[1] < 1.5);
+        assert_eq!(GeometricMedian::default().name(), "geometric_median");
+    }
+
+    #[test]
+    fn test_geometric_median_symmetric() {
+        let points = vec![
+            Point::new(vec![-1.0, 0.0]),
+            Point::new(vec![1.0, 0.0]),
+            Point::new(vec![0.0, 2.0]),
+            Point::new(vec![0.0, -2.0]),
+        ];
+        let median = GeometricMedian::default().merge(&points);
+        assert!(median.dims()[0].abs() < 0.001);
+        assert!(median.dims()[1].abs() < 0.001);
+    }
+
+    #[test]
+    fn test_online_merge_matches_mean() {
+        let points = vec![
+            Point::new(vec![1.0, 2.0]),
+            Point::new(vec![3.0, 4.0]),
+            Point::new(vec![8.0, -3.0]),
+        ];
+        let mut online = OnlineMerge::new(2);
+        for p in &points {
+            online.push(p);
+        }
+        assert_eq!(online.count(), 3);
+
+        let mean = Mean.merge(&points);
+        for (a, b) in online.mean().iter().zip(mean.dims()) {
+            assert!((a - b).abs() < 0.0001);
+        }
+        assert!((online.sum()[0] - 12.0).abs() < 0.0001);
+        assert_eq!(OnlineMerge::from_sum(&online.sum(), 3).count(), 3);
+    }
+
+    #[test]
+    fn test_online_merge_combine() {
+        let a_points = vec![Point::new(vec![0.0]), Point::new(vec![2.0])];
+        let b_points = vec![Point::new(vec![10.0])];
+
+        let mut a = OnlineMerge::from_points(&a_points);
+        a.combine(&OnlineMerge::from_points(&b_points));
+        a.combine(&OnlineMerge::new(1));
+
+        assert_eq!(a.count(), 3);
+        assert!((a.mean()[0] - 4.0).abs() < 0.0001);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot merge empty")]
+    fn test_merge_empty_panics() {
+        let points: Vec<Point> = vec![];
+        Mean.merge(&points);
+    }
+
+    #[test]
+    #[should_panic(expected = "same dimensionality")]
+    fn test_merge_dimension_mismatch_panics() {
+        let points = vec![
+            Point::new(vec![1.0, 2.0]),
+            Point::new(vec![1.0, 2.0, 3.0]),
+        ];
+        Mean.merge(&points);
+    }
+
+    #[test]
+    fn test_describe_round_trips_through_parse() {
+        let median = GeometricMedian::new(10, 0.001);
+        assert_eq!(median.describe(), "geometric_median(10,0.001)");
+        assert_eq!(parse(&median.describe()).unwrap().describe(), median.describe());
+        assert_eq!(parse("max_pool").unwrap().name(), "max_pool");
+        assert!(parse("weighted_mean").is_none());
+        assert!(parse("geometric_median(10)").is_none());
+    }
+}
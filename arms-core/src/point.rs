@@ -7,11 +7,25 @@
 //!
 //! The point IS the thought's position.
 //! The position IS its relationship to all other thoughts.
+//!
+//! Dimensions are reference-counted: cloning a point shares its vector
+//! instead of copying it, so storage and index adapters holding the same
+//! point cost one allocation. Mutation copies on write.
+//!
+//! Adapters that keep the `Point` (storage, `HatIndex`) share it this way.
+//! `FlatIndex` copies vectors into its contiguous arena instead, trading a
+//! second copy of each vector for a linear, cache-friendly scan.
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::math;
 
 /// A point in dimensional space
 #[derive(Clone, Debug, PartialEq)]
 pub struct Point {
-    dims: Vec<f32>,
+    dims: Arc<[f32]>,
 }
 
 impl Point {
@@ -19,26 +33,26 @@ impl Point {
     ///
     /// # Example
     /// ```
-    /// use arms::Point;
+    /// use arms_core::Point;
     /// let p = Point::new(vec![1.0, 2.0, 3.0]);
     /// assert_eq!(p.dimensionality(), 3);
     /// ```
     pub fn new(dims: Vec<f32>) -> Self {
-        Self { dims }
+        Self { dims: dims.into() }
     }
 
     /// Create an origin point (all zeros) of given dimensionality
     ///
     /// # Example
     /// ```
-    /// use arms::Point;
+    /// use arms_core::Point;
     /// let origin = Point::origin(768);
     /// assert_eq!(origin.dimensionality(), 768);
     /// assert!(origin.dims().iter().all(|&x| x == 0.0));
     /// ```
     pub fn origin(dims: usize) -> Self {
         Self {
-            dims: vec![0.0; dims],
+            dims: vec![0.0; dims].into(),
         }
     }
 
@@ -53,20 +67,42 @@ impl Point {
     }
 
     /// Mutable access to dimensions
+    ///
+    /// Copies the vector first if it is shared with other clones.
     pub fn dims_mut(&mut self) -> &mut [f32] {
-        &mut self.dims
+        Arc::make_mut(&mut self.dims)
+    }
+
+    /// The first `n` dimensions as a new point (all of them if `n` is larger)
+    ///
+    /// # Example
+    /// ```
+    /// use arms_core::Point;
+    /// let p = Point::new(vec![1.0, 2.0, 3.0]);
+    /// assert_eq!(p.prefix(2).dims(), &[1.0, 2.0]);
+    /// ```
+    pub fn prefix(&self, n: usize) -> Self {
+        if n >= self.dims.len() {
+            return self.clone();
+        }
+        Self::new(self.dims[..n].to_vec())
+    }
+
+    /// Whether two points share the same vector allocation
+    pub fn shares_dims(&self, other: &Point) -> bool {
+        Arc::ptr_eq(&self.dims, &other.dims)
     }
 
     /// Calculate the magnitude (L2 norm) of this point
     ///
     /// # Example
     /// ```
-    /// use arms::Point;
+    /// use arms_core::Point;
     /// let p = Point::new(vec![3.0, 4.0]);
     /// assert!((p.magnitude() - 5.0).abs() < 0.0001);
     /// ```
     pub fn magnitude(&self) -> f32 {
-        self.dims.iter().map(|x| x * x).sum::<f32>().sqrt()
+        math::sqrt(self.dims.iter().map(|x| x * x).sum::<f32>())
     }
 
     /// Check if this point is normalized (magnitude ≈ 1.0)
@@ -81,7 +117,7 @@ impl Point {
     ///
     /// # Example
     /// ```
-    /// use arms::Point;
+    /// use arms_core::Point;
     /// let p = Point::new(vec![3.0, 4.0]);
     /// let normalized = p.normalize();
     /// assert!(normalized.is_normalized());
@@ -183,4 +219,17 @@ mod tests {
         let b = Point::new(vec![1.0, 2.0, 3.0]);
         let _ = a.add(&b);
     }
+
+    #[test]
+    fn test_clone_shares_dims() {
+        let a = Point::new(vec![1.0, 2.0]);
+        let mut b = a.clone();
+        assert!(a.shares_dims(&b));
+
+        // Writing through a shared clone leaves the original untouched
+        b.dims_mut()[0] = 9.0;
+        assert!(!a.shares_dims(&b));
+        assert_eq!(a.dims(), &[1.0, 2.0]);
+        assert_eq!(b.dims(), &[9.0, 2.0]);
+    }
 }
@@ -0,0 +1,1007 @@
+//! # Proximity
+//!
+//! Trait and implementations for measuring how related two points are.
+//!
+//! This is one of the five primitives of ARMS:
+//! `Proximity: fn(a, b) -> f32` - How related?
+//!
+//! Proximity functions are pluggable - use whichever fits your use case.
+//!
+//! Raw scores come in two flavors: similarities (cosine, dot product) where
+//! higher is more related, and distances (Euclidean, Manhattan) where lower
+//! is. Each function reports its `ScoreOrder`, and indexes rank, threshold
+//! and convert scores through it instead of assuming one convention.
+//!
+//! `Weighted`, `Max` and `Product` combine functions into one. Every
+//! function has a `describe` descriptor that `parse` rebuilds it from.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::math;
+
+use crate::Point;
+
+/// How raw proximity scores rank
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ScoreOrder {
+    /// Similarities: higher scores are more related
+    #[default]
+    HigherIsBetter,
+
+    /// Distances: lower scores are more related
+    LowerIsBetter,
+}
+
+impl ScoreOrder {
+    pub fn from_higher_is_better(higher_is_better: bool) -> Self {
+        if higher_is_better {
+            ScoreOrder::HigherIsBetter
+        } else {
+            ScoreOrder::LowerIsBetter
+        }
+    }
+
+    pub fn higher_is_better(&self) -> bool {
+        *self == ScoreOrder::HigherIsBetter
+    }
+
+    /// Order two scores, most related first
+    pub fn compare(&self, a: f32, b: f32) -> Ordering {
+        match self {
+            ScoreOrder::HigherIsBetter => b.total_cmp(&a),
+            ScoreOrder::LowerIsBetter => a.total_cmp(&b),
+        }
+    }
+
+    /// Whether `a` ranks strictly ahead of `b`
+    pub fn is_better(&self, a: f32, b: f32) -> bool {
+        self.compare(a, b) == Ordering::Less
+    }
+
+    /// Whether a score is at least as related as `threshold`
+    pub fn passes(&self, score: f32, threshold: f32) -> bool {
+        match self {
+            ScoreOrder::HigherIsBetter => score >= threshold,
+            ScoreOrder::LowerIsBetter => score <= threshold,
+        }
+    }
+
+    /// Convert a score to a distance (lower = more related)
+    ///
+    /// Similarities map to `1 - score`, so cosine becomes cosine distance.
+    pub fn to_distance(&self, score: f32) -> f32 {
+        match self {
+            ScoreOrder::HigherIsBetter => 1.0 - score,
+            ScoreOrder::LowerIsBetter => score,
+        }
+    }
+
+    /// Inverse of `to_distance`
+    pub fn from_distance(&self, distance: f32) -> f32 {
+        self.to_distance(distance)
+    }
+}
+
+/// Trait for measuring proximity between points
+///
+/// Whether higher values mean more related depends on the implementation;
+/// see `order`.
+pub trait Proximity: Send + Sync {
+    /// Compute proximity between two points
+    ///
+    /// Both points must have the same dimensionality.
+    fn proximity(&self, a: &Point, b: &Point) -> f32;
+
+    /// Compute proximity between a query and several candidates
+    ///
+    /// Default implementation calls `proximity` per candidate; functions
+    /// with a cheaper batch path (vectorized or foreign code) should
+    /// override it.
+    fn proximity_batch(&self, query: &Point, candidates: &[&Point]) -> Vec<f32> {
+        candidates.iter().map(|c| self.proximity(query, c)).collect()
+    }
+
+    /// Compute proximity between two raw vectors of equal length
+    ///
+    /// Used by scans over contiguous vector storage. The default wraps the
+    /// slices in points (allocating); the built-in functions compute directly.
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        self.proximity(&Point::new(a.to_vec()), &Point::new(b.to_vec()))
+    }
+
+    /// Compute proximity between a query and several raw vectors
+    ///
+    /// Slice counterpart of `proximity_batch`, called by scans over
+    /// contiguous storage one block of rows at a time. SIMD or GPU
+    /// implementations override it to amortize per-query setup.
+    fn proximity_batch_slices(&self, query: &[f32], candidates: &[&[f32]]) -> Vec<f32> {
+        candidates.iter().map(|c| self.proximity_slices(query, c)).collect()
+    }
+
+    /// `proximity_batch_slices`, appending scores to a caller-owned buffer
+    ///
+    /// Lets scans reuse one score buffer across batches and queries. The
+    /// default delegates to `proximity_batch_slices`, so overriding that
+    /// alone is enough; the built-in functions write directly.
+    fn proximity_batch_slices_into(&self, query: &[f32], candidates: &[&[f32]], out: &mut Vec<f32>) {
+        out.extend(self.proximity_batch_slices(query, candidates));
+    }
+
+    /// Whether `distance_within` can stop early (the default never does)
+    ///
+    /// Scans prefer early exit over the batch path when this is true.
+    fn can_prune(&self) -> bool {
+        false
+    }
+
+    /// Compute a distance (lower is better), giving up once it exceeds `bound`
+    ///
+    /// Returns `None` when the result is known to be greater than `bound`,
+    /// so a top-k scan can stop reading a candidate's remaining dimensions
+    /// once it can't win. Only meaningful for distances that grow
+    /// monotonically as dimensions are added; the default never prunes.
+    fn distance_within(&self, a: &[f32], b: &[f32], bound: f32) -> Option<f32> {
+        let _ = bound;
+        Some(self.proximity_slices(a, b))
+    }
+
+    /// How this function's scores rank (similarity by default)
+    fn order(&self) -> ScoreOrder {
+        ScoreOrder::HigherIsBetter
+    }
+
+    /// Whether scores ignore vector length, so indexes may keep points and
+    /// centroids unit-normalized (cosine and combinations of it; false by
+    /// default)
+    fn normalizes(&self) -> bool {
+        false
+    }
+
+    /// Map a raw score to a similarity (higher = more related)
+    ///
+    /// Similarities pass through; distances map to `1 / (1 + d)`, in (0, 1].
+    /// Use this to compare or blend scores across proximity functions.
+    fn similarity(&self, score: f32) -> f32 {
+        match self.order() {
+            ScoreOrder::HigherIsBetter => score,
+            ScoreOrder::LowerIsBetter => 1.0 / (1.0 + score),
+        }
+    }
+
+    /// Name of this proximity function (for debugging/config)
+    fn name(&self) -> &'static str;
+
+    /// Descriptor that `parse` turns back into this function
+    ///
+    /// Defaults to `name`. Combinators describe their parts, e.g.
+    /// `weighted(cosine*0.8,manhattan*0.2)`; snapshots store this.
+    fn describe(&self) -> String {
+        self.name().to_string()
+    }
+}
+
+/// Dimensions accumulated between early-exit checks (one cache line)
+const PRUNE_CHUNK: usize = 16;
+
+/// Sum `term(x, y)` over paired elements, stopping once the sum exceeds `bound`
+///
+/// Terms must be non-negative. The chunked order is also used for unbounded
+/// calls, so pruned and unpruned scans produce identical scores.
+#[inline]
+fn bounded_sum(a: &[f32], b: &[f32], bound: f32, term: impl Fn(f32, f32) -> f32) -> Option<f32> {
+    let mut sum = 0.0f32;
+    let mut chunks_a = a.chunks(PRUNE_CHUNK);
+    let mut chunks_b = b.chunks(PRUNE_CHUNK);
+    while let (Some(ca), Some(cb)) = (chunks_a.next(), chunks_b.next()) {
+        sum += ca.iter().zip(cb).map(|(&x, &y)| term(x, y)).sum::<f32>();
+        if sum > bound {
+            return None;
+        }
+    }
+    Some(sum)
+}
+
+// ============================================================================
+// IMPLEMENTATIONS
+// ============================================================================
+
+/// Cosine similarity
+///
+/// Measures the cosine of the angle between two vectors.
+/// Returns a value in [-1, 1] where 1 means identical direction.
+///
+/// Best for: Normalized vectors, semantic similarity.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Cosine;
+
+impl Proximity for Cosine {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        assert_eq!(
+            a.dimensionality(),
+            b.dimensionality(),
+            "Points must have same dimensionality"
+        );
+
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+        for (x, y) in a.iter().zip(b) {
+            dot += x * y;
+            norm_a += x * x;
+            norm_b += y * y;
+        }
+
+        let mag_a = math::sqrt(norm_a);
+        let mag_b = math::sqrt(norm_b);
+
+        if mag_a == 0.0 || mag_b == 0.0 {
+            return 0.0;
+        }
+
+        dot / (mag_a * mag_b)
+    }
+
+    fn proximity_batch_slices_into(&self, query: &[f32], candidates: &[&[f32]], out: &mut Vec<f32>) {
+        out.extend(candidates.iter().map(|c| self.proximity_slices(query, c)));
+    }
+
+    fn normalizes(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "cosine"
+    }
+}
+
+/// Euclidean distance
+///
+/// The straight-line distance between two points.
+/// Returns a value in [0, ∞) where 0 means identical.
+///
+/// Note: This returns DISTANCE, not similarity.
+/// Lower values = more similar.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Euclidean;
+
+impl Proximity for Euclidean {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        assert_eq!(
+            a.dimensionality(),
+            b.dimensionality(),
+            "Points must have same dimensionality"
+        );
+
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        math::sqrt(EuclideanSquared.proximity_slices(a, b))
+    }
+
+    fn can_prune(&self) -> bool {
+        true
+    }
+
+    fn distance_within(&self, a: &[f32], b: &[f32], bound: f32) -> Option<f32> {
+        EuclideanSquared
+            .distance_within(a, b, bound * bound)
+            .map(math::sqrt)
+    }
+
+    fn order(&self) -> ScoreOrder {
+        ScoreOrder::LowerIsBetter
+    }
+
+    fn proximity_batch_slices_into(&self, query: &[f32], candidates: &[&[f32]], out: &mut Vec<f32>) {
+        out.extend(candidates.iter().map(|c| self.proximity_slices(query, c)));
+    }
+
+    fn name(&self) -> &'static str {
+        "euclidean"
+    }
+}
+
+/// Squared Euclidean distance
+///
+/// Same ordering as Euclidean but faster (no sqrt).
+/// Use when you only need to compare distances, not absolute values.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EuclideanSquared;
+
+impl Proximity for EuclideanSquared {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        assert_eq!(
+            a.dimensionality(),
+            b.dimensionality(),
+            "Points must have same dimensionality"
+        );
+
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        self.distance_within(a, b, f32::INFINITY).unwrap_or(f32::INFINITY)
+    }
+
+    fn can_prune(&self) -> bool {
+        true
+    }
+
+    fn distance_within(&self, a: &[f32], b: &[f32], bound: f32) -> Option<f32> {
+        bounded_sum(a, b, bound, |x, y| (x - y) * (x - y))
+    }
+
+    fn order(&self) -> ScoreOrder {
+        ScoreOrder::LowerIsBetter
+    }
+
+    fn proximity_batch_slices_into(&self, query: &[f32], candidates: &[&[f32]], out: &mut Vec<f32>) {
+        out.extend(candidates.iter().map(|c| self.proximity_slices(query, c)));
+    }
+
+    fn name(&self) -> &'static str {
+        "euclidean_squared"
+    }
+}
+
+/// Dot product
+///
+/// The raw dot product without normalization.
+/// Returns a value that depends on magnitudes.
+///
+/// Best for: When magnitude matters, not just direction.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DotProduct;
+
+impl Proximity for DotProduct {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        assert_eq!(
+            a.dimensionality(),
+            b.dimensionality(),
+            "Points must have same dimensionality"
+        );
+
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    fn proximity_batch_slices_into(&self, query: &[f32], candidates: &[&[f32]], out: &mut Vec<f32>) {
+        out.extend(candidates.iter().map(|c| self.proximity_slices(query, c)));
+    }
+
+    fn name(&self) -> &'static str {
+        "dot_product"
+    }
+}
+
+/// Manhattan (L1) distance
+///
+/// Sum of absolute differences along each dimension.
+/// Returns a value in [0, ∞) where 0 means identical.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Manhattan;
+
+impl Proximity for Manhattan {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        assert_eq!(
+            a.dimensionality(),
+            b.dimensionality(),
+            "Points must have same dimensionality"
+        );
+
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        self.distance_within(a, b, f32::INFINITY).unwrap_or(f32::INFINITY)
+    }
+
+    fn can_prune(&self) -> bool {
+        true
+    }
+
+    fn distance_within(&self, a: &[f32], b: &[f32], bound: f32) -> Option<f32> {
+        bounded_sum(a, b, bound, |x, y| (x - y).abs())
+    }
+
+    fn order(&self) -> ScoreOrder {
+        ScoreOrder::LowerIsBetter
+    }
+
+    fn proximity_batch_slices_into(&self, query: &[f32], candidates: &[&[f32]], out: &mut Vec<f32>) {
+        out.extend(candidates.iter().map(|c| self.proximity_slices(query, c)));
+    }
+
+    fn name(&self) -> &'static str {
+        "manhattan"
+    }
+}
+
+/// Cosine similarity with per-dimension weights
+///
+/// `Σ w·a·b / (√(Σ w·a²) · √(Σ w·b²))`. Use it to downweight known-noisy
+/// dimensions, or with `prefix` to compare Matryoshka embeddings on their
+/// leading dimensions only. Weights should be non-negative.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedCosine {
+    weights: Arc<[f32]>,
+}
+
+impl WeightedCosine {
+    pub fn new(weights: Vec<f32>) -> Self {
+        Self { weights: weights.into() }
+    }
+
+    /// Weight 1 for the first `keep` of `dimensionality` dimensions, 0 after
+    pub fn prefix(dimensionality: usize, keep: usize) -> Self {
+        Self::new(prefix_weights(dimensionality, keep))
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+}
+
+impl Proximity for WeightedCosine {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        assert_eq!(
+            a.dimensionality(),
+            b.dimensionality(),
+            "Points must have same dimensionality"
+        );
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        assert_eq!(a.len(), self.weights.len(), "Weights must match dimensionality");
+
+        let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+        for ((x, y), w) in a.iter().zip(b).zip(self.weights.iter()) {
+            dot += w * x * y;
+            norm_a += w * x * x;
+            norm_b += w * y * y;
+        }
+
+        let mag_a = math::sqrt(norm_a);
+        let mag_b = math::sqrt(norm_b);
+
+        if mag_a == 0.0 || mag_b == 0.0 {
+            return 0.0;
+        }
+
+        dot / (mag_a * mag_b)
+    }
+
+    fn proximity_batch_slices_into(&self, query: &[f32], candidates: &[&[f32]], out: &mut Vec<f32>) {
+        out.extend(candidates.iter().map(|c| self.proximity_slices(query, c)));
+    }
+
+    fn normalizes(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "weighted_cosine"
+    }
+}
+
+/// Euclidean distance with per-dimension weights
+///
+/// `√(Σ w·(a - b)²)`. Lower values = more similar. Weights should be
+/// non-negative.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedEuclidean {
+    weights: Arc<[f32]>,
+}
+
+impl WeightedEuclidean {
+    pub fn new(weights: Vec<f32>) -> Self {
+        Self { weights: weights.into() }
+    }
+
+    /// Weight 1 for the first `keep` of `dimensionality` dimensions, 0 after
+    pub fn prefix(dimensionality: usize, keep: usize) -> Self {
+        Self::new(prefix_weights(dimensionality, keep))
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+}
+
+impl Proximity for WeightedEuclidean {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        assert_eq!(
+            a.dimensionality(),
+            b.dimensionality(),
+            "Points must have same dimensionality"
+        );
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        self.distance_within(a, b, f32::INFINITY).unwrap_or(f32::INFINITY)
+    }
+
+    fn can_prune(&self) -> bool {
+        true
+    }
+
+    fn distance_within(&self, a: &[f32], b: &[f32], bound: f32) -> Option<f32> {
+        assert_eq!(a.len(), self.weights.len(), "Weights must match dimensionality");
+
+        let bound_sq = bound * bound;
+        let mut sum = 0.0f32;
+        let chunks = a
+            .chunks(PRUNE_CHUNK)
+            .zip(b.chunks(PRUNE_CHUNK))
+            .zip(self.weights.chunks(PRUNE_CHUNK));
+        for ((ca, cb), cw) in chunks {
+            sum += ca
+                .iter()
+                .zip(cb)
+                .zip(cw)
+                .map(|((x, y), w)| w * (x - y) * (x - y))
+                .sum::<f32>();
+            if sum > bound_sq {
+                return None;
+            }
+        }
+        Some(math::sqrt(sum))
+    }
+
+    fn order(&self) -> ScoreOrder {
+        ScoreOrder::LowerIsBetter
+    }
+
+    fn proximity_batch_slices_into(&self, query: &[f32], candidates: &[&[f32]], out: &mut Vec<f32>) {
+        out.extend(candidates.iter().map(|c| self.proximity_slices(query, c)));
+    }
+
+    fn name(&self) -> &'static str {
+        "weighted_euclidean"
+    }
+}
+
+/// Weights keeping the first `keep` dimensions (Matryoshka truncation)
+fn prefix_weights(dimensionality: usize, keep: usize) -> Vec<f32> {
+    (0..dimensionality)
+        .map(|i| if i < keep { 1.0 } else { 0.0 })
+        .collect()
+}
+
+// ============================================================================
+// COMBINATORS
+// ============================================================================
+
+/// Weighted average of several proximities
+///
+/// Parts are compared through `similarity`, so similarities and distances
+/// mix freely; the result is a similarity (higher = more related).
+///
+/// ```rust,ignore
+/// let blend = Weighted::default().with(Cosine, 0.8).with(Manhattan, 0.2);
+/// let config = ArmsConfig::new(768).with_proximity(blend);
+/// ```
+#[derive(Clone, Default)]
+pub struct Weighted {
+    parts: Vec<(Arc<dyn Proximity>, f32)>,
+}
+
+impl Weighted {
+    pub fn new(parts: Vec<(Arc<dyn Proximity>, f32)>) -> Self {
+        Self { parts }
+    }
+
+    /// Add a part with a (non-negative) weight
+    pub fn with<P: Proximity + 'static>(mut self, proximity: P, weight: f32) -> Self {
+        self.parts.push((Arc::new(proximity), weight));
+        self
+    }
+
+    pub fn parts(&self) -> &[(Arc<dyn Proximity>, f32)] {
+        &self.parts
+    }
+}
+
+impl Proximity for Weighted {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        let (mut total, mut weights) = (0.0, 0.0);
+        for (part, weight) in &self.parts {
+            total += weight * part.similarity(part.proximity_slices(a, b));
+            weights += weight;
+        }
+        if weights == 0.0 {
+            0.0
+        } else {
+            total / weights
+        }
+    }
+
+    fn normalizes(&self) -> bool {
+        !self.parts.is_empty() && self.parts.iter().all(|(part, _)| part.normalizes())
+    }
+
+    fn name(&self) -> &'static str {
+        "weighted"
+    }
+
+    fn describe(&self) -> String {
+        let parts: Vec<String> = self
+            .parts
+            .iter()
+            .map(|(part, weight)| format!("{}*{}", part.describe(), weight))
+            .collect();
+        format!("weighted({})", parts.join(","))
+    }
+}
+
+/// Best similarity among several proximities
+///
+/// Related if any of the parts says so.
+#[derive(Clone, Default)]
+pub struct Max(pub Vec<Arc<dyn Proximity>>);
+
+impl Max {
+    pub fn with<P: Proximity + 'static>(mut self, proximity: P) -> Self {
+        self.0.push(Arc::new(proximity));
+        self
+    }
+}
+
+impl Proximity for Max {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        self.0
+            .iter()
+            .map(|part| part.similarity(part.proximity_slices(a, b)))
+            .fold(None, |best: Option<f32>, s| Some(best.map_or(s, |b| b.max(s))))
+            .unwrap_or(0.0)
+    }
+
+    fn normalizes(&self) -> bool {
+        !self.0.is_empty() && self.0.iter().all(|part| part.normalizes())
+    }
+
+    fn name(&self) -> &'static str {
+        "max"
+    }
+
+    fn describe(&self) -> String {
+        format!("max({})", describe_all(&self.0))
+    }
+}
+
+/// Product of the similarities of several proximities
+///
+/// Related only if every part agrees. Best with parts scoring in [0, 1]
+/// (distances, or non-negative similarities).
+#[derive(Clone, Default)]
+pub struct Product(pub Vec<Arc<dyn Proximity>>);
+
+impl Product {
+    pub fn with<P: Proximity + 'static>(mut self, proximity: P) -> Self {
+        self.0.push(Arc::new(proximity));
+        self
+    }
+}
+
+impl Proximity for Product {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        self.0
+            .iter()
+            .map(|part| part.similarity(part.proximity_slices(a, b)))
+            .product()
+    }
+
+    fn normalizes(&self) -> bool {
+        !self.0.is_empty() && self.0.iter().all(|part| part.normalizes())
+    }
+
+    fn name(&self) -> &'static str {
+        "product"
+    }
+
+    fn describe(&self) -> String {
+        format!("product({})", describe_all(&self.0))
+    }
+}
+
+fn describe_all(parts: &[Arc<dyn Proximity>]) -> String {
+    let parts: Vec<String> = parts.iter().map(|part| part.describe()).collect();
+    parts.join(",")
+}
+
+/// Build a proximity from a `describe` descriptor
+///
+/// Knows the parameterless built-ins and the combinators over them.
+/// Returns `None` for anything else (weighted or foreign functions).
+pub fn parse(descriptor: &str) -> Option<Arc<dyn Proximity>> {
+    let descriptor = descriptor.trim();
+    let simple: Option<Arc<dyn Proximity>> = match descriptor {
+        "cosine" => Some(Arc::new(Cosine)),
+        "euclidean" => Some(Arc::new(Euclidean)),
+        "euclidean_squared" => Some(Arc::new(EuclideanSquared)),
+        "dot_product" => Some(Arc::new(DotProduct)),
+        "manhattan" => Some(Arc::new(Manhattan)),
+        _ => None,
+    };
+    if simple.is_some() {
+        return simple;
+    }
+
+    let (kind, rest) = descriptor.split_once('(')?;
+    let args = split_args(rest.strip_suffix(')')?)?;
+    match kind {
+        "weighted" => {
+            let parts = args
+                .into_iter()
+                .map(|arg| {
+                    let (part, weight) = arg.rsplit_once('*')?;
+                    Some((parse(part)?, weight.trim().parse().ok()?))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(Arc::new(Weighted::new(parts)))
+        }
+        "max" => Some(Arc::new(Max(parse_all(args)?))),
+        "product" => Some(Arc::new(Product(parse_all(args)?))),
+        _ => None,
+    }
+}
+
+fn parse_all(args: Vec<&str>) -> Option<Vec<Arc<dyn Proximity>>> {
+    args.into_iter().map(parse).collect()
+}
+
+/// Split on commas outside parentheses
+fn split_args(args: &str) -> Option<Vec<&str>> {
+    let (mut parts, mut depth, mut start) = (Vec::new(), 0usize, 0);
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                parts.push(&args[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    if !args.trim().is_empty() {
+        parts.push(&args[start..]);
+    }
+    Some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_proximity_batch_default() {
+        let q = Point::new(vec![1.0, 2.0]);
+        let a = Point::new(vec![1.0, 0.0]);
+        let b = Point::new(vec![0.0, 1.0]);
+        assert_eq!(DotProduct.proximity_batch(&q, &[&a, &b]), vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_cosine_identical() {
+        let a = Point::new(vec![1.0, 0.0, 0.0]);
+        let b = Point::new(vec![1.0, 0.0, 0.0]);
+        let cos = Cosine.proximity(&a, &b);
+        assert!((cos - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_cosine_opposite() {
+        let a = Point::new(vec![1.0, 0.0, 0.0]);
+        let b = Point::new(vec![-1.0, 0.0, 0.0]);
+        let cos = Cosine.proximity(&a, &b);
+        assert!((cos - (-1.0)).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_cosine_orthogonal() {
+        let a = Point::new(vec![1.0, 0.0, 0.0]);
+        let b = Point::new(vec![0.0, 1.0, 0.0]);
+        let cos = Cosine.proximity(&a, &b);
+        assert!(cos.abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_euclidean() {
+        let a = Point::new(vec![0.0, 0.0]);
+        let b = Point::new(vec![3.0, 4.0]);
+        let dist = Euclidean.proximity(&a, &b);
+        assert!((dist - 5.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_euclidean_squared() {
+        let a = Point::new(vec![0.0, 0.0]);
+        let b = Point::new(vec![3.0, 4.0]);
+        let dist_sq = EuclideanSquared.proximity(&a, &b);
+        assert!((dist_sq - 25.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_dot_product() {
+        let a = Point::new(vec![1.0, 2.0, 3.0]);
+        let b = Point::new(vec![4.0, 5.0, 6.0]);
+        let dot = DotProduct.proximity(&a, &b);
+        // 1*4 + 2*5 + 3*6 = 4 + 10 + 18 = 32
+        assert!((dot - 32.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_manhattan() {
+        let a = Point::new(vec![0.0, 0.0]);
+        let b = Point::new(vec![3.0, 4.0]);
+        let dist = Manhattan.proximity(&a, &b);
+        assert!((dist - 7.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_proximity_names() {
+        assert_eq!(Cosine.name(), "cosine");
+        assert_eq!(Euclidean.name(), "euclidean");
+        assert_eq!(DotProduct.name(), "dot_product");
+        assert_eq!(Manhattan.name(), "manhattan");
+    }
+
+    #[test]
+    fn test_normalizes() {
+        assert!(Cosine.normalizes() && WeightedCosine::new(vec![1.0, 0.5]).normalizes());
+        assert!(!Euclidean.normalizes() && !DotProduct.normalizes());
+
+        // Combinators normalize only if every part does
+        assert!(Weighted::default().with(Cosine, 0.5).with(WeightedCosine::new(vec![1.0]), 0.5).normalizes());
+        assert!(!Weighted::default().with(Cosine, 0.8).with(Manhattan, 0.2).normalizes());
+        assert!(Max::default().with(Cosine).normalizes() && !Product::default().normalizes());
+    }
+
+    #[test]
+    #[should_panic(expected = "same dimensionality")]
+    fn test_dimension_mismatch_panics() {
+        let a = Point::new(vec![1.0, 2.0]);
+        let b = Point::new(vec![1.0, 2.0, 3.0]);
+        Cosine.proximity(&a, &b);
+    }
+
+    #[test]
+    fn test_distance_within_prunes() {
+        let a: Vec<f32> = (0..40).map(|i| i as f32).collect();
+        let b = vec![0.0; 40];
+        let full = Euclidean.proximity_slices(&a, &b);
+
+        assert_eq!(Euclidean.distance_within(&a, &b, full + 1.0), Some(full));
+        assert_eq!(Euclidean.distance_within(&a, &b, full - 1.0), None);
+        assert_eq!(Manhattan.distance_within(&a, &b, 10.0), None);
+
+        // Similarities never prune
+        assert!(Cosine.distance_within(&a, &b, -1.0).is_some());
+    }
+
+    #[test]
+    fn test_score_order() {
+        assert_eq!(Cosine.order(), ScoreOrder::HigherIsBetter);
+        assert_eq!(Euclidean.order(), ScoreOrder::LowerIsBetter);
+
+        let distance = ScoreOrder::LowerIsBetter;
+        assert!(distance.is_better(0.5, 2.0));
+        assert!(distance.passes(1.0, 1.0) && !distance.passes(1.5, 1.0));
+        assert!(ScoreOrder::HigherIsBetter.is_better(0.9, 0.1));
+        assert_eq!(ScoreOrder::HigherIsBetter.to_distance(0.25), 0.75);
+
+        // Normalized similarities rank the same way regardless of order
+        assert!(Euclidean.similarity(0.5) > Euclidean.similarity(2.0));
+        assert_eq!(Euclidean.similarity(0.0), 1.0);
+        assert_eq!(Cosine.similarity(0.3), 0.3);
+    }
+
+    #[test]
+    fn test_proximity_batch_slices_default() {
+        let q = [1.0, 0.0];
+        let rows: [&[f32]; 2] = [&[1.0, 0.0], &[0.0, 1.0]];
+        assert_eq!(Cosine.proximity_batch_slices(&q, &rows), vec![1.0, 0.0]);
+
+        let mut out = vec![9.0];
+        Cosine.proximity_batch_slices_into(&q, &rows, &mut out);
+        assert_eq!(out, vec![9.0, 1.0, 0.0]);
+        assert!(Euclidean.can_prune() && !Cosine.can_prune());
+    }
+
+    #[test]
+    fn test_weighted_cosine() {
+        let a = Point::new(vec![1.0, 0.0, 5.0]);
+        let b = Point::new(vec![1.0, 0.0, -5.0]);
+
+        // Ignoring the noisy third dimension makes the points identical
+        let weighted = WeightedCosine::new(vec![1.0, 1.0, 0.0]);
+        assert!((weighted.proximity(&a, &b) - 1.0).abs() < 0.0001);
+        assert!(Cosine.proximity(&a, &b) < 0.0);
+
+        // Uniform weights reproduce plain cosine
+        let uniform = WeightedCosine::new(vec![1.0; 3]);
+        assert!((uniform.proximity(&a, &b) - Cosine.proximity(&a, &b)).abs() < 0.0001);
+        assert_eq!(WeightedCosine::prefix(3, 2).weights(), &[1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_weighted_euclidean() {
+        let a = Point::new(vec![0.0, 0.0]);
+        let b = Point::new(vec![3.0, 4.0]);
+
+        let weighted = WeightedEuclidean::new(vec![1.0, 0.0]);
+        assert!((weighted.proximity(&a, &b) - 3.0).abs() < 0.0001);
+        assert_eq!(weighted.order(), ScoreOrder::LowerIsBetter);
+        assert_eq!(weighted.distance_within(a.dims(), b.dims(), 2.0), None);
+
+        let uniform = WeightedEuclidean::new(vec![1.0, 1.0]);
+        assert!((uniform.proximity(&a, &b) - 5.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_combinators_blend_similarities() {
+        let a = Point::new(vec![1.0, 0.0]);
+        let b = Point::new(vec![0.0, 1.0]);
+
+        // cosine 0, manhattan 2 -> similarity 1/3
+        let weighted = Weighted::default().with(Cosine, 0.5).with(Manhattan, 0.5);
+        assert!((weighted.proximity(&a, &b) - 1.0 / 6.0).abs() < 1e-6);
+        assert_eq!(weighted.order(), ScoreOrder::HigherIsBetter);
+
+        let max = Max::default().with(Cosine).with(Manhattan);
+        assert!((max.proximity(&a, &b) - 1.0 / 3.0).abs() < 1e-6);
+
+        let product = Product::default().with(Cosine).with(Manhattan);
+        assert_eq!(product.proximity(&a, &b), 0.0);
+    }
+
+    #[test]
+    fn test_describe_round_trips_through_parse() {
+        let nested = Weighted::default()
+            .with(Cosine, 0.8)
+            .with(Max::default().with(Euclidean).with(Manhattan), 0.2);
+        let descriptor = nested.describe();
+        assert_eq!(descriptor, "weighted(cosine*0.8,max(euclidean,manhattan)*0.2)");
+
+        let parsed = parse(&descriptor).unwrap();
+        assert_eq!(parsed.describe(), descriptor);
+
+        let a = Point::new(vec![1.0, 2.0]);
+        let b = Point::new(vec![2.0, 0.5]);
+        assert_eq!(parsed.proximity(&a, &b), nested.proximity(&a, &b));
+
+        assert_eq!(parse("dot_product").unwrap().name(), "dot_product");
+        assert!(parse("weighted_cosine").is_none());
+        assert!(parse("max(cosine,unknown)").is_none());
+        assert!(parse("max(cosine").is_none());
+    }
+}
@@ -0,0 +1,99 @@
+/*
+ * ARMS-HAT C API
+ *
+ * Generated from src/adapters/ffi.rs:
+ *   cbindgen --config cbindgen.toml --output include/arms_hat.h
+ *
+ * Build the library with `cargo build --release --features ffi`.
+ */
+
+#ifndef ARMS_HAT_H
+#define ARMS_HAT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/* Status codes returned by fallible calls */
+typedef enum ArmsStatus {
+  ArmsStatus_Ok = 0,
+  ArmsStatus_NullPointer = 1,
+  ArmsStatus_DimensionalityMismatch = 2,
+  ArmsStatus_CapacityExceeded = 3,
+  ArmsStatus_DuplicateId = 4,
+  ArmsStatus_NotFound = 5,
+  ArmsStatus_IndexNotReady = 6,
+  ArmsStatus_Error = 7,
+} ArmsStatus;
+
+/* Opaque engine handle */
+typedef struct ArmsHandle ArmsHandle;
+
+/* 128-bit point identifier */
+typedef struct ArmsId {
+  uint8_t bytes[16];
+} ArmsId;
+
+/* A single search hit */
+typedef struct ArmsSearchResult {
+  struct ArmsId id;
+  float score;
+} ArmsSearchResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/* Create an engine with a flat (exact) cosine index */
+struct ArmsHandle *arms_new(size_t dimensionality);
+
+/* Create an engine backed by a HAT index */
+struct ArmsHandle *arms_new_hat(size_t dimensionality);
+
+/* Destroy an engine (null is ignored) */
+void arms_free(struct ArmsHandle *handle);
+
+/* Place a point with an optional payload */
+enum ArmsStatus arms_place(struct ArmsHandle *handle,
+                           const float *vector,
+                           size_t dim,
+                           const uint8_t *blob,
+                           size_t blob_len,
+                           struct ArmsId *out_id);
+
+/* Find the `k` nearest points; writes up to `k` results and the count */
+enum ArmsStatus arms_near(const struct ArmsHandle *handle,
+                          const float *query,
+                          size_t dim,
+                          size_t k,
+                          struct ArmsSearchResult *out_results,
+                          size_t *out_len);
+
+/* Remove a point */
+enum ArmsStatus arms_remove(struct ArmsHandle *handle, struct ArmsId id);
+
+/* Borrow a point's payload (valid until the next mutating call) */
+enum ArmsStatus arms_get_blob(const struct ArmsHandle *handle,
+                              struct ArmsId id,
+                              const uint8_t **out_data,
+                              size_t *out_len);
+
+/* Number of stored points (0 for null) */
+size_t arms_len(const struct ArmsHandle *handle);
+
+/* Dimensionality of the space (0 for null) */
+size_t arms_dimensionality(const struct ArmsHandle *handle);
+
+/* Static description of a status code */
+const char *arms_status_message(enum ArmsStatus status);
+
+/* Library version (static, NUL-terminated) */
+const char *arms_version(void);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* ARMS_HAT_H */
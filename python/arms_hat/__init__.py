@@ -23,6 +23,10 @@ Example:
     >>> # Persistence
     >>> index.save("memory.hat")
     >>> loaded = HatIndex.load("memory.hat")
+
+Errors raise one exception type per error code (``DuplicateIdError``,
+``CorruptedError``, ...). Bad arguments and index state subclass
+``ValueError``; storage, decoding and OS failures subclass ``IOError``.
 """
 
 from .arms_hat import (
@@ -31,7 +35,24 @@ from .arms_hat import (
     SearchResult,
     SessionSummary,
     DocumentSummary,
+    ResultGroup,
+    TopicSpan,
+    SessionTimeline,
     HatStats,
+    CompressedKV,
+    DimensionalityMismatchError,
+    CapacityExceededError,
+    DuplicateIdError,
+    NotFoundError,
+    IndexNotReadyError,
+    InvalidInputError,
+    CancelledError,
+    CorruptedError,
+    UnsupportedVersionError,
+    ArmsIOError,
+    BackendError,
+    ReadOnlyError,
+    LockedError,
 )
 
 __all__ = [
@@ -40,7 +61,24 @@ __all__ = [
     "SearchResult",
     "SessionSummary",
     "DocumentSummary",
+    "ResultGroup",
+    "TopicSpan",
+    "SessionTimeline",
     "HatStats",
+    "CompressedKV",
+    "DimensionalityMismatchError",
+    "CapacityExceededError",
+    "DuplicateIdError",
+    "NotFoundError",
+    "IndexNotReadyError",
+    "InvalidInputError",
+    "CancelledError",
+    "CorruptedError",
+    "UnsupportedVersionError",
+    "ArmsIOError",
+    "BackendError",
+    "ReadOnlyError",
+    "LockedError",
 ]
 
 __version__ = "0.1.0"
@@ -0,0 +1,196 @@
+"""
+HuggingFace Transformers bridge for attention-state reuse.
+
+Converts between HAT's `CompressedKV` (raw tensor bytes in
+`[layer][head][seq][key/value][head_dim]` layout) and the KV caches used by
+`transformers` models (`DynamicCache` or legacy `past_key_values` tuples).
+
+Example:
+    >>> from transformers import AutoModelForCausalLM, AutoTokenizer
+    >>> from arms_hat import CompressedKV
+    >>> from arms_hat.hf import capture_kv, kv_to_dynamic_cache
+    >>>
+    >>> model = AutoModelForCausalLM.from_pretrained("gpt2")
+    >>> tok = AutoTokenizer.from_pretrained("gpt2")
+    >>> input_ids = tok("The user likes hiking.", return_tensors="pt").input_ids
+    >>>
+    >>> # Capture the KV cache of a prefill and store it
+    >>> kv = capture_kv(model, input_ids, model_id="gpt2")
+    >>> blob = kv.to_bytes()
+    >>>
+    >>> # Later: restore it and continue generation without recomputing the prefill
+    >>> cache = kv_to_dynamic_cache(CompressedKV.from_bytes(blob))
+    >>> out = model(next_ids, past_key_values=cache, use_cache=True)
+
+Requires `torch` and `transformers` (imported lazily).
+"""
+
+from .arms_hat import CompressedKV
+
+# Quantization name -> torch dtype name
+_DTYPES = {
+    "fp32": "float32",
+    "fp16": "float16",
+    "bf16": "bfloat16",
+}
+
+
+def _torch():
+    try:
+        import torch
+    except ImportError as e:
+        raise ImportError("arms_hat.hf requires torch: pip install torch") from e
+    return torch
+
+
+def _torch_dtype(quantization):
+    torch = _torch()
+    if quantization not in _DTYPES:
+        raise ValueError(
+            f"Unsupported quantization '{quantization}' (expected one of {sorted(_DTYPES)})"
+        )
+    return getattr(torch, _DTYPES[quantization])
+
+
+def _legacy_layers(past_key_values):
+    """Yield (key, value) per layer from a DynamicCache or legacy tuple."""
+    if hasattr(past_key_values, "to_legacy_cache"):
+        past_key_values = past_key_values.to_legacy_cache()
+    for layer in past_key_values:
+        key, value = layer[0], layer[1]
+        yield key, value
+
+
+def kv_from_past_key_values(past_key_values, model_id, quantization="fp16", batch_index=0):
+    """Convert a transformers KV cache into a CompressedKV.
+
+    Args:
+        past_key_values: `DynamicCache` or tuple of (key, value) per layer,
+            each shaped [batch, heads, seq, head_dim]
+        model_id: Model identifier stored with the cache (e.g., "llama-3-8b")
+        quantization: "fp16", "bf16", or "fp32"
+        batch_index: Which batch element to capture
+
+    Returns:
+        CompressedKV: The cache in HAT layout
+    """
+    torch = _torch()
+    dtype = _torch_dtype(quantization)
+
+    layers = []
+    for key, value in _legacy_layers(past_key_values):
+        # [heads, seq, head_dim] x2 -> [heads, seq, 2, head_dim]
+        layers.append(torch.stack([key[batch_index], value[batch_index]], dim=-2))
+
+    if not layers:
+        raise ValueError("past_key_values contains no layers")
+
+    stacked = torch.stack(layers).to(dtype=dtype, device="cpu").contiguous()
+    num_layers, num_heads, seq_len, _, head_dim = stacked.shape
+
+    # numpy has no bfloat16, so reinterpret as raw 16-bit words
+    if dtype == torch.bfloat16:
+        data = stacked.view(torch.int16).numpy().tobytes()
+    else:
+        data = stacked.numpy().tobytes()
+
+    return CompressedKV(
+        model_id,
+        num_layers,
+        num_heads,
+        head_dim,
+        seq_len,
+        quantization,
+        data,
+    )
+
+
+def kv_to_past_key_values(kv, device=None, dtype=None):
+    """Convert a CompressedKV into a legacy tuple of (key, value) per layer.
+
+    Args:
+        kv: CompressedKV in HAT layout
+        device: Target device (default: CPU)
+        dtype: Target dtype (default: the stored quantization's dtype)
+
+    Returns:
+        tuple: ((key, value), ...) with tensors shaped [1, heads, seq, head_dim]
+    """
+    torch = _torch()
+    stored = _torch_dtype(kv.quantization)
+
+    raw = bytearray(kv.data)
+    expected = kv.num_layers * kv.num_heads * kv.seq_len * 2 * kv.head_dim
+    if stored == torch.bfloat16:
+        flat = torch.frombuffer(raw, dtype=torch.int16).view(torch.bfloat16)
+    else:
+        flat = torch.frombuffer(raw, dtype=stored)
+    if flat.numel() != expected:
+        raise ValueError(
+            f"KV data has {flat.numel()} elements, expected {expected} "
+            f"({kv.num_layers}x{kv.num_heads}x{kv.seq_len}x2x{kv.head_dim})"
+        )
+
+    tensor = flat.view(kv.num_layers, kv.num_heads, kv.seq_len, 2, kv.head_dim)
+    tensor = tensor.to(device=device or "cpu", dtype=dtype or stored)
+
+    return tuple(
+        (
+            tensor[layer, :, :, 0, :].unsqueeze(0).contiguous(),
+            tensor[layer, :, :, 1, :].unsqueeze(0).contiguous(),
+        )
+        for layer in range(kv.num_layers)
+    )
+
+
+def kv_to_dynamic_cache(kv, device=None, dtype=None):
+    """Convert a CompressedKV into a transformers `DynamicCache`.
+
+    Args:
+        kv: CompressedKV in HAT layout
+        device: Target device (default: CPU)
+        dtype: Target dtype (default: the stored quantization's dtype)
+
+    Returns:
+        DynamicCache: Ready to pass as `past_key_values`
+    """
+    try:
+        from transformers import DynamicCache
+    except ImportError as e:
+        raise ImportError("kv_to_dynamic_cache requires transformers: pip install transformers") from e
+
+    cache = DynamicCache()
+    for layer, (key, value) in enumerate(kv_to_past_key_values(kv, device=device, dtype=dtype)):
+        cache.update(key, value, layer)
+    return cache
+
+
+def capture_kv(model, input_ids, model_id=None, quantization="fp16", **forward_kwargs):
+    """Run a forward pass and capture the resulting KV cache.
+
+    Args:
+        model: A transformers causal LM
+        input_ids: Token IDs tensor [1, seq]
+        model_id: Identifier stored with the cache (default: model.name_or_path)
+        quantization: "fp16", "bf16", or "fp32"
+        **forward_kwargs: Extra arguments passed to the forward call
+
+    Returns:
+        CompressedKV: The prefill's KV cache in HAT layout
+    """
+    torch = _torch()
+    if model_id is None:
+        model_id = getattr(model, "name_or_path", None) or type(model).__name__
+
+    with torch.no_grad():
+        outputs = model(input_ids=input_ids, use_cache=True, **forward_kwargs)
+
+    return kv_from_past_key_values(outputs.past_key_values, model_id, quantization=quantization)
+
+
+__all__ = [
+    "kv_from_past_key_values",
+    "kv_to_past_key_values",
+    "kv_to_dynamic_cache",
+    "capture_kv",
+]
@@ -272,6 +272,218 @@ def test_near_sessions():
         assert sessions[0].score >= sessions[1].score
 
 
+def test_session_summary():
+    """Test summaries routing and coming back from near_sessions."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(2)
+    travel = index.new_session_named("travel")
+    index.add([1.0, 0.0])
+    chat = index.new_session_named("chat")
+    index.add([0.6, 0.8])
+
+    index.set_session_summary(travel, "trip planning", [0.0, 1.0])
+    sessions = index.near_sessions([0.0, 1.0], k=2)
+    assert sessions[0].id == travel
+    assert sessions[0].summary == "trip planning"
+    assert sessions[1].id == chat and sessions[1].summary is None
+
+
+def test_importance_report():
+    """Test ranking a session's chunks by centrality."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(2)
+    session = index.new_session_named("history")
+    for vector, text in [([1.0, 0.1], "theme"), ([0.1, 1.0], "tangent"), ([1.0, 0.15], "theme again")]:
+        index.add(vector, text.encode())
+
+    report = index.importance_report(session, k=2)
+    assert {r.text for r in report} == {"theme", "theme again"}
+    assert report[0].score >= report[1].score
+
+
+def test_facts():
+    """Test upserting facts and retrieving them alongside vector hits."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(2)
+    tea = index.add([1.0, 0.0], b"I switched to tea")
+    index.add([0.0, 1.0], b"the weather is nice")
+
+    assert index.upsert_fact("user", "drinks", "coffee", source=tea) is None
+    replaced = index.upsert_fact("user", "drinks", "tea", confidence=0.9, source=tea)
+    assert replaced.value == "coffee"
+    index.upsert_fact("user", "age", 41)
+
+    assert [f.predicate for f in index.facts_about("user")] == ["age", "drinks"]
+    facts = index.facts_for(index.near([1.0, 0.1], k=1))
+    assert len(facts) == 1 and facts[0].value == "tea" and facts[0].source == tea
+    assert index.facts_for([tea])[0].confidence == pytest.approx(0.9)
+
+    assert index.remove_fact("user", "age").value == 41
+    assert index.fact("user", "age") is None
+
+
+def test_add_checked_contradictions():
+    """Test surfacing superseded memories when adding a new one."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(2)
+    paris, found = index.add_checked([1.0, 0.1], {"text": "lives in Paris", "supersedable": "residence"})
+    assert found == []
+    index.add([1.0, 0.12], {"text": "likes Paris in spring", "supersedable": "travel"})
+
+    berlin, found = index.add_checked([1.0, 0.15], {"text": "moved to Berlin", "supersedable": "residence"})
+    assert [r.id for r in found] == [paris]
+    assert found[0].payload["text"] == "lives in Paris"
+    assert berlin in index
+
+
+def test_near_min_score():
+    """Test dropping low-relevance results."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(4)
+    index.add([1.0, 0.0, 0.0, 0.0])
+    index.add([0.9, 0.1, 0.0, 0.0])
+    index.add([0.0, 0.0, 1.0, 0.0])
+
+    results = index.near([1.0, 0.0, 0.0, 0.0], k=3, min_score=0.5)
+    assert len(results) == 2
+    assert all(r.score >= 0.5 for r in results)
+
+
+def test_near_dedup():
+    """Test collapsing near-identical results."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(4)
+    best = index.add([1.0, 0.0, 0.0, 0.0])
+    index.add([1.0, 0.001, 0.0, 0.0])
+    other = index.add([0.6, 0.8, 0.0, 0.0])
+
+    results = index.near([1.0, 0.0, 0.0, 0.0], k=2, dedup=0.99)
+    assert [r.id for r in results] == [best, other]
+
+
+def test_near_fields():
+    """Test choosing which stored fields come back with results."""
+    import pytest
+    from arms_hat import HatIndex
+
+    index = HatIndex.euclidean(2)
+    index.add([1.0, 0.0], {"name": "a"})
+
+    default = index.near([1.0, 0.0], k=1)[0]
+    assert default.payload == {"name": "a"}
+    assert default.vector is None
+
+    bare = index.near([1.0, 0.0], k=1, fields=[])[0]
+    assert bare.payload is None and bare.vector is None
+
+    full = index.near_batch([[1.0, 0.0]], k=1, fields=["vector", "metadata"])[0][0]
+    assert full.vector == [1.0, 0.0]
+    assert full.payload == {"name": "a"}
+
+    with pytest.raises(ValueError):
+        index.near([1.0, 0.0], k=1, fields=["colour"])
+
+
+def test_near_grouped():
+    """Test top-k per session grouping."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(8)
+    for session in range(3):
+        if session:
+            index.new_session()
+        for i in range(4):
+            embedding = [0.0] * 8
+            embedding[session] = 1.0
+            embedding[session + 4] = 0.1 * i
+            index.add(embedding)
+
+    query = [1.0] + [0.0] * 7
+    groups = index.near_grouped(query, k_groups=2, k_per_group=2)
+    assert len(groups) == 2
+    assert all(len(g.results) == 2 for g in groups)
+    assert groups[0].key != groups[1].key
+    assert groups[0].results[0].score >= groups[1].results[0].score
+
+    with pytest.raises(ValueError):
+        index.near_grouped(query, 2, 2, group_by="metadata")
+
+
+def test_near_roles():
+    """Test restricting queries to chunk roles."""
+    import pytest
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(2)
+    system = index.add([1.0, 0.0], role="system")
+    answer = index.add([0.9, 0.1], role="assistant")
+    untagged = index.add([0.8, 0.2])
+    assert index.role(answer) == "assistant"
+    assert index.role(untagged) is None
+
+    assert [r.id for r in index.near([1.0, 0.0], k=3)] == [system, answer, untagged]
+    assert [r.id for r in index.near([1.0, 0.0], k=3, roles=["assistant"])] == [answer]
+
+    index.set_role(untagged, "user")
+    assert [r.id for r in index.near([1.0, 0.0], k=3, roles=["user", "assistant"])] == [answer, untagged]
+
+    with pytest.raises(ValueError):
+        index.add([1.0, 0.0], role="narrator")
+
+
+def test_topic_timeline():
+    """Test per-session topic timelines."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(4)
+    for topic in [0, 0, 2]:
+        index.new_document()
+        for i in range(3):
+            embedding = [0.0] * 4
+            embedding[topic] = 1.0
+            embedding[topic + 1] = 0.01 * i
+            index.add(embedding)
+
+    timelines = index.topic_timeline(k_topics=2, seed=1)
+    assert len(timelines) == 1
+    spans = timelines[0].spans
+    assert [s.documents for s in spans] == [2, 1]
+    assert spans[0].topic != spans[1].topic
+    assert sum(s.chunks for s in spans) == 9
+
+
+def test_project_2d():
+    """Test 2-D projection export."""
+    import json
+    import pytest
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(4)
+    for i in range(20):
+        embedding = [0.0] * 4
+        embedding[i % 2] = 1.0
+        embedding[2] = 0.01 * i
+        index.add(embedding)
+
+    records = json.loads(index.project_2d(sample_size=8, seed=3))
+    assert len(records) == 8
+    assert all({"id", "x", "y"} <= set(r) for r in records)
+    assert "session" in records[0]["metadata"]
+
+    lines = index.project_2d(format="csv").splitlines()
+    assert lines[0] == "id,x,y,document,session"
+    assert len(lines) == 21
+
+    with pytest.raises(ValueError):
+        index.project_2d(format="xml")
+
+
 def test_high_dimensions():
     """Test with OpenAI embedding dimensions."""
     from arms_hat import HatIndex
@@ -292,5 +504,359 @@ def test_high_dimensions():
     assert len(results) == 5
 
 
+
+def test_iteration():
+    """Test enumerating contents."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(4)
+    id1 = index.add([1.0, 0.0, 0.0, 0.0])
+    index.new_session()
+    id2 = index.add([0.0, 1.0, 0.0, 0.0])
+
+    assert list(index) == [id1, id2]
+
+    items = index.items()
+    assert [item[0] for item in items] == [id1, id2]
+    assert items[1][1] == [0.0, 1.0, 0.0, 0.0]
+    assert items[0][2] is None
+
+
+
+def test_get_and_contains():
+    """Test retrieving stored embeddings."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(3)
+    id1 = index.add([1.0, 0.0, 0.0])
+
+    assert index.contains(id1)
+    assert id1 in index
+    assert "not-an-id" not in index
+
+    vector, blob = index.get(id1)
+    assert vector == [1.0, 0.0, 0.0]
+    assert blob is None
+    assert index.get_vector(id1) == [1.0, 0.0, 0.0]
+    assert index.get_blob(id1) is None
+
+    missing = "0" * 32
+    assert index.get(missing) is None
+    assert not index.contains(missing)
+
+
+
+def test_payloads():
+    """Test attaching payloads on add."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(3)
+    raw = index.add([1.0, 0.0, 0.0], payload=b"\x00raw")
+    text = index.add([0.0, 1.0, 0.0], payload="hello")
+    meta = index.add([0.0, 0.0, 1.0], payload={"user": "ana", "turn": 3, "score": 0.5, "pinned": True, "parent": None})
+
+    assert index.get_blob(raw) == b"\x00raw"
+    assert index.get(text)[1] == b"hello"
+    assert index.get_metadata(meta) == {"user": "ana", "turn": 3, "score": 0.5, "pinned": True, "parent": None}
+
+    results = index.near([0.0, 1.0, 0.0], k=3)
+    assert results[0].id == text
+    assert results[0].payload == b"hello"
+    assert results[0].text == "hello"
+
+    top = index.near([0.0, 0.0, 1.0], k=1)[0]
+    assert top.payload["turn"] == 3
+    assert isinstance(top.payload["pinned"], bool)
+
+    with pytest.raises(TypeError):
+        index.add([1.0, 1.0, 0.0], payload={"nested": {"a": 1}})
+    assert len(index) == 3
+
+
+
+def test_proximity_choice():
+    """Test euclidean and dot-product indexes."""
+    from arms_hat import HatIndex, HatConfig
+
+    index = HatIndex.euclidean(2)
+    assert index.proximity == "euclidean"
+    near = index.add([1.0, 1.0])
+    far = index.add([10.0, 10.0])
+
+    # Same direction; only distance separates them
+    results = index.near([1.5, 1.5], k=2)
+    assert results[0].id == near
+    assert results[0].score < results[1].score
+    assert index.near([9.0, 9.0], k=1)[0].id == far
+
+    assert HatIndex.dot_product(2).proximity == "dot_product"
+
+    config = HatConfig().with_proximity("dot_product")
+    assert HatIndex.with_config(2, config).proximity == "dot_product"
+    assert HatIndex.with_config(2, HatConfig()).proximity == "cosine"
+
+    with pytest.raises(ValueError):
+        HatConfig().with_proximity("hamming")
+
+
+def test_proximity_persists():
+    """Test that proximity survives save/load."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.euclidean(2)
+    index.add([1.0, 1.0])
+
+    with tempfile.TemporaryDirectory() as tmpdir:
+        path = os.path.join(tmpdir, "euclidean.hat")
+        index.save(path)
+        assert HatIndex.load(path).proximity == "euclidean"
+
+
+
+def test_custom_proximity():
+    """Test Python callable proximity with and without a batch path."""
+    from arms_hat import HatIndex
+
+    calls = {"single": 0, "batch": 0}
+
+    def manhattan(a, b):
+        calls["single"] += 1
+        return sum(abs(x - y) for x, y in zip(a, b))
+
+    def manhattan_batch(query, candidates):
+        calls["batch"] += 1
+        return [manhattan(query, c) for c in candidates]
+
+    for batch in (None, manhattan_batch):
+        index = HatIndex.custom(2, manhattan, higher_is_better=False, batch=batch)
+        assert index.proximity == "python"
+        near = index.add([1.0, 1.0])
+        index.add([5.0, 5.0])
+        assert index.near([1.2, 0.9], k=1)[0].id == near
+
+    assert calls["batch"] > 0
+
+
+def test_custom_proximity_errors():
+    """Test errors raised by a custom proximity surface in Python."""
+    from arms_hat import HatIndex
+
+    def broken(a, b):
+        raise RuntimeError("boom")
+
+    index = HatIndex.custom(2, broken)
+    index.add([1.0, 0.0])
+    with pytest.raises(RuntimeError):
+        index.add([0.0, 1.0])
+        index.near([1.0, 0.0], k=1)
+
+    with pytest.raises(TypeError):
+        HatIndex.custom(2, "not callable")
+
+
+
+def test_batch_and_threads():
+    """Test batch add/query and sharing an index across threads."""
+    from concurrent.futures import ThreadPoolExecutor
+    from arms_hat import HatIndex, HatConfig
+
+    index = HatIndex.with_config(3, HatConfig().with_n_threads(4))
+    ids = index.add_batch(
+        [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        payloads=["x", None, {"axis": "z"}],
+    )
+    assert len(index) == 3
+    assert index.get_blob(ids[0]) == b"x"
+    assert index.get_metadata(ids[2]) == {"axis": "z"}
+
+    batches = index.near_batch([[1.0, 0.0, 0.0], [0.0, 0.0, 1.0]], k=1)
+    assert [b[0].id for b in batches] == [ids[0], ids[2]]
+
+    with pytest.raises(ValueError):
+        index.add_batch([[1.0, 0.0, 0.0]], payloads=["a", "b"])
+
+    def worker(i):
+        if i % 4 == 0:
+            index.add([0.5, 0.5, 0.0])
+        return index.near([1.0, 0.0, 0.0], k=1)[0].id
+
+    with ThreadPoolExecutor(max_workers=8) as pool:
+        results = list(pool.map(worker, range(32)))
+
+    assert all(r == ids[0] for r in results)
+    assert len(index) == 3 + 8
+
+
+def test_named_sessions():
+    """Test naming sessions/documents and looking them up."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(2)
+    support = index.new_session_named("2024-07-04 support chat")
+    intro = index.new_document_named("intro")
+    index.add([1.0, 0.0])
+
+    index.set_metadata(support, {"name": "2024-07-04 support chat", "team": "infra"})
+
+    assert index.find_session("2024-07-04 support chat") == support
+    assert index.find_document("intro") == intro
+    assert index.find_session("missing") is None
+    assert index.find_by_metadata("team", "infra") == [support]
+    assert index.get_metadata(intro) == {"name": "intro"}
+
+    with pytest.raises(ValueError):
+        index.set_metadata("0" * 32, {"name": "nobody"})
+
+
+def test_export_import_session():
+    """Test moving a session between indexes as a shard."""
+    from arms_hat import HatIndex, DuplicateIdError
+
+    source = HatIndex.cosine(2)
+    session = source.new_session_named("travel")
+    chunk = source.add([1.0, 0.0], "paris")
+    shard = source.export_session(session)
+
+    target = HatIndex.cosine(2)
+    target.add([0.0, 1.0])
+    assert target.import_session(shard) == session
+    assert len(target) == 2
+    assert target.find_session("travel") == session
+    assert target.get_blob(chunk) == b"paris"
+
+    with pytest.raises(DuplicateIdError):
+        target.import_session(shard)
+
+
+def test_consolidate_async_job():
+    """Test background consolidation with progress and cancellation."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(8)
+    for i in range(200):
+        index.add([float((i * j) % 7) + 0.1 for j in range(8)])
+
+    job = index.consolidate_async("full")
+    assert job.name == "consolidate (full)"
+    assert job.wait() is True
+    assert job.finished
+    assert job.percent == 100.0
+    assert job.eta == 0.0
+    assert len(index.near([0.1] * 8, k=3)) == 3
+
+    cancelled = index.consolidate_async("deep")
+    cancelled.cancel()
+    assert cancelled.wait() is False
+    assert cancelled.cancelled
+
+    with pytest.raises(ValueError):
+        cancelled.wait()
+    with pytest.raises(ValueError):
+        index.consolidate_async("extreme")
+
+
+def test_compress():
+    """Test replacing old documents with callback-written summaries."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(2)
+    for doc in range(3):
+        index.new_document()
+        for turn in range(3):
+            index.add([1.0, 0.1 * turn], f"doc {doc} turn {turn}".encode())
+
+    def summarize(chunks):
+        texts = [payload.decode() for _, _, payload in chunks]
+        if texts[0].startswith("doc 1"):
+            return None
+        return " / ".join(texts), [0.0, 1.0]
+
+    summaries = index.compress(summarize, older_than_ms=0)
+    assert len(summaries) == 1
+    assert len(index) == 7
+    top = index.near([0.0, 1.0], k=1)[0]
+    assert top.id == summaries[0]
+    assert top.payload == b"doc 0 turn 0 / doc 0 turn 1 / doc 0 turn 2"
+
+    def broken(chunks):
+        raise RuntimeError("model offline")
+
+    with pytest.raises(RuntimeError):
+        index.compress(broken, older_than_ms=0)
+    assert index.compress_async(broken, older_than_ms=0).wait() is False
+
+
+def test_numpy_roundtrip():
+    """Test exporting to NumPy arrays and rebuilding from them."""
+    np = pytest.importorskip("numpy")
+    from arms_hat import HatIndex
+
+    index = HatIndex.euclidean(3)
+    first = index.add([1.0, 0.0, 0.0], {"name": "first"})
+    index.add([0.0, 2.0, 0.0], b"raw")
+    index.add([0.0, 0.0, 3.0])
+
+    ids, vectors, payloads = index.to_numpy()
+    assert ids[0] == first
+    assert vectors.dtype == np.float32
+    assert vectors.shape == (3, 3)
+    assert payloads == [{"name": "first"}, b"raw", None]
+
+    rebuilt = HatIndex.from_numpy(ids, vectors * 2, payloads, proximity="euclidean")
+    assert rebuilt.proximity == "euclidean"
+    assert rebuilt.get(first) == ([2.0, 0.0, 0.0], {"name": "first"})
+    assert rebuilt.near([0.0, 0.0, 6.0], k=1)[0].id == ids[2]
+
+    generated = HatIndex.from_numpy(None, [[1.0, 0.0], [0.0, 1.0]])
+    assert len(generated) == 2
+
+    with pytest.raises(ValueError):
+        HatIndex.from_numpy(ids[:2], vectors)
+    with pytest.raises(ValueError):
+        HatIndex.from_numpy(None, np.zeros(3, dtype=np.float32))
+
+
+def test_provenance():
+    """Test provenance riding along with search results."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(2)
+    wiki = index.add([1.0, 0.0], b"Berlin is the capital")
+    forum = index.add([1.0, 0.1], b"Berlin is in Bavaria")
+    index.set_provenance(wiki, source="wiki/berlin", model_version="e5-large-v2", confidence=0.95)
+    index.set_provenance(forum, source="forum", confidence=0.2)
+
+    results = {r.id: r for r in index.near([1.0, 0.05], k=2)}
+    assert results[wiki].provenance["source"] == "wiki/berlin"
+    assert results[forum].confidence == pytest.approx(0.2)
+    assert index.near([1.0, 0.05], k=1, fields=[])[0].provenance is None
+
+    index.set_provenance(forum)
+    assert index.get_provenance(forum) is None
+    assert index.get_provenance(wiki)["model_version"] == "e5-large-v2"
+
+
+def test_ephemeral_overlay():
+    """Test session overlay points: searched, then promoted or discarded."""
+    from arms_hat import HatIndex
+
+    index = HatIndex.cosine(2)
+    index.add([1.0, 0.0], b"long-term")
+    plan = index.add_ephemeral([0.0, 1.0], payload="draft plan")
+    aside = index.add_ephemeral([0.6, 0.8])
+
+    results = index.near([0.1, 1.0], k=3)
+    assert [r.id for r in results][:2] == [plan, aside]
+    assert results[0].text == "draft plan"
+    assert len(index) == 1 and index.ephemeral_ids() == [plan, aside]
+
+    assert index.close_ephemeral(keep=[plan]) == [plan]
+    assert len(index) == 2 and plan in index and aside not in index
+    assert index.ephemeral_ids() == []
+
+    index.add_ephemeral([0.5, 0.5])
+    assert index.discard_ephemeral() == 1
+
+
 if __name__ == "__main__":
     pytest.main([__file__, "-v"])
@@ -0,0 +1,76 @@
+"""Tests for the HuggingFace KV cache bridge."""
+
+import pytest
+
+torch = pytest.importorskip("torch")
+
+
+def make_past_key_values(layers=2, heads=4, seq=5, head_dim=8):
+    return tuple(
+        (torch.randn(1, heads, seq, head_dim), torch.randn(1, heads, seq, head_dim))
+        for _ in range(layers)
+    )
+
+
+def test_compressed_kv_bytes_roundtrip():
+    """Test CompressedKV serialization."""
+    from arms_hat import CompressedKV
+
+    kv = CompressedKV("test-model", 1, 1, 2, 1, "fp32", b"\x00" * 8)
+    restored = CompressedKV.from_bytes(kv.to_bytes())
+
+    assert restored.model_id == "test-model"
+    assert restored.head_dim == 2
+    assert restored.data == b"\x00" * 8
+
+
+def test_past_key_values_roundtrip():
+    """Test legacy cache -> CompressedKV -> legacy cache."""
+    from arms_hat.hf import kv_from_past_key_values, kv_to_past_key_values
+
+    past = make_past_key_values()
+    kv = kv_from_past_key_values(past, "test-model", quantization="fp32")
+
+    assert kv.num_layers == 2
+    assert kv.num_heads == 4
+    assert kv.seq_len == 5
+    assert kv.head_dim == 8
+    assert kv.size_bytes() == 2 * 4 * 5 * 2 * 8 * 4
+
+    restored = kv_to_past_key_values(kv)
+    for (k, v), (rk, rv) in zip(past, restored):
+        assert torch.equal(k, rk)
+        assert torch.equal(v, rv)
+
+
+def test_fp16_quantization():
+    """Test that fp16 storage halves the size."""
+    from arms_hat.hf import kv_from_past_key_values, kv_to_past_key_values
+
+    past = make_past_key_values()
+    kv = kv_from_past_key_values(past, "test-model", quantization="fp16")
+
+    assert kv.size_bytes() == 2 * 4 * 5 * 2 * 8 * 2
+
+    restored = kv_to_past_key_values(kv, dtype=torch.float32)
+    assert torch.allclose(past[0][0], restored[0][0], atol=1e-2)
+
+
+def test_dynamic_cache():
+    """Test conversion into a transformers DynamicCache."""
+    pytest.importorskip("transformers")
+    from arms_hat.hf import kv_from_past_key_values, kv_to_dynamic_cache
+
+    past = make_past_key_values()
+    kv = kv_from_past_key_values(past, "test-model", quantization="fp32")
+    cache = kv_to_dynamic_cache(kv)
+
+    assert cache.get_seq_length() == 5
+
+
+def test_invalid_quantization():
+    """Test that unsupported quantizations are rejected."""
+    from arms_hat.hf import kv_from_past_key_values
+
+    with pytest.raises(ValueError):
+        kv_from_past_key_values(make_past_key_values(), "test-model", quantization="int3")
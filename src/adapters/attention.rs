@@ -27,8 +27,32 @@
 //! ├── kv_cache: Option<CompressedKV> (model-specific)
 //! └── metadata: HashMap<String, String>
 //! ```
+//!
+//! ## Versions
+//!
+//! - **v1**: Single contiguous buffer, KV cache embedded as one length-prefixed blob.
+//! - **v2**: Streamable via `AttentionState::write_to` / `read_from`. The KV cache
+//!   header is written inline and its data follows as length-prefixed chunks
+//!   (at most `KV_CHUNK_SIZE` bytes each, terminated by a zero-length chunk),
+//!   so multi-GB caches never need a second contiguous copy.
+//!
+//! Readers accept both versions; writers emit v2.
+//!
+//! `AttentionStateRef` / `AttentionBatchRef` parse either version in place
+//! over a borrowed buffer (e.g., an mmap'd file) without copying contents.
+
+use std::io::{self, Read, Write};
+use std::sync::Arc;
 
-use crate::core::Id;
+use crate::core::bytes::{ByteError, ByteReader};
+use crate::core::gen::{Speaker, Turn};
+use crate::core::{Cause, Id};
+
+/// Current AttentionState format version
+pub const ATTENTION_FORMAT_VERSION: u32 = 2;
+
+/// Maximum KV bytes per chunk in the v2 format
+pub const KV_CHUNK_SIZE: usize = 64 * 1024;
 
 /// Role in conversation
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -56,6 +80,7 @@ impl Role {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "system" => Some(Role::System),
@@ -67,7 +92,7 @@ impl Role {
         }
     }
 
-    fn to_byte(&self) -> u8 {
+    pub(crate) fn to_byte(self) -> u8 {
         match self {
             Role::System => 0,
             Role::User => 1,
@@ -77,7 +102,7 @@ impl Role {
         }
     }
 
-    fn from_byte(b: u8) -> Option<Self> {
+    pub(crate) fn from_byte(b: u8) -> Option<Self> {
         match b {
             0 => Some(Role::System),
             1 => Some(Role::User),
@@ -89,6 +114,49 @@ impl Role {
     }
 }
 
+/// A set of roles to restrict retrieval to
+///
+/// Chunks stored without a role count as a member of their own, kept by
+/// `all()` and `without` but not by `only`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Roles(u8);
+
+impl Roles {
+    /// Bit for chunks stored without a role
+    const UNTAGGED: u8 = 1 << 5;
+
+    /// Every chunk, tagged or not
+    pub fn all() -> Self {
+        Roles(0b11_1111)
+    }
+
+    /// Just chunks with one of `roles`
+    pub fn only(roles: impl IntoIterator<Item = Role>) -> Self {
+        Roles(roles.into_iter().fold(0, |mask, role| mask | 1 << role.to_byte()))
+    }
+
+    /// This set minus `role`
+    pub fn without(self, role: Role) -> Self {
+        Roles(self.0 & !(1 << role.to_byte()))
+    }
+
+    /// Whether a chunk with `role` (None = untagged) is in the set
+    pub fn contains(self, role: Option<Role>) -> bool {
+        let bit = role.map_or(Self::UNTAGGED, |role| 1 << role.to_byte());
+        self.0 & bit != 0
+    }
+
+    pub fn is_all(self) -> bool {
+        self == Self::all()
+    }
+}
+
+impl Default for Roles {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
 /// Compressed KV cache for a specific model architecture
 ///
 /// This is model-specific. Different models have different:
@@ -128,6 +196,53 @@ impl CompressedKV {
         self.data.len()
     }
 
+    /// Bytes per element for the quantization format
+    ///
+    /// Returns None for packed or unknown formats (e.g., "int4", "none").
+    pub fn element_size(&self) -> Option<usize> {
+        match self.quantization.as_str() {
+            "fp32" => Some(4),
+            "fp16" | "bf16" => Some(2),
+            "int8" | "fp8" => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Extract the KV states for a token range `[start, end)`
+    ///
+    /// Returns None if the range is out of bounds, the quantization
+    /// isn't byte-addressable, or the data doesn't match the declared shape.
+    pub fn slice_seq(&self, start: usize, end: usize) -> Option<Self> {
+        let elem = self.element_size()?;
+        let seq_len = self.seq_len as usize;
+        if start > end || end > seq_len {
+            return None;
+        }
+
+        // One token = key + value rows for a single head
+        let token_bytes = 2 * self.head_dim as usize * elem;
+        let heads = self.num_layers as usize * self.num_heads as usize;
+        if self.data.len() != heads * seq_len * token_bytes {
+            return None;
+        }
+
+        let mut data = Vec::with_capacity(heads * (end - start) * token_bytes);
+        for head in 0..heads {
+            let base = head * seq_len * token_bytes;
+            data.extend_from_slice(&self.data[base + start * token_bytes..base + end * token_bytes]);
+        }
+
+        Some(Self {
+            model_id: self.model_id.clone(),
+            num_layers: self.num_layers,
+            num_heads: self.num_heads,
+            head_dim: self.head_dim,
+            seq_len: (end - start) as u32,
+            quantization: self.quantization.clone(),
+            data,
+        })
+    }
+
     /// Create a placeholder (for models that don't support KV export)
     pub fn placeholder(model_id: &str) -> Self {
         Self {
@@ -170,59 +285,12 @@ impl CompressedKV {
 
     /// Deserialize from bytes
     pub fn from_bytes(data: &[u8]) -> Option<(Self, usize)> {
-        let mut offset = 0;
-
-        // Model ID
-        if data.len() < offset + 4 {
-            return None;
-        }
-        let model_len = u32::from_le_bytes(data[offset..offset + 4].try_into().ok()?) as usize;
-        offset += 4;
-
-        if data.len() < offset + model_len {
-            return None;
-        }
-        let model_id = String::from_utf8(data[offset..offset + model_len].to_vec()).ok()?;
-        offset += model_len;
-
-        // Architecture params
-        if data.len() < offset + 16 {
-            return None;
-        }
-        let num_layers = u32::from_le_bytes(data[offset..offset + 4].try_into().ok()?);
-        offset += 4;
-        let num_heads = u32::from_le_bytes(data[offset..offset + 4].try_into().ok()?);
-        offset += 4;
-        let head_dim = u32::from_le_bytes(data[offset..offset + 4].try_into().ok()?);
-        offset += 4;
-        let seq_len = u32::from_le_bytes(data[offset..offset + 4].try_into().ok()?);
-        offset += 4;
-
-        // Quantization
-        if data.len() < offset + 4 {
-            return None;
-        }
-        let quant_len = u32::from_le_bytes(data[offset..offset + 4].try_into().ok()?) as usize;
-        offset += 4;
-
-        if data.len() < offset + quant_len {
-            return None;
-        }
-        let quantization = String::from_utf8(data[offset..offset + quant_len].to_vec()).ok()?;
-        offset += quant_len;
-
-        // Data
-        if data.len() < offset + 8 {
-            return None;
-        }
-        let data_len = u64::from_le_bytes(data[offset..offset + 8].try_into().ok()?) as usize;
-        offset += 8;
-
-        if data.len() < offset + data_len {
-            return None;
-        }
-        let kv_data = data[offset..offset + data_len].to_vec();
-        offset += data_len;
+        let mut r = ByteReader::new(data);
+        let model_id = r.str("model ID").ok()?.to_string();
+        let (num_layers, num_heads, head_dim, seq_len) = read_kv_dims(&mut r).ok()?;
+        let quantization = r.str("quantization").ok()?.to_string();
+        let data_len = r.u64("KV cache length").ok()?;
+        let kv_data = r.bytes_u64(data_len, "KV cache").ok()?.to_vec();
 
         Some((
             Self {
@@ -234,7 +302,7 @@ impl CompressedKV {
                 quantization,
                 data: kv_data,
             },
-            offset,
+            r.position(),
         ))
     }
 }
@@ -269,10 +337,7 @@ impl AttentionState {
     pub fn new(role: Role, text: String, embedding: Vec<f32>) -> Self {
         Self {
             id: Id::now(),
-            timestamp_ms: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
+            timestamp_ms: crate::core::clock::now_ms(),
             role,
             text,
             embedding,
@@ -304,8 +369,169 @@ impl AttentionState {
         self.metadata.iter().map(|(k, v)| k.len() + v.len() + 8).sum::<usize>()
     }
 
-    /// Serialize to bytes
+    /// Serialize to bytes (current format version)
     pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.size_bytes() + 64);
+        self.write_to(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
+    }
+
+    /// Stream the state to a writer in the v2 format
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        // Magic + version
+        w.write_all(b"ATTN")?;
+        w.write_all(&ATTENTION_FORMAT_VERSION.to_le_bytes())?;
+
+        // ID, timestamp, role
+        w.write_all(self.id.as_bytes())?;
+        w.write_all(&self.timestamp_ms.to_le_bytes())?;
+        w.write_all(&[self.role.to_byte()])?;
+
+        // Text
+        write_str(w, &self.text)?;
+
+        // Embedding
+        w.write_all(&(self.embedding.len() as u32).to_le_bytes())?;
+        for &v in &self.embedding {
+            w.write_all(&v.to_le_bytes())?;
+        }
+
+        // KV cache (present flag + header + chunked data)
+        if let Some(ref kv) = self.kv_cache {
+            w.write_all(&[1])?;
+            write_str(w, &kv.model_id)?;
+            w.write_all(&kv.num_layers.to_le_bytes())?;
+            w.write_all(&kv.num_heads.to_le_bytes())?;
+            w.write_all(&kv.head_dim.to_le_bytes())?;
+            w.write_all(&kv.seq_len.to_le_bytes())?;
+            write_str(w, &kv.quantization)?;
+            w.write_all(&(kv.data.len() as u64).to_le_bytes())?;
+            for chunk in kv.data.chunks(KV_CHUNK_SIZE) {
+                w.write_all(&(chunk.len() as u32).to_le_bytes())?;
+                w.write_all(chunk)?;
+            }
+            w.write_all(&0u32.to_le_bytes())?;
+        } else {
+            w.write_all(&[0])?;
+        }
+
+        // Metadata (count + entries)
+        w.write_all(&(self.metadata.len() as u32).to_le_bytes())?;
+        for (key, value) in &self.metadata {
+            write_str(w, key)?;
+            write_str(w, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Stream a state from a reader (v1 or v2)
+    ///
+    /// v2 states are consumed exactly, so several states can be read back to
+    /// back from one stream. v1 has no streamable layout: the reader is
+    /// drained to the end and parsed as a single state.
+    pub fn read_from<R: Read>(r: &mut R) -> Result<Self, AttentionError> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != b"ATTN" {
+            return Err(AttentionError::InvalidMagic);
+        }
+
+        let version = read_u32(r)?;
+        match version {
+            1 => {
+                let mut data = b"ATTN".to_vec();
+                data.extend_from_slice(&1u32.to_le_bytes());
+                r.read_to_end(&mut data)?;
+                Self::from_bytes_v1(&data)
+            }
+            2 => Self::read_body_v2(r),
+            v => Err(AttentionError::UnsupportedVersion(v)),
+        }
+    }
+
+    /// Read everything after the magic + version of a v2 state
+    fn read_body_v2<R: Read>(r: &mut R) -> Result<Self, AttentionError> {
+        let mut id_bytes = [0u8; 16];
+        r.read_exact(&mut id_bytes)?;
+        let id = Id::from_bytes(id_bytes);
+
+        let timestamp_ms = read_u64(r)?;
+
+        let role = Role::from_byte(read_u8(r)?)
+            .ok_or_else(|| AttentionError::InvalidFormat("Invalid role".into()))?;
+
+        let text = read_str(r, "text")?;
+
+        let emb_len = read_u32(r)? as usize;
+        let emb_bytes = read_vec(r, emb_len * 4, "Embedding truncated")?;
+        let embedding = emb_bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+
+        let kv_cache = if read_u8(r)? != 0 {
+            let model_id = read_str(r, "model ID")?;
+            let num_layers = read_u32(r)?;
+            let num_heads = read_u32(r)?;
+            let head_dim = read_u32(r)?;
+            let seq_len = read_u32(r)?;
+            let quantization = read_str(r, "quantization")?;
+            let total_len = read_u64(r)? as usize;
+
+            // Don't trust the declared length for preallocation
+            let mut data = Vec::with_capacity(total_len.min(KV_CHUNK_SIZE * 16));
+            loop {
+                let chunk_len = read_u32(r)? as usize;
+                if chunk_len == 0 {
+                    break;
+                }
+                if data.len() + chunk_len > total_len {
+                    return Err(AttentionError::InvalidFormat("KV chunk overflows declared length".into()));
+                }
+                data.extend_from_slice(&read_vec(r, chunk_len, "KV data truncated")?);
+            }
+            if data.len() != total_len {
+                return Err(AttentionError::InvalidFormat("KV data truncated".into()));
+            }
+
+            Some(CompressedKV {
+                model_id,
+                num_layers,
+                num_heads,
+                head_dim,
+                seq_len,
+                quantization,
+                data,
+            })
+        } else {
+            None
+        };
+
+        let meta_count = read_u32(r)? as usize;
+        let mut metadata = std::collections::HashMap::new();
+        for _ in 0..meta_count {
+            let key = read_str(r, "key")?;
+            let value = read_str(r, "value")?;
+            metadata.insert(key, value);
+        }
+
+        Ok(Self {
+            id,
+            timestamp_ms,
+            role,
+            text,
+            embedding,
+            kv_cache,
+            metadata,
+        })
+    }
+
+    /// Serialize to the legacy v1 format
+    ///
+    /// Only needed when handing states to readers that predate v2.
+    pub fn to_bytes_v1(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
 
         // Magic + version
@@ -356,143 +582,189 @@ impl AttentionState {
         bytes
     }
 
-    /// Deserialize from bytes
+    /// Deserialize from bytes (v1 or v2)
     pub fn from_bytes(data: &[u8]) -> Result<Self, AttentionError> {
-        let mut offset = 0;
-
-        // Magic
-        if data.len() < 8 {
-            return Err(AttentionError::InvalidFormat("Too short".into()));
-        }
-        if &data[0..4] != b"ATTN" {
+        let mut r = ByteReader::new(data);
+        if r.array::<4>("magic")? != *b"ATTN" {
             return Err(AttentionError::InvalidMagic);
         }
-        offset += 4;
 
-        // Version
-        let version = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
-        if version != 1 {
-            return Err(AttentionError::UnsupportedVersion(version));
+        let version = r.u32("version")?;
+        match version {
+            1 => Self::from_bytes_v1(data),
+            2 => Self::read_body_v2(&mut &data[8..]),
+            v => Err(AttentionError::UnsupportedVersion(v)),
         }
-        offset += 4;
+    }
 
-        // ID
-        if data.len() < offset + 16 {
-            return Err(AttentionError::InvalidFormat("Missing ID".into()));
-        }
-        let mut id_bytes = [0u8; 16];
-        id_bytes.copy_from_slice(&data[offset..offset + 16]);
-        let id = Id::from_bytes(id_bytes);
-        offset += 16;
+    /// Deserialize the legacy v1 format
+    fn from_bytes_v1(data: &[u8]) -> Result<Self, AttentionError> {
+        Ok(AttentionStateRef::from_bytes(data)?.to_owned_state())
+    }
+}
 
-        // Timestamp
-        if data.len() < offset + 8 {
-            return Err(AttentionError::InvalidFormat("Missing timestamp".into()));
-        }
-        let timestamp_ms = u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
-        offset += 8;
+// ============================================================================
+// ZERO-COPY VIEWS
+// ============================================================================
 
-        // Role
-        if data.len() < offset + 1 {
-            return Err(AttentionError::InvalidFormat("Missing role".into()));
-        }
-        let role = Role::from_byte(data[offset])
-            .ok_or_else(|| AttentionError::InvalidFormat("Invalid role".into()))?;
-        offset += 1;
+/// Borrowed view of a `CompressedKV` inside a serialized state
+///
+/// v1 states store KV data contiguously (one chunk); v2 states store it as
+/// chunks, which are exposed as-is rather than concatenated.
+#[derive(Debug, Clone)]
+pub struct CompressedKVRef<'a> {
+    pub model_id: &'a str,
+    pub num_layers: u32,
+    pub num_heads: u32,
+    pub head_dim: u32,
+    pub seq_len: u32,
+    pub quantization: &'a str,
+    chunks: Vec<&'a [u8]>,
+}
 
-        // Text
-        if data.len() < offset + 4 {
-            return Err(AttentionError::InvalidFormat("Missing text length".into()));
-        }
-        let text_len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
+impl<'a> CompressedKVRef<'a> {
+    /// Total KV data size in bytes
+    pub fn data_len(&self) -> usize {
+        self.chunks.iter().map(|c| c.len()).sum()
+    }
 
-        if data.len() < offset + text_len {
-            return Err(AttentionError::InvalidFormat("Text truncated".into()));
-        }
-        let text = String::from_utf8(data[offset..offset + text_len].to_vec())
-            .map_err(|_| AttentionError::InvalidFormat("Invalid UTF-8 in text".into()))?;
-        offset += text_len;
+    /// KV data chunks in order
+    pub fn chunks(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.chunks.iter().copied()
+    }
 
-        // Embedding
-        if data.len() < offset + 4 {
-            return Err(AttentionError::InvalidFormat("Missing embedding length".into()));
+    /// KV data as a single slice, if it is stored contiguously
+    pub fn as_contiguous(&self) -> Option<&'a [u8]> {
+        match self.chunks.as_slice() {
+            [] => Some(&[]),
+            [only] => Some(only),
+            _ => None,
         }
-        let emb_len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
+    }
 
-        if data.len() < offset + emb_len * 4 {
-            return Err(AttentionError::InvalidFormat("Embedding truncated".into()));
+    /// Copy into an owned `CompressedKV`
+    pub fn to_owned_kv(&self) -> CompressedKV {
+        let mut data = Vec::with_capacity(self.data_len());
+        for chunk in &self.chunks {
+            data.extend_from_slice(chunk);
         }
-        let mut embedding = Vec::with_capacity(emb_len);
-        for _ in 0..emb_len {
-            embedding.push(f32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()));
-            offset += 4;
+        CompressedKV {
+            model_id: self.model_id.to_string(),
+            num_layers: self.num_layers,
+            num_heads: self.num_heads,
+            head_dim: self.head_dim,
+            seq_len: self.seq_len,
+            quantization: self.quantization.to_string(),
+            data,
         }
+    }
+}
+
+/// Borrowed view of a serialized `AttentionState`
+///
+/// Parses in place over a byte buffer (e.g., an mmap'd file): text, metadata
+/// and KV data are slices into the buffer. The embedding is kept as raw
+/// little-endian bytes since the buffer gives no alignment guarantee.
+#[derive(Debug, Clone)]
+pub struct AttentionStateRef<'a> {
+    pub id: Id,
+    pub timestamp_ms: u64,
+    pub role: Role,
+    pub text: &'a str,
+    embedding: &'a [u8],
+    pub kv_cache: Option<CompressedKVRef<'a>>,
+    metadata: Vec<(&'a str, &'a str)>,
+}
 
-        // KV cache
-        if data.len() < offset + 1 {
-            return Err(AttentionError::InvalidFormat("Missing KV flag".into()));
+impl<'a> AttentionStateRef<'a> {
+    /// Parse a serialized state (v1 or v2) without copying
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, AttentionError> {
+        let mut r = ByteReader::new(data);
+
+        if r.bytes(4, "magic")? != b"ATTN" {
+            return Err(AttentionError::InvalidMagic);
+        }
+        let version = r.u32("version")?;
+        if version != 1 && version != 2 {
+            return Err(AttentionError::UnsupportedVersion(version));
         }
-        let has_kv = data[offset] != 0;
-        offset += 1;
 
-        let kv_cache = if has_kv {
-            if data.len() < offset + 8 {
-                return Err(AttentionError::InvalidFormat("Missing KV length".into()));
-            }
-            let kv_len = u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap()) as usize;
-            offset += 8;
+        let mut id_bytes = [0u8; 16];
+        id_bytes.copy_from_slice(r.bytes(16, "ID")?);
+        let id = Id::from_bytes(id_bytes);
 
-            if data.len() < offset + kv_len {
-                return Err(AttentionError::InvalidFormat("KV data truncated".into()));
-            }
-            let (kv, _) = CompressedKV::from_bytes(&data[offset..offset + kv_len])
-                .ok_or_else(|| AttentionError::InvalidFormat("Invalid KV cache".into()))?;
-            offset += kv_len;
-            Some(kv)
+        let timestamp_ms = r.u64("timestamp")?;
+        let role = Role::from_byte(r.u8("role")?)
+            .ok_or_else(|| AttentionError::InvalidFormat("Invalid role".into()))?;
+        let text = r.str("text")?;
+
+        let emb_len = r.u32("embedding length")?;
+        let emb_len = r.count(emb_len as u64, 4, "embedding")?;
+        let embedding = r.bytes(emb_len * 4, "embedding")?;
+
+        let kv_cache = if r.u8("KV flag")? != 0 {
+            Some(if version == 1 {
+                // v1: length-prefixed CompressedKV blob
+                let kv_len = r.u64("KV length")?;
+                let mut kv = ByteReader::new(r.bytes_u64(kv_len, "KV data")?);
+                let model_id = kv.str("model ID")?;
+                let (num_layers, num_heads, head_dim, seq_len) = read_kv_dims(&mut kv)?;
+                let quantization = kv.str("quantization")?;
+                let data_len = kv.u64("KV cache length")?;
+                let data = kv.bytes_u64(data_len, "KV cache")?;
+                CompressedKVRef {
+                    model_id,
+                    num_layers,
+                    num_heads,
+                    head_dim,
+                    seq_len,
+                    quantization,
+                    chunks: vec![data],
+                }
+            } else {
+                // v2: inline header + chunked data
+                let model_id = r.str("model ID")?;
+                let (num_layers, num_heads, head_dim, seq_len) = read_kv_dims(&mut r)?;
+                let quantization = r.str("quantization")?;
+                let total_len = r.u64("KV length")?;
+                let total_len = r.count(total_len, 1, "KV data")?;
+
+                let mut chunks = Vec::new();
+                let mut seen = 0usize;
+                loop {
+                    let chunk_len = r.u32("KV chunk length")? as usize;
+                    if chunk_len == 0 {
+                        break;
+                    }
+                    if seen + chunk_len > total_len {
+                        return Err(AttentionError::InvalidFormat("KV chunk overflows declared length".into()));
+                    }
+                    chunks.push(r.bytes(chunk_len, "KV chunk")?);
+                    seen += chunk_len;
+                }
+                if seen != total_len {
+                    return Err(AttentionError::InvalidFormat("KV data truncated".into()));
+                }
+                CompressedKVRef {
+                    model_id,
+                    num_layers,
+                    num_heads,
+                    head_dim,
+                    seq_len,
+                    quantization,
+                    chunks,
+                }
+            })
         } else {
             None
         };
 
-        // Metadata
-        if data.len() < offset + 4 {
-            return Err(AttentionError::InvalidFormat("Missing metadata count".into()));
-        }
-        let meta_count = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-
-        let mut metadata = std::collections::HashMap::new();
+        let meta_count = r.u32("metadata count")? as usize;
+        let mut metadata = Vec::with_capacity(meta_count.min(1024));
         for _ in 0..meta_count {
-            // Key
-            if data.len() < offset + 4 {
-                return Err(AttentionError::InvalidFormat("Missing key length".into()));
-            }
-            let key_len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
-            offset += 4;
-
-            if data.len() < offset + key_len {
-                return Err(AttentionError::InvalidFormat("Key truncated".into()));
-            }
-            let key = String::from_utf8(data[offset..offset + key_len].to_vec())
-                .map_err(|_| AttentionError::InvalidFormat("Invalid UTF-8 in key".into()))?;
-            offset += key_len;
-
-            // Value
-            if data.len() < offset + 4 {
-                return Err(AttentionError::InvalidFormat("Missing value length".into()));
-            }
-            let value_len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
-            offset += 4;
-
-            if data.len() < offset + value_len {
-                return Err(AttentionError::InvalidFormat("Value truncated".into()));
-            }
-            let value = String::from_utf8(data[offset..offset + value_len].to_vec())
-                .map_err(|_| AttentionError::InvalidFormat("Invalid UTF-8 in value".into()))?;
-            offset += value_len;
-
-            metadata.insert(key, value);
+            let key = r.str("key")?;
+            let value = r.str("value")?;
+            metadata.push((key, value));
         }
 
         Ok(Self {
@@ -505,27 +777,133 @@ impl AttentionState {
             metadata,
         })
     }
+
+    /// Embedding dimensionality
+    pub fn embedding_len(&self) -> usize {
+        self.embedding.len() / 4
+    }
+
+    /// Embedding values (decoded on the fly)
+    pub fn embedding(&self) -> impl Iterator<Item = f32> + 'a {
+        self.embedding
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Raw little-endian f32 embedding bytes
+    pub fn embedding_bytes(&self) -> &'a [u8] {
+        self.embedding
+    }
+
+    /// Metadata entries in stored order
+    pub fn metadata(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.metadata.iter().copied()
+    }
+
+    /// Look up a metadata value
+    pub fn get_metadata(&self, key: &str) -> Option<&'a str> {
+        self.metadata.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+    }
+
+    /// Copy into an owned `AttentionState`
+    pub fn to_owned_state(&self) -> AttentionState {
+        AttentionState {
+            id: self.id,
+            timestamp_ms: self.timestamp_ms,
+            role: self.role,
+            text: self.text.to_string(),
+            embedding: self.embedding().collect(),
+            kv_cache: self.kv_cache.as_ref().map(|kv| kv.to_owned_kv()),
+            metadata: self
+                .metadata
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Layer, head, head-dim and sequence-length fields of a KV cache header
+fn read_kv_dims(r: &mut ByteReader<'_>) -> Result<(u32, u32, u32, u32), ByteError> {
+    Ok((r.u32("KV dims")?, r.u32("KV dims")?, r.u32("KV dims")?, r.u32("KV dims")?))
 }
 
 /// Errors for attention state operations
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
 pub enum AttentionError {
+    #[error("Invalid magic bytes")]
     InvalidMagic,
+
+    #[error("Unsupported version: {0}")]
     UnsupportedVersion(u32),
-    InvalidFormat(String),
+
+    #[error("Invalid format: {0}")]
+    InvalidFormat(#[source] Cause),
+
+    #[error("IO error: {0}")]
+    Io(#[source] Arc<io::Error>),
 }
 
-impl std::fmt::Display for AttentionError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            AttentionError::InvalidMagic => write!(f, "Invalid magic bytes"),
-            AttentionError::UnsupportedVersion(v) => write!(f, "Unsupported version: {}", v),
-            AttentionError::InvalidFormat(msg) => write!(f, "Invalid format: {}", msg),
+impl From<ByteError> for AttentionError {
+    fn from(e: ByteError) -> Self {
+        AttentionError::InvalidFormat(Cause::new(e))
+    }
+}
+
+impl From<io::Error> for AttentionError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            AttentionError::InvalidFormat("Unexpected end of data".into())
+        } else {
+            AttentionError::Io(Arc::new(e))
         }
     }
 }
 
-impl std::error::Error for AttentionError {}
+// ============================================================================
+// STREAM HELPERS
+// ============================================================================
+
+fn write_str<W: Write>(w: &mut W, s: &str) -> io::Result<()> {
+    w.write_all(&(s.len() as u32).to_le_bytes())?;
+    w.write_all(s.as_bytes())
+}
+
+fn read_u8<R: Read>(r: &mut R) -> io::Result<u8> {
+    let mut b = [0u8; 1];
+    r.read_exact(&mut b)?;
+    Ok(b[0])
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut b = [0u8; 4];
+    r.read_exact(&mut b)?;
+    Ok(u32::from_le_bytes(b))
+}
+
+fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut b = [0u8; 8];
+    r.read_exact(&mut b)?;
+    Ok(u64::from_le_bytes(b))
+}
+
+/// Read exactly `len` bytes without trusting `len` for preallocation
+fn read_vec<R: Read>(r: &mut R, len: usize, what: &str) -> Result<Vec<u8>, AttentionError> {
+    let mut buf = Vec::new();
+    r.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() != len {
+        return Err(AttentionError::InvalidFormat(what.into()));
+    }
+    Ok(buf)
+}
+
+fn read_str<R: Read>(r: &mut R, what: &str) -> Result<String, AttentionError> {
+    let len = read_u32(r)? as usize;
+    let bytes = read_vec(r, len, &format!("{} truncated", what))?;
+    String::from_utf8(bytes)
+        .map_err(|_| AttentionError::InvalidFormat(format!("Invalid UTF-8 in {}", what).into()))
+}
 
 /// A batch of attention states for efficient storage
 #[derive(Debug, Clone)]
@@ -563,6 +941,22 @@ impl AttentionBatch {
         self.states.push(state);
     }
 
+    /// Batch of states from synthetic conversation turns (see `core::gen`)
+    pub fn from_turns(turns: &[Turn]) -> Self {
+        let mut batch = Self::new();
+        for turn in turns {
+            let role = match turn.speaker {
+                Speaker::User => Role::User,
+                Speaker::Assistant => Role::Assistant,
+            };
+            let mut state = AttentionState::new(role, turn.text.clone(), turn.point.dims().to_vec())
+                .with_metadata("topic", &turn.topic.to_string());
+            state.timestamp_ms = turn.timestamp_ms;
+            batch.add(state);
+        }
+        batch
+    }
+
     /// Total size in bytes
     pub fn size_bytes(&self) -> usize {
         self.states.iter().map(|s| s.size_bytes()).sum()
@@ -607,92 +1001,68 @@ impl AttentionBatch {
 
     /// Deserialize batch from bytes
     pub fn from_bytes(data: &[u8]) -> Result<Self, AttentionError> {
-        let mut offset = 0;
+        Ok(AttentionBatchRef::from_bytes(data)?.to_owned_batch())
+    }
+}
 
-        // Magic
-        if data.len() < 8 {
-            return Err(AttentionError::InvalidFormat("Too short".into()));
-        }
-        if &data[0..4] != b"ATNB" {
+/// Borrowed view of a serialized `AttentionBatch`
+///
+/// Only the per-state framing is parsed up front; states are borrowed views
+/// into the same buffer, so reading a large batch doesn't double memory.
+#[derive(Debug, Clone)]
+pub struct AttentionBatchRef<'a> {
+    pub session_id: Option<Id>,
+    pub document_id: Option<Id>,
+    pub states: Vec<AttentionStateRef<'a>>,
+}
+
+impl<'a> AttentionBatchRef<'a> {
+    /// Parse a serialized batch without copying state contents
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, AttentionError> {
+        let mut r = ByteReader::new(data);
+
+        if r.bytes(4, "magic")? != b"ATNB" {
             return Err(AttentionError::InvalidMagic);
         }
-        offset += 4;
-
-        // Version
-        let version = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+        let version = r.u32("version")?;
         if version != 1 {
             return Err(AttentionError::UnsupportedVersion(version));
         }
-        offset += 4;
 
-        // Session ID
-        if data.len() < offset + 1 {
-            return Err(AttentionError::InvalidFormat("Missing session flag".into()));
-        }
-        let has_session = data[offset] != 0;
-        offset += 1;
-
-        let session_id = if has_session {
-            if data.len() < offset + 16 {
-                return Err(AttentionError::InvalidFormat("Missing session ID".into()));
+        let mut read_id = |what: &str| -> Result<Option<Id>, AttentionError> {
+            if r.u8(&format!("{} flag", what))? == 0 {
+                return Ok(None);
             }
             let mut id_bytes = [0u8; 16];
-            id_bytes.copy_from_slice(&data[offset..offset + 16]);
-            offset += 16;
-            Some(Id::from_bytes(id_bytes))
-        } else {
-            None
+            id_bytes.copy_from_slice(r.bytes(16, &format!("{} ID", what))?);
+            Ok(Some(Id::from_bytes(id_bytes)))
         };
+        let session_id = read_id("session")?;
+        let document_id = read_id("document")?;
 
-        // Document ID
-        if data.len() < offset + 1 {
-            return Err(AttentionError::InvalidFormat("Missing document flag".into()));
-        }
-        let has_document = data[offset] != 0;
-        offset += 1;
-
-        let document_id = if has_document {
-            if data.len() < offset + 16 {
-                return Err(AttentionError::InvalidFormat("Missing document ID".into()));
-            }
-            let mut id_bytes = [0u8; 16];
-            id_bytes.copy_from_slice(&data[offset..offset + 16]);
-            offset += 16;
-            Some(Id::from_bytes(id_bytes))
-        } else {
-            None
-        };
-
-        // States count
-        if data.len() < offset + 4 {
-            return Err(AttentionError::InvalidFormat("Missing state count".into()));
-        }
-        let state_count = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-
-        // States
-        let mut states = Vec::with_capacity(state_count);
+        let state_count = r.u32("state count")? as usize;
+        let mut states = Vec::with_capacity(state_count.min(1024));
         for _ in 0..state_count {
-            if data.len() < offset + 8 {
-                return Err(AttentionError::InvalidFormat("Missing state length".into()));
-            }
-            let state_len = u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap()) as usize;
-            offset += 8;
-
-            if data.len() < offset + state_len {
-                return Err(AttentionError::InvalidFormat("State truncated".into()));
-            }
-            let state = AttentionState::from_bytes(&data[offset..offset + state_len])?;
-            offset += state_len;
-            states.push(state);
+            let state_len = r.u64("state length")?;
+            let state_bytes = r.bytes_u64(state_len, "state")?;
+            states.push(AttentionStateRef::from_bytes(state_bytes)?);
         }
 
         Ok(Self {
-            states,
             session_id,
             document_id,
+            states,
         })
     }
+
+    /// Copy into an owned `AttentionBatch`
+    pub fn to_owned_batch(&self) -> AttentionBatch {
+        AttentionBatch {
+            states: self.states.iter().map(|s| s.to_owned_state()).collect(),
+            session_id: self.session_id,
+            document_id: self.document_id,
+        }
+    }
 }
 
 impl Default for AttentionBatch {
@@ -761,6 +1131,97 @@ mod tests {
         assert_eq!(restored_kv.data, vec![1, 2, 3, 4, 5]);
     }
 
+    #[test]
+    fn test_attention_state_v1_compat() {
+        let kv = CompressedKV {
+            model_id: "llama-3-8b".to_string(),
+            num_layers: 1,
+            num_heads: 1,
+            head_dim: 2,
+            seq_len: 1,
+            quantization: "int8".to_string(),
+            data: vec![1, 2, 3, 4],
+        };
+        let state = AttentionState::new(Role::User, "legacy".to_string(), vec![0.5, 0.25])
+            .with_kv_cache(kv)
+            .with_metadata("turn", "7");
+
+        let v1 = state.to_bytes_v1();
+        assert_eq!(&v1[4..8], &1u32.to_le_bytes());
+
+        let from_slice = AttentionState::from_bytes(&v1).unwrap();
+        let from_reader = AttentionState::read_from(&mut v1.as_slice()).unwrap();
+
+        for restored in [from_slice, from_reader] {
+            assert_eq!(restored.text, "legacy");
+            assert_eq!(restored.embedding, vec![0.5, 0.25]);
+            assert_eq!(restored.kv_cache.unwrap().data, vec![1, 2, 3, 4]);
+            assert_eq!(restored.metadata.get("turn").map(String::as_str), Some("7"));
+        }
+    }
+
+    #[test]
+    fn test_attention_state_stream_chunked_kv() {
+        // KV larger than one chunk to exercise chunk framing
+        let data: Vec<u8> = (0..KV_CHUNK_SIZE * 2 + 17).map(|i| i as u8).collect();
+        let kv = CompressedKV {
+            model_id: "big".to_string(),
+            num_layers: 1,
+            num_heads: 1,
+            head_dim: 1,
+            seq_len: 1,
+            quantization: "raw".to_string(),
+            data: data.clone(),
+        };
+        let first = AttentionState::new(Role::Assistant, "first".to_string(), vec![1.0])
+            .with_kv_cache(kv);
+        let second = AttentionState::new(Role::User, "second".to_string(), vec![2.0]);
+
+        // Two states back to back in one stream
+        let mut stream = Vec::new();
+        first.write_to(&mut stream).unwrap();
+        second.write_to(&mut stream).unwrap();
+
+        let mut reader = stream.as_slice();
+        let a = AttentionState::read_from(&mut reader).unwrap();
+        let b = AttentionState::read_from(&mut reader).unwrap();
+
+        assert_eq!(a.kv_cache.unwrap().data, data);
+        assert_eq!(b.text, "second");
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_attention_state_truncated() {
+        let state = AttentionState::new(Role::User, "hello".to_string(), vec![0.1; 8]);
+        let bytes = state.to_bytes();
+
+        let result = AttentionState::from_bytes(&bytes[..bytes.len() - 3]);
+        assert!(matches!(result, Err(AttentionError::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_kv_slice_seq() {
+        // 1 layer, 2 heads, 3 tokens, head_dim 1, int8 => 12 bytes
+        // Per head: [t0k, t0v, t1k, t1v, t2k, t2v]
+        let kv = CompressedKV {
+            model_id: "test".to_string(),
+            num_layers: 1,
+            num_heads: 2,
+            head_dim: 1,
+            seq_len: 3,
+            quantization: "int8".to_string(),
+            data: vec![0, 1, 2, 3, 4, 5, 10, 11, 12, 13, 14, 15],
+        };
+
+        let slice = kv.slice_seq(1, 3).unwrap();
+        assert_eq!(slice.seq_len, 2);
+        assert_eq!(slice.data, vec![2, 3, 4, 5, 12, 13, 14, 15]);
+
+        assert!(kv.slice_seq(2, 4).is_none());
+        assert!(CompressedKV::placeholder("test").slice_seq(0, 0).is_none());
+    }
+
     #[test]
     fn test_batch_roundtrip() {
         let mut batch = AttentionBatch::new()
@@ -786,4 +1247,66 @@ mod tests {
         assert_eq!(restored.states[1].text, "Answer 1");
         assert!(restored.session_id.is_some());
     }
+
+    #[test]
+    fn test_state_ref_borrows_buffer() {
+        let data: Vec<u8> = (0..KV_CHUNK_SIZE + 5).map(|i| i as u8).collect();
+        let kv = CompressedKV {
+            model_id: "m".to_string(),
+            num_layers: 1,
+            num_heads: 1,
+            head_dim: 1,
+            seq_len: 1,
+            quantization: "raw".to_string(),
+            data: data.clone(),
+        };
+        let state = AttentionState::new(Role::User, "hello".to_string(), vec![1.5, -2.0])
+            .with_kv_cache(kv)
+            .with_metadata("k", "v");
+
+        for bytes in [state.to_bytes(), state.to_bytes_v1()] {
+            let view = AttentionStateRef::from_bytes(&bytes).unwrap();
+            let range = bytes.as_ptr_range();
+
+            // Text points into the source buffer
+            assert!(range.contains(&view.text.as_ptr()));
+            assert_eq!(view.text, "hello");
+            assert_eq!(view.embedding().collect::<Vec<_>>(), vec![1.5, -2.0]);
+            assert_eq!(view.get_metadata("k"), Some("v"));
+
+            let kv_ref = view.kv_cache.as_ref().unwrap();
+            assert_eq!(kv_ref.data_len(), data.len());
+            assert!(kv_ref.chunks().all(|c| range.contains(&c.as_ptr())));
+
+            let owned = view.to_owned_state();
+            assert_eq!(owned.kv_cache.unwrap().data, data);
+        }
+    }
+
+    #[test]
+    fn test_batch_from_turns() {
+        let turns = crate::core::gen::Conversation::new(8, 6).with_timing(100, 10).generate();
+        let batch = AttentionBatch::from_turns(&turns);
+
+        assert_eq!(batch.states.len(), 6);
+        assert_eq!(batch.states[1].role, Role::Assistant);
+        assert_eq!(batch.states[2].timestamp_ms, 120);
+        assert_eq!(batch.states[0].embedding.len(), 8);
+        assert!(batch.states[0].metadata.contains_key("topic"));
+    }
+
+    #[test]
+    fn test_batch_ref() {
+        let mut batch = AttentionBatch::new().with_document(Id::now());
+        batch.add(AttentionState::new(Role::User, "a".to_string(), vec![0.1]));
+        batch.add(AttentionState::new(Role::Assistant, "b".to_string(), vec![0.2]));
+
+        let bytes = batch.to_bytes();
+        let view = AttentionBatchRef::from_bytes(&bytes).unwrap();
+
+        assert_eq!(view.document_id, batch.document_id);
+        assert_eq!(view.states.len(), 2);
+        assert_eq!(view.states[1].text, "b");
+        assert!(AttentionBatchRef::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
 }
@@ -0,0 +1,529 @@
+//! # Framed Attention Batches
+//!
+//! Compressed, randomly accessible container for `AttentionState`s.
+//!
+//! `AttentionBatch::to_bytes` concatenates states raw, so reading one state
+//! means walking (and, once compressed, inflating) everything before it.
+//! The framed format groups states into independently compressed frames and
+//! puts an offset table up front:
+//!
+//! ```text
+//! "ATNF" | version u32 | codec u8
+//! session flag u8 [+ id 16] | document flag u8 [+ id 16]
+//! state_count u32 | frame_count u32
+//! frame index: frame_count × { offset u64, compressed_len u64,
+//!                              uncompressed_len u64, first_state u32, states u32 }
+//! frames: compressed([u64 len][state bytes] ...)
+//! ```
+//!
+//! Fetching state `i` reads the index, decompresses one frame and parses one
+//! state. Compression is pluggable through `FrameCodec`; the codec ID is
+//! stored in the header so readers can reject data they can't decode.
+//! `RawCodec` (ID 0) stores frames as they are; with the `zstd` feature,
+//! `ZstdCodec` (ID 1) compresses them as standard Zstandard frames (through
+//! the pure-Rust `ruzstd`), readable by any other zstd decoder.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let bytes = FramedBatchWriter::new(&RawCodec).states_per_frame(32).write(&batch);
+//!
+//! let reader = FramedBatchReader::open(&bytes, &RawCodec)?;
+//! let state = reader.get(17)?;
+//! ```
+
+use super::attention::{AttentionBatch, AttentionError, AttentionState};
+use crate::core::bytes::ByteReader;
+use crate::core::Id;
+
+/// Framed batch format version
+pub const FRAMED_BATCH_VERSION: u32 = 1;
+
+/// Default number of states per frame
+pub const DEFAULT_STATES_PER_FRAME: usize = 16;
+
+/// Size of one frame index entry in bytes
+const INDEX_ENTRY_SIZE: usize = 8 + 8 + 8 + 4 + 4;
+
+/// Compression codec applied to each frame
+pub trait FrameCodec: Send + Sync {
+    /// Stable codec identifier stored in the header
+    fn id(&self) -> u8;
+
+    /// Compress one frame
+    fn compress(&self, data: &[u8]) -> Vec<u8>;
+
+    /// Decompress one frame (its uncompressed size is known from the index)
+    fn decompress(&self, data: &[u8], uncompressed_len: usize) -> Result<Vec<u8>, AttentionError>;
+}
+
+/// Identity codec (frames stored uncompressed)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawCodec;
+
+impl FrameCodec for RawCodec {
+    fn id(&self) -> u8 {
+        0
+    }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        data.to_vec()
+    }
+
+    fn decompress(&self, data: &[u8], uncompressed_len: usize) -> Result<Vec<u8>, AttentionError> {
+        if data.len() != uncompressed_len {
+            return Err(AttentionError::InvalidFormat("Frame length mismatch".into()));
+        }
+        Ok(data.to_vec())
+    }
+}
+
+/// Zstandard codec (`ruzstd`, at its fastest level)
+#[cfg(feature = "zstd")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZstdCodec;
+
+#[cfg(feature = "zstd")]
+impl FrameCodec for ZstdCodec {
+    fn id(&self) -> u8 {
+        1
+    }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        ruzstd::encoding::compress_to_vec(data, ruzstd::encoding::CompressionLevel::Fastest)
+    }
+
+    fn decompress(&self, data: &[u8], uncompressed_len: usize) -> Result<Vec<u8>, AttentionError> {
+        use std::io::Read;
+
+        let decoder = ruzstd::decoding::StreamingDecoder::new(data)
+            .map_err(|e| AttentionError::InvalidFormat(crate::core::Cause::new(e)))?;
+
+        // Read one byte past the expected length, so a frame that inflates
+        // further is caught without decoding the rest of it
+        let mut out = Vec::new();
+        decoder
+            .take(uncompressed_len as u64 + 1)
+            .read_to_end(&mut out)
+            .map_err(|e| AttentionError::InvalidFormat(crate::core::Cause::new(e)))?;
+        if out.len() != uncompressed_len {
+            return Err(AttentionError::InvalidFormat("Frame length mismatch".into()));
+        }
+        Ok(out)
+    }
+}
+
+/// Location of one frame in a framed batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameEntry {
+    /// Byte offset of the compressed frame from the start of the buffer
+    pub offset: u64,
+
+    /// Compressed frame size
+    pub compressed_len: u64,
+
+    /// Decompressed frame size
+    pub uncompressed_len: u64,
+
+    /// Index of the first state in this frame
+    pub first_state: u32,
+
+    /// Number of states in this frame
+    pub state_count: u32,
+}
+
+/// Writer for the framed batch format
+pub struct FramedBatchWriter<'c> {
+    codec: &'c dyn FrameCodec,
+    states_per_frame: usize,
+}
+
+impl<'c> FramedBatchWriter<'c> {
+    /// Create a writer using the given codec
+    pub fn new(codec: &'c dyn FrameCodec) -> Self {
+        Self {
+            codec,
+            states_per_frame: DEFAULT_STATES_PER_FRAME,
+        }
+    }
+
+    /// Set how many states are grouped into each frame
+    ///
+    /// Larger frames compress better; smaller frames make random access cheaper.
+    pub fn states_per_frame(mut self, n: usize) -> Self {
+        self.states_per_frame = n.max(1);
+        self
+    }
+
+    /// Serialize a batch
+    pub fn write(&self, batch: &AttentionBatch) -> Vec<u8> {
+        let frame_count = batch.states.len().div_ceil(self.states_per_frame);
+
+        // Compress frames first so the index can be written up front
+        let mut frames = Vec::with_capacity(frame_count);
+        let mut entries = Vec::with_capacity(frame_count);
+        for (i, group) in batch.states.chunks(self.states_per_frame).enumerate() {
+            let mut raw = Vec::new();
+            for state in group {
+                let state_bytes = state.to_bytes();
+                raw.extend_from_slice(&(state_bytes.len() as u64).to_le_bytes());
+                raw.extend_from_slice(&state_bytes);
+            }
+            let compressed = self.codec.compress(&raw);
+            entries.push(FrameEntry {
+                offset: 0,
+                compressed_len: compressed.len() as u64,
+                uncompressed_len: raw.len() as u64,
+                first_state: (i * self.states_per_frame) as u32,
+                state_count: group.len() as u32,
+            });
+            frames.push(compressed);
+        }
+
+        let mut bytes = Vec::new();
+
+        // Header
+        bytes.extend_from_slice(b"ATNF");
+        bytes.extend_from_slice(&FRAMED_BATCH_VERSION.to_le_bytes());
+        bytes.push(self.codec.id());
+        write_opt_id(&mut bytes, batch.session_id);
+        write_opt_id(&mut bytes, batch.document_id);
+        bytes.extend_from_slice(&(batch.states.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(frame_count as u32).to_le_bytes());
+
+        // Frame index
+        let mut offset = (bytes.len() + frame_count * INDEX_ENTRY_SIZE) as u64;
+        for entry in &mut entries {
+            entry.offset = offset;
+            offset += entry.compressed_len;
+
+            bytes.extend_from_slice(&entry.offset.to_le_bytes());
+            bytes.extend_from_slice(&entry.compressed_len.to_le_bytes());
+            bytes.extend_from_slice(&entry.uncompressed_len.to_le_bytes());
+            bytes.extend_from_slice(&entry.first_state.to_le_bytes());
+            bytes.extend_from_slice(&entry.state_count.to_le_bytes());
+        }
+
+        // Frames
+        for frame in &frames {
+            bytes.extend_from_slice(frame);
+        }
+
+        bytes
+    }
+}
+
+/// Random-access reader over a framed batch
+pub struct FramedBatchReader<'a, 'c> {
+    data: &'a [u8],
+    codec: &'c dyn FrameCodec,
+    session_id: Option<Id>,
+    document_id: Option<Id>,
+    state_count: usize,
+    frames: Vec<FrameEntry>,
+}
+
+impl<'a, 'c> FramedBatchReader<'a, 'c> {
+    /// Parse the header and frame index
+    ///
+    /// Frames are not touched until a state is requested.
+    pub fn open(data: &'a [u8], codec: &'c dyn FrameCodec) -> Result<Self, AttentionError> {
+        let mut r = ByteReader::new(data);
+
+        if r.array::<4>("magic")? != *b"ATNF" {
+            return Err(AttentionError::InvalidMagic);
+        }
+
+        let version = r.u32("version")?;
+        if version != FRAMED_BATCH_VERSION {
+            return Err(AttentionError::UnsupportedVersion(version));
+        }
+
+        let codec_id = r.u8("codec")?;
+        if codec_id != codec.id() {
+            return Err(AttentionError::InvalidFormat(format!(
+                "Codec mismatch: data uses {}, reader has {}",
+                codec_id,
+                codec.id()
+            ).into()));
+        }
+
+        let session_id = read_opt_id(&mut r, "session")?;
+        let document_id = read_opt_id(&mut r, "document")?;
+
+        let state_count = r.u32("state count")? as usize;
+        let frame_count = r.u32("frame count")?;
+        let frame_count = r.count(frame_count as u64, INDEX_ENTRY_SIZE, "frame index")?;
+
+        let mut frames = Vec::with_capacity(frame_count);
+        let mut expected_first = 0u32;
+        for _ in 0..frame_count {
+            let entry = FrameEntry {
+                offset: r.u64("frame offset")?,
+                compressed_len: r.u64("frame length")?,
+                uncompressed_len: r.u64("frame length")?,
+                first_state: r.u32("frame states")?,
+                state_count: r.u32("frame states")?,
+            };
+
+            let end = entry.offset.checked_add(entry.compressed_len);
+            if end.is_none_or(|end| end > data.len() as u64) {
+                return Err(AttentionError::InvalidFormat("Frame out of bounds".into()));
+            }
+            if entry.first_state != expected_first || entry.state_count == 0 {
+                return Err(AttentionError::InvalidFormat("Frame index out of order".into()));
+            }
+            expected_first = expected_first
+                .checked_add(entry.state_count)
+                .ok_or_else(|| AttentionError::InvalidFormat("Frame index overflows".into()))?;
+            frames.push(entry);
+        }
+
+        if expected_first as usize != state_count {
+            return Err(AttentionError::InvalidFormat("Frame index doesn't cover all states".into()));
+        }
+
+        Ok(Self {
+            data,
+            codec,
+            session_id,
+            document_id,
+            state_count,
+            frames,
+        })
+    }
+
+    /// Number of states
+    pub fn len(&self) -> usize {
+        self.state_count
+    }
+
+    /// Check if the batch holds no states
+    pub fn is_empty(&self) -> bool {
+        self.state_count == 0
+    }
+
+    /// Session ID of the batch
+    pub fn session_id(&self) -> Option<Id> {
+        self.session_id
+    }
+
+    /// Document ID of the batch
+    pub fn document_id(&self) -> Option<Id> {
+        self.document_id
+    }
+
+    /// Frame index entries
+    pub fn frames(&self) -> &[FrameEntry] {
+        &self.frames
+    }
+
+    /// Get a single state, decompressing only its frame
+    pub fn get(&self, index: usize) -> Result<AttentionState, AttentionError> {
+        if index >= self.state_count {
+            return Err(AttentionError::InvalidFormat(format!(
+                "State index {} out of range ({} states)",
+                index, self.state_count
+            ).into()));
+        }
+
+        // Frames are contiguous and ordered by first_state
+        let frame_idx = self
+            .frames
+            .partition_point(|f| (f.first_state + f.state_count) as usize <= index);
+        let entry = self.frames[frame_idx];
+        let frame = self.decompress_frame(&entry)?;
+
+        let mut states = FrameStates::new(&frame);
+        for _ in entry.first_state as usize..index {
+            states.next_slice()?;
+        }
+        AttentionState::from_bytes(states.next_slice()?)
+    }
+
+    /// Decode all states in a frame
+    pub fn frame_states(&self, frame_idx: usize) -> Result<Vec<AttentionState>, AttentionError> {
+        let entry = *self
+            .frames
+            .get(frame_idx)
+            .ok_or_else(|| AttentionError::InvalidFormat("Frame index out of range".into()))?;
+        let frame = self.decompress_frame(&entry)?;
+
+        let mut states = FrameStates::new(&frame);
+        (0..entry.state_count)
+            .map(|_| AttentionState::from_bytes(states.next_slice()?))
+            .collect()
+    }
+
+    /// Decode the whole batch
+    pub fn to_batch(&self) -> Result<AttentionBatch, AttentionError> {
+        let mut states = Vec::with_capacity(self.state_count);
+        for i in 0..self.frames.len() {
+            states.extend(self.frame_states(i)?);
+        }
+
+        Ok(AttentionBatch {
+            states,
+            session_id: self.session_id,
+            document_id: self.document_id,
+        })
+    }
+
+    fn decompress_frame(&self, entry: &FrameEntry) -> Result<Vec<u8>, AttentionError> {
+        let start = entry.offset as usize;
+        let end = start + entry.compressed_len as usize;
+        let frame = self
+            .codec
+            .decompress(&self.data[start..end], entry.uncompressed_len as usize)?;
+        if frame.len() as u64 != entry.uncompressed_len {
+            return Err(AttentionError::InvalidFormat("Frame length mismatch".into()));
+        }
+        Ok(frame)
+    }
+}
+
+/// Cursor over the length-prefixed states in a decompressed frame
+struct FrameStates<'f> {
+    reader: ByteReader<'f>,
+}
+
+impl<'f> FrameStates<'f> {
+    fn new(frame: &'f [u8]) -> Self {
+        Self { reader: ByteReader::new(frame) }
+    }
+
+    fn next_slice(&mut self) -> Result<&'f [u8], AttentionError> {
+        let len = self.reader.u64("state length")?;
+        Ok(self.reader.bytes_u64(len, "state")?)
+    }
+}
+
+fn write_opt_id(bytes: &mut Vec<u8>, id: Option<Id>) {
+    if let Some(id) = id {
+        bytes.push(1);
+        bytes.extend_from_slice(id.as_bytes());
+    } else {
+        bytes.push(0);
+    }
+}
+
+fn read_opt_id(r: &mut ByteReader<'_>, what: &str) -> Result<Option<Id>, AttentionError> {
+    if r.u8(what)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(Id::from_bytes(r.array(what)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::attention::Role;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Byte-wise run-length codec that counts decompressions
+    #[derive(Default)]
+    struct CountingRle {
+        decompressions: AtomicUsize,
+    }
+
+    impl FrameCodec for CountingRle {
+        fn id(&self) -> u8 {
+            200
+        }
+
+        fn compress(&self, data: &[u8]) -> Vec<u8> {
+            let mut out = Vec::new();
+            for run in data.chunk_by(|a, b| a == b) {
+                for piece in run.chunks(255) {
+                    out.push(piece.len() as u8);
+                    out.push(piece[0]);
+                }
+            }
+            out
+        }
+
+        fn decompress(&self, data: &[u8], uncompressed_len: usize) -> Result<Vec<u8>, AttentionError> {
+            self.decompressions.fetch_add(1, Ordering::SeqCst);
+            let mut out = Vec::with_capacity(uncompressed_len);
+            for pair in data.chunks_exact(2) {
+                out.extend(std::iter::repeat_n(pair[1], pair[0] as usize));
+            }
+            Ok(out)
+        }
+    }
+
+    fn make_batch(n: usize) -> AttentionBatch {
+        let mut batch = AttentionBatch::new().with_session(Id::now());
+        for i in 0..n {
+            batch.add(AttentionState::new(Role::User, format!("message {}", i), vec![0.0; 64]));
+        }
+        batch
+    }
+
+    #[test]
+    fn test_framed_roundtrip() {
+        let batch = make_batch(10);
+        let bytes = FramedBatchWriter::new(&RawCodec).states_per_frame(3).write(&batch);
+
+        let reader = FramedBatchReader::open(&bytes, &RawCodec).unwrap();
+        assert_eq!(reader.len(), 10);
+        assert_eq!(reader.frames().len(), 4);
+        assert_eq!(reader.session_id(), batch.session_id);
+
+        let restored = reader.to_batch().unwrap();
+        assert_eq!(restored.states.len(), 10);
+        assert_eq!(restored.states[9].text, "message 9");
+    }
+
+    #[test]
+    fn test_random_access_decompresses_one_frame() {
+        let codec = CountingRle::default();
+        let batch = make_batch(20);
+        let bytes = FramedBatchWriter::new(&codec).states_per_frame(4).write(&batch);
+
+        // Zeroed embeddings compress well
+        assert!(bytes.len() < batch.to_bytes().len());
+
+        let reader = FramedBatchReader::open(&bytes, &codec).unwrap();
+        let state = reader.get(13).unwrap();
+
+        assert_eq!(state.text, "message 13");
+        assert_eq!(codec.decompressions.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_roundtrip() {
+        let batch = make_batch(12);
+        let bytes = FramedBatchWriter::new(&ZstdCodec).states_per_frame(5).write(&batch);
+        assert!(bytes.len() < batch.to_bytes().len());
+
+        let reader = FramedBatchReader::open(&bytes, &ZstdCodec).unwrap();
+        assert_eq!(reader.get(11).unwrap().text, "message 11");
+        assert_eq!(reader.to_batch().unwrap().states.len(), 12);
+        assert!(FramedBatchReader::open(&bytes, &RawCodec).is_err());
+
+        // Frames that aren't zstd, or inflate past their stated length
+        let frame = ZstdCodec.compress(&[7; 4096]);
+        assert_eq!(ZstdCodec.decompress(&frame, 4096).unwrap(), [7; 4096]);
+        assert!(ZstdCodec.decompress(&frame, 4095).is_err());
+        assert!(ZstdCodec.decompress(&frame[..frame.len() / 2], 4096).is_err());
+        assert!(ZstdCodec.decompress(b"nope", 4).is_err());
+    }
+
+    #[test]
+    fn test_codec_mismatch() {
+        let bytes = FramedBatchWriter::new(&CountingRle::default()).write(&make_batch(2));
+        assert!(FramedBatchReader::open(&bytes, &RawCodec).is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_and_truncated() {
+        let bytes = FramedBatchWriter::new(&RawCodec).write(&make_batch(3));
+
+        let reader = FramedBatchReader::open(&bytes, &RawCodec).unwrap();
+        assert!(reader.get(3).is_err());
+
+        assert!(FramedBatchReader::open(&bytes[..bytes.len() - 1], &RawCodec).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+//! # Memory Explorer
+//!
+//! Browser UI for looking inside a `HatIndex`: browse sessions, documents and
+//! chunks, run ad-hoc queries and trigger consolidation.
+//!
+//! Served over the same `std::net` HTTP machinery as the `server` adapter; the
+//! page is embedded in the binary and talks to a small JSON API:
+//!
+//! - `GET /` - the explorer page
+//! - `GET /api/stats` - container counts
+//! - `GET /api/sessions` - sessions in time order
+//! - `GET /api/container?id=` - a session, document or chunk with its
+//!   children, metadata and payload
+//! - `GET /api/query?like=<chunk id>&k=` or `?vector=0.1,0.2,...&k=` -
+//!   nearest chunks to a stored chunk or a raw vector
+//! - `POST /api/consolidate?mode=light|full` - run consolidation, returning
+//!   its metrics
+//!
+//! ```rust,ignore
+//! let index = Arc::new(RwLock::new(HatIndex::load_from_file(path)?));
+//! let explorer = Explorer::bind("127.0.0.1:8080", index.clone())?;
+//! std::thread::spawn(move || explorer.run());
+//! ```
+//!
+//! There is no authentication: bind to localhost or put it behind a proxy.
+
+use std::fmt::Write;
+use std::io;
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::core::metadata::{push_json_string, push_json_value};
+use crate::core::{Id, Point};
+use crate::adapters::index::{Consolidate, ConsolidationConfig, ContainerInfo, ContainerLevel, HatIndex};
+use crate::ports::Near;
+use super::server::{serve, Request, Response};
+
+/// The explorer page
+const PAGE: &str = include_str!("explorer.html");
+
+/// Characters of a text payload shown in listings
+const PREVIEW_CHARS: usize = 120;
+
+/// Most results an ad-hoc query returns
+const MAX_QUERY_K: usize = 1000;
+
+/// Explorer UI over a shared `HatIndex`
+pub struct Explorer {
+    listener: TcpListener,
+    index: Arc<RwLock<HatIndex>>,
+}
+
+impl Explorer {
+    /// Bind to an address (port 0 picks a free port)
+    pub fn bind(addr: impl ToSocketAddrs, index: Arc<RwLock<HatIndex>>) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            index,
+        })
+    }
+
+    /// Address the explorer is listening on
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serve connections until the listener fails (blocking)
+    pub fn run(&self) -> io::Result<()> {
+        let index = self.index.clone();
+        serve(&self.listener, |_: &Request| Ok(()), move |request| route(request, &index))
+    }
+
+    /// Answer a single request (routing without the network)
+    pub fn respond(&self, request: &Request) -> Response {
+        route(request, &self.index)
+    }
+}
+
+/// Dispatch a request to its endpoint
+fn route(request: &Request, index: &RwLock<HatIndex>) -> Response {
+    let read = || index.read().unwrap_or_else(PoisonError::into_inner);
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => Response::new(200, "text/html; charset=utf-8", PAGE),
+        ("GET", "/api/stats") => stats(&read()),
+        ("GET", "/api/sessions") => sessions(&read()),
+        ("GET", "/api/container") => container(request, &read()),
+        ("GET", "/api/query") => query(request, &read()),
+        ("POST", "/api/consolidate") => {
+            consolidate(request, &mut index.write().unwrap_or_else(PoisonError::into_inner))
+        }
+        (_, "/" | "/api/stats" | "/api/sessions" | "/api/container" | "/api/query" | "/api/consolidate") => {
+            Response::text(405, "method not allowed\n")
+        }
+        _ => Response::text(404, "not found\n"),
+    }
+}
+
+/// `GET /api/stats`
+fn stats(index: &HatIndex) -> Response {
+    let stats = index.stats();
+    Response::json(
+        200,
+        format!(
+            "{{\"proximity\":\"{}\",\"dimensionality\":{},\"sessions\":{},\"documents\":{},\"chunks\":{},\"consolidating\":{}}}",
+            index.proximity_name(),
+            index.dimensionality(),
+            stats.session_count,
+            stats.document_count,
+            stats.chunk_count,
+            index.is_consolidating()
+        ),
+    )
+}
+
+/// `GET /api/sessions`
+fn sessions(index: &HatIndex) -> Response {
+    let mut out = String::from("[");
+    for (i, info) in index.sessions().into_iter().filter_map(|id| index.container(id)).enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        push_summary(&mut out, index, &info);
+    }
+    out.push(']');
+    Response::json(200, out)
+}
+
+/// `GET /api/container?id=`
+fn container(request: &Request, index: &HatIndex) -> Response {
+    let Some(id) = request.param("id").and_then(|id| Id::from_hex(&id)) else {
+        return Response::text(400, "expected ?id=<hex id>\n");
+    };
+    let Some(info) = index.container(id) else {
+        return Response::text(404, "no such container\n");
+    };
+
+    let mut out = String::new();
+    push_summary(&mut out, index, &info);
+    out.pop(); // reopen the summary object
+    out.push_str(",\"metadata\":{");
+    for (i, (key, value)) in index.metadata(id).into_iter().flatten().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        push_json_string(&mut out, key);
+        out.push(':');
+        push_json_value(&mut out, value);
+    }
+    out.push_str("},\"payload\":");
+    match index.payload(id).and_then(|blob| blob.as_str()) {
+        Some(text) => push_json_string(&mut out, text),
+        None => out.push_str("null"),
+    }
+    let _ = write!(out, ",\"payload_bytes\":{}", index.payload(id).map_or(0, |blob| blob.size()));
+    out.push_str(",\"children\":[");
+    for (i, child) in info.children.iter().filter_map(|id| index.container(*id)).enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        push_summary(&mut out, index, &child);
+    }
+    out.push_str("]}");
+    Response::json(200, out)
+}
+
+/// `GET /api/query?like=<id>|vector=...&k=`
+fn query(request: &Request, index: &HatIndex) -> Response {
+    let point = match (request.param("like"), request.param("vector")) {
+        (Some(like), _) => match Id::from_hex(&like).and_then(|id| index.get(id)) {
+            Some(point) => point.clone(),
+            None => return Response::text(404, "no such chunk\n"),
+        },
+        (None, Some(vector)) => {
+            let dims: Result<Vec<f32>, _> = vector.split(',').map(|x| x.trim().parse::<f32>()).collect();
+            match dims {
+                Ok(dims) => Point::new(dims),
+                Err(_) => return Response::text(400, "vector must be comma-separated numbers\n"),
+            }
+        }
+        (None, None) => return Response::text(400, "expected ?like=<chunk id> or ?vector=...\n"),
+    };
+    let k = match request.param("k").map(|k| k.parse::<usize>()) {
+        None => 10,
+        Some(Ok(k)) => k.min(MAX_QUERY_K),
+        Some(Err(_)) => return Response::text(400, "k must be a number\n"),
+    };
+
+    let results = match index.near(&point, k) {
+        Ok(results) => results,
+        Err(e) => return Response::text(400, format!("{}\n", e)),
+    };
+    let mut out = String::from("[");
+    for (i, result) in results.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "{{\"id\":\"{}\",\"score\":", result.id);
+        push_json_value(&mut out, &(result.score as f64).into());
+        out.push_str(",\"preview\":");
+        push_preview(&mut out, index, result.id);
+        out.push('}');
+    }
+    out.push(']');
+    Response::json(200, out)
+}
+
+/// `POST /api/consolidate?mode=light|full`
+fn consolidate(request: &Request, index: &mut HatIndex) -> Response {
+    let config = match request.param("mode").as_deref() {
+        None | Some("light") => ConsolidationConfig::light(),
+        Some("full") => ConsolidationConfig::full(),
+        Some(_) => return Response::text(400, "mode must be light or full\n"),
+    };
+    let metrics = index.consolidate(config);
+    Response::json(
+        200,
+        format!(
+            "{{\"containers_processed\":{},\"centroids_recomputed\":{},\"containers_merged\":{},\"containers_split\":{},\"containers_pruned\":{},\"max_centroid_drift\":{},\"total_time_us\":{}}}",
+            metrics.containers_processed,
+            metrics.centroids_recomputed,
+            metrics.containers_merged,
+            metrics.containers_split,
+            metrics.containers_pruned,
+            metrics.max_centroid_drift,
+            metrics.total_time_us
+        ),
+    )
+}
+
+/// `{"id", "level", "name", "role", "timestamp", "chunks", "preview"}`
+fn push_summary(out: &mut String, index: &HatIndex, info: &ContainerInfo) {
+    let level = match info.level {
+        ContainerLevel::Global => "global",
+        ContainerLevel::Session => "session",
+        ContainerLevel::Document => "document",
+        ContainerLevel::Chunk => "chunk",
+    };
+    let _ = write!(out, "{{\"id\":\"{}\",\"level\":\"{}\",\"name\":", info.id, level);
+    match index.name(info.id) {
+        Some(name) => push_json_string(out, name),
+        None => out.push_str("null"),
+    }
+    match index.role(info.id) {
+        Some(role) => {
+            let _ = write!(out, ",\"role\":\"{}\"", role.as_str());
+        }
+        None => out.push_str(",\"role\":null"),
+    }
+    let _ = write!(out, ",\"timestamp\":{},\"chunks\":{},\"preview\":", info.timestamp, info.chunk_count);
+    push_preview(out, index, info.id);
+    out.push('}');
+}
+
+/// Start of a text payload, or `null`
+fn push_preview(out: &mut String, index: &HatIndex, id: Id) {
+    match index.payload(id).and_then(|blob| blob.as_str()) {
+        Some(text) => push_json_string(out, &text.chars().take(PREVIEW_CHARS).collect::<String>()),
+        None => out.push_str("null"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::attention::Role;
+    use crate::core::{Blob, Metadata};
+
+    fn request(method: &str, path: &str, query: &str) -> Request {
+        Request {
+            method: method.into(),
+            path: path.into(),
+            query: query.into(),
+            ..Request::default()
+        }
+    }
+
+    #[test]
+    fn test_explorer_api() {
+        let mut index = HatIndex::cosine(2);
+        let session = index.new_session_named("standup");
+        let first = Id::now();
+        index.add_with_role(first, &Point::new(vec![1.0, 0.0]), Role::User).unwrap();
+        index.set_payload(first, Blob::from_str("ship \"it\"")).unwrap();
+        index.set_metadata(first, Metadata::from([("speaker".to_string(), "ana".into())])).unwrap();
+        let second = Id::now();
+        index.add(second, &Point::new(vec![0.0, 1.0])).unwrap();
+        let explorer = Explorer::bind("127.0.0.1:0", Arc::new(RwLock::new(index))).unwrap();
+
+        let page = explorer.respond(&request("GET", "/", ""));
+        assert_eq!(page.status, 200);
+        assert!(page.content_type.starts_with("text/html"));
+
+        let stats = explorer.respond(&request("GET", "/api/stats", "")).body;
+        assert!(stats.contains("\"sessions\":1,") && stats.contains("\"chunks\":2,"));
+
+        let sessions = explorer.respond(&request("GET", "/api/sessions", "")).body;
+        assert!(sessions.starts_with(&format!("[{{\"id\":\"{}\",\"level\":\"session\",\"name\":\"standup\"", session)));
+        assert!(sessions.contains("\"chunks\":2,"));
+
+        let chunk = explorer.respond(&request("GET", "/api/container", &format!("id={}", first))).body;
+        assert!(chunk.contains("\"level\":\"chunk\",\"name\":null,\"role\":\"user\","));
+        assert!(chunk.contains(r#""metadata":{"speaker":"ana"},"payload":"ship \"it\"","payload_bytes":9,"children":[]}"#));
+
+        let results = explorer.respond(&request("GET", "/api/query", &format!("like={}&k=1", second))).body;
+        assert!(results.starts_with(&format!("[{{\"id\":\"{}\"", second)));
+        let results = explorer.respond(&request("GET", "/api/query", "vector=1%2C0")).body;
+        assert!(results.contains("\"preview\":\"ship \\\"it\\\"\""));
+
+        let consolidated = explorer.respond(&request("POST", "/api/consolidate", "mode=full"));
+        assert_eq!(consolidated.status, 200);
+        assert!(consolidated.body.contains("\"containers_processed\":"));
+    }
+
+    #[test]
+    fn test_explorer_errors() {
+        let explorer = Explorer::bind("127.0.0.1:0", Arc::new(RwLock::new(HatIndex::cosine(2)))).unwrap();
+        let status = |method: &str, path: &str, query: &str| explorer.respond(&request(method, path, query)).status;
+
+        assert_eq!(status("GET", "/api/container", "id=zz"), 400);
+        assert_eq!(status("GET", "/api/container", &format!("id={}", Id::now())), 404);
+        assert_eq!(status("GET", "/api/query", ""), 400);
+        assert_eq!(status("GET", "/api/query", "vector=1,x"), 400);
+        assert_eq!(status("GET", "/api/query", "vector=1,0,0"), 400);
+        assert_eq!(status("POST", "/api/consolidate", "mode=deep"), 400);
+        assert_eq!(status("GET", "/api/consolidate", ""), 405);
+        assert_eq!(status("GET", "/nope", ""), 404);
+    }
+}
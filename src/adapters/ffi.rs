@@ -0,0 +1,415 @@
+//! # C FFI
+//!
+//! Stable `extern "C"` API for embedding ARMS from C, C++, Go (cgo),
+//! Node.js (N-API / ffi-napi) and anything else that can call C.
+//!
+//! The header lives at `include/arms_hat.h`; regenerate it with
+//! `cbindgen --config cbindgen.toml --output include/arms_hat.h` after
+//! changing this file (a unit test checks every export is declared).
+//!
+//! ## Conventions
+//!
+//! - Handles are opaque pointers created by `arms_new*` and released with `arms_free`.
+//! - Fallible calls return `ArmsStatus`; outputs go through pointer arguments.
+//!   In C its values carry the enum's name (`ArmsStatus_Ok`, `ArmsStatus_NotFound`).
+//! - Buffers are caller-owned. Pointers returned by the library (blob data)
+//!   stay valid until the next mutating call on the same handle.
+//! - A handle must not be used from two threads at once.
+//!
+//! ```c
+//! ArmsHandle *arms = arms_new_hat(768);
+//! ArmsId id;
+//! ArmsStatus status = arms_place(arms, embedding, 768, (const uint8_t *)"hello", 5, &id);
+//! if (status != ArmsStatus_Ok) {
+//!     fprintf(stderr, "%s\n", arms_status_message(status));
+//! }
+//!
+//! ArmsSearchResult results[10];
+//! size_t found = 0;
+//! arms_near(arms, query, 768, 10, results, &found);
+//! arms_free(arms);
+//! ```
+
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+use super::index::HatIndex;
+use super::storage::MemoryStorage;
+use crate::core::config::ArmsConfig;
+use crate::core::{Blob, Id, Point};
+use crate::engine::Arms;
+use crate::error::{ArmsError, ErrorCode};
+use crate::ports::{NearError, PlaceError};
+
+/// Opaque engine handle
+pub struct ArmsHandle {
+    arms: Arms,
+}
+
+/// 128-bit point identifier
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArmsId {
+    pub bytes: [u8; 16],
+}
+
+/// A single search hit
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArmsSearchResult {
+    pub id: ArmsId,
+    pub score: f32,
+}
+
+/// Status codes returned by fallible calls
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmsStatus {
+    Ok = 0,
+    NullPointer = 1,
+    DimensionalityMismatch = 2,
+    CapacityExceeded = 3,
+    DuplicateId = 4,
+    NotFound = 5,
+    IndexNotReady = 6,
+    Error = 7,
+}
+
+impl From<ErrorCode> for ArmsStatus {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::DimensionalityMismatch => ArmsStatus::DimensionalityMismatch,
+            ErrorCode::CapacityExceeded => ArmsStatus::CapacityExceeded,
+            ErrorCode::DuplicateId => ArmsStatus::DuplicateId,
+            ErrorCode::NotFound => ArmsStatus::NotFound,
+            ErrorCode::IndexNotReady => ArmsStatus::IndexNotReady,
+            _ => ArmsStatus::Error,
+        }
+    }
+}
+
+impl From<PlaceError> for ArmsStatus {
+    fn from(e: PlaceError) -> Self {
+        e.code().into()
+    }
+}
+
+impl From<NearError> for ArmsStatus {
+    fn from(e: NearError) -> Self {
+        e.code().into()
+    }
+}
+
+impl From<ArmsError> for ArmsStatus {
+    fn from(e: ArmsError) -> Self {
+        e.code().into()
+    }
+}
+
+impl From<Id> for ArmsId {
+    fn from(id: Id) -> Self {
+        Self { bytes: *id.as_bytes() }
+    }
+}
+
+impl From<ArmsId> for Id {
+    fn from(id: ArmsId) -> Self {
+        Id::from_bytes(id.bytes)
+    }
+}
+
+/// Borrow a float slice from C, rejecting null for non-empty input
+///
+/// # Safety
+/// `data` must point to `len` readable floats when non-null.
+unsafe fn slice_arg<'a, T>(data: *const T, len: usize) -> Option<&'a [T]> {
+    if len == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(std::slice::from_raw_parts(data, len))
+    }
+}
+
+// ============================================================================
+// LIFECYCLE
+// ============================================================================
+
+/// Create an engine with a flat (exact) cosine index
+#[no_mangle]
+pub extern "C" fn arms_new(dimensionality: usize) -> *mut ArmsHandle {
+    Box::into_raw(Box::new(ArmsHandle {
+        arms: Arms::new(ArmsConfig::new(dimensionality)),
+    }))
+}
+
+/// Create an engine backed by a HAT index
+#[no_mangle]
+pub extern "C" fn arms_new_hat(dimensionality: usize) -> *mut ArmsHandle {
+    let arms = Arms::with_adapters(
+        ArmsConfig::new(dimensionality),
+        Box::new(MemoryStorage::new(dimensionality)),
+        Box::new(HatIndex::cosine(dimensionality)),
+    );
+    Box::into_raw(Box::new(ArmsHandle { arms }))
+}
+
+/// Destroy an engine (null is ignored)
+///
+/// # Safety
+/// `handle` must come from `arms_new*` and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn arms_free(handle: *mut ArmsHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+// ============================================================================
+// OPERATIONS
+// ============================================================================
+
+/// Place a point with an optional payload
+///
+/// # Safety
+/// `vector` must hold `dim` floats, `blob` `blob_len` bytes (may be null
+/// when `blob_len` is 0), and `out_id` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn arms_place(
+    handle: *mut ArmsHandle,
+    vector: *const f32,
+    dim: usize,
+    blob: *const u8,
+    blob_len: usize,
+    out_id: *mut ArmsId,
+) -> ArmsStatus {
+    let (Some(handle), Some(vector), Some(blob)) =
+        (handle.as_mut(), slice_arg(vector, dim), slice_arg(blob, blob_len))
+    else {
+        return ArmsStatus::NullPointer;
+    };
+    if out_id.is_null() {
+        return ArmsStatus::NullPointer;
+    }
+
+    match handle.arms.place(Point::new(vector.to_vec()), Blob::new(blob.to_vec())) {
+        Ok(id) => {
+            *out_id = id.into();
+            ArmsStatus::Ok
+        }
+        Err(e) => e.into(),
+    }
+}
+
+/// Find the `k` nearest points
+///
+/// Writes up to `k` results into `out_results` (capacity `k`) and the
+/// number written into `out_len`.
+///
+/// # Safety
+/// `query` must hold `dim` floats, `out_results` must have room for `k`
+/// results, and `out_len` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn arms_near(
+    handle: *const ArmsHandle,
+    query: *const f32,
+    dim: usize,
+    k: usize,
+    out_results: *mut ArmsSearchResult,
+    out_len: *mut usize,
+) -> ArmsStatus {
+    let (Some(handle), Some(query)) = (handle.as_ref(), slice_arg(query, dim)) else {
+        return ArmsStatus::NullPointer;
+    };
+    if out_len.is_null() || (k > 0 && out_results.is_null()) {
+        return ArmsStatus::NullPointer;
+    }
+
+    match handle.arms.near(&Point::new(query.to_vec()), k) {
+        Ok(results) => {
+            let n = results.len().min(k);
+            for (i, r) in results.iter().take(n).enumerate() {
+                *out_results.add(i) = ArmsSearchResult {
+                    id: r.id.into(),
+                    score: r.score,
+                };
+            }
+            *out_len = n;
+            ArmsStatus::Ok
+        }
+        Err(e) => e.into(),
+    }
+}
+
+/// Remove a point
+///
+/// # Safety
+/// `handle` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn arms_remove(handle: *mut ArmsHandle, id: ArmsId) -> ArmsStatus {
+    let Some(handle) = handle.as_mut() else {
+        return ArmsStatus::NullPointer;
+    };
+    match handle.arms.remove(id.into()) {
+        Some(_) => ArmsStatus::Ok,
+        None => ArmsStatus::NotFound,
+    }
+}
+
+/// Borrow a point's payload
+///
+/// The returned pointer is valid until the next mutating call on `handle`.
+///
+/// # Safety
+/// `out_data` and `out_len` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn arms_get_blob(
+    handle: *const ArmsHandle,
+    id: ArmsId,
+    out_data: *mut *const u8,
+    out_len: *mut usize,
+) -> ArmsStatus {
+    let Some(handle) = handle.as_ref() else {
+        return ArmsStatus::NullPointer;
+    };
+    if out_data.is_null() || out_len.is_null() {
+        return ArmsStatus::NullPointer;
+    }
+
+    match handle.arms.get(id.into()) {
+        Some(placed) => {
+            let data = placed.blob.data();
+            *out_data = if data.is_empty() { ptr::null() } else { data.as_ptr() };
+            *out_len = data.len();
+            ArmsStatus::Ok
+        }
+        None => ArmsStatus::NotFound,
+    }
+}
+
+/// Number of stored points (0 for null)
+///
+/// # Safety
+/// `handle` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn arms_len(handle: *const ArmsHandle) -> usize {
+    handle.as_ref().map_or(0, |h| h.arms.len())
+}
+
+/// Dimensionality of the space (0 for null)
+///
+/// # Safety
+/// `handle` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn arms_dimensionality(handle: *const ArmsHandle) -> usize {
+    handle.as_ref().map_or(0, |h| h.arms.dimensionality())
+}
+
+// ============================================================================
+// INFO
+// ============================================================================
+
+/// Static description of a status code
+#[no_mangle]
+pub extern "C" fn arms_status_message(status: ArmsStatus) -> *const c_char {
+    let msg: &'static CStr = match status {
+        ArmsStatus::Ok => c"ok",
+        ArmsStatus::NullPointer => c"null pointer argument",
+        ArmsStatus::DimensionalityMismatch => c"dimensionality mismatch",
+        ArmsStatus::CapacityExceeded => c"storage capacity exceeded",
+        ArmsStatus::DuplicateId => c"duplicate id",
+        ArmsStatus::NotFound => c"not found",
+        ArmsStatus::IndexNotReady => c"index not ready",
+        ArmsStatus::Error => c"internal error",
+    };
+    msg.as_ptr()
+}
+
+/// Library version (static, NUL-terminated)
+#[no_mangle]
+pub extern "C" fn arms_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_place_near_free() {
+        let handle = arms_new_hat(3);
+        unsafe {
+            let mut id = ArmsId { bytes: [0; 16] };
+            let v = [1.0f32, 0.0, 0.0];
+            let blob = b"hello";
+            assert_eq!(arms_place(handle, v.as_ptr(), 3, blob.as_ptr(), blob.len(), &mut id), ArmsStatus::Ok);
+
+            let w = [0.0f32, 1.0, 0.0];
+            let mut other = ArmsId { bytes: [0; 16] };
+            assert_eq!(arms_place(handle, w.as_ptr(), 3, ptr::null(), 0, &mut other), ArmsStatus::Ok);
+            assert_eq!(arms_len(handle), 2);
+
+            let mut results = [ArmsSearchResult { id: ArmsId { bytes: [0; 16] }, score: 0.0 }; 4];
+            let mut found = 0;
+            assert_eq!(arms_near(handle, v.as_ptr(), 3, 4, results.as_mut_ptr(), &mut found), ArmsStatus::Ok);
+            assert_eq!(found, 2);
+            assert_eq!(results[0].id, id);
+
+            let mut data = ptr::null();
+            let mut len = 0;
+            assert_eq!(arms_get_blob(handle, id, &mut data, &mut len), ArmsStatus::Ok);
+            assert_eq!(std::slice::from_raw_parts(data, len), b"hello");
+
+            assert_eq!(arms_remove(handle, id), ArmsStatus::Ok);
+            assert_eq!(arms_remove(handle, id), ArmsStatus::NotFound);
+            arms_free(handle);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let handle = arms_new(2);
+        unsafe {
+            let mut id = ArmsId { bytes: [0; 16] };
+            let v = [1.0f32, 0.0, 0.0];
+            assert_eq!(
+                arms_place(handle, v.as_ptr(), 3, ptr::null(), 0, &mut id),
+                ArmsStatus::DimensionalityMismatch
+            );
+            assert_eq!(arms_place(handle, ptr::null(), 2, ptr::null(), 0, &mut id), ArmsStatus::NullPointer);
+            assert_eq!(arms_len(ptr::null()), 0);
+
+            let msg = CStr::from_ptr(arms_status_message(ArmsStatus::NotFound));
+            assert_eq!(msg.to_str().unwrap(), "not found");
+            arms_free(handle);
+            arms_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_header_declares_all_exports() {
+        let header = include_str!("../../include/arms_hat.h");
+        let source = include_str!("ffi.rs");
+
+        for line in source.lines() {
+            let line = line.trim_start();
+            let Some(rest) = line
+                .strip_prefix("pub extern \"C\" fn ")
+                .or_else(|| line.strip_prefix("pub unsafe extern \"C\" fn "))
+            else {
+                continue;
+            };
+            let name = &rest[..rest.find('(').unwrap()];
+            assert!(header.contains(&format!("{}(", name)), "{} missing from header", name);
+        }
+
+        // Status values are prefixed with the enum name (cbindgen.toml)
+        let variants = source.split("pub enum ArmsStatus {").nth(1).unwrap();
+        let variants = &variants[..variants.find('}').unwrap()];
+        for variant in variants.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let variant = variant.trim_end_matches(',');
+            assert!(header.contains(&format!("ArmsStatus_{},", variant)), "ArmsStatus_{} missing from header", variant);
+        }
+    }
+}
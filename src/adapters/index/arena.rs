@@ -0,0 +1,235 @@
+//! # Vector Arena
+//!
+//! Contiguous storage for fixed-size vectors.
+//!
+//! All vectors live in one allocation, each row padded to a multiple of
+//! 64 bytes and starting on a 64-byte boundary (one cache line). A scan
+//! walks memory linearly, which the hardware prefetcher handles well and
+//! compilers can vectorize, instead of chasing one heap pointer per vector.
+//!
+//! Rows are addressed by slot. Slots are stable: removing a row frees its
+//! slot for reuse but never moves other rows.
+//!
+//! ```text
+//! slot 0: [x0 x1 ... x(d-1) 0 0 ..]   ← 64-byte aligned, padded
+//! slot 1: [free                    ]
+//! slot 2: [x0 x1 ... x(d-1) 0 0 ..]
+//! ```
+
+use std::ops::Range;
+
+/// Floats per 64-byte block
+const BLOCK_FLOATS: usize = 16;
+
+/// One cache line of floats
+#[derive(Clone, Copy)]
+#[repr(C, align(64))]
+struct Block([f32; BLOCK_FLOATS]);
+
+const ZERO_BLOCK: Block = Block([0.0; BLOCK_FLOATS]);
+
+/// Contiguous, cache-line-aligned storage for vectors of one dimensionality
+#[derive(Clone)]
+pub struct VectorArena {
+    /// Row data, `blocks_per_row` blocks per slot
+    blocks: Vec<Block>,
+
+    /// Length of each vector
+    dimensionality: usize,
+
+    /// Blocks per row (dimensionality rounded up to whole cache lines)
+    blocks_per_row: usize,
+
+    /// Whether each slot holds a vector
+    live: Vec<bool>,
+
+    /// Freed slots, reused before growing
+    free: Vec<usize>,
+}
+
+impl VectorArena {
+    /// Create an empty arena for vectors of `dimensionality` floats
+    pub fn new(dimensionality: usize) -> Self {
+        Self {
+            blocks: Vec::new(),
+            dimensionality,
+            blocks_per_row: dimensionality.div_ceil(BLOCK_FLOATS),
+            live: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Length of each vector
+    pub fn dimensionality(&self) -> usize {
+        self.dimensionality
+    }
+
+    /// Number of vectors stored
+    pub fn len(&self) -> usize {
+        self.live.len() - self.free.len()
+    }
+
+    /// Check if the arena holds no vectors
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of slots (live and free)
+    pub fn slots(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Distance between consecutive rows, in floats
+    pub fn row_stride(&self) -> usize {
+        self.blocks_per_row * BLOCK_FLOATS
+    }
+
+    /// Store a vector, returning its slot
+    ///
+    /// # Panics
+    /// If `vector` doesn't have the arena's dimensionality.
+    pub fn insert(&mut self, vector: &[f32]) -> usize {
+        let slot = match self.free.pop() {
+            Some(slot) => slot,
+            None => {
+                self.blocks
+                    .resize(self.blocks.len() + self.blocks_per_row, ZERO_BLOCK);
+                self.live.push(false);
+                self.live.len() - 1
+            }
+        };
+        self.set(slot, vector);
+        self.live[slot] = true;
+        slot
+    }
+
+    /// Overwrite the vector in a slot
+    ///
+    /// # Panics
+    /// If the slot doesn't exist or `vector` has the wrong length.
+    pub fn set(&mut self, slot: usize, vector: &[f32]) {
+        assert_eq!(vector.len(), self.dimensionality, "Vector has wrong dimensionality");
+        self.row_mut(slot)[..vector.len()].copy_from_slice(vector);
+    }
+
+    /// Free a slot (no-op if it's already free)
+    pub fn remove(&mut self, slot: usize) {
+        if self.live.get(slot).copied().unwrap_or(false) {
+            self.live[slot] = false;
+            self.row_mut(slot).fill(0.0);
+            self.free.push(slot);
+        }
+    }
+
+    /// Vector in a slot, if the slot is live
+    pub fn get(&self, slot: usize) -> Option<&[f32]> {
+        if self.live.get(slot).copied().unwrap_or(false) {
+            Some(&self.row(slot)[..self.dimensionality])
+        } else {
+            None
+        }
+    }
+
+    /// Live vectors with their slots, in slot (memory) order
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &[f32])> + '_ {
+        self.iter_slots(0..self.slots())
+    }
+
+    /// Live vectors within a range of slots (for splitting scans)
+    pub fn iter_slots(&self, slots: Range<usize>) -> impl Iterator<Item = (usize, &[f32])> + '_ {
+        let end = slots.end.min(self.slots());
+        (slots.start..end)
+            .filter(|&slot| self.live[slot])
+            .map(|slot| (slot, &self.row(slot)[..self.dimensionality]))
+    }
+
+    /// Heap bytes reserved (rows plus slot bookkeeping)
+    pub fn size_bytes(&self) -> usize {
+        self.blocks.capacity() * std::mem::size_of::<Block>()
+            + self.live.capacity()
+            + self.free.capacity() * std::mem::size_of::<usize>()
+    }
+
+    /// Remove everything
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.live.clear();
+        self.free.clear();
+    }
+
+    /// Whole padded row of a slot
+    fn row(&self, slot: usize) -> &[f32] {
+        let stride = self.row_stride();
+        &self.floats()[slot * stride..(slot + 1) * stride]
+    }
+
+    fn row_mut(&mut self, slot: usize) -> &mut [f32] {
+        let stride = self.row_stride();
+        &mut self.floats_mut()[slot * stride..(slot + 1) * stride]
+    }
+
+    /// All rows as one float slice
+    fn floats(&self) -> &[f32] {
+        // SAFETY: `Block` is `repr(C)` around `[f32; BLOCK_FLOATS]` with no
+        // padding (64 bytes of floats, 64-byte alignment), so a slice of
+        // blocks is a valid slice of `len * BLOCK_FLOATS` floats.
+        unsafe {
+            std::slice::from_raw_parts(
+                self.blocks.as_ptr().cast::<f32>(),
+                self.blocks.len() * BLOCK_FLOATS,
+            )
+        }
+    }
+
+    fn floats_mut(&mut self) -> &mut [f32] {
+        // SAFETY: as in `floats`; the borrow of `self.blocks` is exclusive.
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                self.blocks.as_mut_ptr().cast::<f32>(),
+                self.blocks.len() * BLOCK_FLOATS,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows_are_aligned_and_padded() {
+        let mut arena = VectorArena::new(20);
+        assert_eq!(arena.row_stride(), 32);
+
+        let a = arena.insert(&[1.0; 20]);
+        let b = arena.insert(&[2.0; 20]);
+
+        for slot in [a, b] {
+            let row = arena.get(slot).unwrap();
+            assert_eq!(row.len(), 20);
+            assert_eq!(row.as_ptr() as usize % 64, 0);
+        }
+        assert_eq!(arena.get(b).unwrap()[19], 2.0);
+    }
+
+    #[test]
+    fn test_slots_are_stable_and_reused() {
+        let mut arena = VectorArena::new(2);
+        let a = arena.insert(&[1.0, 0.0]);
+        let b = arena.insert(&[0.0, 1.0]);
+        let c = arena.insert(&[1.0, 1.0]);
+
+        arena.remove(b);
+        assert_eq!(arena.len(), 2);
+        assert!(arena.get(b).is_none());
+        assert_eq!(arena.get(c), Some(&[1.0, 1.0][..]));
+
+        // The freed slot is reused instead of growing
+        let d = arena.insert(&[5.0, 5.0]);
+        assert_eq!(d, b);
+        assert_eq!(arena.slots(), 3);
+
+        let slots: Vec<_> = arena.iter().map(|(slot, _)| slot).collect();
+        assert_eq!(slots, vec![a, b, c]);
+    }
+}
@@ -0,0 +1,649 @@
+//! # Backups
+//!
+//! Timestamped HAT snapshots kept in a `BackupStore`, pruned by a
+//! `RetentionPolicy`, checked for integrity and restored by time.
+//!
+//! Each backup is one object named after its timestamp, holding the
+//! regular `.hat` bytes behind a small header with their length and an
+//! FNV-1a checksum, so truncation or bit rot is caught before decoding.
+//!
+//! ```text
+//! {timestamp_ms}.hatbak = "ARMB" | version u32 | timestamp u64 | len u64 | fnv64 u64 | .hat bytes
+//! ```
+//!
+//! `DirBackupStore` keeps backups in a directory; with feature `cold-tier`,
+//! `ObjectBackupStore` keeps them in any `object_store::ObjectStore`.
+//!
+//! ```rust,ignore
+//! let backups = Arc::new(
+//!     BackupManager::new(DirBackupStore::new("/var/backups/arms")?)
+//!         .with_retention(RetentionPolicy::new().keep_last(6).keep_daily(7).keep_weekly(4)),
+//! );
+//!
+//! // Snapshot every hour on a background thread (pruning as it goes)
+//! let schedule = backups.clone().schedule(index.clone(), Duration::from_secs(3600));
+//!
+//! // Later: the index as of the last backup at or before a moment
+//! let restored = backups.restore_from_backup(yesterday_ms)?;
+//!
+//! // Erasures reach the backups too
+//! let arms = arms.with_erasure_target(backups.clone());
+//! ```
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::sync::mpsc::{self, RecvTimeoutError};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::sync::{Arc, PoisonError, RwLock};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::thread::JoinHandle;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Duration;
+
+use super::persistence::PersistError;
+use super::{HatIndex, HatSnapshot};
+use crate::core::bytes::{ByteError, ByteReader};
+use crate::core::{clock, Id};
+use crate::engine::ErasureTarget;
+use crate::ports::Near;
+
+/// Backup header magic
+const MAGIC: &[u8; 4] = b"ARMB";
+
+/// Backup header version
+const VERSION: u32 = 1;
+
+/// Backup object name suffix
+const SUFFIX: &str = ".hatbak";
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Errors from taking, checking or restoring backups
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum BackupError {
+    /// No backup at or before the requested time
+    #[error("No backup at or before {0} ms")]
+    NotFound(u64),
+
+    /// The backup's bytes don't match its header
+    #[error("Backup {timestamp_ms} is corrupt: {reason}")]
+    Corrupt { timestamp_ms: u64, reason: String },
+
+    /// The index couldn't be serialized or restored
+    #[error(transparent)]
+    Persist(#[from] PersistError),
+
+    /// The store failed
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Somewhere to keep backup objects by name
+pub trait BackupStore: Send + Sync {
+    /// Write an object, replacing any previous one
+    fn put(&self, name: &str, data: &[u8]) -> io::Result<()>;
+
+    /// Read an object back
+    fn get(&self, name: &str) -> io::Result<Vec<u8>>;
+
+    /// Names of all objects
+    fn list(&self) -> io::Result<Vec<String>>;
+
+    /// Remove an object (missing objects are fine)
+    fn delete(&self, name: &str) -> io::Result<()>;
+}
+
+/// Backups as files in a directory
+#[derive(Debug, Clone)]
+pub struct DirBackupStore {
+    dir: PathBuf,
+}
+
+impl DirBackupStore {
+    /// Keep backups in `dir`, creating it if needed
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+}
+
+impl BackupStore for DirBackupStore {
+    fn put(&self, name: &str, data: &[u8]) -> io::Result<()> {
+        // Write aside, sync and rename, so a crash never leaves half a backup
+        let path = self.dir.join(name);
+        let temp = self.dir.join(format!("{}.tmp", name));
+        let file = fs::File::create(&temp)?;
+        io::Write::write_all(&mut &file, data)?;
+        file.sync_all()?;
+        fs::rename(&temp, &path)
+    }
+
+    fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.dir.join(name))
+    }
+
+    fn list(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            if let Some(name) = entry?.file_name().to_str() {
+                names.push(name.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    fn delete(&self, name: &str) -> io::Result<()> {
+        match fs::remove_file(self.dir.join(name)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Backups in an object store (feature `cold-tier`)
+///
+/// Like `ColdTier`, drives the async store on a private single-threaded
+/// runtime; don't call it from inside another runtime's worker thread.
+#[cfg(feature = "cold-tier")]
+pub struct ObjectBackupStore {
+    store: Arc<dyn object_store::ObjectStore>,
+    prefix: object_store::path::Path,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[cfg(feature = "cold-tier")]
+impl ObjectBackupStore {
+    /// Keep backups under `prefix` in `store`
+    pub fn new(store: Arc<dyn object_store::ObjectStore>, prefix: &str) -> io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        Ok(Self { store, prefix: object_store::path::Path::from(prefix), runtime })
+    }
+
+    fn location(&self, name: &str) -> object_store::path::Path {
+        self.prefix.child(name)
+    }
+}
+
+#[cfg(feature = "cold-tier")]
+impl BackupStore for ObjectBackupStore {
+    fn put(&self, name: &str, data: &[u8]) -> io::Result<()> {
+        let payload = object_store::PutPayload::from(data.to_vec());
+        self.runtime.block_on(self.store.put(&self.location(name), payload)).map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+        self.runtime
+            .block_on(async { self.store.get(&self.location(name)).await?.bytes().await })
+            .map(|bytes| bytes.to_vec())
+            .map_err(io::Error::other)
+    }
+
+    fn list(&self) -> io::Result<Vec<String>> {
+        let listing = self
+            .runtime
+            .block_on(self.store.list_with_delimiter(Some(&self.prefix)))
+            .map_err(io::Error::other)?;
+        Ok(listing.objects.iter().filter_map(|meta| meta.location.filename().map(str::to_string)).collect())
+    }
+
+    fn delete(&self, name: &str) -> io::Result<()> {
+        match self.runtime.block_on(self.store.delete(&self.location(name))) {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+}
+
+/// Which backups to keep
+///
+/// A backup survives pruning if any rule keeps it: one of the newest
+/// `keep_last`, the newest of one of the latest `keep_daily` days with a
+/// backup, or the newest of one of the latest `keep_weekly` weeks (UTC,
+/// weeks starting Monday). The newest backup is always kept. A policy
+/// with no rules keeps everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionPolicy {
+    pub keep_last: usize,
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+}
+
+impl RetentionPolicy {
+    /// No rules yet (keeps everything until one is added)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keep_last(mut self, n: usize) -> Self {
+        self.keep_last = n;
+        self
+    }
+
+    pub fn keep_daily(mut self, n: usize) -> Self {
+        self.keep_daily = n;
+        self
+    }
+
+    pub fn keep_weekly(mut self, n: usize) -> Self {
+        self.keep_weekly = n;
+        self
+    }
+
+    fn keeps_everything(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Timestamps to keep out of `timestamps`
+    pub fn select(&self, timestamps: &[u64]) -> BTreeSet<u64> {
+        let mut newest_first = timestamps.to_vec();
+        newest_first.sort_unstable_by(|a, b| b.cmp(a));
+        newest_first.dedup();
+        if self.keeps_everything() {
+            return newest_first.into_iter().collect();
+        }
+
+        let mut keep: BTreeSet<u64> = newest_first.iter().take(self.keep_last.max(1)).copied().collect();
+        keep.extend(newest_per_bucket(&newest_first, self.keep_daily, |ts| ts / DAY_MS));
+        // Monday-based weeks: the epoch fell on a Thursday
+        keep.extend(newest_per_bucket(&newest_first, self.keep_weekly, |ts| (ts / DAY_MS + 3) / 7));
+        keep
+    }
+}
+
+/// Newest timestamp of each of the latest `count` buckets
+fn newest_per_bucket(newest_first: &[u64], count: usize, bucket: impl Fn(u64) -> u64) -> Vec<u64> {
+    let mut picked: Vec<u64> = Vec::new();
+    for &ts in newest_first {
+        if picked.len() == count {
+            break;
+        }
+        if picked.last().map(|&prev| bucket(prev)) != Some(bucket(ts)) {
+            picked.push(ts);
+        }
+    }
+    picked
+}
+
+/// A stored backup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupInfo {
+    /// When the snapshot was taken (ms since the Unix epoch)
+    pub timestamp_ms: u64,
+
+    /// Object name in the store
+    pub name: String,
+}
+
+impl BackupInfo {
+    fn new(timestamp_ms: u64) -> Self {
+        // Zero-padded so names sort by time
+        Self { timestamp_ms, name: format!("{:020}{}", timestamp_ms, SUFFIX) }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        let timestamp_ms = name.strip_suffix(SUFFIX)?.parse().ok()?;
+        Some(Self { timestamp_ms, name: name.to_string() })
+    }
+}
+
+/// A backup object: header, then the `.hat` bytes
+fn encode(payload: &[u8], timestamp_ms: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(payload.len() + 32);
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&VERSION.to_le_bytes());
+    data.extend_from_slice(&timestamp_ms.to_le_bytes());
+    data.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    data.extend_from_slice(&checksum(payload).to_le_bytes());
+    data.extend_from_slice(payload);
+    data
+}
+
+/// 64-bit FNV-1a
+fn checksum(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &b in data {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Takes, prunes, verifies and restores backups in one store
+pub struct BackupManager {
+    store: Box<dyn BackupStore>,
+    retention: RetentionPolicy,
+}
+
+impl BackupManager {
+    pub fn new(store: impl BackupStore + 'static) -> Self {
+        Self { store: Box::new(store), retention: RetentionPolicy::default() }
+    }
+
+    /// Prune to `retention` after each backup
+    pub fn with_retention(mut self, retention: RetentionPolicy) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    pub fn retention(&self) -> RetentionPolicy {
+        self.retention
+    }
+
+    /// Back up `index` as of now, then prune
+    pub fn backup(&self, index: &HatIndex) -> Result<BackupInfo, BackupError> {
+        self.backup_snapshot(&index.snapshot(), clock::now_ms())
+    }
+
+    /// Store a snapshot taken at `timestamp_ms`, then prune
+    ///
+    /// Taking the `HatSnapshot` is cheap; serializing it can happen without
+    /// holding the index.
+    pub fn backup_snapshot(&self, snapshot: &HatSnapshot, timestamp_ms: u64) -> Result<BackupInfo, BackupError> {
+        let info = BackupInfo::new(timestamp_ms);
+        self.store.put(&info.name, &encode(&snapshot.to_bytes()?, timestamp_ms))?;
+        self.prune()?;
+        Ok(info)
+    }
+
+    /// Rewrite every backup holding any of `ids` without them
+    ///
+    /// Returns how many backups were rewritten. A backup that can't be read
+    /// still holds whatever it held; the first such error is returned after
+    /// the others are purged.
+    pub fn purge(&self, ids: &[Id]) -> Result<usize, BackupError> {
+        let mut rewritten = 0;
+        let mut failed = None;
+        for info in self.list()? {
+            let purged = self.read(&info).and_then(|bytes| {
+                let mut index = HatIndex::from_bytes(&bytes)?;
+                let before = index.len();
+                for &id in ids {
+                    let _ = index.remove(id);
+                }
+                if index.len() == before {
+                    return Ok(false);
+                }
+                self.store.put(&info.name, &encode(&index.to_bytes()?, info.timestamp_ms))?;
+                Ok(true)
+            });
+            match purged {
+                Ok(true) => rewritten += 1,
+                Ok(false) => {}
+                Err(e) => {
+                    failed.get_or_insert(e);
+                }
+            }
+        }
+        match failed {
+            Some(e) => Err(e),
+            None => Ok(rewritten),
+        }
+    }
+
+    /// Stored backups, oldest first
+    pub fn list(&self) -> Result<Vec<BackupInfo>, BackupError> {
+        let mut backups: Vec<BackupInfo> = self.store.list()?.iter().filter_map(|name| BackupInfo::parse(name)).collect();
+        backups.sort_by_key(|info| info.timestamp_ms);
+        Ok(backups)
+    }
+
+    /// Delete backups the retention policy doesn't keep; returns them
+    pub fn prune(&self) -> Result<Vec<BackupInfo>, BackupError> {
+        let backups = self.list()?;
+        let timestamps: Vec<u64> = backups.iter().map(|info| info.timestamp_ms).collect();
+        let keep = self.retention.select(&timestamps);
+        let mut removed = Vec::new();
+        for info in backups {
+            if !keep.contains(&info.timestamp_ms) {
+                self.store.delete(&info.name)?;
+                removed.push(info);
+            }
+        }
+        Ok(removed)
+    }
+
+    /// The `.hat` bytes of a backup, after checking header and checksum
+    fn read(&self, info: &BackupInfo) -> Result<Vec<u8>, BackupError> {
+        let data = self.store.get(&info.name)?;
+        let corrupt = |reason: String| BackupError::Corrupt { timestamp_ms: info.timestamp_ms, reason };
+
+        let mut r = ByteReader::new(&data);
+        let header = (|| -> Result<_, ByteError> {
+            Ok((r.array::<4>("magic")?, r.u32("version")?, r.u64("timestamp")?, r.u64("length")?, r.u64("checksum")?))
+        })();
+        let (magic, version, timestamp_ms, len, sum) = header.map_err(|e| corrupt(e.to_string()))?;
+        if &magic != MAGIC {
+            return Err(corrupt("not a backup".into()));
+        }
+        if version != VERSION {
+            return Err(corrupt(format!("unsupported backup version {}", version)));
+        }
+        if timestamp_ms != info.timestamp_ms {
+            return Err(corrupt(format!("header says {} ms", timestamp_ms)));
+        }
+        if len != r.remaining() as u64 {
+            return Err(corrupt(format!("expected {} bytes, found {}", len, r.remaining())));
+        }
+        let payload = r.rest();
+        if checksum(payload) != sum {
+            return Err(corrupt("checksum mismatch".into()));
+        }
+        Ok(payload.to_vec())
+    }
+
+    /// Latest backup at or before `timestamp_ms`
+    fn at(&self, timestamp_ms: u64) -> Result<BackupInfo, BackupError> {
+        self.list()?
+            .into_iter()
+            .rev()
+            .find(|info| info.timestamp_ms <= timestamp_ms)
+            .ok_or(BackupError::NotFound(timestamp_ms))
+    }
+
+    /// Check a backup end to end: header, checksum and a full decode
+    pub fn verify(&self, timestamp_ms: u64) -> Result<BackupInfo, BackupError> {
+        let info = self.at(timestamp_ms)?;
+        HatIndex::from_bytes(&self.read(&info)?)?;
+        Ok(info)
+    }
+
+    /// Verify every backup; returns the ones that fail
+    pub fn verify_all(&self) -> Result<Vec<(BackupInfo, BackupError)>, BackupError> {
+        let mut failures = Vec::new();
+        for info in self.list()? {
+            if let Err(e) = self.read(&info).and_then(|bytes| Ok(HatIndex::from_bytes(&bytes)?)) {
+                failures.push((info, e));
+            }
+        }
+        Ok(failures)
+    }
+
+    /// The index as of the latest backup taken at or before `timestamp_ms`
+    ///
+    /// Pass `u64::MAX` for the newest backup.
+    pub fn restore_from_backup(&self, timestamp_ms: u64) -> Result<HatIndex, BackupError> {
+        let info = self.at(timestamp_ms)?;
+        Ok(HatIndex::from_bytes(&self.read(&info)?)?)
+    }
+
+    /// Back up `index` every `every` on a background thread
+    ///
+    /// Each run holds the read lock only while taking a snapshot. Not
+    /// available on `wasm32-unknown-unknown`, which has no threads.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn schedule(self: Arc<Self>, index: Arc<RwLock<HatIndex>>, every: Duration) -> BackupSchedule {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            let mut last = Ok(());
+            loop {
+                match stopped.recv_timeout(every) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    _ => return last,
+                }
+                let snapshot = index.read().unwrap_or_else(PoisonError::into_inner).snapshot();
+                last = self.backup_snapshot(&snapshot, clock::now_ms()).map(|_| ());
+            }
+        });
+        BackupSchedule { stop: Some(stop), thread: Some(thread) }
+    }
+}
+
+impl ErasureTarget for BackupManager {
+    fn describe(&self) -> String {
+        "HAT backups".to_string()
+    }
+
+    fn purge(&self, ids: &[Id]) -> Result<(), String> {
+        BackupManager::purge(self, ids).map(|_| ()).map_err(|e| e.to_string())
+    }
+}
+
+/// A running backup schedule; stops when dropped
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub struct BackupSchedule {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<Result<(), BackupError>>>,
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl BackupSchedule {
+    /// Stop after any backup in progress; returns the last run's error
+    pub fn stop(mut self) -> Result<(), BackupError> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> Result<(), BackupError> {
+        drop(self.stop.take());
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(io::Error::other("backup thread panicked").into()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl Drop for BackupSchedule {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Id, Point};
+    use crate::ports::Near;
+    use crate::testing::temp_path;
+
+    fn index_with(points: usize) -> HatIndex {
+        let mut index = HatIndex::cosine(2);
+        for i in 0..points {
+            let angle = i as f32 * 0.1;
+            index.add(Id::seeded(5, i as u64), &Point::new(vec![angle.cos(), angle.sin()])).unwrap();
+        }
+        index
+    }
+
+    #[test]
+    fn test_retention_selection() {
+        let hour = DAY_MS / 24;
+        // Two backups a day for ten days, starting on a Monday (1970-01-05)
+        let timestamps: Vec<u64> = (0..10).flat_map(|d| [(4 + d) * DAY_MS + hour, (4 + d) * DAY_MS + 13 * hour]).collect();
+        let newest = *timestamps.last().unwrap();
+
+        assert_eq!(RetentionPolicy::default().select(&timestamps).len(), 20);
+        assert_eq!(RetentionPolicy::new().keep_last(1).select(&timestamps), BTreeSet::from([newest]));
+        assert_eq!(RetentionPolicy::new().keep_last(3).select(&timestamps).len(), 3);
+
+        // Newest of each of the last three days
+        let daily = RetentionPolicy::new().keep_daily(3).select(&timestamps);
+        assert_eq!(daily, BTreeSet::from([newest, newest - DAY_MS, newest - 2 * DAY_MS]));
+
+        // Days 0-6 are one week, 7-9 the next
+        let weekly = RetentionPolicy::new().keep_weekly(5).select(&timestamps);
+        assert_eq!(weekly, BTreeSet::from([newest, 10 * DAY_MS + 13 * hour]));
+    }
+
+    #[test]
+    fn test_backup_restore_and_prune() {
+        let dir = temp_path("arms-backup-restore");
+        let manager = BackupManager::new(DirBackupStore::new(&dir).unwrap())
+            .with_retention(RetentionPolicy::new().keep_last(2));
+
+        for (points, ts) in [(1, 1_000), (2, 2_000), (3, 3_000)] {
+            manager.backup_snapshot(&index_with(points).snapshot(), ts).unwrap();
+        }
+        let kept: Vec<u64> = manager.list().unwrap().iter().map(|info| info.timestamp_ms).collect();
+        assert_eq!(kept, vec![2_000, 3_000]);
+
+        assert_eq!(manager.restore_from_backup(2_500).unwrap().len(), 2);
+        assert_eq!(manager.restore_from_backup(u64::MAX).unwrap().len(), 3);
+        assert!(matches!(manager.restore_from_backup(1_500), Err(BackupError::NotFound(1_500))));
+        assert_eq!(manager.verify(3_000).unwrap().timestamp_ms, 3_000);
+        assert!(manager.verify_all().unwrap().is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_purge_rewrites_backups() {
+        let dir = temp_path("arms-backup-purge");
+        let manager = BackupManager::new(DirBackupStore::new(&dir).unwrap());
+        manager.backup_snapshot(&index_with(2).snapshot(), 1_000).unwrap();
+        manager.backup_snapshot(&index_with(3).snapshot(), 2_000).unwrap();
+
+        // Only the newer backup holds the third point
+        let erased = Id::seeded(5, 2);
+        assert_eq!(manager.purge(&[erased]).unwrap(), 1);
+        let restored = manager.restore_from_backup(2_000).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert!(!restored.contains(erased));
+        assert!(manager.verify_all().unwrap().is_empty());
+        assert_eq!(manager.purge(&[erased]).unwrap(), 0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_corruption_is_detected() {
+        let dir = temp_path("arms-backup-corrupt");
+        let manager = BackupManager::new(DirBackupStore::new(&dir).unwrap());
+        let info = manager.backup_snapshot(&index_with(4).snapshot(), 7_000).unwrap();
+
+        let path = dir.join(&info.name);
+        let mut data = fs::read(&path).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+        fs::write(&path, &data).unwrap();
+        assert!(matches!(manager.verify(7_000), Err(BackupError::Corrupt { reason, .. }) if reason.contains("checksum")));
+
+        fs::write(&path, &data[..10]).unwrap();
+        assert!(matches!(manager.restore_from_backup(7_000), Err(BackupError::Corrupt { .. })));
+        assert_eq!(manager.verify_all().unwrap().len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_schedule() {
+        let dir = temp_path("arms-backup-schedule");
+        let manager = Arc::new(BackupManager::new(DirBackupStore::new(&dir).unwrap()));
+        let index = Arc::new(RwLock::new(index_with(2)));
+
+        let schedule = manager.clone().schedule(index, Duration::from_millis(20));
+        while manager.list().unwrap().is_empty() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        schedule.stop().unwrap();
+        assert_eq!(manager.restore_from_backup(u64::MAX).unwrap().len(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,216 @@
+//! # Cold Tier
+//!
+//! Old HAT sessions offloaded to an object store (feature `cold-tier`).
+//!
+//! A session leaves the index as the shard `export_session` writes, stored
+//! as one object; it comes back with `import_session` when it's needed.
+//! Fetched shards can be cached on local disk, so a session revisited often
+//! is downloaded once.
+//!
+//! ```text
+//! HatIndex ──offload──▶ {prefix}/sessions/{id}.hat ──restore──▶ HatIndex
+//!                                │
+//!                          cache_dir/{id}.hat
+//! ```
+//!
+//! Any `object_store::ObjectStore` works: S3, GCS and Azure through the
+//! `object_store` crate's `aws`/`gcp`/`azure` features, the local filesystem,
+//! or memory for tests. `ObjectStore` is async; `ColdTier` drives it on its
+//! own single-threaded runtime so it fits the synchronous index API. Don't
+//! call it from inside another async runtime's worker thread.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutPayload};
+
+use super::persistence::PersistError;
+use super::HatIndex;
+use crate::core::Id;
+use crate::ports::NearError;
+
+/// Errors from the cold tier
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ColdTierError {
+    /// The object store failed (including "not found")
+    #[error("Object store error: {0}")]
+    Store(#[from] object_store::Error),
+
+    /// A shard couldn't be written or read
+    #[error(transparent)]
+    Persist(#[from] PersistError),
+
+    /// The index refused the operation
+    #[error(transparent)]
+    Index(#[from] NearError),
+
+    /// Local cache or runtime I/O failed
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Session shards kept in an object store
+pub struct ColdTier {
+    store: Arc<dyn ObjectStore>,
+
+    /// Key prefix for this index's sessions
+    prefix: ObjectPath,
+
+    /// Local directory caching fetched shards (None = always fetch)
+    cache_dir: Option<PathBuf>,
+
+    runtime: tokio::runtime::Runtime,
+}
+
+impl ColdTier {
+    /// Use `store`, keeping sessions under `sessions/`
+    pub fn new(store: Arc<dyn ObjectStore>) -> Result<Self, ColdTierError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            store,
+            prefix: ObjectPath::from("sessions"),
+            cache_dir: None,
+            runtime,
+        })
+    }
+
+    /// Keep sessions under `{prefix}/sessions/` (e.g., one prefix per agent)
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = ObjectPath::from(prefix).child("sessions");
+        self
+    }
+
+    /// Cache fetched shards in a local directory
+    pub fn with_cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    fn location(&self, session_id: Id) -> ObjectPath {
+        self.prefix.child(format!("{}.hat", session_id))
+    }
+
+    fn cache_path(&self, session_id: Id) -> Option<PathBuf> {
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.hat", session_id)))
+    }
+
+    /// Upload a session, then remove it from the index
+    ///
+    /// Returns the number of chunks offloaded. The index is only changed
+    /// once the upload succeeded.
+    pub fn offload(&self, index: &mut HatIndex, session_id: Id) -> Result<usize, ColdTierError> {
+        let shard = index.export_session(session_id)?;
+        self.upload(session_id, shard)?;
+        Ok(index.remove_session(session_id)?)
+    }
+
+    /// Store a session shard produced by `HatIndex::export_session`
+    pub fn upload(&self, session_id: Id, shard: Vec<u8>) -> Result<(), ColdTierError> {
+        let location = self.location(session_id);
+        self.runtime
+            .block_on(self.store.put(&location, PutPayload::from(shard)))?;
+        Ok(())
+    }
+
+    /// A session's shard, from the local cache or the store
+    pub fn fetch(&self, session_id: Id) -> Result<Vec<u8>, ColdTierError> {
+        let cache_path = self.cache_path(session_id);
+        if let Some(bytes) = cache_path.as_ref().and_then(|p| std::fs::read(p).ok()) {
+            return Ok(bytes);
+        }
+
+        let location = self.location(session_id);
+        let bytes = self.runtime.block_on(async {
+            self.store.get(&location).await?.bytes().await
+        })?;
+
+        if let Some(path) = cache_path {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, &bytes)?;
+        }
+        Ok(bytes.to_vec())
+    }
+
+    /// Bring a session back into the index
+    ///
+    /// The shard stays in the store; `delete` it if the session should
+    /// live only in the index again.
+    pub fn restore(&self, index: &mut HatIndex, session_id: Id) -> Result<Id, ColdTierError> {
+        let shard = self.fetch(session_id)?;
+        Ok(index.import_session(&shard)?)
+    }
+
+    /// Sessions in the store, sorted by ID (oldest first)
+    pub fn sessions(&self) -> Result<Vec<Id>, ColdTierError> {
+        let listing = self
+            .runtime
+            .block_on(self.store.list_with_delimiter(Some(&self.prefix)))?;
+        let mut ids: Vec<Id> = listing
+            .objects
+            .iter()
+            .filter_map(|meta| meta.location.filename())
+            .filter_map(|name| name.strip_suffix(".hat"))
+            .filter_map(Id::from_hex)
+            .collect();
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Delete a session from the store and the local cache
+    pub fn delete(&self, session_id: Id) -> Result<(), ColdTierError> {
+        let location = self.location(session_id);
+        self.runtime.block_on(self.store.delete(&location))?;
+        if let Some(path) = self.cache_path(session_id) {
+            let _ = std::fs::remove_file(path);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Point;
+    use crate::ports::Near;
+    use object_store::memory::InMemory;
+
+    #[test]
+    fn test_offload_and_restore() {
+        let store = Arc::new(InMemory::new());
+        let cache = crate::testing::temp_path("arms-cold");
+        let tier = ColdTier::new(store.clone())
+            .unwrap()
+            .with_prefix("agent-1")
+            .with_cache_dir(&cache);
+
+        let mut index = HatIndex::cosine(2);
+        let old = index.new_session_named("old");
+        let chunk = Id::now();
+        index.add(chunk, &Point::new(vec![1.0, 0.0])).unwrap();
+        index.new_session();
+        index.add(Id::now(), &Point::new(vec![0.0, 1.0])).unwrap();
+
+        assert_eq!(tier.offload(&mut index, old).unwrap(), 1);
+        assert_eq!(index.len(), 1);
+        assert_eq!(tier.sessions().unwrap(), vec![old]);
+
+        assert_eq!(tier.restore(&mut index, old).unwrap(), old);
+        assert_eq!(index.near(&Point::new(vec![1.0, 0.0]), 1).unwrap()[0].id, chunk);
+
+        // The fetched shard was cached locally
+        assert!(cache.join(format!("{}.hat", old)).exists());
+
+        tier.delete(old).unwrap();
+        assert!(tier.sessions().unwrap().is_empty());
+        assert!(matches!(tier.fetch(old), Err(ColdTierError::Store(_))));
+        let _ = std::fs::remove_dir_all(&cache);
+    }
+}
@@ -0,0 +1,298 @@
+//! # Memory Compression for HAT
+//!
+//! "Sleep-time" compression: documents of old chunks are handed to a
+//! caller-supplied `Summarizer` (typically an LLM plus an embedding model),
+//! and each summary replaces the chunks it was written from.
+//!
+//! The summary becomes an ordinary chunk in the same document - its payload
+//! is the summary text, its point the summary embedding - so queries keep
+//! finding what the old chunks were about. It also becomes the document's
+//! summary (see `HatIndex::set_document_summary`) and takes the removed
+//! chunks' place in the session's turn chain.
+//!
+//! Two ways to run it:
+//!
+//! - `HatIndex::compress`: blocking, summarizes under `&mut self`
+//! - `compress_shared`: for slow summarizers - gathers clusters under the
+//!   read lock, calls the summarizer with no lock held, and takes the write
+//!   lock only to swap each summary in; runs as a `Job`
+//!
+//! Callers driving their own async pipeline can use the two halves
+//! directly: `compression_candidates`, then `apply_compression` per
+//! finished summary.
+//!
+//! ```rust,ignore
+//! let summarizer = |cluster: &ChunkCluster| {
+//!     let text = llm.summarize(cluster.texts())?;
+//!     Some(MemorySummary { vector: embedder.embed(&text).ok()?, text })
+//! };
+//! let report = index.compress(&CompressionConfig::older_than_ms(7 * DAY_MS), &summarizer);
+//! ```
+
+use std::sync::{PoisonError, RwLock};
+
+use crate::core::{Blob, Id, Point};
+use crate::ports::JobHandle;
+
+use super::hat::HatIndex;
+
+/// Which documents are old enough to compress
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressionConfig {
+    /// Compress documents whose newest chunk is at least this old
+    pub older_than_ms: u64,
+
+    /// Leave documents with fewer live chunks alone
+    pub min_chunks: usize,
+
+    /// Most documents to compress in one pass (None = all candidates)
+    pub max_clusters: Option<usize>,
+}
+
+impl CompressionConfig {
+    /// Documents untouched for `older_than_ms`, with at least 2 chunks
+    pub fn older_than_ms(older_than_ms: u64) -> Self {
+        Self { older_than_ms, min_chunks: 2, max_clusters: None }
+    }
+
+    pub fn with_min_chunks(mut self, min_chunks: usize) -> Self {
+        self.min_chunks = min_chunks.max(1);
+        self
+    }
+
+    pub fn with_max_clusters(mut self, max_clusters: usize) -> Self {
+        self.max_clusters = Some(max_clusters);
+        self
+    }
+}
+
+/// An old chunk handed to the summarizer
+#[derive(Debug, Clone)]
+pub struct ClusterChunk {
+    pub id: Id,
+    pub point: Point,
+
+    /// Creation timestamp (ms since epoch)
+    pub timestamp: u64,
+
+    pub payload: Option<Blob>,
+}
+
+/// The chunks of one document, offered for compression
+#[derive(Debug, Clone)]
+pub struct ChunkCluster {
+    pub session: Id,
+    pub document: Id,
+
+    /// In turn order
+    pub chunks: Vec<ClusterChunk>,
+}
+
+impl ChunkCluster {
+    /// Chunk payloads read as UTF-8 text, skipping chunks without one
+    pub fn texts(&self) -> Vec<String> {
+        self.chunks
+            .iter()
+            .filter_map(|chunk| chunk.payload.as_ref())
+            .map(|blob| String::from_utf8_lossy(blob.data()).into_owned())
+            .collect()
+    }
+
+    /// Timestamp of the newest chunk
+    pub fn newest(&self) -> u64 {
+        self.chunks.iter().map(|chunk| chunk.timestamp).max().unwrap_or(0)
+    }
+}
+
+/// What a summarizer writes for a cluster
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemorySummary {
+    pub text: String,
+
+    /// Embedding of `text` (same space as the chunks)
+    pub vector: Point,
+}
+
+/// Writes summaries of old chunks
+///
+/// Returning None leaves the cluster as it is. Implemented for closures.
+pub trait Summarizer: Send + Sync {
+    fn summarize(&self, cluster: &ChunkCluster) -> Option<MemorySummary>;
+}
+
+impl<F> Summarizer for F
+where
+    F: Fn(&ChunkCluster) -> Option<MemorySummary> + Send + Sync,
+{
+    fn summarize(&self, cluster: &ChunkCluster) -> Option<MemorySummary> {
+        self(cluster)
+    }
+}
+
+/// Outcome of a compression pass
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompressionReport {
+    /// Documents offered to the summarizer
+    pub clusters: usize,
+
+    /// Documents the summarizer declined, or that changed before the
+    /// summary could be applied
+    pub skipped: usize,
+
+    /// Chunks replaced by summaries
+    pub chunks_removed: usize,
+
+    /// IDs of the summary chunks, one per compressed document
+    pub summaries: Vec<Id>,
+}
+
+impl CompressionReport {
+    fn record(&mut self, applied: Option<(Id, usize)>) {
+        match applied {
+            Some((id, removed)) => {
+                self.summaries.push(id);
+                self.chunks_removed += removed;
+            }
+            None => self.skipped += 1,
+        }
+    }
+}
+
+/// `HatIndex::compress` on a shared index
+///
+/// The summarizer runs with no lock held, so queries and inserts carry on
+/// while it waits on a model. Checks for cancellation between clusters; a
+/// cancelled pass keeps the summaries already applied and returns None.
+pub fn compress_shared(
+    index: &RwLock<HatIndex>,
+    config: &CompressionConfig,
+    summarizer: &dyn Summarizer,
+    job: &JobHandle,
+) -> Option<CompressionReport> {
+    let clusters = index.read().unwrap_or_else(PoisonError::into_inner).compression_candidates(config);
+    job.set_total(clusters.len());
+
+    let mut report = CompressionReport { clusters: clusters.len(), ..Default::default() };
+    for cluster in &clusters {
+        if job.is_cancelled() {
+            return None;
+        }
+        let applied = summarizer.summarize(cluster).and_then(|summary| {
+            let mut index = index.write().unwrap_or_else(PoisonError::into_inner);
+            let removed = cluster.chunks.iter().filter(|chunk| index.contains(chunk.id)).count();
+            index.apply_compression(cluster, summary).ok().map(|id| (id, removed))
+        });
+        report.record(applied);
+        job.advance(1);
+    }
+    Some(report)
+}
+
+impl HatIndex {
+    /// Compress every candidate document with `summarizer` (blocking)
+    ///
+    /// See `compress_shared` to summarize without holding the index.
+    pub fn compress(&mut self, config: &CompressionConfig, summarizer: &dyn Summarizer) -> CompressionReport {
+        let clusters = self.compression_candidates(config);
+        let mut report = CompressionReport { clusters: clusters.len(), ..Default::default() };
+        for cluster in &clusters {
+            let applied = summarizer.summarize(cluster).and_then(|summary| {
+                let removed = cluster.chunks.iter().filter(|chunk| self.contains(chunk.id)).count();
+                self.apply_compression(cluster, summary).ok().map(|id| (id, removed))
+            });
+            report.record(applied);
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ports::Near;
+    use std::sync::Arc;
+
+    /// Two finished documents of three chunks, then an active one
+    fn conversation() -> (HatIndex, Vec<Id>) {
+        let mut index = HatIndex::cosine(2);
+        index.new_session();
+        let mut docs = Vec::new();
+        for d in 0..3u64 {
+            index.new_document();
+            for c in 0..3u64 {
+                let id = Id::seeded(9, d * 3 + c);
+                index.add(id, &Point::new(vec![1.0, (d * 3 + c) as f32 * 0.1])).unwrap();
+                index.set_payload(id, Blob::from_str(&format!("turn {}", d * 3 + c))).unwrap();
+            }
+            docs.push(index.active_document().unwrap());
+        }
+        (index, docs)
+    }
+
+    fn joiner(cluster: &ChunkCluster) -> Option<MemorySummary> {
+        Some(MemorySummary { text: cluster.texts().join(" / "), vector: Point::new(vec![0.0, 1.0]) })
+    }
+
+    #[test]
+    fn test_compress() {
+        let (mut index, docs) = conversation();
+        let first = index.thread_of(Id::seeded(9, 0)).unwrap();
+        assert_eq!(first.len(), 9);
+
+        // Nothing is old enough yet
+        assert!(index.compression_candidates(&CompressionConfig::older_than_ms(3_600_000)).is_empty());
+
+        let config = CompressionConfig::older_than_ms(0);
+        let candidates = index.compression_candidates(&config);
+        assert_eq!(candidates.iter().map(|c| c.document).collect::<Vec<_>>(), &docs[..2]);
+
+        // The callback may decline a cluster
+        let declined = index.compress(&config.with_max_clusters(1), &|_: &ChunkCluster| None);
+        assert_eq!((declined.clusters, declined.skipped, index.len()), (1, 1, 9));
+
+        let report = index.compress(&config, &joiner);
+        assert_eq!((report.clusters, report.chunks_removed, report.summaries.len()), (2, 6, 2));
+        assert_eq!(index.len(), 5);
+        assert!(!index.contains(Id::seeded(9, 4)));
+
+        let summary = report.summaries[1];
+        assert_eq!(index.payload(summary).unwrap().data(), b"turn 3 / turn 4 / turn 5");
+        assert_eq!(index.summary(docs[1]).unwrap().text, "turn 3 / turn 4 / turn 5");
+        assert_eq!(index.container(docs[1]).unwrap().children, vec![summary]);
+
+        // Summaries sit where their chunks were in the conversation
+        let thread = index.thread_of(Id::seeded(9, 8)).unwrap();
+        assert_eq!(thread[..2], report.summaries[..]);
+        assert_eq!(thread.len(), 5);
+
+        let hits = index.near(&Point::new(vec![0.0, 1.0]), 2).unwrap();
+        assert!(hits.iter().all(|hit| report.summaries.contains(&hit.id)));
+
+        // Already-applied clusters are refused
+        assert!(matches!(
+            index.apply_compression(&candidates[0], joiner(&candidates[0]).unwrap()),
+            Err(crate::ports::NearError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_compress_shared() {
+        use crate::engine::Job;
+
+        let (index, _) = conversation();
+        let index = Arc::new(RwLock::new(index));
+        let job = Job::spawn("compress", {
+            let index = index.clone();
+            move |handle: &JobHandle| compress_shared(&index, &CompressionConfig::older_than_ms(0), &joiner, handle)
+        });
+        let report = job.wait().unwrap().unwrap();
+        assert_eq!(report.summaries.len(), 2);
+        assert_eq!(index.read().unwrap().len(), 5);
+
+        let fresh = RwLock::new(conversation().0);
+        let cancelled = JobHandle::new();
+        cancelled.cancel();
+        assert!(compress_shared(&fresh, &CompressionConfig::older_than_ms(0), &joiner, &cancelled).is_none());
+        assert_eq!(fresh.read().unwrap().len(), 9);
+    }
+}
@@ -25,11 +25,16 @@
 //! - **Full** (θ): Complete rebuild from scratch (~REM)
 
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{PoisonError, RwLock};
 
 use crate::core::{Id, Point};
+use crate::ports::JobHandle;
+
+/// Job units per consolidation phase (see `Consolidate::consolidate_with`)
+const JOB_UNITS_PER_PHASE: usize = 1000;
 
 /// Consolidation level - determines how deep the maintenance goes
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ConsolidationLevel {
     /// Light: Recompute centroids only
     /// Fast, minimal disruption, good for frequent runs
@@ -37,6 +42,7 @@ pub enum ConsolidationLevel {
 
     /// Medium: Recompute centroids + rebalance tree
     /// Moderate time, restructures containers
+    #[default]
     Medium,
 
     /// Deep: Full maintenance including layout optimization
@@ -48,12 +54,6 @@ pub enum ConsolidationLevel {
     Full,
 }
 
-impl Default for ConsolidationLevel {
-    fn default() -> Self {
-        ConsolidationLevel::Medium
-    }
-}
-
 /// Configuration for consolidation operations
 #[derive(Debug, Clone)]
 pub struct ConsolidationConfig {
@@ -125,6 +125,17 @@ impl ConsolidationConfig {
     }
 }
 
+impl ConsolidationLevel {
+    /// Phases a consolidation at this level goes through
+    pub fn phase_count(self) -> usize {
+        match self {
+            ConsolidationLevel::Light => 2,
+            ConsolidationLevel::Medium => 5,
+            ConsolidationLevel::Deep | ConsolidationLevel::Full => 7,
+        }
+    }
+}
+
 /// Current state of consolidation
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConsolidationPhase {
@@ -156,6 +167,22 @@ pub enum ConsolidationPhase {
     Complete,
 }
 
+impl ConsolidationPhase {
+    /// Phases completed before this one (`Idle` and `Complete` don't count)
+    fn ordinal(self) -> usize {
+        match self {
+            ConsolidationPhase::Idle | ConsolidationPhase::CollectingLeaves => 0,
+            ConsolidationPhase::RecomputingCentroids => 1,
+            ConsolidationPhase::AnalyzingStructure => 2,
+            ConsolidationPhase::Merging => 3,
+            ConsolidationPhase::Splitting => 4,
+            ConsolidationPhase::Pruning => 5,
+            ConsolidationPhase::OptimizingLayout => 6,
+            ConsolidationPhase::Complete => 7,
+        }
+    }
+}
+
 /// Metrics collected during consolidation
 #[derive(Debug, Clone, Default)]
 pub struct ConsolidationMetrics {
@@ -243,10 +270,7 @@ pub struct ConsolidationState {
 impl ConsolidationState {
     /// Create a new consolidation state
     pub fn new(config: ConsolidationConfig) -> Self {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_micros() as u64;
+        let now = crate::core::clock::now_micros();
 
         Self {
             config,
@@ -264,10 +288,7 @@ impl ConsolidationState {
 
     /// Start consolidation
     pub fn start(&mut self) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_micros() as u64;
+        let now = crate::core::clock::now_micros();
 
         self.start_us = now;
         self.phase_start_us = now;
@@ -282,10 +303,7 @@ impl ConsolidationState {
 
     /// Transition to next phase
     pub fn next_phase(&mut self) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_micros() as u64;
+        let now = crate::core::clock::now_micros();
 
         // Record time for previous phase
         let phase_time = now - self.phase_start_us;
@@ -419,6 +437,24 @@ pub trait Consolidate {
     /// Returns Continue if more work remains, Complete when done
     fn consolidation_tick(&mut self) -> ConsolidationTickResult;
 
+    /// Run consolidation to completion, reporting progress to `job`
+    ///
+    /// Checks for cancellation between ticks; a cancelled consolidation is
+    /// abandoned and returns None.
+    fn consolidate_with(&mut self, config: ConsolidationConfig, job: &JobHandle) -> Option<ConsolidationMetrics> {
+        job.set_total(config.level.phase_count() * JOB_UNITS_PER_PHASE);
+        self.begin_consolidation(config);
+        loop {
+            if job.is_cancelled() {
+                self.cancel_consolidation();
+                return None;
+            }
+            if let Some(metrics) = report_tick(self.consolidation_tick(), job) {
+                return Some(metrics);
+            }
+        }
+    }
+
     /// Run consolidation to completion (blocking)
     fn consolidate(&mut self, config: ConsolidationConfig) -> ConsolidationMetrics {
         self.begin_consolidation(config);
@@ -440,6 +476,45 @@ pub trait Consolidate {
     fn cancel_consolidation(&mut self);
 }
 
+/// `Consolidate::consolidate_with` on a shared index
+///
+/// Takes the write lock one tick at a time, so queries run in between.
+pub fn consolidate_shared<C: Consolidate>(
+    index: &RwLock<C>,
+    config: ConsolidationConfig,
+    job: &JobHandle,
+) -> Option<ConsolidationMetrics> {
+    let write = || index.write().unwrap_or_else(PoisonError::into_inner);
+
+    job.set_total(config.level.phase_count() * JOB_UNITS_PER_PHASE);
+    write().begin_consolidation(config);
+    loop {
+        let mut index = write();
+        if job.is_cancelled() {
+            index.cancel_consolidation();
+            return None;
+        }
+        if let Some(metrics) = report_tick(index.consolidation_tick(), job) {
+            return Some(metrics);
+        }
+    }
+}
+
+/// Report a tick's progress to `job`; the metrics once complete
+fn report_tick(tick: ConsolidationTickResult, job: &JobHandle) -> Option<ConsolidationMetrics> {
+    match tick {
+        ConsolidationTickResult::Continue(progress) => {
+            let within_phase = (progress.progress * JOB_UNITS_PER_PHASE as f32) as usize;
+            job.set_done(progress.phase.ordinal() * JOB_UNITS_PER_PHASE + within_phase);
+            None
+        }
+        ConsolidationTickResult::Complete(metrics) => {
+            job.set_done(job.progress().total);
+            Some(metrics)
+        }
+    }
+}
+
 /// Helper for computing exact centroids from a set of points
 pub fn compute_exact_centroid(points: &[Point]) -> Option<Point> {
     if points.is_empty() {
@@ -0,0 +1,154 @@
+//! # Copy-on-Write Map
+//!
+//! A `HashMap` whose values are shared with snapshots until written.
+//!
+//! Cloning the map copies one pointer per entry, not the entries. A write
+//! through `get_mut` copies the value first if a snapshot still holds it,
+//! so the snapshot keeps seeing the state it was taken at:
+//!
+//! ```text
+//! live:      a ─┬─ b ─┬─ c          live:      a ── b' ── c
+//!               │     │       write b             │
+//! snapshot:  a ─┘  b ─┘  c    ──────▶  snapshot:  a ── b ── c
+//! ```
+//!
+//! Lets an index hand a consistent view to a background serializer and
+//! keep accepting writes while it runs.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// Map with values shared between clones until one of them writes
+#[derive(Debug)]
+pub(crate) struct CowMap<K, V> {
+    map: HashMap<K, Arc<V>>,
+}
+
+impl<K, V> Default for CowMap<K, V> {
+    fn default() -> Self {
+        Self { map: HashMap::new() }
+    }
+}
+
+impl<K: Clone, V> Clone for CowMap<K, V> {
+    /// O(n) pointer copies; values are shared
+    fn clone(&self) -> Self {
+        Self { map: self.map.clone() }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> CowMap<K, V> {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Bytes held by the table and the shared allocations (not the
+    /// values' own heap data)
+    pub(crate) fn size_bytes(&self) -> usize {
+        use std::mem::size_of;
+        self.map.capacity() * (size_of::<(K, Arc<V>)>() + 1)
+            + self.map.len() * (size_of::<V>() + 2 * size_of::<usize>())
+    }
+
+    pub(crate) fn contains_key<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.map.contains_key(key)
+    }
+
+    pub(crate) fn get<Q: Eq + Hash + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.get(key).map(Arc::as_ref)
+    }
+
+    /// Mutable access, copying the value first if a snapshot shares it
+    pub(crate) fn get_mut<Q: Eq + Hash + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.get_mut(key).map(Arc::make_mut)
+    }
+
+    /// The value at `key`, inserting the default first if absent
+    pub(crate) fn get_or_default(&mut self, key: K) -> &mut V
+    where
+        V: Default,
+    {
+        Arc::make_mut(self.map.entry(key).or_default())
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        self.map.insert(key, Arc::new(value));
+    }
+
+    /// Remove an entry (the value is only copied if a snapshot shares it)
+    pub(crate) fn remove<Q: Eq + Hash + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        self.map.remove(key).map(Arc::unwrap_or_clone)
+    }
+
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.map.keys()
+    }
+
+    pub(crate) fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.map.values().map(Arc::as_ref)
+    }
+
+    /// Mutable iteration (copies every value still shared with a snapshot)
+    pub(crate) fn values_mut(&mut self) -> impl Iterator<Item = &mut V> + '_ {
+        self.map.values_mut().map(Arc::make_mut)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.map.iter().map(|(k, v)| (k, v.as_ref()))
+    }
+}
+
+impl<K: Eq + Hash, V> Extend<(K, V)> for CowMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|(k, v)| (k, Arc::new(v))));
+    }
+}
+
+impl<K: Eq + Hash + Borrow<Q>, Q: Eq + Hash + ?Sized, V> std::ops::Index<&Q> for CowMap<K, V> {
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        &self.map[key]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_is_isolated_from_writes() {
+        let mut live = CowMap::new();
+        live.insert(1, vec![1.0]);
+        live.insert(2, vec![2.0]);
+
+        let snapshot = live.clone();
+        live.get_mut(&1).unwrap().push(1.5);
+        live.remove(&2);
+        live.insert(3, vec![3.0]);
+
+        assert_eq!(snapshot.get(&1), Some(&vec![1.0]));
+        assert_eq!(snapshot.get(&2), Some(&vec![2.0]));
+        assert!(!snapshot.contains_key(&3));
+        assert_eq!(live[&1], vec![1.0, 1.5]);
+        assert_eq!(live.len(), 2);
+    }
+}
@@ -0,0 +1,77 @@
+//! # Result Deduplication
+//!
+//! Collapses near-identical results into one representative at query time.
+//!
+//! Results are walked best first; one is kept unless its proximity to an
+//! already kept result passes the threshold. The representative of each
+//! cluster of copies is therefore its best-scoring member:
+//!
+//! ```text
+//! ranked:  p1 (0.91)  p1' (0.90)  p2 (0.85)  p1'' (0.84)
+//! kept:    p1 (0.91)              p2 (0.85)
+//! ```
+//!
+//! Comparisons are pairwise against kept results, so cost grows with
+//! `k * candidates`; meant for the tens of results handed to a prompt.
+
+use crate::core::proximity::Proximity;
+use crate::core::{Id, Point};
+use crate::ports::SearchResult;
+
+/// Keep up to `k` results, dropping any within `threshold` of a better one
+///
+/// `vector` looks up a result's point; results without one are kept.
+pub(crate) fn collapse_duplicates<'a>(
+    ranked: impl IntoIterator<Item = SearchResult>,
+    k: usize,
+    threshold: f32,
+    proximity: &dyn Proximity,
+    vector: impl Fn(Id) -> Option<&'a Point>,
+) -> Vec<SearchResult> {
+    let order = proximity.order();
+    let mut kept: Vec<(SearchResult, Option<&Point>)> = Vec::with_capacity(k);
+    for result in ranked {
+        if kept.len() == k {
+            break;
+        }
+        let point = vector(result.id);
+        let duplicate = point.is_some_and(|p| {
+            kept.iter()
+                .filter_map(|(_, q)| *q)
+                .any(|q| order.passes(proximity.proximity(p, q), threshold))
+        });
+        if !duplicate {
+            kept.push((result, point));
+        }
+    }
+    kept.into_iter().map(|(result, _)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::proximity::{Cosine, Euclidean};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_keeps_best_of_each_cluster() {
+        let points: HashMap<Id, Point> = [
+            (Id::from_bytes([1; 16]), Point::new(vec![1.0, 0.0])),
+            (Id::from_bytes([2; 16]), Point::new(vec![1.0, 0.01])),
+            (Id::from_bytes([3; 16]), Point::new(vec![0.0, 1.0])),
+            (Id::from_bytes([4; 16]), Point::new(vec![0.99, 0.0])),
+        ]
+        .into();
+        let ranked: Vec<SearchResult> = (1..=4u8)
+            .map(|n| SearchResult::new(Id::from_bytes([n; 16]), 1.0 - n as f32 * 0.1))
+            .collect();
+
+        let kept = collapse_duplicates(ranked.clone(), 10, 0.99, &Cosine, |id| points.get(&id));
+        let ids: Vec<u8> = kept.iter().map(|r| r.id.as_bytes()[0]).collect();
+        assert_eq!(ids, vec![1, 3]);
+
+        // Distances collapse below the threshold
+        let kept = collapse_duplicates(ranked, 10, 0.05, &Euclidean, |id| points.get(&id));
+        assert_eq!(kept.len(), 2);
+    }
+}
@@ -0,0 +1,522 @@
+//! # Federated Index Adapter
+//!
+//! Fans a query out to several Near backends and merges their answers.
+//!
+//! Members can be anything implementing `Near`: local shards, or adapters
+//! forwarding to remote nodes. Each query runs on every member in parallel
+//! (one thread per member); a member that errors or misses its timeout is
+//! left out of the merge instead of failing the whole query.
+//!
+//! Members may rank with different proximity functions, so their raw scores
+//! aren't comparable. Scores are normalized per member (`ScoreNormalization`)
+//! before merging; normalized scores are similarities (higher is better).
+//!
+//! ```text
+//!              ┌── member "local"   (5 ms) ──┐
+//! query ──fan──┼── member "shard-2" (8 ms) ──┼──normalize──merge──▶ top k
+//!              └── member "remote"  (timeout)┘
+//! ```
+//!
+//! A member that times out keeps running on its thread; its late answer is
+//! discarded.
+//!
+//! On `wasm32-unknown-unknown`, which has no threads, members are queried
+//! one after another on the calling thread and timeouts aren't enforced.
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::sync::mpsc;
+use std::sync::{Arc, PoisonError, RwLock};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::thread;
+use std::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
+
+use super::top_k::TopK;
+use crate::core::proximity::ScoreOrder;
+use crate::core::{Id, Point};
+use crate::ports::{IndexMemory, Near, NearError, NearResult, SearchResult};
+
+/// How member scores are made comparable before merging
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScoreNormalization {
+    /// Keep raw scores; every member must use the same proximity
+    Raw,
+
+    /// Map each score to a similarity: distances become `1 / (1 + d)`
+    #[default]
+    Similarity,
+
+    /// Rescale each member's results to [0, 1] (best = 1, worst = 0)
+    MinMax,
+}
+
+/// A backend taking part in federated queries
+#[derive(Clone)]
+pub struct FederatedMember {
+    name: String,
+    index: Arc<RwLock<dyn Near>>,
+    order: ScoreOrder,
+    timeout: Option<Duration>,
+}
+
+impl FederatedMember {
+    /// Create a member ranking by `order`
+    pub fn new(name: impl Into<String>, index: Arc<RwLock<dyn Near>>, order: ScoreOrder) -> Self {
+        Self {
+            name: name.into(),
+            index,
+            order,
+            timeout: None,
+        }
+    }
+
+    /// Give up on this member after `timeout` (default: the federation's)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn order(&self) -> ScoreOrder {
+        self.order
+    }
+}
+
+/// Why a member was left out of a query
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemberFailure {
+    /// The member returned an error
+    Error(NearError),
+
+    /// The member didn't answer in time
+    TimedOut,
+}
+
+/// Merged results of a federated query, with the members that failed
+#[derive(Debug, Clone, PartialEq)]
+pub struct FederatedResults {
+    /// Merged results, most relevant first
+    pub results: Vec<SearchResult>,
+
+    /// Members left out, by name
+    pub failures: Vec<(String, MemberFailure)>,
+}
+
+/// A member's results, by member position
+type Answer = (usize, Vec<SearchResult>);
+
+/// Near adapter querying several backends as one
+///
+/// `add` goes to the primary member (the first, unless changed with
+/// `with_primary`); `remove` goes to every member holding the point.
+pub struct FederatedNear {
+    members: Vec<FederatedMember>,
+    primary: usize,
+    normalization: ScoreNormalization,
+    timeout: Option<Duration>,
+}
+
+impl FederatedNear {
+    pub fn new(members: Vec<FederatedMember>) -> Self {
+        Self {
+            members,
+            primary: 0,
+            normalization: ScoreNormalization::default(),
+            timeout: None,
+        }
+    }
+
+    /// Set how member scores are made comparable
+    pub fn with_normalization(mut self, normalization: ScoreNormalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Timeout for members without their own
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send `add` to the member at `index`
+    pub fn with_primary(mut self, index: usize) -> Self {
+        self.primary = index;
+        self
+    }
+
+    pub fn members(&self) -> &[FederatedMember] {
+        &self.members
+    }
+
+    /// How merged scores rank
+    ///
+    /// Raw scores keep the first member's order; normalized scores are
+    /// similarities.
+    pub fn order(&self) -> ScoreOrder {
+        match self.normalization {
+            ScoreNormalization::Raw => self
+                .members
+                .first()
+                .map_or(ScoreOrder::HigherIsBetter, |m| m.order),
+            _ => ScoreOrder::HigherIsBetter,
+        }
+    }
+
+    /// Find the k nearest points across members, reporting failed members
+    pub fn search(&self, query: &Point, k: usize) -> FederatedResults {
+        let (answers, failures) = self.fan_out(query, move |index, query| index.near(query, k));
+        let mut results = self.merge(answers);
+        results.truncate(k);
+        FederatedResults { results, failures }
+    }
+
+    /// Find all points within `threshold` across members, reporting failed members
+    ///
+    /// The threshold is passed to each member as is, in its raw score scale.
+    pub fn search_within(&self, query: &Point, threshold: f32) -> FederatedResults {
+        let (answers, failures) =
+            self.fan_out(query, move |index, query| index.within(query, threshold));
+        FederatedResults {
+            results: self.merge(answers),
+            failures,
+        }
+    }
+
+    /// Run `op` on every member in parallel, honoring timeouts
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn fan_out<F>(&self, query: &Point, op: F) -> (Vec<Answer>, Vec<(String, MemberFailure)>)
+    where
+        F: Fn(&dyn Near, &Point) -> NearResult<Vec<SearchResult>> + Send + Sync + Copy + 'static,
+    {
+        let start = Instant::now();
+        let (tx, rx) = mpsc::channel();
+        for (i, member) in self.members.iter().enumerate() {
+            let index = Arc::clone(&member.index);
+            let query = query.clone();
+            let tx = tx.clone();
+            thread::spawn(move || {
+                let result = {
+                    let index = index.read().unwrap_or_else(PoisonError::into_inner);
+                    op(&*index, &query)
+                };
+                // The receiver is gone if the query already timed out
+                let _ = tx.send((i, result));
+            });
+        }
+        drop(tx);
+
+        let deadlines: Vec<Option<Instant>> = self
+            .members
+            .iter()
+            .map(|m| m.timeout.or(self.timeout).map(|t| start + t))
+            .collect();
+        let mut pending: Vec<bool> = vec![true; self.members.len()];
+        let mut answers = Vec::new();
+        let mut failures = Vec::new();
+
+        while pending.iter().any(|p| *p) {
+            let now = Instant::now();
+            for (i, deadline) in deadlines.iter().enumerate() {
+                if pending[i] && deadline.is_some_and(|d| d <= now) {
+                    pending[i] = false;
+                    failures.push((self.members[i].name.clone(), MemberFailure::TimedOut));
+                }
+            }
+
+            let next_deadline = deadlines
+                .iter()
+                .zip(&pending)
+                .filter(|(_, p)| **p)
+                .filter_map(|(d, _)| *d)
+                .min();
+            let received = match next_deadline {
+                Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(now)).ok(),
+                None if pending.iter().any(|p| *p) => rx.recv().ok(),
+                None => None,
+            };
+
+            match received {
+                Some((i, result)) if pending[i] => {
+                    pending[i] = false;
+                    match result {
+                        Ok(results) => answers.push((i, results)),
+                        Err(e) => failures.push((self.members[i].name.clone(), MemberFailure::Error(e))),
+                    }
+                }
+                Some(_) => {}
+                None if next_deadline.is_none() => break,
+                None => {}
+            }
+        }
+
+        (answers, failures)
+    }
+
+    /// Run `op` on every member in turn (no threads, so no timeouts)
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    fn fan_out<F>(&self, query: &Point, op: F) -> (Vec<Answer>, Vec<(String, MemberFailure)>)
+    where
+        F: Fn(&dyn Near, &Point) -> NearResult<Vec<SearchResult>> + Send + Sync + Copy + 'static,
+    {
+        let mut answers = Vec::new();
+        let mut failures = Vec::new();
+        for (i, member) in self.members.iter().enumerate() {
+            let result = op(&*member.index.read().unwrap_or_else(PoisonError::into_inner), query);
+            match result {
+                Ok(results) => answers.push((i, results)),
+                Err(e) => failures.push((member.name.clone(), MemberFailure::Error(e))),
+            }
+        }
+        (answers, failures)
+    }
+
+    /// Normalize each member's scores and merge, keeping each ID's best score
+    fn merge(&self, answers: Vec<Answer>) -> Vec<SearchResult> {
+        let order = self.order();
+        let mut best: std::collections::HashMap<Id, f32> = std::collections::HashMap::new();
+
+        for (i, results) in answers {
+            let member_order = self.members[i].order;
+            let (lo, hi) = results.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), r| {
+                (lo.min(r.score), hi.max(r.score))
+            });
+
+            for r in results {
+                let score = match self.normalization {
+                    ScoreNormalization::Raw => r.score,
+                    ScoreNormalization::Similarity => match member_order {
+                        ScoreOrder::HigherIsBetter => r.score,
+                        ScoreOrder::LowerIsBetter => 1.0 / (1.0 + r.score),
+                    },
+                    ScoreNormalization::MinMax => {
+                        let unit = if hi > lo { (r.score - lo) / (hi - lo) } else { 1.0 };
+                        match member_order {
+                            ScoreOrder::HigherIsBetter => unit,
+                            ScoreOrder::LowerIsBetter => 1.0 - unit,
+                        }
+                    }
+                };
+                best.entry(r.id)
+                    .and_modify(|s| {
+                        if order.is_better(score, *s) {
+                            *s = score;
+                        }
+                    })
+                    .or_insert(score);
+            }
+        }
+
+        let mut top = TopK::new(best.len(), order);
+        for (id, score) in best {
+            top.push(SearchResult::new(id, score));
+        }
+        top.into_sorted_vec()
+    }
+
+    /// Error for a query every member failed
+    fn all_failed(failures: Vec<(String, MemberFailure)>) -> NearError {
+        match failures.into_iter().next() {
+            Some((_, MemberFailure::Error(e))) => e,
+            Some((name, MemberFailure::TimedOut)) => {
+                NearError::IndexError(format!("federated member '{}' timed out", name).into())
+            }
+            None => NearError::IndexNotReady,
+        }
+    }
+}
+
+impl Near for FederatedNear {
+    /// Merged results of the members that answered
+    ///
+    /// Fails only if no member answered; use `search` to see partial failures.
+    fn near(&self, query: &Point, k: usize) -> NearResult<Vec<SearchResult>> {
+        let FederatedResults { results, failures } = self.search(query, k);
+        if failures.len() == self.members.len() {
+            return Err(Self::all_failed(failures));
+        }
+        Ok(results)
+    }
+
+    fn within(&self, query: &Point, threshold: f32) -> NearResult<Vec<SearchResult>> {
+        let FederatedResults { results, failures } = self.search_within(query, threshold);
+        if failures.len() == self.members.len() {
+            return Err(Self::all_failed(failures));
+        }
+        Ok(results)
+    }
+
+    fn add(&mut self, id: Id, point: &Point) -> NearResult<()> {
+        let member = self.members.get(self.primary).ok_or(NearError::IndexNotReady)?;
+        member.index.write().unwrap_or_else(PoisonError::into_inner).add(id, point)
+    }
+
+    fn remove(&mut self, id: Id) -> NearResult<()> {
+        for member in &self.members {
+            match member.index.write().unwrap_or_else(PoisonError::into_inner).remove(id) {
+                Ok(()) | Err(NearError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    fn rebuild(&mut self) -> NearResult<()> {
+        for member in &self.members {
+            member.index.write().unwrap_or_else(PoisonError::into_inner).rebuild()?;
+        }
+        Ok(())
+    }
+
+    fn is_ready(&self) -> bool {
+        !self.members.is_empty()
+    }
+
+    fn order(&self) -> ScoreOrder {
+        FederatedNear::order(self)
+    }
+
+    /// Total points across members (replicated points count once per member)
+    fn len(&self) -> usize {
+        self.members
+            .iter()
+            .map(|m| m.index.read().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    /// Sum over members (shared members are counted once per membership)
+    fn memory_usage(&self) -> IndexMemory {
+        self.members.iter().fold(IndexMemory::default(), |total, m| {
+            let usage = m.index.read().unwrap_or_else(PoisonError::into_inner).memory_usage();
+            IndexMemory {
+                vectors: total.vectors + usage.vectors,
+                structure: total.structure + usage.structure,
+                metadata: total.metadata + usage.metadata,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::index::FlatIndex;
+
+    /// Member that sleeps before answering, or always fails
+    struct Flaky {
+        delay: Duration,
+        fail: bool,
+    }
+
+    impl Near for Flaky {
+        fn near(&self, _query: &Point, _k: usize) -> NearResult<Vec<SearchResult>> {
+            thread::sleep(self.delay);
+            if self.fail {
+                Err(NearError::IndexError("down".into()))
+            } else {
+                Ok(vec![SearchResult::new(Id::from_bytes([9; 16]), 1.0)])
+            }
+        }
+
+        fn within(&self, query: &Point, _threshold: f32) -> NearResult<Vec<SearchResult>> {
+            self.near(query, 1)
+        }
+
+        fn add(&mut self, _id: Id, _point: &Point) -> NearResult<()> {
+            Ok(())
+        }
+
+        fn remove(&mut self, id: Id) -> NearResult<()> {
+            Err(NearError::NotFound(id))
+        }
+
+        fn rebuild(&mut self) -> NearResult<()> {
+            Ok(())
+        }
+
+        fn is_ready(&self) -> bool {
+            true
+        }
+
+        fn len(&self) -> usize {
+            1
+        }
+    }
+
+    fn flat(order: ScoreOrder, points: &[(u8, [f32; 2])]) -> FederatedMember {
+        let mut index = if order.higher_is_better() {
+            FlatIndex::cosine(2)
+        } else {
+            FlatIndex::euclidean(2)
+        };
+        for (n, dims) in points {
+            index.add(Id::from_bytes([*n; 16]), &Point::new(dims.to_vec())).unwrap();
+        }
+        FederatedMember::new(format!("flat-{}", points[0].0), Arc::new(RwLock::new(index)), order)
+    }
+
+    #[test]
+    fn test_merges_across_proximities() {
+        let cosine = flat(ScoreOrder::HigherIsBetter, &[(1, [1.0, 0.0]), (2, [0.0, 1.0])]);
+        let euclid = flat(ScoreOrder::LowerIsBetter, &[(3, [1.0, 0.1]), (4, [-5.0, 0.0])]);
+        let mut fed = FederatedNear::new(vec![cosine, euclid]);
+
+        let results = fed.near(&Point::new(vec![1.0, 0.0]), 3).unwrap();
+        let ids: Vec<u8> = results.iter().map(|r| r.id.as_bytes()[0]).collect();
+        assert_eq!(ids[0], 1);
+        assert_eq!(ids[1], 3);
+        assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+
+        // Adds go to the primary; removes reach whoever holds the point
+        fed.add(Id::from_bytes([5; 16]), &Point::new(vec![0.5, 0.5])).unwrap();
+        assert_eq!(fed.len(), 5);
+        fed.remove(Id::from_bytes([3; 16])).unwrap();
+        assert_eq!(fed.len(), 4);
+    }
+
+    #[test]
+    fn test_min_max_normalization() {
+        let a = flat(ScoreOrder::LowerIsBetter, &[(1, [0.0, 0.0]), (2, [10.0, 0.0])]);
+        let fed = FederatedNear::new(vec![a]).with_normalization(ScoreNormalization::MinMax);
+
+        let results = fed.near(&Point::new(vec![0.0, 0.0]), 2).unwrap();
+        assert_eq!(results[0].score, 1.0);
+        assert_eq!(results[1].score, 0.0);
+
+        // The default cutoff stops at the first normalized score below it
+        let params = crate::ports::QueryParams::new(2).with_min_score(0.5);
+        assert_eq!(fed.near_with_params(&Point::new(vec![0.0, 0.0]), &params).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tolerates_slow_and_failed_members() {
+        let good = flat(ScoreOrder::HigherIsBetter, &[(1, [1.0, 0.0])]);
+        let slow = FederatedMember::new(
+            "slow",
+            Arc::new(RwLock::new(Flaky { delay: Duration::from_secs(5), fail: false })),
+            ScoreOrder::HigherIsBetter,
+        )
+        .with_timeout(Duration::from_millis(50));
+        let down = FederatedMember::new(
+            "down",
+            Arc::new(RwLock::new(Flaky { delay: Duration::ZERO, fail: true })),
+            ScoreOrder::HigherIsBetter,
+        );
+        let fed = FederatedNear::new(vec![good, slow, down]);
+
+        let started = Instant::now();
+        let outcome = fed.search(&Point::new(vec![1.0, 0.0]), 5);
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        assert_eq!(outcome.results.len(), 1);
+        assert_eq!(outcome.failures.len(), 2);
+        assert!(outcome.failures.contains(&("slow".to_string(), MemberFailure::TimedOut)));
+
+        // With every member failing, the query fails
+        let only_down = FederatedNear::new(vec![fed.members()[2].clone()]);
+        assert!(only_down.near(&Point::new(vec![1.0, 0.0]), 1).is_err());
+    }
+}
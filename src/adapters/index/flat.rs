@@ -10,18 +10,51 @@
 //!
 //! Not good for:
 //! - Large datasets (use HNSW instead)
+//!
+//! Vectors are copied into a contiguous `VectorArena` rather than shared
+//! with storage: the extra copy buys a linear, cache-friendly scan.
+//!
+//! `near` keeps a bounded `TopK` heap instead of sorting every score. For
+//! distances (lower is better), candidates are abandoned part-way through
+//! their dimensions once they can no longer beat the current k-th result.
+//!
+//! Batch, score and heap buffers come from the calling thread's scratch
+//! space, so repeated queries allocate little beyond their results.
+//!
+//! With the `parallel` feature, large scans are split across the rayon
+//! thread pool, each task keeping its own top-k before a final merge.
 
 use std::collections::HashMap;
+use std::ops::{ControlFlow, Range};
 use std::sync::Arc;
 
+use super::arena::VectorArena;
+use super::scratch::{recycle, with_scratch, Scratch};
+use super::top_k::TopK;
 use crate::core::{Id, Point};
-use crate::core::proximity::Proximity;
-use crate::ports::{Near, NearError, NearResult, SearchResult};
+use crate::core::proximity::{Proximity, ScoreOrder};
+use crate::ports::{Deadline, IndexMemory, Near, NearError, NearResult, PartialResults, QueryParams, SearchResult};
+
+/// Rows handed to `Proximity::proximity_batch_slices` per call
+const BATCH_ROWS: usize = 256;
+
+/// Slots scanned between deadline checks
+const DEADLINE_CHECK_SLOTS: usize = 16 * BATCH_ROWS;
+
+/// Slots scored per parallel task (smaller scans stay on the calling thread)
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK_SLOTS: usize = 16 * 1024;
 
 /// Brute force index - searches all points
 pub struct FlatIndex {
-    /// Stored points (ID -> Point)
-    points: HashMap<Id, Point>,
+    /// Vector data, one row per point
+    arena: VectorArena,
+
+    /// ID of the point in each arena slot
+    ids: Vec<Id>,
+
+    /// Slot of each point
+    slots: HashMap<Id, usize>,
 
     /// Expected dimensionality
     dimensionality: usize,
@@ -29,51 +62,192 @@ pub struct FlatIndex {
     /// Proximity function to use
     proximity: Arc<dyn Proximity>,
 
-    /// Whether higher proximity = more similar
-    /// true for cosine/dot product, false for euclidean
-    higher_is_better: bool,
+    /// How proximity scores rank
+    order: ScoreOrder,
 }
 
 impl FlatIndex {
-    /// Create a new flat index
+    /// Create a new flat index with an explicit score order
     ///
     /// `higher_is_better` indicates whether higher proximity scores mean more similar.
     /// - `true` for Cosine, DotProduct
     /// - `false` for Euclidean, Manhattan
+    ///
+    /// Prefer `from_proximity`, which takes the order from the function.
     pub fn new(
         dimensionality: usize,
         proximity: Arc<dyn Proximity>,
         higher_is_better: bool,
     ) -> Self {
         Self {
-            points: HashMap::new(),
+            arena: VectorArena::new(dimensionality),
+            ids: Vec::new(),
+            slots: HashMap::new(),
             dimensionality,
             proximity,
-            higher_is_better,
+            order: ScoreOrder::from_higher_is_better(higher_is_better),
         }
     }
 
+    /// Create a flat index ranking by the proximity function's own order
+    pub fn from_proximity(dimensionality: usize, proximity: Arc<dyn Proximity>) -> Self {
+        let higher_is_better = proximity.order().higher_is_better();
+        Self::new(dimensionality, proximity, higher_is_better)
+    }
+
     /// Create with cosine similarity (higher = better)
     pub fn cosine(dimensionality: usize) -> Self {
         use crate::core::proximity::Cosine;
-        Self::new(dimensionality, Arc::new(Cosine), true)
+        Self::from_proximity(dimensionality, Arc::new(Cosine))
     }
 
     /// Create with euclidean distance (lower = better)
     pub fn euclidean(dimensionality: usize) -> Self {
         use crate::core::proximity::Euclidean;
-        Self::new(dimensionality, Arc::new(Euclidean), false)
+        Self::from_proximity(dimensionality, Arc::new(Euclidean))
+    }
+
+    /// How scores rank
+    pub fn order(&self) -> ScoreOrder {
+        self.order
+    }
+
+    /// Score the points in a range of arena slots, a batch of rows at a time
+    fn scan(
+        &self,
+        query: &Point,
+        slots: Range<usize>,
+        scratch: &mut Scratch,
+        mut visit: impl FnMut(SearchResult),
+    ) {
+        let Scratch { ids, scores, .. } = scratch;
+        let mut rows: Vec<&[f32]> = std::mem::take(&mut scratch.rows);
+        let mut flush = |ids: &mut Vec<Id>, rows: &mut Vec<&[f32]>| {
+            scores.clear();
+            self.proximity.proximity_batch_slices_into(query.dims(), rows, scores);
+            for (id, &score) in ids.drain(..).zip(scores.iter()) {
+                visit(SearchResult::new(id, score));
+            }
+            rows.clear();
+        };
+
+        for (slot, row) in self.arena.iter_slots(slots) {
+            ids.push(self.ids[slot]);
+            rows.push(row);
+            if rows.len() == BATCH_ROWS {
+                flush(ids, &mut rows);
+            }
+        }
+        if !rows.is_empty() {
+            flush(ids, &mut rows);
+        }
+        scratch.rows = recycle(rows);
     }
 
-    /// Sort results by relevance
-    fn sort_results(&self, results: &mut Vec<SearchResult>) {
-        if self.higher_is_better {
-            // Higher score = more relevant, sort descending
-            results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-        } else {
-            // Lower score = more relevant, sort ascending
-            results.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap());
+    /// Offer the points in a range of arena slots to a top-k selection
+    ///
+    /// Distances that can stop early are scored row by row against the
+    /// current k-th result; everything else goes through the batch path.
+    fn select(&self, query: &Point, slots: Range<usize>, top: &mut TopK, scratch: &mut Scratch) {
+        if self.order != ScoreOrder::LowerIsBetter || !self.proximity.can_prune() {
+            self.scan(query, slots, scratch, |result| top.push(result));
+            return;
+        }
+
+        for (slot, row) in self.arena.iter_slots(slots) {
+            let score = match top.bound() {
+                // Distances can stop early once a candidate can't win
+                Some(bound) => {
+                    match self.proximity.distance_within(query.dims(), row, bound) {
+                        Some(score) => score,
+                        None => continue,
+                    }
+                }
+                None => self.proximity.proximity_slices(query.dims(), row),
+            };
+            top.push(SearchResult::new(self.ids[slot], score));
+        }
+    }
+
+    /// `select` over the points the filter accepts that clear `min_score`
+    ///
+    /// A distance cutoff bounds candidates from the first row, so pruning
+    /// starts before the heap is full.
+    fn select_with(
+        &self,
+        query: &Point,
+        slots: Range<usize>,
+        top: &mut TopK,
+        scratch: &mut Scratch,
+        params: &QueryParams,
+    ) {
+        if self.order != ScoreOrder::LowerIsBetter || !self.proximity.can_prune() {
+            self.scan(query, slots, scratch, |result| {
+                if params.accepts(result.id) && params.passes(result.score, self.order) {
+                    top.push(result);
+                }
+            });
+            return;
         }
+
+        for (slot, row) in self.arena.iter_slots(slots) {
+            let id = self.ids[slot];
+            if !params.accepts(id) {
+                continue;
+            }
+            let bound = match (top.bound(), params.min_score) {
+                (Some(bound), Some(cutoff)) => Some(bound.min(cutoff)),
+                (bound, cutoff) => bound.or(cutoff),
+            };
+            let score = match bound {
+                Some(bound) => match self.proximity.distance_within(query.dims(), row, bound) {
+                    Some(score) => score,
+                    None => continue,
+                },
+                None => self.proximity.proximity_slices(query.dims(), row),
+            };
+            if params.passes(score, self.order) {
+                top.push(SearchResult::new(id, score));
+            }
+        }
+    }
+
+    /// Exact top-k over the whole arena
+    fn top_k(&self, query: &Point, k: usize, scratch: &mut Scratch) -> TopK {
+        #[cfg(feature = "parallel")]
+        if self.arena.slots() > PARALLEL_CHUNK_SLOTS {
+            return self.near_parallel(query, k);
+        }
+
+        // Compute proximity to all points, keeping the top k
+        let mut top = TopK::with_buffer(k, self.order, std::mem::take(&mut scratch.ranked));
+        self.select(query, 0..self.arena.slots(), &mut top, scratch);
+        top
+    }
+
+    /// Exact top-k over the whole arena, split across the rayon pool
+    #[cfg(feature = "parallel")]
+    fn near_parallel(&self, query: &Point, k: usize) -> TopK {
+        use rayon::prelude::*;
+
+        let slots = self.arena.slots();
+        (0..slots.div_ceil(PARALLEL_CHUNK_SLOTS))
+            .into_par_iter()
+            .map(|chunk| {
+                let start = chunk * PARALLEL_CHUNK_SLOTS;
+                let mut top = TopK::new(k, self.order);
+                with_scratch(|scratch| {
+                    self.select(query, start..(start + PARALLEL_CHUNK_SLOTS).min(slots), &mut top, scratch);
+                });
+                top
+            })
+            .reduce(
+                || TopK::new(k, self.order),
+                |mut a, b| {
+                    a.merge(b);
+                    a
+                },
+            )
     }
 }
 
@@ -87,23 +261,86 @@ impl Near for FlatIndex {
             });
         }
 
-        // Compute proximity to all points
-        let mut results: Vec<SearchResult> = self
-            .points
-            .iter()
-            .map(|(id, point)| {
-                let score = self.proximity.proximity(query, point);
-                SearchResult::new(*id, score)
-            })
-            .collect();
+        Ok(with_scratch(|scratch| {
+            let top = self.top_k(query, k, scratch);
+            let mut results = Vec::with_capacity(top.len());
+            scratch.ranked = top.drain_sorted(&mut |result| {
+                results.push(result);
+                ControlFlow::Continue(())
+            });
+            results
+        }))
+    }
 
-        // Sort by relevance
-        self.sort_results(&mut results);
+    fn near_visit(
+        &self,
+        query: &Point,
+        k: usize,
+        visitor: &mut dyn FnMut(SearchResult) -> ControlFlow<()>,
+    ) -> NearResult<()> {
+        if query.dimensionality() != self.dimensionality {
+            return Err(NearError::DimensionalityMismatch {
+                expected: self.dimensionality,
+                got: query.dimensionality(),
+            });
+        }
 
-        // Take top k
-        results.truncate(k);
+        with_scratch(|scratch| {
+            let top = self.top_k(query, k, scratch);
+            scratch.ranked = top.drain_sorted(visitor);
+        });
+        Ok(())
+    }
 
-        Ok(results)
+    fn near_with_deadline(
+        &self,
+        query: &Point,
+        k: usize,
+        deadline: &Deadline,
+    ) -> NearResult<PartialResults> {
+        if query.dimensionality() != self.dimensionality {
+            return Err(NearError::DimensionalityMismatch {
+                expected: self.dimensionality,
+                got: query.dimensionality(),
+            });
+        }
+
+        // Scan in slices, keeping the best results seen if time runs out
+        let slots = self.arena.slots();
+        let mut top = TopK::new(k, self.order);
+        let mut start = 0;
+        let complete = with_scratch(|scratch| {
+            while start < slots {
+                if deadline.is_expired() {
+                    return false;
+                }
+                let end = (start + DEADLINE_CHECK_SLOTS).min(slots);
+                self.select(query, start..end, &mut top, scratch);
+                start = end;
+            }
+            true
+        });
+
+        Ok(PartialResults { results: top.into_sorted_vec(), complete })
+    }
+
+    fn near_with_params(&self, query: &Point, params: &QueryParams) -> NearResult<Vec<SearchResult>> {
+        if params.filter.is_none() && params.min_score.is_none() {
+            return self.near(query, params.k);
+        }
+        if query.dimensionality() != self.dimensionality {
+            return Err(NearError::DimensionalityMismatch {
+                expected: self.dimensionality,
+                got: query.dimensionality(),
+            });
+        }
+
+        // Exact, so filtering during the scan loses nothing
+        let mut top = TopK::new(params.k, self.order);
+        with_scratch(|scratch| {
+            self.select_with(query, 0..self.arena.slots(), &mut top, scratch, params);
+        });
+        Ok(top.into_sorted_vec())
     }
 
     fn within(&self, query: &Point, threshold: f32) -> NearResult<Vec<SearchResult>> {
@@ -116,26 +353,17 @@ impl Near for FlatIndex {
         }
 
         // Find all points within threshold
-        let mut results: Vec<SearchResult> = self
-            .points
-            .iter()
-            .filter_map(|(id, point)| {
-                let score = self.proximity.proximity(query, point);
-                let within = if self.higher_is_better {
-                    score >= threshold
-                } else {
-                    score <= threshold
-                };
-                if within {
-                    Some(SearchResult::new(*id, score))
-                } else {
-                    None
+        let mut results = Vec::new();
+        with_scratch(|scratch| {
+            self.scan(query, 0..self.arena.slots(), scratch, |r| {
+                if self.order.passes(r.score, threshold) {
+                    results.push(r);
                 }
-            })
-            .collect();
+            });
+        });
 
-        // Sort by relevance
-        self.sort_results(&mut results);
+        // Sort by relevance, ties by ID
+        SearchResult::sort(&mut results, self.order);
 
         Ok(results)
     }
@@ -148,12 +376,25 @@ impl Near for FlatIndex {
             });
         }
 
-        self.points.insert(id, point.clone());
+        match self.slots.get(&id) {
+            Some(&slot) => self.arena.set(slot, point.dims()),
+            None => {
+                let slot = self.arena.insert(point.dims());
+                if slot == self.ids.len() {
+                    self.ids.push(id);
+                } else {
+                    self.ids[slot] = id;
+                }
+                self.slots.insert(id, slot);
+            }
+        }
         Ok(())
     }
 
     fn remove(&mut self, id: Id) -> NearResult<()> {
-        self.points.remove(&id);
+        if let Some(slot) = self.slots.remove(&id) {
+            self.arena.remove(slot);
+        }
         Ok(())
     }
 
@@ -166,14 +407,28 @@ impl Near for FlatIndex {
         true // Always ready
     }
 
+    fn order(&self) -> ScoreOrder {
+        self.order
+    }
+
     fn len(&self) -> usize {
-        self.points.len()
+        self.arena.len()
+    }
+
+    fn memory_usage(&self) -> IndexMemory {
+        IndexMemory {
+            vectors: self.arena.size_bytes(),
+            structure: self.ids.capacity() * std::mem::size_of::<Id>()
+                + self.slots.capacity() * (std::mem::size_of::<(Id, usize)>() + 1),
+            metadata: 0,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::proximity::Euclidean;
 
     fn setup_index() -> FlatIndex {
         let mut index = FlatIndex::cosine(3);
@@ -193,6 +448,56 @@ mod tests {
         index
     }
 
+    #[test]
+    fn test_flat_min_score() {
+        use crate::ports::QueryParams;
+
+        // Similarities: cut off on the batch path
+        let index = setup_index();
+        let query = Point::new(vec![1.0, 0.0, 0.0]);
+        let results = index.near_with_params(&query, &QueryParams::new(4).with_min_score(0.5)).unwrap();
+        let ids: Vec<Id> = results.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![Id::from_bytes([1; 16]), Id::from_bytes([4; 16])]);
+
+        // Distances: the cutoff prunes rows before the heap fills
+        let mut index = FlatIndex::euclidean(1);
+        for i in 0..10u8 {
+            index.add(Id::from_bytes([i; 16]), &Point::new(vec![i as f32])).unwrap();
+        }
+        let params = QueryParams::new(5)
+            .with_min_score(3.0)
+            .with_filter(|id| id != Id::from_bytes([1; 16]));
+        let results = index.near_with_params(&Point::new(vec![0.0]), &params).unwrap();
+        let scores: Vec<f32> = results.iter().map(|r| r.score).collect();
+        assert_eq!(scores, vec![0.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_flat_near_with_deadline() {
+        use crate::ports::{CancellationToken, Deadline};
+        use std::time::Duration;
+
+        let mut index = FlatIndex::euclidean(1);
+        for i in 0..(3 * DEADLINE_CHECK_SLOTS) {
+            index.add(Id::from_bytes((i as u128).to_le_bytes()), &Point::new(vec![i as f32])).unwrap();
+        }
+        let query = Point::new(vec![0.0]);
+
+        let done = index.near_with_deadline(&query, 2, &Deadline::none()).unwrap();
+        assert!(done.complete);
+        assert_eq!(done.results, index.near(&query, 2).unwrap());
+
+        // An already-expired budget scans nothing
+        let expired = index.near_with_deadline(&query, 2, &Deadline::after(Duration::ZERO)).unwrap();
+        assert!(!expired.complete);
+        assert!(expired.results.is_empty());
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let cancelled = Deadline::none().with_token(token);
+        assert!(!index.near_with_deadline(&query, 2, &cancelled).unwrap().complete);
+    }
+
     #[test]
     fn test_flat_index_near() {
         let index = setup_index();
@@ -275,4 +580,148 @@ mod tests {
         let index = FlatIndex::cosine(3);
         assert!(index.is_ready());
     }
+
+    #[test]
+    fn test_flat_remove_and_reuse_slot() {
+        let mut index = setup_index();
+        index.remove(Id::from_bytes([1; 16])).unwrap();
+        assert_eq!(index.len(), 3);
+
+        // Re-adding an existing ID overwrites in place
+        index.add(Id::from_bytes([2; 16]), &Point::new(vec![1.0, 0.0, 0.0])).unwrap();
+        index.add(Id::from_bytes([5; 16]), &Point::new(vec![0.0, 0.0, -1.0])).unwrap();
+        assert_eq!(index.len(), 4);
+
+        let results = index.near(&Point::new(vec![1.0, 0.0, 0.0]), 4).unwrap();
+        assert_eq!(results[0].id, Id::from_bytes([2; 16]));
+        assert!(results.iter().all(|r| r.id != Id::from_bytes([1; 16])));
+
+        let results = index.near(&Point::new(vec![0.0, 0.0, -1.0]), 1).unwrap();
+        assert_eq!(results[0].id, Id::from_bytes([5; 16]));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_flat_parallel_matches_sequential() {
+        let mut index = FlatIndex::euclidean(2);
+        let n = PARALLEL_CHUNK_SLOTS * 3 + 17;
+        for i in 0..n {
+            let mut bytes = [0u8; 16];
+            bytes[..8].copy_from_slice(&(i as u64).to_be_bytes());
+            index.add(Id::from_bytes(bytes), &Point::new(vec![i as f32, 0.0])).unwrap();
+        }
+
+        let query = Point::new(vec![40_000.3, 0.0]);
+        let parallel = index.near(&query, 5).unwrap();
+        let mut top = TopK::new(5, ScoreOrder::LowerIsBetter);
+        with_scratch(|scratch| index.select(&query, 0..n, &mut top, scratch));
+        let sequential = top.into_sorted_vec();
+
+        assert_eq!(parallel, sequential);
+        assert!((parallel[0].score - 0.3).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_flat_pruned_euclidean_matches_sort() {
+        let mut index = FlatIndex::euclidean(40);
+        let mut all = Vec::new();
+        for i in 0..200u32 {
+            let dims: Vec<f32> = (0..40).map(|d| ((i * 31 + d * 7) % 17) as f32).collect();
+            let id = Id::from_bytes([(i % 256) as u8; 16]);
+            index.add(id, &Point::new(dims.clone())).unwrap();
+            all.push(dims);
+        }
+
+        let query = Point::new(vec![8.0; 40]);
+        let results = index.near(&query, 10).unwrap();
+
+        let mut expected: Vec<f32> = all
+            .iter()
+            .map(|dims| Euclidean.proximity_slices(query.dims(), dims))
+            .collect();
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let scores: Vec<f32> = results.iter().map(|r| r.score).collect();
+        assert_eq!(scores, expected[..10]);
+    }
+
+    #[test]
+    fn test_flat_near_visit() {
+        let index = setup_index();
+        let query = Point::new(vec![1.0, 0.0, 0.0]);
+
+        let mut visited = Vec::new();
+        index
+            .near_visit(&query, 3, &mut |r| {
+                visited.push(r);
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(visited, index.near(&query, 3).unwrap());
+
+        // Breaking stops after the first result
+        let mut count = 0;
+        index
+            .near_visit(&query, 3, &mut |_| {
+                count += 1;
+                ControlFlow::Break(())
+            })
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_flat_reuses_scratch() {
+        use super::super::scratch::scratch_bytes;
+
+        let mut index = FlatIndex::cosine(2);
+        for i in 0..(BATCH_ROWS + 10) {
+            index.add(Id::from_bytes((i as u128).to_le_bytes()), &Point::new(vec![1.0, i as f32])).unwrap();
+        }
+        let query = Point::new(vec![1.0, 3.0]);
+
+        let first = index.near(&query, 5).unwrap();
+        let kept = scratch_bytes();
+        assert!(kept > 0);
+
+        // Same answers from the recycled buffers, without growing them
+        assert_eq!(index.near(&query, 5).unwrap(), first);
+        assert!(!index.within(&query, 0.99).unwrap().is_empty());
+        assert_eq!(scratch_bytes(), kept);
+    }
+
+    #[test]
+    fn test_flat_uses_batch_path() {
+        use crate::core::proximity::Cosine;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Cosine that counts batch calls
+        struct Counting(AtomicUsize);
+
+        impl Proximity for Counting {
+            fn proximity(&self, a: &Point, b: &Point) -> f32 {
+                Cosine.proximity(a, b)
+            }
+
+            fn proximity_batch_slices(&self, query: &[f32], candidates: &[&[f32]]) -> Vec<f32> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Cosine.proximity_batch_slices(query, candidates)
+            }
+
+            fn name(&self) -> &'static str {
+                "counting"
+            }
+        }
+
+        let proximity = Arc::new(Counting(AtomicUsize::new(0)));
+        let mut index = FlatIndex::from_proximity(2, proximity.clone());
+        for i in 0..(BATCH_ROWS + 1) {
+            let mut bytes = [0u8; 16];
+            bytes[..8].copy_from_slice(&(i as u64).to_be_bytes());
+            index.add(Id::from_bytes(bytes), &Point::new(vec![1.0, i as f32])).unwrap();
+        }
+
+        let results = index.near(&Point::new(vec![1.0, 0.0]), 1).unwrap();
+        assert_eq!(results[0].score, 1.0);
+        assert_eq!(proximity.0.load(Ordering::Relaxed), 2);
+    }
 }
@@ -11,31 +11,139 @@
 
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::core::{Id, Point};
-use crate::core::proximity::Proximity;
-use crate::core::merge::Merge;
-use crate::ports::{Near, NearError, NearResult, SearchResult};
+use crate::core::{clock, Blob, Fact, Id, MetaValue, Metadata, Point, Provenance, SeededIds};
+use crate::core::clustering::{Clustering, KMeans};
+use crate::core::gen::Rng;
+use crate::core::projection::{Projection, Projector};
+use crate::core::proximity::{Proximity, ScoreOrder};
+use crate::core::merge::{GeometricMedian, Merge, OnlineMerge};
+use crate::adapters::attention::{Role, Roles};
+use crate::ports::{Deadline, IndexMemory, Near, NearError, NearResult, PartialResults, ProjectedResult, QueryParams, ReturnFields, SearchResult, ContextChunk};
 
 use super::consolidation::{
     Consolidate, ConsolidationConfig, ConsolidationPhase, ConsolidationState,
     ConsolidationMetrics, ConsolidationProgress, ConsolidationTickResult,
     compute_exact_centroid, centroid_drift,
 };
+use super::compression::{ChunkCluster, ClusterChunk, CompressionConfig, MemorySummary};
+use super::scratch::{with_scratch, Scratch};
+use super::cow::CowMap;
+use super::dedup::collapse_duplicates;
 
 /// Centroid computation method
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum CentroidMethod {
     /// Euclidean mean + renormalize (fast but geometrically imprecise)
+    #[default]
     Euclidean,
     /// Fréchet mean on hypersphere (manifold-aware, more accurate)
     Frechet,
+    /// Geometric median of children (robust to outlier chunks)
+    ///
+    /// Inserts take the median of the container's child centroids, which is
+    /// exact for documents and approximate above them; consolidation
+    /// recomputes it from all descendant points.
+    GeometricMedian,
 }
 
-impl Default for CentroidMethod {
-    fn default() -> Self {
-        CentroidMethod::Euclidean
+/// Metadata key holding a session or document name
+pub const NAME_KEY: &str = "name";
+
+/// Default metadata key marking chunks a newer memory may supersede
+pub const SUPERSEDABLE_KEY: &str = "supersedable";
+
+/// Nearest neighbours each chunk links to in `importance_report`'s graph
+const IMPORTANCE_NEIGHBORS: usize = 8;
+
+/// PageRank damping and iterations for `importance_report`
+const IMPORTANCE_DAMPING: f32 = 0.85;
+const IMPORTANCE_ITERATIONS: usize = 30;
+
+/// Mixed into `HatConfig::seed` for container IDs ("HAT_CONT")
+const CONTAINER_SEED_SALT: u64 = 0x4841_545f_434f_4e54;
+
+/// Merge functions overriding `centroid_method` at specific levels
+///
+/// An override recomputes the container's centroid by merging its direct
+/// children on every insert: chunk points for documents, document centroids
+/// for sessions. That costs O(children × d) instead of O(d), bounded by
+/// `max_children`. The merge must accept any number of points, so
+/// fixed-length strategies like `WeightedMean::new` don't fit here.
+#[derive(Clone, Default)]
+pub struct LevelMerges {
+    /// Chunk → document aggregation
+    pub document: Option<Arc<dyn Merge>>,
+
+    /// Document → session aggregation
+    pub session: Option<Arc<dyn Merge>>,
+}
+
+impl LevelMerges {
+    /// Override for a container level, if any
+    pub fn for_level(&self, level: ContainerLevel) -> Option<&Arc<dyn Merge>> {
+        match level {
+            ContainerLevel::Document => self.document.as_ref(),
+            ContainerLevel::Session => self.session.as_ref(),
+            ContainerLevel::Global | ContainerLevel::Chunk => None,
+        }
+    }
+}
+
+impl std::fmt::Debug for LevelMerges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LevelMerges")
+            .field("document", &self.document.as_ref().map(|m| m.name()))
+            .field("session", &self.session.as_ref().map(|m| m.name()))
+            .finish()
+    }
+}
+
+/// Automatic session and document boundaries
+///
+/// Each new chunk is compared with the previous one; a large semantic shift
+/// or a long pause starts a new document or session, so callers don't have
+/// to call `new_session()`/`new_document()` themselves. Similarity goes
+/// through `Proximity::similarity`, so with a distance function the
+/// thresholds apply to `1 / (1 + d)`. All checks are off by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SegmentationConfig {
+    /// Start a new document when similarity to the previous chunk drops below this
+    pub document_similarity: Option<f32>,
+
+    /// Start a new session when similarity to the previous chunk drops below this
+    pub session_similarity: Option<f32>,
+
+    /// Start a new document when more than this many ms passed since the previous chunk
+    pub document_gap_ms: Option<u64>,
+
+    /// Start a new session when more than this many ms passed since the previous chunk
+    pub session_gap_ms: Option<u64>,
+}
+
+impl SegmentationConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_document_similarity(mut self, threshold: f32) -> Self {
+        self.document_similarity = Some(threshold);
+        self
+    }
+
+    pub fn with_session_similarity(mut self, threshold: f32) -> Self {
+        self.session_similarity = Some(threshold);
+        self
+    }
+
+    pub fn with_document_gap_ms(mut self, gap_ms: u64) -> Self {
+        self.document_gap_ms = Some(gap_ms);
+        self
+    }
+
+    pub fn with_session_gap_ms(mut self, gap_ms: u64) -> Self {
+        self.session_gap_ms = Some(gap_ms);
+        self
     }
 }
 
@@ -67,6 +175,9 @@ pub struct HatConfig {
     /// Number of iterations for Fréchet mean computation
     pub frechet_iterations: usize,
 
+    /// Per-level merge functions (default: none, use `centroid_method`)
+    pub level_merges: LevelMerges,
+
     /// Enable subspace-aware routing (default: false for backward compatibility)
     pub subspace_enabled: bool,
 
@@ -78,6 +189,18 @@ pub struct HatConfig {
 
     /// Configuration for learnable routing
     pub learnable_routing_config: super::learnable_routing::LearnableRoutingConfig,
+
+    /// Worker threads for batched queries (`near_batch`)
+    pub n_threads: usize,
+
+    /// Automatic session/document boundaries (default: off)
+    pub segmentation: SegmentationConfig,
+
+    /// Seed for reproducible container IDs (None = wall-clock IDs)
+    ///
+    /// Chunk IDs come from the caller. Temporal scoring and time-gap
+    /// segmentation still read the clock; leave them off for exact replays.
+    pub seed: Option<u64>,
 }
 
 impl Default for HatConfig {
@@ -91,10 +214,14 @@ impl Default for HatConfig {
             propagation_threshold: 0.0, // Default: always propagate (backward compatible)
             centroid_method: CentroidMethod::Euclidean, // Default: backward compatible
             frechet_iterations: 5, // Enough for convergence on hypersphere
+            level_merges: LevelMerges::default(),
             subspace_enabled: false, // Default: disabled for backward compatibility
             subspace_config: super::subspace::SubspaceConfig::default(),
             learnable_routing_enabled: false, // Default: disabled for backward compatibility
             learnable_routing_config: super::learnable_routing::LearnableRoutingConfig::default(),
+            n_threads: 1, // Default: queries run on the caller's thread
+            segmentation: SegmentationConfig::default(),
+            seed: None,
         }
     }
 }
@@ -129,6 +256,18 @@ impl HatConfig {
         self
     }
 
+    /// Aggregate chunks into document centroids with `merge`
+    pub fn with_document_merge(mut self, merge: Arc<dyn Merge>) -> Self {
+        self.level_merges.document = Some(merge);
+        self
+    }
+
+    /// Aggregate document centroids into session centroids with `merge`
+    pub fn with_session_merge(mut self, merge: Arc<dyn Merge>) -> Self {
+        self.level_merges.session = Some(merge);
+        self
+    }
+
     pub fn with_subspace_enabled(mut self, enabled: bool) -> Self {
         self.subspace_enabled = enabled;
         self
@@ -150,6 +289,26 @@ impl HatConfig {
         self.learnable_routing_enabled = true;  // Automatically enable when config is provided
         self
     }
+
+    pub fn with_n_threads(mut self, n_threads: usize) -> Self {
+        self.n_threads = n_threads.max(1);
+        self
+    }
+
+    pub fn with_segmentation(mut self, segmentation: SegmentationConfig) -> Self {
+        self.segmentation = segmentation;
+        self
+    }
+
+    /// Derive container IDs from `seed` instead of the clock
+    ///
+    /// The seed is salted first, so container IDs never come from the same
+    /// sequence as point IDs seeded with the same value (e.g.
+    /// `ArmsConfig::with_deterministic`).
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
 }
 
 /// Level in the hierarchy
@@ -166,6 +325,7 @@ pub enum ContainerLevel {
 }
 
 impl ContainerLevel {
+    #[allow(dead_code)]
     fn child_level(&self) -> Option<ContainerLevel> {
         match self {
             ContainerLevel::Global => Some(ContainerLevel::Session),
@@ -185,6 +345,22 @@ impl ContainerLevel {
     }
 }
 
+/// A container's place in the hierarchy, for browsing
+#[derive(Debug, Clone)]
+pub struct ContainerInfo {
+    pub id: Id,
+    pub level: ContainerLevel,
+
+    /// Creation timestamp (ms since epoch)
+    pub timestamp: u64,
+
+    /// Child containers, in insertion order (empty for chunks)
+    pub children: Vec<Id>,
+
+    /// Number of chunks under this container (1 for a chunk)
+    pub chunk_count: usize,
+}
+
 /// Summary of a session for coarse queries (multi-resolution API)
 #[derive(Debug, Clone)]
 pub struct SessionSummary {
@@ -199,6 +375,9 @@ pub struct SessionSummary {
 
     /// Session timestamp
     pub timestamp: u64,
+
+    /// Summary text set with `set_session_summary`
+    pub summary: Option<String>,
 }
 
 /// Summary of a document for coarse queries
@@ -215,6 +394,190 @@ pub struct DocumentSummary {
 
     /// Document timestamp
     pub timestamp: u64,
+
+    /// Summary text set with `set_document_summary`
+    pub summary: Option<String>,
+}
+
+/// A caller-written summary of a session or document
+///
+/// Its vector routes queries alongside the container's merged centroid, so
+/// a session can be found by what it was about, not only by the average of
+/// what was said.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeSummary {
+    pub text: String,
+
+    /// Embedding of the summary (same space as the chunks)
+    pub vector: Point,
+}
+
+/// A document found through its best-matching chunk
+#[derive(Debug, Clone)]
+pub struct ParentDocument {
+    /// Document ID
+    pub id: Id,
+
+    /// Score of the best-matching chunk
+    pub score: f32,
+
+    /// The chunk that matched best
+    pub chunk: Id,
+
+    /// The document's own payload (None if it has none)
+    pub blob: Option<Blob>,
+}
+
+/// A chunk ranked by how central it is to its session
+#[derive(Debug, Clone, PartialEq)]
+pub struct CentralChunk {
+    pub id: Id,
+
+    /// PageRank in the session's proximity graph (sums to 1 over the
+    /// session; higher = more central)
+    pub centrality: f32,
+
+    /// Creation timestamp (ms since epoch)
+    pub timestamp: u64,
+}
+
+/// How `contradictions` looks for stored chunks a new one may supersede
+///
+/// Only chunks whose metadata carries `marker` are candidates - `true`, or
+/// a slot value such as `"residence"`. When the new chunk's metadata gives
+/// a slot value too, only candidates in the same slot match, so "moved to
+/// Berlin" meets "lives in Paris" but not "likes Paris in spring".
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContradictionCheck {
+    /// Score a candidate must reach (a similarity, or for distances the
+    /// most it may be)
+    pub threshold: f32,
+
+    /// Metadata key marking candidates
+    pub marker: String,
+
+    /// Most candidates returned
+    pub k: usize,
+}
+
+impl ContradictionCheck {
+    /// Candidates marked with `SUPERSEDABLE_KEY` scoring at least `threshold`
+    pub fn new(threshold: f32) -> Self {
+        Self { threshold, marker: SUPERSEDABLE_KEY.to_string(), k: 10 }
+    }
+
+    pub fn with_marker(mut self, marker: impl Into<String>) -> Self {
+        self.marker = marker.into();
+        self
+    }
+
+    pub fn with_k(mut self, k: usize) -> Self {
+        self.k = k;
+        self
+    }
+
+    /// Whether a stored marker value is a candidate for the new chunk's
+    fn matches(&self, stored: &MetaValue, new: Option<&MetaValue>) -> bool {
+        match (stored, new) {
+            (MetaValue::Null | MetaValue::Bool(false), _) => false,
+            (MetaValue::Bool(true), _) => true,
+            (slot, Some(new @ (MetaValue::Str(_) | MetaValue::Int(_) | MetaValue::Float(_)))) => slot == new,
+            _ => true,
+        }
+    }
+}
+
+/// A stored chunk a new memory may contradict
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contradiction {
+    pub id: Id,
+
+    /// Proximity to the new chunk
+    pub score: f32,
+
+    /// The chunk's marker value
+    pub marker: MetaValue,
+}
+
+/// A point in the session overlay (see `HatIndex::add_ephemeral`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct EphemeralPoint {
+    pub id: Id,
+    pub point: Point,
+    pub payload: Option<Blob>,
+    pub metadata: Option<Metadata>,
+
+    /// When it was added (ms since epoch; set by `add_ephemeral`)
+    pub timestamp: u64,
+}
+
+impl EphemeralPoint {
+    pub fn new(id: Id, point: Point) -> Self {
+        Self { id, point, payload: None, metadata: None, timestamp: 0 }
+    }
+
+    pub fn with_payload(mut self, payload: Blob) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+/// What `near_grouped` groups chunks by
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupBy {
+    /// The session holding the chunk
+    Session,
+    /// The document holding the chunk
+    Document,
+    /// A metadata key, looked up on the chunk, then its document, then its
+    /// session; chunks without it are left out
+    Metadata(String),
+}
+
+/// Value shared by the chunks of a result group
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupKey {
+    /// Session or document ID
+    Container(Id),
+    /// Metadata value
+    Value(MetaValue),
+}
+
+/// Best chunks sharing a group key
+#[derive(Debug, Clone)]
+pub struct ResultGroup {
+    pub key: GroupKey,
+
+    /// Most relevant first
+    pub hits: Vec<SearchResult>,
+}
+
+/// A run of consecutive documents sharing a dominant topic
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopicSpan {
+    /// Cluster label (see `Clustering::clusters`)
+    pub label: usize,
+
+    /// Earliest chunk timestamp (ms since epoch)
+    pub start: u64,
+
+    /// Latest chunk timestamp (ms since epoch)
+    pub end: u64,
+
+    pub documents: usize,
+    pub chunks: usize,
+}
+
+/// Dominant topics of a session in time order
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionTimeline {
+    pub session: Id,
+    pub spans: Vec<TopicSpan>,
 }
 
 /// A container in the HAT hierarchy
@@ -235,30 +598,35 @@ struct Container {
     /// Child container IDs (empty for chunks)
     children: Vec<Id>,
 
-    /// Number of descendant chunks (for weighted centroid updates)
-    descendant_count: usize,
+    /// Container listing this one as a child (None for the root; not
+    /// saved, relinked on load)
+    parent: Option<Id>,
 
-    /// Accumulated sum of all descendant points (for Euclidean centroid)
-    /// Stored as unnormalized to enable incremental updates
-    accumulated_sum: Option<Point>,
+    /// Running mean of all descendant points and their count
+    /// Kept unnormalized so the centroid updates in O(d) per insert
+    running: OnlineMerge,
 
     /// Subspace representation (optional, for non-chunk containers)
     /// Captures variance/spread of points within the container
     subspace: Option<super::subspace::Subspace>,
+
+    /// Conversation role (chunks only; None if untagged)
+    role: Option<Role>,
+
+    /// Previous and next chunk added to the same session (chunks only)
+    prev_turn: Option<Id>,
+    next_turn: Option<Id>,
 }
 
 impl Container {
     fn new(id: Id, level: ContainerLevel, centroid: Point) -> Self {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
-
-        // For chunks, the accumulated sum is the point itself
-        let accumulated_sum = if level == ContainerLevel::Chunk {
-            Some(centroid.clone())
+        let timestamp = clock::now_ms();
+
+        // For chunks, the running mean is the point itself
+        let running = if level == ContainerLevel::Chunk {
+            OnlineMerge::from_points(std::slice::from_ref(&centroid))
         } else {
-            None
+            OnlineMerge::new(centroid.dimensionality())
         };
 
         // Initialize subspace for non-chunk containers
@@ -274,21 +642,99 @@ impl Container {
             centroid,
             timestamp,
             children: Vec::new(),
-            descendant_count: if level == ContainerLevel::Chunk { 1 } else { 0 },
-            accumulated_sum,
+            parent: None,
+            running,
             subspace,
+            role: None,
+            prev_turn: None,
+            next_turn: None,
         }
     }
 
     fn is_leaf(&self) -> bool {
         self.level == ContainerLevel::Chunk
     }
+
+    /// Number of descendant chunks
+    fn descendant_count(&self) -> usize {
+        self.running.count()
+    }
+
+    fn to_serialized(&self) -> super::persistence::SerializedContainer {
+        use super::persistence::{LevelByte, SerializedContainer};
+
+        let level = match self.level {
+            ContainerLevel::Global => LevelByte::Root,
+            ContainerLevel::Session => LevelByte::Session,
+            ContainerLevel::Document => LevelByte::Document,
+            ContainerLevel::Chunk => LevelByte::Chunk,
+        };
+
+        SerializedContainer {
+            id: self.id,
+            level,
+            timestamp: self.timestamp,
+            children: self.children.clone(),
+            descendant_count: self.descendant_count() as u64,
+            centroid: self.centroid.dims().to_vec(),
+            accumulated_sum: (!self.running.is_empty()).then(|| self.running.sum()),
+            role: self.role.map(Role::to_byte),
+            prev_turn: self.prev_turn,
+            next_turn: self.next_turn,
+        }
+    }
+
+    fn from_serialized(
+        sc: super::persistence::SerializedContainer,
+        dimensionality: usize,
+    ) -> Result<Self, super::persistence::PersistError> {
+        use super::persistence::{LevelByte, PersistError};
+
+        let level = match sc.level {
+            LevelByte::Root => ContainerLevel::Global,
+            LevelByte::Session => ContainerLevel::Session,
+            LevelByte::Document => ContainerLevel::Document,
+            LevelByte::Chunk => ContainerLevel::Chunk,
+        };
+
+        // Verify dimension
+        if sc.centroid.len() != dimensionality {
+            return Err(PersistError::DimensionMismatch {
+                expected: dimensionality,
+                found: sc.centroid.len(),
+            });
+        }
+
+        let running = match &sc.accumulated_sum {
+            Some(sum) => OnlineMerge::from_sum(sum, sc.descendant_count as usize),
+            None => OnlineMerge::new(dimensionality),
+        };
+
+        Ok(Self {
+            id: sc.id,
+            level,
+            centroid: Point::new(sc.centroid),
+            timestamp: sc.timestamp,
+            children: sc.children,
+            parent: None,
+            running,
+            subspace: if level != ContainerLevel::Chunk {
+                Some(super::subspace::Subspace::new(dimensionality))
+            } else {
+                None
+            },
+            role: sc.role.and_then(Role::from_byte),
+            prev_turn: sc.prev_turn,
+            next_turn: sc.next_turn,
+        })
+    }
 }
 
 /// Hierarchical Attention Tree Index
 pub struct HatIndex {
-    /// All containers (including root, sessions, documents, chunks)
-    containers: HashMap<Id, Container>,
+    /// All containers (including root, sessions, documents, chunks),
+    /// shared with snapshots until written
+    containers: CowMap<Id, Container>,
 
     /// Root container ID
     root_id: Option<Id>,
@@ -299,17 +745,20 @@ pub struct HatIndex {
     /// Current active document (where new chunks go)
     active_document: Option<Id>,
 
+    /// Most recently added chunk (for automatic segmentation)
+    last_chunk: Option<Id>,
+
     /// Expected dimensionality
     dimensionality: usize,
 
     /// Proximity function
     proximity: Arc<dyn Proximity>,
 
-    /// Merge function (for centroids)
+    /// Merge function (recorded in snapshots)
     merge: Arc<dyn Merge>,
 
-    /// Whether higher proximity = more similar
-    higher_is_better: bool,
+    /// How proximity scores rank
+    order: ScoreOrder,
 
     /// Configuration
     config: HatConfig,
@@ -322,6 +771,28 @@ pub struct HatIndex {
 
     /// Learnable router for adaptive routing weights
     learnable_router: Option<super::learnable_routing::LearnableRouter>,
+
+    /// Payloads attached to chunks
+    payloads: CowMap<Id, Blob>,
+
+    /// Typed metadata attached to chunks
+    metadata: CowMap<Id, Metadata>,
+
+    /// Where chunks came from and how far to trust them
+    provenance: CowMap<Id, Provenance>,
+
+    /// Caller-written summaries of sessions and documents
+    summaries: CowMap<Id, NodeSummary>,
+
+    /// Facts store, keyed by subject and predicate
+    facts: CowMap<(String, String), Fact>,
+
+    /// Session overlay: scratch points searched beside the tree but kept
+    /// out of it (and out of saved files)
+    ephemeral: HashMap<Id, EphemeralPoint>,
+
+    /// Container ID source when seeded (None = wall-clock IDs)
+    ids: Option<SeededIds>,
 }
 
 impl HatIndex {
@@ -329,13 +800,49 @@ impl HatIndex {
     pub fn cosine(dimensionality: usize) -> Self {
         use crate::core::proximity::Cosine;
         use crate::core::merge::Mean;
-        Self::new(
-            dimensionality,
-            Arc::new(Cosine),
-            Arc::new(Mean),
-            true,
-            HatConfig::default(),
-        )
+        Self::from_proximity(dimensionality, Arc::new(Cosine), Arc::new(Mean), HatConfig::default())
+    }
+
+    /// Create a new HAT index with Euclidean distance
+    ///
+    /// For embedding models whose vectors are not normalized, where
+    /// magnitude carries meaning. Scores are distances (lower = closer).
+    pub fn euclidean(dimensionality: usize) -> Self {
+        use crate::core::proximity::Euclidean;
+        use crate::core::merge::Mean;
+        Self::from_proximity(dimensionality, Arc::new(Euclidean), Arc::new(Mean), HatConfig::default())
+    }
+
+    /// Create a new HAT index with dot product similarity
+    ///
+    /// For models trained for maximum inner product search.
+    pub fn dot_product(dimensionality: usize) -> Self {
+        use crate::core::proximity::DotProduct;
+        use crate::core::merge::Mean;
+        Self::from_proximity(dimensionality, Arc::new(DotProduct), Arc::new(Mean), HatConfig::default())
+    }
+
+    /// Create an index for a built-in proximity by name
+    ///
+    /// Accepts "cosine", "euclidean" and "dot_product" (the names reported
+    /// by `Proximity::name`).
+    pub fn with_proximity_name(dimensionality: usize, name: &str) -> Option<Self> {
+        match name {
+            "cosine" => Some(Self::cosine(dimensionality)),
+            "euclidean" => Some(Self::euclidean(dimensionality)),
+            "dot_product" => Some(Self::dot_product(dimensionality)),
+            _ => None,
+        }
+    }
+
+    /// Name of the proximity function in use
+    pub fn proximity_name(&self) -> &'static str {
+        self.proximity.name()
+    }
+
+    /// Dimensionality of stored points
+    pub fn dimensionality(&self) -> usize {
+        self.dimensionality
     }
 
     /// Create with custom config
@@ -351,7 +858,24 @@ impl HatIndex {
         self
     }
 
-    /// Create with custom proximity and merge functions
+    /// Create with custom proximity and merge functions, ranking by the
+    /// proximity function's own `ScoreOrder`
+    pub fn from_proximity(
+        dimensionality: usize,
+        proximity: Arc<dyn Proximity>,
+        merge: Arc<dyn Merge>,
+        config: HatConfig,
+    ) -> Self {
+        let higher_is_better = proximity.order().higher_is_better();
+        Self::new(dimensionality, proximity, merge, higher_is_better, config)
+    }
+
+    /// How scores rank
+    pub fn order(&self) -> ScoreOrder {
+        self.order
+    }
+
+    /// Create with custom proximity and merge functions and an explicit score order
     pub fn new(
         dimensionality: usize,
         proximity: Arc<dyn Proximity>,
@@ -369,30 +893,35 @@ impl HatIndex {
             None
         };
 
+        let ids = config.seed.map(|seed| SeededIds::new(seed ^ CONTAINER_SEED_SALT));
+
         Self {
-            containers: HashMap::new(),
+            containers: CowMap::new(),
             root_id: None,
             active_session: None,
             active_document: None,
+            last_chunk: None,
             dimensionality,
             proximity,
             merge,
-            higher_is_better,
+            order: ScoreOrder::from_higher_is_better(higher_is_better),
             config,
             consolidation_state: None,
             consolidation_points_cache: HashMap::new(),
             learnable_router,
+            payloads: CowMap::new(),
+            metadata: CowMap::new(),
+            provenance: CowMap::new(),
+            summaries: CowMap::new(),
+            facts: CowMap::new(),
+            ephemeral: HashMap::new(),
+            ids,
         }
     }
 
     /// Compute distance (lower = more similar)
     fn distance(&self, a: &Point, b: &Point) -> f32 {
-        let prox = self.proximity.proximity(a, b);
-        if self.higher_is_better {
-            1.0 - prox
-        } else {
-            prox
-        }
+        self.order.to_distance(self.proximity.proximity(a, b))
     }
 
     /// Compute temporal distance (normalized to 0-1)
@@ -405,7 +934,9 @@ impl HatIndex {
     }
 
     /// Combined distance with temporal component, optional subspace, and learnable routing
-    fn combined_distance(&self, query: &Point, query_time: u64, container: &Container) -> f32 {
+    ///
+    /// `w` weighs the temporal component (normally `config.temporal_weight`).
+    fn combined_distance(&self, query: &Point, query_time: u64, w: f32, container: &Container) -> f32 {
         // Compute semantic distance
         let semantic = if self.config.learnable_routing_enabled {
             // Use learnable routing weights
@@ -432,19 +963,81 @@ impl HatIndex {
         } else {
             self.distance(query, &container.centroid)
         };
+        let semantic = self.summary_distance(query, container.id).map_or(semantic, |d| d.min(semantic));
 
         let temporal = self.temporal_distance(query_time, container.timestamp);
 
         // Weighted combination
-        let w = self.config.temporal_weight;
         semantic * (1.0 - w) + temporal * w
     }
 
+    /// Combined distance for several containers at once
+    ///
+    /// Plain proximity scoring goes through `Proximity::proximity_batch`, so
+    /// functions with a batch path are called once per tree level.
+    fn combined_distances(&self, query: &Point, query_time: u64, w: f32, containers: &[&Container]) -> Vec<f32> {
+        if self.config.learnable_routing_enabled || self.config.subspace_enabled {
+            return containers.iter()
+                .map(|c| self.combined_distance(query, query_time, w, c))
+                .collect();
+        }
+
+        let centroids: Vec<&Point> = containers.iter().map(|c| &c.centroid).collect();
+
+        self.proximity.proximity_batch(query, &centroids)
+            .into_iter()
+            .zip(containers)
+            .map(|(prox, c)| {
+                let semantic = self.order.to_distance(prox);
+                let semantic = self.summary_distance(query, c.id).map_or(semantic, |d| d.min(semantic));
+                let temporal = self.temporal_distance(query_time, c.timestamp);
+                semantic * (1.0 - w) + temporal * w
+            })
+            .collect()
+    }
+
+    /// Distance from the query to a container's summary, if it has one
+    ///
+    /// A summary is a second way into its container: routing takes the
+    /// closer of the summary and the merged centroid.
+    fn summary_distance(&self, query: &Point, id: Id) -> Option<f32> {
+        if self.summaries.len() == 0 {
+            return None;
+        }
+        self.summaries.get(&id).map(|summary| self.distance(query, &summary.vector))
+    }
+
+    /// ID for a new container: seeded when configured, else wall-clock
+    fn fresh_id(&mut self) -> Id {
+        match &mut self.ids {
+            Some(ids) => loop {
+                // Skip IDs already taken (e.g., after loading a saved index)
+                let id = ids.next_id();
+                if !self.containers.contains_key(&id) {
+                    return id;
+                }
+            },
+            None => Id::now(),
+        }
+    }
+
+    /// Point the children of each of `parents` back at it
+    fn relink(&mut self, parents: impl IntoIterator<Item = Id>) {
+        for parent in parents {
+            let children = self.containers.get(&parent).map(|c| c.children.clone()).unwrap_or_default();
+            for child in children {
+                if let Some(child) = self.containers.get_mut(&child) {
+                    child.parent = Some(parent);
+                }
+            }
+        }
+    }
+
     /// Ensure root exists
     fn ensure_root(&mut self) {
         if self.root_id.is_none() {
             let root = Container::new(
-                Id::now(),
+                self.fresh_id(),
                 ContainerLevel::Global,
                 Point::origin(self.dimensionality),
             );
@@ -459,11 +1052,12 @@ impl HatIndex {
         self.ensure_root();
 
         if self.active_session.is_none() {
-            let session = Container::new(
-                Id::now(),
+            let mut session = Container::new(
+                self.fresh_id(),
                 ContainerLevel::Session,
                 Point::origin(self.dimensionality),
             );
+            session.parent = self.root_id;
             let session_id = session.id;
             self.containers.insert(session_id, session);
 
@@ -483,11 +1077,12 @@ impl HatIndex {
         self.ensure_session();
 
         if self.active_document.is_none() {
-            let document = Container::new(
-                Id::now(),
+            let mut document = Container::new(
+                self.fresh_id(),
                 ContainerLevel::Document,
                 Point::origin(self.dimensionality),
             );
+            document.parent = self.active_session;
             let doc_id = document.id;
             self.containers.insert(doc_id, document);
 
@@ -513,35 +1108,655 @@ impl HatIndex {
         self.active_document = None;
     }
 
-    /// Compute Fréchet mean on the unit hypersphere using iterative algorithm
-    /// This finds the point that minimizes sum of squared geodesic distances
-    fn compute_frechet_mean(&self, points: &[Point], initial: &Point) -> Point {
-        let mut mean = initial.clone();
-        let iterations = self.config.frechet_iterations;
+    /// Start a new session with a name, returning its ID
+    ///
+    /// The name is stored as the session's `"name"` metadata, so it can be
+    /// found with `find_session` and survives persistence.
+    pub fn new_session_named(&mut self, name: impl Into<String>) -> Id {
+        self.new_session();
+        self.ensure_session();
+        let id = self.active_session.expect("session just created");
+        self.metadata.get_or_default(id).insert(NAME_KEY.to_string(), name.into().into());
+        id
+    }
 
-        for _ in 0..iterations {
-            // Compute weighted tangent vectors (log map)
-            let mut tangent_sum = vec![0.0f32; mean.dimensionality()];
+    /// Start a new document with a name in the current session, returning its ID
+    pub fn new_document_named(&mut self, name: impl Into<String>) -> Id {
+        self.new_document();
+        self.ensure_document();
+        let id = self.active_document.expect("document just created");
+        self.metadata.get_or_default(id).insert(NAME_KEY.to_string(), name.into().into());
+        id
+    }
 
-            for point in points {
-                // Log map: project point onto tangent space at mean
-                // For unit sphere: log_p(q) = θ * (q - (q·p)p) / ||q - (q·p)p||
-                // where θ = arccos(p·q)
-                let dot: f32 = mean.dims().iter()
-                    .zip(point.dims().iter())
-                    .map(|(a, b)| a * b)
-                    .sum();
+    /// Name of a session or document
+    pub fn name(&self, id: Id) -> Option<&str> {
+        self.metadata(id)?.get(NAME_KEY)?.as_str()
+    }
 
-                // Clamp dot product to valid range for arccos
-                let dot_clamped = dot.clamp(-1.0, 1.0);
-                let theta = dot_clamped.acos();
+    /// Most recent session with the given name
+    pub fn find_session(&self, name: &str) -> Option<Id> {
+        self.find_named(ContainerLevel::Session, name)
+    }
 
-                if theta.abs() < 1e-8 {
-                    // Points are identical, tangent vector is zero
-                    continue;
-                }
+    /// Most recent document with the given name
+    pub fn find_document(&self, name: &str) -> Option<Id> {
+        self.find_named(ContainerLevel::Document, name)
+    }
 
-                // Direction in tangent space
+    fn find_named(&self, level: ContainerLevel, name: &str) -> Option<Id> {
+        self.find_by_metadata(NAME_KEY, &MetaValue::from(name))
+            .into_iter()
+            .filter(|id| self.containers.get(id).is_some_and(|c| c.level == level))
+            .max_by_key(|id| (self.containers[id].timestamp, *id))
+    }
+
+    /// IDs of points, sessions and documents whose metadata has `key` set to
+    /// `value`, oldest first
+    pub fn find_by_metadata(&self, key: &str, value: &MetaValue) -> Vec<Id> {
+        let mut ids: Vec<Id> = self.metadata
+            .iter()
+            .filter(|(_, m)| m.get(key) == Some(value))
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Start a new session or document if `point` breaks from the previous chunk
+    fn segment(&mut self, point: &Point) {
+        // A segment the caller just started is never split before its first chunk
+        let fresh = self.active_document
+            .and_then(|id| self.containers.get(&id))
+            .is_none_or(|doc| doc.children.is_empty());
+        if fresh {
+            return;
+        }
+
+        let seg = self.config.segmentation;
+        let Some(last) = self.last_chunk.and_then(|id| self.containers.get(&id)) else {
+            return;
+        };
+
+        let gap = clock::now_ms().saturating_sub(last.timestamp);
+        let similarity = self.proximity.similarity(self.proximity.proximity(&last.centroid, point));
+        let breaks = |threshold: Option<f32>, gap_ms: Option<u64>| {
+            threshold.is_some_and(|t| similarity < t) || gap_ms.is_some_and(|g| gap > g)
+        };
+
+        if breaks(seg.session_similarity, seg.session_gap_ms) {
+            self.new_session();
+        } else if breaks(seg.document_similarity, seg.document_gap_ms) {
+            self.new_document();
+        }
+    }
+
+    /// Get an indexed point by ID
+    pub fn get(&self, id: Id) -> Option<&Point> {
+        self.containers
+            .get(&id)
+            .filter(|c| c.is_leaf())
+            .map(|c| &c.centroid)
+    }
+
+    /// Check if a point is indexed
+    pub fn contains(&self, id: Id) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Attach a payload to an indexed point or a document (replaces any
+    /// existing one)
+    ///
+    /// A document's payload is typically its full text, returned by
+    /// `near_parent_documents` when one of its chunks matches.
+    pub fn set_payload(&mut self, id: Id, payload: Blob) -> NearResult<()> {
+        if !self.holds_payload(id) {
+            return Err(NearError::NotFound(id));
+        }
+        self.payloads.insert(id, payload);
+        Ok(())
+    }
+
+    /// Get the payload attached to a point or document
+    pub fn payload(&self, id: Id) -> Option<&Blob> {
+        self.payloads.get(&id).or_else(|| self.ephemeral.get(&id)?.payload.as_ref())
+    }
+
+    /// Whether `id` is a chunk or document (the containers with payloads)
+    fn holds_payload(&self, id: Id) -> bool {
+        self.containers
+            .get(&id)
+            .is_some_and(|c| matches!(c.level, ContainerLevel::Chunk | ContainerLevel::Document))
+    }
+
+    /// Add a point tagged with its conversation role
+    pub fn add_with_role(&mut self, id: Id, point: &Point, role: Role) -> NearResult<()> {
+        self.add(id, point)?;
+        self.set_role(id, role)
+    }
+
+    /// Tag an indexed point with its conversation role (replaces any existing one)
+    pub fn set_role(&mut self, id: Id, role: Role) -> NearResult<()> {
+        match self.containers.get_mut(&id).filter(|c| c.is_leaf()) {
+            Some(chunk) => {
+                chunk.role = Some(role);
+                Ok(())
+            }
+            None => Err(NearError::NotFound(id)),
+        }
+    }
+
+    /// Conversation role of a point, if tagged
+    pub fn role(&self, id: Id) -> Option<Role> {
+        self.containers.get(&id).filter(|c| c.is_leaf())?.role
+    }
+
+    /// Attach metadata to an indexed point, session or document (replaces
+    /// any existing metadata)
+    pub fn set_metadata(&mut self, id: Id, metadata: Metadata) -> NearResult<()> {
+        let labelled = self.containers
+            .get(&id)
+            .is_some_and(|c| c.level != ContainerLevel::Global);
+        if !labelled {
+            return Err(NearError::NotFound(id));
+        }
+        self.metadata.insert(id, metadata);
+        Ok(())
+    }
+
+    /// Get the metadata attached to a point, session or document
+    pub fn metadata(&self, id: Id) -> Option<&Metadata> {
+        self.metadata.get(&id).or_else(|| self.ephemeral.get(&id)?.metadata.as_ref())
+    }
+
+    /// Add a point with its provenance
+    pub fn add_with_provenance(&mut self, id: Id, point: &Point, provenance: Provenance) -> NearResult<()> {
+        self.add(id, point)?;
+        self.set_provenance(id, provenance)
+    }
+
+    /// Record where an indexed point came from (replaces any existing
+    /// provenance; an empty one clears it)
+    pub fn set_provenance(&mut self, id: Id, provenance: Provenance) -> NearResult<()> {
+        if !self.containers.get(&id).is_some_and(|c| c.is_leaf()) {
+            return Err(NearError::NotFound(id));
+        }
+        if provenance.is_empty() {
+            self.provenance.remove(&id);
+        } else {
+            self.provenance.insert(id, provenance);
+        }
+        Ok(())
+    }
+
+    /// Get the provenance recorded for a point
+    pub fn provenance(&self, id: Id) -> Option<&Provenance> {
+        self.provenance.get(&id)
+    }
+
+    /// Summarize a session (replaces any existing summary)
+    ///
+    /// `vector` is an embedding of `text`; queries close to it reach the
+    /// session even when its merged centroid has drifted elsewhere, and
+    /// `near_sessions` returns the text.
+    pub fn set_session_summary(&mut self, id: Id, text: impl Into<String>, vector: Point) -> NearResult<()> {
+        self.set_summary(id, ContainerLevel::Session, text.into(), vector)
+    }
+
+    /// Summarize a document (replaces any existing summary)
+    pub fn set_document_summary(&mut self, id: Id, text: impl Into<String>, vector: Point) -> NearResult<()> {
+        self.set_summary(id, ContainerLevel::Document, text.into(), vector)
+    }
+
+    fn set_summary(&mut self, id: Id, level: ContainerLevel, text: String, vector: Point) -> NearResult<()> {
+        if !self.containers.get(&id).is_some_and(|c| c.level == level) {
+            return Err(NearError::NotFound(id));
+        }
+        if vector.dimensionality() != self.dimensionality {
+            return Err(NearError::DimensionalityMismatch {
+                expected: self.dimensionality,
+                got: vector.dimensionality(),
+            });
+        }
+        // Stored as routed: normalized when the proximity allows, like centroids
+        let vector = if self.proximity.normalizes() { vector.normalize() } else { vector };
+        self.summaries.insert(id, NodeSummary { text, vector });
+        Ok(())
+    }
+
+    /// Summary of a session or document, if one was set
+    pub fn summary(&self, id: Id) -> Option<&NodeSummary> {
+        self.summaries.get(&id)
+    }
+
+    /// Drop a session's or document's summary, returning it
+    pub fn clear_summary(&mut self, id: Id) -> Option<NodeSummary> {
+        self.summaries.remove(&id)
+    }
+
+    /// Stored chunks a new chunk at `point` may contradict, closest first
+    ///
+    /// Scans only chunks marked as candidates (see `ContradictionCheck`),
+    /// exactly, so the check stays cheap when few memories are marked.
+    /// `metadata` is the new chunk's, for its slot value.
+    pub fn contradictions(
+        &self,
+        point: &Point,
+        metadata: Option<&Metadata>,
+        check: &ContradictionCheck,
+    ) -> NearResult<Vec<Contradiction>> {
+        if point.dimensionality() != self.dimensionality {
+            return Err(NearError::DimensionalityMismatch {
+                expected: self.dimensionality,
+                got: point.dimensionality(),
+            });
+        }
+        let slot = metadata.and_then(|m| m.get(&check.marker));
+        let mut found: Vec<Contradiction> = self.metadata.iter()
+            .filter_map(|(id, m)| Some((id, m.get(&check.marker)?)))
+            .filter(|(_, marker)| check.matches(marker, slot))
+            .filter_map(|(id, marker)| {
+                let chunk = self.containers.get(id).filter(|c| c.is_leaf())?;
+                let score = self.proximity.proximity(point, &chunk.centroid);
+                self.order.passes(score, check.threshold)
+                    .then(|| Contradiction { id: *id, score, marker: marker.clone() })
+            })
+            .collect();
+        found.sort_by(|a, b| self.order.compare(a.score, b.score).then_with(|| a.id.cmp(&b.id)));
+        found.truncate(check.k);
+        Ok(found)
+    }
+
+    /// Add a point with its metadata, returning the stored chunks it may
+    /// contradict (see `contradictions`)
+    ///
+    /// The point is added either way; reconciling - removing the old chunk,
+    /// clearing its marker, or keeping both - is left to the caller.
+    pub fn add_checked(
+        &mut self,
+        id: Id,
+        point: &Point,
+        metadata: Option<Metadata>,
+        check: &ContradictionCheck,
+    ) -> NearResult<Vec<Contradiction>> {
+        let found = self.contradictions(point, metadata.as_ref(), check)?;
+        self.add(id, point)?;
+        if let Some(metadata) = metadata {
+            self.set_metadata(id, metadata)?;
+        }
+        Ok(found)
+    }
+
+    /// The stored fact `fact` would replace, if it says something else
+    ///
+    /// `upsert_fact` replaces it either way; check first to reconcile.
+    pub fn conflicting_fact(&self, fact: &Fact) -> Option<&Fact> {
+        self.fact(&fact.subject, &fact.predicate).filter(|stored| stored.value != fact.value)
+    }
+
+    /// Store a fact, replacing any with the same subject and predicate
+    ///
+    /// Returns the fact it replaced. Its source, if any, must be an indexed
+    /// chunk; the fact outlives it (removing or compressing the chunk
+    /// leaves the fact in place). `updated_ms` is set to now.
+    pub fn upsert_fact(&mut self, mut fact: Fact) -> NearResult<Option<Fact>> {
+        if let Some(source) = fact.source.filter(|source| !self.contains(*source)) {
+            return Err(NearError::NotFound(source));
+        }
+        fact.updated_ms = clock::now_ms();
+        let key = fact.key();
+        let replaced = self.facts.remove(&key);
+        self.facts.insert(key, fact);
+        Ok(replaced)
+    }
+
+    /// The fact about `subject` under `predicate`, if stored
+    pub fn fact(&self, subject: &str, predicate: &str) -> Option<&Fact> {
+        self.facts.get(&(subject.to_string(), predicate.to_string()))
+    }
+
+    /// Every fact about `subject`, by predicate
+    pub fn facts_about(&self, subject: &str) -> Vec<&Fact> {
+        let mut facts: Vec<&Fact> = self.facts.values().filter(|f| f.subject == subject).collect();
+        facts.sort_by(|a, b| a.predicate.cmp(&b.predicate));
+        facts
+    }
+
+    /// Facts extracted from the chunks in `hits`, in hit order
+    ///
+    /// Pairs semantic memory with the episodes a query retrieved: run
+    /// `near`, then attach what was learned from each hit.
+    pub fn facts_for(&self, hits: &[SearchResult]) -> Vec<&Fact> {
+        let rank: HashMap<Id, usize> = hits.iter().enumerate().rev().map(|(i, hit)| (hit.id, i)).collect();
+        let mut facts: Vec<(usize, &Fact)> = self.facts.values()
+            .filter_map(|f| Some((*rank.get(&f.source?)?, f)))
+            .collect();
+        facts.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(b.1.confidence.total_cmp(&a.1.confidence))
+                .then_with(|| a.1.key().cmp(&b.1.key()))
+        });
+        facts.into_iter().map(|(_, f)| f).collect()
+    }
+
+    /// Remove a fact, returning it
+    pub fn remove_fact(&mut self, subject: &str, predicate: &str) -> Option<Fact> {
+        self.facts.remove(&(subject.to_string(), predicate.to_string()))
+    }
+
+    /// Number of stored facts
+    pub fn fact_count(&self) -> usize {
+        self.facts.len()
+    }
+
+    /// Hold a point in the session overlay
+    ///
+    /// Overlay points are found by every search built on `near` (exactly
+    /// scored, so they need no tree), and their payload and metadata come
+    /// back like a chunk's. They never join a session or document and are
+    /// not saved: at the end of a conversation, `promote` the ones worth
+    /// keeping and `discard_ephemeral` the rest. Replaces an overlay point
+    /// with the same ID.
+    pub fn add_ephemeral(&mut self, mut point: EphemeralPoint) -> NearResult<()> {
+        if point.point.dimensionality() != self.dimensionality {
+            return Err(NearError::DimensionalityMismatch {
+                expected: self.dimensionality,
+                got: point.point.dimensionality(),
+            });
+        }
+        if self.contains(point.id) {
+            return Err(NearError::IndexError(format!("{} is already indexed", point.id).into()));
+        }
+        point.timestamp = clock::now_ms();
+        self.ephemeral.insert(point.id, point);
+        Ok(())
+    }
+
+    /// An overlay point
+    pub fn ephemeral(&self, id: Id) -> Option<&EphemeralPoint> {
+        self.ephemeral.get(&id)
+    }
+
+    /// Overlay points, oldest first
+    pub fn ephemeral_points(&self) -> Vec<&EphemeralPoint> {
+        let mut points: Vec<&EphemeralPoint> = self.ephemeral.values().collect();
+        points.sort_by_key(|p| (p.timestamp, p.id));
+        points
+    }
+
+    /// Number of overlay points
+    pub fn ephemeral_count(&self) -> usize {
+        self.ephemeral.len()
+    }
+
+    /// Drop one overlay point
+    pub fn remove_ephemeral(&mut self, id: Id) -> Option<EphemeralPoint> {
+        self.ephemeral.remove(&id)
+    }
+
+    /// Move an overlay point into the tree, as the next chunk of the
+    /// active document
+    ///
+    /// If the tree refuses it, the point stays in the overlay.
+    pub fn promote(&mut self, id: Id) -> NearResult<()> {
+        let point = self.ephemeral.remove(&id).ok_or(NearError::NotFound(id))?;
+        if let Err(e) = self.add(id, &point.point) {
+            self.ephemeral.insert(id, point);
+            return Err(e);
+        }
+        if let Some(payload) = point.payload {
+            self.payloads.insert(id, payload);
+        }
+        if let Some(metadata) = point.metadata {
+            self.metadata.insert(id, metadata);
+        }
+        Ok(())
+    }
+
+    /// Promote the overlay points `keep` accepts (oldest first) and drop
+    /// the rest, returning the promoted IDs
+    ///
+    /// Stops at the first point that fails to promote, leaving it and the
+    /// points not yet promoted or dropped in the overlay.
+    pub fn close_ephemeral<F>(&mut self, keep: F) -> NearResult<Vec<Id>>
+    where
+        F: Fn(&EphemeralPoint) -> bool,
+    {
+        let kept: Vec<Id> = self.ephemeral_points().into_iter().filter(|p| keep(p)).map(|p| p.id).collect();
+        for &id in &kept {
+            self.promote(id)?;
+        }
+        self.ephemeral.clear();
+        Ok(kept)
+    }
+
+    /// Drop every overlay point, returning how many there were
+    pub fn discard_ephemeral(&mut self) -> usize {
+        let count = self.ephemeral.len();
+        self.ephemeral.clear();
+        count
+    }
+
+    /// Overlay points for `params`, as (ID, distance) like tree results
+    fn search_ephemeral(&self, query: &Point, query_time: u64, params: &QueryParams, roles: Roles) -> Vec<(Id, f32)> {
+        if !roles.contains(None) {
+            return Vec::new();
+        }
+        let w = params.temporal_weight.unwrap_or(self.config.temporal_weight);
+        let max_distance = params.min_score.map(|score| self.order.to_distance(score));
+        self.ephemeral
+            .values()
+            .filter(|p| params.accepts(p.id))
+            .map(|p| {
+                let semantic = self.distance(query, &p.point);
+                (p.id, semantic * (1.0 - w) + self.temporal_distance(query_time, p.timestamp) * w)
+            })
+            .filter(|&(_, dist)| max_distance.is_none_or(|max| dist <= max))
+            .collect()
+    }
+
+    /// Attach the stored fields `fields` asks for to search results
+    pub fn project(&self, results: &[SearchResult], fields: ReturnFields) -> Vec<ProjectedResult> {
+        results
+            .iter()
+            .map(|result| ProjectedResult {
+                vector: self.get(result.id)
+                    .or_else(|| self.ephemeral.get(&result.id).map(|p| &p.point))
+                    .filter(|_| fields.vector)
+                    .cloned(),
+                blob: self.payload(result.id).filter(|_| fields.blob).cloned(),
+                metadata: self.metadata(result.id).filter(|_| fields.metadata).cloned(),
+                provenance: self.provenance(result.id).filter(|_| fields.provenance).cloned(),
+                ..ProjectedResult::bare(result, fields)
+            })
+            .collect()
+    }
+
+    /// Search and attach the stored fields and context `params` asks for
+    ///
+    /// With `expand_context: n`, each hit also carries up to `n` chunks
+    /// before and after it in its document - sentence-window retrieval
+    /// without a second query. Neighbours are read from each hit's
+    /// document, with no vector comparisons.
+    pub fn near_fields(&self, query: &Point, params: &QueryParams) -> NearResult<Vec<ProjectedResult>> {
+        let results = self.near_with_params(query, params)?;
+        let mut projected = self.project(&results, params.fields);
+        if let Some(n) = params.expand_context.filter(|n| *n > 0) {
+            self.attach_context(&mut projected, n, params.fields);
+        }
+        Ok(projected)
+    }
+
+    /// Fill `context` with up to `n` document neighbours either side of each result
+    fn attach_context(&self, results: &mut [ProjectedResult], n: usize, fields: ReturnFields) {
+        // Live chunks of each hit's document, and each listed chunk's position
+        let mut documents: HashMap<Id, Vec<Id>> = HashMap::new();
+        let mut positions: HashMap<Id, usize> = HashMap::new();
+
+        for result in results.iter_mut() {
+            let Some(doc) = self.containers.get(&result.id).and_then(|c| c.parent).and_then(|id| self.containers.get(&id)) else {
+                continue;
+            };
+            let chunks = documents.entry(doc.id).or_insert_with(|| {
+                // Removed chunks stay listed in their document until consolidation
+                let chunks: Vec<Id> = doc.children.iter().copied().filter(|id| self.contains(*id)).collect();
+                positions.extend(chunks.iter().enumerate().map(|(i, id)| (*id, i)));
+                chunks
+            });
+            let Some(&live) = positions.get(&result.id) else {
+                continue;
+            };
+            let (start, end) = (live.saturating_sub(n), (live + n + 1).min(chunks.len()));
+            result.context = (start..end)
+                .filter(|&j| j != live)
+                .map(|j| ContextChunk {
+                    id: chunks[j],
+                    offset: j as isize - live as isize,
+                    blob: self.payload(chunks[j]).filter(|_| fields.blob).cloned(),
+                })
+                .collect();
+        }
+    }
+
+    /// Iterate over all indexed points (in no particular order)
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &Point)> + '_ {
+        self.containers
+            .values()
+            .filter(|c| c.is_leaf())
+            .map(|c| (c.id, &c.centroid))
+    }
+
+    /// Session IDs in time order
+    pub fn sessions(&self) -> Vec<Id> {
+        let mut sessions: Vec<&Container> = self.containers
+            .values()
+            .filter(|c| c.level == ContainerLevel::Session)
+            .collect();
+        sessions.sort_by_key(|s| (s.timestamp, s.id));
+        sessions.into_iter().map(|s| s.id).collect()
+    }
+
+    /// Hierarchy details of a session, document or chunk
+    pub fn container(&self, id: Id) -> Option<ContainerInfo> {
+        let container = self.containers.get(&id)?;
+        Some(ContainerInfo {
+            id,
+            level: container.level,
+            timestamp: container.timestamp,
+            children: container.children.clone(),
+            chunk_count: if container.is_leaf() { 1 } else { container.descendant_count() },
+        })
+    }
+
+    /// Session that new points are added to (None until the next add)
+    pub fn active_session(&self) -> Option<Id> {
+        self.active_session
+    }
+
+    /// Document that new points are added to (None until the next add)
+    pub fn active_document(&self) -> Option<Id> {
+        self.active_document
+    }
+
+    /// Chunk added just before `id` in the same session
+    pub fn previous_turn(&self, id: Id) -> Option<Id> {
+        self.containers.get(&id).filter(|c| c.is_leaf())?.prev_turn
+    }
+
+    /// Chunk added just after `id` in the same session
+    pub fn next_turn(&self, id: Id) -> Option<Id> {
+        self.containers.get(&id).filter(|c| c.is_leaf())?.next_turn
+    }
+
+    /// The conversation `id` belongs to: every turn of its session in the
+    /// order they were added, `id` included
+    ///
+    /// Lets a retrieved chunk be shown with its surroundings. Returns None
+    /// if `id` isn't an indexed point.
+    pub fn thread_of(&self, id: Id) -> Option<Vec<Id>> {
+        let chunk = self.containers.get(&id).filter(|c| c.is_leaf())?;
+
+        // Bounded walks, in case a corrupt file links turns in a cycle
+        let limit = self.containers.len();
+        let mut before = Vec::new();
+        let mut cursor = chunk.prev_turn;
+        while let Some(prev) = cursor.filter(|_| before.len() < limit) {
+            before.push(prev);
+            cursor = self.containers.get(&prev).and_then(|c| c.prev_turn);
+        }
+        before.reverse();
+        before.push(id);
+
+        let mut cursor = chunk.next_turn;
+        while let Some(next) = cursor.filter(|_| before.len() < limit) {
+            before.push(next);
+            cursor = self.containers.get(&next).and_then(|c| c.next_turn);
+        }
+        Some(before)
+    }
+
+    /// Latest chunk still indexed in a session's documents
+    fn last_turn_in(&self, session_id: Id) -> Option<Id> {
+        let session = self.containers.get(&session_id)?;
+        session.children.iter().rev()
+            .filter_map(|doc| self.containers.get(doc))
+            .flat_map(|doc| doc.children.iter().rev())
+            .copied()
+            .find(|id| self.containers.get(id).is_some_and(|c| c.is_leaf()))
+    }
+
+    /// Link each session's chunks in hierarchy order (documents, then
+    /// chunks, each in the order they were added)
+    fn link_turns_in_order(&mut self) {
+        let sessions: Vec<Id> = self.containers.values()
+            .filter(|c| c.level == ContainerLevel::Session)
+            .map(|c| c.id)
+            .collect();
+        for session_id in sessions {
+            let chunks: Vec<Id> = self.containers[&session_id].children.iter()
+                .filter_map(|doc| self.containers.get(doc))
+                .flat_map(|doc| doc.children.iter().copied())
+                .filter(|id| self.containers.get(id).is_some_and(|c| c.is_leaf()))
+                .collect();
+            for (i, id) in chunks.iter().enumerate() {
+                if let Some(chunk) = self.containers.get_mut(id) {
+                    chunk.prev_turn = i.checked_sub(1).map(|prev| chunks[prev]);
+                    chunk.next_turn = chunks.get(i + 1).copied();
+                }
+            }
+        }
+    }
+
+    /// Compute Fréchet mean on the unit hypersphere using iterative algorithm
+    /// This finds the point that minimizes sum of squared geodesic distances
+    #[allow(dead_code)]
+    fn compute_frechet_mean(&self, points: &[Point], initial: &Point) -> Point {
+        let mut mean = initial.clone();
+        let iterations = self.config.frechet_iterations;
+
+        for _ in 0..iterations {
+            // Compute weighted tangent vectors (log map)
+            let mut tangent_sum = vec![0.0f32; mean.dimensionality()];
+
+            for point in points {
+                // Log map: project point onto tangent space at mean
+                // For unit sphere: log_p(q) = θ * (q - (q·p)p) / ||q - (q·p)p||
+                // where θ = arccos(p·q)
+                let dot: f32 = mean.dims().iter()
+                    .zip(point.dims().iter())
+                    .map(|(a, b)| a * b)
+                    .sum();
+
+                // Clamp dot product to valid range for arccos
+                let dot_clamped = dot.clamp(-1.0, 1.0);
+                let theta = dot_clamped.acos();
+
+                if theta.abs() < 1e-8 {
+                    // Points are identical, tangent vector is zero
+                    continue;
+                }
+
+                // Direction in tangent space
                 let mut direction: Vec<f32> = point.dims().iter()
                     .zip(mean.dims().iter())
                     .map(|(q, p)| q - dot * p)
@@ -593,96 +1808,89 @@ impl HatIndex {
     /// Returns the magnitude of the change (for sparse propagation)
     fn update_centroid(&mut self, container_id: Id, new_point: &Point) -> f32 {
         let method = self.config.centroid_method;
+        let normalize = self.proximity.normalizes();
+        let update_subspace =
+            self.config.subspace_enabled && self.config.subspace_config.incremental_covariance;
+        let level_merge = self.containers
+            .get(&container_id)
+            .and_then(|c| self.config.level_merges.for_level(c.level))
+            .cloned();
+        let child_centroids = if level_merge.is_some() || method == CentroidMethod::GeometricMedian {
+            self.child_centroids(container_id)
+        } else {
+            Vec::new()
+        };
 
-        // First, extract what we need from the container
-        let (old_centroid, n, accumulated_sum) = {
-            if let Some(container) = self.containers.get(&container_id) {
-                (
-                    container.centroid.clone(),
-                    container.descendant_count as f32,
-                    container.accumulated_sum.clone(),
-                )
-            } else {
-                return 0.0;
-            }
+        let Some(container) = self.containers.get_mut(&container_id) else {
+            return 0.0;
         };
 
         // Handle first child case
-        if n == 0.0 {
-            if let Some(container) = self.containers.get_mut(&container_id) {
-                container.centroid = new_point.clone();
-                container.accumulated_sum = Some(new_point.clone());
-                container.descendant_count += 1;
-            }
+        if container.running.is_empty() {
+            container.running.push(new_point);
+            container.centroid = new_point.clone();
             return f32::MAX; // Always propagate first point
         }
 
-        // Compute new centroid based on method
-        let (new_centroid, new_sum) = match method {
-            CentroidMethod::Euclidean => {
-                // Incremental Euclidean mean using accumulated sum
-                let new_sum = if let Some(ref sum) = accumulated_sum {
-                    sum.dims().iter()
-                        .zip(new_point.dims().iter())
-                        .map(|(s, p)| s + p)
-                        .collect::<Vec<f32>>()
-                } else {
-                    new_point.dims().to_vec()
-                };
+        container.running.push(new_point);
 
-                // Compute centroid as normalized mean
-                let count = n + 1.0;
-                let mean_dims: Vec<f32> = new_sum.iter().map(|s| s / count).collect();
-                let centroid = Point::new(mean_dims).normalize();
-                (centroid, Point::new(new_sum))
-            }
-            CentroidMethod::Frechet => {
-                // Update accumulated sum
-                let new_sum = if let Some(ref sum) = accumulated_sum {
-                    sum.dims().iter()
-                        .zip(new_point.dims().iter())
-                        .map(|(s, p)| s + p)
-                        .collect::<Vec<f32>>()
+        // Compute new centroid based on the level's merge, else the method
+        let level_merge = level_merge.filter(|_| !child_centroids.is_empty());
+        let new_centroid = match (method, level_merge) {
+            (_, Some(merge)) => {
+                let merged = merge.merge(&child_centroids);
+                if normalize {
+                    merged.normalize()
                 } else {
-                    new_point.dims().to_vec()
-                };
-
+                    merged
+                }
+            }
+            (CentroidMethod::Euclidean, None) => {
+                // Running mean, normalized when comparing by angle
+                let centroid = container.running.centroid();
+                if normalize {
+                    centroid.normalize()
+                } else {
+                    centroid
+                }
+            }
+            (CentroidMethod::Frechet, None) => {
                 // For incremental Fréchet, use geodesic interpolation
-                let new_count = n + 1.0;
-                let weight = 1.0 / new_count;
-                let centroid = Self::geodesic_interpolate_static(&old_centroid, new_point, weight);
-                (centroid, Point::new(new_sum))
+                let weight = 1.0 / container.running.count() as f32;
+                Self::geodesic_interpolate_static(&container.centroid, new_point, weight)
             }
-        };
-
-        // Now update the container
-        let subspace_enabled = self.config.subspace_enabled;
-        if let Some(container) = self.containers.get_mut(&container_id) {
-            container.centroid = new_centroid.clone();
-            container.accumulated_sum = Some(new_sum);
-            container.descendant_count += 1;
-
-            // Update subspace if enabled, incremental covariance is on, and not a chunk
-            // When incremental_covariance is false (default), we skip the expensive
-            // O(d²) outer product accumulation per insert, deferring to consolidation.
-            if subspace_enabled
-                && self.config.subspace_config.incremental_covariance
-                && container.level != ContainerLevel::Chunk
-            {
-                if let Some(ref mut subspace) = container.subspace {
-                    subspace.add_point(new_point);
-                    // Principal directions recomputed during consolidation
+            (CentroidMethod::GeometricMedian, None) => {
+                let median = if child_centroids.is_empty() {
+                    container.running.centroid()
+                } else {
+                    GeometricMedian::default().merge(&child_centroids)
+                };
+                if normalize {
+                    median.normalize()
+                } else {
+                    median
                 }
             }
-        }
+        };
 
         // Calculate change magnitude (L2 norm of delta)
-        let delta: f32 = old_centroid.dims()
+        let delta: f32 = container.centroid.dims()
             .iter()
             .zip(new_centroid.dims().iter())
             .map(|(old, new)| (new - old).powi(2))
             .sum::<f32>()
             .sqrt();
+        container.centroid = new_centroid;
+
+        // Update subspace if enabled, incremental covariance is on, and not a chunk
+        // When incremental_covariance is false (default), we skip the expensive
+        // O(d²) outer product accumulation per insert, deferring to consolidation.
+        if update_subspace && container.level != ContainerLevel::Chunk {
+            if let Some(ref mut subspace) = container.subspace {
+                subspace.add_point(new_point);
+                // Principal directions recomputed during consolidation
+            }
+        }
 
         delta
     }
@@ -719,6 +1927,7 @@ impl HatIndex {
 
     /// Geodesic interpolation on the unit hypersphere (slerp)
     /// Returns a point t fraction of the way from a to b along the great circle
+    #[allow(dead_code)]
     fn geodesic_interpolate(&self, a: &Point, b: &Point, t: f32) -> Point {
         // Compute dot product
         let dot: f32 = a.dims().iter()
@@ -768,65 +1977,114 @@ impl HatIndex {
     }
 
     /// Search the tree from a starting container
+    ///
+    /// Overrides in `params` replace the configured beam width and temporal
+    /// weight; leaves the filter rejects or scoring below `min_score` are
+    /// skipped before they're collected. Stops descending once
+    /// `deadline` expires, returning the leaves reached so far and `false`.
+    ///
+    /// `min_score` doesn't prune internal nodes: a centroid's score bounds
+    /// neither side of its children's (containers keep no radius), so the
+    /// beam still descends through containers scoring below the cutoff and
+    /// the traversal costs the same as without it.
     fn search_tree(
         &self,
         query: &Point,
         query_time: u64,
         start_id: Id,
-        k: usize,
-    ) -> Vec<(Id, f32)> {
+        params: &QueryParams,
+        roles: Roles,
+        deadline: &Deadline,
+    ) -> (Vec<(Id, f32)>, bool) {
         let mut results: Vec<(Id, f32)> = Vec::new();
+        let mut complete = true;
+        let k = params.k;
+        let w = params.temporal_weight.unwrap_or(self.config.temporal_weight);
+        let max_distance = params.min_score.map(|score| self.order.to_distance(score));
 
         // Adaptive beam width based on k
-        let beam_width = self.config.beam_width.max(k);
-
-        // BFS with beam search
-        let mut current_level = vec![start_id];
-
-        while !current_level.is_empty() {
-            let mut next_level: Vec<(Id, f32)> = Vec::new();
-
-            for container_id in &current_level {
-                if let Some(container) = self.containers.get(container_id) {
-                    if container.is_leaf() {
-                        // Leaf node - add to results
-                        let dist = self.combined_distance(query, query_time, container);
-                        results.push((*container_id, dist));
-                    } else {
-                        // Internal node - score children and add to next level
-                        for child_id in &container.children {
-                            if let Some(child) = self.containers.get(child_id) {
-                                let dist = self.combined_distance(query, query_time, child);
-                                next_level.push((*child_id, dist));
+        let beam_width = params.beam_width.unwrap_or(self.config.beam_width).max(k);
+
+        // BFS with beam search, in this thread's reusable buffers
+        with_scratch(|scratch| {
+            let Scratch { frontier, candidates, .. } = scratch;
+            frontier.push(start_id);
+
+            'levels: while !frontier.is_empty() {
+                candidates.clear();
+
+                for container_id in frontier.iter() {
+                    if deadline.is_expired() {
+                        complete = false;
+                        break 'levels;
+                    }
+                    if let Some(container) = self.containers.get(container_id) {
+                        if container.is_leaf() {
+                            // Leaf node - add to results
+                            if !roles.contains(container.role) || !params.accepts(*container_id) {
+                                continue;
+                            }
+                            let dist = self.combined_distance(query, query_time, w, container);
+                            if max_distance.is_none_or(|max| dist <= max) {
+                                results.push((*container_id, dist));
                             }
+                        } else {
+                            // Internal node - score children and add to next level
+                            let children: Vec<&Container> = container.children
+                                .iter()
+                                .filter_map(|child_id| self.containers.get(child_id))
+                                .collect();
+                            let dists = self.combined_distances(query, query_time, w, &children);
+                            candidates.extend(children.iter().map(|c| c.id).zip(dists));
                         }
                     }
                 }
-            }
 
-            if next_level.is_empty() {
-                break;
-            }
+                if candidates.is_empty() {
+                    break;
+                }
 
-            // Sort by distance and take beam_width best
-            next_level.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-            current_level = next_level
-                .into_iter()
-                .take(beam_width)
-                .map(|(id, _)| id)
-                .collect();
-        }
+                // Sort by distance (ties by ID) and take beam_width best
+                candidates.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+                frontier.clear();
+                frontier.extend(candidates.iter().take(beam_width).map(|&(id, _)| id));
+            }
+        });
 
-        // Sort results and return top k
-        results.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        // Sort results (ties by ID) and return top k
+        results.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
         results.truncate(k);
-        results
+        (results, complete)
     }
 
     // =========================================================================
     // Multi-Resolution Query API (inspired by VAR next-scale prediction)
     // =========================================================================
 
+    /// `near_with_params` over only chunks whose role is in `roles`
+    ///
+    /// Roles are checked on each chunk as the traversal reaches it, so
+    /// excluded chunks never take a result slot.
+    pub fn near_with_roles(&self, query: &Point, params: &QueryParams, roles: Roles) -> NearResult<Vec<SearchResult>> {
+        let Some(threshold) = params.dedup else {
+            return Ok(self.search(query, params, roles, &Deadline::none())?.results);
+        };
+
+        // Widen the search until k distinct results survive or the tree runs out
+        let mut wide = QueryParams { dedup: None, ..params.clone() };
+        loop {
+            let results = self.search(query, &wide, roles, &Deadline::none())?.results;
+            let exhausted = results.len() < wide.k || wide.k >= self.len();
+            let kept = collapse_duplicates(results, params.k, threshold, self.proximity.as_ref(), |id| {
+                self.containers.get(&id).map(|c| &c.centroid)
+            });
+            if kept.len() == params.k || exhausted {
+                return Ok(kept);
+            }
+            wide.k = wide.k.saturating_mul(4).max(1);
+        }
+    }
+
     /// Coarse query: Get session summaries without descending to chunks
     /// Use this for fast "is there relevant memory?" checks
     pub fn near_sessions(&self, query: &Point, k: usize) -> NearResult<Vec<SessionSummary>> {
@@ -842,10 +2100,7 @@ impl HatIndex {
             None => return Ok(vec![]),
         };
 
-        let query_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        let query_time = clock::now_ms();
 
         // Get root's children (sessions)
         let root = match self.containers.get(&root_id) {
@@ -860,20 +2115,21 @@ impl HatIndex {
                 if session.level != ContainerLevel::Session {
                     return None;
                 }
-                let dist = self.combined_distance(query, query_time, session);
-                let score = if self.higher_is_better { 1.0 - dist } else { dist };
+                let dist = self.combined_distance(query, query_time, self.config.temporal_weight, session);
+                let score = self.order.from_distance(dist);
 
                 Some(SessionSummary {
                     id: *session_id,
                     score,
-                    chunk_count: session.descendant_count,
+                    chunk_count: session.descendant_count(),
                     timestamp: session.timestamp,
+                    summary: self.summaries.get(session_id).map(|s| s.text.clone()),
                 })
             })
             .collect();
 
         // Sort by score (higher is better)
-        sessions.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        sessions.sort_by(|a, b| self.order.compare(a.score, b.score).then_with(|| a.id.cmp(&b.id)));
         sessions.truncate(k);
 
         Ok(sessions)
@@ -888,10 +2144,7 @@ impl HatIndex {
             });
         }
 
-        let query_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        let query_time = clock::now_ms();
 
         let session = match self.containers.get(&session_id) {
             Some(s) => s,
@@ -905,19 +2158,20 @@ impl HatIndex {
                 if doc.level != ContainerLevel::Document {
                     return None;
                 }
-                let dist = self.combined_distance(query, query_time, doc);
-                let score = if self.higher_is_better { 1.0 - dist } else { dist };
+                let dist = self.combined_distance(query, query_time, self.config.temporal_weight, doc);
+                let score = self.order.from_distance(dist);
 
                 Some(DocumentSummary {
                     id: *doc_id,
                     score,
-                    chunk_count: doc.descendant_count,
+                    chunk_count: doc.descendant_count(),
                     timestamp: doc.timestamp,
+                    summary: self.summaries.get(doc_id).map(|s| s.text.clone()),
                 })
             })
             .collect();
 
-        documents.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        documents.sort_by(|a, b| self.order.compare(a.score, b.score).then_with(|| a.id.cmp(&b.id)));
         documents.truncate(k);
 
         Ok(documents)
@@ -932,34 +2186,278 @@ impl HatIndex {
             });
         }
 
-        let query_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        let query_time = clock::now_ms();
+
+        let doc = match self.containers.get(&doc_id) {
+            Some(d) => d,
+            None => return Ok(vec![]),
+        };
+
+        let mut chunks: Vec<SearchResult> = doc.children
+            .iter()
+            .filter_map(|chunk_id| {
+                let chunk = self.containers.get(chunk_id)?;
+                if chunk.level != ContainerLevel::Chunk {
+                    return None;
+                }
+                let dist = self.combined_distance(query, query_time, self.config.temporal_weight, chunk);
+                let score = self.order.from_distance(dist);
+
+                Some(SearchResult::new(*chunk_id, score))
+            })
+            .collect();
+
+        SearchResult::sort(&mut chunks, self.order);
+        chunks.truncate(k);
+
+        Ok(chunks)
+    }
+
+    /// Best chunks grouped by session, document or metadata value
+    ///
+    /// Returns up to `k_groups` groups, ordered by their best chunk, each
+    /// with up to `k_per_group` chunks. Candidates are fetched in growing
+    /// rounds until every group is full or the index is exhausted.
+    pub fn near_grouped(
+        &self,
+        query: &Point,
+        k_groups: usize,
+        k_per_group: usize,
+        group_by: &GroupBy,
+    ) -> NearResult<Vec<ResultGroup>> {
+        if query.dimensionality() != self.dimensionality {
+            return Err(NearError::DimensionalityMismatch {
+                expected: self.dimensionality,
+                got: query.dimensionality(),
+            });
+        }
+        if k_groups == 0 || k_per_group == 0 {
+            return Ok(vec![]);
+        }
+
+        let ancestry = self.chunk_ancestry();
+        let mut fetch = k_groups.saturating_mul(k_per_group);
+        loop {
+            let candidates = self.near(query, fetch)?;
+            let exhausted = candidates.len() < fetch || fetch >= self.len();
 
-        let doc = match self.containers.get(&doc_id) {
-            Some(d) => d,
-            None => return Ok(vec![]),
-        };
+            let mut groups: Vec<ResultGroup> = Vec::new();
+            for hit in candidates {
+                let Some(key) = self.group_key(hit.id, group_by, &ancestry) else {
+                    continue;
+                };
+                match groups.iter().position(|g| g.key == key) {
+                    Some(i) if groups[i].hits.len() < k_per_group => groups[i].hits.push(hit),
+                    Some(_) => {}
+                    None if groups.len() < k_groups => groups.push(ResultGroup { key, hits: vec![hit] }),
+                    None => {}
+                }
+            }
 
-        let mut chunks: Vec<SearchResult> = doc.children
-            .iter()
-            .filter_map(|chunk_id| {
-                let chunk = self.containers.get(chunk_id)?;
-                if chunk.level != ContainerLevel::Chunk {
+            let full = groups.len() == k_groups && groups.iter().all(|g| g.hits.len() == k_per_group);
+            if full || exhausted {
+                return Ok(groups);
+            }
+            fetch = fetch.saturating_mul(4);
+        }
+    }
+
+    /// Parent-document retrieval: match chunks, return their documents
+    ///
+    /// Searches chunk vectors as usual but answers with up to `k` distinct
+    /// documents, ranked by their best chunk, each with the payload set on
+    /// the document (see `set_payload`). Lets small chunks drive matching
+    /// while the caller gets whole documents back in one call.
+    pub fn near_parent_documents(&self, query: &Point, k: usize) -> NearResult<Vec<ParentDocument>> {
+        let groups = self.near_grouped(query, k, 1, &GroupBy::Document)?;
+        Ok(groups
+            .into_iter()
+            .filter_map(|group| {
+                let (GroupKey::Container(id), Some(best)) = (group.key, group.hits.first()) else {
                     return None;
+                };
+                Some(ParentDocument {
+                    id,
+                    score: best.score,
+                    chunk: best.id,
+                    blob: self.payload(id).cloned(),
+                })
+            })
+            .collect())
+    }
+
+    /// Cluster the indexed chunks (see `KMeans`)
+    pub fn discover_clusters(&self, kmeans: &KMeans) -> Clustering {
+        kmeans.fit(self.iter(), self.proximity.clone())
+    }
+
+    /// Lay out a sample of up to `sample_size` chunks in 2-D
+    ///
+    /// Each point carries its chunk's metadata plus `"session"` and
+    /// `"document"` IDs, so exports can be colored by conversation.
+    pub fn project_2d(&self, sample_size: usize, projector: &Projector) -> Projection {
+        let sample = Rng::new(projector.seed).reservoir(self.iter(), sample_size);
+        let mut projection = projector.project(sample, self.proximity.as_ref());
+        let ancestry = self.chunk_ancestry();
+        for point in &mut projection.points {
+            if let Some(metadata) = self.metadata.get(&point.id) {
+                point.metadata.extend(metadata.iter().map(|(k, v)| (k.clone(), v.clone())));
+            }
+            if let Some((session, document)) = ancestry.get(&point.id) {
+                point.metadata.insert("session".to_string(), session.to_string().into());
+                point.metadata.insert("document".to_string(), document.to_string().into());
+            }
+        }
+        projection
+    }
+
+    /// Per session, the sequence of dominant topics over time
+    ///
+    /// Each document takes the cluster most of its chunks belong to (chunks
+    /// the clustering hasn't seen go to their nearest cluster); consecutive
+    /// documents with the same topic merge into one span. Sessions and spans
+    /// are in time order.
+    pub fn topic_timeline(&self, clustering: &Clustering) -> Vec<SessionTimeline> {
+        let mut sessions: Vec<&Container> = self.containers
+            .values()
+            .filter(|c| c.level == ContainerLevel::Session)
+            .collect();
+        sessions.sort_by_key(|s| (s.timestamp, s.id));
+
+        sessions
+            .into_iter()
+            .map(|session| {
+                let mut documents: Vec<TopicSpan> = session.children
+                    .iter()
+                    .filter_map(|doc_id| self.document_topic(self.containers.get(doc_id)?, clustering))
+                    .collect();
+                documents.sort_by_key(|d| (d.start, d.end));
+
+                let mut spans: Vec<TopicSpan> = Vec::new();
+                for document in documents {
+                    match spans.last_mut() {
+                        Some(span) if span.label == document.label => {
+                            span.end = span.end.max(document.end);
+                            span.documents += 1;
+                            span.chunks += document.chunks;
+                        }
+                        _ => spans.push(document),
+                    }
                 }
-                let dist = self.combined_distance(query, query_time, chunk);
-                let score = if self.higher_is_better { 1.0 - dist } else { dist };
+                SessionTimeline { session: session.id, spans }
+            })
+            .collect()
+    }
 
-                Some(SearchResult::new(*chunk_id, score))
+    /// The `k` chunks most central to a session, most central first
+    ///
+    /// Builds the session's proximity graph - each chunk linked to its
+    /// nearest chunks, weighted by closeness - and ranks chunks by PageRank
+    /// over it, so a chunk many others sit close to outranks an isolated
+    /// one. Their payloads make a standing digest of a long history (a
+    /// "profile" prompt) within a fixed budget.
+    ///
+    /// O(n²) in the session's chunks.
+    pub fn importance_report(&self, session_id: Id, k: usize) -> NearResult<Vec<CentralChunk>> {
+        let session = self.containers.get(&session_id)
+            .filter(|c| c.level == ContainerLevel::Session)
+            .ok_or(NearError::NotFound(session_id))?;
+        let chunks: Vec<&Container> = session.children.iter()
+            .filter_map(|doc| self.containers.get(doc))
+            .flat_map(|doc| doc.children.iter())
+            .filter_map(|id| self.containers.get(id).filter(|c| c.is_leaf()))
+            .collect();
+        let n = chunks.len();
+        if n == 0 {
+            return Ok(vec![]);
+        }
+
+        // Out-edges to each chunk's nearest neighbours, weighted by closeness
+        let neighbors = IMPORTANCE_NEIGHBORS.min(n - 1);
+        let edges: Vec<Vec<(usize, f32)>> = chunks.iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                let mut near: Vec<(usize, f32)> = chunks.iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(j, other)| (j, self.distance(&chunk.centroid, &other.centroid)))
+                    .collect();
+                near.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+                near.truncate(neighbors);
+                near.into_iter().map(|(j, d)| (j, 1.0 / (1.0 + d.max(0.0)))).collect()
             })
             .collect();
 
-        chunks.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-        chunks.truncate(k);
+        let mut rank = vec![1.0 / n as f32; n];
+        for _ in 0..IMPORTANCE_ITERATIONS {
+            let mut next = vec![(1.0 - IMPORTANCE_DAMPING) / n as f32; n];
+            for (i, out) in edges.iter().enumerate() {
+                let total: f32 = out.iter().map(|(_, w)| w).sum();
+                if total > 0.0 {
+                    for (j, w) in out {
+                        next[*j] += IMPORTANCE_DAMPING * rank[i] * w / total;
+                    }
+                } else {
+                    // A lone chunk spreads its rank evenly
+                    next.iter_mut().for_each(|r| *r += IMPORTANCE_DAMPING * rank[i] / n as f32);
+                }
+            }
+            rank = next;
+        }
 
-        Ok(chunks)
+        let mut ranked: Vec<CentralChunk> = chunks.iter()
+            .zip(rank)
+            .map(|(chunk, centrality)| CentralChunk { id: chunk.id, centrality, timestamp: chunk.timestamp })
+            .collect();
+        // Stable: equally central chunks stay in turn order
+        ranked.sort_by(|a, b| b.centrality.total_cmp(&a.centrality));
+        ranked.truncate(k);
+        Ok(ranked)
+    }
+
+    /// A document's dominant topic, as a one-document span
+    fn document_topic(&self, doc: &Container, clustering: &Clustering) -> Option<TopicSpan> {
+        let mut votes: HashMap<usize, usize> = HashMap::new();
+        let (mut start, mut end, mut chunks) = (u64::MAX, 0, 0);
+        for chunk in doc.children.iter().filter_map(|id| self.containers.get(id)) {
+            let label = clustering
+                .label_of(chunk.id)
+                .or_else(|| clustering.nearest(&chunk.centroid).map(|c| c.label))?;
+            *votes.entry(label).or_default() += 1;
+            start = start.min(chunk.timestamp);
+            end = end.max(chunk.timestamp);
+            chunks += 1;
+        }
+
+        // Most votes; ties go to the larger (lower-labelled) cluster
+        let (label, _) = votes.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))?;
+        Some(TopicSpan { label, start, end, documents: 1, chunks })
+    }
+
+    /// Session and document of every chunk
+    fn chunk_ancestry(&self) -> HashMap<Id, (Id, Id)> {
+        let mut ancestry = HashMap::new();
+        for session in self.containers.values().filter(|c| c.level == ContainerLevel::Session) {
+            for doc_id in &session.children {
+                if let Some(doc) = self.containers.get(doc_id) {
+                    ancestry.extend(doc.children.iter().map(|chunk| (*chunk, (session.id, *doc_id))));
+                }
+            }
+        }
+        ancestry
+    }
+
+    /// The group a chunk belongs to, if any
+    fn group_key(&self, chunk: Id, group_by: &GroupBy, ancestry: &HashMap<Id, (Id, Id)>) -> Option<GroupKey> {
+        let (session, document) = ancestry.get(&chunk).copied()?;
+        match group_by {
+            GroupBy::Session => Some(GroupKey::Container(session)),
+            GroupBy::Document => Some(GroupKey::Container(document)),
+            GroupBy::Metadata(key) => [chunk, document, session]
+                .iter()
+                .find_map(|id| self.metadata.get(id)?.get(key))
+                .map(|value| GroupKey::Value(value.clone())),
+        }
     }
 
     /// Get statistics about the tree structure
@@ -1038,6 +2536,35 @@ impl HatIndex {
     pub fn is_learnable_routing_enabled(&self) -> bool {
         self.learnable_router.is_some()
     }
+
+    /// Answer several queries, spread over `config.n_threads` threads
+    ///
+    /// Results are in query order. Searches only read the tree, so they
+    /// run concurrently without locking.
+    pub fn near_batch(&self, queries: &[Point], k: usize) -> NearResult<Vec<Vec<SearchResult>>> {
+        let n_threads = self.config.n_threads.clamp(1, queries.len().max(1));
+        if n_threads == 1 {
+            return queries.iter().map(|q| self.near(q, k)).collect();
+        }
+
+        let chunk_size = queries.len().div_ceil(n_threads);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = queries
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk.iter().map(|q| self.near(q, k)).collect::<NearResult<Vec<_>>>()
+                    })
+                })
+                .collect();
+
+            let mut results = Vec::with_capacity(queries.len());
+            for handle in handles {
+                results.extend(handle.join().expect("query thread panicked")?);
+            }
+            Ok(results)
+        })
+    }
 }
 
 /// Statistics about the HAT tree structure
@@ -1049,8 +2576,9 @@ pub struct HatStats {
     pub chunk_count: usize,
 }
 
-impl Near for HatIndex {
-    fn near(&self, query: &Point, k: usize) -> NearResult<Vec<SearchResult>> {
+impl HatIndex {
+    /// Top results for `params`, cut short if `deadline` expires
+    fn search(&self, query: &Point, params: &QueryParams, roles: Roles, deadline: &Deadline) -> NearResult<PartialResults> {
         // Check dimensionality
         if query.dimensionality() != self.dimensionality {
             return Err(NearError::DimensionalityMismatch {
@@ -1059,35 +2587,49 @@ impl Near for HatIndex {
             });
         }
 
-        // Handle empty index
-        let root_id = match self.root_id {
-            Some(id) => id,
-            None => return Ok(vec![]),
-        };
-
         // Current time for temporal scoring
-        let query_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        let query_time = clock::now_ms();
 
-        // Search tree
-        let results = self.search_tree(query, query_time, root_id, k);
+        // Search tree (if anything is indexed), then fold in the session overlay
+        let (mut results, complete) = match self.root_id {
+            Some(root_id) => self.search_tree(query, query_time, root_id, params, roles, deadline),
+            None => (Vec::new(), true),
+        };
+        if !self.ephemeral.is_empty() {
+            results.extend(self.search_ephemeral(query, query_time, params, roles));
+            results.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+            results.truncate(params.k);
+        }
 
         // Convert to SearchResult
-        let search_results: Vec<SearchResult> = results
+        let results: Vec<SearchResult> = results
             .into_iter()
             .map(|(id, dist)| {
-                let score = if self.higher_is_better {
-                    1.0 - dist
-                } else {
-                    dist
-                };
+                let score = self.order.from_distance(dist);
                 SearchResult::new(id, score)
             })
             .collect();
 
-        Ok(search_results)
+        Ok(PartialResults { results, complete })
+    }
+}
+
+impl Near for HatIndex {
+    fn near(&self, query: &Point, k: usize) -> NearResult<Vec<SearchResult>> {
+        Ok(self.near_with_deadline(query, k, &Deadline::none())?.results)
+    }
+
+    fn near_with_deadline(
+        &self,
+        query: &Point,
+        k: usize,
+        deadline: &Deadline,
+    ) -> NearResult<PartialResults> {
+        self.search(query, &QueryParams::new(k), Roles::all(), deadline)
+    }
+
+    fn near_with_params(&self, query: &Point, params: &QueryParams) -> NearResult<Vec<SearchResult>> {
+        self.near_with_roles(query, params, Roles::all())
     }
 
     fn within(&self, query: &Point, threshold: f32) -> NearResult<Vec<SearchResult>> {
@@ -1100,17 +2642,11 @@ impl Near for HatIndex {
         }
 
         // Use near with all points, then filter
-        let all_results = self.near(query, self.containers.len())?;
+        let all_results = self.near(query, self.containers.len() + self.ephemeral.len())?;
 
         let filtered: Vec<SearchResult> = all_results
             .into_iter()
-            .filter(|r| {
-                if self.higher_is_better {
-                    r.score >= threshold
-                } else {
-                    r.score <= threshold
-                }
-            })
+            .filter(|r| self.order.passes(r.score, threshold))
             .collect();
 
         Ok(filtered)
@@ -1125,11 +2661,26 @@ impl Near for HatIndex {
             });
         }
 
-        // Ensure hierarchy exists
-        self.ensure_document();
+        // Overlay points join the tree through `promote`
+        if self.ephemeral.contains_key(&id) {
+            return Err(NearError::IndexError(format!("{} is in the session overlay; promote it", id).into()));
+        }
+        if self.containers.get(&id).is_some_and(|c| c.level != ContainerLevel::Chunk) {
+            return Err(NearError::IndexError(format!("{} is the ID of a HAT container", id).into()));
+        }
 
-        // Create chunk container
-        let chunk = Container::new(id, ContainerLevel::Chunk, point.clone());
+        // Ensure hierarchy exists, starting a new segment on a boundary
+        self.segment(point);
+        self.ensure_document();
+        self.last_chunk = Some(id);
+
+        // Create chunk container, following the session's latest turn
+        let mut chunk = Container::new(id, ContainerLevel::Chunk, point.clone());
+        chunk.parent = self.active_document;
+        chunk.prev_turn = self.active_session.and_then(|session| self.last_turn_in(session));
+        if let Some(prev) = chunk.prev_turn.and_then(|prev| self.containers.get_mut(&prev)) {
+            prev.next_turn = Some(id);
+        }
         self.containers.insert(id, chunk);
 
         // Add to document's children
@@ -1175,8 +2726,19 @@ impl Near for HatIndex {
     }
 
     fn remove(&mut self, id: Id) -> NearResult<()> {
-        // Remove the chunk
-        self.containers.remove(&id);
+        // Remove the chunk, joining the turns on either side of it
+        if let Some(chunk) = self.containers.remove(&id) {
+            if let Some(prev) = chunk.prev_turn.and_then(|prev| self.containers.get_mut(&prev)) {
+                prev.next_turn = chunk.next_turn;
+            }
+            if let Some(next) = chunk.next_turn.and_then(|next| self.containers.get_mut(&next)) {
+                next.prev_turn = chunk.prev_turn;
+            }
+        }
+        self.payloads.remove(&id);
+        self.metadata.remove(&id);
+        self.provenance.remove(&id);
+        self.ephemeral.remove(&id);
 
         // Note: We don't update centroids on remove for simplicity
         // A production implementation would need to handle this
@@ -1194,12 +2756,70 @@ impl Near for HatIndex {
         true
     }
 
+    fn order(&self) -> ScoreOrder {
+        self.order
+    }
+
     fn len(&self) -> usize {
         // Count only chunk-level containers
         self.containers.values()
             .filter(|c| c.level == ContainerLevel::Chunk)
             .count()
     }
+
+    /// Chunk points are the vectors; every other centroid is structure
+    fn memory_usage(&self) -> IndexMemory {
+        use std::mem::size_of;
+
+        let mut usage = IndexMemory {
+            vectors: 0,
+            structure: self.containers.size_bytes(),
+            metadata: self.payloads.size_bytes() + self.metadata.size_bytes() + self.summaries.size_bytes()
+                + self.facts.size_bytes() + self.provenance.size_bytes(),
+        };
+
+        for c in self.containers.values() {
+            let centroid = c.centroid.dimensionality() * size_of::<f32>();
+            if c.level == ContainerLevel::Chunk {
+                usage.vectors += centroid;
+            } else {
+                usage.structure += centroid;
+            }
+            usage.structure += c.children.capacity() * size_of::<Id>()
+                + std::mem::size_of_val(c.running.mean())
+                + c.subspace.as_ref().map_or(0, |s| s.size_bytes());
+        }
+
+        for points in self.consolidation_points_cache.values() {
+            usage.structure += points.iter().map(|p| p.dimensionality() * size_of::<f32>()).sum::<usize>();
+        }
+
+        usage.metadata += self.payloads.values().map(|b| b.size()).sum::<usize>();
+        usage.metadata += self.summaries.values()
+            .map(|s| s.text.capacity() + s.vector.dimensionality() * size_of::<f32>())
+            .sum::<usize>();
+        usage.metadata += self.facts.values()
+            .map(|f| 2 * (f.subject.capacity() + f.predicate.capacity()) + f.value.as_str().map_or(0, str::len))
+            .sum::<usize>();
+        for point in self.ephemeral.values() {
+            usage.vectors += point.point.dimensionality() * size_of::<f32>();
+            usage.metadata += size_of::<EphemeralPoint>() + point.payload.as_ref().map_or(0, Blob::size);
+        }
+        usage.metadata += self.provenance.values()
+            .flat_map(|p| [&p.source, &p.pipeline, &p.model_version])
+            .map(|s| s.as_ref().map_or(0, String::capacity))
+            .sum::<usize>();
+        for metadata in self.metadata.values() {
+            for (key, value) in metadata {
+                usage.metadata += key.capacity() + size_of::<MetaValue>() + size_of::<String>();
+                if let MetaValue::Str(s) = value {
+                    usage.metadata += s.capacity();
+                }
+            }
+        }
+
+        usage
+    }
 }
 
 // =============================================================================
@@ -1225,13 +2845,30 @@ impl HatIndex {
         points
     }
 
+    /// Centroids of a container's direct children
+    fn child_centroids(&self, container_id: Id) -> Vec<Point> {
+        self.containers
+            .get(&container_id)
+            .map(|c| {
+                c.children
+                    .iter()
+                    .filter_map(|child| self.containers.get(child))
+                    .map(|child| child.centroid.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Get all container IDs at a given level
     fn containers_at_level(&self, level: ContainerLevel) -> Vec<Id> {
-        self.containers
+        let mut ids: Vec<Id> = self.containers
             .iter()
             .filter(|(_, c)| c.level == level)
             .map(|(id, _)| *id)
-            .collect()
+            .collect();
+        // Fixed (creation) order, not the map's
+        ids.sort_unstable();
+        ids
     }
 
     /// Recompute a container's centroid from its descendants
@@ -1243,9 +2880,27 @@ impl HatIndex {
             return None;
         }
 
-        let new_centroid = match compute_exact_centroid(&points) {
-            Some(c) => c,
-            None => return None,
+        let level_merge = self.containers
+            .get(&container_id)
+            .and_then(|c| self.config.level_merges.for_level(c.level));
+        let new_centroid = match (self.config.centroid_method, level_merge) {
+            (_, Some(merge)) => {
+                let merged = merge.merge(&self.child_centroids(container_id));
+                if self.proximity.normalizes() {
+                    merged.normalize()
+                } else {
+                    merged
+                }
+            }
+            (CentroidMethod::GeometricMedian, None) => {
+                let median = GeometricMedian::default().merge(&points);
+                if self.proximity.normalizes() {
+                    median.normalize()
+                } else {
+                    median
+                }
+            }
+            _ => compute_exact_centroid(&points)?,
         };
 
         // Get subspace config for recomputation
@@ -1257,17 +2912,7 @@ impl HatIndex {
             let old_centroid = container.centroid.clone();
             let drift = centroid_drift(&old_centroid, &new_centroid);
             container.centroid = new_centroid;
-            container.descendant_count = points.len();
-
-            // Update accumulated sum
-            let sum: Vec<f32> = points.iter()
-                .fold(vec![0.0f32; self.dimensionality], |mut acc, p| {
-                    for (i, &v) in p.dims().iter().enumerate() {
-                        acc[i] += v;
-                    }
-                    acc
-                });
-            container.accumulated_sum = Some(Point::new(sum));
+            container.running = OnlineMerge::from_points(&points);
 
             // Recompute subspace during consolidation if enabled
             if subspace_enabled && container.level != ContainerLevel::Chunk {
@@ -1317,11 +2962,7 @@ impl HatIndex {
 
     /// Find a sibling container to merge with
     fn find_merge_sibling(&self, container_id: Id) -> Option<Id> {
-        // Find parent
-        let parent_id = self.containers.iter()
-            .find(|(_, c)| c.children.contains(&container_id))
-            .map(|(id, _)| *id)?;
-
+        let parent_id = self.containers.get(&container_id)?.parent?;
         let parent = self.containers.get(&parent_id)?;
 
         // Find smallest sibling
@@ -1343,9 +2984,14 @@ impl HatIndex {
 
     /// Merge container B into container A
     fn merge_containers(&mut self, a_id: Id, b_id: Id) {
+        // A may have been merged away earlier in the batch
+        if !self.containers.contains_key(&a_id) {
+            return;
+        }
+
         // Get children from B
-        let b_children: Vec<Id> = if let Some(b) = self.containers.get(&b_id) {
-            b.children.clone()
+        let (b_children, parent_id) = if let Some(b) = self.containers.get(&b_id) {
+            (b.children.clone(), b.parent)
         } else {
             return;
         };
@@ -1354,12 +3000,9 @@ impl HatIndex {
         if let Some(a) = self.containers.get_mut(&a_id) {
             a.children.extend(b_children);
         }
+        self.relink([a_id]);
 
         // Remove B from its parent's children
-        let parent_id = self.containers.iter()
-            .find(|(_, c)| c.children.contains(&b_id))
-            .map(|(id, _)| *id);
-
         if let Some(pid) = parent_id {
             if let Some(parent) = self.containers.get_mut(&pid) {
                 parent.children.retain(|id| *id != b_id);
@@ -1368,6 +3011,10 @@ impl HatIndex {
 
         // Remove B
         self.containers.remove(&b_id);
+        self.payloads.remove(&b_id);
+        self.metadata.remove(&b_id);
+        self.provenance.remove(&b_id);
+        self.summaries.remove(&b_id);
 
         // Recompute A's centroid
         self.recompute_centroid(a_id);
@@ -1378,10 +3025,7 @@ impl HatIndex {
         // Get container info
         let (level, children, parent_id) = {
             let container = self.containers.get(&container_id)?;
-            let parent_id = self.containers.iter()
-                .find(|(_, c)| c.children.contains(&container_id))
-                .map(|(id, _)| *id);
-            (container.level, container.children.clone(), parent_id)
+            (container.level, container.children.clone(), container.parent)
         };
 
         if children.len() < 2 {
@@ -1393,12 +3037,13 @@ impl HatIndex {
         let (keep, move_to_new) = children.split_at(mid);
 
         // Create new container
-        let new_id = Id::now();
-        let new_container = Container::new(
+        let new_id = self.fresh_id();
+        let mut new_container = Container::new(
             new_id,
             level,
             Point::origin(self.dimensionality),
         );
+        new_container.parent = parent_id;
         self.containers.insert(new_id, new_container);
 
         // Update original container
@@ -1410,6 +3055,7 @@ impl HatIndex {
         if let Some(new_container) = self.containers.get_mut(&new_id) {
             new_container.children = move_to_new.to_vec();
         }
+        self.relink([new_id]);
 
         // Add new container to parent
         if let Some(pid) = parent_id {
@@ -1446,9 +3092,7 @@ impl HatIndex {
 
             for id in empty_ids {
                 // Remove from parent's children
-                let parent_id = self.containers.iter()
-                    .find(|(_, c)| c.children.contains(&id))
-                    .map(|(pid, _)| *pid);
+                let parent_id = self.containers.get(&id).and_then(|c| c.parent);
 
                 if let Some(pid) = parent_id {
                     if let Some(parent) = self.containers.get_mut(&pid) {
@@ -1457,6 +3101,10 @@ impl HatIndex {
                 }
 
                 self.containers.remove(&id);
+                self.payloads.remove(&id);
+                self.metadata.remove(&id);
+                self.provenance.remove(&id);
+                self.summaries.remove(&id);
                 pruned += 1;
             }
         }
@@ -1675,7 +3323,118 @@ impl Consolidate for HatIndex {
 // Persistence Implementation
 // =============================================================================
 
+/// Point-in-time view of a `HatIndex` for serializing off the write path
+///
+/// Taking one copies a pointer per container, payload and metadata entry.
+/// The index keeps accepting writes while the snapshot is serialized; only
+/// entries written in the meantime are copied (once each), so the snapshot
+/// still sees the state it was taken at.
+///
+/// # Example
+/// ```rust,ignore
+/// let snapshot = index.read().unwrap().snapshot();   // brief read lock
+/// let saving = snapshot.save_in_background("index.hat");
+/// index.write().unwrap().add(id, &point)?;            // not blocked
+/// saving.join().unwrap()?;
+/// ```
+pub struct HatSnapshot {
+    dimensionality: usize,
+    root_id: Option<Id>,
+    active_session: Option<Id>,
+    active_document: Option<Id>,
+    containers: CowMap<Id, Container>,
+    payloads: CowMap<Id, Blob>,
+    metadata: CowMap<Id, Metadata>,
+    provenance: CowMap<Id, Provenance>,
+    summaries: CowMap<Id, NodeSummary>,
+    facts: CowMap<(String, String), Fact>,
+    router_weights: Option<Vec<f32>>,
+    proximity: String,
+    merge: String,
+}
+
+impl HatSnapshot {
+    fn to_serialized(&self) -> super::persistence::SerializedHat {
+        super::persistence::SerializedHat {
+            version: 1,
+            dimensionality: self.dimensionality as u32,
+            root_id: self.root_id,
+            containers: self.containers.values().map(Container::to_serialized).collect(),
+            active_session: self.active_session,
+            active_document: self.active_document,
+            router_weights: self.router_weights.clone(),
+            payloads: self.payloads.iter()
+                .map(|(id, blob)| (*id, blob.data().to_vec()))
+                .collect(),
+            metadata: self.metadata.iter()
+                .map(|(id, m)| (*id, m.clone()))
+                .collect(),
+            proximity: Some(self.proximity.clone()),
+            merge: Some(self.merge.clone()),
+            has_turns: true,
+            summaries: self.summaries.iter()
+                .map(|(id, s)| (*id, s.text.clone(), s.vector.dims().to_vec()))
+                .collect(),
+            facts: self.facts.values().cloned().collect(),
+            provenance: self.provenance.iter()
+                .map(|(id, p)| (*id, p.clone()))
+                .collect(),
+        }
+    }
+
+    /// Serialize to bytes (same format as `HatIndex::to_bytes`)
+    pub fn to_bytes(&self) -> Result<Vec<u8>, super::persistence::PersistError> {
+        self.to_serialized().to_bytes()
+    }
+
+    /// Write to a file
+    ///
+    /// Writes `<path>.tmp` and renames it over `path`, so a crash mid-save
+    /// leaves the previous file intact.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn save_to_file(&self, path: &std::path::Path) -> Result<(), super::persistence::PersistError> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = std::path::PathBuf::from(tmp);
+
+        let file = std::fs::File::create(&tmp)?;
+        self.to_serialized().write_to(std::io::BufWriter::new(&file))?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Write to a file on a new thread
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn save_in_background(
+        self,
+        path: impl Into<std::path::PathBuf>,
+    ) -> std::thread::JoinHandle<Result<(), super::persistence::PersistError>> {
+        let path = path.into();
+        std::thread::spawn(move || self.save_to_file(&path))
+    }
+}
+
 impl HatIndex {
+    /// Take a point-in-time view for serialization (see `HatSnapshot`)
+    pub fn snapshot(&self) -> HatSnapshot {
+        HatSnapshot {
+            dimensionality: self.dimensionality,
+            root_id: self.root_id,
+            active_session: self.active_session,
+            active_document: self.active_document,
+            containers: self.containers.clone(),
+            payloads: self.payloads.clone(),
+            metadata: self.metadata.clone(),
+            provenance: self.provenance.clone(),
+            summaries: self.summaries.clone(),
+            facts: self.facts.clone(),
+            router_weights: self.learnable_router.as_ref().map(|r| r.weights().to_vec()),
+            proximity: self.proximity.describe(),
+            merge: self.merge.describe(),
+        }
+    }
+
     /// Serialize the index to bytes
     ///
     /// # Example
@@ -1684,105 +3443,417 @@ impl HatIndex {
     /// std::fs::write("index.hat", bytes)?;
     /// ```
     pub fn to_bytes(&self) -> Result<Vec<u8>, super::persistence::PersistError> {
-        use super::persistence::{SerializedHat, SerializedContainer, LevelByte};
-
-        let containers: Vec<SerializedContainer> = self.containers.iter()
-            .map(|(_, c)| {
-                let level = match c.level {
-                    ContainerLevel::Global => LevelByte::Root,
-                    ContainerLevel::Session => LevelByte::Session,
-                    ContainerLevel::Document => LevelByte::Document,
-                    ContainerLevel::Chunk => LevelByte::Chunk,
-                };
+        self.snapshot().to_bytes()
+    }
+
+    /// Serialize one session as a self-contained shard
+    ///
+    /// The shard holds the session with its documents, chunks, payloads
+    /// and metadata in the regular `.hat` format: it loads on its own with
+    /// `from_bytes`, or joins another index with `import_session`.
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// let shard = hat.export_session(session_id)?;
+    /// other.import_session(&shard)?;
+    /// ```
+    pub fn export_session(&self, session_id: Id) -> Result<Vec<u8>, super::persistence::PersistError> {
+        use super::persistence::{SerializedHat, PersistError};
+
+        let session = self.containers
+            .get(&session_id)
+            .filter(|c| c.level == ContainerLevel::Session)
+            .ok_or(PersistError::SessionNotFound(session_id))?;
+
+        let mut ids = vec![session_id];
+        let mut i = 0;
+        while i < ids.len() {
+            if let Some(c) = self.containers.get(&ids[i]) {
+                ids.extend(c.children.iter().filter(|id| self.containers.contains_key(id)));
+            }
+            i += 1;
+        }
+
+        // A root holding just this session, so the shard is a usable index
+        let root_id = self.root_id.unwrap_or_else(Id::now);
+        let mut root = Container::new(root_id, ContainerLevel::Global, session.centroid.clone());
+        root.children = vec![session_id];
+        root.running = session.running.clone();
+
+        let mut containers = vec![root.to_serialized()];
+        containers.extend(ids.iter().map(|id| self.containers[id].to_serialized()));
+
+        let serialized = SerializedHat {
+            version: 1,
+            dimensionality: self.dimensionality as u32,
+            root_id: Some(root.id),
+            containers,
+            active_session: Some(session_id),
+            active_document: None,
+            router_weights: None,
+            payloads: ids.iter()
+                .filter_map(|id| self.payloads.get(id).map(|blob| (*id, blob.data().to_vec())))
+                .collect(),
+            metadata: ids.iter()
+                .filter_map(|id| self.metadata.get(id).map(|m| (*id, m.clone())))
+                .collect(),
+            proximity: Some(self.proximity.describe()),
+            merge: Some(self.merge.describe()),
+            has_turns: true,
+            summaries: ids.iter()
+                .filter_map(|id| self.summaries.get(id).map(|s| (*id, s.text.clone(), s.vector.dims().to_vec())))
+                .collect(),
+            facts: Vec::new(),
+            provenance: ids.iter()
+                .filter_map(|id| self.provenance.get(id).map(|p| (*id, p.clone())))
+                .collect(),
+        };
+
+        serialized.to_bytes()
+    }
+
+    /// Add a session exported with `export_session`, returning its ID
+    ///
+    /// The session joins this index alongside existing ones; the active
+    /// session is unchanged. Centroids are recomputed with this index's
+    /// settings. Fails without changing anything if the shard has another
+    /// dimensionality or reuses an ID already present.
+    pub fn import_session(&mut self, shard: &[u8]) -> Result<Id, super::persistence::PersistError> {
+        use super::persistence::{SerializedHat, PersistError};
+
+        let serialized = SerializedHat::from_bytes(shard)?;
+        if serialized.dimensionality as usize != self.dimensionality {
+            return Err(PersistError::DimensionMismatch {
+                expected: self.dimensionality,
+                found: serialized.dimensionality as usize,
+            });
+        }
+        // Centroids are recomputed here, but stored chunk vectors only make
+        // sense under the proximity they were added with
+        super::persistence::check_descriptor(
+            "proximity",
+            serialized.proximity.as_deref(),
+            self.proximity.describe(),
+        )?;
+        let session_id = serialized.active_session
+            .ok_or_else(|| PersistError::Corrupted("shard has no session".into()))?;
+
+        let mut imported = HashMap::new();
+        for sc in serialized.containers {
+            let container = Container::from_serialized(sc, self.dimensionality)?;
+            if container.level == ContainerLevel::Global {
+                continue;
+            }
+            if self.containers.contains_key(&container.id) {
+                return Err(PersistError::DuplicateId(container.id));
+            }
+            imported.insert(container.id, container);
+        }
+        if !imported.get(&session_id).is_some_and(|c| c.level == ContainerLevel::Session) {
+            return Err(PersistError::Corrupted("shard session is missing".into()));
+        }
+
+        let documents: Vec<Id> = imported.values()
+            .filter(|c| c.level == ContainerLevel::Document)
+            .map(|c| c.id)
+            .collect();
+        let parents: Vec<Id> = imported.keys().copied().collect();
+        self.containers.extend(imported);
+        self.relink(parents);
+        for (id, data) in serialized.payloads {
+            if self.holds_payload(id) {
+                self.payloads.insert(id, Blob::new(data));
+            }
+        }
+        for (id, metadata) in serialized.metadata {
+            if self.containers.get(&id).is_some_and(|c| c.level != ContainerLevel::Global) {
+                self.metadata.insert(id, metadata);
+            }
+        }
+        for (id, provenance) in serialized.provenance {
+            if self.containers.get(&id).is_some_and(|c| c.is_leaf()) {
+                self.provenance.insert(id, provenance);
+            }
+        }
+        self.restore_summaries(serialized.summaries);
+
+        for doc_id in documents {
+            self.recompute_centroid(doc_id);
+        }
+        self.recompute_centroid(session_id);
+
+        // Fold the session into the root without revisiting existing points
+        self.ensure_root();
+        let normalize = self.proximity.normalizes();
+        let session_running = self.containers[&session_id].running.clone();
+        if let Some(root) = self.root_id.and_then(|id| self.containers.get_mut(&id)) {
+            root.children.push(session_id);
+            root.running.combine(&session_running);
+            let centroid = root.running.centroid();
+            root.centroid = if normalize { centroid.normalize() } else { centroid };
+        }
+        self.relink(self.root_id);
+
+        Ok(session_id)
+    }
+
+    /// Remove a session with its documents, chunks, payloads and metadata
+    ///
+    /// Returns the number of chunks removed. Pair with `export_session` to
+    /// move a session out of the index (e.g., to a cold tier).
+    pub fn remove_session(&mut self, session_id: Id) -> NearResult<usize> {
+        if !self.containers.get(&session_id).is_some_and(|c| c.level == ContainerLevel::Session) {
+            return Err(NearError::NotFound(session_id));
+        }
+
+        let mut ids = vec![session_id];
+        let mut i = 0;
+        while i < ids.len() {
+            if let Some(c) = self.containers.get(&ids[i]) {
+                ids.extend(c.children.iter().filter(|id| self.containers.contains_key(id)));
+            }
+            i += 1;
+        }
+
+        let session_running = self.containers[&session_id].running.clone();
+        let mut chunks = 0;
+        for id in &ids {
+            if self.containers.remove(id).is_some_and(|c| c.is_leaf()) {
+                chunks += 1;
+            }
+            self.payloads.remove(id);
+            self.metadata.remove(id);
+            self.provenance.remove(id);
+            self.summaries.remove(id);
+        }
+
+        // Take the session's points back out of the root
+        let normalize = self.proximity.normalizes();
+        if let Some(root) = self.root_id.and_then(|id| self.containers.get_mut(&id)) {
+            root.children.retain(|id| *id != session_id);
+            let sum: Vec<f32> = root.running.sum().iter()
+                .zip(session_running.sum())
+                .map(|(r, s)| r - s)
+                .collect();
+            root.running = OnlineMerge::from_sum(&sum, root.running.count().saturating_sub(session_running.count()));
+            if !root.running.is_empty() {
+                let centroid = root.running.centroid();
+                root.centroid = if normalize { centroid.normalize() } else { centroid };
+            }
+        }
+
+        if self.active_session == Some(session_id) {
+            self.active_session = None;
+            self.active_document = None;
+        }
+        if self.last_chunk.is_some_and(|id| !self.containers.contains_key(&id)) {
+            self.last_chunk = None;
+        }
+
+        Ok(chunks)
+    }
 
-                SerializedContainer {
-                    id: c.id,
-                    level,
-                    timestamp: c.timestamp,
-                    children: c.children.clone(),
-                    descendant_count: c.descendant_count as u64,
-                    centroid: c.centroid.dims().to_vec(),
-                    accumulated_sum: c.accumulated_sum.as_ref().map(|p| p.dims().to_vec()),
+    /// Documents old enough to compress, oldest first (see `compress`)
+    ///
+    /// The active document is never a candidate: it is still being written.
+    pub fn compression_candidates(&self, config: &CompressionConfig) -> Vec<ChunkCluster> {
+        let cutoff = clock::now_ms().saturating_sub(config.older_than_ms);
+        let mut clusters = Vec::new();
+        for session_id in self.containers_at_level(ContainerLevel::Session) {
+            for &doc_id in &self.containers[&session_id].children {
+                if Some(doc_id) == self.active_document {
+                    continue;
                 }
-            })
+                let Some(doc) = self.containers.get(&doc_id) else {
+                    continue;
+                };
+                let chunks: Vec<ClusterChunk> = doc.children.iter()
+                    .filter_map(|id| self.containers.get(id).filter(|c| c.is_leaf()))
+                    .map(|chunk| ClusterChunk {
+                        id: chunk.id,
+                        point: chunk.centroid.clone(),
+                        timestamp: chunk.timestamp,
+                        payload: self.payload(chunk.id).cloned(),
+                    })
+                    .collect();
+                let cluster = ChunkCluster { session: session_id, document: doc_id, chunks };
+                if cluster.chunks.len() >= config.min_chunks && cluster.newest() <= cutoff {
+                    clusters.push(cluster);
+                }
+            }
+        }
+        clusters.sort_by_key(ChunkCluster::newest);
+        clusters.truncate(config.max_clusters.unwrap_or(usize::MAX));
+        clusters
+    }
+
+    /// Replace a cluster's chunks with a summary chunk, returning its ID
+    ///
+    /// The summary chunk takes the chunks' place in their document and turn
+    /// chain, carries `summary.text` as its payload, and keeps the newest
+    /// chunk's timestamp; the text and vector also become the document's
+    /// summary. Chunks removed since the cluster was gathered are skipped.
+    pub fn apply_compression(&mut self, cluster: &ChunkCluster, summary: MemorySummary) -> NearResult<Id> {
+        if !self.containers.get(&cluster.document).is_some_and(|c| c.level == ContainerLevel::Document) {
+            return Err(NearError::NotFound(cluster.document));
+        }
+        if summary.vector.dimensionality() != self.dimensionality {
+            return Err(NearError::DimensionalityMismatch {
+                expected: self.dimensionality,
+                got: summary.vector.dimensionality(),
+            });
+        }
+        let live: Vec<Id> = cluster.chunks.iter()
+            .map(|chunk| chunk.id)
+            .filter(|id| self.containers[&cluster.document].children.contains(id) && self.contains(*id))
             .collect();
+        if live.is_empty() {
+            return Err(NearError::NotFound(cluster.document));
+        }
 
-        let router_weights = self.learnable_router.as_ref()
-            .map(|r| r.weights().to_vec());
+        // Take the chunks out, remembering where the last one sat in the
+        // turn chain (its neighbours are live once the others are spliced out)
+        let mut removed_sum = vec![0.0f32; self.dimensionality];
+        let (mut prev_turn, mut next_turn) = (None, None);
+        for id in &live {
+            let chunk = &self.containers[id];
+            for (s, x) in removed_sum.iter_mut().zip(chunk.centroid.dims()) {
+                *s += x;
+            }
+            (prev_turn, next_turn) = (chunk.prev_turn, chunk.next_turn);
+            self.remove(*id)?;
+        }
 
-        let serialized = SerializedHat {
-            version: 1,
-            dimensionality: self.dimensionality as u32,
-            root_id: self.root_id,
-            containers,
-            active_session: self.active_session,
-            active_document: self.active_document,
-            router_weights,
-        };
+        let id = self.fresh_id();
+        let mut chunk = Container::new(id, ContainerLevel::Chunk, summary.vector.clone());
+        chunk.parent = Some(cluster.document);
+        chunk.timestamp = cluster.newest();
+        chunk.prev_turn = prev_turn;
+        chunk.next_turn = next_turn;
+        if let Some(prev) = prev_turn.and_then(|prev| self.containers.get_mut(&prev)) {
+            prev.next_turn = Some(id);
+        }
+        if let Some(next) = next_turn.and_then(|next| self.containers.get_mut(&next)) {
+            next.prev_turn = Some(id);
+        }
+        self.containers.insert(id, chunk);
+        if let Some(doc) = self.containers.get_mut(&cluster.document) {
+            let at = doc.children.iter().position(|child| *child == live[0]).unwrap_or(doc.children.len());
+            doc.children.insert(at, id);
+            doc.children.retain(|child| !live.contains(child));
+        }
+        if self.last_chunk.is_some_and(|last| live.contains(&last)) {
+            self.last_chunk = Some(id);
+        }
 
-        serialized.to_bytes()
+        // Swap the chunks' points for the summary's in every ancestor's mean
+        let session = self.containers.get(&cluster.document).and_then(|doc| doc.parent);
+        let normalize = self.proximity.normalizes();
+        for ancestor in [Some(cluster.document), session, self.root_id].into_iter().flatten() {
+            let Some(container) = self.containers.get_mut(&ancestor) else {
+                continue;
+            };
+            let sum: Vec<f32> = container.running.sum().iter()
+                .zip(&removed_sum)
+                .zip(summary.vector.dims())
+                .map(|((r, s), v)| r - s + v)
+                .collect();
+            container.running = OnlineMerge::from_sum(&sum, container.running.count().saturating_sub(live.len()) + 1);
+            let centroid = container.running.centroid();
+            container.centroid = if normalize { centroid.normalize() } else { centroid };
+        }
+
+        self.payloads.insert(id, Blob::from_str(&summary.text));
+        self.set_document_summary(cluster.document, summary.text, summary.vector)?;
+        Ok(id)
     }
 
     /// Deserialize an index from bytes
     ///
+    /// The recorded proximity and merge are restored when they are built-in
+    /// (see `proximity::parse` and `merge::parse`); files that predate them
+    /// load with cosine and mean. Anything else fails with
+    /// `PersistError::UnknownConfig` rather than silently changing how the
+    /// index scores - use `from_bytes_with` to supply the functions.
+    ///
     /// # Example
     /// ```rust,ignore
     /// let bytes = std::fs::read("index.hat")?;
     /// let hat = HatIndex::from_bytes(&bytes)?;
     /// ```
     pub fn from_bytes(data: &[u8]) -> Result<Self, super::persistence::PersistError> {
-        use super::persistence::{SerializedHat, LevelByte, PersistError};
-        use crate::core::proximity::Cosine;
-        use crate::core::merge::Mean;
+        use super::persistence::{SerializedHat, PersistError};
+        use crate::core::{merge, proximity};
 
         let serialized = SerializedHat::from_bytes(data)?;
-        let dimensionality = serialized.dimensionality as usize;
+        let proximity = match serialized.proximity.as_deref() {
+            None => Arc::new(proximity::Cosine) as Arc<dyn Proximity>,
+            Some(descriptor) => proximity::parse(descriptor).ok_or_else(|| PersistError::UnknownConfig {
+                field: "proximity",
+                descriptor: descriptor.to_string(),
+            })?,
+        };
+        let merge = match serialized.merge.as_deref() {
+            None => Arc::new(merge::Mean) as Arc<dyn Merge>,
+            Some(descriptor) => merge::parse(descriptor).ok_or_else(|| PersistError::UnknownConfig {
+                field: "merge",
+                descriptor: descriptor.to_string(),
+            })?,
+        };
 
-        // Create a new index with default settings
-        let mut index = Self::new(
-            dimensionality,
-            Arc::new(Cosine),
-            Arc::new(Mean),
-            true,
+        let index = Self::from_proximity(
+            serialized.dimensionality as usize,
+            proximity,
+            merge,
             HatConfig::default(),
         );
+        index.restore(serialized)
+    }
+
+    /// Deserialize an index saved with the given proximity and merge
+    ///
+    /// For functions `from_bytes` can't rebuild (weighted, foreign or
+    /// custom ones). Fails with `PersistError::ConfigMismatch` if the file
+    /// recorded different functions than the ones supplied.
+    pub fn from_bytes_with(
+        data: &[u8],
+        proximity: Arc<dyn Proximity>,
+        merge: Arc<dyn Merge>,
+        config: HatConfig,
+    ) -> Result<Self, super::persistence::PersistError> {
+        use super::persistence::{check_descriptor, SerializedHat};
+
+        let serialized = SerializedHat::from_bytes(data)?;
+        check_descriptor("proximity", serialized.proximity.as_deref(), proximity.describe())?;
+        check_descriptor("merge", serialized.merge.as_deref(), merge.describe())?;
+
+        let index = Self::from_proximity(serialized.dimensionality as usize, proximity, merge, config);
+        index.restore(serialized)
+    }
+
+    /// Fill a fresh index with deserialized state
+    fn restore(
+        self,
+        serialized: super::persistence::SerializedHat,
+    ) -> Result<Self, super::persistence::PersistError> {
+        use super::persistence::PersistError;
+
+        let mut index = self;
+        let dimensionality = index.dimensionality;
 
         // Restore containers
+        let has_turns = serialized.has_turns;
         for sc in serialized.containers {
-            let level = match sc.level {
-                LevelByte::Root => ContainerLevel::Global,
-                LevelByte::Session => ContainerLevel::Session,
-                LevelByte::Document => ContainerLevel::Document,
-                LevelByte::Chunk => ContainerLevel::Chunk,
-            };
-
-            // Verify dimension
-            if sc.centroid.len() != dimensionality {
-                return Err(PersistError::DimensionMismatch {
-                    expected: dimensionality,
-                    found: sc.centroid.len(),
-                });
-            }
-
-            let centroid = Point::new(sc.centroid);
-            let accumulated_sum = sc.accumulated_sum.map(Point::new);
-
-            let container = Container {
-                id: sc.id,
-                level,
-                centroid,
-                timestamp: sc.timestamp,
-                children: sc.children,
-                descendant_count: sc.descendant_count as usize,
-                accumulated_sum,
-                subspace: if level != ContainerLevel::Chunk {
-                    Some(super::subspace::Subspace::new(dimensionality))
-                } else {
-                    None
-                },
-            };
+            let container = Container::from_serialized(sc, dimensionality)?;
+            index.containers.insert(container.id, container);
+        }
+        let parents: Vec<Id> = index.containers.keys().copied().collect();
+        index.relink(parents);
 
-            index.containers.insert(sc.id, container);
+        // Files from before turn links were stored chain each session's
+        // chunks in the order they were added
+        if !has_turns {
+            index.link_turns_in_order();
         }
 
         // Restore state
@@ -1790,6 +3861,27 @@ impl HatIndex {
         index.active_session = serialized.active_session;
         index.active_document = serialized.active_document;
 
+        // Restore payloads (only for chunks and documents that exist)
+        for (id, data) in serialized.payloads {
+            if index.holds_payload(id) {
+                index.payloads.insert(id, Blob::new(data));
+            }
+        }
+        for (id, metadata) in serialized.metadata {
+            if index.containers.get(&id).is_some_and(|c| c.level != ContainerLevel::Global) {
+                index.metadata.insert(id, metadata);
+            }
+        }
+        for (id, provenance) in serialized.provenance {
+            if index.containers.get(&id).is_some_and(|c| c.is_leaf()) {
+                index.provenance.insert(id, provenance);
+            }
+        }
+        index.restore_summaries(serialized.summaries);
+        for fact in serialized.facts {
+            index.facts.insert(fact.key(), fact);
+        }
+
         // Restore router weights if present
         if let Some(weights) = serialized.router_weights {
             let mut router = super::learnable_routing::LearnableRouter::default_for_dims(dimensionality);
@@ -1797,25 +3889,60 @@ impl HatIndex {
                 .flat_map(|w| w.to_le_bytes())
                 .collect();
             router.deserialize_weights(&weight_bytes)
-                .map_err(|e| PersistError::Corrupted(e.to_string()))?;
+                .map_err(|e| PersistError::Corrupted(e.into()))?;
             index.learnable_router = Some(router);
         }
 
         Ok(index)
     }
 
-    /// Save the index to a file
+    /// Take back deserialized summaries (only for sessions and documents that exist)
+    fn restore_summaries(&mut self, summaries: Vec<(Id, String, Vec<f32>)>) {
+        for (id, text, vector) in summaries {
+            let summarized = self.containers
+                .get(&id)
+                .is_some_and(|c| matches!(c.level, ContainerLevel::Session | ContainerLevel::Document));
+            if summarized {
+                self.summaries.insert(id, NodeSummary { text, vector: Point::new(vector) });
+            }
+        }
+    }
+
+    /// Save the index to a file (atomically, see `HatSnapshot::save_to_file`)
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
     pub fn save_to_file(&self, path: &std::path::Path) -> Result<(), super::persistence::PersistError> {
-        let bytes = self.to_bytes()?;
-        std::fs::write(path, bytes)?;
-        Ok(())
+        self.snapshot().save_to_file(path)
+    }
+
+    /// Save the index to a file on a new thread, leaving the index free for writes
+    ///
+    /// The file holds the state at the time of the call.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn save_in_background(
+        &self,
+        path: impl Into<std::path::PathBuf>,
+    ) -> std::thread::JoinHandle<Result<(), super::persistence::PersistError>> {
+        self.snapshot().save_in_background(path)
     }
 
     /// Load an index from a file
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
     pub fn load_from_file(path: &std::path::Path) -> Result<Self, super::persistence::PersistError> {
         let bytes = std::fs::read(path)?;
         Self::from_bytes(&bytes)
     }
+
+    /// Load an index from a file with the given proximity and merge (see `from_bytes_with`)
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn load_from_file_with(
+        path: &std::path::Path,
+        proximity: Arc<dyn Proximity>,
+        merge: Arc<dyn Merge>,
+        config: HatConfig,
+    ) -> Result<Self, super::persistence::PersistError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes_with(&bytes, proximity, merge, config)
+    }
 }
 
 #[cfg(test)]
@@ -1834,6 +3961,51 @@ mod tests {
         assert_eq!(index.len(), 1);
     }
 
+    #[test]
+    fn test_hat_iter() {
+        let mut index = HatIndex::cosine(2);
+        let a = Id::now();
+        let b = Id::now();
+        index.add(a, &Point::new(vec![1.0, 0.0])).unwrap();
+        index.new_session();
+        index.add(b, &Point::new(vec![0.0, 1.0])).unwrap();
+
+        let mut items: Vec<_> = index.iter().collect();
+        items.sort_by_key(|(id, _)| *id);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].0, a);
+        assert_eq!(items[1].1.dims(), &[0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_hat_get_and_payload() {
+        let mut index = HatIndex::cosine(2);
+        let id = Id::now();
+        index.add(id, &Point::new(vec![1.0, 0.0])).unwrap();
+
+        assert!(index.contains(id));
+        assert_eq!(index.get(id).unwrap().dims(), &[1.0, 0.0]);
+        assert!(index.payload(id).is_none());
+
+        index.set_payload(id, Blob::from_str("hello")).unwrap();
+        assert!(index.set_payload(Id::now(), Blob::empty()).is_err());
+
+        index
+            .set_metadata(id, Metadata::from([("turn".to_string(), 3i64.into())]))
+            .unwrap();
+
+        // Payloads and metadata survive a save/load
+        let restored = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.payload(id).unwrap().as_str(), Some("hello"));
+        assert_eq!(restored.metadata(id).unwrap()["turn"], 3i64.into());
+
+        index.remove(id).unwrap();
+        assert!(!index.contains(id));
+        assert!(index.payload(id).is_none());
+        assert!(index.metadata(id).is_none());
+    }
+
     #[test]
     fn test_hat_near() {
         let mut index = HatIndex::cosine(3);
@@ -1859,6 +4031,820 @@ mod tests {
         assert!(results[0].score > 0.5);
     }
 
+    #[test]
+    fn test_hat_euclidean_unnormalized() {
+        let mut index = HatIndex::euclidean(2);
+        assert_eq!(index.proximity_name(), "euclidean");
+
+        // Same direction, different magnitudes: cosine can't tell them apart
+        let near = Id::now();
+        let far = Id::now();
+        index.add(near, &Point::new(vec![1.0, 1.0])).unwrap();
+        index.add(far, &Point::new(vec![10.0, 10.0])).unwrap();
+
+        let results = index.near(&Point::new(vec![1.5, 1.5]), 2).unwrap();
+        assert_eq!(results[0].id, near);
+        assert!(results[0].score < results[1].score);
+
+        // Proximity survives persistence
+        let restored = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.proximity_name(), "euclidean");
+        assert_eq!(restored.near(&Point::new(vec![9.0, 9.0]), 1).unwrap()[0].id, far);
+    }
+
+    #[test]
+    fn test_hat_geometric_median_centroid() {
+        let config = HatConfig::default().with_centroid_method(CentroidMethod::GeometricMedian);
+        let mut index = HatIndex::euclidean(2).with_config(config);
+
+        for dims in [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0], [100.0, 100.0]] {
+            index.add(Id::now(), &Point::new(dims.to_vec())).unwrap();
+        }
+
+        // The outlier chunk doesn't drag the document representative away
+        let doc = &index.containers[&index.active_document.unwrap()];
+        assert_eq!(doc.descendant_count(), 5);
+        assert!(doc.centroid.dims().iter().all(|d| *d < 1.5));
+
+        // Consolidation recomputes the same robust representative
+        let doc_id = doc.id;
+        index.recompute_centroid(doc_id);
+        assert!(index.containers[&doc_id].centroid.dims().iter().all(|d| *d < 1.5));
+    }
+
+    #[test]
+    fn test_hat_auto_segmentation() {
+        let segmentation = SegmentationConfig::new()
+            .with_document_similarity(0.9)
+            .with_session_similarity(0.1)
+            .with_session_gap_ms(60_000);
+        let mut index = HatIndex::cosine(2)
+            .with_config(HatConfig::default().with_segmentation(segmentation));
+
+        // Close chunks share a document
+        index.add(Id::now(), &Point::new(vec![1.0, 0.0])).unwrap();
+        index.add(Id::now(), &Point::new(vec![0.99, 0.1]).normalize()).unwrap();
+        let stats = index.stats();
+        assert_eq!((stats.session_count, stats.document_count), (1, 1));
+
+        // A moderate shift starts a document, an orthogonal one a session
+        index.add(Id::now(), &Point::new(vec![0.7, 0.7]).normalize()).unwrap();
+        index.add(Id::now(), &Point::new(vec![-0.7, 0.7]).normalize()).unwrap();
+        let stats = index.stats();
+        assert_eq!((stats.session_count, stats.document_count), (2, 3));
+    }
+
+    #[test]
+    fn test_hat_named_sessions() {
+        let mut index = HatIndex::cosine(2);
+        let support = index.new_session_named("2024-07-04 support chat");
+        let intro = index.new_document_named("intro");
+        index.add(Id::now(), &Point::new(vec![1.0, 0.0])).unwrap();
+        assert_eq!(index.active_session(), Some(support));
+        assert_eq!(index.active_document(), Some(intro));
+
+        let other = index.new_session_named("standup");
+        index.set_metadata(other, Metadata::from([
+            (NAME_KEY.to_string(), "standup".into()),
+            ("team".to_string(), "infra".into()),
+        ])).unwrap();
+        index.add(Id::now(), &Point::new(vec![0.0, 1.0])).unwrap();
+
+        assert_eq!(index.find_session("2024-07-04 support chat"), Some(support));
+        assert_eq!(index.find_document("intro"), Some(intro));
+        assert_eq!(index.find_session("intro"), None);
+        assert_eq!(index.find_by_metadata("team", &"infra".into()), vec![other]);
+
+        // Names survive a save/load
+        let restored = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.name(support), Some("2024-07-04 support chat"));
+        assert_eq!(restored.find_session("standup"), Some(other));
+    }
+
+    #[test]
+    fn test_hat_turn_links() {
+        let mut index = HatIndex::cosine(2);
+        let turns: Vec<Id> = (0..4).map(|i| Id::seeded(9, i)).collect();
+        index.new_session_named("chat");
+        index.add(turns[0], &Point::new(vec![1.0, 0.0])).unwrap();
+        index.add(turns[1], &Point::new(vec![0.0, 1.0])).unwrap();
+        // Turns chain across documents, but not across sessions
+        index.new_document();
+        index.add(turns[2], &Point::new(vec![1.0, 1.0])).unwrap();
+        index.new_session();
+        index.add(turns[3], &Point::new(vec![1.0, 0.0])).unwrap();
+
+        assert_eq!(index.thread_of(turns[1]), Some(turns[..3].to_vec()));
+        assert_eq!(index.previous_turn(turns[2]), Some(turns[1]));
+        assert_eq!(index.next_turn(turns[2]), None);
+        assert_eq!(index.thread_of(turns[3]), Some(vec![turns[3]]));
+        assert_eq!(index.thread_of(Id::seeded(9, 99)), None);
+
+        // Removing a turn joins its neighbours
+        index.remove(turns[1]).unwrap();
+        assert_eq!(index.thread_of(turns[0]), Some(vec![turns[0], turns[2]]));
+
+        let bytes = index.to_bytes().unwrap();
+        let restored = HatIndex::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.thread_of(turns[2]), Some(vec![turns[0], turns[2]]));
+
+        // Files without the turns section (8-byte count, 48 bytes per linked
+        // chunk, then empty summaries, facts and provenance sections) chain
+        // each session's chunks in hierarchy order
+        let old = HatIndex::from_bytes(&bytes[..bytes.len() - 8 - 2 * 48 - 8 - 8 - 8]).unwrap();
+        assert_eq!(old.thread_of(turns[2]), Some(vec![turns[0], turns[2]]));
+        assert_eq!(old.thread_of(turns[3]), Some(vec![turns[3]]));
+    }
+
+    #[test]
+    fn test_hat_expand_context() {
+        let mut index = HatIndex::cosine(2);
+        let chunks: Vec<Id> = (0..6).map(|i| Id::seeded(8, i)).collect();
+        for (i, id) in chunks.iter().enumerate() {
+            // Chunks 0-3 in one document, 4-5 in the next
+            if i == 4 {
+                index.new_document();
+            }
+            let angle = i as f32 * 0.3;
+            index.add(*id, &Point::new(vec![angle.cos(), angle.sin()])).unwrap();
+            index.set_payload(*id, Blob::from_str(&format!("chunk {}", i))).unwrap();
+        }
+        index.remove(chunks[1]).unwrap();
+
+        let query = Point::new(vec![(0.6f32).cos(), (0.6f32).sin()]);
+        let results = index.near_fields(&query, &QueryParams::new(1).with_expand_context(2)).unwrap();
+        assert_eq!(results[0].id, chunks[2]);
+        let context: Vec<(Id, isize)> = results[0].context.iter().map(|c| (c.id, c.offset)).collect();
+        assert_eq!(context, vec![(chunks[0], -1), (chunks[3], 1)]);
+        assert_eq!(results[0].context[0].blob.as_ref().unwrap().as_str(), Some("chunk 0"));
+
+        let query = Point::new(vec![(1.5f32).cos(), (1.5f32).sin()]);
+        let params = QueryParams::new(1).with_expand_context(1).with_fields(ReturnFields::NONE);
+        let results = index.near_fields(&query, &params).unwrap();
+        assert_eq!(results[0].id, chunks[5]);
+        assert_eq!(results[0].context.len(), 1);
+        assert_eq!((results[0].context[0].id, results[0].context[0].blob.clone()), (chunks[4], None));
+
+        assert!(index.near_fields(&query, &QueryParams::new(1)).unwrap()[0].context.is_empty());
+    }
+
+    #[test]
+    fn test_hat_parent_links() {
+        fn assert_linked(index: &HatIndex) {
+            for container in index.containers.values() {
+                for child in container.children.iter().filter_map(|id| index.containers.get(id)) {
+                    assert_eq!(child.parent, Some(container.id));
+                }
+                match container.parent {
+                    Some(parent) => assert!(index.containers[&parent].children.contains(&container.id)),
+                    None => assert_eq!(index.root_id, Some(container.id)),
+                }
+            }
+        }
+
+        // A lone chunk, a document of six and another lone chunk
+        let mut index = HatIndex::cosine(2);
+        let chunks: Vec<Id> = (0..8).map(|i| Id::seeded(10, i)).collect();
+        for (i, id) in chunks.iter().enumerate() {
+            if i == 1 || i == 7 {
+                index.new_document();
+            }
+            let angle = i as f32 * 0.1;
+            index.add(*id, &Point::new(vec![angle.cos(), angle.sin()])).unwrap();
+        }
+        assert_linked(&index);
+
+        let config = ConsolidationConfig { split_threshold: 4, ..ConsolidationConfig::full() };
+        let metrics = index.consolidate(config);
+        assert!(metrics.containers_merged > 0 && metrics.containers_split > 0);
+        assert_linked(&index);
+
+        // Context comes from the hit's document as it is now
+        let split = index.containers[&chunks[6]].parent.unwrap();
+        let siblings = index.containers[&split].children.clone();
+        let query = Point::new(vec![(0.6f32).cos(), (0.6f32).sin()]);
+        let results = index.near_fields(&query, &QueryParams::new(1).with_expand_context(8)).unwrap();
+        assert_eq!(results[0].id, chunks[6]);
+        assert_eq!(results[0].context.len(), siblings.len() - 1);
+        assert!(results[0].context.iter().all(|c| siblings.contains(&c.id)));
+
+        let loaded = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_linked(&loaded);
+
+        let session = index.sessions()[0];
+        let mut other = HatIndex::cosine(2);
+        other.add(Id::seeded(11, 0), &Point::new(vec![0.0, 1.0])).unwrap();
+        other.import_session(&index.export_session(session).unwrap()).unwrap();
+        assert_linked(&other);
+    }
+
+    #[test]
+    fn test_hat_near_parent_documents() {
+        let mut index = HatIndex::cosine(2);
+        let first = index.new_document_named("first");
+        index.add(Id::seeded(6, 0), &Point::new(vec![1.0, 0.0])).unwrap();
+        index.add(Id::seeded(6, 1), &Point::new(vec![0.95, 0.05])).unwrap();
+        index.set_payload(first, Blob::from_str("whole first document")).unwrap();
+        let second = index.new_document_named("second");
+        index.add(Id::seeded(6, 2), &Point::new(vec![0.0, 1.0])).unwrap();
+
+        // Both chunks of the first document match before the second, but it
+        // comes back once
+        let parents = index.near_parent_documents(&Point::new(vec![1.0, 0.0]), 2).unwrap();
+        assert_eq!(parents.iter().map(|p| p.id).collect::<Vec<_>>(), vec![first, second]);
+        assert_eq!(parents[0].chunk, Id::seeded(6, 0));
+        assert_eq!(parents[0].blob.as_ref().unwrap().as_str(), Some("whole first document"));
+        assert!(parents[1].blob.is_none());
+
+        // Document payloads survive a save/load
+        let restored = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.payload(first).unwrap().as_str(), Some("whole first document"));
+        let session = index.active_session().unwrap();
+        assert!(matches!(index.set_payload(session, Blob::empty()), Err(NearError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_hat_summaries() {
+        let mut index = HatIndex::cosine(2);
+        let travel = index.new_session_named("travel");
+        index.add(Id::seeded(5, 0), &Point::new(vec![1.0, 0.0])).unwrap();
+        let doc = index.active_document().unwrap();
+        let chat = index.new_session_named("chat");
+        index.add(Id::seeded(5, 1), &Point::new(vec![0.6, 0.8])).unwrap();
+
+        // The summary routes a query its centroid alone would miss
+        let query = Point::new(vec![0.0, 1.0]);
+        assert_eq!(index.near_sessions(&query, 1).unwrap()[0].id, chat);
+        index.set_session_summary(travel, "trip planning", Point::new(vec![0.0, 2.0])).unwrap();
+        let top = &index.near_sessions(&query, 1).unwrap()[0];
+        assert_eq!((top.id, top.summary.as_deref()), (travel, Some("trip planning")));
+        assert_eq!(index.summary(travel).unwrap().vector.dims(), &[0.0, 1.0]);
+
+        assert!(matches!(
+            index.set_session_summary(Id::seeded(5, 0), "chunk", query.clone()),
+            Err(NearError::NotFound(_))
+        ));
+        assert!(matches!(
+            index.set_session_summary(chat, "wide", Point::new(vec![1.0, 0.0, 0.0])),
+            Err(NearError::DimensionalityMismatch { .. })
+        ));
+        index.set_document_summary(doc, "flights", query.clone()).unwrap();
+        assert_eq!(index.near_documents(travel, &query, 1).unwrap()[0].summary.as_deref(), Some("flights"));
+
+        let restored = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.summary(travel), index.summary(travel));
+        let shard = index.export_session(travel).unwrap();
+        assert_eq!(HatIndex::from_bytes(&shard).unwrap().summary(doc).unwrap().text, "flights");
+
+        assert_eq!(index.clear_summary(travel).unwrap().text, "trip planning");
+        assert_eq!(index.near_sessions(&query, 1).unwrap()[0].id, chat);
+    }
+
+    #[test]
+    fn test_hat_facts() {
+        let mut index = HatIndex::cosine(2);
+        let (tea, move_) = (Id::seeded(4, 0), Id::seeded(4, 1));
+        index.add(tea, &Point::new(vec![1.0, 0.0])).unwrap();
+        index.add(move_, &Point::new(vec![0.0, 1.0])).unwrap();
+
+        assert_eq!(index.upsert_fact(Fact::new("user", "drinks", "coffee").with_source(tea)).unwrap(), None);
+        let replaced = index.upsert_fact(Fact::new("user", "drinks", "tea").with_confidence(0.9).with_source(tea));
+        assert_eq!(replaced.unwrap().unwrap().value, MetaValue::Str("coffee".into()));
+        index.upsert_fact(Fact::new("user", "city", "Lisbon").with_source(move_)).unwrap();
+        index.upsert_fact(Fact::new("user", "name", "Ana")).unwrap();
+        assert!(matches!(
+            index.upsert_fact(Fact::new("user", "pet", "cat").with_source(Id::seeded(4, 9))),
+            Err(NearError::NotFound(_))
+        ));
+
+        assert_eq!(index.fact_count(), 3);
+        assert!(index.fact("user", "drinks").unwrap().updated_ms > 0);
+        let predicates: Vec<&str> = index.facts_about("user").iter().map(|f| f.predicate.as_str()).collect();
+        assert_eq!(predicates, ["city", "drinks", "name"]);
+
+        // Facts ride along with the hits they were extracted from
+        let hits = index.near(&Point::new(vec![0.1, 1.0]), 2).unwrap();
+        let found: Vec<&str> = index.facts_for(&hits).iter().map(|f| f.predicate.as_str()).collect();
+        assert_eq!(found, ["city", "drinks"]);
+
+        // Facts outlive their source chunk and survive a round trip
+        index.remove(tea).unwrap();
+        let restored = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.fact("user", "drinks"), index.fact("user", "drinks"));
+        assert_eq!(restored.fact_count(), 3);
+
+        assert_eq!(index.remove_fact("user", "name").unwrap().value, MetaValue::Str("Ana".into()));
+        assert_eq!(index.fact("user", "name"), None);
+    }
+
+    #[test]
+    fn test_hat_provenance() {
+        let mut index = HatIndex::cosine(2);
+        let (wiki, forum) = (Id::seeded(5, 0), Id::seeded(5, 1));
+        let trusted = Provenance::new().with_source("wiki/berlin").with_pipeline("crawl-v3").with_model_version("e5-large-v2");
+        index.add_with_provenance(wiki, &Point::new(vec![1.0, 0.0]), trusted.clone().with_confidence(0.95)).unwrap();
+        index.add_with_provenance(forum, &Point::new(vec![1.0, 0.1]), Provenance::new().with_confidence(0.2)).unwrap();
+        assert!(matches!(
+            index.set_provenance(Id::seeded(5, 9), trusted.clone()),
+            Err(NearError::NotFound(_))
+        ));
+
+        // Results carry provenance unless the query leaves it out
+        let query = Point::new(vec![1.0, 0.05]);
+        let hits = index.near_fields(&query, &QueryParams::new(2)).unwrap();
+        let confidences: Vec<f32> = hits.iter().map(|h| h.provenance.as_ref().unwrap().confidence_or(1.0)).collect();
+        assert_eq!(confidences.len(), 2);
+        assert!(confidences.contains(&0.95) && confidences.contains(&0.2));
+        let bare = index.near_fields(&query, &QueryParams::new(2).with_fields(ReturnFields::NONE)).unwrap();
+        assert!(bare.iter().all(|h| h.provenance.is_none()));
+
+        let restored = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.provenance(wiki), index.provenance(wiki));
+        assert_eq!(restored.provenance(wiki).unwrap().pipeline.as_deref(), Some("crawl-v3"));
+
+        // An empty provenance clears it; removing the point drops it
+        index.set_provenance(forum, Provenance::new()).unwrap();
+        assert_eq!(index.provenance(forum), None);
+        index.remove(wiki).unwrap();
+        assert_eq!(index.provenance(wiki), None);
+    }
+
+    #[test]
+    fn test_hat_ephemeral_overlay() {
+        let mut index = HatIndex::cosine(2);
+        let kept = Id::seeded(6, 0);
+        index.add(kept, &Point::new(vec![1.0, 0.0])).unwrap();
+
+        let (plan, aside) = (Id::seeded(6, 1), Id::seeded(6, 2));
+        index.add_ephemeral(EphemeralPoint::new(plan, Point::new(vec![0.0, 1.0])).with_payload(Blob::from_str("plan"))).unwrap();
+        index.add_ephemeral(EphemeralPoint::new(aside, Point::new(vec![0.6, 0.8]))).unwrap();
+        assert!(matches!(
+            index.add_ephemeral(EphemeralPoint::new(kept, Point::new(vec![1.0, 0.0]))),
+            Err(NearError::IndexError(_))
+        ));
+
+        // Searched with long-term memory, but outside the tree
+        let hits = index.near(&Point::new(vec![0.1, 1.0]), 3).unwrap();
+        assert_eq!(hits.iter().map(|h| h.id).collect::<Vec<_>>(), vec![plan, aside, kept]);
+        assert_eq!(index.payload(plan).unwrap().data(), b"plan");
+        assert_eq!((index.len(), index.ephemeral_count()), (1, 2));
+        assert!(!index.contains(plan));
+
+        // Overlay IDs can't be added behind the overlay's back
+        assert!(matches!(index.add(aside, &Point::new(vec![0.6, 0.8])), Err(NearError::IndexError(_))));
+        assert!(!index.contains(aside) && index.ephemeral(aside).is_some());
+
+        // A point the tree refuses stays in the overlay
+        let odd = Id::seeded(6, 3);
+        index.ephemeral.insert(odd, EphemeralPoint::new(odd, Point::new(vec![1.0])));
+        assert!(matches!(index.promote(odd), Err(NearError::DimensionalityMismatch { .. })));
+        assert!(index.remove_ephemeral(odd).is_some());
+
+        // Overlay points are not saved
+        let restored = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.near(&Point::new(vec![0.0, 1.0]), 3).unwrap().len(), 1);
+
+        // At session end, keep the plan and drop the rest
+        let promoted = index.close_ephemeral(|p| p.payload.is_some()).unwrap();
+        assert_eq!(promoted, vec![plan]);
+        assert_eq!((index.len(), index.ephemeral_count()), (2, 0));
+        assert!(index.contains(plan) && !index.contains(aside));
+        assert_eq!(index.payload(plan).unwrap().data(), b"plan");
+        assert!(matches!(index.promote(aside), Err(NearError::NotFound(_))));
+
+        index.add_ephemeral(EphemeralPoint::new(aside, Point::new(vec![0.6, 0.8]))).unwrap();
+        assert_eq!(index.discard_ephemeral(), 1);
+        assert!(index.near(&Point::new(vec![0.6, 0.8]), 3).unwrap().iter().all(|h| h.id != aside));
+    }
+
+    #[test]
+    fn test_hat_contradictions() {
+        let slot = |value: &str| Metadata::from([(SUPERSEDABLE_KEY.to_string(), MetaValue::from(value))]);
+        let check = ContradictionCheck::new(0.9);
+        let mut index = HatIndex::cosine(2);
+        let (paris, spring, chat) = (Id::seeded(3, 0), Id::seeded(3, 1), Id::seeded(3, 2));
+        index.add_checked(paris, &Point::new(vec![1.0, 0.1]), Some(slot("residence")), &check).unwrap();
+        index.add_checked(spring, &Point::new(vec![1.0, 0.12]), Some(slot("travel")), &check).unwrap();
+        // Unmarked memories are never candidates
+        index.add(chat, &Point::new(vec![1.0, 0.11])).unwrap();
+
+        let berlin = Point::new(vec![1.0, 0.15]);
+        let found = index.add_checked(Id::seeded(3, 3), &berlin, Some(slot("residence")), &check).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].id, &found[0].marker), (paris, &MetaValue::from("residence")));
+        assert!(index.contains(Id::seeded(3, 3)));
+
+        // Without a slot, every marked memory close enough is a candidate
+        let ids: Vec<Id> = index.contradictions(&berlin, None, &check).unwrap().iter().map(|c| c.id).collect();
+        assert_eq!(ids.len(), 3);
+        assert!(!ids.contains(&chat));
+        assert!(index.contradictions(&Point::new(vec![0.0, 1.0]), None, &check).unwrap().is_empty());
+        assert_eq!(index.contradictions(&berlin, None, &check.clone().with_k(1)).unwrap().len(), 1);
+
+        index.upsert_fact(Fact::new("user", "city", "Paris")).unwrap();
+        assert!(index.conflicting_fact(&Fact::new("user", "city", "Paris")).is_none());
+        let stored = index.conflicting_fact(&Fact::new("user", "city", "Berlin")).unwrap();
+        assert_eq!(stored.value, MetaValue::from("Paris"));
+    }
+
+    #[test]
+    fn test_hat_export_import_session() {
+        use super::super::persistence::PersistError;
+
+        let mut source = HatIndex::cosine(2);
+        source.add(Id::now(), &Point::new(vec![0.0, 1.0])).unwrap();
+        let session = source.new_session_named("travel");
+        let chunk = Id::now();
+        source.add(chunk, &Point::new(vec![1.0, 0.0])).unwrap();
+        source.set_payload(chunk, Blob::from_str("paris")).unwrap();
+
+        let shard = source.export_session(session).unwrap();
+
+        // The shard stands alone as an index of just that session
+        let alone = HatIndex::from_bytes(&shard).unwrap();
+        assert_eq!(alone.len(), 1);
+
+        let mut target = HatIndex::cosine(2);
+        let local = Id::now();
+        target.add(local, &Point::new(vec![0.0, 1.0])).unwrap();
+        let active = target.active_session();
+
+        assert_eq!(target.import_session(&shard).unwrap(), session);
+        assert_eq!(target.len(), 2);
+        assert_eq!(target.active_session(), active);
+        assert_eq!(target.find_session("travel"), Some(session));
+
+        let results = target.near(&Point::new(vec![1.0, 0.1]), 1).unwrap();
+        assert_eq!(results[0].id, chunk);
+        assert_eq!(target.payload(chunk).unwrap().as_str(), Some("paris"));
+
+        assert!(matches!(target.import_session(&shard), Err(PersistError::DuplicateId(_))));
+        assert!(matches!(
+            target.export_session(local),
+            Err(PersistError::SessionNotFound(_))
+        ));
+        assert!(matches!(
+            HatIndex::cosine(3).import_session(&shard),
+            Err(PersistError::DimensionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_hat_remove_session() {
+        let mut index = HatIndex::cosine(2);
+        let kept = Id::now();
+        index.add(kept, &Point::new(vec![0.0, 1.0])).unwrap();
+        let session = index.new_session_named("old");
+        let chunk = Id::now();
+        index.add(chunk, &Point::new(vec![1.0, 0.0])).unwrap();
+        let shard = index.export_session(session).unwrap();
+
+        assert_eq!(index.remove_session(session).unwrap(), 1);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.active_session(), None);
+        assert_eq!(index.find_session("old"), None);
+        assert_eq!(index.near(&Point::new(vec![1.0, 0.0]), 5).unwrap()[0].id, kept);
+        assert!(matches!(index.remove_session(session), Err(NearError::NotFound(_))));
+
+        // The session can come back from its shard
+        index.import_session(&shard).unwrap();
+        assert_eq!(index.near(&Point::new(vec![1.0, 0.0]), 1).unwrap()[0].id, chunk);
+    }
+
+    #[test]
+    fn test_hat_memory_usage() {
+        let mut index = HatIndex::cosine(8);
+        for i in 0..20 {
+            let id = Id::now();
+            index.add(id, &Point::new(vec![i as f32; 8])).unwrap();
+            index.set_payload(id, Blob::new(vec![0; 50])).unwrap();
+        }
+
+        let usage = index.memory_usage();
+        assert!(usage.vectors >= 20 * 8 * 4);
+        assert!(usage.structure > 0);
+        assert!(usage.metadata >= 20 * 50);
+    }
+
+    #[test]
+    fn test_hat_level_merges() {
+        use crate::core::merge::{MaxPool, MinPool};
+
+        let config = HatConfig::default()
+            .with_document_merge(Arc::new(MaxPool))
+            .with_session_merge(Arc::new(MinPool));
+        let mut index = HatIndex::euclidean(2).with_config(config);
+
+        index.add(Id::now(), &Point::new(vec![1.0, 5.0])).unwrap();
+        index.add(Id::now(), &Point::new(vec![3.0, 2.0])).unwrap();
+        let first_doc = index.active_document.unwrap();
+        index.new_document();
+        index.add(Id::now(), &Point::new(vec![0.0, 9.0])).unwrap();
+
+        // Documents max-pool their chunks, the session min-pools its documents
+        assert_eq!(index.containers[&first_doc].centroid.dims(), &[3.0, 5.0]);
+        let session = &index.containers[&index.active_session.unwrap()];
+        assert_eq!(session.centroid.dims(), &[0.0, 5.0]);
+        assert_eq!(session.descendant_count(), 3);
+    }
+
+    #[test]
+    fn test_hat_near_batch_threads() {
+        let mut index = HatIndex::cosine(3).with_config(HatConfig::default().with_n_threads(4));
+        let ids: Vec<Id> = (0..3).map(|_| Id::now()).collect();
+        let axes = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        for (id, axis) in ids.iter().zip(&axes) {
+            index.add(*id, &Point::new(axis.to_vec())).unwrap();
+        }
+
+        let queries: Vec<Point> = (0..10).map(|i| Point::new(axes[i % 3].to_vec())).collect();
+        let results = index.near_batch(&queries, 1).unwrap();
+
+        assert_eq!(results.len(), 10);
+        for (i, result) in results.iter().enumerate() {
+            assert_eq!(result[0].id, ids[i % 3]);
+        }
+        assert!(index.near_batch(&[Point::new(vec![1.0])], 1).is_err());
+    }
+
+    #[test]
+    fn test_hat_proximity_by_name() {
+        let index = HatIndex::with_proximity_name(3, "dot_product").unwrap();
+        assert_eq!(index.proximity_name(), "dot_product");
+        assert!(HatIndex::with_proximity_name(3, "hamming").is_none());
+    }
+
+    #[test]
+    fn test_hat_snapshot_restores_combinator() {
+        use crate::core::merge::Mean;
+        use crate::core::proximity::{Cosine, Manhattan, Weighted};
+
+        let blend = Weighted::default().with(Cosine, 0.8).with(Manhattan, 0.2);
+        let mut index = HatIndex::from_proximity(2, Arc::new(blend), Arc::new(Mean), HatConfig::default());
+        index.add(Id::now(), &Point::new(vec![1.0, 0.0])).unwrap();
+
+        let restored = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.proximity.describe(), "weighted(cosine*0.8,manhattan*0.2)");
+    }
+
+    #[test]
+    fn test_hat_background_save_keeps_snapshot_state() {
+        let mut index = HatIndex::cosine(2);
+        let kept = Id::now();
+        let removed = Id::now();
+        index.add(kept, &Point::new(vec![1.0, 0.0])).unwrap();
+        index.add(removed, &Point::new(vec![0.0, 1.0])).unwrap();
+        index.set_payload(kept, Blob::from_str("before")).unwrap();
+
+        let path = crate::testing::temp_path("arms-hat-snapshot");
+        let saving = index.snapshot().save_in_background(&path);
+
+        // Writes go ahead while the snapshot is being saved
+        index.set_payload(kept, Blob::from_str("after")).unwrap();
+        index.remove(removed).unwrap();
+        index.add(Id::now(), &Point::new(vec![0.5, 0.5])).unwrap();
+        saving.join().unwrap().unwrap();
+
+        let restored = HatIndex::load_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert!(restored.contains(removed));
+        assert_eq!(restored.payload(kept).and_then(Blob::as_str), Some("before"));
+        assert_eq!(index.payload(kept).and_then(Blob::as_str), Some("after"));
+        assert_eq!(index.len(), 2);
+        assert!(!index.contains(removed));
+    }
+
+    #[test]
+    fn test_hat_snapshot_checks_recorded_config() {
+        use super::super::persistence::PersistError;
+        use crate::core::merge::{Mean, MaxPool};
+        use crate::core::proximity::{Cosine, WeightedCosine};
+
+        let weighted: Arc<dyn Proximity> = Arc::new(WeightedCosine::new(vec![1.0, 0.5]));
+        let mut index = HatIndex::from_proximity(2, weighted.clone(), Arc::new(MaxPool), HatConfig::default());
+        index.add(Id::now(), &Point::new(vec![1.0, 0.0])).unwrap();
+        let bytes = index.to_bytes().unwrap();
+
+        // Not rebuildable from its descriptor: refuse rather than fall back to cosine
+        assert!(matches!(
+            HatIndex::from_bytes(&bytes),
+            Err(PersistError::UnknownConfig { field: "proximity", .. })
+        ));
+
+        let restored = HatIndex::from_bytes_with(&bytes, weighted.clone(), Arc::new(MaxPool), HatConfig::default()).unwrap();
+        assert_eq!(restored.len(), 1);
+
+        assert!(matches!(
+            HatIndex::from_bytes_with(&bytes, Arc::new(Cosine), Arc::new(MaxPool), HatConfig::default()),
+            Err(PersistError::ConfigMismatch { field: "proximity", .. })
+        ));
+        assert!(matches!(
+            HatIndex::from_bytes_with(&bytes, weighted, Arc::new(Mean), HatConfig::default()),
+            Err(PersistError::ConfigMismatch { field: "merge", .. })
+        ));
+
+        // Sessions only move between indexes scoring the same way
+        let mut source = HatIndex::euclidean(2);
+        source.add(Id::now(), &Point::new(vec![1.0, 0.0])).unwrap();
+        let shard = source.export_session(source.active_session().unwrap()).unwrap();
+        assert!(matches!(
+            HatIndex::cosine(2).import_session(&shard),
+            Err(PersistError::ConfigMismatch { field: "proximity", .. })
+        ));
+    }
+
+    #[test]
+    fn test_hat_near_with_params() {
+        let mut index = HatIndex::cosine(3);
+        let ids: Vec<Id> = (0..20)
+            .map(|i| {
+                let id = Id::now();
+                index.add(id, &Point::new(vec![1.0, i as f32 * 0.1, 0.0])).unwrap();
+                id
+            })
+            .collect();
+        let query = Point::new(vec![1.0, 0.0, 0.0]);
+
+        let best = index.near(&query, 1).unwrap()[0].id;
+        let params = QueryParams::new(3).with_beam_width(64).with_filter(move |id| id != best);
+        let results = index.near_with_params(&query, &params).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| r.id != best && ids.contains(&r.id)));
+
+        // A full temporal weight scores on recency alone
+        let recent = index.near_with_params(&query, &QueryParams::new(1).with_temporal_weight(1.0)).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(index.near_with_params(&query, &QueryParams::new(1)).unwrap()[0].id, best);
+
+        let cutoff = index.near(&query, 5).unwrap()[2].score;
+        let results = index.near_with_params(&query, &QueryParams::new(5).with_min_score(cutoff)).unwrap();
+        assert_eq!(results.len(), 3);
+    }
+
+    #[test]
+    fn test_hat_dedup_keeps_best_copy() {
+        let mut index = HatIndex::cosine(2);
+        let best = Id::now();
+        index.add(best, &Point::new(vec![1.0, 0.0])).unwrap();
+        for i in 1..5 {
+            index.add(Id::now(), &Point::new(vec![1.0, i as f32 * 0.001])).unwrap();
+        }
+        let other = Id::now();
+        index.add(other, &Point::new(vec![0.6, 0.8])).unwrap();
+
+        let query = Point::new(vec![1.0, 0.0]);
+        let results = index.near_with_params(&query, &QueryParams::new(2).with_dedup(0.99)).unwrap();
+        let ids: Vec<Id> = results.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![best, other]);
+    }
+
+    #[test]
+    fn test_hat_near_with_roles() {
+        let mut index = HatIndex::cosine(2);
+        let system = Id::now();
+        index.add_with_role(system, &Point::new(vec![1.0, 0.0]), Role::System).unwrap();
+        let answer = Id::now();
+        index.add_with_role(answer, &Point::new(vec![0.9, 0.1]), Role::Assistant).unwrap();
+        let untagged = Id::now();
+        index.add(untagged, &Point::new(vec![0.8, 0.2])).unwrap();
+        assert_eq!(index.role(answer), Some(Role::Assistant));
+        assert_eq!(index.role(untagged), None);
+
+        let query = Point::new(vec![1.0, 0.0]);
+        let params = QueryParams::new(3);
+        let ids = |roles| -> Vec<Id> {
+            index.near_with_roles(&query, &params, roles).unwrap().iter().map(|r| r.id).collect()
+        };
+        assert_eq!(ids(Roles::all()), vec![system, answer, untagged]);
+        assert_eq!(ids(Roles::only([Role::Assistant])), vec![answer]);
+        assert_eq!(ids(Roles::all().without(Role::System)), vec![answer, untagged]);
+
+        // Roles survive a save/load round trip
+        let restored = HatIndex::from_bytes(&index.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.role(system), Some(Role::System));
+        assert_eq!(restored.role(untagged), None);
+        assert!(matches!(index.set_role(Id::nil(), Role::User), Err(NearError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_hat_near_grouped() {
+        let mut index = HatIndex::cosine(3);
+        let mut sessions = Vec::new();
+        let mut first = Vec::new();
+        for axis in 0..3 {
+            if axis > 0 {
+                index.new_session();
+            }
+            for i in 0..4 {
+                let mut dims = vec![0.0; 3];
+                dims[axis] = 1.0;
+                dims[(axis + 1) % 3] = i as f32 * 0.1;
+                let id = Id::now();
+                index.add(id, &Point::new(dims)).unwrap();
+                if axis == 0 {
+                    first.push(id);
+                }
+            }
+            sessions.push(index.active_session().unwrap());
+        }
+        let query = Point::new(vec![1.0, 0.2, 0.0]);
+
+        let groups = index.near_grouped(&query, 2, 3, &GroupBy::Session).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].key, GroupKey::Container(sessions[0]));
+        assert_eq!(groups[0].hits.len(), 3);
+        assert!(groups[0].hits.iter().all(|h| first.contains(&h.id)));
+        assert_ne!(groups[1].key, groups[0].key);
+
+        // Chunk metadata wins over its session's
+        index.set_metadata(sessions[0], Metadata::from([("topic".to_string(), "a".into())])).unwrap();
+        index.set_metadata(first[3], Metadata::from([("topic".to_string(), "b".into())])).unwrap();
+        let groups = index.near_grouped(&query, 5, 5, &GroupBy::Metadata("topic".into())).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].key, GroupKey::Value("a".into()));
+        assert_eq!(groups[0].hits.len(), 3);
+        assert_eq!(groups[1].hits[0].id, first[3]);
+    }
+
+    #[test]
+    fn test_hat_topic_timeline() {
+        let mut index = HatIndex::cosine(2);
+        let a = |i: usize| Point::new(vec![1.0, i as f32 * 0.01]);
+        let b = |i: usize| Point::new(vec![i as f32 * 0.01, 1.0]);
+
+        // Session 1: topic a, a, b (documents); session 2: b
+        for topic in [a, a, b] {
+            index.new_document();
+            for i in 0..3 {
+                index.add(Id::now(), &topic(i)).unwrap();
+            }
+        }
+        let first = index.active_session().unwrap();
+        index.new_session();
+        index.add(Id::now(), &b(9)).unwrap();
+
+        let clustering = index.discover_clusters(&KMeans::new(2).with_seed(3));
+        assert_eq!(clustering.len(), 2);
+        let label_a = clustering.nearest(&a(0)).unwrap().label;
+        let label_b = clustering.nearest(&b(0)).unwrap().label;
+        assert_ne!(label_a, label_b);
+
+        let timeline = index.topic_timeline(&clustering);
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].session, first);
+        let spans: Vec<(usize, usize, usize)> = timeline[0].spans.iter().map(|s| (s.label, s.documents, s.chunks)).collect();
+        assert_eq!(spans, vec![(label_a, 2, 6), (label_b, 1, 3)]);
+        assert_eq!(timeline[1].spans.len(), 1);
+        assert_eq!(timeline[1].spans[0].label, label_b);
+    }
+
+    #[test]
+    fn test_hat_importance_report() {
+        let mut index = HatIndex::cosine(2);
+        let session = index.new_session_named("history");
+        // A recurring theme, one tangent, and the theme's most typical turn
+        for (i, angle) in [0.10f32, 0.15, 1.50, 0.05, 0.12, 0.08].iter().enumerate() {
+            index.add(Id::seeded(6, i as u64), &Point::new(vec![angle.cos(), angle.sin()])).unwrap();
+        }
+
+        let report = index.importance_report(session, 10).unwrap();
+        assert_eq!(report.len(), 6);
+        assert_eq!(report.last().unwrap().id, Id::seeded(6, 2));
+        assert!(report.windows(2).all(|w| w[0].centrality >= w[1].centrality));
+        let total: f32 = report.iter().map(|c| c.centrality).sum();
+        assert!((total - 1.0).abs() < 1e-4);
+
+        assert_eq!(index.importance_report(session, 2).unwrap()[..], report[..2]);
+        assert!(matches!(index.importance_report(Id::seeded(6, 0), 2), Err(NearError::NotFound(_))));
+        let empty = index.new_session_named("empty");
+        assert!(index.importance_report(empty, 2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hat_project_2d() {
+        let mut index = HatIndex::cosine(3);
+        let mut ids = Vec::new();
+        for i in 0..12 {
+            if i == 6 {
+                index.new_session();
+            }
+            let id = Id::now();
+            index.add(id, &Point::new(vec![1.0, i as f32 * 0.1, (i % 3) as f32])).unwrap();
+            ids.push(id);
+        }
+        index.set_metadata(ids[0], Metadata::from([("speaker".to_string(), "user".into())])).unwrap();
+
+        let projection = index.project_2d(100, &Projector::new().with_epochs(20));
+        assert_eq!(projection.len(), 12);
+        let first = projection.points.iter().find(|p| p.id == ids[0]).unwrap();
+        assert_eq!(first.metadata.get("speaker"), Some(&"user".into()));
+        assert!(first.metadata.contains_key("session") && first.metadata.contains_key("document"));
+        assert!(projection.to_csv().starts_with("id,x,y,document,session,speaker\n"));
+
+        assert_eq!(index.project_2d(5, &Projector::new()).len(), 5);
+    }
+
     #[test]
     fn test_hat_sessions() {
         let mut index = HatIndex::cosine(3);
@@ -1904,6 +4890,62 @@ mod tests {
         assert!(index.root_id.is_some());
     }
 
+    #[test]
+    fn test_hat_near_with_deadline() {
+        use crate::ports::CancellationToken;
+
+        let mut index = HatIndex::cosine(2);
+        let a = Id::now();
+        index.add(a, &Point::new(vec![1.0, 0.0])).unwrap();
+        index.add(Id::now(), &Point::new(vec![0.0, 1.0])).unwrap();
+        let query = Point::new(vec![1.0, 0.0]);
+
+        let done = index.near_with_deadline(&query, 1, &Deadline::none()).unwrap();
+        assert!(done.complete);
+        assert_eq!(done.results[0].id, a);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let cut = index.near_with_deadline(&query, 1, &Deadline::none().with_token(token)).unwrap();
+        assert!(!cut.complete);
+        assert!(cut.results.is_empty());
+    }
+
+    #[test]
+    fn test_hat_seeded_containers() {
+        let build = || {
+            let mut index = HatIndex::from_proximity(
+                2,
+                Arc::new(crate::core::proximity::Cosine),
+                Arc::new(crate::core::merge::Mean),
+                HatConfig::default().with_seed(9),
+            );
+            index.add(Id::from_bytes([1; 16]), &Point::new(vec![1.0, 0.0])).unwrap();
+            index.new_session();
+            index.add(Id::from_bytes([2; 16]), &Point::new(vec![0.0, 1.0])).unwrap();
+            (index.active_session(), index.active_document())
+        };
+
+        let (session, document) = build();
+        assert!(session.is_some() && document.is_some());
+        assert_eq!(build(), (session, document));
+
+        // Container IDs don't collide with points seeded alike, and a point
+        // can't take a container's ID
+        let mut index = HatIndex::from_proximity(
+            2,
+            Arc::new(crate::core::proximity::Cosine),
+            Arc::new(crate::core::merge::Mean),
+            HatConfig::default().with_seed(9),
+        );
+        index.add(Id::seeded(9, 0), &Point::new(vec![1.0, 0.0])).unwrap();
+        assert_eq!(index.len(), 1);
+        let root = index.root_id.unwrap();
+        assert_ne!(root, Id::seeded(9, 0));
+        assert!(matches!(index.add(root, &Point::new(vec![0.0, 1.0])), Err(NearError::IndexError(_))));
+        assert_eq!(index.root_id, Some(root));
+    }
+
     #[test]
     fn test_hat_empty() {
         let index = HatIndex::cosine(3);
@@ -1950,4 +4992,46 @@ mod tests {
 
         assert_eq!(results.len(), 10);
     }
+
+    #[test]
+    fn test_hat_consolidate_with_job() {
+        use std::sync::{Arc, RwLock};
+        use super::super::consolidation::{consolidate_shared, ConsolidationLevel};
+        use crate::engine::Job;
+        use crate::ports::JobHandle;
+
+        let mut index = HatIndex::cosine(8);
+        for i in 0..200 {
+            if i % 50 == 0 {
+                index.new_session();
+            }
+            let mut dims = vec![0.1f32; 8];
+            dims[i % 8] = 1.0;
+            index.add(Id::now(), &Point::new(dims).normalize()).unwrap();
+        }
+
+        let job = JobHandle::new();
+        let metrics = index.consolidate_with(ConsolidationConfig::full(), &job);
+        assert!(metrics.is_some());
+        let progress = job.progress();
+        assert_eq!(progress.total, ConsolidationLevel::Full.phase_count() * 1000);
+        assert_eq!(progress.fraction(), 1.0);
+        assert!(!index.is_consolidating());
+
+        let cancelled = JobHandle::new();
+        cancelled.cancel();
+        assert!(index.consolidate_with(ConsolidationConfig::light(), &cancelled).is_none());
+        assert!(!index.is_consolidating());
+        assert_eq!(index.len(), 200);
+
+        // Shared between a background job and readers
+        let shared = Arc::new(RwLock::new(index));
+        let job = Job::spawn("consolidate", {
+            let shared = shared.clone();
+            move |handle: &JobHandle| consolidate_shared(&shared, ConsolidationConfig::deep(), handle)
+        });
+        let query = Point::new(vec![1.0; 8]).normalize();
+        assert_eq!(shared.read().unwrap().near(&query, 3).unwrap().len(), 3);
+        assert!(job.wait().unwrap().is_some());
+    }
 }
@@ -200,7 +200,7 @@ impl LearnableRouter {
     fn should_update(&self) -> bool {
         self.config.learning_rate > 0.0
             && self.feedback_buffer.len() >= self.config.min_samples_to_learn
-            && self.total_samples % self.config.update_frequency == 0
+            && self.total_samples.is_multiple_of(self.config.update_frequency)
     }
 
     /// Update weights based on accumulated feedback
@@ -3,12 +3,28 @@
 //! Implementations of the Near port for different index backends.
 //!
 //! Available adapters:
-//! - `FlatIndex` - Brute force search (exact, O(n) per query)
+//! - `FlatIndex` - Brute force search (exact, O(n) per query) over a `VectorArena`
 //! - `HatIndex` - Hierarchical Attention Tree (approximate, O(log n) per query)
+//! - `FederatedNear` - Fans queries out to several backends and merges results
+//!
+//! Results:
+//! - Query-time collapsing of near-identical results (`QueryParams::dedup`)
+//! - A HAT session overlay of `EphemeralPoint`s, searched but not kept
+//!
+//! Allocation:
+//! - Per-thread scratch buffers reused across queries (`set_retained_bytes`)
+//!
+//! Persistence:
+//! - `HatSnapshot` - Copy-on-write view of a HAT, saved while writes continue
+//! - `BackupManager` - Scheduled, checksummed backups with retention and restore-by-time
+//!
+//! Tiering:
+//! - `ColdTier` - Offloads HAT sessions to an object store (feature `cold-tier`)
 //!
 //! Consolidation support:
 //! - `Consolidate` trait for background maintenance operations
 //! - `ConsolidationConfig` to configure maintenance behavior
+//! - `Summarizer` callbacks that compress old documents into summary chunks
 //!
 //! Subspace support:
 //! - `Subspace` representation for containers capturing variance/spread
@@ -18,19 +34,46 @@
 //! - `LearnableRouter` for adapting routing weights from feedback
 //! - `LearnableRoutingConfig` for configuring online learning
 
+mod arena;
 mod flat;
+mod top_k;
+mod scratch;
 mod hat;
+mod federated;
 mod consolidation;
+mod compression;
 mod subspace;
 mod learnable_routing;
 mod persistence;
+mod cow;
+mod dedup;
+mod backup;
+
+#[cfg(feature = "cold-tier")]
+mod cold_tier;
 
+pub use arena::VectorArena;
 pub use flat::FlatIndex;
-pub use hat::{HatIndex, HatConfig, CentroidMethod, ContainerLevel, SessionSummary, DocumentSummary, HatStats};
+pub use top_k::TopK;
+pub use scratch::{retained_bytes, scratch_bytes, set_retained_bytes, DEFAULT_RETAINED_BYTES};
+pub(crate) use dedup::collapse_duplicates;
+pub use backup::{BackupError, BackupInfo, BackupManager, BackupStore, DirBackupStore, RetentionPolicy};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use backup::BackupSchedule;
+#[cfg(feature = "cold-tier")]
+pub use backup::ObjectBackupStore;
+#[cfg(feature = "cold-tier")]
+pub use cold_tier::{ColdTier, ColdTierError};
+pub use federated::{FederatedNear, FederatedMember, FederatedResults, MemberFailure, ScoreNormalization};
+pub use hat::{HatIndex, HatSnapshot, HatConfig, LevelMerges, SegmentationConfig, NAME_KEY, SUPERSEDABLE_KEY, CentroidMethod, ContainerLevel, ContainerInfo, SessionSummary, DocumentSummary, NodeSummary, ParentDocument, CentralChunk, Contradiction, ContradictionCheck, EphemeralPoint, HatStats, GroupBy, GroupKey, ResultGroup, SessionTimeline, TopicSpan};
 pub use consolidation::{
     Consolidate, ConsolidationConfig, ConsolidationLevel, ConsolidationPhase,
     ConsolidationState, ConsolidationMetrics, ConsolidationProgress, ConsolidationTickResult,
-    compute_exact_centroid, centroid_drift,
+    compute_exact_centroid, centroid_drift, consolidate_shared,
+};
+pub use compression::{
+    ChunkCluster, ClusterChunk, CompressionConfig, CompressionReport, MemorySummary, Summarizer,
+    compress_shared,
 };
 pub use subspace::{
     Subspace, SubspaceConfig, subspace_similarity, combined_subspace_similarity,
@@ -41,5 +84,5 @@ pub use learnable_routing::{
     compute_routing_score,
 };
 pub use persistence::{
-    PersistError, SerializedHat, SerializedContainer, LevelByte,
+    PersistError, SerializedHat, SerializedContainer, LevelByte, HAT_FORMAT_VERSION,
 };
@@ -4,7 +4,8 @@
 //!
 //! ## Format
 //!
-//! The HAT persistence format is a simple binary format:
+//! The HAT persistence format is a simple binary format. Integers and
+//! floats are little-endian on every host:
 //!
 //! ```text
 //! [Header: 32 bytes]
@@ -34,8 +35,53 @@
 //! [Learnable Router Weights: variable, optional]
 //!   - Has weights: u8 (0 or 1)
 //!   - If has weights: dimensionality * 4 bytes (f32s)
+//!
+//! [Payloads: variable, optional]
+//!   - Payload count: u64 (8 bytes)
+//!   - For each payload: ID (16 bytes), length u64 (8 bytes), data
+//!
+//! [Metadata: variable, optional]
+//!   - Entry count: u64 (8 bytes)
+//!   - For each entry: ID (16 bytes), length u64 (8 bytes), encoded metadata
+//!
+//! [Proximity: variable, optional]
+//!   - Descriptor length: u32 (4 bytes, 0 if unrecorded)
+//!   - Descriptor: UTF-8 (e.g. "cosine", "weighted(cosine*0.8,manhattan*0.2)")
+//!
+//! [Merge: variable, optional]
+//!   - Descriptor length: u32 (4 bytes, 0 if unrecorded)
+//!   - Descriptor: UTF-8 (e.g. "mean", "geometric_median(64,0.00001)")
+//!
+//! [Roles: variable, optional]
+//!   - Entry count: u64 (8 bytes)
+//!   - For each chunk with a role: ID (16 bytes), role u8
+//!
+//! [Turns: variable, optional]
+//!   - Entry count: u64 (8 bytes)
+//!   - For each linked chunk: ID (16 bytes), previous turn ID (16 bytes,
+//!     zeros if none), next turn ID (16 bytes, zeros if none)
+//!
+//! [Summaries: variable, optional]
+//!   - Entry count: u64 (8 bytes)
+//!   - For each summarized session or document: ID (16 bytes), text length
+//!     u32 (4 bytes), text (UTF-8), vector (dimensionality * 4 bytes)
+//!
+//! [Facts: variable, optional]
+//!   - Fact count: u64 (8 bytes)
+//!   - For each fact: subject length u32 (4 bytes), subject (UTF-8),
+//!     length u64 (8 bytes) and encoded metadata `{predicate: value}`,
+//!     confidence f32 (4 bytes), source ID (16 bytes, zeros if none),
+//!     updated u64 (8 bytes, ms since epoch)
+//!
+//! [Provenance: variable, optional]
+//!   - Entry count: u64 (8 bytes)
+//!   - For each chunk with provenance: ID (16 bytes), length u64 (8 bytes),
+//!     encoded metadata (`source`, `pipeline`, `model_version`, `confidence`)
 //! ```
 //!
+//! Optional trailing sections are read only if bytes remain, so files
+//! written before a section existed still load.
+//!
 //! ## Usage
 //!
 //! ```rust,ignore
@@ -48,49 +94,54 @@
 //! let hat = HatIndex::from_bytes(&bytes)?;
 //! ```
 
-use crate::core::{Id, Point};
-use std::io::{self, Read, Write, Cursor};
+use crate::core::{Cause, Fact, Id, Provenance};
+use crate::core::bytes::{ByteError, ByteReader};
+use crate::core::metadata::{decode_metadata, encode_metadata, Metadata};
+use std::collections::HashMap;
+use std::io::{self, Write};
 
 /// Magic bytes for HAT file format
 const MAGIC: &[u8; 4] = b"HAT\0";
 
 /// Current format version
-const VERSION: u32 = 1;
+pub const HAT_FORMAT_VERSION: u32 = 1;
 
 /// Error type for persistence operations
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum PersistError {
     /// Invalid magic bytes
+    #[error("Invalid HAT file magic bytes")]
     InvalidMagic,
     /// Unsupported version
+    #[error("Unsupported HAT version: {0}")]
     UnsupportedVersion(u32),
     /// IO error
-    Io(io::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
     /// Data corruption
-    Corrupted(String),
+    #[error("Data corruption: {0}")]
+    Corrupted(#[source] Cause),
     /// Dimension mismatch
+    #[error("Dimension mismatch: expected {expected}, found {found}")]
     DimensionMismatch { expected: usize, found: usize },
+    /// Session to export doesn't exist
+    #[error("Session not found: {0}")]
+    SessionNotFound(Id),
+    /// Imported data reuses an ID already in the index
+    #[error("ID already in index: {0}")]
+    DuplicateId(Id),
+    /// Recorded function isn't built in; load with `from_bytes_with`
+    #[error("Snapshot {field} '{descriptor}' is not built in; supply it with from_bytes_with")]
+    UnknownConfig { field: &'static str, descriptor: String },
+    /// Recorded function differs from the one supplied
+    #[error("Snapshot {field} is '{recorded}', but '{configured}' was supplied")]
+    ConfigMismatch { field: &'static str, recorded: String, configured: String },
 }
 
-impl std::fmt::Display for PersistError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            PersistError::InvalidMagic => write!(f, "Invalid HAT file magic bytes"),
-            PersistError::UnsupportedVersion(v) => write!(f, "Unsupported HAT version: {}", v),
-            PersistError::Io(e) => write!(f, "IO error: {}", e),
-            PersistError::Corrupted(msg) => write!(f, "Data corruption: {}", msg),
-            PersistError::DimensionMismatch { expected, found } => {
-                write!(f, "Dimension mismatch: expected {}, found {}", expected, found)
-            }
-        }
-    }
-}
-
-impl std::error::Error for PersistError {}
-
-impl From<io::Error> for PersistError {
-    fn from(e: io::Error) -> Self {
-        PersistError::Io(e)
+impl From<ByteError> for PersistError {
+    fn from(e: ByteError) -> Self {
+        PersistError::Corrupted(Cause::new(e))
     }
 }
 
@@ -126,6 +177,13 @@ pub struct SerializedContainer {
     pub descendant_count: u64,
     pub centroid: Vec<f32>,
     pub accumulated_sum: Option<Vec<f32>>,
+
+    /// Conversation role of a chunk (`Role` byte), stored in the roles section
+    pub role: Option<u8>,
+
+    /// Neighbouring turns of a chunk in its session, stored in the turns section
+    pub prev_turn: Option<Id>,
+    pub next_turn: Option<Id>,
 }
 
 /// Serialized HAT index
@@ -138,12 +196,34 @@ pub struct SerializedHat {
     pub active_session: Option<Id>,
     pub active_document: Option<Id>,
     pub router_weights: Option<Vec<f32>>,
+    pub payloads: Vec<(Id, Vec<u8>)>,
+    pub metadata: Vec<(Id, Metadata)>,
+    pub proximity: Option<String>,
+    pub merge: Option<String>,
+
+    /// Whether the file had a turns section (older files don't)
+    pub has_turns: bool,
+
+    /// User-provided session and document summaries: ID, text, vector
+    pub summaries: Vec<(Id, String, Vec<f32>)>,
+
+    /// Facts store entries
+    pub facts: Vec<Fact>,
+
+    /// Chunk provenance
+    pub provenance: Vec<(Id, Provenance)>,
 }
 
 impl SerializedHat {
     /// Serialize to bytes
     pub fn to_bytes(&self) -> Result<Vec<u8>, PersistError> {
         let mut buf = Vec::new();
+        self.write_to(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Serialize into a writer (a file, say) without buffering the whole index
+    pub fn write_to<W: Write>(&self, mut buf: W) -> Result<(), PersistError> {
 
         // Header
         buf.write_all(MAGIC)?;
@@ -217,98 +297,136 @@ impl SerializedHat {
             buf.write_all(&[0u8])?;
         }
 
-        Ok(buf)
+        // Payloads
+        buf.write_all(&(self.payloads.len() as u64).to_le_bytes())?;
+        for (id, data) in &self.payloads {
+            buf.write_all(id.as_bytes())?;
+            buf.write_all(&(data.len() as u64).to_le_bytes())?;
+            buf.write_all(data)?;
+        }
+
+        // Metadata
+        buf.write_all(&(self.metadata.len() as u64).to_le_bytes())?;
+        for (id, metadata) in &self.metadata {
+            let encoded = encode_metadata(metadata);
+            buf.write_all(id.as_bytes())?;
+            buf.write_all(&(encoded.len() as u64).to_le_bytes())?;
+            buf.write_all(&encoded)?;
+        }
+
+        // Proximity and merge descriptors
+        for descriptor in [&self.proximity, &self.merge] {
+            let descriptor = descriptor.as_deref().unwrap_or("");
+            buf.write_all(&(descriptor.len() as u32).to_le_bytes())?;
+            buf.write_all(descriptor.as_bytes())?;
+        }
+
+        // Roles
+        let roles: Vec<(Id, u8)> = self.containers
+            .iter()
+            .filter_map(|c| Some((c.id, c.role?)))
+            .collect();
+        buf.write_all(&(roles.len() as u64).to_le_bytes())?;
+        for (id, role) in roles {
+            buf.write_all(id.as_bytes())?;
+            buf.write_all(&[role])?;
+        }
+
+        // Turns
+        let turns: Vec<&SerializedContainer> = self.containers
+            .iter()
+            .filter(|c| c.prev_turn.is_some() || c.next_turn.is_some())
+            .collect();
+        buf.write_all(&(turns.len() as u64).to_le_bytes())?;
+        for c in turns {
+            buf.write_all(c.id.as_bytes())?;
+            for link in [c.prev_turn, c.next_turn] {
+                buf.write_all(link.as_ref().map_or(&[0u8; 16], |id| id.as_bytes()))?;
+            }
+        }
+
+        // Summaries
+        buf.write_all(&(self.summaries.len() as u64).to_le_bytes())?;
+        for (id, text, vector) in &self.summaries {
+            buf.write_all(id.as_bytes())?;
+            buf.write_all(&(text.len() as u32).to_le_bytes())?;
+            buf.write_all(text.as_bytes())?;
+            for &v in vector {
+                buf.write_all(&v.to_le_bytes())?;
+            }
+        }
+
+        // Facts
+        buf.write_all(&(self.facts.len() as u64).to_le_bytes())?;
+        for fact in &self.facts {
+            let encoded = encode_metadata(&Metadata::from([(fact.predicate.clone(), fact.value.clone())]));
+            buf.write_all(&(fact.subject.len() as u32).to_le_bytes())?;
+            buf.write_all(fact.subject.as_bytes())?;
+            buf.write_all(&(encoded.len() as u64).to_le_bytes())?;
+            buf.write_all(&encoded)?;
+            buf.write_all(&fact.confidence.to_le_bytes())?;
+            buf.write_all(fact.source.as_ref().map_or(&[0u8; 16], |id| id.as_bytes()))?;
+            buf.write_all(&fact.updated_ms.to_le_bytes())?;
+        }
+
+        // Provenance
+        buf.write_all(&(self.provenance.len() as u64).to_le_bytes())?;
+        for (id, provenance) in &self.provenance {
+            let encoded = encode_metadata(&provenance.to_metadata());
+            buf.write_all(id.as_bytes())?;
+            buf.write_all(&(encoded.len() as u64).to_le_bytes())?;
+            buf.write_all(&encoded)?;
+        }
+
+        buf.flush()?;
+        Ok(())
     }
 
     /// Deserialize from bytes
+    ///
+    /// Safe on untrusted input: every count is checked against the bytes
+    /// left before allocating, so a corrupt file fails with `Corrupted`.
     pub fn from_bytes(data: &[u8]) -> Result<Self, PersistError> {
-        let mut cursor = Cursor::new(data);
+        let mut r = ByteReader::new(data);
 
         // Read header
-        let mut magic = [0u8; 4];
-        cursor.read_exact(&mut magic)?;
-        if &magic != MAGIC {
+        if &r.array::<4>("magic")? != MAGIC {
             return Err(PersistError::InvalidMagic);
         }
-
-        let mut version_bytes = [0u8; 4];
-        cursor.read_exact(&mut version_bytes)?;
-        let version = u32::from_le_bytes(version_bytes);
-        if version != VERSION {
+        let version = r.u32("version")?;
+        if version != HAT_FORMAT_VERSION {
             return Err(PersistError::UnsupportedVersion(version));
         }
-
-        let mut dims_bytes = [0u8; 4];
-        cursor.read_exact(&mut dims_bytes)?;
-        let dimensionality = u32::from_le_bytes(dims_bytes);
-
-        let mut count_bytes = [0u8; 8];
-        cursor.read_exact(&mut count_bytes)?;
-        let container_count = u64::from_le_bytes(count_bytes);
-
-        let mut root_bytes = [0u8; 16];
-        cursor.read_exact(&mut root_bytes)?;
-        let root_id = if root_bytes == [0u8; 16] {
-            None
-        } else {
-            Some(Id::from_bytes(root_bytes))
-        };
-
-        // Read containers
-        let mut containers = Vec::with_capacity(container_count as usize);
+        let dimensionality = r.u32("dimensionality")?;
+        let dims = dimensionality as usize;
+        let container_count = r.u64("container count")?;
+        let root_id = read_id(&mut r, "root ID")?;
+
+        // Read containers (each at least ID, level, timestamp, child count,
+        // descendant count, centroid and sum flag)
+        let container_size = 16 + 1 + 8 + 4 + 8 + dims.saturating_mul(4) + 1;
+        let container_count = r.count(container_count, container_size, "containers")?;
+        let mut containers = Vec::with_capacity(container_count);
         for _ in 0..container_count {
-            // ID
-            let mut id_bytes = [0u8; 16];
-            cursor.read_exact(&mut id_bytes)?;
-            let id = Id::from_bytes(id_bytes);
+            let id = Id::from_bytes(r.array("container ID")?);
 
-            // Level
-            let mut level_byte = [0u8; 1];
-            cursor.read_exact(&mut level_byte)?;
-            let level = LevelByte::from_u8(level_byte[0])
-                .ok_or_else(|| PersistError::Corrupted(format!("Invalid level: {}", level_byte[0])))?;
+            let level_byte = r.u8("level")?;
+            let level = LevelByte::from_u8(level_byte)
+                .ok_or_else(|| PersistError::Corrupted(format!("Invalid level: {}", level_byte).into()))?;
 
-            // Timestamp
-            let mut ts_bytes = [0u8; 8];
-            cursor.read_exact(&mut ts_bytes)?;
-            let timestamp = u64::from_le_bytes(ts_bytes);
-
-            // Children
-            let mut child_count_bytes = [0u8; 4];
-            cursor.read_exact(&mut child_count_bytes)?;
-            let child_count = u32::from_le_bytes(child_count_bytes) as usize;
-
-            let mut children = Vec::with_capacity(child_count);
-            for _ in 0..child_count {
-                let mut child_bytes = [0u8; 16];
-                cursor.read_exact(&mut child_bytes)?;
-                children.push(Id::from_bytes(child_bytes));
-            }
+            let timestamp = r.u64("timestamp")?;
 
-            // Descendant count
-            let mut desc_bytes = [0u8; 8];
-            cursor.read_exact(&mut desc_bytes)?;
-            let descendant_count = u64::from_le_bytes(desc_bytes);
+            let child_count = r.u32("child count")?;
+            let child_count = r.count(child_count as u64, 16, "children")?;
+            let children = (0..child_count)
+                .map(|_| r.array("child ID").map(Id::from_bytes))
+                .collect::<Result<Vec<_>, _>>()?;
 
-            // Centroid
-            let mut centroid = Vec::with_capacity(dimensionality as usize);
-            for _ in 0..dimensionality {
-                let mut v_bytes = [0u8; 4];
-                cursor.read_exact(&mut v_bytes)?;
-                centroid.push(f32::from_le_bytes(v_bytes));
-            }
+            let descendant_count = r.u64("descendant count")?;
+            let centroid = r.f32s(dims, "centroid")?;
 
-            // Accumulated sum
-            let mut has_sum = [0u8; 1];
-            cursor.read_exact(&mut has_sum)?;
-            let accumulated_sum = if has_sum[0] == 1 {
-                let mut sum = Vec::with_capacity(dimensionality as usize);
-                for _ in 0..dimensionality {
-                    let mut v_bytes = [0u8; 4];
-                    cursor.read_exact(&mut v_bytes)?;
-                    sum.push(f32::from_le_bytes(v_bytes));
-                }
-                Some(sum)
+            let accumulated_sum = if r.u8("accumulated sum flag")? == 1 {
+                Some(r.f32s(dims, "accumulated sum")?)
             } else {
                 None
             };
@@ -321,44 +439,150 @@ impl SerializedHat {
                 descendant_count,
                 centroid,
                 accumulated_sum,
+                role: None,
+                prev_turn: None,
+                next_turn: None,
             });
         }
 
         // Active state
-        let mut active_session_bytes = [0u8; 16];
-        cursor.read_exact(&mut active_session_bytes)?;
-        let active_session = if active_session_bytes == [0u8; 16] {
-            None
-        } else {
-            Some(Id::from_bytes(active_session_bytes))
-        };
+        let active_session = read_id(&mut r, "active session")?;
+        let active_document = read_id(&mut r, "active document")?;
 
-        let mut active_document_bytes = [0u8; 16];
-        cursor.read_exact(&mut active_document_bytes)?;
-        let active_document = if active_document_bytes == [0u8; 16] {
-            None
+        // Router weights (optional - may not be present in older files)
+        let router_weights = if !r.is_empty() && r.u8("router weights flag")? == 1 {
+            Some(r.f32s(dims, "router weights")?)
         } else {
-            Some(Id::from_bytes(active_document_bytes))
+            None
         };
 
-        // Router weights (optional - may not be present in older files)
-        let router_weights = if cursor.position() < data.len() as u64 {
-            let mut has_weights = [0u8; 1];
-            cursor.read_exact(&mut has_weights)?;
-            if has_weights[0] == 1 {
-                let mut weights = Vec::with_capacity(dimensionality as usize);
-                for _ in 0..dimensionality {
-                    let mut w_bytes = [0u8; 4];
-                    cursor.read_exact(&mut w_bytes)?;
-                    weights.push(f32::from_le_bytes(w_bytes));
+        // Payloads (optional - may not be present in older files)
+        let mut payloads = Vec::new();
+        if !r.is_empty() {
+            let payload_count = r.u64("payload count")?;
+            let payload_count = r.count(payload_count, 16 + 8, "payloads")?;
+            payloads.reserve(payload_count);
+            for _ in 0..payload_count {
+                let id = Id::from_bytes(r.array("payload ID")?);
+                let len = r.u64("payload length")?;
+                payloads.push((id, r.bytes_u64(len, "payload")?.to_vec()));
+            }
+        }
+
+        // Metadata (optional - may not be present in older files)
+        let mut metadata = Vec::new();
+        if !r.is_empty() {
+            let entry_count = r.u64("metadata count")?;
+            let entry_count = r.count(entry_count, 16 + 8, "metadata")?;
+            metadata.reserve(entry_count);
+            for _ in 0..entry_count {
+                let id = Id::from_bytes(r.array("metadata ID")?);
+                let len = r.u64("metadata length")?;
+                let encoded = r.bytes_u64(len, "metadata")?;
+                let (entry, _) = decode_metadata(encoded)
+                    .filter(|(_, used)| *used == encoded.len())
+                    .ok_or_else(|| PersistError::Corrupted("Invalid metadata".into()))?;
+                metadata.push((id, entry));
+            }
+        }
+
+        // Proximity and merge (optional - may not be present in older files)
+        let proximity = read_descriptor(&mut r, "proximity")?;
+        let merge = read_descriptor(&mut r, "merge")?;
+
+        // Roles (optional - may not be present in older files)
+        let positions: HashMap<Id, usize> = containers.iter().enumerate().map(|(i, c)| (c.id, i)).collect();
+        if !r.is_empty() {
+            let entry_count = r.u64("role count")?;
+            let entry_count = r.count(entry_count, 17, "roles")?;
+
+            for _ in 0..entry_count {
+                let id = Id::from_bytes(r.array("role ID")?);
+                let role = r.u8("role")?;
+                if let Some(&i) = positions.get(&id) {
+                    containers[i].role = Some(role);
                 }
-                Some(weights)
-            } else {
-                None
             }
-        } else {
-            None
-        };
+        }
+
+        // Turns (optional - may not be present in older files)
+        let has_turns = !r.is_empty();
+        if has_turns {
+            let entry_count = r.u64("turn count")?;
+            let entry_count = r.count(entry_count, 48, "turns")?;
+
+            for _ in 0..entry_count {
+                let id = Id::from_bytes(r.array("turn ID")?);
+                let prev = read_id(&mut r, "previous turn")?;
+                let next = read_id(&mut r, "next turn")?;
+                if let Some(&i) = positions.get(&id) {
+                    containers[i].prev_turn = prev;
+                    containers[i].next_turn = next;
+                }
+            }
+        }
+
+        // Summaries (optional - may not be present in older files)
+        let mut summaries = Vec::new();
+        if !r.is_empty() {
+            let entry_count = r.u64("summary count")?;
+            let entry_count = r.count(entry_count, 16 + 4 + dims.saturating_mul(4), "summaries")?;
+            summaries.reserve(entry_count);
+            for _ in 0..entry_count {
+                let id = Id::from_bytes(r.array("summary ID")?);
+                let text = r.str("summary text").map_err(|e| match e {
+                    ByteError::InvalidUtf8 { .. } => PersistError::Corrupted("Invalid summary text".into()),
+                    e => e.into(),
+                })?;
+                summaries.push((id, text.to_string(), r.f32s(dims, "summary vector")?));
+            }
+        }
+
+        // Facts (optional - may not be present in older files)
+        let mut facts = Vec::new();
+        if !r.is_empty() {
+            let fact_count = r.u64("fact count")?;
+            let fact_count = r.count(fact_count, 4 + 8 + 4 + 16 + 8, "facts")?;
+            facts.reserve(fact_count);
+            for _ in 0..fact_count {
+                let subject = r.str("fact subject").map_err(|e| match e {
+                    ByteError::InvalidUtf8 { .. } => PersistError::Corrupted("Invalid fact subject".into()),
+                    e => e.into(),
+                })?;
+                let len = r.u64("fact length")?;
+                let encoded = r.bytes_u64(len, "fact")?;
+                let (predicate, value) = decode_metadata(encoded)
+                    .filter(|(entry, used)| *used == encoded.len() && entry.len() == 1)
+                    .and_then(|(entry, _)| entry.into_iter().next())
+                    .ok_or_else(|| PersistError::Corrupted("Invalid fact".into()))?;
+                facts.push(Fact {
+                    subject: subject.to_string(),
+                    predicate,
+                    value,
+                    confidence: r.f32("fact confidence")?,
+                    source: read_id(&mut r, "fact source")?,
+                    updated_ms: r.u64("fact updated")?,
+                });
+            }
+        }
+
+        // Provenance (optional - may not be present in older files)
+        let mut provenance = Vec::new();
+        if !r.is_empty() {
+            let entry_count = r.u64("provenance count")?;
+            let entry_count = r.count(entry_count, 16 + 8, "provenance")?;
+            provenance.reserve(entry_count);
+            for _ in 0..entry_count {
+                let id = Id::from_bytes(r.array("provenance ID")?);
+                let len = r.u64("provenance length")?;
+                let encoded = r.bytes_u64(len, "provenance")?;
+                let entry = decode_metadata(encoded)
+                    .filter(|(_, used)| *used == encoded.len())
+                    .and_then(|(metadata, _)| Provenance::from_metadata(&metadata))
+                    .ok_or_else(|| PersistError::Corrupted("Invalid provenance".into()))?;
+                provenance.push((id, entry));
+            }
+        }
 
         Ok(SerializedHat {
             version,
@@ -368,16 +592,50 @@ impl SerializedHat {
             active_session,
             active_document,
             router_weights,
+            payloads,
+            metadata,
+            proximity,
+            merge,
+            has_turns,
+            summaries,
+            facts,
+            provenance,
         })
     }
 }
 
-/// Helper to read ID from Option
-fn id_to_bytes(id: &Option<Id>) -> [u8; 16] {
-    match id {
-        Some(id) => *id.as_bytes(),
-        None => [0u8; 16],
+/// Fail if a recorded descriptor differs from the configured one
+///
+/// Files that predate descriptors record nothing and always pass.
+pub(crate) fn check_descriptor(field: &'static str, recorded: Option<&str>, configured: String) -> Result<(), PersistError> {
+    match recorded {
+        Some(recorded) if recorded != configured => Err(PersistError::ConfigMismatch {
+            field,
+            recorded: recorded.to_string(),
+            configured,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Read an ID written as zeros when absent
+fn read_id(r: &mut ByteReader<'_>, what: &str) -> Result<Option<Id>, PersistError> {
+    let bytes = r.array::<16>(what)?;
+    Ok((bytes != [0u8; 16]).then(|| Id::from_bytes(bytes)))
+}
+
+/// Read a length-prefixed descriptor, if any bytes remain
+fn read_descriptor(r: &mut ByteReader<'_>, field: &str) -> Result<Option<String>, PersistError> {
+    if r.is_empty() {
+        return Ok(None);
     }
+    let descriptor = r
+        .str(&format!("{} descriptor", field))
+        .map_err(|e| match e {
+            ByteError::InvalidUtf8 { .. } => PersistError::Corrupted(format!("Invalid {} descriptor", field).into()),
+            e => e.into(),
+        })?;
+    Ok((!descriptor.is_empty()).then(|| descriptor.to_string()))
 }
 
 #[cfg(test)]
@@ -387,7 +645,7 @@ mod tests {
     #[test]
     fn test_serialized_hat_roundtrip() {
         let original = SerializedHat {
-            version: VERSION,
+            version: HAT_FORMAT_VERSION,
             dimensionality: 128,
             root_id: Some(Id::now()),
             containers: vec![
@@ -399,6 +657,9 @@ mod tests {
                     descendant_count: 10,
                     centroid: vec![0.1; 128],
                     accumulated_sum: None,
+                    role: None,
+                    prev_turn: None,
+                    next_turn: None,
                 },
                 SerializedContainer {
                     id: Id::now(),
@@ -408,11 +669,25 @@ mod tests {
                     descendant_count: 1,
                     centroid: vec![0.5; 128],
                     accumulated_sum: Some(vec![0.5; 128]),
+                    role: Some(2),
+                    prev_turn: None,
+                    next_turn: Some(Id::seeded(1, 1)),
                 },
             ],
             active_session: Some(Id::now()),
             active_document: None,
             router_weights: Some(vec![1.0; 128]),
+            payloads: vec![(Id::now(), b"payload".to_vec())],
+            metadata: vec![(Id::now(), Metadata::from([("user".to_string(), "ana".into())]))],
+            proximity: Some("euclidean".into()),
+            merge: Some("geometric_median(64,0.00001)".into()),
+            has_turns: true,
+            summaries: vec![(Id::seeded(1, 2), "trip planning".into(), vec![0.25; 128])],
+            facts: vec![
+                Fact::new("user", "prefers", "tea").with_confidence(0.8).with_source(Id::seeded(1, 3)),
+                Fact { updated_ms: 42, ..Fact::new("user", "age", 41i64) },
+            ],
+            provenance: vec![(Id::seeded(1, 4), Provenance::new().with_source("wiki").with_confidence(0.5))],
         };
 
         let bytes = original.to_bytes().unwrap();
@@ -421,7 +696,49 @@ mod tests {
         assert_eq!(restored.version, original.version);
         assert_eq!(restored.dimensionality, original.dimensionality);
         assert_eq!(restored.containers.len(), original.containers.len());
+        assert_eq!(restored.containers[0].role, None);
+        assert_eq!(restored.containers[1].role, Some(2));
+        assert_eq!(restored.containers[1].next_turn, Some(Id::seeded(1, 1)));
+        assert!(restored.has_turns);
+        assert_eq!(restored.summaries, original.summaries);
+        assert_eq!(restored.facts, original.facts);
+        assert_eq!(restored.provenance, original.provenance);
         assert!(restored.router_weights.is_some());
+        assert_eq!(restored.payloads, original.payloads);
+        assert_eq!(restored.metadata, original.metadata);
+        assert_eq!(restored.proximity, original.proximity);
+        assert_eq!(restored.merge, original.merge);
+    }
+
+    #[test]
+    fn test_missing_payload_section() {
+        let original = SerializedHat {
+            version: HAT_FORMAT_VERSION,
+            dimensionality: 4,
+            root_id: None,
+            containers: vec![],
+            active_session: None,
+            active_document: None,
+            router_weights: None,
+            payloads: vec![],
+            metadata: vec![],
+            proximity: None,
+            merge: None,
+            has_turns: true,
+            summaries: vec![],
+            facts: vec![],
+            provenance: vec![],
+        };
+
+        // Files written before the payload section existed end after router weights
+        let mut bytes = original.to_bytes().unwrap();
+        bytes.truncate(bytes.len() - 64);
+
+        let restored = SerializedHat::from_bytes(&bytes).unwrap();
+        assert!(restored.payloads.is_empty());
+        assert!(!restored.has_turns);
+        assert!(restored.proximity.is_none());
+        assert!(restored.merge.is_none());
     }
 
     #[test]
@@ -431,6 +748,52 @@ mod tests {
         assert!(matches!(result, Err(PersistError::InvalidMagic)));
     }
 
+    #[test]
+    fn test_hostile_counts_and_truncation() {
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&HAT_FORMAT_VERSION.to_le_bytes());
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+        header.extend_from_slice(&u64::MAX.to_le_bytes());
+        header.extend_from_slice(&[0u8; 16]);
+        assert!(matches!(SerializedHat::from_bytes(&header), Err(PersistError::Corrupted(_))));
+
+        let original = SerializedHat {
+            version: HAT_FORMAT_VERSION,
+            dimensionality: 2,
+            root_id: Some(Id::now()),
+            containers: vec![SerializedContainer {
+                id: Id::now(),
+                level: LevelByte::Chunk,
+                timestamp: 1,
+                children: vec![],
+                descendant_count: 1,
+                centroid: vec![0.5, 0.5],
+                accumulated_sum: None,
+                role: None,
+                prev_turn: None,
+                next_turn: None,
+            }],
+            active_session: None,
+            active_document: None,
+            router_weights: None,
+            payloads: vec![(Id::now(), vec![1, 2, 3])],
+            metadata: vec![],
+            proximity: Some("cosine".into()),
+            merge: None,
+            has_turns: true,
+            summaries: vec![],
+            facts: vec![],
+            provenance: vec![],
+        };
+        let bytes = original.to_bytes().unwrap();
+        for len in 0..bytes.len() {
+            // Every prefix either loads (a trailing section is missing) or fails cleanly
+            let _ = SerializedHat::from_bytes(&bytes[..len]);
+        }
+        let mid_container = &bytes[..40];
+        assert!(matches!(SerializedHat::from_bytes(mid_container), Err(PersistError::Corrupted(_))));
+    }
+
     #[test]
     fn test_level_byte_conversion() {
         assert_eq!(LevelByte::from_u8(0), Some(LevelByte::Root));
@@ -0,0 +1,157 @@
+//! # Scratch Buffers
+//!
+//! Reusable per-thread buffers for short-lived search allocations.
+//!
+//! Every query used to allocate its candidate lists, score buffers and
+//! heap from scratch and free them on return. At high QPS that's most of
+//! the allocator traffic. Index adapters instead borrow this thread's
+//! `Scratch`, clear the buffers they use, and hand them back grown to the
+//! size the workload needs:
+//!
+//! ```text
+//! query 1: alloc ids/scores/heap ──▶ keep
+//! query 2: reuse ──────────────────▶ keep
+//! query n: reuse (no allocation besides the returned results)
+//! ```
+//!
+//! Buffers larger than the retain limit (`set_retained_bytes`, 1 MiB per
+//! thread by default) are released after use, so one huge query doesn't pin
+//! memory for the life of the thread. A limit of 0 disables reuse.
+
+use std::cell::{Cell, RefCell};
+
+use super::top_k::Ranked;
+use crate::core::Id;
+
+/// Default per-thread retain limit in bytes
+pub const DEFAULT_RETAINED_BYTES: usize = 1 << 20;
+
+thread_local! {
+    static SCRATCH: RefCell<Scratch> = RefCell::new(Scratch::default());
+    static RETAINED_BYTES: Cell<usize> = const { Cell::new(DEFAULT_RETAINED_BYTES) };
+}
+
+/// Search buffers reused across queries on one thread
+#[derive(Default)]
+pub struct Scratch {
+    /// IDs of a batch of rows being scored
+    pub(crate) ids: Vec<Id>,
+
+    /// Scores of a batch of rows
+    pub(crate) scores: Vec<f32>,
+
+    /// Rows of a batch, stored empty between uses (see `recycle`)
+    pub(crate) rows: Vec<&'static [f32]>,
+
+    /// Heap storage for `TopK`
+    pub(crate) ranked: Vec<Ranked>,
+
+    /// Containers to expand at the current tree level
+    pub(crate) frontier: Vec<Id>,
+
+    /// Scored candidates for the next tree level
+    pub(crate) candidates: Vec<(Id, f32)>,
+}
+
+impl Scratch {
+    /// Heap bytes held by the buffers
+    pub fn size_bytes(&self) -> usize {
+        use std::mem::size_of;
+        self.ids.capacity() * size_of::<Id>()
+            + self.scores.capacity() * size_of::<f32>()
+            + self.rows.capacity() * size_of::<&[f32]>()
+            + self.ranked.capacity() * size_of::<Ranked>()
+            + self.frontier.capacity() * size_of::<Id>()
+            + self.candidates.capacity() * size_of::<(Id, f32)>()
+    }
+
+    /// Empty every buffer, releasing any that outgrew `limit` bytes
+    fn reset(&mut self, limit: usize) {
+        fn trim<T>(buffer: &mut Vec<T>, limit: usize) {
+            buffer.clear();
+            if buffer.capacity() * std::mem::size_of::<T>() > limit {
+                *buffer = Vec::new();
+            }
+        }
+        trim(&mut self.ids, limit);
+        trim(&mut self.scores, limit);
+        trim(&mut self.rows, limit);
+        trim(&mut self.ranked, limit);
+        trim(&mut self.frontier, limit);
+        trim(&mut self.candidates, limit);
+    }
+}
+
+/// Run `f` with this thread's scratch buffers
+///
+/// Re-entrant calls (a search inside a search on the same thread) get
+/// fresh buffers instead of the borrowed ones.
+pub fn with_scratch<R>(f: impl FnOnce(&mut Scratch) -> R) -> R {
+    SCRATCH.with(|cell| match cell.try_borrow_mut() {
+        Ok(mut scratch) => {
+            let result = f(&mut scratch);
+            scratch.reset(retained_bytes());
+            result
+        }
+        Err(_) => f(&mut Scratch::default()),
+    })
+}
+
+/// Largest buffer (in bytes) this thread keeps between queries
+pub fn retained_bytes() -> usize {
+    RETAINED_BYTES.with(Cell::get)
+}
+
+/// Set the largest buffer this thread keeps between queries (0 = never reuse)
+pub fn set_retained_bytes(bytes: usize) {
+    RETAINED_BYTES.with(|limit| limit.set(bytes));
+    SCRATCH.with(|cell| {
+        if let Ok(mut scratch) = cell.try_borrow_mut() {
+            scratch.reset(bytes);
+        }
+    });
+}
+
+/// Bytes currently held by this thread's scratch buffers
+pub fn scratch_bytes() -> usize {
+    SCRATCH.with(|cell| cell.try_borrow().map_or(0, |s| s.size_bytes()))
+}
+
+/// Empty a buffer of borrowed rows so it can be stored until the next query
+///
+/// The buffer is empty, so no borrow outlives its data; collecting in place
+/// keeps the allocation.
+pub(crate) fn recycle(mut rows: Vec<&[f32]>) -> Vec<&'static [f32]> {
+    rows.clear();
+    rows.into_iter().map(|_| -> &'static [f32] { unreachable!() }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffers_are_reused_and_trimmed() {
+        with_scratch(|s| s.scores.extend([1.0; 64]));
+        let kept = scratch_bytes();
+        assert!(kept >= 64 * 4);
+
+        // Reused: cleared but not reallocated
+        with_scratch(|s| {
+            assert!(s.scores.is_empty());
+            assert!(s.scores.capacity() >= 64);
+        });
+
+        // Over the limit: released after use
+        set_retained_bytes(16);
+        with_scratch(|s| s.scores.extend([1.0; 64]));
+        assert_eq!(scratch_bytes(), 0);
+        set_retained_bytes(DEFAULT_RETAINED_BYTES);
+
+        // Re-entrant use gets its own buffers
+        with_scratch(|outer| {
+            outer.ids.push(Id::nil());
+            with_scratch(|inner| assert!(inner.ids.is_empty()));
+        });
+    }
+}
@@ -108,6 +108,16 @@ impl Subspace {
         }
     }
 
+    /// Approximate heap bytes held
+    pub fn size_bytes(&self) -> usize {
+        let floats = self.centroid.dimensionality()
+            + self.principal_directions.iter().map(|d| d.dimensionality()).sum::<usize>()
+            + self.eigenvalues.capacity()
+            + self.accumulated_sum.capacity()
+            + self.accumulated_outer_product.capacity();
+        floats * std::mem::size_of::<f32>()
+    }
+
     /// Create from a single point
     pub fn from_point(point: &Point) -> Self {
         Self {
@@ -339,13 +349,12 @@ pub fn subspace_similarity(a: &Subspace, b: &Subspace) -> f32 {
 
     // Build matrix M where M[i][j] = <a_i, b_j> (dot products)
     let mut m = vec![vec![0.0f32; rank_b]; rank_a];
-    for i in 0..rank_a {
-        for j in 0..rank_b {
-            let dot: f32 = a.principal_directions[i].dims().iter()
-                .zip(b.principal_directions[j].dims().iter())
+    for (row, a_dir) in m.iter_mut().zip(&a.principal_directions) {
+        for (cell, b_dir) in row.iter_mut().zip(&b.principal_directions) {
+            *cell = a_dir.dims().iter()
+                .zip(b_dir.dims().iter())
                 .map(|(x, y)| x * y)
                 .sum();
-            m[i][j] = dot;
         }
     }
 
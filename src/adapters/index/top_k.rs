@@ -0,0 +1,199 @@
+//! # Top-k Selection
+//!
+//! Bounded heap keeping the k most relevant results of a scan.
+//!
+//! The heap's root is the worst result kept so far, so each candidate costs
+//! one comparison against it and only winners pay for a heap update:
+//! O(n log k) for n candidates instead of sorting all n. The root's score is
+//! also the bound a distance scan can prune against (`Proximity::distance_within`).
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::ControlFlow;
+
+use crate::core::proximity::ScoreOrder;
+use crate::ports::SearchResult;
+
+/// Heap entry ordered by "badness" (greater = less relevant)
+#[derive(Debug, Clone)]
+pub(crate) struct Ranked {
+    badness: f32,
+    result: SearchResult,
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.badness
+            .total_cmp(&other.badness)
+            .then_with(|| self.result.id.cmp(&other.result.id))
+    }
+}
+
+/// The k most relevant results seen so far
+#[derive(Debug, Clone)]
+pub struct TopK {
+    k: usize,
+    order: ScoreOrder,
+    heap: BinaryHeap<Ranked>,
+}
+
+impl TopK {
+    /// Keep the `k` best results under a score order
+    pub fn new(k: usize, order: ScoreOrder) -> Self {
+        Self {
+            k,
+            order,
+            heap: BinaryHeap::with_capacity(k.min(1024) + 1),
+        }
+    }
+
+    /// Like `new`, but keeping results in a recycled buffer
+    pub(crate) fn with_buffer(k: usize, order: ScoreOrder, mut buffer: Vec<Ranked>) -> Self {
+        buffer.clear();
+        Self {
+            k,
+            order,
+            heap: BinaryHeap::from(buffer),
+        }
+    }
+
+    fn badness(&self, score: f32) -> f32 {
+        match self.order {
+            ScoreOrder::HigherIsBetter => -score,
+            ScoreOrder::LowerIsBetter => score,
+        }
+    }
+
+    /// Number of results kept
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Check if nothing has been kept
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Whether k results are already kept
+    pub fn is_full(&self) -> bool {
+        self.heap.len() >= self.k
+    }
+
+    /// Score a new candidate must beat once full (the worst kept score)
+    pub fn bound(&self) -> Option<f32> {
+        if self.is_full() {
+            self.heap.peek().map(|r| r.result.score)
+        } else {
+            None
+        }
+    }
+
+    /// Offer a result; kept if it's among the best k so far
+    pub fn push(&mut self, result: SearchResult) {
+        if self.k == 0 {
+            return;
+        }
+        let ranked = Ranked {
+            badness: self.badness(result.score),
+            result,
+        };
+        if !self.is_full() {
+            self.heap.push(ranked);
+        } else if let Some(mut worst) = self.heap.peek_mut() {
+            if ranked < *worst {
+                *worst = ranked;
+            }
+        }
+    }
+
+    /// Fold another selection's results into this one
+    pub fn merge(&mut self, other: TopK) {
+        for ranked in other.heap {
+            self.push(ranked.result);
+        }
+    }
+
+    /// Hand kept results to `visitor`, most relevant first, until it breaks
+    ///
+    /// Sorts in the heap's own buffer, so nothing else is allocated.
+    pub fn visit_sorted(self, visitor: &mut dyn FnMut(SearchResult) -> ControlFlow<()>) {
+        self.drain_sorted(visitor);
+    }
+
+    /// `visit_sorted`, handing back the emptied buffer for reuse
+    pub(crate) fn drain_sorted(
+        self,
+        visitor: &mut dyn FnMut(SearchResult) -> ControlFlow<()>,
+    ) -> Vec<Ranked> {
+        let mut sorted = self.heap.into_sorted_vec();
+        for ranked in sorted.drain(..) {
+            if visitor(ranked.result).is_break() {
+                break;
+            }
+        }
+        sorted
+    }
+
+    /// Kept results, most relevant first
+    pub fn into_sorted_vec(self) -> Vec<SearchResult> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|r| r.result)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Id;
+
+    fn result(n: u8, score: f32) -> SearchResult {
+        SearchResult::new(Id::from_bytes([n; 16]), score)
+    }
+
+    #[test]
+    fn test_keeps_best_k() {
+        let mut top = TopK::new(2, ScoreOrder::HigherIsBetter);
+        for (n, score) in [(1, 0.1), (2, 0.9), (3, 0.5), (4, 0.7)] {
+            top.push(result(n, score));
+        }
+        assert_eq!(top.bound(), Some(0.7));
+
+        let scores: Vec<f32> = top.into_sorted_vec().iter().map(|r| r.score).collect();
+        assert_eq!(scores, vec![0.9, 0.7]);
+    }
+
+    #[test]
+    fn test_lower_is_better_and_merge() {
+        let mut a = TopK::new(2, ScoreOrder::LowerIsBetter);
+        a.push(result(1, 3.0));
+        a.push(result(2, 1.0));
+        let mut b = TopK::new(2, ScoreOrder::LowerIsBetter);
+        b.push(result(3, 0.5));
+        b.push(result(4, 9.0));
+
+        a.merge(b);
+        let ids: Vec<Id> = a.into_sorted_vec().iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![Id::from_bytes([3; 16]), Id::from_bytes([2; 16])]);
+
+        let mut empty = TopK::new(0, ScoreOrder::HigherIsBetter);
+        empty.push(result(1, 1.0));
+        assert!(empty.is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+//! # Client Limits
+//!
+//! Per-API-key request rate limits and storage quotas for `HttpServer`, so
+//! one misbehaving client can't exhaust the memory service.
+//!
+//! Clients identify themselves with an `X-Api-Key` header (or
+//! `Authorization: Bearer <key>`); requests without one share the anonymous
+//! key `""`. Every key gets the default `KeyLimits` unless given its own.
+//!
+//! - Rates are token buckets: `burst` requests at once, refilled at
+//!   `requests_per_sec`
+//! - Quotas cap the points and bytes (vector + blob) a key has stored
+//!   through `PUT /points`; `DELETE` gives them back
+//!
+//! Rejections answer `429 Too Many Requests` and are counted in the
+//! `arms_rate_limited_total` and `arms_quota_rejections_total` metrics.
+//!
+//! ```rust,ignore
+//! let limits = Limits::new(KeyLimits::rate(50.0, 100))
+//!     .with_key("ingest-job", KeyLimits::rate(500.0, 1000).with_max_points(1_000_000));
+//!
+//! let server = HttpServer::bind("0.0.0.0:9090", arms)?.with_limits(limits);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::{Mutex, PoisonError};
+
+use crate::core::{clock, Id};
+
+/// Limits applied to one API key
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyLimits {
+    /// Sustained request rate (None = unlimited)
+    pub requests_per_sec: Option<f64>,
+
+    /// Requests allowed at once before the rate applies
+    pub burst: u32,
+
+    /// Most points the key may have stored (None = unlimited)
+    pub max_points: Option<usize>,
+
+    /// Most vector + blob bytes the key may have stored (None = unlimited)
+    pub max_bytes: Option<u64>,
+}
+
+impl KeyLimits {
+    /// No rate limit and no quota
+    pub fn unlimited() -> Self {
+        Self {
+            requests_per_sec: None,
+            burst: 0,
+            max_points: None,
+            max_bytes: None,
+        }
+    }
+
+    /// `requests_per_sec` sustained, bursts of up to `burst`, no quota
+    pub fn rate(requests_per_sec: f64, burst: u32) -> Self {
+        Self {
+            requests_per_sec: Some(requests_per_sec),
+            burst: burst.max(1),
+            ..Self::unlimited()
+        }
+    }
+
+    /// Cap the number of stored points
+    pub fn with_max_points(mut self, max_points: usize) -> Self {
+        self.max_points = Some(max_points);
+        self
+    }
+
+    /// Cap the stored vector + blob bytes
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+}
+
+impl Default for KeyLimits {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+/// Why a request was turned away
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rejection {
+    /// Out of request tokens; one comes back after `retry_after_ms`
+    RateLimited { retry_after_ms: u64 },
+
+    /// Storing the point would take the key past its quota
+    QuotaExceeded { reason: String },
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::RateLimited { retry_after_ms } => {
+                write!(f, "rate limit exceeded, retry in {}ms", retry_after_ms)
+            }
+            Rejection::QuotaExceeded { reason } => write!(f, "quota exceeded: {}", reason),
+        }
+    }
+}
+
+/// Points and bytes a key has stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Usage {
+    pub points: usize,
+    pub bytes: u64,
+}
+
+/// Live state of one key
+#[derive(Debug, Default)]
+struct KeyState {
+    /// Request tokens left, and when they were last refilled
+    tokens: f64,
+    refilled_micros: u64,
+    primed: bool,
+
+    usage: Usage,
+    rate_limited: u64,
+    quota_rejections: u64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    keys: HashMap<String, KeyState>,
+
+    /// Key and size each quota-counted point was stored under
+    owners: HashMap<Id, (String, u64)>,
+}
+
+/// Rate limits and quotas for every API key
+#[derive(Debug)]
+pub struct Limits {
+    default: KeyLimits,
+    overrides: HashMap<String, KeyLimits>,
+    state: Mutex<State>,
+}
+
+impl Limits {
+    /// Apply `default` to every key without its own limits
+    pub fn new(default: KeyLimits) -> Self {
+        Self {
+            default,
+            overrides: HashMap::new(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Give `key` its own limits
+    pub fn with_key(mut self, key: impl Into<String>, limits: KeyLimits) -> Self {
+        self.overrides.insert(key.into(), limits);
+        self
+    }
+
+    /// Limits in force for `key`
+    pub fn limits_for(&self, key: &str) -> KeyLimits {
+        self.overrides.get(key).copied().unwrap_or(self.default)
+    }
+
+    /// Spend one request token for `key`
+    pub fn admit(&self, key: &str) -> Result<(), Rejection> {
+        self.admit_at(key, clock::now_micros())
+    }
+
+    fn admit_at(&self, key: &str, now_micros: u64) -> Result<(), Rejection> {
+        let limits = self.limits_for(key);
+        let Some(rate) = limits.requests_per_sec.filter(|rate| *rate > 0.0) else {
+            return Ok(());
+        };
+        let burst = limits.burst.max(1) as f64;
+
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = state.keys.entry(key.to_string()).or_default();
+        if !entry.primed {
+            entry.tokens = burst;
+            entry.refilled_micros = now_micros;
+            entry.primed = true;
+        }
+        let elapsed = now_micros.saturating_sub(entry.refilled_micros) as f64 / 1e6;
+        entry.tokens = (entry.tokens + elapsed * rate).min(burst);
+        entry.refilled_micros = now_micros;
+
+        if entry.tokens >= 1.0 {
+            entry.tokens -= 1.0;
+            return Ok(());
+        }
+        entry.rate_limited += 1;
+        let retry_after_ms = ((1.0 - entry.tokens) / rate * 1000.0).ceil() as u64;
+        Err(Rejection::RateLimited { retry_after_ms })
+    }
+
+    /// Count a point of `bytes` against `key`'s quota before storing it
+    ///
+    /// Hand it back with `release` if the point isn't stored after all.
+    pub fn reserve(&self, key: &str, id: Id, bytes: u64) -> Result<(), Rejection> {
+        let limits = self.limits_for(key);
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = state.keys.entry(key.to_string()).or_default();
+
+        let reason = match limits {
+            KeyLimits { max_points: Some(max), .. } if entry.usage.points >= max => {
+                Some(format!("{} points stored (limit {})", entry.usage.points, max))
+            }
+            KeyLimits { max_bytes: Some(max), .. } if entry.usage.bytes + bytes > max => Some(format!(
+                "{} + {} bytes stored (limit {})",
+                entry.usage.bytes, bytes, max
+            )),
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            entry.quota_rejections += 1;
+            return Err(Rejection::QuotaExceeded { reason });
+        }
+
+        entry.usage.points += 1;
+        entry.usage.bytes += bytes;
+        state.owners.insert(id, (key.to_string(), bytes));
+        Ok(())
+    }
+
+    /// Give a point's share of quota back to whichever key stored it
+    pub fn release(&self, id: Id) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some((key, bytes)) = state.owners.remove(&id) else {
+            return;
+        };
+        if let Some(entry) = state.keys.get_mut(&key) {
+            entry.usage.points = entry.usage.points.saturating_sub(1);
+            entry.usage.bytes = entry.usage.bytes.saturating_sub(bytes);
+        }
+    }
+
+    /// What `key` has stored
+    pub fn usage(&self, key: &str) -> Usage {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.keys.get(key).map(|entry| entry.usage).unwrap_or_default()
+    }
+
+    /// Render rejection counters and usage in Prometheus text format
+    pub fn render_prometheus(&self) -> String {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut keys: Vec<_> = state.keys.iter().collect();
+        keys.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut out = String::new();
+        let families: [Family; 4] = [
+            ("arms_rate_limited_total", "Requests rejected by the rate limit.", "counter", |s| s.rate_limited),
+            ("arms_quota_rejections_total", "Points rejected by a storage quota.", "counter", |s| s.quota_rejections),
+            ("arms_quota_points", "Points stored per API key.", "gauge", |s| s.usage.points as u64),
+            ("arms_quota_bytes", "Vector and blob bytes stored per API key.", "gauge", |s| s.usage.bytes),
+        ];
+        for (name, help, kind, value) in families {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for (key, entry) in &keys {
+                let _ = writeln!(out, "{}{{key=\"{}\"}} {}", name, escape_label(key), value(entry));
+            }
+        }
+        out
+    }
+}
+
+/// Name, help, type and per-key value of a rendered metric family
+type Family = (&'static str, &'static str, &'static str, fn(&KeyState) -> u64);
+
+/// Escape a Prometheus label value
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let limits = Limits::new(KeyLimits::rate(2.0, 3)).with_key("vip", KeyLimits::unlimited());
+
+        for _ in 0..3 {
+            assert!(limits.admit_at("a", 0).is_ok());
+        }
+        assert_eq!(limits.admit_at("a", 0), Err(Rejection::RateLimited { retry_after_ms: 500 }));
+        // Keys don't share a bucket
+        assert!(limits.admit_at("b", 0).is_ok());
+
+        // Half a second refills one token, and never past the burst
+        assert!(limits.admit_at("a", 500_000).is_ok());
+        assert!(limits.admit_at("a", 500_000).is_err());
+        for _ in 0..3 {
+            assert!(limits.admit_at("a", 60_000_000).is_ok());
+        }
+        assert!(limits.admit_at("a", 60_000_000).is_err());
+
+        for _ in 0..100 {
+            assert!(limits.admit_at("vip", 0).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_quotas() {
+        let limits = Limits::new(KeyLimits::unlimited().with_max_points(2).with_max_bytes(100));
+        let ids: Vec<Id> = (0..4).map(|i| Id::seeded(7, i)).collect();
+
+        assert!(limits.reserve("a", ids[0], 60).is_ok());
+        assert!(matches!(limits.reserve("a", ids[1], 50), Err(Rejection::QuotaExceeded { .. })));
+        assert!(limits.reserve("a", ids[1], 40).is_ok());
+        assert!(matches!(limits.reserve("a", ids[2], 0), Err(Rejection::QuotaExceeded { .. })));
+        assert_eq!(limits.usage("a"), Usage { points: 2, bytes: 100 });
+        assert!(limits.reserve("b", ids[2], 10).is_ok());
+
+        limits.release(ids[0]);
+        limits.release(ids[3]);
+        assert_eq!(limits.usage("a"), Usage { points: 1, bytes: 40 });
+        assert!(limits.reserve("a", ids[3], 60).is_ok());
+
+        let rendered = limits.render_prometheus();
+        assert!(rendered.contains("arms_quota_rejections_total{key=\"a\"} 2\n"));
+        assert!(rendered.contains("arms_quota_points{key=\"b\"} 1\n"));
+        assert!(rendered.contains("arms_quota_bytes{key=\"a\"} 100\n"));
+    }
+}
@@ -0,0 +1,321 @@
+//! # Migrations
+//!
+//! Detects the format and version of files this crate writes and upgrades
+//! old versions in place.
+//!
+//! | Format            | Magic     | Current version              |
+//! |-------------------|-----------|------------------------------|
+//! | HAT snapshot      | `HAT\0`   | `HAT_FORMAT_VERSION`         |
+//! | Write-ahead log   | `ARMSWAL` | `WAL_FORMAT_VERSION`         |
+//! | Attention state   | `ATTN`    | `ATTENTION_FORMAT_VERSION`   |
+//! | Attention batch   | `ATNB`    | 1                            |
+//! | Framed batch      | `ATNF`    | `FRAMED_BATCH_VERSION`       |
+//!
+//! Each format change adds a `Step` upgrading one version to the next;
+//! `migrate_bytes` chains them up to the current version.
+//! `migrate_file` keeps the original as `<path>.v<old>.bak` and swaps the
+//! upgraded file in with a rename, so a crash leaves either version intact.
+//!
+//! ```rust,ignore
+//! match migrations::migrate_file("state.attn")? {
+//!     Some(done) => println!("{} v{} -> v{} (backup {:?})", done.format, done.from, done.to, done.backup),
+//!     None => println!("already current"),
+//! }
+//! ```
+//!
+//! RocksDB and redb stores keep their version inside the database and
+//! check it on open; they aren't covered here.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::attention::{AttentionError, AttentionState, ATTENTION_FORMAT_VERSION};
+use super::attention_frames::FRAMED_BATCH_VERSION;
+use super::index::HAT_FORMAT_VERSION;
+use super::storage::WAL_FORMAT_VERSION;
+
+/// Error type for migrations
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum MigrationError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    /// No known magic bytes
+    #[error("Unrecognized file format")]
+    UnknownFormat,
+
+    /// Written by a newer build
+    #[error("{format} version {version} is newer than this build reads (up to {current})")]
+    TooNew { format: Format, version: u32, current: u32 },
+
+    /// An old version no step upgrades
+    #[error("No migration from {format} version {version}")]
+    NoPath { format: Format, version: u32 },
+
+    #[error(transparent)]
+    Attention(#[from] AttentionError),
+}
+
+/// On-disk formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    HatSnapshot,
+    Wal,
+    AttentionState,
+    AttentionBatch,
+    FramedBatch,
+}
+
+impl Format {
+    pub const ALL: [Format; 5] = [
+        Format::HatSnapshot,
+        Format::Wal,
+        Format::AttentionState,
+        Format::AttentionBatch,
+        Format::FramedBatch,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::HatSnapshot => "HAT snapshot",
+            Format::Wal => "write-ahead log",
+            Format::AttentionState => "attention state",
+            Format::AttentionBatch => "attention batch",
+            Format::FramedBatch => "framed attention batch",
+        }
+    }
+
+    fn magic(&self) -> &'static [u8] {
+        match self {
+            Format::HatSnapshot => b"HAT\0",
+            Format::Wal => b"ARMSWAL",
+            Format::AttentionState => b"ATTN",
+            Format::AttentionBatch => b"ATNB",
+            Format::FramedBatch => b"ATNF",
+        }
+    }
+
+    /// Version this build writes
+    pub fn current_version(&self) -> u32 {
+        match self {
+            Format::HatSnapshot => HAT_FORMAT_VERSION,
+            Format::Wal => WAL_FORMAT_VERSION as u32,
+            Format::AttentionState => ATTENTION_FORMAT_VERSION,
+            Format::AttentionBatch => 1,
+            Format::FramedBatch => FRAMED_BATCH_VERSION,
+        }
+    }
+
+    /// Version stored after the magic (the WAL stores a single byte)
+    fn read_version(&self, bytes: &[u8]) -> Option<u32> {
+        let at = self.magic().len();
+        match self {
+            Format::Wal => bytes.get(at).map(|&v| v as u32),
+            _ => bytes.get(at..at + 4).map(|v| u32::from_le_bytes(v.try_into().unwrap())),
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Format and version of some data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Detected {
+    pub format: Format,
+    pub version: u32,
+}
+
+impl Detected {
+    pub fn is_current(&self) -> bool {
+        self.version == self.format.current_version()
+    }
+}
+
+/// Bytes `detect` needs at most
+const HEADER_LEN: usize = 8;
+
+/// Identify data by its magic bytes (None if unrecognized or truncated)
+pub fn detect(bytes: &[u8]) -> Option<Detected> {
+    let format = Format::ALL.into_iter().find(|f| bytes.starts_with(f.magic()))?;
+    Some(Detected { format, version: format.read_version(bytes)? })
+}
+
+/// Identify a file by its first bytes
+pub fn detect_file(path: impl AsRef<Path>) -> Result<Option<Detected>, MigrationError> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    File::open(path)?.take(HEADER_LEN as u64).read_to_end(&mut header)?;
+    Ok(detect(&header))
+}
+
+/// One upgrade: data at `from` to `from + 1`
+struct Step {
+    format: Format,
+    from: u32,
+    apply: fn(&[u8]) -> Result<Vec<u8>, MigrationError>,
+}
+
+/// Known upgrades, oldest first
+const STEPS: &[Step] = &[Step { format: Format::AttentionState, from: 1, apply: attention_v1_to_v2 }];
+
+/// v1 embedded the KV cache as one blob; v2 streams it in chunks
+fn attention_v1_to_v2(bytes: &[u8]) -> Result<Vec<u8>, MigrationError> {
+    Ok(AttentionState::from_bytes(bytes)?.to_bytes())
+}
+
+/// Upgrade data to its format's current version (None if already current)
+pub fn migrate_bytes(bytes: &[u8]) -> Result<Option<Vec<u8>>, MigrationError> {
+    let detected = detect(bytes).ok_or(MigrationError::UnknownFormat)?;
+    check(detected)?;
+    if detected.is_current() {
+        return Ok(None);
+    }
+
+    let mut data = bytes.to_vec();
+    for version in detected.version..detected.format.current_version() {
+        let step = STEPS
+            .iter()
+            .find(|s| s.format == detected.format && s.from == version)
+            .ok_or(MigrationError::NoPath { format: detected.format, version })?;
+        data = (step.apply)(&data)?;
+    }
+    Ok(Some(data))
+}
+
+/// Reject versions no chain of steps reaches the current one from
+fn check(detected: Detected) -> Result<(), MigrationError> {
+    let Detected { format, version } = detected;
+    let current = format.current_version();
+    if version > current {
+        return Err(MigrationError::TooNew { format, version, current });
+    }
+    if let Some(missing) = (version..current).find(|&v| !STEPS.iter().any(|s| s.format == format && s.from == v)) {
+        return Err(MigrationError::NoPath { format, version: missing });
+    }
+    Ok(())
+}
+
+/// A completed file migration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migrated {
+    pub format: Format,
+    pub from: u32,
+    pub to: u32,
+
+    /// Copy of the original file
+    pub backup: PathBuf,
+}
+
+/// Upgrade a file in place, keeping a backup (None if already current)
+pub fn migrate_file(path: impl AsRef<Path>) -> Result<Option<Migrated>, MigrationError> {
+    let path = path.as_ref();
+    let detected = detect_file(path)?.ok_or(MigrationError::UnknownFormat)?;
+    check(detected)?;
+    if detected.is_current() {
+        return Ok(None);
+    }
+
+    let Some(upgraded) = migrate_bytes(&fs::read(path)?)? else {
+        return Ok(None);
+    };
+
+    let backup = backup_path(path, detected.version);
+    fs::copy(path, &backup)?;
+
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".migrating");
+    let temp = PathBuf::from(temp);
+    let mut file = File::create(&temp)?;
+    file.write_all(&upgraded)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temp, path)?;
+
+    Ok(Some(Migrated {
+        format: detected.format,
+        from: detected.version,
+        to: detected.format.current_version(),
+        backup,
+    }))
+}
+
+/// `<path>.v<version>.bak`, numbered if that exists already
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    (0..)
+        .map(|n| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(format!(".v{}.bak", version));
+            if n > 0 {
+                name.push(format!(".{}", n));
+            }
+            PathBuf::from(name)
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("some backup name is free")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::attention::{AttentionBatch, CompressedKV, Role};
+    use crate::adapters::index::HatIndex;
+
+    fn state() -> AttentionState {
+        let mut state = AttentionState::new(Role::User, "hello".into(), vec![0.5, -0.5]);
+        state.kv_cache = Some(CompressedKV {
+            model_id: "model".into(),
+            num_layers: 1,
+            num_heads: 1,
+            head_dim: 2,
+            seq_len: 1,
+            quantization: "fp32".into(),
+            data: vec![7; 8],
+        });
+        state
+    }
+
+    #[test]
+    fn test_detect_formats() {
+        let hat = HatIndex::cosine(2).to_bytes().unwrap();
+        assert_eq!(detect(&hat), Some(Detected { format: Format::HatSnapshot, version: HAT_FORMAT_VERSION }));
+        let batch = AttentionBatch::new().to_bytes();
+        assert!(detect(&batch).unwrap().is_current());
+        assert_eq!(detect(&state().to_bytes_v1()).map(|d| d.version), Some(1));
+        assert_eq!(detect(b"ATTN"), None);
+        assert_eq!(detect(b"nothing"), None);
+
+        assert!(matches!(migrate_bytes(b"nothing"), Err(MigrationError::UnknownFormat)));
+        assert!(migrate_bytes(&hat).unwrap().is_none());
+        let mut future = hat.clone();
+        future[4..8].copy_from_slice(&(HAT_FORMAT_VERSION + 1).to_le_bytes());
+        assert!(matches!(migrate_bytes(&future), Err(MigrationError::TooNew { .. })));
+    }
+
+    #[test]
+    fn test_migrate_attention_file() {
+        let dir = crate::testing::temp_path("arms-migrations");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.attn");
+        let original = state();
+        fs::write(&path, original.to_bytes_v1()).unwrap();
+
+        let migrated = migrate_file(&path).unwrap().unwrap();
+        assert_eq!((migrated.format, migrated.from, migrated.to), (Format::AttentionState, 1, ATTENTION_FORMAT_VERSION));
+        assert_eq!(fs::read(&migrated.backup).unwrap(), original.to_bytes_v1());
+        assert_eq!(fs::read(&path).unwrap(), original.to_bytes());
+        assert!(detect_file(&path).unwrap().unwrap().is_current());
+        assert_eq!(migrate_file(&path).unwrap(), None);
+
+        // A second backup of the same version doesn't overwrite the first
+        fs::write(&path, original.to_bytes_v1()).unwrap();
+        let again = migrate_file(&path).unwrap().unwrap();
+        assert_ne!(again.backup, migrated.backup);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
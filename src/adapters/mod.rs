@@ -6,6 +6,15 @@
 //! - Storage adapters: Memory, NVMe
 //! - Index adapters: Flat (brute force), HNSW (approximate)
 //! - Attention state serialization
+//! - Framed, compressed attention batches (zstd when enabled)
+//! - vLLM prefix-cache interop
+//! - On-disk format detection and upgrades (migrations)
+//! - Chat transcript import (when enabled)
+//! - HTTP server with Prometheus metrics and per-key limits (when enabled)
+//! - Remote shard nodes over that server, for `ShardRouter` (when enabled)
+//! - Browser memory explorer (when enabled)
+//! - Postgres/pgvector export (when enabled)
+//! - C FFI (when enabled)
 //! - Python bindings (when enabled)
 //!
 //! Each adapter implements one or more port traits.
@@ -14,6 +23,31 @@
 pub mod storage;
 pub mod index;
 pub mod attention;
+pub mod attention_frames;
+pub mod vllm;
+pub mod migrations;
+
+
+#[cfg(feature = "import")]
+pub mod transcript;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "server")]
+pub mod server;
+
+#[cfg(feature = "server")]
+pub mod limits;
+
+#[cfg(feature = "server")]
+pub mod remote_shard;
+
+#[cfg(feature = "explorer")]
+pub mod explorer;
+
+#[cfg(feature = "pgvector")]
+pub mod pgvector;
 
 #[cfg(feature = "python")]
 pub mod python;
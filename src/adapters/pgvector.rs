@@ -0,0 +1,233 @@
+//! # Postgres/pgvector Export
+//!
+//! Mirrors placed points into a Postgres table with a pgvector column
+//! (feature `pgvector`), and reads them back.
+//!
+//! ARMS stays the online serving path; the table is a copy for analysts to
+//! query with SQL (joins, aggregates, `ORDER BY embedding <=> $1`):
+//!
+//! ```sql
+//! CREATE TABLE arms_points (
+//!     id         bytea PRIMARY KEY,   -- 16-byte ARMS ID
+//!     placed_at  timestamptz,         -- from the ID's timestamp
+//!     embedding  vector(768),
+//!     blob       bytea
+//! );
+//! ```
+//!
+//! Exports upsert by ID inside one transaction, so re-running an export
+//! refreshes the mirror. Vectors travel as pgvector's text form
+//! (`[0.1,0.2,...]`), which needs no client-side pgvector types.
+//!
+//! ```rust,ignore
+//! let mut export = PgvectorExport::connect("host=localhost user=arms", "arms_points", 768)?;
+//! export.create_table()?;
+//! export.export(arms.iter())?;
+//!
+//! // Later: rebuild a store from the mirror
+//! export.import_into(&mut storage)?;
+//! ```
+
+use postgres::{Client, NoTls};
+
+use crate::core::{Blob, Id, PlacedPoint, Point};
+use crate::ports::{Place, PlaceError};
+
+/// Errors from the pgvector exporter
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PgvectorError {
+    #[error("Postgres error: {0}")]
+    Postgres(#[from] postgres::Error),
+
+    /// The table name isn't a plain (optionally schema-qualified) identifier
+    #[error("Invalid table name: {0}")]
+    InvalidTable(String),
+
+    /// A point has the wrong dimensionality for the table
+    #[error("Dimensionality mismatch: expected {expected}, got {got}")]
+    DimensionalityMismatch { expected: usize, got: usize },
+
+    /// A row can't be decoded
+    #[error("Corrupt row: {0}")]
+    Corrupted(String),
+
+    /// An imported point was rejected by the storage
+    #[error(transparent)]
+    Place(#[from] PlaceError),
+}
+
+/// Mirror of placed points in a Postgres table
+pub struct PgvectorExport {
+    client: Client,
+    table: String,
+    dimensionality: usize,
+}
+
+impl PgvectorExport {
+    /// Connect without TLS (e.g., "host=localhost user=postgres dbname=arms")
+    pub fn connect(params: &str, table: &str, dimensionality: usize) -> Result<Self, PgvectorError> {
+        let client = Client::connect(params, NoTls)?;
+        Self::new(client, table, dimensionality)
+    }
+
+    /// Use an existing client (TLS, pooling, ... configured by the caller)
+    pub fn new(client: Client, table: &str, dimensionality: usize) -> Result<Self, PgvectorError> {
+        check_table_name(table)?;
+        Ok(Self {
+            client,
+            table: table.to_string(),
+            dimensionality,
+        })
+    }
+
+    /// Create the pgvector extension and the table if they don't exist
+    pub fn create_table(&mut self) -> Result<(), PgvectorError> {
+        self.client.batch_execute(&format!(
+            "CREATE EXTENSION IF NOT EXISTS vector;
+             CREATE TABLE IF NOT EXISTS {} (
+                 id bytea PRIMARY KEY,
+                 placed_at timestamptz,
+                 embedding vector({}) NOT NULL,
+                 blob bytea NOT NULL
+             );",
+            self.table, self.dimensionality
+        ))?;
+        Ok(())
+    }
+
+    /// Upsert points into the table in one transaction, returning how many
+    pub fn export<'a, I>(&mut self, points: I) -> Result<usize, PgvectorError>
+    where
+        I: IntoIterator<Item = &'a PlacedPoint>,
+    {
+        let sql = format!(
+            "INSERT INTO {} (id, placed_at, embedding, blob)
+             VALUES ($1, to_timestamp($2::float8 / 1000), $3::text::vector, $4)
+             ON CONFLICT (id) DO UPDATE
+             SET embedding = EXCLUDED.embedding, blob = EXCLUDED.blob",
+            self.table
+        );
+
+        let mut txn = self.client.transaction()?;
+        let statement = txn.prepare(&sql)?;
+        let mut count = 0;
+        for placed in points {
+            if placed.point.dimensionality() != self.dimensionality {
+                return Err(PgvectorError::DimensionalityMismatch {
+                    expected: self.dimensionality,
+                    got: placed.point.dimensionality(),
+                });
+            }
+            txn.execute(
+                &statement,
+                &[
+                    &placed.id.as_bytes().as_slice(),
+                    &(placed.id.timestamp_ms() as f64),
+                    &to_vector_literal(&placed.point),
+                    &placed.blob.data(),
+                ],
+            )?;
+            count += 1;
+        }
+        txn.commit()?;
+        Ok(count)
+    }
+
+    /// Delete points from the table, returning how many rows went away
+    pub fn delete(&mut self, ids: &[Id]) -> Result<u64, PgvectorError> {
+        let keys: Vec<&[u8]> = ids.iter().map(|id| id.as_bytes().as_slice()).collect();
+        let sql = format!("DELETE FROM {} WHERE id = ANY($1)", self.table);
+        Ok(self.client.execute(&sql, &[&keys])?)
+    }
+
+    /// Read every row back as a placed point, in ID (creation) order
+    pub fn import(&mut self) -> Result<Vec<PlacedPoint>, PgvectorError> {
+        let sql = format!("SELECT id, embedding::text, blob FROM {} ORDER BY id", self.table);
+        self.client
+            .query(&sql, &[])?
+            .iter()
+            .map(|row| {
+                let id: &[u8] = row.try_get(0)?;
+                let embedding: &str = row.try_get(1)?;
+                let blob: Vec<u8> = row.try_get(2)?;
+
+                let id: [u8; 16] = id
+                    .try_into()
+                    .map_err(|_| PgvectorError::Corrupted("id is not 16 bytes".into()))?;
+                let point = parse_vector_literal(embedding)
+                    .ok_or_else(|| PgvectorError::Corrupted(format!("embedding {:?}", embedding)))?;
+                Ok(PlacedPoint::new(Id::from_bytes(id), point, Blob::new(blob)))
+            })
+            .collect()
+    }
+
+    /// Place every row into `storage` under its original ID, returning how many
+    pub fn import_into(&mut self, storage: &mut dyn Place) -> Result<usize, PgvectorError> {
+        let points = self.import()?;
+        let count = points.len();
+        for placed in points {
+            storage.place_with_id(placed.id, placed.point, placed.blob)?;
+        }
+        Ok(count)
+    }
+}
+
+/// Reject anything but `name` or `schema.name` made of `[A-Za-z0-9_]`
+///
+/// Table names are spliced into SQL, so they can't be parameters.
+fn check_table_name(table: &str) -> Result<(), PgvectorError> {
+    let valid_part = |part: &str| {
+        part.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    let parts: Vec<&str> = table.split('.').collect();
+    if parts.len() <= 2 && parts.iter().all(|p| valid_part(p)) {
+        Ok(())
+    } else {
+        Err(PgvectorError::InvalidTable(table.to_string()))
+    }
+}
+
+/// pgvector text form: `[1,2.5,-3]`
+fn to_vector_literal(point: &Point) -> String {
+    let dims: Vec<String> = point.dims().iter().map(|v| v.to_string()).collect();
+    format!("[{}]", dims.join(","))
+}
+
+fn parse_vector_literal(text: &str) -> Option<Point> {
+    let inner = text.trim().strip_prefix('[')?.strip_suffix(']')?;
+    if inner.trim().is_empty() {
+        return Some(Point::new(Vec::new()));
+    }
+    let dims = inner
+        .split(',')
+        .map(|v| v.trim().parse::<f32>().ok())
+        .collect::<Option<Vec<f32>>>()?;
+    Some(Point::new(dims))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector_literal_roundtrip() {
+        let point = Point::new(vec![1.0, -0.25, 3.5e-8]);
+        let literal = to_vector_literal(&point);
+        assert_eq!(literal, "[1,-0.25,0.000000035]");
+        assert_eq!(parse_vector_literal(&literal), Some(point));
+
+        assert!(parse_vector_literal("1,2").is_none());
+        assert!(parse_vector_literal("[1,x]").is_none());
+    }
+
+    #[test]
+    fn test_table_names() {
+        assert!(check_table_name("arms_points").is_ok());
+        assert!(check_table_name("analytics.arms_points").is_ok());
+        assert!(check_table_name("points; DROP TABLE users").is_err());
+        assert!(check_table_name("a.b.c").is_err());
+        assert!(check_table_name("1points").is_err());
+    }
+}
@@ -10,14 +10,25 @@
 //! # Create index for OpenAI embeddings (1536 dims)
 //! index = HatIndex.cosine(1536)
 //!
+//! # Non-normalized embeddings: HatIndex.euclidean(dims) / HatIndex.dot_product(dims)
+//! # or HatIndex.with_config(dims, HatConfig().with_proximity("euclidean"))
+//! # Research: HatIndex.custom(dims, my_fn, batch=my_numpy_fn) (slow, see docs)
+//!
 //! # Add embeddings
 //! id = index.add([0.1, 0.2, ...])  # Auto-generates ID
 //! index.add_with_id("custom_id", [0.1, 0.2, ...])  # Custom ID
 //!
+//! # Attach payloads (bytes, str, or a dict of scalars)
+//! index.add([0.1, 0.2, ...], payload={"user": "ana", "turn": 3})
+//!
 //! # Query
 //! results = index.near([0.1, 0.2, ...], k=10)
 //! for result in results:
-//!     print(f"{result.id}: {result.score}")
+//!     print(f"{result.id}: {result.score} {result.payload}")
+//!
+//! # Many queries at once (threads set by HatConfig().with_n_threads(n));
+//! # searches release the GIL, so the index can be shared across threads
+//! batches = index.near_batch([[0.1, 0.2, ...], [0.3, 0.4, ...]], k=10)
 //!
 //! # Session management
 //! index.new_session()
@@ -26,14 +37,57 @@
 //! # Persistence
 //! index.save("memory.hat")
 //! loaded = HatIndex.load("memory.hat")
+//!
+//! # Interop with NumPy
+//! ids, vectors, payloads = index.to_numpy()
+//! rebuilt = HatIndex.from_numpy(ids, vectors, payloads)
+//!
+//! # Long maintenance in the background (queries keep working)
+//! job = index.consolidate_async("full")
+//! print(job.percent, job.eta)
+//! job.cancel()  # or job.wait()
+//!
+//! # Provenance rides along with results, for trust-aware ranking
+//! index.set_provenance(id, source="wiki/berlin", model_version="e5-large-v2", confidence=0.9)
+//! ranked = sorted(results, key=lambda r: r.score * (r.confidence or 1.0), reverse=True)
+//!
+//! # Scratch memory for this conversation: searched, not kept unless promoted
+//! note = index.add_ephemeral([0.1, 0.2, ...], payload="draft plan")
+//! index.close_ephemeral(keep=[note])
+//!
+//! # Facts alongside vector hits
+//! index.upsert_fact("user", "prefers", "tea", confidence=0.9, source=id)
+//! facts = index.facts_for(index.near([0.1, 0.2, ...], k=5))
+//!
+//! # Sleep-time compression: old documents become LLM-written summaries
+//! index.compress(lambda chunks: (text := llm(chunks), embed(text)), older_than_ms=DAY_MS)
 //! ```
 
-use pyo3::prelude::*;
-use pyo3::exceptions::{PyValueError, PyIOError};
+// pyo3 0.22's `#[pymethods]` wraps every `PyResult` return in `PyErr::from`
+#![allow(clippy::useless_conversion)]
 
-use crate::core::{Id, Point};
-use crate::adapters::index::{HatIndex as RustHatIndex, HatConfig, ConsolidationConfig, Consolidate};
-use crate::ports::Near;
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+use pyo3::prelude::*;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyValueError, PyIOError, PyTypeError};
+use pyo3::types::{PyBytes, PyDict, PyString};
+
+use crate::core::{Blob, Fact, Id, Metadata, MetaValue, Point, Provenance};
+use crate::core::clustering::KMeans;
+use crate::core::projection::Projector;
+use crate::core::merge::Mean;
+use crate::core::proximity::{Proximity, ScoreOrder};
+use crate::adapters::index::{
+    compress_shared, consolidate_shared, ChunkCluster, CompressionConfig, HatIndex as RustHatIndex, HatConfig,
+    ConsolidationConfig, Consolidate, ContradictionCheck, EphemeralPoint, GroupBy, GroupKey, MemorySummary, Summarizer,
+};
+use crate::adapters::attention::{CompressedKV, Role, Roles};
+use crate::engine::Job;
+use crate::ports::{JobHandle, Near, QueryParams, ReturnFields, SearchResult};
+use crate::error::{ArmsError, ErrorCode};
 
 /// Python wrapper for search results
 #[pyclass(name = "SearchResult")]
@@ -46,10 +100,47 @@ pub struct PySearchResult {
     /// The similarity/distance score
     #[pyo3(get)]
     pub score: f32,
+
+    /// Attached payload bytes (if any)
+    blob: Option<Vec<u8>>,
+
+    /// Attached metadata (if any)
+    metadata: Option<Metadata>,
+
+    /// The stored embedding (only when asked for with `fields`)
+    #[pyo3(get)]
+    pub vector: Option<Vec<f32>>,
+
+    /// Where the point came from (if recorded)
+    provenance: Option<Provenance>,
 }
 
 #[pymethods]
 impl PySearchResult {
+    /// The attached payload: dict for metadata, bytes for raw/str payloads, else None
+    #[getter]
+    fn payload(&self, py: Python<'_>) -> PyResult<PyObject> {
+        payload_to_py(py, self.blob.as_deref(), self.metadata.as_ref())
+    }
+
+    /// The payload decoded as UTF-8 text (None if absent or not text)
+    #[getter]
+    fn text(&self) -> Option<String> {
+        self.blob.as_ref().and_then(|b| String::from_utf8(b.clone()).ok())
+    }
+
+    /// Provenance as a dict (source, pipeline, model_version, confidence), or None
+    #[getter]
+    fn provenance<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        self.provenance.as_ref().map(|p| metadata_to_dict(py, &p.to_metadata())).transpose()
+    }
+
+    /// Recorded confidence of the point (None if not recorded)
+    #[getter]
+    fn confidence(&self) -> Option<f32> {
+        self.provenance.as_ref().and_then(|p| p.confidence)
+    }
+
     fn __repr__(&self) -> String {
         format!("SearchResult(id='{}', score={:.4})", self.id, self.score)
     }
@@ -64,13 +155,26 @@ impl PySearchResult {
 #[derive(Clone)]
 pub struct PyHatConfig {
     inner: HatConfig,
+    proximity: String,
 }
 
 #[pymethods]
 impl PyHatConfig {
     #[new]
     fn new() -> Self {
-        Self { inner: HatConfig::default() }
+        Self { inner: HatConfig::default(), proximity: "cosine".to_string() }
+    }
+
+    /// Set proximity: "cosine" (default), "euclidean" or "dot_product"
+    fn with_proximity<'py>(mut slf: PyRefMut<'py, Self>, proximity: &str) -> PyResult<PyRefMut<'py, Self>> {
+        if RustHatIndex::with_proximity_name(0, proximity).is_none() {
+            return Err(PyValueError::new_err(format!(
+                "Unknown proximity '{}', expected 'cosine', 'euclidean' or 'dot_product'",
+                proximity
+            )));
+        }
+        slf.proximity = proximity.to_string();
+        Ok(slf)
     }
 
     /// Set beam width for search (default: 3)
@@ -85,6 +189,12 @@ impl PyHatConfig {
         slf
     }
 
+    /// Set worker threads for `HatIndex.near_batch` (default: 1)
+    fn with_n_threads(mut slf: PyRefMut<'_, Self>, n_threads: usize) -> PyRefMut<'_, Self> {
+        slf.inner.n_threads = n_threads.max(1);
+        slf
+    }
+
     /// Set propagation threshold for sparse updates
     fn with_propagation_threshold(mut slf: PyRefMut<'_, Self>, threshold: f32) -> PyRefMut<'_, Self> {
         slf.inner.propagation_threshold = threshold;
@@ -93,8 +203,9 @@ impl PyHatConfig {
 
     fn __repr__(&self) -> String {
         format!(
-            "HatConfig(beam_width={}, temporal_weight={:.2}, propagation_threshold={:.3})",
-            self.inner.beam_width, self.inner.temporal_weight, self.inner.propagation_threshold
+            "HatConfig(proximity='{}', beam_width={}, temporal_weight={:.2}, propagation_threshold={:.3}, n_threads={})",
+            self.proximity, self.inner.beam_width, self.inner.temporal_weight, self.inner.propagation_threshold,
+            self.inner.n_threads
         )
     }
 }
@@ -114,6 +225,10 @@ pub struct PySessionSummary {
 
     #[pyo3(get)]
     pub timestamp_ms: u64,
+
+    /// Text set with `set_session_summary` (None if unset)
+    #[pyo3(get)]
+    pub summary: Option<String>,
 }
 
 #[pymethods]
@@ -126,6 +241,58 @@ impl PySessionSummary {
     }
 }
 
+/// A (subject, predicate, value) statement from the facts store
+#[pyclass(name = "Fact")]
+#[derive(Clone)]
+pub struct PyFact {
+    #[pyo3(get)]
+    pub subject: String,
+
+    #[pyo3(get)]
+    pub predicate: String,
+
+    value: MetaValue,
+
+    #[pyo3(get)]
+    pub confidence: f32,
+
+    /// ID of the chunk it was extracted from (None if not tied to one)
+    #[pyo3(get)]
+    pub source: Option<String>,
+
+    #[pyo3(get)]
+    pub updated_ms: u64,
+}
+
+impl From<&Fact> for PyFact {
+    fn from(fact: &Fact) -> Self {
+        Self {
+            subject: fact.subject.clone(),
+            predicate: fact.predicate.clone(),
+            value: fact.value.clone(),
+            confidence: fact.confidence,
+            source: fact.source.map(|id| format!("{}", id)),
+            updated_ms: fact.updated_ms,
+        }
+    }
+}
+
+#[pymethods]
+impl PyFact {
+    /// The value: str, int, float, bool or None
+    #[getter]
+    fn value(&self, py: Python<'_>) -> PyObject {
+        meta_value_to_py(py, &self.value)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Fact(subject='{}', predicate='{}', value={:?}, confidence={:.2})",
+            self.subject, self.predicate, self.value, self.confidence
+        )
+    }
+}
+
 /// Document summary for mid-level retrieval
 #[pyclass(name = "DocumentSummary")]
 #[derive(Clone)]
@@ -138,6 +305,10 @@ pub struct PyDocumentSummary {
 
     #[pyo3(get)]
     pub chunk_count: usize,
+
+    /// Text set with `set_document_summary` (None if unset)
+    #[pyo3(get)]
+    pub summary: Option<String>,
 }
 
 #[pymethods]
@@ -150,6 +321,167 @@ impl PyDocumentSummary {
     }
 }
 
+/// Best chunks sharing a session, document or metadata value
+#[pyclass(name = "ResultGroup")]
+#[derive(Clone)]
+pub struct PyResultGroup {
+    key: GroupKey,
+
+    /// Most relevant first
+    #[pyo3(get)]
+    pub results: Vec<PySearchResult>,
+}
+
+#[pymethods]
+impl PyResultGroup {
+    /// Session/document ID (hex string) or metadata value
+    #[getter]
+    fn key(&self, py: Python<'_>) -> PyObject {
+        match &self.key {
+            GroupKey::Container(id) => format!("{}", id).into_py(py),
+            GroupKey::Value(value) => meta_value_to_py(py, value),
+        }
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> String {
+        format!("ResultGroup(key={}, results={})", self.key(py), self.results.len())
+    }
+}
+
+/// A background operation: progress, ETA and cancellation
+#[pyclass(name = "Job")]
+pub struct PyJob {
+    #[pyo3(get)]
+    name: String,
+
+    handle: JobHandle,
+
+    /// The worker; True when it ran to completion (None once waited on)
+    job: Mutex<Option<Job<bool>>>,
+}
+
+impl From<Job<bool>> for PyJob {
+    fn from(job: Job<bool>) -> Self {
+        Self {
+            name: job.name().to_string(),
+            handle: job.handle().clone(),
+            job: Mutex::new(Some(job)),
+        }
+    }
+}
+
+#[pymethods]
+impl PyJob {
+    /// Share of the work done, 0.0 - 1.0
+    #[getter]
+    fn progress(&self) -> f32 {
+        self.handle.progress().fraction()
+    }
+
+    /// Share of the work done, 0 - 100
+    #[getter]
+    fn percent(&self) -> f32 {
+        self.handle.progress().percent()
+    }
+
+    /// Estimated seconds left (None until some work is done)
+    #[getter]
+    fn eta(&self) -> Option<f64> {
+        self.handle.progress().eta().map(|eta| eta.as_secs_f64())
+    }
+
+    /// Seconds since the job started
+    #[getter]
+    fn elapsed(&self) -> f64 {
+        self.handle.progress().elapsed.as_secs_f64()
+    }
+
+    #[getter]
+    fn finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.handle.is_cancelled()
+    }
+
+    /// Ask the job to stop at its next step
+    fn cancel(&self) {
+        self.handle.cancel();
+    }
+
+    /// Block until the job ends (GIL released)
+    ///
+    /// Returns:
+    ///     bool: True if it ran to completion, False if cancelled
+    fn wait(&self, py: Python<'_>) -> PyResult<bool> {
+        let job = self.job.lock().unwrap_or_else(PoisonError::into_inner).take();
+        match job {
+            Some(job) => py.allow_threads(|| job.wait()).map_err(py_err),
+            None => Err(PyValueError::new_err("job already waited on")),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        let progress = self.handle.progress();
+        format!("Job(name='{}', percent={:.1}, finished={})", self.name, progress.percent(), progress.finished)
+    }
+}
+
+/// A run of documents sharing a dominant topic
+#[pyclass(name = "TopicSpan")]
+#[derive(Clone)]
+pub struct PyTopicSpan {
+    /// Topic (cluster) label, 0 = largest
+    #[pyo3(get)]
+    pub topic: usize,
+
+    /// Chunk closest to the topic's centroid (hex ID)
+    #[pyo3(get)]
+    pub representative: String,
+
+    #[pyo3(get)]
+    pub start_ms: u64,
+
+    #[pyo3(get)]
+    pub end_ms: u64,
+
+    #[pyo3(get)]
+    pub documents: usize,
+
+    #[pyo3(get)]
+    pub chunks: usize,
+}
+
+#[pymethods]
+impl PyTopicSpan {
+    fn __repr__(&self) -> String {
+        format!(
+            "TopicSpan(topic={}, documents={}, chunks={})",
+            self.topic, self.documents, self.chunks
+        )
+    }
+}
+
+/// Dominant topics of a session in time order
+#[pyclass(name = "SessionTimeline")]
+#[derive(Clone)]
+pub struct PySessionTimeline {
+    #[pyo3(get)]
+    pub session: String,
+
+    #[pyo3(get)]
+    pub spans: Vec<PyTopicSpan>,
+}
+
+#[pymethods]
+impl PySessionTimeline {
+    fn __repr__(&self) -> String {
+        format!("SessionTimeline(session='{}', spans={})", self.session, self.spans.len())
+    }
+}
+
 /// Index statistics
 #[pyclass(name = "HatStats")]
 #[derive(Clone)]
@@ -183,143 +515,1303 @@ impl PyHatStats {
     }
 }
 
-/// Hierarchical Attention Tree Index
+/// Compressed KV cache for attention-state reuse
 ///
-/// A semantic memory index optimized for conversation history retrieval.
-/// Uses hierarchical structure (session -> document -> chunk) to enable
-/// O(log n) queries while maintaining high recall.
-#[pyclass(name = "HatIndex")]
-pub struct PyHatIndex {
-    inner: RustHatIndex,
+/// Raw tensor bytes in `[layer][head][seq][key/value][head_dim]` layout.
+/// See `arms_hat.hf` for conversion to/from HuggingFace caches.
+#[pyclass(name = "CompressedKV")]
+#[derive(Clone)]
+pub struct PyCompressedKV {
+    inner: CompressedKV,
 }
 
 #[pymethods]
-impl PyHatIndex {
-    /// Create a new HAT index with cosine similarity
-    ///
-    /// Args:
-    ///     dimensionality: Number of embedding dimensions (e.g., 1536 for OpenAI)
-    #[staticmethod]
-    fn cosine(dimensionality: usize) -> Self {
+impl PyCompressedKV {
+    #[new]
+    #[pyo3(signature = (model_id, num_layers, num_heads, head_dim, seq_len, quantization, data))]
+    fn new(
+        model_id: String,
+        num_layers: u32,
+        num_heads: u32,
+        head_dim: u32,
+        seq_len: u32,
+        quantization: String,
+        data: Vec<u8>,
+    ) -> Self {
         Self {
-            inner: RustHatIndex::cosine(dimensionality),
+            inner: CompressedKV {
+                model_id,
+                num_layers,
+                num_heads,
+                head_dim,
+                seq_len,
+                quantization,
+                data,
+            },
         }
     }
 
-    /// Create a new HAT index with custom configuration
-    ///
-    /// Args:
-    ///     dimensionality: Number of embedding dimensions
-    ///     config: HatConfig instance
-    #[staticmethod]
-    fn with_config(dimensionality: usize, config: &PyHatConfig) -> Self {
-        Self {
-            inner: RustHatIndex::cosine(dimensionality).with_config(config.inner.clone()),
-        }
+    #[getter]
+    fn model_id(&self) -> &str {
+        &self.inner.model_id
     }
 
-    /// Add an embedding to the index
-    ///
-    /// Args:
-    ///     embedding: List of floats (must match dimensionality)
-    ///
-    /// Returns:
-    ///     str: The generated ID as a hex string
-    fn add(&mut self, embedding: Vec<f32>) -> PyResult<String> {
-        let point = Point::new(embedding);
-        let id = Id::now();
+    #[getter]
+    fn num_layers(&self) -> u32 {
+        self.inner.num_layers
+    }
 
-        self.inner.add(id, &point)
-            .map_err(|e| PyValueError::new_err(format!("{}", e)))?;
+    #[getter]
+    fn num_heads(&self) -> u32 {
+        self.inner.num_heads
+    }
 
-        Ok(format!("{}", id))
+    #[getter]
+    fn head_dim(&self) -> u32 {
+        self.inner.head_dim
     }
 
-    /// Add an embedding with a custom ID
-    ///
-    /// Args:
-    ///     id_hex: 32-character hex string for the ID
-    ///     embedding: List of floats (must match dimensionality)
-    fn add_with_id(&mut self, id_hex: &str, embedding: Vec<f32>) -> PyResult<()> {
-        let id = parse_id_hex(id_hex)?;
-        let point = Point::new(embedding);
+    #[getter]
+    fn seq_len(&self) -> u32 {
+        self.inner.seq_len
+    }
 
-        self.inner.add(id, &point)
-            .map_err(|e| PyValueError::new_err(format!("{}", e)))?;
+    #[getter]
+    fn quantization(&self) -> &str {
+        &self.inner.quantization
+    }
 
-        Ok(())
+    /// Raw KV tensor bytes
+    #[getter]
+    fn data<'py>(&self, py: Python<'py>) -> Bound<'py, pyo3::types::PyBytes> {
+        pyo3::types::PyBytes::new_bound(py, &self.inner.data)
     }
 
-    /// Find k nearest neighbors to a query embedding
-    ///
-    /// Args:
-    ///     query: Query embedding (list of floats)
-    ///     k: Number of results to return
-    ///
-    /// Returns:
-    ///     List[SearchResult]: Results sorted by relevance (best first)
-    fn near(&self, query: Vec<f32>, k: usize) -> PyResult<Vec<PySearchResult>> {
-        let point = Point::new(query);
+    /// Size of the KV data in bytes
+    fn size_bytes(&self) -> usize {
+        self.inner.size_bytes()
+    }
 
-        let results = self.inner.near(&point, k)
-            .map_err(|e| PyValueError::new_err(format!("{}", e)))?;
+    /// Serialize to bytes
+    fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, pyo3::types::PyBytes> {
+        pyo3::types::PyBytes::new_bound(py, &self.inner.to_bytes())
+    }
 
-        Ok(results.into_iter().map(|r| PySearchResult {
-            id: format!("{}", r.id),
-            score: r.score,
-        }).collect())
+    /// Deserialize from bytes
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        let (inner, _) = CompressedKV::from_bytes(data)
+            .ok_or_else(|| PyValueError::new_err("Invalid CompressedKV data"))?;
+        Ok(Self { inner })
     }
 
-    /// Start a new session (conversation boundary)
-    ///
-    /// Call this when starting a new conversation or context.
-    fn new_session(&mut self) {
-        self.inner.new_session();
+    fn __repr__(&self) -> String {
+        format!(
+            "CompressedKV(model_id='{}', layers={}, heads={}, head_dim={}, seq_len={}, quantization='{}')",
+            self.inner.model_id,
+            self.inner.num_layers,
+            self.inner.num_heads,
+            self.inner.head_dim,
+            self.inner.seq_len,
+            self.inner.quantization
+        )
     }
+}
 
-    /// Start a new document within the current session
-    ///
-    /// Call this for logical groupings within a conversation
-    /// (e.g., topic change, user turn).
-    fn new_document(&mut self) {
-        self.inner.new_document();
+/// Proximity backed by Python callables
+///
+/// Every score crosses into the interpreter, so this is orders of magnitude
+/// slower than the built-in functions; meant for small research indexes.
+/// Errors can't propagate through `Proximity`, so the first one is kept,
+/// later calls short-circuit, and the index raises it after the operation.
+struct PyProximity {
+    /// `fn(a, b) -> float`
+    func: PyObject,
+
+    /// Optional `fn(query, candidates) -> sequence of floats`
+    batch: Option<PyObject>,
+
+    /// Whether scores are similarities or distances
+    order: ScoreOrder,
+
+    /// First error raised by a callable
+    error: Mutex<Option<PyErr>>,
+}
+
+impl PyProximity {
+    fn take_error(&self) -> Option<PyErr> {
+        self.error.lock().unwrap().take()
     }
 
-    /// Get index statistics
-    fn stats(&self) -> PyHatStats {
-        let s = self.inner.stats();
-        PyHatStats {
-            global_count: s.global_count,
-            session_count: s.session_count,
-            document_count: s.document_count,
-            chunk_count: s.chunk_count,
+    /// Run a callable unless an earlier call failed, recording its error
+    fn call<T>(&self, f: impl FnOnce(Python<'_>) -> PyResult<T>) -> Option<T> {
+        // Not held across the call, so a callable may query the index itself
+        if self.error.lock().unwrap().is_some() {
+            return None;
+        }
+        match Python::with_gil(f) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.error.lock().unwrap().get_or_insert(err);
+                None
+            }
         }
     }
+}
 
-    /// Get the number of indexed points
-    fn __len__(&self) -> usize {
-        self.inner.len()
+/// Summarizer backed by a Python callable
+///
+/// `fn(chunks) -> (text, vector) | None`, where `chunks` is a list of
+/// `(id, vector, payload)` tuples. Like `PyProximity`, the first error is
+/// kept (declining every later cluster) and raised once the pass is over.
+struct PySummarizer {
+    func: PyObject,
+    error: Mutex<Option<PyErr>>,
+}
+
+impl PySummarizer {
+    fn new(func: PyObject) -> Self {
+        Self { func, error: Mutex::new(None) }
     }
 
-    /// Check if the index is empty
-    fn is_empty(&self) -> bool {
-        self.inner.is_empty()
+    fn take_error(&self) -> Option<PyErr> {
+        self.error.lock().unwrap().take()
     }
+}
 
-    /// Remove a point by ID
-    ///
-    /// Args:
-    ///     id_hex: 32-character hex string for the ID
-    fn remove(&mut self, id_hex: &str) -> PyResult<()> {
-        let id = parse_id_hex(id_hex)?;
+impl Summarizer for PySummarizer {
+    fn summarize(&self, cluster: &ChunkCluster) -> Option<MemorySummary> {
+        if self.error.lock().unwrap().is_some() {
+            return None;
+        }
+        let summary = Python::with_gil(|py| {
+            let chunks = cluster.chunks
+                .iter()
+                .map(|chunk| {
+                    let payload = payload_to_py(py, chunk.payload.as_ref().map(|blob| blob.data()), None)?;
+                    Ok((format!("{}", chunk.id), chunk.point.dims().to_vec(), payload))
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            let summary: Option<(String, Vec<f32>)> = self.func.call1(py, (chunks,))?.extract(py)?;
+            Ok(summary.map(|(text, vector)| MemorySummary { text, vector: Point::new(vector) }))
+        });
+        summary.unwrap_or_else(|err: PyErr| {
+            self.error.lock().unwrap().get_or_insert(err);
+            None
+        })
+    }
+}
 
-        self.inner.remove(id)
-            .map_err(|e| PyValueError::new_err(format!("{}", e)))?;
+/// Reject NaN scores, which can't be ranked
+fn check_score(score: f32) -> PyResult<f32> {
+    if score.is_nan() {
+        Err(PyValueError::new_err("proximity returned NaN"))
+    } else {
+        Ok(score)
+    }
+}
 
-        Ok(())
+impl Proximity for PyProximity {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        self.proximity_slices(a.dims(), b.dims())
     }
 
-    /// Find similar sessions (coarse-grained search)
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        self.call(|py| {
+            let score: f32 = self.func.call1(py, (a.to_vec(), b.to_vec()))?.extract(py)?;
+            check_score(score)
+        })
+        .unwrap_or(0.0)
+    }
+
+    fn proximity_batch(&self, query: &Point, candidates: &[&Point]) -> Vec<f32> {
+        let rows: Vec<&[f32]> = candidates.iter().map(|c| c.dims()).collect();
+        self.proximity_batch_slices(query.dims(), &rows)
+    }
+
+    fn proximity_batch_slices(&self, query: &[f32], candidates: &[&[f32]]) -> Vec<f32> {
+        let Some(batch) = &self.batch else {
+            return candidates.iter().map(|c| self.proximity_slices(query, c)).collect();
+        };
+
+        let scores = self.call(|py| {
+            let rows: Vec<Vec<f32>> = candidates.iter().map(|c| c.to_vec()).collect();
+            let scores: Vec<f32> = batch.call1(py, (query.to_vec(), rows))?.extract(py)?;
+            if scores.len() != candidates.len() {
+                return Err(PyValueError::new_err(format!(
+                    "batch proximity returned {} scores for {} candidates",
+                    scores.len(),
+                    candidates.len()
+                )));
+            }
+            scores.into_iter().map(check_score).collect::<PyResult<Vec<f32>>>()
+        });
+        scores.unwrap_or_else(|| vec![0.0; candidates.len()])
+    }
+
+    fn order(&self) -> ScoreOrder {
+        self.order
+    }
+
+    fn name(&self) -> &'static str {
+        "python"
+    }
+}
+
+/// Hierarchical Attention Tree Index
+///
+/// A semantic memory index optimized for conversation history retrieval.
+/// Uses hierarchical structure (session -> document -> chunk) to enable
+/// O(log n) queries while maintaining high recall.
+///
+/// Safe to share between threads: searches run concurrently with the GIL
+/// released, and writes wait for them (and vice versa).
+#[pyclass(name = "HatIndex", frozen)]
+pub struct PyHatIndex {
+    inner: Arc<RwLock<RustHatIndex>>,
+
+    /// Python proximity in use, if any (holds errors raised by the callable)
+    custom_proximity: Option<Arc<PyProximity>>,
+}
+
+impl From<RustHatIndex> for PyHatIndex {
+    fn from(inner: RustHatIndex) -> Self {
+        Self { inner: Arc::new(RwLock::new(inner)), custom_proximity: None }
+    }
+}
+
+impl PyHatIndex {
+    /// Read access while holding the GIL
+    ///
+    /// Never blocks with the GIL held: a writer may need it to finish (a
+    /// Python proximity), so contention waits with the GIL released.
+    fn read(&self, py: Python<'_>) -> RwLockReadGuard<'_, RustHatIndex> {
+        loop {
+            match self.inner.try_read() {
+                Ok(guard) => return guard,
+                Err(TryLockError::Poisoned(e)) => return e.into_inner(),
+                Err(TryLockError::WouldBlock) => py.allow_threads(std::thread::yield_now),
+            }
+        }
+    }
+
+    /// Write access while holding the GIL (see `read`)
+    fn write(&self, py: Python<'_>) -> RwLockWriteGuard<'_, RustHatIndex> {
+        loop {
+            match self.inner.try_write() {
+                Ok(guard) => return guard,
+                Err(TryLockError::Poisoned(e)) => return e.into_inner(),
+                Err(TryLockError::WouldBlock) => py.allow_threads(std::thread::yield_now),
+            }
+        }
+    }
+
+    /// Run a read-only operation with the GIL released
+    fn with_read<T: Send>(&self, py: Python<'_>, f: impl FnOnce(&RustHatIndex) -> T + Send) -> T {
+        py.allow_threads(|| f(&self.inner.read().unwrap_or_else(PoisonError::into_inner)))
+    }
+
+    /// Run a mutating operation with the GIL released
+    fn with_write<T: Send>(&self, py: Python<'_>, f: impl FnOnce(&mut RustHatIndex) -> T + Send) -> T {
+        py.allow_threads(|| f(&mut self.inner.write().unwrap_or_else(PoisonError::into_inner)))
+    }
+
+    /// Raise the first error from a Python proximity callable, if any
+    fn check_proximity(&self) -> PyResult<()> {
+        match self.custom_proximity.as_ref().and_then(|p| p.take_error()) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Add points and attach their payloads, with the GIL released
+    fn add_points(&self, py: Python<'_>, points: Vec<NewPoint>) -> PyResult<()> {
+        let added = self.with_write(py, |index| {
+            for (id, embedding, payload, role) in points {
+                index.add(id, &Point::new(embedding))?;
+                match payload {
+                    Some(Payload::Blob(blob)) => index.set_payload(id, blob)?,
+                    Some(Payload::Metadata(metadata)) => index.set_metadata(id, metadata)?,
+                    None => {}
+                }
+                if let Some(role) = role {
+                    index.set_role(id, role)?;
+                }
+            }
+            Ok::<_, crate::ports::NearError>(())
+        });
+        added.map_err(py_err)?;
+        self.check_proximity()
+    }
+}
+
+// One exception per `ErrorCode`. Bad arguments and index state subclass
+// `ValueError`; storage, decoding and OS failures subclass `IOError`.
+create_exception!(arms_hat, DimensionalityMismatchError, PyValueError, "A point or query has the wrong number of dimensions");
+create_exception!(arms_hat, CapacityExceededError, PyValueError, "Storage is full");
+create_exception!(arms_hat, DuplicateIdError, PyValueError, "An ID is already in use");
+create_exception!(arms_hat, NotFoundError, PyValueError, "An ID is unknown");
+create_exception!(arms_hat, IndexNotReadyError, PyValueError, "The index can't answer queries yet");
+create_exception!(arms_hat, InvalidInputError, PyValueError, "Caller-supplied data is malformed or inconsistent");
+create_exception!(arms_hat, CancelledError, PyValueError, "The operation was cancelled through its job handle");
+create_exception!(arms_hat, CorruptedError, PyIOError, "Stored or serialized data can't be decoded");
+create_exception!(arms_hat, UnsupportedVersionError, PyIOError, "Serialized data uses a format version this build doesn't read");
+create_exception!(arms_hat, ArmsIOError, PyIOError, "Operating system I/O failure");
+create_exception!(arms_hat, BackendError, PyIOError, "A backend (model, storage, index) failed");
+create_exception!(arms_hat, ReadOnlyError, PyIOError, "A write reached a store opened read-only");
+create_exception!(arms_hat, LockedError, PyIOError, "Another process holds the resource's lock");
+
+/// Convert a crate error into the Python exception for its `ErrorCode`
+fn py_err(e: impl Into<ArmsError>) -> PyErr {
+    let e = e.into();
+    let message = e.to_string();
+    match e.code() {
+        ErrorCode::DimensionalityMismatch => DimensionalityMismatchError::new_err(message),
+        ErrorCode::CapacityExceeded => CapacityExceededError::new_err(message),
+        ErrorCode::DuplicateId => DuplicateIdError::new_err(message),
+        ErrorCode::NotFound => NotFoundError::new_err(message),
+        ErrorCode::IndexNotReady => IndexNotReadyError::new_err(message),
+        ErrorCode::InvalidInput => InvalidInputError::new_err(message),
+        ErrorCode::Cancelled => CancelledError::new_err(message),
+        ErrorCode::Corrupted => CorruptedError::new_err(message),
+        ErrorCode::UnsupportedVersion => UnsupportedVersionError::new_err(message),
+        ErrorCode::Io => ArmsIOError::new_err(message),
+        ErrorCode::Backend => BackendError::new_err(message),
+        ErrorCode::ReadOnly => ReadOnlyError::new_err(message),
+        ErrorCode::Locked => LockedError::new_err(message),
+    }
+}
+
+/// Points sorted by ID (IDs are timestamp-prefixed, so oldest first)
+fn sorted_items(index: &RustHatIndex) -> Vec<(Id, &Point)> {
+    let mut items: Vec<_> = index.iter().collect();
+    items.sort_by_key(|(id, _)| *id);
+    items
+}
+
+/// Payload of a point as its Python value (dict, bytes, or None)
+fn payload_object(py: Python<'_>, index: &RustHatIndex, id: Id) -> PyResult<PyObject> {
+    payload_to_py(py, index.payload(id).map(|b| b.data()), index.metadata(id))
+}
+
+/// Build a search result with its payload attached
+fn search_result(index: &RustHatIndex, id: Id, score: f32) -> PySearchResult {
+    projected_result(index, id, score, ReturnFields::default())
+}
+
+/// Build a search result with the requested fields attached
+fn projected_result(index: &RustHatIndex, id: Id, score: f32, fields: ReturnFields) -> PySearchResult {
+    PySearchResult {
+        id: format!("{}", id),
+        score,
+        blob: index.payload(id).filter(|_| fields.blob).map(|b| b.data().to_vec()),
+        metadata: index.metadata(id).filter(|_| fields.metadata).cloned(),
+        vector: index.get(id).filter(|_| fields.vector).map(|p| p.dims().to_vec()),
+        provenance: index.provenance(id).filter(|_| fields.provenance).cloned(),
+    }
+}
+
+/// Parse a `fields` argument (None = the default fields)
+fn parse_fields(fields: Option<Vec<String>>) -> PyResult<ReturnFields> {
+    match fields {
+        Some(fields) => ReturnFields::parse(&fields.join(",")).map_err(PyValueError::new_err),
+        None => Ok(ReturnFields::default()),
+    }
+}
+
+/// A payload passed to `add`
+enum Payload {
+    Blob(Blob),
+    Metadata(Metadata),
+}
+
+/// A point for `add_points`: ID, embedding, payload and role
+type NewPoint = (Id, Vec<f32>, Option<Payload>, Option<Role>);
+
+/// Convert a Python payload (bytes, str, or dict of scalars)
+fn extract_payload(obj: &Bound<'_, PyAny>) -> PyResult<Payload> {
+    if let Ok(bytes) = obj.downcast::<PyBytes>() {
+        Ok(Payload::Blob(Blob::new(bytes.as_bytes().to_vec())))
+    } else if let Ok(s) = obj.downcast::<PyString>() {
+        Ok(Payload::Blob(Blob::from_str(s.to_str()?)))
+    } else if let Ok(dict) = obj.downcast::<PyDict>() {
+        Ok(Payload::Metadata(extract_metadata(dict)?))
+    } else {
+        Err(PyTypeError::new_err("payload must be bytes, str, or dict"))
+    }
+}
+
+/// Convert a dict of scalars to metadata
+fn extract_metadata(dict: &Bound<'_, PyDict>) -> PyResult<Metadata> {
+    let mut metadata = Metadata::new();
+    for (key, value) in dict.iter() {
+        let key: String = key
+            .extract()
+            .map_err(|_| PyTypeError::new_err("payload dict keys must be str"))?;
+        let value = extract_meta_value(&value)
+            .map_err(|_| PyTypeError::new_err(format!(
+                "payload['{}'] must be str, int, float, bool, or None", key
+            )))?;
+        metadata.insert(key, value);
+    }
+    Ok(metadata)
+}
+
+fn extract_meta_value(value: &Bound<'_, PyAny>) -> PyResult<MetaValue> {
+    // bool before int: Python bools are ints
+    if value.is_none() {
+        Ok(MetaValue::Null)
+    } else if let Ok(b) = value.downcast::<pyo3::types::PyBool>() {
+        Ok(MetaValue::Bool(b.is_true()))
+    } else if let Ok(i) = value.downcast::<pyo3::types::PyInt>() {
+        Ok(MetaValue::Int(i.extract()?))
+    } else if let Ok(f) = value.downcast::<pyo3::types::PyFloat>() {
+        Ok(MetaValue::Float(f.value()))
+    } else if let Ok(s) = value.downcast::<PyString>() {
+        Ok(MetaValue::Str(s.to_str()?.to_string()))
+    } else {
+        Err(PyTypeError::new_err("unsupported metadata value"))
+    }
+}
+
+fn metadata_to_dict<'py>(py: Python<'py>, metadata: &Metadata) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    for (key, value) in metadata {
+        dict.set_item(key, meta_value_to_py(py, value))?;
+    }
+    Ok(dict)
+}
+
+fn meta_value_to_py(py: Python<'_>, value: &MetaValue) -> PyObject {
+    match value {
+        MetaValue::Null => py.None(),
+        MetaValue::Bool(b) => b.into_py(py),
+        MetaValue::Int(i) => i.into_py(py),
+        MetaValue::Float(f) => f.into_py(py),
+        MetaValue::Str(s) => s.into_py(py),
+    }
+}
+
+fn payload_to_py(py: Python<'_>, blob: Option<&[u8]>, metadata: Option<&Metadata>) -> PyResult<PyObject> {
+    if let Some(metadata) = metadata {
+        Ok(metadata_to_dict(py, metadata)?.into_any().unbind())
+    } else if let Some(blob) = blob {
+        Ok(PyBytes::new_bound(py, blob).into_any().unbind())
+    } else {
+        Ok(py.None())
+    }
+}
+
+#[pymethods]
+impl PyHatIndex {
+    /// Create a new HAT index with cosine similarity
+    ///
+    /// Args:
+    ///     dimensionality: Number of embedding dimensions (e.g., 1536 for OpenAI)
+    #[staticmethod]
+    fn cosine(dimensionality: usize) -> Self {
+        RustHatIndex::cosine(dimensionality).into()
+    }
+
+    /// Create a new HAT index with Euclidean distance
+    ///
+    /// For embeddings that are not normalized. Result scores are distances
+    /// (lower = closer).
+    ///
+    /// Args:
+    ///     dimensionality: Number of embedding dimensions
+    #[staticmethod]
+    fn euclidean(dimensionality: usize) -> Self {
+        RustHatIndex::euclidean(dimensionality).into()
+    }
+
+    /// Create a new HAT index with dot product similarity
+    ///
+    /// Args:
+    ///     dimensionality: Number of embedding dimensions
+    #[staticmethod]
+    fn dot_product(dimensionality: usize) -> Self {
+        RustHatIndex::dot_product(dimensionality).into()
+    }
+
+    /// Create a new HAT index with custom configuration
+    ///
+    /// Args:
+    ///     dimensionality: Number of embedding dimensions
+    ///     config: HatConfig instance (including its proximity choice)
+    #[staticmethod]
+    fn with_config(dimensionality: usize, config: &PyHatConfig) -> Self {
+        let index = RustHatIndex::with_proximity_name(dimensionality, &config.proximity)
+            .expect("HatConfig only holds known proximity names");
+        index.with_config(config.inner.clone()).into()
+    }
+
+    /// Create a new HAT index scored by a Python function
+    ///
+    /// Every comparison calls into Python, which is orders of magnitude
+    /// slower than the built-in proximities. Use it for small indexes and
+    /// experiments. Passing `batch` cuts the overhead to one call per tree
+    /// level and lets the scoring be vectorized:
+    ///
+    /// ```python
+    /// def batch(query, candidates):
+    ///     return np.asarray(candidates) @ np.asarray(query)
+    /// ```
+    ///
+    /// Custom proximities aren't saved; a loaded index uses cosine.
+    ///
+    /// Args:
+    ///     dimensionality: Number of embedding dimensions
+    ///     proximity: Callable `(a, b) -> float` taking two lists of floats
+    ///     higher_is_better: True for similarities, False for distances
+    ///     batch: Optional callable `(query, candidates) -> sequence of floats`,
+    ///         where candidates is a list of vectors; must agree with `proximity`
+    #[staticmethod]
+    #[pyo3(signature = (dimensionality, proximity, higher_is_better=true, batch=None))]
+    fn custom(
+        dimensionality: usize,
+        proximity: &Bound<'_, PyAny>,
+        higher_is_better: bool,
+        batch: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        if !proximity.is_callable() || batch.is_some_and(|b| !b.is_callable()) {
+            return Err(PyTypeError::new_err("proximity and batch must be callable"));
+        }
+
+        let custom = Arc::new(PyProximity {
+            func: proximity.clone().unbind(),
+            batch: batch.map(|b| b.clone().unbind()),
+            order: ScoreOrder::from_higher_is_better(higher_is_better),
+            error: Mutex::new(None),
+        });
+        let inner = RustHatIndex::from_proximity(
+            dimensionality,
+            custom.clone(),
+            Arc::new(Mean),
+            HatConfig::default(),
+        );
+
+        Ok(Self { inner: Arc::new(RwLock::new(inner)), custom_proximity: Some(custom) })
+    }
+
+    /// Name of the proximity function ("cosine", "euclidean", "dot_product", or "python")
+    #[getter]
+    fn proximity(&self, py: Python<'_>) -> &'static str {
+        self.read(py).proximity_name()
+    }
+
+    /// Add an embedding to the index
+    ///
+    /// Args:
+    ///     embedding: List of floats (must match dimensionality)
+    ///     payload: Optional bytes, str (stored as UTF-8), or dict of
+    ///         str/int/float/bool/None values (stored as typed metadata)
+    ///     role: Optional conversation role: "system", "user", "assistant",
+    ///         "tool" or "context" (see `near(roles=...)`)
+    ///
+    /// Returns:
+    ///     str: The generated ID as a hex string
+    #[pyo3(signature = (embedding, payload=None, role=None))]
+    fn add(&self, py: Python<'_>, embedding: Vec<f32>, payload: Option<&Bound<'_, PyAny>>, role: Option<&str>) -> PyResult<String> {
+        let id = Id::now();
+        let payload = payload.map(extract_payload).transpose()?;
+        let role = role.map(parse_role).transpose()?;
+        self.add_points(py, vec![(id, embedding, payload, role)])?;
+        Ok(format!("{}", id))
+    }
+
+    /// Add an embedding, returning stored memories it may contradict
+    ///
+    /// Candidates are chunks whose dict payload has `marker` set - True, or
+    /// a slot name like "residence". Give the new payload a slot too to
+    /// only meet memories in the same slot.
+    ///
+    /// Args:
+    ///     embedding: List of floats (must match dimensionality)
+    ///     payload: Optional bytes, str, or dict (see `add`)
+    ///     threshold: Score a candidate must reach
+    ///     marker: Payload key marking candidates
+    ///     k: Most candidates returned
+    ///
+    /// Returns:
+    ///     Tuple[str, List[SearchResult]]: The new ID, and the candidates
+    ///     closest first
+    #[pyo3(signature = (embedding, payload=None, threshold=0.9, marker="supersedable", k=10))]
+    fn add_checked(
+        &self,
+        py: Python<'_>,
+        embedding: Vec<f32>,
+        payload: Option<&Bound<'_, PyAny>>,
+        threshold: f32,
+        marker: &str,
+        k: usize,
+    ) -> PyResult<(String, Vec<PySearchResult>)> {
+        let id = Id::now();
+        let payload = payload.map(extract_payload).transpose()?;
+        let check = ContradictionCheck::new(threshold).with_marker(marker).with_k(k);
+        let found = self.with_write(py, |index| {
+            let (blob, metadata) = match payload {
+                Some(Payload::Blob(blob)) => (Some(blob), None),
+                Some(Payload::Metadata(metadata)) => (None, Some(metadata)),
+                None => (None, None),
+            };
+            let found = index.add_checked(id, &Point::new(embedding), metadata, &check)?;
+            if let Some(blob) = blob {
+                index.set_payload(id, blob)?;
+            }
+            Ok::<_, crate::ports::NearError>(found.iter().map(|c| search_result(index, c.id, c.score)).collect())
+        });
+        let found = found.map_err(py_err)?;
+        self.check_proximity()?;
+        Ok((format!("{}", id), found))
+    }
+
+    /// Add an embedding with a custom ID
+    ///
+    /// Args:
+    ///     id_hex: 32-character hex string for the ID
+    ///     embedding: List of floats (must match dimensionality)
+    ///     payload: Optional bytes, str, or dict (see `add`)
+    ///     role: Optional conversation role (see `add`)
+    #[pyo3(signature = (id_hex, embedding, payload=None, role=None))]
+    fn add_with_id(
+        &self,
+        py: Python<'_>,
+        id_hex: &str,
+        embedding: Vec<f32>,
+        payload: Option<&Bound<'_, PyAny>>,
+        role: Option<&str>,
+    ) -> PyResult<()> {
+        let id = parse_id_hex(id_hex)?;
+        let payload = payload.map(extract_payload).transpose()?;
+        let role = role.map(parse_role).transpose()?;
+        self.add_points(py, vec![(id, embedding, payload, role)])
+    }
+
+    /// Add several embeddings in one call
+    ///
+    /// Payloads are converted up front, so a bad payload adds nothing.
+    ///
+    /// Args:
+    ///     embeddings: List of embeddings
+    ///     payloads: Optional list of payloads (see `add`), one per embedding
+    ///
+    /// Returns:
+    ///     List[str]: The generated IDs, in input order
+    #[pyo3(signature = (embeddings, payloads=None))]
+    fn add_batch(
+        &self,
+        py: Python<'_>,
+        embeddings: Vec<Vec<f32>>,
+        payloads: Option<Vec<Bound<'_, PyAny>>>,
+    ) -> PyResult<Vec<String>> {
+        let mut payloads: Vec<Option<Payload>> = match payloads {
+            Some(payloads) if payloads.len() != embeddings.len() => {
+                return Err(PyValueError::new_err(format!(
+                    "Got {} payloads for {} embeddings",
+                    payloads.len(),
+                    embeddings.len()
+                )));
+            }
+            Some(payloads) => payloads
+                .iter()
+                .map(|p| if p.is_none() { Ok(None) } else { extract_payload(p).map(Some) })
+                .collect::<PyResult<_>>()?,
+            None => Vec::new(),
+        };
+        payloads.resize_with(embeddings.len(), || None);
+
+        let points: Vec<_> = embeddings
+            .into_iter()
+            .zip(payloads)
+            .map(|(embedding, payload)| (Id::now(), embedding, payload, None))
+            .collect();
+        let ids = points.iter().map(|(id, _, _, _)| format!("{}", id)).collect();
+
+        self.add_points(py, points)?;
+        Ok(ids)
+    }
+
+    /// Find k nearest neighbors to a query embedding
+    ///
+    /// Args:
+    ///     query: Query embedding (list of floats)
+    ///     k: Number of results to return
+    ///     min_score: Drop results less related than this score (optional)
+    ///     dedup: Collapse results at least this close to a better one,
+    ///         e.g. 0.98 for cosine (optional)
+    ///     roles: Only return chunks added with one of these roles, e.g.
+    ///         ["assistant"] (optional)
+    ///     fields: Stored fields to attach, from "vector", "blob",
+    ///         "metadata", "provenance" (default ["blob", "metadata",
+    ///         "provenance"]); [] returns IDs and scores only
+    ///
+    /// Returns:
+    ///     List[SearchResult]: Results sorted by relevance (best first)
+    #[pyo3(signature = (query, k, min_score=None, dedup=None, roles=None, fields=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn near(
+        &self,
+        py: Python<'_>,
+        query: Vec<f32>,
+        k: usize,
+        min_score: Option<f32>,
+        dedup: Option<f32>,
+        roles: Option<Vec<String>>,
+        fields: Option<Vec<String>>,
+    ) -> PyResult<Vec<PySearchResult>> {
+        let point = Point::new(query);
+        let fields = parse_fields(fields)?;
+        let roles = match roles {
+            Some(roles) => Roles::only(roles.iter().map(|r| parse_role(r)).collect::<PyResult<Vec<_>>>()?),
+            None => Roles::all(),
+        };
+
+        let results = self.with_read(py, |index| {
+            if min_score.is_some() || dedup.is_some() || !roles.is_all() {
+                let params = QueryParams { min_score, dedup, ..QueryParams::new(k) };
+                return Ok(index
+                    .near_with_roles(&point, &params, roles)?
+                    .into_iter()
+                    .map(|r| projected_result(index, r.id, r.score, fields))
+                    .collect());
+            }
+            let mut results = Vec::with_capacity(k.min(index.len()));
+            index.near_visit(&point, k, &mut |r| {
+                results.push(projected_result(index, r.id, r.score, fields));
+                ControlFlow::Continue(())
+            })?;
+            Ok(results)
+        });
+        let results = results.map_err(|e: crate::ports::NearError| py_err(e))?;
+        self.check_proximity()?;
+
+        Ok(results)
+    }
+
+    /// Answer several queries at once
+    ///
+    /// Queries are spread over `HatConfig.with_n_threads(n)` threads, with
+    /// the GIL released.
+    ///
+    /// Args:
+    ///     queries: List of query embeddings
+    ///     k: Number of results per query
+    ///     fields: Stored fields to attach (see `near`)
+    ///
+    /// Returns:
+    ///     List[List[SearchResult]]: Results per query, in query order
+    #[pyo3(signature = (queries, k, fields=None))]
+    fn near_batch(
+        &self,
+        py: Python<'_>,
+        queries: Vec<Vec<f32>>,
+        k: usize,
+        fields: Option<Vec<String>>,
+    ) -> PyResult<Vec<Vec<PySearchResult>>> {
+        let points: Vec<Point> = queries.into_iter().map(Point::new).collect();
+        let fields = parse_fields(fields)?;
+
+        let results = self.with_read(py, |index| {
+            let batches = index.near_batch(&points, k)?;
+            Ok(batches
+                .into_iter()
+                .map(|results| results.into_iter().map(|r| projected_result(index, r.id, r.score, fields)).collect())
+                .collect())
+        });
+        let results = results.map_err(|e: crate::ports::NearError| py_err(e))?;
+        self.check_proximity()?;
+
+        Ok(results)
+    }
+
+    /// Start a new session (conversation boundary)
+    ///
+    /// Call this when starting a new conversation or context.
+    fn new_session(&self, py: Python<'_>) {
+        self.write(py).new_session();
+    }
+
+    /// Start a new document within the current session
+    ///
+    /// Call this for logical groupings within a conversation
+    /// (e.g., topic change, user turn).
+    fn new_document(&self, py: Python<'_>) {
+        self.write(py).new_document();
+    }
+
+    /// Start a new named session
+    ///
+    /// Args:
+    ///     name: Human-readable label (e.g., "2024-07-04 support chat")
+    ///
+    /// Returns:
+    ///     str: The session ID (hex)
+    fn new_session_named(&self, py: Python<'_>, name: String) -> String {
+        format!("{}", self.write(py).new_session_named(name))
+    }
+
+    /// Start a new named document within the current session
+    ///
+    /// Returns:
+    ///     str: The document ID (hex)
+    fn new_document_named(&self, py: Python<'_>, name: String) -> String {
+        format!("{}", self.write(py).new_document_named(name))
+    }
+
+    /// Find the most recent session with a name
+    ///
+    /// Returns:
+    ///     Optional[str]: The session ID (hex), or None if no session has that name
+    fn find_session(&self, py: Python<'_>, name: &str) -> Option<String> {
+        self.read(py).find_session(name).map(|id| format!("{}", id))
+    }
+
+    /// Find the most recent document with a name
+    ///
+    /// Returns:
+    ///     Optional[str]: The document ID (hex), or None if no document has that name
+    fn find_document(&self, py: Python<'_>, name: &str) -> Option<String> {
+        self.read(py).find_document(name).map(|id| format!("{}", id))
+    }
+
+    /// Find points, sessions and documents by a metadata value
+    ///
+    /// Args:
+    ///     key: Metadata key
+    ///     value: str, int, float, bool, or None
+    ///
+    /// Returns:
+    ///     List[str]: Matching IDs (hex), oldest first
+    fn find_by_metadata(&self, py: Python<'_>, key: &str, value: &Bound<'_, PyAny>) -> PyResult<Vec<String>> {
+        let value = extract_meta_value(value)?;
+        Ok(self.read(py)
+            .find_by_metadata(key, &value)
+            .into_iter()
+            .map(|id| format!("{}", id))
+            .collect())
+    }
+
+    /// Attach metadata to a point, session or document
+    ///
+    /// Args:
+    ///     id_hex: 32-character hex string for the ID
+    ///     metadata: dict with str keys and str/int/float/bool/None values
+    fn set_metadata(&self, py: Python<'_>, id_hex: &str, metadata: &Bound<'_, PyDict>) -> PyResult<()> {
+        let id = parse_id_hex(id_hex)?;
+        let metadata = extract_metadata(metadata)?;
+        self.write(py).set_metadata(id, metadata).map_err(py_err)
+    }
+
+    /// Record where a point came from (replaces any existing provenance)
+    ///
+    /// Search results carry it as `provenance` and `confidence`, so
+    /// ranking can discount low-trust memories. Passing nothing clears it.
+    ///
+    /// Args:
+    ///     id_hex: 32-character hex string for the ID
+    ///     source: Document, URL or tool the content came from
+    ///     pipeline: Ingestion pipeline that produced the chunk
+    ///     model_version: Embedding model that produced the point
+    ///     confidence: How much to trust it, 0.0 - 1.0
+    #[pyo3(signature = (id_hex, source=None, pipeline=None, model_version=None, confidence=None))]
+    fn set_provenance(
+        &self,
+        py: Python<'_>,
+        id_hex: &str,
+        source: Option<String>,
+        pipeline: Option<String>,
+        model_version: Option<String>,
+        confidence: Option<f32>,
+    ) -> PyResult<()> {
+        let id = parse_id_hex(id_hex)?;
+        let provenance = Provenance { source, pipeline, model_version, confidence: None };
+        let provenance = match confidence {
+            Some(confidence) => provenance.with_confidence(confidence),
+            None => provenance,
+        };
+        self.write(py).set_provenance(id, provenance).map_err(py_err)
+    }
+
+    /// Provenance of a point as a dict, or None
+    fn get_provenance<'py>(&self, py: Python<'py>, id_hex: &str) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let id = parse_id_hex(id_hex)?;
+        self.read(py).provenance(id).map(|p| metadata_to_dict(py, &p.to_metadata())).transpose()
+    }
+
+    /// Summarize a session; queries near `vector` reach it directly
+    ///
+    /// Args:
+    ///     id_hex: Session ID (hex string)
+    ///     text: Summary text, returned by `near_sessions`
+    ///     vector: Embedding of the summary
+    fn set_session_summary(&self, py: Python<'_>, id_hex: &str, text: String, vector: Vec<f32>) -> PyResult<()> {
+        let id = parse_id_hex(id_hex)?;
+        self.write(py).set_session_summary(id, text, Point::new(vector)).map_err(py_err)
+    }
+
+    /// Summarize a document (see `set_session_summary`)
+    fn set_document_summary(&self, py: Python<'_>, id_hex: &str, text: String, vector: Vec<f32>) -> PyResult<()> {
+        let id = parse_id_hex(id_hex)?;
+        self.write(py).set_document_summary(id, text, Point::new(vector)).map_err(py_err)
+    }
+
+    /// Store a fact, replacing any with the same subject and predicate
+    ///
+    /// Args:
+    ///     subject: What the fact is about (e.g., "user")
+    ///     predicate: The property (e.g., "prefers")
+    ///     value: str, int, float, bool or None
+    ///     confidence: How sure the extractor was, 0.0 - 1.0
+    ///     source: ID of the chunk it was extracted from (hex string)
+    ///
+    /// Returns:
+    ///     Optional[Fact]: The fact it replaced
+    #[pyo3(signature = (subject, predicate, value, confidence=1.0, source=None))]
+    fn upsert_fact(
+        &self,
+        py: Python<'_>,
+        subject: String,
+        predicate: String,
+        value: &Bound<'_, PyAny>,
+        confidence: f32,
+        source: Option<&str>,
+    ) -> PyResult<Option<PyFact>> {
+        let value = extract_meta_value(value)
+            .map_err(|_| PyTypeError::new_err("fact value must be str, int, float, bool, or None"))?;
+        let mut fact = Fact::new(subject, predicate, value).with_confidence(confidence);
+        if let Some(source) = source {
+            fact = fact.with_source(parse_id_hex(source)?);
+        }
+        let replaced = self.write(py).upsert_fact(fact).map_err(py_err)?;
+        Ok(replaced.as_ref().map(PyFact::from))
+    }
+
+    /// The fact about `subject` under `predicate`, if stored
+    fn fact(&self, py: Python<'_>, subject: &str, predicate: &str) -> Option<PyFact> {
+        self.read(py).fact(subject, predicate).map(PyFact::from)
+    }
+
+    /// Every fact about `subject`, by predicate
+    fn facts_about(&self, py: Python<'_>, subject: &str) -> Vec<PyFact> {
+        self.read(py).facts_about(subject).into_iter().map(PyFact::from).collect()
+    }
+
+    /// Facts extracted from the given results' chunks, in result order
+    ///
+    /// Args:
+    ///     results: SearchResults (or their IDs) from a query
+    fn facts_for(&self, py: Python<'_>, results: Vec<Bound<'_, PyAny>>) -> PyResult<Vec<PyFact>> {
+        let hits = results
+            .iter()
+            .map(|result| {
+                let id: String = match result.extract::<PySearchResult>() {
+                    Ok(result) => result.id,
+                    Err(_) => result.extract()?,
+                };
+                Ok(SearchResult::new(parse_id_hex(&id)?, 0.0))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(self.read(py).facts_for(&hits).into_iter().map(PyFact::from).collect())
+    }
+
+    /// Remove a fact, returning it
+    fn remove_fact(&self, py: Python<'_>, subject: &str, predicate: &str) -> Option<PyFact> {
+        self.write(py).remove_fact(subject, predicate).as_ref().map(PyFact::from)
+    }
+
+    /// Hold an embedding in the session overlay instead of the index
+    ///
+    /// Overlay points show up in searches like indexed ones but are not
+    /// saved; at the end of the conversation `promote` the ones worth
+    /// keeping (or `close_ephemeral`) and discard the rest.
+    ///
+    /// Args:
+    ///     embedding: List of floats (must match dimensionality)
+    ///     payload: Optional bytes, str, or dict (see `add`)
+    ///
+    /// Returns:
+    ///     str: The ID (hex string)
+    #[pyo3(signature = (embedding, payload=None))]
+    fn add_ephemeral(&self, py: Python<'_>, embedding: Vec<f32>, payload: Option<&Bound<'_, PyAny>>) -> PyResult<String> {
+        let id = Id::now();
+        let point = EphemeralPoint::new(id, Point::new(embedding));
+        let point = match payload.map(extract_payload).transpose()? {
+            Some(Payload::Blob(blob)) => point.with_payload(blob),
+            Some(Payload::Metadata(metadata)) => point.with_metadata(metadata),
+            None => point,
+        };
+        self.with_write(py, |index| index.add_ephemeral(point)).map_err(py_err)?;
+        Ok(format!("{}", id))
+    }
+
+    /// IDs in the session overlay, oldest first
+    fn ephemeral_ids(&self, py: Python<'_>) -> Vec<String> {
+        self.read(py).ephemeral_points().iter().map(|p| format!("{}", p.id)).collect()
+    }
+
+    /// Move an overlay point into the index (the active document)
+    fn promote(&self, py: Python<'_>, id_hex: &str) -> PyResult<()> {
+        let id = parse_id_hex(id_hex)?;
+        self.write(py).promote(id).map_err(py_err)
+    }
+
+    /// Promote the listed overlay points and discard the rest
+    ///
+    /// Args:
+    ///     keep: IDs (hex strings) to promote
+    ///
+    /// Returns:
+    ///     List[str]: The promoted IDs, oldest first
+    #[pyo3(signature = (keep=Vec::new()))]
+    fn close_ephemeral(&self, py: Python<'_>, keep: Vec<String>) -> PyResult<Vec<String>> {
+        let keep = keep.iter().map(|id| parse_id_hex(id)).collect::<PyResult<HashSet<Id>>>()?;
+        let promoted = self.with_write(py, |index| index.close_ephemeral(|p| keep.contains(&p.id))).map_err(py_err)?;
+        Ok(promoted.iter().map(|id| format!("{}", id)).collect())
+    }
+
+    /// Drop every overlay point, returning how many there were
+    fn discard_ephemeral(&self, py: Python<'_>) -> usize {
+        self.write(py).discard_ephemeral()
+    }
+
+    /// Tag a point with its conversation role
+    ///
+    /// Args:
+    ///     id_hex: 32-character hex string for the ID
+    ///     role: "system", "user", "assistant", "tool" or "context"
+    fn set_role(&self, py: Python<'_>, id_hex: &str, role: &str) -> PyResult<()> {
+        let id = parse_id_hex(id_hex)?;
+        let role = parse_role(role)?;
+        self.write(py).set_role(id, role).map_err(py_err)
+    }
+
+    /// Conversation role of a point, or None if untagged
+    fn role(&self, py: Python<'_>, id_hex: &str) -> PyResult<Option<&'static str>> {
+        let id = parse_id_hex(id_hex)?;
+        Ok(self.read(py).role(id).map(|role| role.as_str()))
+    }
+
+    /// Get index statistics
+    fn stats(&self, py: Python<'_>) -> PyHatStats {
+        let s = self.read(py).stats();
+        PyHatStats {
+            global_count: s.global_count,
+            session_count: s.session_count,
+            document_count: s.document_count,
+            chunk_count: s.chunk_count,
+        }
+    }
+
+    /// Iterate over point IDs (hex strings), oldest first
+    fn __iter__(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let ids: Vec<String> = sorted_items(&self.read(py)).into_iter().map(|(id, _)| format!("{}", id)).collect();
+        let list = pyo3::types::PyList::new_bound(py, ids);
+        Ok(list.as_any().iter()?.into_any().unbind())
+    }
+
+    /// Enumerate all points, oldest first
+    ///
+    /// Returns:
+    ///     List[Tuple[str, List[float], Any]]: (id, vector, payload) tuples,
+    ///     where payload is a dict, bytes, or None
+    fn items(&self, py: Python<'_>) -> PyResult<Vec<(String, Vec<f32>, PyObject)>> {
+        let index = self.read(py);
+        sorted_items(&index)
+            .into_iter()
+            .map(|(id, point)| Ok((format!("{}", id), point.dims().to_vec(), payload_object(py, &index, id)?)))
+            .collect()
+    }
+
+    /// Export all points as NumPy arrays, oldest first
+    ///
+    /// Save with `np.savez(path, ids=ids, vectors=vectors)` and rebuild with
+    /// `from_numpy`. Sessions, documents and roles aren't exported.
+    ///
+    /// Returns:
+    ///     Tuple[List[str], np.ndarray, List[Any]]: (ids, vectors, payloads),
+    ///     where vectors is float32 of shape (len, dimensionality)
+    fn to_numpy(&self, py: Python<'_>) -> PyResult<(Vec<String>, PyObject, Vec<PyObject>)> {
+        let numpy = py.import_bound("numpy")?;
+        let index = self.read(py);
+        let items = sorted_items(&index);
+
+        let mut bytes = Vec::with_capacity(items.len() * index.dimensionality() * 4);
+        for (_, point) in &items {
+            for value in point.dims() {
+                bytes.extend_from_slice(&value.to_ne_bytes());
+            }
+        }
+        let vectors = numpy
+            .call_method1("frombuffer", (PyBytes::new_bound(py, &bytes), "float32"))?
+            .call_method1("reshape", (items.len(), index.dimensionality()))?;
+
+        let ids = items.iter().map(|(id, _)| format!("{}", id)).collect();
+        let payloads = items
+            .iter()
+            .map(|(id, _)| payload_object(py, &index, *id))
+            .collect::<PyResult<_>>()?;
+        Ok((ids, vectors.unbind(), payloads))
+    }
+
+    /// Build an index from NumPy arrays (e.g., the output of `to_numpy`)
+    ///
+    /// Args:
+    ///     ids: Hex string IDs, one per row, or None to generate them
+    ///     vectors: 2-D array-like of shape (n, dimensionality)
+    ///     metadata: Optional payloads (see `add`), one per row
+    ///     proximity: "cosine", "euclidean" or "dot_product"
+    #[staticmethod]
+    #[pyo3(signature = (ids, vectors, metadata=None, proximity="cosine"))]
+    fn from_numpy(
+        py: Python<'_>,
+        ids: Option<Vec<String>>,
+        vectors: &Bound<'_, PyAny>,
+        metadata: Option<Vec<Bound<'_, PyAny>>>,
+        proximity: &str,
+    ) -> PyResult<Self> {
+        let numpy = py.import_bound("numpy")?;
+        let array = numpy.call_method1("ascontiguousarray", (vectors, "float32"))?;
+        let shape: Vec<usize> = array.getattr("shape")?.extract()?;
+        let [rows, dimensionality] = shape[..] else {
+            return Err(PyValueError::new_err(format!("vectors must be 2-D, got shape {:?}", shape)));
+        };
+        if dimensionality == 0 {
+            return Err(PyValueError::new_err("vectors must have at least one column"));
+        }
+        let bytes: Vec<u8> = array.call_method0("tobytes")?.extract()?;
+
+        let ids = match ids {
+            Some(ids) if ids.len() != rows => {
+                return Err(PyValueError::new_err(format!("Got {} ids for {} vectors", ids.len(), rows)));
+            }
+            Some(ids) => ids.iter().map(|id| parse_id_hex(id)).collect::<PyResult<Vec<_>>>()?,
+            None => (0..rows).map(|_| Id::now()).collect(),
+        };
+        let mut payloads: Vec<Option<Payload>> = match metadata {
+            Some(metadata) if metadata.len() != rows => {
+                return Err(PyValueError::new_err(format!(
+                    "Got {} metadata entries for {} vectors",
+                    metadata.len(),
+                    rows
+                )));
+            }
+            Some(metadata) => metadata
+                .iter()
+                .map(|p| if p.is_none() { Ok(None) } else { extract_payload(p).map(Some) })
+                .collect::<PyResult<_>>()?,
+            None => Vec::new(),
+        };
+        payloads.resize_with(rows, || None);
+
+        let index: Self = RustHatIndex::with_proximity_name(dimensionality, proximity)
+            .ok_or_else(|| PyValueError::new_err(format!("unknown proximity '{}'", proximity)))?
+            .into();
+        let vectors = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .collect::<Vec<_>>();
+        let points = ids
+            .into_iter()
+            .zip(vectors.chunks_exact(dimensionality))
+            .zip(payloads)
+            .map(|((id, vector), payload)| (id, vector.to_vec(), payload, None))
+            .collect();
+        index.add_points(py, points)?;
+        Ok(index)
+    }
+
+    /// Get a stored point
+    ///
+    /// Args:
+    ///     id_hex: 32-character hex string for the ID
+    ///
+    /// Returns:
+    ///     Optional[Tuple[List[float], Any]]: (vector, payload), or None if not found.
+    ///     The payload is a dict, bytes, or None.
+    fn get(&self, py: Python<'_>, id_hex: &str) -> PyResult<Option<(Vec<f32>, PyObject)>> {
+        let id = parse_id_hex(id_hex)?;
+        let index = self.read(py);
+        match index.get(id) {
+            Some(p) => Ok(Some((p.dims().to_vec(), payload_object(py, &index, id)?))),
+            None => Ok(None),
+        }
+    }
+
+    /// Get a stored embedding
+    ///
+    /// Args:
+    ///     id_hex: 32-character hex string for the ID
+    ///
+    /// Returns:
+    ///     Optional[List[float]]: The embedding, or None if not found
+    fn get_vector(&self, py: Python<'_>, id_hex: &str) -> PyResult<Option<Vec<f32>>> {
+        let id = parse_id_hex(id_hex)?;
+        Ok(self.read(py).get(id).map(|p| p.dims().to_vec()))
+    }
+
+    /// Get the payload attached to a point
+    ///
+    /// Args:
+    ///     id_hex: 32-character hex string for the ID
+    ///
+    /// Returns:
+    ///     Optional[bytes]: The payload, or None if not found or none attached
+    fn get_blob<'py>(&self, py: Python<'py>, id_hex: &str) -> PyResult<Option<Bound<'py, PyBytes>>> {
+        let id = parse_id_hex(id_hex)?;
+        Ok(self.read(py).payload(id).map(|blob| PyBytes::new_bound(py, blob.data())))
+    }
+
+    /// Get the metadata attached to a point (from a dict payload), session or document
+    ///
+    /// Args:
+    ///     id_hex: 32-character hex string for the ID
+    ///
+    /// Returns:
+    ///     Optional[dict]: The metadata, or None if not found or none attached
+    fn get_metadata<'py>(&self, py: Python<'py>, id_hex: &str) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let id = parse_id_hex(id_hex)?;
+        self.read(py).metadata(id).map(|m| metadata_to_dict(py, m)).transpose()
+    }
+
+    /// Check if a point is stored
+    ///
+    /// Args:
+    ///     id_hex: 32-character hex string for the ID
+    fn contains(&self, py: Python<'_>, id_hex: &str) -> PyResult<bool> {
+        let id = parse_id_hex(id_hex)?;
+        Ok(self.read(py).contains(id))
+    }
+
+    /// Support `id in index` (malformed IDs are simply not contained)
+    fn __contains__(&self, py: Python<'_>, id_hex: &str) -> bool {
+        parse_id_hex(id_hex).is_ok_and(|id| self.read(py).contains(id))
+    }
+
+    /// Get the number of indexed points
+    fn __len__(&self, py: Python<'_>) -> usize {
+        self.read(py).len()
+    }
+
+    /// Check if the index is empty
+    fn is_empty(&self, py: Python<'_>) -> bool {
+        self.read(py).is_empty()
+    }
+
+    /// Remove a point by ID
+    ///
+    /// Args:
+    ///     id_hex: 32-character hex string for the ID
+    fn remove(&self, py: Python<'_>, id_hex: &str) -> PyResult<()> {
+        let id = parse_id_hex(id_hex)?;
+
+        self.write(py).remove(id)
+            .map_err(py_err)?;
+
+        Ok(())
+    }
+
+    /// Find similar sessions (coarse-grained search)
     ///
     /// Args:
     ///     query: Query embedding
@@ -327,20 +1819,157 @@ impl PyHatIndex {
     ///
     /// Returns:
     ///     List[SessionSummary]: Most relevant sessions
-    fn near_sessions(&self, query: Vec<f32>, k: usize) -> PyResult<Vec<PySessionSummary>> {
+    fn near_sessions(&self, py: Python<'_>, query: Vec<f32>, k: usize) -> PyResult<Vec<PySessionSummary>> {
         let point = Point::new(query);
 
-        let results = self.inner.near_sessions(&point, k)
-            .map_err(|e| PyValueError::new_err(format!("{}", e)))?;
+        let results = self.with_read(py, |index| index.near_sessions(&point, k))
+            .map_err(py_err)?;
+        self.check_proximity()?;
 
         Ok(results.into_iter().map(|s| PySessionSummary {
             id: format!("{}", s.id),
             score: s.score,
             chunk_count: s.chunk_count,
             timestamp_ms: s.timestamp,
+            summary: s.summary,
         }).collect())
     }
 
+    /// Best chunks grouped by session, document or metadata value
+    ///
+    /// Args:
+    ///     query: Query embedding
+    ///     k_groups: Number of groups to return
+    ///     k_per_group: Chunks per group
+    ///     group_by: "session", "document" or "metadata"
+    ///     key: Metadata key to group by (with group_by="metadata")
+    ///
+    /// Returns:
+    ///     List[ResultGroup]: Groups ordered by their best chunk
+    #[pyo3(signature = (query, k_groups, k_per_group, group_by="session", key=None))]
+    fn near_grouped(
+        &self,
+        py: Python<'_>,
+        query: Vec<f32>,
+        k_groups: usize,
+        k_per_group: usize,
+        group_by: &str,
+        key: Option<String>,
+    ) -> PyResult<Vec<PyResultGroup>> {
+        let group_by = match (group_by, key) {
+            ("session", None) => GroupBy::Session,
+            ("document", None) => GroupBy::Document,
+            ("metadata", Some(key)) => GroupBy::Metadata(key),
+            ("metadata", None) => return Err(PyValueError::new_err("group_by='metadata' needs a key")),
+            (other, _) => {
+                return Err(PyValueError::new_err(format!(
+                    "group_by must be 'session', 'document' or 'metadata' (with key), got '{}'",
+                    other
+                )))
+            }
+        };
+        let point = Point::new(query);
+
+        let groups = self.with_read(py, |index| {
+            Ok(index
+                .near_grouped(&point, k_groups, k_per_group, &group_by)?
+                .into_iter()
+                .map(|g| PyResultGroup {
+                    key: g.key,
+                    results: g.hits.into_iter().map(|r| search_result(index, r.id, r.score)).collect(),
+                })
+                .collect())
+        });
+        let groups = groups.map_err(|e: crate::ports::NearError| py_err(e))?;
+        self.check_proximity()?;
+
+        Ok(groups)
+    }
+
+    /// A session's most central chunks, for a standing "profile" prompt
+    ///
+    /// Args:
+    ///     session_id: Session ID (hex string)
+    ///     k: Number of chunks to return
+    ///
+    /// Returns:
+    ///     List[SearchResult]: Most central first; score is the chunk's
+    ///     centrality (PageRank in the session's proximity graph)
+    #[pyo3(signature = (session_id, k=10))]
+    fn importance_report(&self, py: Python<'_>, session_id: &str, k: usize) -> PyResult<Vec<PySearchResult>> {
+        let id = parse_id_hex(session_id)?;
+        let results = self.with_read(py, |index| {
+            index.importance_report(id, k).map(|report| {
+                report.into_iter().map(|c| search_result(index, c.id, c.centrality)).collect()
+            })
+        });
+        self.check_proximity()?;
+        results.map_err(py_err)
+    }
+
+    /// Cluster chunks into topics and list each session's topics over time
+    ///
+    /// Args:
+    ///     k_topics: Number of topics to discover
+    ///     seed: Seed for clustering (same seed, same topics)
+    ///
+    /// Returns:
+    ///     List[SessionTimeline]: Sessions in time order
+    #[pyo3(signature = (k_topics, seed=0))]
+    fn topic_timeline(&self, py: Python<'_>, k_topics: usize, seed: u64) -> PyResult<Vec<PySessionTimeline>> {
+        let kmeans = KMeans::new(k_topics).with_seed(seed);
+        let timelines = self.with_read(py, |index| {
+            let clustering = index.discover_clusters(&kmeans);
+            index
+                .topic_timeline(&clustering)
+                .into_iter()
+                .map(|timeline| PySessionTimeline {
+                    session: format!("{}", timeline.session),
+                    spans: timeline
+                        .spans
+                        .into_iter()
+                        .map(|span| PyTopicSpan {
+                            topic: span.label,
+                            representative: format!("{}", clustering.clusters()[span.label].representative),
+                            start_ms: span.start,
+                            end_ms: span.end,
+                            documents: span.documents,
+                            chunks: span.chunks,
+                        })
+                        .collect(),
+                })
+                .collect()
+        });
+        self.check_proximity()?;
+
+        Ok(timelines)
+    }
+
+    /// Approximate 2-D layout of (a sample of) chunks, for plotting
+    ///
+    /// Args:
+    ///     sample_size: Maximum number of chunks to lay out
+    ///     seed: Seed for sampling and layout
+    ///     format: "json" or "csv"
+    ///
+    /// Returns:
+    ///     str: One record per chunk with id, x, y, session, document and
+    ///     the chunk's metadata
+    #[pyo3(signature = (sample_size=1000, seed=0, format="json"))]
+    fn project_2d(&self, py: Python<'_>, sample_size: usize, seed: u64, format: &str) -> PyResult<String> {
+        if format != "json" && format != "csv" {
+            return Err(PyValueError::new_err(format!(
+                "Unknown format '{}', expected 'json' or 'csv'",
+                format
+            )));
+        }
+        let projector = Projector::new().with_seed(seed);
+        let projection = self.with_read(py, |index| index.project_2d(sample_size, &projector));
+        self.check_proximity()?;
+
+        Ok(if format == "csv" { projection.to_csv() } else { projection.to_json() })
+    }
+
     /// Find similar documents within a session
     ///
     /// Args:
@@ -350,17 +1979,19 @@ impl PyHatIndex {
     ///
     /// Returns:
     ///     List[DocumentSummary]: Most relevant documents in the session
-    fn near_documents(&self, session_id: &str, query: Vec<f32>, k: usize) -> PyResult<Vec<PyDocumentSummary>> {
+    fn near_documents(&self, py: Python<'_>, session_id: &str, query: Vec<f32>, k: usize) -> PyResult<Vec<PyDocumentSummary>> {
         let sid = parse_id_hex(session_id)?;
         let point = Point::new(query);
 
-        let results = self.inner.near_documents(sid, &point, k)
-            .map_err(|e| PyValueError::new_err(format!("{}", e)))?;
+        let results = self.with_read(py, |index| index.near_documents(sid, &point, k))
+            .map_err(py_err)?;
+        self.check_proximity()?;
 
         Ok(results.into_iter().map(|d| PyDocumentSummary {
             id: format!("{}", d.id),
             score: d.score,
             chunk_count: d.chunk_count,
+            summary: d.summary,
         }).collect())
     }
 
@@ -373,39 +2004,113 @@ impl PyHatIndex {
     ///
     /// Returns:
     ///     List[SearchResult]: Most relevant chunks in the document
-    fn near_in_document(&self, doc_id: &str, query: Vec<f32>, k: usize) -> PyResult<Vec<PySearchResult>> {
+    fn near_in_document(&self, py: Python<'_>, doc_id: &str, query: Vec<f32>, k: usize) -> PyResult<Vec<PySearchResult>> {
         let did = parse_id_hex(doc_id)?;
         let point = Point::new(query);
 
-        let results = self.inner.near_in_document(did, &point, k)
-            .map_err(|e| PyValueError::new_err(format!("{}", e)))?;
+        let results = self.with_read(py, |index| index.near_in_document(did, &point, k))
+            .map_err(py_err)?;
+        self.check_proximity()?;
 
-        Ok(results.into_iter().map(|r| PySearchResult {
-            id: format!("{}", r.id),
-            score: r.score,
-        }).collect())
+        let index = self.read(py);
+        Ok(results.into_iter().map(|r| search_result(&index, r.id, r.score)).collect())
     }
 
     /// Run light consolidation (background maintenance)
     ///
     /// This optimizes the index structure. Call periodically
     /// (e.g., after every 100 inserts).
-    fn consolidate(&mut self) {
-        self.inner.consolidate(ConsolidationConfig::light());
+    fn consolidate(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_write(py, |index| index.consolidate(ConsolidationConfig::light()));
+        self.check_proximity()
     }
 
     /// Run full consolidation (more thorough optimization)
-    fn consolidate_full(&mut self) {
-        self.inner.consolidate(ConsolidationConfig::full());
+    fn consolidate_full(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_write(py, |index| index.consolidate(ConsolidationConfig::full()));
+        self.check_proximity()
+    }
+
+    /// Consolidate on a background thread
+    ///
+    /// The index stays usable: the job takes the lock one step at a time.
+    ///
+    /// Args:
+    ///     mode: "light", "medium", "deep" or "full"
+    ///
+    /// Returns:
+    ///     Job: progress, ETA and cancellation; wait() returns False if cancelled
+    #[pyo3(signature = (mode="light"))]
+    fn consolidate_async(&self, mode: &str) -> PyResult<PyJob> {
+        let config = match mode {
+            "light" => ConsolidationConfig::light(),
+            "medium" => ConsolidationConfig::medium(),
+            "deep" => ConsolidationConfig::deep(),
+            "full" => ConsolidationConfig::full(),
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown mode '{}': expected light, medium, deep or full",
+                    other
+                )))
+            }
+        };
+        let index = self.inner.clone();
+        let job = Job::spawn(format!("consolidate ({})", mode), move |handle: &JobHandle| {
+            consolidate_shared(&index, config, handle).is_some()
+        });
+        Ok(PyJob::from(job))
+    }
+
+    /// Replace old documents with summaries written by `summarize`
+    ///
+    /// Each document whose newest chunk is at least `older_than_ms` old is
+    /// passed to `summarize(chunks)` as a list of `(id, vector, payload)`;
+    /// it returns `(text, vector)`, or None to keep the document as it is.
+    /// The summary becomes a chunk (payload: the text) in the document's
+    /// place. The index isn't locked while `summarize` runs.
+    ///
+    /// Returns:
+    ///     List of summary chunk IDs
+    #[pyo3(signature = (summarize, older_than_ms, min_chunks=2))]
+    fn compress(&self, py: Python<'_>, summarize: PyObject, older_than_ms: u64, min_chunks: usize) -> PyResult<Vec<String>> {
+        let config = CompressionConfig::older_than_ms(older_than_ms).with_min_chunks(min_chunks);
+        let summarizer = PySummarizer::new(summarize);
+        let index = self.inner.clone();
+        let report = py.allow_threads(|| compress_shared(&index, &config, &summarizer, &JobHandle::new()));
+        if let Some(err) = summarizer.take_error() {
+            return Err(err);
+        }
+        let summaries = report.map(|report| report.summaries).unwrap_or_default();
+        Ok(summaries.iter().map(|id| format!("{}", id)).collect())
+    }
+
+    /// `compress` on a background thread
+    ///
+    /// Returns:
+    ///     Job: progress, ETA and cancellation; wait() returns False if
+    ///     cancelled or `summarize` raised
+    #[pyo3(signature = (summarize, older_than_ms, min_chunks=2))]
+    fn compress_async(&self, summarize: PyObject, older_than_ms: u64, min_chunks: usize) -> PyJob {
+        let config = CompressionConfig::older_than_ms(older_than_ms).with_min_chunks(min_chunks);
+        let summarizer = PySummarizer::new(summarize);
+        let index = self.inner.clone();
+        let job = Job::spawn("compress", move |handle: &JobHandle| {
+            compress_shared(&index, &config, &summarizer, handle).is_some() && summarizer.take_error().is_none()
+        });
+        PyJob::from(job)
     }
 
     /// Save the index to a file
     ///
+    /// Other threads can keep adding to the index while the file is
+    /// written; it holds the state at the time of the call.
+    ///
     /// Args:
     ///     path: File path to save to
-    fn save(&self, path: &str) -> PyResult<()> {
-        self.inner.save_to_file(std::path::Path::new(path))
-            .map_err(|e| PyIOError::new_err(format!("{}", e)))
+    fn save(&self, py: Python<'_>, path: &str) -> PyResult<()> {
+        let snapshot = self.with_read(py, |index| index.snapshot());
+        py.allow_threads(|| snapshot.save_to_file(std::path::Path::new(path)))
+            .map_err(py_err)
     }
 
     /// Load an index from a file
@@ -416,11 +2121,11 @@ impl PyHatIndex {
     /// Returns:
     ///     HatIndex: The loaded index
     #[staticmethod]
-    fn load(path: &str) -> PyResult<Self> {
-        let inner = RustHatIndex::load_from_file(std::path::Path::new(path))
-            .map_err(|e| PyIOError::new_err(format!("{}", e)))?;
+    fn load(py: Python<'_>, path: &str) -> PyResult<Self> {
+        let inner = py.allow_threads(|| RustHatIndex::load_from_file(std::path::Path::new(path)))
+            .map_err(py_err)?;
 
-        Ok(Self { inner })
+        Ok(inner.into())
     }
 
     /// Serialize the index to bytes
@@ -428,8 +2133,8 @@ impl PyHatIndex {
     /// Returns:
     ///     bytes: Serialized index data
     fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, pyo3::types::PyBytes>> {
-        let data = self.inner.to_bytes()
-            .map_err(|e| PyIOError::new_err(format!("{}", e)))?;
+        let data = self.with_read(py, |index| index.to_bytes())
+            .map_err(py_err)?;
         Ok(pyo3::types::PyBytes::new_bound(py, &data))
     }
 
@@ -441,22 +2146,60 @@ impl PyHatIndex {
     /// Returns:
     ///     HatIndex: The loaded index
     #[staticmethod]
-    fn from_bytes(data: &[u8]) -> PyResult<Self> {
-        let inner = RustHatIndex::from_bytes(data)
-            .map_err(|e| PyIOError::new_err(format!("{}", e)))?;
+    fn from_bytes(py: Python<'_>, data: &[u8]) -> PyResult<Self> {
+        let inner = py.allow_threads(|| RustHatIndex::from_bytes(data))
+            .map_err(py_err)?;
 
-        Ok(Self { inner })
+        Ok(inner.into())
     }
 
-    fn __repr__(&self) -> String {
-        let stats = self.inner.stats();
+    /// Export one session as a portable shard
+    ///
+    /// Args:
+    ///     session_id: Session ID (hex string)
+    ///
+    /// Returns:
+    ///     bytes: Self-contained shard (loads with `from_bytes` or `import_session`)
+    fn export_session<'py>(&self, py: Python<'py>, session_id: &str) -> PyResult<Bound<'py, pyo3::types::PyBytes>> {
+        let sid = parse_id_hex(session_id)?;
+        let data = self.with_read(py, |index| index.export_session(sid))
+            .map_err(py_err)?;
+        Ok(pyo3::types::PyBytes::new_bound(py, &data))
+    }
+
+    /// Add a session exported with `export_session`
+    ///
+    /// Args:
+    ///     data: Shard bytes
+    ///
+    /// Returns:
+    ///     str: The imported session ID (hex)
+    fn import_session(&self, py: Python<'_>, data: &[u8]) -> PyResult<String> {
+        let sid = self.with_write(py, |index| index.import_session(data))
+            .map_err(py_err)?;
+        Ok(format!("{}", sid))
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> String {
+        let index = self.read(py);
+        let stats = index.stats();
         format!(
-            "HatIndex(points={}, sessions={})",
-            stats.chunk_count, stats.session_count
+            "HatIndex(proximity='{}', points={}, sessions={})",
+            index.proximity_name(), stats.chunk_count, stats.session_count
         )
     }
 }
 
+/// Parse a role name (as in `Role::from_str`)
+fn parse_role(role: &str) -> PyResult<Role> {
+    Role::from_str(role).ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "Unknown role '{}', expected 'system', 'user', 'assistant', 'tool' or 'context'",
+            role
+        ))
+    })
+}
+
 /// Parse a hex string to an Id
 fn parse_id_hex(hex: &str) -> PyResult<Id> {
     if hex.len() != 32 {
@@ -492,7 +2235,28 @@ fn arms_hat(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PySearchResult>()?;
     m.add_class::<PySessionSummary>()?;
     m.add_class::<PyDocumentSummary>()?;
+    m.add_class::<PyFact>()?;
+    m.add_class::<PyResultGroup>()?;
+    m.add_class::<PyTopicSpan>()?;
+    m.add_class::<PyJob>()?;
+    m.add_class::<PySessionTimeline>()?;
     m.add_class::<PyHatStats>()?;
+    m.add_class::<PyCompressedKV>()?;
+
+    let py = m.py();
+    m.add("DimensionalityMismatchError", py.get_type_bound::<DimensionalityMismatchError>())?;
+    m.add("CapacityExceededError", py.get_type_bound::<CapacityExceededError>())?;
+    m.add("DuplicateIdError", py.get_type_bound::<DuplicateIdError>())?;
+    m.add("NotFoundError", py.get_type_bound::<NotFoundError>())?;
+    m.add("IndexNotReadyError", py.get_type_bound::<IndexNotReadyError>())?;
+    m.add("InvalidInputError", py.get_type_bound::<InvalidInputError>())?;
+    m.add("CancelledError", py.get_type_bound::<CancelledError>())?;
+    m.add("CorruptedError", py.get_type_bound::<CorruptedError>())?;
+    m.add("UnsupportedVersionError", py.get_type_bound::<UnsupportedVersionError>())?;
+    m.add("ArmsIOError", py.get_type_bound::<ArmsIOError>())?;
+    m.add("BackendError", py.get_type_bound::<BackendError>())?;
+    m.add("ReadOnlyError", py.get_type_bound::<ReadOnlyError>())?;
+    m.add("LockedError", py.get_type_bound::<LockedError>())?;
 
     // Add module docstring
     m.add("__doc__", "ARMS-HAT: Hierarchical Attention Tree for AI memory retrieval")?;
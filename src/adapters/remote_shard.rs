@@ -0,0 +1,306 @@
+//! # Remote Shard Adapter
+//!
+//! A `ShardNode` backed by another process's `HttpServer`, so a
+//! `ShardRouter` can spread points across machines.
+//!
+//! Speaks the server's point endpoints (`PUT`/`GET`/`DELETE /points`,
+//! `GET /ids`) and `GET /query?format=tsv`, one connection per request.
+//!
+//! ```rust,ignore
+//! // On each node
+//! HttpServer::bind("0.0.0.0:9090", arms)?.run()?;
+//!
+//! // On the client
+//! let mut router = ShardRouter::new(ScoreOrder::HigherIsBetter);
+//! router.add_node(Arc::new(RemoteShard::new("a", "10.0.0.1:9090")))?;
+//! ```
+
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::core::{Blob, Id, Point};
+use crate::ports::{SearchResult, ShardError, ShardNode, ShardResult};
+
+/// Default connect/read/write timeout
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A shard served by a remote `HttpServer`
+#[derive(Debug, Clone)]
+pub struct RemoteShard {
+    name: String,
+    addr: String,
+    timeout: Duration,
+
+    /// Sent as `X-Api-Key` to servers that require one
+    api_key: Option<String>,
+}
+
+/// Status and body of a response
+struct Reply {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+impl RemoteShard {
+    /// A node named `name` served at `addr` (`host:port`)
+    pub fn new(name: impl Into<String>, addr: impl Into<String>) -> Self {
+        Self { name: name.into(), addr: addr.into(), timeout: DEFAULT_TIMEOUT, api_key: None }
+    }
+
+    /// Per-request network timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Authenticate to the node with an API key
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    fn unreachable(&self, e: impl std::fmt::Display) -> ShardError {
+        ShardError::Unreachable { node: self.name.clone(), reason: e.to_string() }
+    }
+
+    fn failed(&self, message: impl Into<String>) -> ShardError {
+        ShardError::Node { node: self.name.clone(), message: message.into() }
+    }
+
+    /// Failure for an unexpected status
+    fn refused(&self, reply: &Reply) -> ShardError {
+        self.failed(format!("HTTP {}: {}", reply.status, reply.text().trim()))
+    }
+
+    /// Send one request and read the whole response
+    fn call(&self, method: &str, target: &str, body: &[u8]) -> ShardResult<Reply> {
+        let addr = self
+            .addr
+            .to_socket_addrs()
+            .map_err(|e| self.unreachable(e))?
+            .next()
+            .ok_or_else(|| self.unreachable("address did not resolve"))?;
+        let mut stream = TcpStream::connect_timeout(&addr, self.timeout).map_err(|e| self.unreachable(e))?;
+        stream.set_read_timeout(Some(self.timeout)).map_err(|e| self.unreachable(e))?;
+        stream.set_write_timeout(Some(self.timeout)).map_err(|e| self.unreachable(e))?;
+
+        let mut head = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            method,
+            target,
+            self.addr,
+            body.len()
+        );
+        if let Some(key) = &self.api_key {
+            let _ = write!(head, "X-Api-Key: {}\r\n", key);
+        }
+        head.push_str("\r\n");
+        stream
+            .write_all(head.as_bytes())
+            .and_then(|_| stream.write_all(body))
+            .map_err(|e| self.unreachable(e))?;
+
+        // The server closes after each response, so read to the end
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).map_err(|e| self.unreachable(e))?;
+        let split = raw
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .ok_or_else(|| self.failed("malformed response"))?;
+        let status = std::str::from_utf8(&raw[..split])
+            .ok()
+            .and_then(|head| head.split_whitespace().nth(1))
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| self.failed("malformed status line"))?;
+        Ok(Reply { status, body: raw[split + 4..].to_vec() })
+    }
+}
+
+/// Dims as the comma-separated `vector` parameter
+fn vector_param(point: &Point) -> String {
+    let mut out = String::new();
+    for (i, x) in point.dims().iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _ = write!(out, "{}", x);
+    }
+    out
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+impl ShardNode for RemoteShard {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn place(&self, id: Id, point: Point, blob: Blob) -> ShardResult<()> {
+        let target = format!("/points?id={}&vector={}", id, vector_param(&point));
+        let data = blob.try_data().map_err(|e| self.failed(e.to_string()))?;
+        let reply = self.call("PUT", &target, data)?;
+        match reply.status {
+            200 => Ok(()),
+            _ => Err(self.refused(&reply)),
+        }
+    }
+
+    fn remove(&self, id: Id) -> ShardResult<bool> {
+        let reply = self.call("DELETE", &format!("/points?id={}", id), &[])?;
+        match reply.status {
+            200 => Ok(true),
+            404 => Ok(false),
+            _ => Err(self.refused(&reply)),
+        }
+    }
+
+    fn get(&self, id: Id) -> ShardResult<Option<(Point, Blob)>> {
+        let reply = self.call("GET", &format!("/points?id={}", id), &[])?;
+        match reply.status {
+            200 => {}
+            404 => return Ok(None),
+            _ => return Err(self.refused(&reply)),
+        }
+        let text = reply.text();
+        let mut lines = text.lines();
+        let dims: Option<Vec<f32>> = lines
+            .next()
+            .map(|line| line.split(',').map(|x| x.parse().ok()).collect())
+            .unwrap_or_default();
+        let blob = lines.next().and_then(decode_hex);
+        match (dims, blob) {
+            (Some(dims), Some(blob)) => Ok(Some((Point::new(dims), Blob::new(blob)))),
+            _ => Err(self.failed("malformed point")),
+        }
+    }
+
+    fn ids(&self) -> ShardResult<Vec<Id>> {
+        let reply = self.call("GET", "/ids", &[])?;
+        if reply.status != 200 {
+            return Err(self.refused(&reply));
+        }
+        reply
+            .text()
+            .lines()
+            .map(|line| Id::from_hex(line).ok_or_else(|| self.failed(format!("bad id: {}", line))))
+            .collect()
+    }
+
+    fn near(&self, query: &Point, k: usize) -> ShardResult<Vec<SearchResult>> {
+        let target = format!("/query?vector={}&k={}&fields=none&format=tsv", vector_param(query), k);
+        let reply = self.call("GET", &target, &[])?;
+        if reply.status != 200 {
+            return Err(self.refused(&reply));
+        }
+        reply
+            .text()
+            .lines()
+            .map(|line| {
+                line.split_once('\t')
+                    .and_then(|(id, score)| Some(SearchResult::new(Id::from_hex(id)?, score.parse().ok()?)))
+                    .ok_or_else(|| self.failed(format!("bad result: {}", line)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::server::HttpServer;
+    use crate::core::config::ArmsConfig;
+    use crate::core::proximity::ScoreOrder;
+    use crate::engine::{Arms, LocalShard, ShardRouter};
+    use std::sync::{Arc, RwLock};
+
+    fn serve() -> (RemoteShard, Arc<RwLock<Arms>>) {
+        let arms = Arc::new(RwLock::new(Arms::new(ArmsConfig::new(2))));
+        let server = HttpServer::bind("127.0.0.1:0", arms.clone()).unwrap();
+        let addr = server.local_addr().unwrap();
+        std::thread::spawn(move || server.run());
+        (RemoteShard::new("remote", addr.to_string()), arms)
+    }
+
+    #[test]
+    fn test_remote_shard_round_trip() {
+        let (shard, arms) = serve();
+        let id = Id::seeded(3, 1);
+        shard.place(id, Point::new(vec![0.6, 0.8]), Blob::new(vec![0, 0xff])).unwrap();
+        assert!(matches!(
+            shard.place(id, Point::new(vec![0.6, 0.8]), Blob::empty()),
+            Err(ShardError::Node { .. })
+        ));
+        assert_eq!(arms.read().unwrap().len(), 1);
+
+        let (point, blob) = shard.get(id).unwrap().unwrap();
+        assert_eq!(point.dims(), &[0.6, 0.8]);
+        assert_eq!(blob.data(), &[0, 0xff]);
+        assert_eq!(shard.ids().unwrap(), vec![id]);
+
+        let results = shard.near(&Point::new(vec![0.6, 0.8]), 5).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, id);
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+
+        assert!(shard.remove(id).unwrap());
+        assert!(!shard.remove(id).unwrap());
+        assert_eq!(shard.get(id).unwrap(), None);
+    }
+
+    #[test]
+    fn test_router_mixes_local_and_remote() {
+        let (remote, remote_arms) = serve();
+        let local_arms = Arc::new(RwLock::new(Arms::new(ArmsConfig::new(2))));
+        let mut router = ShardRouter::new(ScoreOrder::HigherIsBetter);
+        router.add_node(Arc::new(LocalShard::new("local", local_arms.clone()))).unwrap();
+        for i in 0..20 {
+            router.place(Point::new(vec![1.0, i as f32]), Blob::from_str("x")).unwrap();
+        }
+
+        let report = router.add_node(Arc::new(remote)).unwrap();
+        assert_eq!(report.scanned, 20);
+        let remote_len = remote_arms.read().unwrap().len();
+        assert_eq!(report.moved, remote_len);
+        assert_eq!(local_arms.read().unwrap().len() + remote_len, 20);
+        assert_eq!(router.near(&Point::new(vec![1.0, 0.0]), 20).unwrap().len(), 20);
+    }
+
+    #[test]
+    fn test_api_key() {
+        let server = HttpServer::bind("127.0.0.1:0", Arc::new(RwLock::new(Arms::new(ArmsConfig::new(2)))))
+            .unwrap()
+            .with_api_keys(["s3cret"]);
+        let addr = server.local_addr().unwrap().to_string();
+        std::thread::spawn(move || server.run());
+
+        let anonymous = RemoteShard::new("remote", addr.clone());
+        assert!(matches!(anonymous.ids(), Err(ShardError::Node { message, .. }) if message.starts_with("HTTP 401")));
+        assert_eq!(anonymous.with_api_key("s3cret").ids().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let shard = RemoteShard::new("gone", "127.0.0.1:1").with_timeout(Duration::from_millis(200));
+        assert!(matches!(shard.ids(), Err(ShardError::Unreachable { .. })));
+    }
+}
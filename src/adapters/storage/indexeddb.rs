@@ -0,0 +1,264 @@
+//! # IndexedDB Storage Adapter
+//!
+//! `JournaledStorage` persisted to a browser IndexedDB object store, for
+//! in-browser agents built for `wasm32-unknown-unknown` (feature `wasm`).
+//!
+//! The crate doesn't depend on wasm-bindgen or web-sys. As with the clock
+//! (`core::clock`), the JS glue provides a few functions in the `arms`
+//! import module, and `HostIndexedDb` calls them. Keys are the 16 ID bytes;
+//! values are `encode_record` records:
+//!
+//! ```text
+//! idb_put(store, store_len, key, value, value_len)   store.put(value, hex(key))
+//! idb_delete(store, store_len, key)                  store.delete(hex(key))
+//! idb_clear(store, store_len)                        store.clear()
+//! idb_commit(store, store_len)                       end of one readwrite transaction
+//! idb_loaded_count(store, store_len) -> u32          values read by getAll() before open
+//! idb_loaded_len(store, store_len, i) -> u32
+//! idb_loaded_copy(store, store_len, i, dst)
+//! ```
+//!
+//! IndexedDB is async-only while `Place` is synchronous, so the host reads
+//! the store before `open` and applies writes without blocking: a failed
+//! transaction is reported on the JS side, and the next `open` loads what
+//! was committed.
+//!
+//! ```rust,ignore
+//! // JS: await arms_glue.load("memory"); then, in Rust:
+//! let storage = IndexedDbStorage::open(HostIndexedDb, "memory", 384)?.with_batch(32);
+//! let arms = Arms::new(config).with_adapters(Box::new(storage), index);
+//! ```
+//!
+//! `IndexedDb` can be implemented by other hosts (or a test double), so the
+//! adapter builds and is tested on every target; only `HostIndexedDb` is
+//! specific to wasm32.
+
+use super::journal::{JournalRecord, JournaledStorage};
+use crate::core::{Blob, Id, PlacedPoint, Point};
+use crate::ports::{Place, PlaceResult};
+
+/// Default number of pending changes that triggers a write
+pub const DEFAULT_IDB_BATCH: usize = 1;
+
+/// An IndexedDB database, as `IndexedDbStorage` uses it
+pub trait IndexedDb: Send + Sync {
+    /// Values of `store`, as read when the host opened it
+    fn load(&self, store: &str) -> Vec<Vec<u8>>;
+
+    /// Apply `records` to `store` in one transaction, in order
+    fn apply(&self, store: &str, records: &[JournalRecord]);
+}
+
+/// Storage persisted to an IndexedDB object store
+pub struct IndexedDbStorage {
+    journal: JournaledStorage,
+    db: Box<dyn IndexedDb>,
+    store: String,
+
+    /// Pending changes that trigger a write
+    batch: usize,
+}
+
+impl IndexedDbStorage {
+    /// Open `store`, restoring the points the host loaded from it
+    pub fn open(db: impl IndexedDb + 'static, store: impl Into<String>, dimensionality: usize) -> PlaceResult<Self> {
+        let store = store.into();
+        let values = db.load(&store);
+        let journal = JournaledStorage::restore(dimensionality, values.iter().map(Vec::as_slice))?;
+        Ok(Self { journal, db: Box::new(db), store, batch: DEFAULT_IDB_BATCH })
+    }
+
+    /// Write once `batch` changes are pending (1 = after every change)
+    ///
+    /// Changes not yet written are lost if the page closes first; `flush`
+    /// writes them.
+    pub fn with_batch(mut self, batch: usize) -> Self {
+        self.batch = batch.max(1);
+        self
+    }
+
+    pub fn store(&self) -> &str {
+        &self.store
+    }
+
+    /// Number of changes not yet handed to IndexedDB
+    pub fn pending_len(&self) -> usize {
+        self.journal.pending_len()
+    }
+
+    /// Hand pending changes to IndexedDB in one transaction, returning how
+    /// many records were written
+    pub fn flush(&mut self) -> usize {
+        let records = self.journal.take_pending();
+        if !records.is_empty() {
+            self.db.apply(&self.store, &records);
+        }
+        records.len()
+    }
+
+    fn flush_full(&mut self) {
+        if self.journal.pending_len() >= self.batch {
+            self.flush();
+        }
+    }
+}
+
+impl Place for IndexedDbStorage {
+    fn place(&mut self, point: Point, blob: Blob) -> PlaceResult<Id> {
+        let id = self.journal.place(point, blob)?;
+        self.flush_full();
+        Ok(id)
+    }
+
+    fn place_with_id(&mut self, id: Id, point: Point, blob: Blob) -> PlaceResult<()> {
+        self.journal.place_with_id(id, point, blob)?;
+        self.flush_full();
+        Ok(())
+    }
+
+    fn remove(&mut self, id: Id) -> Option<PlacedPoint> {
+        let removed = self.journal.remove(id)?;
+        self.flush_full();
+        Some(removed)
+    }
+
+    fn get(&self, id: Id) -> Option<&PlacedPoint> {
+        self.journal.get(id)
+    }
+
+    fn len(&self) -> usize {
+        self.journal.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &PlacedPoint> + '_> {
+        self.journal.iter()
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.journal.size_bytes()
+    }
+
+    fn clear(&mut self) {
+        self.journal.clear();
+        self.flush_full();
+    }
+}
+
+impl Drop for IndexedDbStorage {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// The IndexedDB the JS glue exposes through the `arms` import module
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostIndexedDb;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod host {
+    #[link(wasm_import_module = "arms")]
+    extern "C" {
+        pub fn idb_put(store: *const u8, store_len: usize, key: *const u8, value: *const u8, value_len: usize);
+        pub fn idb_delete(store: *const u8, store_len: usize, key: *const u8);
+        pub fn idb_clear(store: *const u8, store_len: usize);
+        pub fn idb_commit(store: *const u8, store_len: usize);
+        pub fn idb_loaded_count(store: *const u8, store_len: usize) -> u32;
+        pub fn idb_loaded_len(store: *const u8, store_len: usize, index: u32) -> u32;
+        pub fn idb_loaded_copy(store: *const u8, store_len: usize, index: u32, dst: *mut u8);
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+impl IndexedDb for HostIndexedDb {
+    fn load(&self, store: &str) -> Vec<Vec<u8>> {
+        let (name, len) = (store.as_ptr(), store.len());
+        // SAFETY: the host reads `len` bytes of `name` and writes exactly
+        // `idb_loaded_len` bytes to `dst`, which has that capacity
+        unsafe {
+            (0..host::idb_loaded_count(name, len))
+                .map(|i| {
+                    let mut value = vec![0u8; host::idb_loaded_len(name, len, i) as usize];
+                    host::idb_loaded_copy(name, len, i, value.as_mut_ptr());
+                    value
+                })
+                .collect()
+        }
+    }
+
+    fn apply(&self, store: &str, records: &[JournalRecord]) {
+        let (name, len) = (store.as_ptr(), store.len());
+        // SAFETY: the host only reads the given pointers, within their lengths
+        unsafe {
+            for record in records {
+                match record {
+                    JournalRecord::Put { key, value } => {
+                        host::idb_put(name, len, key.as_bytes().as_ptr(), value.as_ptr(), value.len())
+                    }
+                    JournalRecord::Delete { key } => host::idb_delete(name, len, key.as_bytes().as_ptr()),
+                    JournalRecord::Clear => host::idb_clear(name, len),
+                }
+            }
+            host::idb_commit(name, len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    /// An object store in memory, counting transactions
+    #[derive(Default, Clone)]
+    struct FakeDb {
+        values: Arc<Mutex<BTreeMap<Id, Vec<u8>>>>,
+        transactions: Arc<Mutex<usize>>,
+    }
+
+    impl IndexedDb for FakeDb {
+        fn load(&self, _store: &str) -> Vec<Vec<u8>> {
+            self.values.lock().unwrap().values().cloned().collect()
+        }
+
+        fn apply(&self, _store: &str, records: &[JournalRecord]) {
+            let mut values = self.values.lock().unwrap();
+            for record in records {
+                match record {
+                    JournalRecord::Put { key, value } => drop(values.insert(*key, value.clone())),
+                    JournalRecord::Delete { key } => drop(values.remove(key)),
+                    JournalRecord::Clear => values.clear(),
+                }
+            }
+            *self.transactions.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn test_indexeddb_roundtrip() {
+        let db = FakeDb::default();
+        let (kept, removed) = {
+            let mut storage = IndexedDbStorage::open(db.clone(), "memory", 2).unwrap().with_batch(3);
+            let kept = storage.place(Point::new(vec![1.0, 0.0]), Blob::from_str("kept")).unwrap();
+            let removed = storage.place(Point::new(vec![0.0, 1.0]), Blob::empty()).unwrap();
+            assert_eq!((storage.pending_len(), *db.transactions.lock().unwrap()), (2, 0));
+
+            // The third change fills the batch; the removal collapses into one delete
+            storage.remove(removed);
+            storage.place(Point::new(vec![0.5, 0.5]), Blob::empty()).unwrap();
+            assert_eq!((storage.pending_len(), *db.transactions.lock().unwrap()), (0, 1));
+
+            storage.place(Point::new(vec![0.0, 0.5]), Blob::empty()).unwrap();
+            (kept, removed)
+        };
+
+        // Dropping flushed the rest
+        assert_eq!(*db.transactions.lock().unwrap(), 2);
+        let storage = IndexedDbStorage::open(db.clone(), "memory", 2).unwrap();
+        assert_eq!(storage.len(), 3);
+        assert_eq!(storage.get(kept).unwrap().blob.as_str(), Some("kept"));
+        assert!(!storage.contains(removed));
+        assert!(IndexedDbStorage::open(db, "memory", 3).is_err());
+    }
+}
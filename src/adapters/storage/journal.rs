@@ -0,0 +1,267 @@
+//! # Journaled Storage Adapter
+//!
+//! In-memory storage that records every change as a key/value journal,
+//! for hosts that persist asynchronously (e.g., IndexedDB in the browser).
+//!
+//! IndexedDB is async-only, while `Place` is synchronous. Instead of
+//! blocking, writes land in memory and are queued; the host drains the queue
+//! and applies it in one transaction:
+//!
+//! ```text
+//! Rust (wasm)                         JS host
+//! ───────────                         ───────
+//! place / remove / clear  ──queue──▶  take_pending()
+//!                                       └─ tx = db.transaction("points", "readwrite")
+//!                                          Put    → store.put(value, hex(key))
+//!                                          Delete → store.delete(hex(key))
+//!                                          Clear  → store.clear()
+//! restore(store.getAll())  ◀──load──  on startup
+//! ```
+//!
+//! Records are self-contained, so any key/value store works the same way.
+//!
+//! Good for:
+//! - In-browser agents (wasm32) keeping local memory
+//! - Embedding in hosts that own persistence
+
+use std::collections::HashMap;
+
+use super::MemoryStorage;
+use crate::core::bytes::{ByteError, ByteReader};
+use crate::core::{Blob, Id, PlacedPoint, Point};
+use crate::ports::{Place, PlaceError, PlaceResult};
+
+/// Record format version
+const RECORD_VERSION: u8 = 1;
+
+/// A change to apply to the backing store
+#[derive(Debug, Clone, PartialEq)]
+pub enum JournalRecord {
+    /// Insert or overwrite a point
+    Put { key: Id, value: Vec<u8> },
+
+    /// Delete a point
+    Delete { key: Id },
+
+    /// Delete everything
+    Clear,
+}
+
+/// Memory storage with a change journal
+pub struct JournaledStorage {
+    /// Live data
+    inner: MemoryStorage,
+
+    /// Latest pending change per point (true = put, false = delete)
+    pending: HashMap<Id, bool>,
+
+    /// Whether a clear must be applied before the pending changes
+    pending_clear: bool,
+}
+
+impl JournaledStorage {
+    /// Create empty storage with specified dimensionality
+    pub fn new(dimensionality: usize) -> Self {
+        Self {
+            inner: MemoryStorage::new(dimensionality),
+            pending: HashMap::new(),
+            pending_clear: false,
+        }
+    }
+
+    /// Rebuild from stored record values (nothing is journaled)
+    pub fn restore<'a, I>(dimensionality: usize, values: I) -> PlaceResult<Self>
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+    {
+        let mut storage = Self::new(dimensionality);
+        for value in values {
+            let placed = decode_record(value)?;
+            storage.inner.place_with_id(placed.id, placed.point, placed.blob)?;
+        }
+        Ok(storage)
+    }
+
+    /// Number of changes waiting to be persisted
+    pub fn pending_len(&self) -> usize {
+        self.pending.len() + self.pending_clear as usize
+    }
+
+    /// Drain pending changes, in the order they must be applied
+    ///
+    /// Multiple changes to the same point collapse into the latest one.
+    pub fn take_pending(&mut self) -> Vec<JournalRecord> {
+        let mut records = Vec::with_capacity(self.pending_len());
+        if std::mem::take(&mut self.pending_clear) {
+            records.push(JournalRecord::Clear);
+        }
+
+        let mut pending: Vec<_> = self.pending.drain().collect();
+        pending.sort_by_key(|(id, _)| *id);
+
+        for (id, is_put) in pending {
+            if is_put {
+                // A put is only pending while the point exists
+                if let Some(placed) = self.inner.get(id) {
+                    records.push(JournalRecord::Put {
+                        key: id,
+                        value: encode_record(placed),
+                    });
+                }
+            } else {
+                records.push(JournalRecord::Delete { key: id });
+            }
+        }
+
+        records
+    }
+}
+
+impl Place for JournaledStorage {
+    fn place(&mut self, point: Point, blob: Blob) -> PlaceResult<Id> {
+        let id = self.inner.place(point, blob)?;
+        self.pending.insert(id, true);
+        Ok(id)
+    }
+
+    fn place_with_id(&mut self, id: Id, point: Point, blob: Blob) -> PlaceResult<()> {
+        self.inner.place_with_id(id, point, blob)?;
+        self.pending.insert(id, true);
+        Ok(())
+    }
+
+    fn remove(&mut self, id: Id) -> Option<PlacedPoint> {
+        let removed = self.inner.remove(id)?;
+        self.pending.insert(id, false);
+        Some(removed)
+    }
+
+    fn get(&self, id: Id) -> Option<&PlacedPoint> {
+        self.inner.get(id)
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &PlacedPoint> + '_> {
+        self.inner.iter()
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.inner.size_bytes()
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.pending.clear();
+        self.pending_clear = true;
+    }
+}
+
+/// Encode a placed point as a self-contained record
+///
+/// Layout: version u8 | id 16 | dims u32 | f32 LE × dims | blob_len u32 | blob
+pub fn encode_record(placed: &PlacedPoint) -> Vec<u8> {
+    let dims = placed.point.dims();
+    let blob = placed.blob.data();
+
+    let mut bytes = Vec::with_capacity(1 + 16 + 4 + dims.len() * 4 + 4 + blob.len());
+    bytes.push(RECORD_VERSION);
+    bytes.extend_from_slice(placed.id.as_bytes());
+    bytes.extend_from_slice(&(dims.len() as u32).to_le_bytes());
+    for &v in dims {
+        bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    bytes.extend_from_slice(&(blob.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(blob);
+    bytes
+}
+
+/// Decode a record produced by `encode_record`
+pub fn decode_record(data: &[u8]) -> PlaceResult<PlacedPoint> {
+    let corrupt = |what: &str| PlaceError::Corrupted(format!("record {}", what).into());
+    let truncated = |e: ByteError| PlaceError::Corrupted(format!("record {}", e).into());
+
+    let mut r = ByteReader::new(data);
+    if r.u8("version").map_err(truncated)? != RECORD_VERSION {
+        return Err(corrupt("unsupported version"));
+    }
+    let id = Id::from_bytes(r.array("ID").map_err(truncated)?);
+    let dim_count = r.u32("dimension count").map_err(truncated)? as usize;
+    let dims = r.f32s(dim_count, "point").map_err(truncated)?;
+
+    let blob_len = r.u32("blob length").map_err(truncated)? as usize;
+    if r.remaining() != blob_len {
+        return Err(corrupt("blob length mismatch"));
+    }
+
+    Ok(PlacedPoint::new(id, Point::new(dims), Blob::new(r.rest().to_vec())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_collapses_changes() {
+        let mut storage = JournaledStorage::new(2);
+
+        let a = storage.place(Point::new(vec![1.0, 0.0]), Blob::from_str("a")).unwrap();
+        let b = storage.place(Point::new(vec![0.0, 1.0]), Blob::from_str("b")).unwrap();
+        storage.remove(b);
+        assert_eq!(storage.pending_len(), 2);
+
+        let records = storage.take_pending();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().any(|r| matches!(r, JournalRecord::Put { key, .. } if *key == a)));
+        assert!(records.contains(&JournalRecord::Delete { key: b }));
+        assert_eq!(storage.pending_len(), 0);
+    }
+
+    #[test]
+    fn test_clear_comes_first() {
+        let mut storage = JournaledStorage::new(1);
+        storage.place(Point::new(vec![1.0]), Blob::empty()).unwrap();
+        storage.clear();
+        let id = storage.place(Point::new(vec![2.0]), Blob::empty()).unwrap();
+
+        let records = storage.take_pending();
+        assert_eq!(records[0], JournalRecord::Clear);
+        assert_eq!(records.len(), 2);
+        assert!(matches!(&records[1], JournalRecord::Put { key, .. } if *key == id));
+    }
+
+    #[test]
+    fn test_restore_from_records() {
+        let mut storage = JournaledStorage::new(3);
+        let id = storage
+            .place(Point::new(vec![1.0, 2.0, 3.0]), Blob::from_str("payload"))
+            .unwrap();
+
+        // Simulate the host's key/value store
+        let values: Vec<Vec<u8>> = storage
+            .take_pending()
+            .into_iter()
+            .filter_map(|r| match r {
+                JournalRecord::Put { value, .. } => Some(value),
+                _ => None,
+            })
+            .collect();
+
+        let restored = JournaledStorage::restore(3, values.iter().map(Vec::as_slice)).unwrap();
+        let placed = restored.get(id).unwrap();
+        assert_eq!(placed.point.dims(), &[1.0, 2.0, 3.0]);
+        assert_eq!(placed.blob.as_str(), Some("payload"));
+        assert_eq!(restored.pending_len(), 0);
+    }
+
+    #[test]
+    fn test_decode_rejects_corrupt() {
+        let placed = PlacedPoint::new(Id::now(), Point::new(vec![1.0]), Blob::from_str("x"));
+        let bytes = encode_record(&placed);
+
+        assert!(decode_record(&bytes[..bytes.len() - 1]).is_err());
+        assert!(JournaledStorage::restore(2, [bytes.as_slice()]).is_err());
+    }
+}
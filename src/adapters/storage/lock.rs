@@ -0,0 +1,93 @@
+//! # Store Locks
+//!
+//! Advisory locks keeping a persistent store to one writing process.
+//!
+//! Opening a store for writing takes an exclusive lock on `<path>.lock`
+//! and records the process ID in it. A second writer, in this process or
+//! another, fails fast with `PlaceError::StoreLocked` naming the holder
+//! instead of corrupting the store or failing deep inside the backend:
+//!
+//! ```text
+//! process 41  open(store)  ──▶ lock store.lock, write "41"
+//! process 57  open(store)  ──▶ StoreLocked { pid: Some(41) }
+//! process 41  drop         ──▶ lock released (file stays)
+//! ```
+//!
+//! The lock is released when the store is dropped or the process exits,
+//! however it exits. Read-only opens don't lock.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::ports::{PlaceError, PlaceResult};
+
+/// Exclusive lock on a store, held until dropped
+#[derive(Debug)]
+pub(crate) struct StoreLock {
+    // Closing the file releases the lock
+    _file: File,
+}
+
+impl StoreLock {
+    /// Lock the store at `store` for this process
+    pub(crate) fn acquire(store: &Path) -> PlaceResult<Self> {
+        let path = lock_path(store);
+        let io_error = |e: std::io::Error| PlaceError::StorageError(format!("{}: {}", path.display(), e).into());
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(io_error)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut holder = String::new();
+                let pid = file.read_to_string(&mut holder).ok().and_then(|_| holder.trim().parse().ok());
+                return Err(PlaceError::StoreLocked {
+                    path: store.display().to_string(),
+                    pid,
+                });
+            }
+            Err(TryLockError::Error(e)) => return Err(io_error(e)),
+        }
+
+        file.set_len(0).map_err(io_error)?;
+        file.seek(SeekFrom::Start(0)).map_err(io_error)?;
+        write!(file, "{}", std::process::id()).map_err(io_error)?;
+        file.flush().map_err(io_error)?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// `<store>.lock`, next to the store
+fn lock_path(store: &Path) -> PathBuf {
+    let mut path = store.as_os_str().to_owned();
+    path.push(".lock");
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_writer_sees_holder() {
+        let store = crate::testing::temp_path("arms-lock");
+
+        let held = StoreLock::acquire(&store).unwrap();
+        match StoreLock::acquire(&store) {
+            Err(PlaceError::StoreLocked { pid, .. }) => assert_eq!(pid, Some(std::process::id())),
+            other => panic!("expected StoreLocked, got {:?}", other),
+        }
+
+        drop(held);
+        let again = StoreLock::acquire(&store).unwrap();
+        drop(again);
+        let _ = std::fs::remove_file(lock_path(&store));
+    }
+}
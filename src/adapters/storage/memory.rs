@@ -7,12 +7,52 @@
 //! - Testing
 //! - Hot tier storage
 //! - Small datasets
+//!
+//! With a capacity limit, a full store either rejects new points (the
+//! caller applies backpressure) or evicts existing ones per `CapacityPolicy`.
+//! An eviction callback receives each evicted point, so it can be demoted to
+//! a colder tier instead of being lost:
+//!
+//! `EvictLeastSalient` forgets the points `Place::set_salience` rated least
+//! important first.
+//!
+//! ```rust,ignore
+//! let storage = MemoryStorage::with_capacity(768, 64 * 1024 * 1024)
+//!     .with_capacity_policy(CapacityPolicy::EvictOldest)
+//!     .with_eviction_callback(move |placed| cold_tier.archive(placed));
+//! ```
+//!
+//! Blobs over a spill threshold go to a `SpillStore` instead of memory (see
+//! `spill`); `get` still returns them, loading the bytes on first access.
+//! Spilled blobs don't count toward the capacity.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
+use super::spill::SpillStore;
 use crate::core::{Blob, Id, PlacedPoint, Point};
 use crate::ports::{Place, PlaceError, PlaceResult};
 
+/// What a full store does with a point that doesn't fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CapacityPolicy {
+    /// Fail with `CapacityExceeded` (default)
+    #[default]
+    Reject,
+
+    /// Evict the oldest points (lowest IDs) until the new one fits
+    EvictOldest,
+
+    /// Evict the largest points until the new one fits
+    EvictLargest,
+
+    /// Evict the least salient points (unscored = 0, ties oldest first)
+    EvictLeastSalient,
+}
+
+/// Called with each point evicted to make room
+pub type EvictionCallback = Box<dyn FnMut(PlacedPoint) + Send + Sync>;
+
 /// In-memory storage adapter
 pub struct MemoryStorage {
     /// The stored points
@@ -26,6 +66,24 @@ pub struct MemoryStorage {
 
     /// Current size in bytes
     current_size: usize,
+
+    /// Behavior when a point doesn't fit
+    policy: CapacityPolicy,
+
+    /// Receives evicted points
+    on_evict: Option<EvictionCallback>,
+
+    /// IDs evicted since the last `take_evicted`
+    evicted: Vec<Id>,
+
+    /// Importance of stored points, from `set_salience`
+    salience: HashMap<Id, f32>,
+
+    /// Blobs larger than this many bytes go to the spill store
+    spill: Option<(usize, Arc<dyn SpillStore>)>,
+
+    /// Points whose blob is in the spill store
+    spilled: HashSet<Id>,
 }
 
 impl MemoryStorage {
@@ -36,6 +94,12 @@ impl MemoryStorage {
             dimensionality,
             capacity: 0,
             current_size: 0,
+            policy: CapacityPolicy::Reject,
+            on_evict: None,
+            evicted: Vec::new(),
+            salience: HashMap::new(),
+            spill: None,
+            spilled: HashSet::new(),
         }
     }
 
@@ -46,16 +110,140 @@ impl MemoryStorage {
             dimensionality,
             capacity,
             current_size: 0,
+            policy: CapacityPolicy::Reject,
+            on_evict: None,
+            evicted: Vec::new(),
+            salience: HashMap::new(),
+            spill: None,
+            spilled: HashSet::new(),
         }
     }
 
+    /// Set the behavior when a point doesn't fit
+    pub fn with_capacity_policy(mut self, policy: CapacityPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Hand each evicted point to `callback` (e.g., to demote it to a cold tier)
+    pub fn with_eviction_callback<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(PlacedPoint) + Send + Sync + 'static,
+    {
+        self.on_evict = Some(Box::new(callback));
+        self
+    }
+
+    /// Keep blobs larger than `threshold` bytes in `store` rather than memory
+    pub fn with_blob_spill(mut self, threshold: usize, store: impl SpillStore + 'static) -> Self {
+        self.spill = Some((threshold, Arc::new(store)));
+        self
+    }
+
+    /// Number of points whose blob is spilled
+    pub fn spilled_len(&self) -> usize {
+        self.spilled.len()
+    }
+
+    /// Capacity policy in effect
+    pub fn capacity_policy(&self) -> CapacityPolicy {
+        self.policy
+    }
+
+    /// Bytes left before the capacity limit (None = unlimited)
+    pub fn remaining_bytes(&self) -> Option<usize> {
+        (self.capacity > 0).then(|| self.capacity.saturating_sub(self.current_size))
+    }
+
     /// Calculate size of a placed point in bytes
     fn point_size(point: &PlacedPoint) -> usize {
         // Id: 16 bytes
         // Point: dims.len() * 4 bytes (f32)
-        // Blob: data.len() bytes
+        // Blob: data.len() bytes (none if deferred)
         // Overhead: ~48 bytes for struct padding and HashMap entry
-        16 + (point.point.dimensionality() * 4) + point.blob.size() + 48
+        let blob = if point.blob.is_deferred() { 0 } else { point.blob.size() };
+        16 + (point.point.dimensionality() * 4) + blob + 48
+    }
+
+    /// Move an oversized blob to the spill store, leaving a deferred blob
+    fn spill_blob(&mut self, id: Id, blob: Blob) -> PlaceResult<Blob> {
+        let Some((threshold, store)) = &self.spill else {
+            return Ok(blob);
+        };
+        if blob.size() <= *threshold || blob.is_deferred() {
+            return Ok(blob);
+        }
+
+        store
+            .put(id, blob.data())
+            .map_err(|e| PlaceError::StorageError(format!("spilling blob: {}", e).into()))?;
+        self.spilled.insert(id);
+        let store = store.clone();
+        Ok(Blob::deferred(blob.size(), Arc::new(move || store.get(id))))
+    }
+
+    /// Drop a point's spilled blob, if any
+    fn unspill(&mut self, id: Id) {
+        if self.spilled.remove(&id) {
+            if let Some((_, store)) = &self.spill {
+                let _ = store.delete(id);
+            }
+        }
+    }
+
+    /// Store a point that passed validation
+    fn insert(&mut self, id: Id, point: Point, blob: Blob) -> PlaceResult<()> {
+        let blob = self.spill_blob(id, blob)?;
+        let placed = PlacedPoint::new(id, point, blob);
+
+        // Check capacity
+        let size = Self::point_size(&placed);
+        if let Err(e) = self.make_room(size) {
+            self.unspill(id);
+            return Err(e);
+        }
+
+        self.current_size += size;
+        self.points.insert(id, placed);
+        Ok(())
+    }
+
+    /// Ensure `size` more bytes fit, evicting per policy if allowed
+    fn make_room(&mut self, size: usize) -> PlaceResult<()> {
+        if self.capacity == 0 || self.current_size + size <= self.capacity {
+            return Ok(());
+        }
+        // Nothing can be evicted to fit a point larger than the whole store
+        if self.policy == CapacityPolicy::Reject || size > self.capacity {
+            return Err(PlaceError::CapacityExceeded);
+        }
+
+        while self.current_size + size > self.capacity {
+            let victim = match self.policy {
+                CapacityPolicy::EvictOldest => self.points.keys().min().copied(),
+                CapacityPolicy::EvictLargest => self
+                    .points
+                    .values()
+                    .max_by_key(|p| (Self::point_size(p), std::cmp::Reverse(p.id)))
+                    .map(|p| p.id),
+                CapacityPolicy::EvictLeastSalient => self
+                    .points
+                    .keys()
+                    .map(|id| (self.salience.get(id).copied().unwrap_or(0.0), *id))
+                    .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+                    .map(|(_, id)| id),
+                CapacityPolicy::Reject => None,
+            };
+            let Some(id) = victim else { break };
+
+            if let Some(placed) = self.remove(id) {
+                self.evicted.push(id);
+                if let Some(callback) = self.on_evict.as_mut() {
+                    callback(placed);
+                }
+            }
+        }
+        Ok(())
     }
 }
 
@@ -70,16 +258,7 @@ impl Place for MemoryStorage {
         }
 
         let id = Id::now();
-        let placed = PlacedPoint::new(id, point, blob);
-
-        // Check capacity
-        let size = Self::point_size(&placed);
-        if self.capacity > 0 && self.current_size + size > self.capacity {
-            return Err(PlaceError::CapacityExceeded);
-        }
-
-        self.current_size += size;
-        self.points.insert(id, placed);
+        self.insert(id, point, blob)?;
 
         Ok(id)
     }
@@ -98,23 +277,22 @@ impl Place for MemoryStorage {
             return Err(PlaceError::DuplicateId(id));
         }
 
-        let placed = PlacedPoint::new(id, point, blob);
-
-        // Check capacity
-        let size = Self::point_size(&placed);
-        if self.capacity > 0 && self.current_size + size > self.capacity {
-            return Err(PlaceError::CapacityExceeded);
-        }
-
-        self.current_size += size;
-        self.points.insert(id, placed);
-
-        Ok(())
+        self.insert(id, point, blob)
     }
 
     fn remove(&mut self, id: Id) -> Option<PlacedPoint> {
-        if let Some(placed) = self.points.remove(&id) {
+        if let Some(mut placed) = self.points.remove(&id) {
             self.current_size -= Self::point_size(&placed);
+            self.salience.remove(&id);
+
+            // The caller gets the bytes (e.g., to demote them), so read a
+            // spilled blob back before deleting it
+            if self.spilled.contains(&id) {
+                if let Ok(data) = placed.blob.try_data() {
+                    placed.blob = Blob::new(data.to_vec());
+                    self.unspill(id);
+                }
+            }
             Some(placed)
         } else {
             None
@@ -138,9 +316,23 @@ impl Place for MemoryStorage {
     }
 
     fn clear(&mut self) {
+        for id in self.spilled.iter().copied().collect::<Vec<_>>() {
+            self.unspill(id);
+        }
         self.points.clear();
+        self.salience.clear();
         self.current_size = 0;
     }
+
+    fn set_salience(&mut self, id: Id, salience: f32) {
+        if self.points.contains_key(&id) {
+            self.salience.insert(id, salience);
+        }
+    }
+
+    fn take_evicted(&mut self) -> Vec<Id> {
+        std::mem::take(&mut self.evicted)
+    }
 }
 
 #[cfg(test)]
@@ -250,4 +442,100 @@ mod tests {
         let points: Vec<_> = storage.iter().collect();
         assert_eq!(points.len(), 2);
     }
+
+    #[test]
+    fn test_memory_storage_evict_oldest() {
+        use std::sync::{Arc, Mutex};
+
+        // Room for exactly two 3-dim points with empty blobs (76 bytes each)
+        let demoted = Arc::new(Mutex::new(Vec::new()));
+        let sink = demoted.clone();
+        let mut storage = MemoryStorage::with_capacity(3, 152)
+            .with_capacity_policy(CapacityPolicy::EvictOldest)
+            .with_eviction_callback(move |placed| sink.lock().unwrap().push(placed.id));
+
+        let first = storage.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        let second = storage.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::empty()).unwrap();
+        assert_eq!(storage.remaining_bytes(), Some(0));
+
+        let third = storage.place(Point::new(vec![0.0, 0.0, 1.0]), Blob::empty()).unwrap();
+
+        assert!(!storage.contains(first));
+        assert!(storage.contains(second) && storage.contains(third));
+        assert_eq!(*demoted.lock().unwrap(), vec![first]);
+        assert_eq!(storage.take_evicted(), vec![first]);
+        assert!(storage.take_evicted().is_empty());
+    }
+
+    #[test]
+    fn test_memory_storage_evict_largest() {
+        let mut storage = MemoryStorage::with_capacity(3, 200)
+            .with_capacity_policy(CapacityPolicy::EvictLargest);
+
+        let big = storage.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::new(vec![0; 40])).unwrap();
+        let small = storage.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::empty()).unwrap();
+        storage.place(Point::new(vec![0.0, 0.0, 1.0]), Blob::empty()).unwrap();
+
+        assert!(!storage.contains(big));
+        assert!(storage.contains(small));
+        assert_eq!(storage.len(), 2);
+
+        // A point larger than the whole store is still rejected
+        let huge = storage.place(Point::new(vec![0.0, 0.0, 1.0]), Blob::new(vec![0; 500]));
+        assert!(matches!(huge, Err(PlaceError::CapacityExceeded)));
+        assert_eq!(storage.len(), 2);
+    }
+
+    #[test]
+    fn test_memory_storage_spills_large_blobs() {
+        use crate::adapters::storage::DirSpillStore;
+
+        let dir = crate::testing::temp_path("arms-spill");
+        let store = DirSpillStore::new(&dir).unwrap();
+        let mut storage = MemoryStorage::with_capacity(2, 1000).with_blob_spill(64, store.clone());
+
+        let small = storage.place(Point::new(vec![1.0, 0.0]), Blob::new(vec![1; 10])).unwrap();
+        let big = storage.place(Point::new(vec![0.0, 1.0]), Blob::new(vec![7; 4096])).unwrap();
+        assert_eq!(storage.spilled_len(), 1);
+        assert!(store.path(big).exists());
+
+        // Only the small blob counts toward capacity
+        assert_eq!(storage.size_bytes(), 2 * (16 + 8 + 48) + 10);
+        assert!(!storage.get(small).unwrap().blob.is_deferred());
+        let blob = &storage.get(big).unwrap().blob;
+        assert_eq!((blob.size(), blob.is_loaded()), (4096, false));
+        assert_eq!(blob.data(), &[7; 4096][..]);
+
+        // Removing hands back the bytes and deletes the file
+        let removed = storage.remove(big).unwrap();
+        assert_eq!(removed.blob.data().len(), 4096);
+        assert!(!store.path(big).exists());
+
+        let again = storage.place(Point::new(vec![0.0, 1.0]), Blob::new(vec![7; 100])).unwrap();
+        storage.clear();
+        assert!(!store.path(again).exists());
+        assert_eq!(storage.spilled_len(), 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_memory_storage_evict_least_salient() {
+        let mut storage = MemoryStorage::with_capacity(3, 152)
+            .with_capacity_policy(CapacityPolicy::EvictLeastSalient);
+
+        let kept = storage.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        let dull = storage.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::empty()).unwrap();
+        storage.set_salience(kept, 0.9);
+        storage.set_salience(dull, 0.1);
+
+        let third = storage.place(Point::new(vec![0.0, 0.0, 1.0]), Blob::empty()).unwrap();
+        assert!(storage.contains(kept) && storage.contains(third));
+        assert_eq!(storage.take_evicted(), vec![dull]);
+
+        // Unscored points go before scored ones
+        storage.place(Point::new(vec![1.0, 1.0, 0.0]), Blob::empty()).unwrap();
+        assert_eq!(storage.take_evicted(), vec![third]);
+        assert!(storage.contains(kept));
+    }
 }
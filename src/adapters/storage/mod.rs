@@ -4,11 +4,51 @@
 //!
 //! Available adapters:
 //! - `MemoryStorage` - In-memory HashMap (fast, volatile)
+//! - `JournaledStorage` - In-memory with a change journal for async hosts (IndexedDB)
+//! - `IndexedDbStorage` - `JournaledStorage` written to IndexedDB (browser hosts, feature `wasm`)
+//! - `WalStorage` - In-memory with an append-only log and group commit (persistent, not on wasm32)
+//! - `RocksStorage` - RocksDB column families (persistent, feature `rocksdb`)
+//! - `RedbStorage` - redb tables (persistent, pure Rust, feature `redb`)
+//! - `DirSpillStore` - Side files for blobs over `MemoryStorage`'s spill threshold
 //! - `NvmeStorage` - Memory-mapped NVMe (persistent, large) [TODO]
 
 mod memory;
+mod journal;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+mod wal;
+mod spill;
 
-pub use memory::MemoryStorage;
+#[cfg(any(feature = "redb", feature = "rocksdb"))]
+mod lock;
+
+pub use memory::{MemoryStorage, CapacityPolicy, EvictionCallback};
+pub use journal::{JournaledStorage, JournalRecord, encode_record, decode_record};
+pub use spill::{DirSpillStore, SpillStore};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use wal::{Durability, GroupCommit, WalStorage};
+
+/// Write-ahead log format version (see `WalStorage`)
+pub const WAL_FORMAT_VERSION: u8 = 1;
+
+#[cfg(feature = "wasm")]
+mod indexeddb;
+
+#[cfg(feature = "wasm")]
+pub use indexeddb::{IndexedDb, IndexedDbStorage, DEFAULT_IDB_BATCH};
+#[cfg(all(feature = "wasm", target_arch = "wasm32", target_os = "unknown"))]
+pub use indexeddb::HostIndexedDb;
+
+#[cfg(feature = "rocksdb")]
+mod rocks;
+
+#[cfg(feature = "rocksdb")]
+pub use rocks::RocksStorage;
+
+#[cfg(feature = "redb")]
+mod redb;
+
+#[cfg(feature = "redb")]
+pub use self::redb::RedbStorage;
 
 // TODO: Add NVMe adapter
 // mod nvme;
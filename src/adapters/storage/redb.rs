@@ -0,0 +1,555 @@
+//! # redb Storage Adapter
+//!
+//! Durable storage on redb, a pure-Rust embedded database (feature `redb`).
+//!
+//! No C toolchain needed, unlike `RocksStorage`. Points are split across
+//! tables keyed by their 16-byte ID:
+//!
+//! ```text
+//! vectors   id → f32 LE × dims
+//! blobs     id → payload bytes
+//! metadata  "version" → u32 LE, "dimensionality" → u32 LE
+//! ```
+//!
+//! Every write is a redb transaction: a point's vector and blob commit
+//! together, and `place_all` commits many points at once. IDs sort by
+//! creation time, so `range` reads the points placed in a time window.
+//!
+//! As with `RocksStorage`, points are mirrored in a `MemoryStorage` loaded
+//! on open so `Place::get` can hand out references. `remove` and `clear`
+//! can't report errors through `Place`: if the transaction fails, the point
+//! is still dropped from memory and reappears on the next open.
+//!
+//! `open` locks the store (see `StoreLock`): a second writer fails with
+//! `PlaceError::StoreLocked` naming the process holding it.
+//!
+//! `open_read_only` serves a published snapshot: the file is opened without
+//! write access and without redb's exclusive lock, and never modified, so
+//! any number of processes can serve it at once. Places fail with
+//! `PlaceError::ReadOnly`; `remove` and `clear` do nothing. The file must
+//! not change while served.
+
+use std::fs::File;
+use std::io;
+use std::ops::{Bound, RangeBounds};
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+
+use redb::{Database, ReadableTable, StorageBackend, TableDefinition};
+
+use super::lock::StoreLock;
+use super::MemoryStorage;
+use crate::core::{Blob, Cause, Id, PlacedPoint, Point};
+use crate::ports::{Place, PlaceError, PlaceResult};
+
+/// On-disk layout version
+const STORE_VERSION: u32 = 1;
+
+const VECTORS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("vectors");
+const BLOBS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("blobs");
+const METADATA: TableDefinition<&str, u32> = TableDefinition::new("metadata");
+
+fn storage_error(e: impl Into<redb::Error>) -> PlaceError {
+    PlaceError::StorageError(Cause::new(e.into()))
+}
+
+fn key_bound(bound: Bound<&Id>) -> Bound<&[u8]> {
+    bound.map(|id| id.as_bytes().as_slice())
+}
+
+fn encode_vector(point: &Point) -> Vec<u8> {
+    point.dims().iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode_point(key: &[u8], vector: &[u8], blob: Option<&[u8]>) -> PlaceResult<PlacedPoint> {
+    let id_bytes: [u8; 16] = key
+        .try_into()
+        .map_err(|_| PlaceError::Corrupted("vector key is not an ID".into()))?;
+    let id = Id::from_bytes(id_bytes);
+    if !vector.len().is_multiple_of(4) {
+        return Err(PlaceError::Corrupted(format!("vector of {}", id).into()));
+    }
+    let dims = vector
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    Ok(PlacedPoint::new(
+        id,
+        Point::new(dims),
+        Blob::new(blob.unwrap_or_default().to_vec()),
+    ))
+}
+
+/// redb backend over a file opened without write access (and unlocked)
+///
+/// redb updates its header even when only reading, so writes land in a
+/// private in-memory overlay instead of the file, like a private mapping:
+/// this process sees them, the file and other processes never do.
+#[derive(Debug)]
+struct PrivateFile {
+    file: File,
+
+    /// Writes in order, replayed over file reads
+    overlay: Mutex<Vec<(u64, Vec<u8>)>>,
+
+    /// Length after `set_len` (None = the file's)
+    len: Mutex<Option<u64>>,
+}
+
+impl PrivateFile {
+    fn new(file: File) -> Self {
+        Self { file, overlay: Mutex::new(Vec::new()), len: Mutex::new(None) }
+    }
+
+    #[cfg(unix)]
+    fn read_file(&self, buffer: &mut [u8], offset: u64) -> io::Result<()> {
+        use std::os::unix::fs::FileExt;
+        self.file.read_exact_at(buffer, offset)
+    }
+
+    #[cfg(windows)]
+    fn read_file(&self, buffer: &mut [u8], offset: u64) -> io::Result<()> {
+        use std::os::windows::fs::FileExt;
+        let mut done = 0;
+        while done < buffer.len() {
+            let read = self.file.seek_read(&mut buffer[done..], offset + done as u64)?;
+            if read == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            done += read;
+        }
+        Ok(())
+    }
+}
+
+impl StorageBackend for PrivateFile {
+    fn len(&self) -> io::Result<u64> {
+        match *self.len.lock().unwrap_or_else(PoisonError::into_inner) {
+            Some(len) => Ok(len),
+            None => Ok(self.file.metadata()?.len()),
+        }
+    }
+
+    fn read(&self, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+        let mut buffer = vec![0; len];
+
+        // File bytes, zeros past its end (the overlay may have grown it)
+        let file_len = self.file.metadata()?.len();
+        let from_file = file_len.saturating_sub(offset).min(len as u64) as usize;
+        self.read_file(&mut buffer[..from_file], offset)?;
+
+        let end = offset + len as u64;
+        for (at, data) in self.overlay.lock().unwrap_or_else(PoisonError::into_inner).iter() {
+            let (start, stop) = ((*at).max(offset), (at + data.len() as u64).min(end));
+            if start < stop {
+                buffer[(start - offset) as usize..(stop - offset) as usize]
+                    .copy_from_slice(&data[(start - at) as usize..(stop - at) as usize]);
+            }
+        }
+        Ok(buffer)
+    }
+
+    fn set_len(&self, len: u64) -> io::Result<()> {
+        *self.len.lock().unwrap_or_else(PoisonError::into_inner) = Some(len);
+        Ok(())
+    }
+
+    fn sync_data(&self, _: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write(&self, offset: u64, data: &[u8]) -> io::Result<()> {
+        let mut overlay = self.overlay.lock().unwrap_or_else(PoisonError::into_inner);
+        // redb rewrites the same header slots; replace instead of growing
+        overlay.retain(|(at, old)| !(*at == offset && old.len() == data.len()));
+        overlay.push((offset, data.to_vec()));
+        Ok(())
+    }
+}
+
+/// Place adapter persisting to a redb database file
+pub struct RedbStorage {
+    db: Database,
+
+    /// Length of each stored vector
+    dimensionality: usize,
+
+    /// Live data (mirrors the database)
+    inner: MemoryStorage,
+
+    /// Opened with `open_read_only`
+    read_only: bool,
+
+    /// Writer lock (None when read-only); declared last so it's released
+    /// after the database closes
+    _lock: Option<StoreLock>,
+}
+
+impl RedbStorage {
+    /// Open (or create) a database file at `path` for points of `dimensionality`
+    ///
+    /// Fails with `StoreLocked` if another writer has it open,
+    /// `DimensionalityMismatch` if the database was created with another
+    /// dimensionality, and `Corrupted` if a stored point can't be decoded.
+    pub fn open(path: impl AsRef<Path>, dimensionality: usize) -> PlaceResult<Self> {
+        let lock = StoreLock::acquire(path.as_ref())?;
+        let db = Database::create(path).map_err(storage_error)?;
+        let mut storage = Self {
+            db,
+            dimensionality,
+            inner: MemoryStorage::new(dimensionality),
+            read_only: false,
+            _lock: Some(lock),
+        };
+        storage.check_metadata()?;
+        storage.load()?;
+        Ok(storage)
+    }
+
+    /// Open an existing database file for reading only
+    ///
+    /// Takes no lock and never writes the file, so many processes can open
+    /// it at once. It must not be written while open. Fails like `open`,
+    /// and with `Corrupted` if the file isn't an ARMS store.
+    pub fn open_read_only(path: impl AsRef<Path>, dimensionality: usize) -> PlaceResult<Self> {
+        let file = File::open(path).map_err(|e| PlaceError::StorageError(Cause::new(e)))?;
+        let db = Database::builder()
+            .create_with_backend(PrivateFile::new(file))
+            .map_err(storage_error)?;
+        let mut storage = Self {
+            db,
+            dimensionality,
+            inner: MemoryStorage::new(dimensionality),
+            read_only: true,
+            _lock: None,
+        };
+        storage.check_stored_metadata()?;
+        storage.load()?;
+        Ok(storage)
+    }
+
+    /// Whether the store was opened with `open_read_only`
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Check the recorded layout without writing
+    fn check_stored_metadata(&self) -> PlaceResult<()> {
+        let txn = self.db.begin_read().map_err(storage_error)?;
+        let metadata = txn
+            .open_table(METADATA)
+            .map_err(|_| PlaceError::Corrupted("not an ARMS store".into()))?;
+        let version = metadata.get("version").map_err(storage_error)?.map(|v| v.value());
+        let stored = metadata.get("dimensionality").map_err(storage_error)?.map(|v| v.value());
+        match (version, stored) {
+            (Some(STORE_VERSION), Some(stored)) if stored as usize == self.dimensionality => Ok(()),
+            (Some(STORE_VERSION), Some(stored)) => Err(PlaceError::DimensionalityMismatch {
+                expected: stored as usize,
+                got: self.dimensionality,
+            }),
+            (Some(STORE_VERSION), None) => Err(PlaceError::Corrupted("metadata missing dimensionality".into())),
+            (Some(version), _) => Err(PlaceError::Corrupted(format!("unsupported store version {}", version).into())),
+            (None, _) => Err(PlaceError::Corrupted("not an ARMS store".into())),
+        }
+    }
+
+    /// Create the tables, record the layout, or check it against an existing database
+    fn check_metadata(&self) -> PlaceResult<()> {
+        let txn = self.db.begin_write().map_err(storage_error)?;
+        {
+            txn.open_table(VECTORS).map_err(storage_error)?;
+            txn.open_table(BLOBS).map_err(storage_error)?;
+            let mut metadata = txn.open_table(METADATA).map_err(storage_error)?;
+
+            let version = metadata.get("version").map_err(storage_error)?.map(|v| v.value());
+            let stored = metadata.get("dimensionality").map_err(storage_error)?.map(|v| v.value());
+            match (version, stored) {
+                (None, _) => {
+                    metadata.insert("version", STORE_VERSION).map_err(storage_error)?;
+                    metadata
+                        .insert("dimensionality", self.dimensionality as u32)
+                        .map_err(storage_error)?;
+                }
+                (Some(STORE_VERSION), Some(stored)) if stored as usize == self.dimensionality => {}
+                (Some(STORE_VERSION), Some(stored)) => {
+                    return Err(PlaceError::DimensionalityMismatch {
+                        expected: stored as usize,
+                        got: self.dimensionality,
+                    });
+                }
+                (Some(STORE_VERSION), None) => {
+                    return Err(PlaceError::Corrupted("metadata missing dimensionality".into()));
+                }
+                (Some(version), _) => {
+                    return Err(PlaceError::Corrupted(format!("unsupported store version {}", version).into()));
+                }
+            }
+        }
+        txn.commit().map_err(storage_error)
+    }
+
+    /// Fill the memory mirror from the database
+    fn load(&mut self) -> PlaceResult<()> {
+        for placed in self.range(..)? {
+            self.inner.place_with_id(placed.id, placed.point, placed.blob)?;
+        }
+        Ok(())
+    }
+
+    /// Read the points whose IDs fall in `range`, in ID (creation) order
+    ///
+    /// Reads from the database, so it also serves snapshots and exports.
+    pub fn range(&self, range: impl RangeBounds<Id>) -> PlaceResult<Vec<PlacedPoint>> {
+        let bounds = (key_bound(range.start_bound()), key_bound(range.end_bound()));
+
+        let txn = self.db.begin_read().map_err(storage_error)?;
+        let vectors = txn.open_table(VECTORS).map_err(storage_error)?;
+        let blobs = txn.open_table(BLOBS).map_err(storage_error)?;
+
+        let mut points = Vec::new();
+        for entry in vectors.range::<&[u8]>(bounds).map_err(storage_error)? {
+            let (key, vector) = entry.map_err(storage_error)?;
+            let blob = blobs.get(key.value()).map_err(storage_error)?;
+            points.push(decode_point(key.value(), vector.value(), blob.as_ref().map(|b| b.value()))?);
+        }
+        Ok(points)
+    }
+
+    /// Place many points in one transaction
+    ///
+    /// Either every point is stored or, on error, none is.
+    pub fn place_all(&mut self, items: Vec<(Point, Blob)>) -> PlaceResult<Vec<Id>> {
+        self.check_writable()?;
+        let items: Vec<(Id, Point, Blob)> = items
+            .into_iter()
+            .map(|(point, blob)| (Id::now(), point, blob))
+            .collect();
+        for (_, point, _) in &items {
+            self.check_point(point)?;
+        }
+
+        self.write(|vectors, blobs| {
+            for (id, point, blob) in &items {
+                vectors.insert(id.as_bytes().as_slice(), encode_vector(point).as_slice())?;
+                blobs.insert(id.as_bytes().as_slice(), blob.data())?;
+            }
+            Ok(())
+        })?;
+
+        let ids = items.iter().map(|(id, _, _)| *id).collect();
+        for (id, point, blob) in items {
+            self.inner.place_with_id(id, point, blob)?;
+        }
+        Ok(ids)
+    }
+
+    fn check_writable(&self) -> PlaceResult<()> {
+        if self.read_only {
+            return Err(PlaceError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    fn check_point(&self, point: &Point) -> PlaceResult<()> {
+        if point.dimensionality() != self.dimensionality {
+            return Err(PlaceError::DimensionalityMismatch {
+                expected: self.dimensionality,
+                got: point.dimensionality(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Run `f` on the vector and blob tables in one write transaction
+    fn write<F>(&self, f: F) -> PlaceResult<()>
+    where
+        F: FnOnce(
+            &mut redb::Table<&[u8], &[u8]>,
+            &mut redb::Table<&[u8], &[u8]>,
+        ) -> Result<(), redb::StorageError>,
+    {
+        let txn = self.db.begin_write().map_err(storage_error)?;
+        {
+            let mut vectors = txn.open_table(VECTORS).map_err(storage_error)?;
+            let mut blobs = txn.open_table(BLOBS).map_err(storage_error)?;
+            f(&mut vectors, &mut blobs).map_err(storage_error)?;
+        }
+        txn.commit().map_err(storage_error)
+    }
+
+    /// Delete points from the database
+    fn erase(&self, ids: &[Id]) -> PlaceResult<()> {
+        self.write(|vectors, blobs| {
+            for id in ids {
+                vectors.remove(id.as_bytes().as_slice())?;
+                blobs.remove(id.as_bytes().as_slice())?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Place for RedbStorage {
+    fn place(&mut self, point: Point, blob: Blob) -> PlaceResult<Id> {
+        let id = Id::now();
+        self.place_with_id(id, point, blob)?;
+        Ok(id)
+    }
+
+    fn place_with_id(&mut self, id: Id, point: Point, blob: Blob) -> PlaceResult<()> {
+        // Validate against memory before touching the database
+        self.check_writable()?;
+        if self.inner.contains(id) {
+            return Err(PlaceError::DuplicateId(id));
+        }
+        self.check_point(&point)?;
+
+        self.write(|vectors, blobs| {
+            vectors.insert(id.as_bytes().as_slice(), encode_vector(&point).as_slice())?;
+            blobs.insert(id.as_bytes().as_slice(), blob.data())?;
+            Ok(())
+        })?;
+        self.inner.place_with_id(id, point, blob)
+    }
+
+    fn remove(&mut self, id: Id) -> Option<PlacedPoint> {
+        if self.read_only {
+            return None;
+        }
+        let removed = self.inner.remove(id)?;
+        let _ = self.erase(&[id]);
+        Some(removed)
+    }
+
+    fn get(&self, id: Id) -> Option<&PlacedPoint> {
+        self.inner.get(id)
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &PlacedPoint> + '_> {
+        self.inner.iter()
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.inner.size_bytes()
+    }
+
+    fn compact(&mut self) -> PlaceResult<bool> {
+        // Freed pages may still hold removed payloads until rewritten
+        self.check_writable()?;
+        self.db.compact().map_err(storage_error)
+    }
+
+    fn clear(&mut self) {
+        if self.read_only {
+            return;
+        }
+        let ids: Vec<Id> = self.inner.iter().map(|p| p.id).collect();
+        let _ = self.erase(&ids);
+        self.inner.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_path;
+
+    #[test]
+    fn test_redb_survives_reopen() {
+        let path = temp_path("arms-redb");
+        let (kept, removed) = {
+            let mut storage = RedbStorage::open(&path, 3).unwrap();
+            let kept = storage.place(Point::new(vec![1.0, 2.0, 3.0]), Blob::from_str("kept")).unwrap();
+            let removed = storage.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::empty()).unwrap();
+            storage.remove(removed);
+            (kept, removed)
+        };
+
+        let storage = RedbStorage::open(&path, 3).unwrap();
+        assert_eq!(storage.len(), 1);
+        let placed = storage.get(kept).unwrap();
+        assert_eq!(placed.point.dims(), &[1.0, 2.0, 3.0]);
+        assert_eq!(placed.blob.as_str(), Some("kept"));
+        assert!(storage.get(removed).is_none());
+        drop(storage);
+
+        assert!(matches!(
+            RedbStorage::open(&path, 4),
+            Err(PlaceError::DimensionalityMismatch { expected: 3, got: 4 })
+        ));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_redb_batch_and_range() {
+        let path = temp_path("arms-redb");
+        let mut storage = RedbStorage::open(&path, 2).unwrap();
+        let ids = storage
+            .place_all((0..5).map(|i| (Point::new(vec![i as f32, 1.0]), Blob::empty())).collect())
+            .unwrap();
+        assert_eq!(storage.len(), 5);
+
+        // A bad point rejects the whole batch
+        let bad = vec![(Point::new(vec![1.0, 1.0]), Blob::empty()), (Point::new(vec![1.0]), Blob::empty())];
+        assert!(storage.place_all(bad).is_err());
+        assert_eq!(storage.len(), 5);
+
+        let middle: Vec<Id> = storage.range(ids[1]..ids[4]).unwrap().iter().map(|p| p.id).collect();
+        assert_eq!(middle, ids[1..4]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_redb_single_writer() {
+        let path = temp_path("arms-redb");
+        let writer = RedbStorage::open(&path, 2).unwrap();
+        assert!(matches!(
+            RedbStorage::open(&path, 2),
+            Err(PlaceError::StoreLocked { pid: Some(pid), .. }) if pid == std::process::id()
+        ));
+
+        drop(writer);
+        assert!(RedbStorage::open(&path, 2).is_ok());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_redb_read_only_serves_concurrently() {
+        let path = temp_path("arms-redb");
+        let id = {
+            let mut storage = RedbStorage::open(&path, 2).unwrap();
+            storage.place(Point::new(vec![1.0, 0.0]), Blob::from_str("shared")).unwrap()
+        };
+        let before = std::fs::read(&path).unwrap();
+
+        // Several readers at once (a writable open would hold an exclusive lock)
+        let mut first = RedbStorage::open_read_only(&path, 2).unwrap();
+        let second = RedbStorage::open_read_only(&path, 2).unwrap();
+        assert!(first.is_read_only());
+        assert_eq!(second.get(id).unwrap().blob.as_str(), Some("shared"));
+
+        assert_eq!(first.place(Point::new(vec![0.0, 1.0]), Blob::empty()), Err(PlaceError::ReadOnly));
+        assert_eq!(first.place_all(vec![(Point::new(vec![0.0, 1.0]), Blob::empty())]), Err(PlaceError::ReadOnly));
+        assert!(first.remove(id).is_none());
+        first.clear();
+        assert_eq!(first.len(), 1);
+        drop((first, second));
+
+        assert_eq!(std::fs::read(&path).unwrap(), before);
+        assert!(matches!(
+            RedbStorage::open_read_only(&path, 3),
+            Err(PlaceError::DimensionalityMismatch { expected: 2, got: 3 })
+        ));
+        let _ = std::fs::remove_file(&path);
+        assert!(RedbStorage::open_read_only(&path, 2).is_err());
+    }
+
+    #[test]
+    fn test_redb_conforms() {
+        crate::testing::conformance::check_place(|dim| RedbStorage::open(temp_path("arms-redb"), dim).unwrap());
+    }
+}
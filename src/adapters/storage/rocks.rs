@@ -0,0 +1,365 @@
+//! # RocksDB Storage Adapter
+//!
+//! Durable storage on RocksDB (feature `rocksdb`).
+//!
+//! Each point is split across column families keyed by its 16-byte ID:
+//!
+//! ```text
+//! vectors   id → f32 LE × dims
+//! blobs     id → payload bytes
+//! metadata  "version" → u32 LE, "dimensionality" → u32 LE
+//! ```
+//!
+//! `Place::get` hands out references, so the points are also kept in a
+//! `MemoryStorage` mirror loaded on open; RocksDB provides durability and
+//! compaction, not larger-than-memory capacity.
+//!
+//! Writes go to RocksDB first (one atomic batch per point) and only then to
+//! memory, so a failed write leaves both unchanged. `remove` and `clear`
+//! can't report errors through `Place`: if RocksDB rejects the delete, the
+//! point is still dropped from memory and reappears on the next open.
+//!
+//! `open` locks the store (see `StoreLock`): a second writer fails with
+//! `PlaceError::StoreLocked` naming the process holding it.
+//!
+//! `open_read_only` uses RocksDB's read-only mode, which takes no lock, so
+//! any number of processes can serve the same database. Places fail with
+//! `PlaceError::ReadOnly`; `remove` and `clear` do nothing.
+//!
+//! Good for:
+//! - Single-node deployments that need to survive restarts
+//! - Users who want proven compaction without a custom on-disk format
+
+use std::path::Path;
+
+use rocksdb::{IteratorMode, Options, WriteBatch, DB};
+
+use super::lock::StoreLock;
+use super::MemoryStorage;
+use crate::core::{Blob, Cause, Id, PlacedPoint, Point};
+use crate::ports::{Place, PlaceError, PlaceResult};
+
+/// On-disk layout version
+const STORE_VERSION: u32 = 1;
+
+const CF_VECTORS: &str = "vectors";
+const CF_BLOBS: &str = "blobs";
+const CF_METADATA: &str = "metadata";
+
+const KEY_VERSION: &[u8] = b"version";
+const KEY_DIMENSIONALITY: &[u8] = b"dimensionality";
+
+fn storage_error(e: rocksdb::Error) -> PlaceError {
+    PlaceError::StorageError(Cause::new(e))
+}
+
+/// Place adapter persisting to a RocksDB database
+pub struct RocksStorage {
+    db: DB,
+
+    /// Length of each stored vector
+    dimensionality: usize,
+
+    /// Live data (mirrors the database)
+    inner: MemoryStorage,
+
+    /// Opened with `open_read_only`
+    read_only: bool,
+
+    /// Writer lock (None when read-only); declared last so it's released
+    /// after the database closes
+    _lock: Option<StoreLock>,
+}
+
+impl RocksStorage {
+    /// Open (or create) a database at `path` for points of `dimensionality`
+    ///
+    /// Fails with `StoreLocked` if another writer has it open,
+    /// `DimensionalityMismatch` if the database was created with another
+    /// dimensionality, and `Corrupted` if a stored point can't be decoded.
+    pub fn open(path: impl AsRef<Path>, dimensionality: usize) -> PlaceResult<Self> {
+        let lock = StoreLock::acquire(path.as_ref())?;
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+
+        let db = DB::open_cf(&options, path, [CF_VECTORS, CF_BLOBS, CF_METADATA])
+            .map_err(storage_error)?;
+
+        let mut storage = Self {
+            db,
+            dimensionality,
+            inner: MemoryStorage::new(dimensionality),
+            read_only: false,
+            _lock: Some(lock),
+        };
+        storage.check_metadata(dimensionality)?;
+        storage.load()?;
+        Ok(storage)
+    }
+
+    /// Open an existing database for reading only
+    ///
+    /// Many processes can open the same database this way; writes made
+    /// elsewhere after opening aren't seen. Fails like `open`, and with
+    /// `Corrupted` if the database isn't an ARMS store.
+    pub fn open_read_only(path: impl AsRef<Path>, dimensionality: usize) -> PlaceResult<Self> {
+        let db = DB::open_cf_for_read_only(&Options::default(), path, [CF_VECTORS, CF_BLOBS, CF_METADATA], false)
+            .map_err(storage_error)?;
+
+        let mut storage = Self {
+            db,
+            dimensionality,
+            inner: MemoryStorage::new(dimensionality),
+            read_only: true,
+            _lock: None,
+        };
+        storage.check_metadata(dimensionality)?;
+        storage.load()?;
+        Ok(storage)
+    }
+
+    /// Whether the store was opened with `open_read_only`
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Flush memtables to disk
+    pub fn flush(&self) -> PlaceResult<()> {
+        for name in [CF_VECTORS, CF_BLOBS, CF_METADATA] {
+            self.db.flush_cf(self.cf(name)).map_err(storage_error)?;
+        }
+        Ok(())
+    }
+
+    fn cf(&self, name: &str) -> &rocksdb::ColumnFamily {
+        // All column families are created on open
+        self.db.cf_handle(name).expect("column family exists")
+    }
+
+    /// Record the layout, or check it against an existing database
+    fn check_metadata(&self, dimensionality: usize) -> PlaceResult<()> {
+        let cf = self.cf(CF_METADATA);
+        let read_u32 = |key: &[u8]| -> PlaceResult<Option<u32>> {
+            match self.db.get_cf(cf, key).map_err(storage_error)? {
+                None => Ok(None),
+                Some(bytes) => bytes
+                    .as_slice()
+                    .try_into()
+                    .map(|b| Some(u32::from_le_bytes(b)))
+                    .map_err(|_| PlaceError::Corrupted(format!("metadata {}", String::from_utf8_lossy(key)).into())),
+            }
+        };
+
+        match read_u32(KEY_VERSION)? {
+            None if self.read_only => Err(PlaceError::Corrupted("not an ARMS store".into())),
+            None => {
+                let mut batch = WriteBatch::default();
+                batch.put_cf(cf, KEY_VERSION, STORE_VERSION.to_le_bytes());
+                batch.put_cf(cf, KEY_DIMENSIONALITY, (dimensionality as u32).to_le_bytes());
+                self.db.write(batch).map_err(storage_error)
+            }
+            Some(STORE_VERSION) => match read_u32(KEY_DIMENSIONALITY)? {
+                Some(stored) if stored as usize == dimensionality => Ok(()),
+                Some(stored) => Err(PlaceError::DimensionalityMismatch {
+                    expected: stored as usize,
+                    got: dimensionality,
+                }),
+                None => Err(PlaceError::Corrupted("metadata missing dimensionality".into())),
+            },
+            Some(version) => Err(PlaceError::Corrupted(format!("unsupported store version {}", version).into())),
+        }
+    }
+
+    /// Fill the memory mirror from the database
+    fn load(&mut self) -> PlaceResult<()> {
+        let mut points = Vec::new();
+        for entry in self.db.iterator_cf(self.cf(CF_VECTORS), IteratorMode::Start) {
+            let (key, value) = entry.map_err(storage_error)?;
+            let id = decode_id(&key)?;
+            if !value.len().is_multiple_of(4) {
+                return Err(PlaceError::Corrupted(format!("vector of {}", id).into()));
+            }
+            let dims = value
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            let blob = self
+                .db
+                .get_cf(self.cf(CF_BLOBS), key)
+                .map_err(storage_error)?
+                .unwrap_or_default();
+            points.push((id, Point::new(dims), Blob::new(blob)));
+        }
+
+        for (id, point, blob) in points {
+            self.inner.place_with_id(id, point, blob)?;
+        }
+        Ok(())
+    }
+
+    /// Write one point to the database
+    fn persist(&self, id: Id, point: &Point, blob: &Blob) -> PlaceResult<()> {
+        let vector: Vec<u8> = point.dims().iter().flat_map(|v| v.to_le_bytes()).collect();
+        let mut batch = WriteBatch::default();
+        batch.put_cf(self.cf(CF_VECTORS), id.as_bytes(), vector);
+        batch.put_cf(self.cf(CF_BLOBS), id.as_bytes(), blob.data());
+        self.db.write(batch).map_err(storage_error)
+    }
+
+    /// Delete points from the database
+    fn erase(&self, ids: &[Id]) -> PlaceResult<()> {
+        let mut batch = WriteBatch::default();
+        for id in ids {
+            batch.delete_cf(self.cf(CF_VECTORS), id.as_bytes());
+            batch.delete_cf(self.cf(CF_BLOBS), id.as_bytes());
+        }
+        self.db.write(batch).map_err(storage_error)
+    }
+}
+
+fn decode_id(key: &[u8]) -> PlaceResult<Id> {
+    let bytes: [u8; 16] = key
+        .try_into()
+        .map_err(|_| PlaceError::Corrupted("vector key is not an ID".into()))?;
+    Ok(Id::from_bytes(bytes))
+}
+
+impl Place for RocksStorage {
+    fn place(&mut self, point: Point, blob: Blob) -> PlaceResult<Id> {
+        let id = Id::now();
+        self.place_with_id(id, point, blob)?;
+        Ok(id)
+    }
+
+    fn place_with_id(&mut self, id: Id, point: Point, blob: Blob) -> PlaceResult<()> {
+        // Validate against memory before touching the database
+        if self.read_only {
+            return Err(PlaceError::ReadOnly);
+        }
+        if self.inner.contains(id) {
+            return Err(PlaceError::DuplicateId(id));
+        }
+        if point.dimensionality() != self.dimensionality {
+            return Err(PlaceError::DimensionalityMismatch {
+                expected: self.dimensionality,
+                got: point.dimensionality(),
+            });
+        }
+
+        self.persist(id, &point, &blob)?;
+        if let Err(e) = self.inner.place_with_id(id, point, blob) {
+            let _ = self.erase(&[id]);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, id: Id) -> Option<PlacedPoint> {
+        if self.read_only {
+            return None;
+        }
+        let removed = self.inner.remove(id)?;
+        let _ = self.erase(&[id]);
+        Some(removed)
+    }
+
+    fn get(&self, id: Id) -> Option<&PlacedPoint> {
+        self.inner.get(id)
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &PlacedPoint> + '_> {
+        self.inner.iter()
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.inner.size_bytes()
+    }
+
+    fn compact(&mut self) -> PlaceResult<bool> {
+        // Deletes are tombstones until compaction drops the old values
+        if self.read_only {
+            return Err(PlaceError::ReadOnly);
+        }
+        for cf in [CF_VECTORS, CF_BLOBS] {
+            self.db.compact_range_cf(self.cf(cf), None::<&[u8]>, None::<&[u8]>);
+        }
+        Ok(true)
+    }
+
+    fn clear(&mut self) {
+        if self.read_only {
+            return;
+        }
+        let ids: Vec<Id> = self.inner.iter().map(|p| p.id).collect();
+        let _ = self.erase(&ids);
+        self.inner.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_path;
+
+    #[test]
+    fn test_rocks_survives_reopen() {
+        let path = temp_path("arms-rocks");
+        let (kept, removed) = {
+            let mut storage = RocksStorage::open(&path, 3).unwrap();
+            let kept = storage.place(Point::new(vec![1.0, 2.0, 3.0]), Blob::from_str("kept")).unwrap();
+            let removed = storage.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::empty()).unwrap();
+            storage.remove(removed);
+            (kept, removed)
+        };
+
+        let storage = RocksStorage::open(&path, 3).unwrap();
+        assert_eq!(storage.len(), 1);
+        let placed = storage.get(kept).unwrap();
+        assert_eq!(placed.point.dims(), &[1.0, 2.0, 3.0]);
+        assert_eq!(placed.blob.as_str(), Some("kept"));
+        assert!(storage.get(removed).is_none());
+        drop(storage);
+
+        assert!(matches!(
+            RocksStorage::open(&path, 4),
+            Err(PlaceError::DimensionalityMismatch { expected: 3, got: 4 })
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn test_rocks_read_only_serves_concurrently() {
+        let path = temp_path("arms-rocks");
+        let id = {
+            let mut storage = RocksStorage::open(&path, 2).unwrap();
+            storage.place(Point::new(vec![1.0, 0.0]), Blob::from_str("shared")).unwrap()
+        };
+
+        let mut first = RocksStorage::open_read_only(&path, 2).unwrap();
+        let second = RocksStorage::open_read_only(&path, 2).unwrap();
+        assert!(first.is_read_only());
+        assert_eq!(second.get(id).unwrap().blob.as_str(), Some("shared"));
+
+        assert_eq!(first.place(Point::new(vec![0.0, 1.0]), Blob::empty()), Err(PlaceError::ReadOnly));
+        assert!(first.remove(id).is_none());
+        first.clear();
+        assert_eq!(first.len(), 1);
+        drop((first, second));
+
+        assert!(matches!(
+            RocksStorage::open_read_only(&path, 3),
+            Err(PlaceError::DimensionalityMismatch { expected: 2, got: 3 })
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+    }
+
+    #[test]
+    fn test_rocks_conforms() {
+        crate::testing::conformance::check_place(|dim| RocksStorage::open(temp_path("arms-rocks"), dim).unwrap());
+    }
+}
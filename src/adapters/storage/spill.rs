@@ -0,0 +1,73 @@
+//! # Blob Spilling
+//!
+//! Side storage for blobs too large to keep in memory. A store configured
+//! with `MemoryStorage::with_blob_spill` writes each blob over the threshold
+//! here and keeps only a deferred `Blob` inline, which reads the bytes back
+//! the first time they're accessed.
+//!
+//! `DirSpillStore` keeps one file per blob. Implement `SpillStore` to spill
+//! to an object store or anything else addressable by ID.
+//!
+//! ```rust,ignore
+//! let storage = MemoryStorage::new(768)
+//!     .with_blob_spill(1024 * 1024, DirSpillStore::new("/var/lib/arms/blobs")?);
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::core::Id;
+
+/// Somewhere to put blobs by ID
+pub trait SpillStore: Send + Sync {
+    /// Store a blob, replacing any previous one
+    fn put(&self, id: Id, data: &[u8]) -> io::Result<()>;
+
+    /// Read a blob back
+    fn get(&self, id: Id) -> io::Result<Vec<u8>>;
+
+    /// Forget a blob (missing blobs are fine)
+    fn delete(&self, id: Id) -> io::Result<()>;
+}
+
+/// One file per blob in a directory
+#[derive(Debug, Clone)]
+pub struct DirSpillStore {
+    dir: PathBuf,
+}
+
+impl DirSpillStore {
+    /// Spill into `dir`, creating it if needed
+    pub fn new(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// File holding a blob
+    pub fn path(&self, id: Id) -> PathBuf {
+        self.dir.join(format!("{}.blob", id))
+    }
+}
+
+impl SpillStore for DirSpillStore {
+    fn put(&self, id: Id, data: &[u8]) -> io::Result<()> {
+        // Write aside and rename, so a reader never sees half a blob
+        let path = self.path(id);
+        let temp = path.with_extension("blob.tmp");
+        fs::write(&temp, data)?;
+        fs::rename(&temp, &path)
+    }
+
+    fn get(&self, id: Id) -> io::Result<Vec<u8>> {
+        fs::read(self.path(id))
+    }
+
+    fn delete(&self, id: Id) -> io::Result<()> {
+        match fs::remove_file(self.path(id)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,653 @@
+//! # Write-Ahead Log Storage Adapter
+//!
+//! In-memory storage backed by an append-only log file: every change is
+//! appended as a checksummed entry and replayed on open.
+//!
+//! ```text
+//! header   "ARMSWAL" | version u8 | dimensionality u32 LE
+//! entry    tag u8 | len u32 LE | payload | FNV-1a u32 LE (tag + payload)
+//!          tag 1 = put (payload: `encode_record`), 2 = delete (payload: id)
+//! ```
+//!
+//! Writes are grouped: entries collect in memory until `GroupCommit`'s
+//! entry count or delay is reached, then go out in one write and at most
+//! one fsync. `Durability` decides when that fsync happens. Deadlines are
+//! kept by a flusher thread, started when `GroupCommit::max_delay` or
+//! `Durability::Interval` is set, so a quiet log still commits on time.
+//! Writes still buffered when the process dies are lost; `flush` forces a
+//! commit, and dropping the storage flushes.
+//!
+//! ```rust,ignore
+//! let storage = WalStorage::open("memory.wal", 768)?
+//!     .with_group_commit(GroupCommit::new(Duration::from_micros(500), 64))
+//!     .with_durability(Durability::Interval(Duration::from_millis(100)));
+//! ```
+//!
+//! Settings belong to the storage, so each collection in `Collections`
+//! picks its own by wrapping its own `WalStorage` in `Arms::with_adapters`.
+//!
+//! A torn entry at the end of the log (a crash mid-write) is dropped on
+//! open; a bad entry before the end fails with `Corrupted`. `clear`
+//! truncates the log, and `compact` rewrites it with only live points, so
+//! removed payloads no longer exist on disk. The log isn't locked: open it
+//! from one process.
+//!
+//! Not built for `wasm32-unknown-unknown`, which has neither files nor
+//! threads; browser hosts persist through `IndexedDbStorage` instead.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::journal::{decode_record, encode_record};
+use super::{MemoryStorage, WAL_FORMAT_VERSION};
+use crate::core::bytes::ByteReader;
+use crate::core::{Blob, Cause, Id, PlacedPoint, Point};
+use crate::ports::{Place, PlaceError, PlaceResult};
+
+/// File magic
+const MAGIC: &[u8; 7] = b"ARMSWAL";
+
+/// Header bytes: magic, version, dimensionality
+const HEADER_LEN: usize = 7 + 1 + 4;
+
+const TAG_PUT: u8 = 1;
+const TAG_DELETE: u8 = 2;
+
+fn io_error(e: std::io::Error) -> PlaceError {
+    PlaceError::StorageError(Cause::new(e))
+}
+
+/// When committed log writes reach stable storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Durability {
+    /// fsync on every commit (default)
+    #[default]
+    Always,
+
+    /// fsync at most once per interval, and within an interval of a write
+    /// (by the flusher thread); a crash loses up to one interval
+    Interval(Duration),
+
+    /// Never fsync; the OS writes back on its own schedule
+    Os,
+}
+
+/// How many writes are buffered before a commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupCommit {
+    /// Commit once the oldest buffered entry is this old (checked on each
+    /// write and by the flusher thread)
+    pub max_delay: Duration,
+
+    /// Commit once this many entries are buffered
+    pub max_entries: usize,
+}
+
+impl GroupCommit {
+    pub fn new(max_delay: Duration, max_entries: usize) -> Self {
+        Self { max_delay, max_entries }
+    }
+}
+
+impl Default for GroupCommit {
+    /// No grouping: every write commits on its own
+    fn default() -> Self {
+        Self::new(Duration::ZERO, 1)
+    }
+}
+
+/// Memory storage with an append-only log for durability
+pub struct WalStorage {
+    /// Live data
+    inner: MemoryStorage,
+
+    /// Where the log lives (for `compact`)
+    path: PathBuf,
+
+    /// Length of each stored vector
+    dimensionality: usize,
+
+    /// The log and its unwritten entries, shared with the flusher
+    log: Arc<Mutex<Log>>,
+
+    /// Thread committing on `max_delay` and fsyncing on `Interval` (None =
+    /// neither is set)
+    flusher: Option<Flusher>,
+}
+
+/// The log file and the entries waiting to be written to it
+struct Log {
+    /// The log, opened for appending
+    file: File,
+
+    /// Encoded entries not yet written
+    buffer: Vec<u8>,
+
+    /// Entries in `buffer`
+    buffered: usize,
+
+    /// When the oldest buffered entry was added
+    oldest: Option<Instant>,
+
+    /// Last fsync (for `Durability::Interval`)
+    last_sync: Instant,
+
+    /// Written since the last fsync
+    unsynced: bool,
+
+    durability: Durability,
+    group: GroupCommit,
+}
+
+impl WalStorage {
+    /// Open (or create) the log at `path` and replay it
+    ///
+    /// Fails with `DimensionalityMismatch` if the log was created with
+    /// another dimensionality and `Corrupted` if an entry before the end
+    /// can't be decoded.
+    pub fn open(path: impl AsRef<Path>, dimensionality: usize) -> PlaceResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)
+            .map_err(io_error)?;
+        let mut log = Vec::new();
+        file.read_to_end(&mut log).map_err(io_error)?;
+
+        let mut inner = MemoryStorage::new(dimensionality);
+        if log.is_empty() {
+            file.write_all(&header(dimensionality)).map_err(io_error)?;
+            file.sync_data().map_err(io_error)?;
+        } else {
+            let valid = replay(&log, dimensionality, &mut inner)?;
+            if valid < log.len() {
+                file.set_len(valid as u64).map_err(io_error)?;
+            }
+        }
+
+        let log = Log {
+            file,
+            buffer: Vec::new(),
+            buffered: 0,
+            oldest: None,
+            last_sync: Instant::now(),
+            unsynced: false,
+            durability: Durability::default(),
+            group: GroupCommit::default(),
+        };
+        Ok(Self { inner, path, dimensionality, log: Arc::new(Mutex::new(log)), flusher: None })
+    }
+
+    /// Set when commits are fsynced
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.log().durability = durability;
+        self.restart_flusher();
+        self
+    }
+
+    /// Set how writes are grouped into commits
+    pub fn with_group_commit(mut self, group: GroupCommit) -> Self {
+        self.log().group = group;
+        self.restart_flusher();
+        self
+    }
+
+    /// Entries buffered and not yet written to the log
+    pub fn pending_len(&self) -> usize {
+        self.log().buffered
+    }
+
+    /// Write buffered entries and fsync (unless `Durability::Os`)
+    pub fn flush(&mut self) -> PlaceResult<()> {
+        self.log().flush()
+    }
+
+    fn log(&self) -> MutexGuard<'_, Log> {
+        self.log.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Start a flusher for the current settings, stopping the old one
+    fn restart_flusher(&mut self) {
+        self.flusher = None;
+        let period = self.log().flush_period();
+        if let Some(period) = period {
+            self.flusher = Some(Flusher::start(self.log.clone(), period));
+        }
+    }
+
+    /// Log a point just placed in `inner`, dropping it again if that fails
+    fn log_put(&mut self, id: Id) -> PlaceResult<()> {
+        let Some(placed) = self.inner.get(id) else { return Ok(()) };
+        let record = encode_record(placed);
+
+        let appended = {
+            let mut log = self.log();
+            let start = log.buffer.len();
+            let appended = log.append(TAG_PUT, &record);
+            // Still buffered if the write failed; don't log a rejected point
+            if appended.is_err() && log.buffer.len() > start {
+                log.buffer.truncate(start);
+                log.buffered -= 1;
+            }
+            appended
+        };
+        if appended.is_err() {
+            self.inner.remove(id);
+        }
+        appended
+    }
+}
+
+impl Log {
+    /// How often the flusher should check deadlines (None = no deadlines)
+    fn flush_period(&self) -> Option<Duration> {
+        let delay = (self.group.max_entries > 1 && !self.group.max_delay.is_zero()).then_some(self.group.max_delay);
+        let interval = match self.durability {
+            Durability::Interval(interval) if !interval.is_zero() => Some(interval),
+            _ => None,
+        };
+        match (delay, interval) {
+            (Some(delay), Some(interval)) => Some(delay.min(interval)),
+            (delay, interval) => delay.or(interval),
+        }
+    }
+
+    /// Buffer an entry and commit if the group is full or old enough
+    fn append(&mut self, tag: u8, payload: &[u8]) -> PlaceResult<()> {
+        push_entry(&mut self.buffer, tag, payload);
+        self.buffered += 1;
+        let oldest = *self.oldest.get_or_insert_with(Instant::now);
+        if self.buffered >= self.group.max_entries || oldest.elapsed() >= self.group.max_delay {
+            self.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Commit a group that's waited `max_delay`, and fsync writes that have
+    /// waited an interval (run by the flusher)
+    fn tick(&mut self) -> PlaceResult<()> {
+        if self.oldest.is_some_and(|oldest| oldest.elapsed() >= self.group.max_delay) {
+            self.write_buffer()?;
+        }
+        let due = match self.durability {
+            Durability::Always => true,
+            Durability::Interval(interval) => self.last_sync.elapsed() >= interval,
+            Durability::Os => false,
+        };
+        if self.unsynced && due {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered entries and fsync (unless `Durability::Os`)
+    fn flush(&mut self) -> PlaceResult<()> {
+        self.write_buffer()?;
+        if self.unsynced && self.durability != Durability::Os {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    /// Write the buffer in one go and fsync per the durability setting
+    fn commit(&mut self) -> PlaceResult<()> {
+        self.write_buffer()?;
+        match self.durability {
+            Durability::Always => self.sync(),
+            Durability::Interval(interval) if self.last_sync.elapsed() >= interval => self.sync(),
+            Durability::Interval(_) | Durability::Os => Ok(()),
+        }
+    }
+
+    /// Append the buffer to the log (kept for a retry if the write fails)
+    fn write_buffer(&mut self) -> PlaceResult<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.file.write_all(&self.buffer).map_err(io_error)?;
+        self.discard_buffer();
+        self.unsynced = true;
+        Ok(())
+    }
+
+    fn discard_buffer(&mut self) {
+        self.buffer.clear();
+        self.buffered = 0;
+        self.oldest = None;
+    }
+
+    fn sync(&mut self) -> PlaceResult<()> {
+        self.file.sync_data().map_err(io_error)?;
+        self.last_sync = Instant::now();
+        self.unsynced = false;
+        Ok(())
+    }
+}
+
+/// Background thread running `Log::tick` every period until dropped
+struct Flusher {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Flusher {
+    fn start(log: Arc<Mutex<Log>>, period: Duration) -> Self {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let signal = stop.clone();
+        let worker = std::thread::spawn(move || {
+            let (stopped, wake) = &*signal;
+            let mut stopped = stopped.lock().unwrap_or_else(PoisonError::into_inner);
+            while !*stopped {
+                stopped = wake.wait_timeout(stopped, period).unwrap_or_else(PoisonError::into_inner).0;
+                // A failed write stays buffered for the next commit
+                let _ = log.lock().unwrap_or_else(PoisonError::into_inner).tick();
+            }
+        });
+        Self { stop, worker: Some(worker) }
+    }
+}
+
+impl Drop for Flusher {
+    fn drop(&mut self) {
+        let (stopped, wake) = &*self.stop;
+        *stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        wake.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Place for WalStorage {
+    fn place(&mut self, point: Point, blob: Blob) -> PlaceResult<Id> {
+        let id = self.inner.place(point, blob)?;
+        self.log_put(id)?;
+        Ok(id)
+    }
+
+    fn place_with_id(&mut self, id: Id, point: Point, blob: Blob) -> PlaceResult<()> {
+        self.inner.place_with_id(id, point, blob)?;
+        self.log_put(id)
+    }
+
+    fn remove(&mut self, id: Id) -> Option<PlacedPoint> {
+        let removed = self.inner.remove(id)?;
+        // A failed commit leaves the entry buffered for the next one
+        let _ = self.log().append(TAG_DELETE, id.as_bytes());
+        Some(removed)
+    }
+
+    fn get(&self, id: Id) -> Option<&PlacedPoint> {
+        self.inner.get(id)
+    }
+
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = &PlacedPoint> + '_> {
+        self.inner.iter()
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.inner.size_bytes()
+    }
+
+    fn compact(&mut self) -> PlaceResult<bool> {
+        // Everything buffered is superseded by the rewrite
+        let mut wal = self.log.lock().unwrap_or_else(PoisonError::into_inner);
+        wal.discard_buffer();
+
+        let mut log = header(self.dimensionality);
+        for placed in self.inner.iter() {
+            push_entry(&mut log, TAG_PUT, &encode_record(placed));
+        }
+
+        // Write aside and rename, so a crash leaves the old log or the new one
+        let mut temp = self.path.as_os_str().to_owned();
+        temp.push(".compact");
+        let temp = PathBuf::from(temp);
+        let mut file = File::create(&temp).map_err(io_error)?;
+        file.write_all(&log).and_then(|()| file.sync_all()).map_err(io_error)?;
+        std::fs::rename(&temp, &self.path).map_err(io_error)?;
+
+        wal.file = OpenOptions::new().read(true).append(true).open(&self.path).map_err(io_error)?;
+        wal.last_sync = Instant::now();
+        wal.unsynced = false;
+        Ok(true)
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+        let header = header(self.dimensionality);
+        let mut log = self.log();
+        log.discard_buffer();
+
+        // If truncation fails, the old entries replay on the next open
+        let _ = log
+            .file
+            .set_len(0)
+            .and_then(|()| log.file.write_all(&header))
+            .and_then(|()| log.file.sync_data());
+    }
+}
+
+impl Drop for WalStorage {
+    fn drop(&mut self) {
+        self.flusher = None;
+        let _ = self.flush();
+    }
+}
+
+/// Frame an entry onto `out`: tag, length, payload, checksum
+fn push_entry(out: &mut Vec<u8>, tag: u8, payload: &[u8]) {
+    let start = out.len();
+    out.push(tag);
+    out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    out.extend_from_slice(payload);
+    let checksum = fnv1a(&out[start..]);
+    out.extend_from_slice(&checksum.to_le_bytes());
+}
+
+fn header(dimensionality: usize) -> Vec<u8> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(WAL_FORMAT_VERSION);
+    header.extend_from_slice(&(dimensionality as u32).to_le_bytes());
+    header
+}
+
+/// Apply a log's entries to `storage`, returning the length of the valid prefix
+fn replay(log: &[u8], dimensionality: usize, storage: &mut MemoryStorage) -> PlaceResult<usize> {
+    let mut r = ByteReader::new(log);
+    if r.array::<7>("magic").ok().as_ref() != Some(MAGIC) {
+        return Err(PlaceError::Corrupted("not an ARMS log".into()));
+    }
+    let version = r.u8("log version")?;
+    if version != WAL_FORMAT_VERSION {
+        return Err(PlaceError::Corrupted(format!("unsupported log version {}", version).into()));
+    }
+    let stored = r.u32("log dimensionality")? as usize;
+    if stored != dimensionality {
+        return Err(PlaceError::DimensionalityMismatch {
+            expected: stored,
+            got: dimensionality,
+        });
+    }
+
+    let mut offset = r.position();
+    while let Some((tag, payload, checksum)) = next_entry(&mut r) {
+        if fnv1a(&log[offset..offset + 5 + payload.len()]) != checksum {
+            // Only the last entry can be torn
+            if r.is_empty() {
+                break;
+            }
+            return Err(PlaceError::Corrupted(format!("log entry at byte {}", offset).into()));
+        }
+
+        match tag {
+            TAG_PUT => {
+                let placed = decode_record(payload)?;
+                storage.remove(placed.id);
+                storage.place_with_id(placed.id, placed.point, placed.blob)?;
+            }
+            TAG_DELETE => {
+                let bytes: [u8; 16] = payload
+                    .try_into()
+                    .map_err(|_| PlaceError::Corrupted("delete entry without an ID".into()))?;
+                storage.remove(Id::from_bytes(bytes));
+            }
+            tag => return Err(PlaceError::Corrupted(format!("unknown log entry tag {}", tag).into())),
+        }
+        offset = r.position();
+    }
+    Ok(offset)
+}
+
+/// Read the next entry's tag, payload and checksum (None = the log ends
+/// partway through it)
+fn next_entry<'a>(r: &mut ByteReader<'a>) -> Option<(u8, &'a [u8], u32)> {
+    let tag = r.u8("entry tag").ok()?;
+    let len = r.u32("entry length").ok()?;
+    let payload = r.bytes(len as usize, "entry").ok()?;
+    Some((tag, payload, r.u32("entry checksum").ok()?))
+}
+
+/// 32-bit FNV-1a
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0x811c_9dc5, |hash, &b| (hash ^ b as u32).wrapping_mul(0x0100_0193))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_path;
+
+    #[test]
+    fn test_wal_survives_reopen() {
+        let path = temp_path("arms-wal");
+        let (kept, removed) = {
+            let mut storage = WalStorage::open(&path, 3).unwrap();
+            let kept = storage.place(Point::new(vec![1.0, 2.0, 3.0]), Blob::from_str("kept")).unwrap();
+            let removed = storage.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::empty()).unwrap();
+            storage.remove(removed);
+            (kept, removed)
+        };
+
+        let storage = WalStorage::open(&path, 3).unwrap();
+        assert_eq!(storage.len(), 1);
+        assert_eq!(storage.get(kept).unwrap().blob.as_str(), Some("kept"));
+        assert!(!storage.contains(removed));
+
+        drop(storage);
+        assert!(matches!(
+            WalStorage::open(&path, 4),
+            Err(PlaceError::DimensionalityMismatch { expected: 3, got: 4 })
+        ));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_wal_compact_drops_removed_payloads() {
+        let path = temp_path("arms-wal");
+        let mut storage = WalStorage::open(&path, 1).unwrap().with_durability(Durability::Os);
+        let kept = storage.place(Point::new(vec![1.0]), Blob::from_str("kept")).unwrap();
+        let secret = storage.place(Point::new(vec![2.0]), Blob::from_str("secret")).unwrap();
+        storage.remove(secret);
+        storage.flush().unwrap();
+        let contains = |needle: &[u8]| std::fs::read(&path).unwrap().windows(needle.len()).any(|w| w == needle);
+        assert!(contains(b"secret"));
+
+        assert!(storage.compact().unwrap());
+        assert!(!contains(b"secret") && contains(b"kept"));
+
+        // The log stays appendable and replays
+        storage.place(Point::new(vec![3.0]), Blob::empty()).unwrap();
+        drop(storage);
+        let storage = WalStorage::open(&path, 1).unwrap();
+        assert_eq!(storage.len(), 2);
+        assert!(storage.contains(kept) && !storage.contains(secret));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_wal_group_commit() {
+        let path = temp_path("arms-wal");
+        let mut storage = WalStorage::open(&path, 1)
+            .unwrap()
+            .with_group_commit(GroupCommit::new(Duration::from_secs(60), 3))
+            .with_durability(Durability::Os);
+
+        storage.place(Point::new(vec![1.0]), Blob::empty()).unwrap();
+        storage.place(Point::new(vec![2.0]), Blob::empty()).unwrap();
+        assert_eq!(storage.pending_len(), 2);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), HEADER_LEN as u64);
+
+        storage.place(Point::new(vec![3.0]), Blob::empty()).unwrap();
+        assert_eq!(storage.pending_len(), 0);
+
+        storage.place(Point::new(vec![4.0]), Blob::empty()).unwrap();
+        storage.flush().unwrap();
+        assert_eq!(WalStorage::open(&path, 1).unwrap().len(), 4);
+
+        storage.clear();
+        assert_eq!(WalStorage::open(&path, 1).unwrap().len(), 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_wal_flusher_keeps_deadlines() {
+        let path = temp_path("arms-wal");
+        let mut storage = WalStorage::open(&path, 1)
+            .unwrap()
+            .with_group_commit(GroupCommit::new(Duration::from_millis(20), 100))
+            .with_durability(Durability::Interval(Duration::from_millis(20)));
+
+        // No further writes arrive, yet the group commits and is fsynced
+        storage.place(Point::new(vec![1.0]), Blob::empty()).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while (storage.pending_len() > 0 || storage.log().unsynced) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(storage.pending_len(), 0);
+        assert!(!storage.log().unsynced);
+        assert_eq!(WalStorage::open(&path, 1).unwrap().len(), 1);
+
+        // Without deadlines there's nothing to flush in the background
+        storage = storage.with_group_commit(GroupCommit::default()).with_durability(Durability::Os);
+        assert!(storage.flusher.is_none());
+        drop(storage);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_wal_drops_torn_tail() {
+        let path = temp_path("arms-wal");
+        let id = {
+            let mut storage = WalStorage::open(&path, 2).unwrap();
+            let id = storage.place(Point::new(vec![1.0, 0.0]), Blob::from_str("a")).unwrap();
+            storage.place(Point::new(vec![0.0, 1.0]), Blob::from_str("b")).unwrap();
+            id
+        };
+        let len = std::fs::metadata(&path).unwrap().len();
+        OpenOptions::new().write(true).open(&path).unwrap().set_len(len - 3).unwrap();
+
+        let mut storage = WalStorage::open(&path, 2).unwrap();
+        assert_eq!(storage.len(), 1);
+        assert!(storage.contains(id));
+
+        // Appends after the torn entry replay cleanly
+        storage.place(Point::new(vec![1.0, 1.0]), Blob::empty()).unwrap();
+        drop(storage);
+        assert_eq!(WalStorage::open(&path, 2).unwrap().len(), 2);
+        let _ = std::fs::remove_file(&path);
+    }
+}
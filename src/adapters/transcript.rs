@@ -0,0 +1,509 @@
+//! # Transcript Import
+//!
+//! Ingest existing chat logs as HAT sessions and documents.
+//!
+//! ## Supported Formats
+//!
+//! - **OpenAI chat-completions JSON**: a message array
+//!   (`[{"role": "user", "content": "..."}]`), a request object
+//!   (`{"messages": [...]}`), an array of request objects, or JSONL with one
+//!   of these per line. Content may be a string or an array of parts; only
+//!   `text` parts are kept.
+//! - **ChatML**: `<|im_start|>role\ncontent<|im_end|>` blocks.
+//!
+//! ## Mapping
+//!
+//! Each transcript becomes a session. By default it is a single document;
+//! `DocumentSplit::PerUserTurn` starts a new document at every user message
+//! so each exchange can be retrieved on its own.
+//!
+//! ```rust,ignore
+//! let transcripts = parse_transcripts(&std::fs::read_to_string("chats.jsonl")?)?;
+//!
+//! let importer = TranscriptImporter::new()
+//!     .with_embedder(&embedder)
+//!     .with_document_split(DocumentSplit::PerUserTurn);
+//! let batches = importer.import(&mut index, &transcripts)?;
+//! ```
+
+use serde_json::Value;
+
+use super::attention::{AttentionBatch, AttentionState, Role};
+use super::index::HatIndex;
+use crate::core::Point;
+use crate::ports::{EmbedError, Embedder, JobHandle, Near, NearError};
+
+/// A single message from a transcript
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptMessage {
+    pub role: Role,
+    pub content: String,
+    /// Participant name (OpenAI `name` field or ChatML `role name=...`)
+    pub name: Option<String>,
+}
+
+/// An ordered conversation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    pub messages: Vec<TranscriptMessage>,
+}
+
+/// Errors for transcript import
+#[derive(Debug, Clone, thiserror::Error)]
+#[non_exhaustive]
+pub enum ImportError {
+    /// Input is not valid JSON / JSONL
+    #[error("JSON error: {0}")]
+    Json(String),
+
+    /// Input parsed but doesn't have the expected shape
+    #[error("Invalid transcript: {0}")]
+    InvalidFormat(String),
+
+    /// Role string doesn't map to a `Role`
+    #[error("Unknown role: {0}")]
+    UnknownRole(String),
+
+    /// Importing into an index requires an embedder
+    #[error("Importing into an index requires an embedder")]
+    NoEmbedder,
+
+    /// Embedding failed
+    #[error("Embedding failed: {0}")]
+    Embed(#[from] EmbedError),
+
+    /// Index rejected a point
+    #[error("Index error: {0}")]
+    Index(#[from] NearError),
+
+    /// The job was cancelled; earlier transcripts stay imported
+    #[error("Import cancelled after {0} transcript(s)")]
+    Cancelled(usize),
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+/// Parse transcripts, detecting ChatML vs OpenAI JSON
+pub fn parse_transcripts(input: &str) -> Result<Vec<Transcript>, ImportError> {
+    if input.trim_start().starts_with("<|im_start|>") {
+        Ok(vec![parse_chatml(input)?])
+    } else {
+        parse_openai_json(input)
+    }
+}
+
+/// Parse OpenAI chat-completions JSON or JSONL
+pub fn parse_openai_json(input: &str) -> Result<Vec<Transcript>, ImportError> {
+    match serde_json::from_str::<Value>(input) {
+        Ok(value) => transcripts_from_value(&value),
+        Err(whole_err) => {
+            // Fall back to JSONL
+            let mut transcripts = Vec::new();
+            for (line_no, line) in input.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let value: Value = serde_json::from_str(line).map_err(|e| {
+                    if line_no == 0 {
+                        ImportError::Json(whole_err.to_string())
+                    } else {
+                        ImportError::Json(format!("line {}: {}", line_no + 1, e))
+                    }
+                })?;
+                transcripts.extend(transcripts_from_value(&value)?);
+            }
+            Ok(transcripts)
+        }
+    }
+}
+
+fn transcripts_from_value(value: &Value) -> Result<Vec<Transcript>, ImportError> {
+    match value {
+        // {"messages": [...]}
+        Value::Object(obj) => {
+            let messages = obj
+                .get("messages")
+                .and_then(Value::as_array)
+                .ok_or_else(|| ImportError::InvalidFormat("Object has no \"messages\" array".into()))?;
+            Ok(vec![transcript_from_messages(messages)?])
+        }
+        Value::Array(items) => {
+            // A message array has "role" on its elements; otherwise it's a list of transcripts
+            if items.iter().all(|item| item.get("role").is_some()) {
+                Ok(vec![transcript_from_messages(items)?])
+            } else {
+                let mut transcripts = Vec::with_capacity(items.len());
+                for item in items {
+                    transcripts.extend(transcripts_from_value(item)?);
+                }
+                Ok(transcripts)
+            }
+        }
+        _ => Err(ImportError::InvalidFormat("Expected a JSON object or array".into())),
+    }
+}
+
+fn transcript_from_messages(messages: &[Value]) -> Result<Transcript, ImportError> {
+    let mut transcript = Transcript::default();
+
+    for message in messages {
+        let role_str = message
+            .get("role")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ImportError::InvalidFormat("Message has no \"role\"".into()))?;
+        let role = parse_role(role_str)?;
+
+        let content = match message.get("content") {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Array(parts)) => parts
+                .iter()
+                .filter(|p| p.get("type").and_then(Value::as_str) == Some("text"))
+                .filter_map(|p| p.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Some(Value::Null) | None => String::new(),
+            Some(_) => return Err(ImportError::InvalidFormat("Unsupported \"content\" type".into())),
+        };
+
+        // Tool-call-only assistant turns carry no text to retrieve
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        transcript.messages.push(TranscriptMessage {
+            role,
+            content,
+            name: message.get("name").and_then(Value::as_str).map(String::from),
+        });
+    }
+
+    Ok(transcript)
+}
+
+/// Parse a ChatML transcript
+pub fn parse_chatml(input: &str) -> Result<Transcript, ImportError> {
+    let mut transcript = Transcript::default();
+
+    for block in input.split("<|im_start|>").skip(1) {
+        let (body, closed) = match block.find("<|im_end|>") {
+            Some(end) => (&block[..end], true),
+            None => (block, false),
+        };
+
+        let (header, content) = body.split_once('\n').unwrap_or((body, ""));
+        let content = content.trim_end_matches('\n');
+
+        // A trailing open block is a generation prompt, not a message
+        if !closed && content.trim().is_empty() {
+            continue;
+        }
+
+        let mut header_parts = header.split_whitespace();
+        let role_str = header_parts
+            .next()
+            .ok_or_else(|| ImportError::InvalidFormat("ChatML block has no role".into()))?;
+        let name = header_parts
+            .find_map(|part| part.strip_prefix("name="))
+            .map(String::from);
+
+        transcript.messages.push(TranscriptMessage {
+            role: parse_role(role_str)?,
+            content: content.to_string(),
+            name,
+        });
+    }
+
+    Ok(transcript)
+}
+
+fn parse_role(s: &str) -> Result<Role, ImportError> {
+    // OpenAI's "developer" role replaces "system" for reasoning models
+    if s.eq_ignore_ascii_case("developer") {
+        return Ok(Role::System);
+    }
+    Role::from_str(s).ok_or_else(|| ImportError::UnknownRole(s.to_string()))
+}
+
+// ============================================================================
+// IMPORT
+// ============================================================================
+
+/// How transcripts are split into documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DocumentSplit {
+    /// One document per transcript
+    #[default]
+    PerTranscript,
+
+    /// New document at every user message
+    PerUserTurn,
+}
+
+/// Converts transcripts into attention states and ingests them
+#[derive(Default)]
+pub struct TranscriptImporter<'e> {
+    embedder: Option<&'e dyn Embedder>,
+    split: DocumentSplit,
+}
+
+impl<'e> TranscriptImporter<'e> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Embed messages with this embedder
+    pub fn with_embedder(mut self, embedder: &'e dyn Embedder) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// Set how transcripts are split into documents
+    pub fn with_document_split(mut self, split: DocumentSplit) -> Self {
+        self.split = split;
+        self
+    }
+
+    /// Convert a transcript into batches (one per document) without indexing
+    ///
+    /// Without an embedder, states carry empty embeddings.
+    pub fn to_batches(&self, transcript: &Transcript) -> Result<Vec<AttentionBatch>, ImportError> {
+        let embeddings = self.embed(transcript)?;
+
+        let mut batches = Vec::new();
+        for (i, message) in transcript.messages.iter().enumerate() {
+            if batches.is_empty() || self.starts_document(transcript, i) {
+                batches.push(AttentionBatch::new());
+            }
+
+            let embedding = embeddings
+                .as_ref()
+                .map(|e| e[i].dims().to_vec())
+                .unwrap_or_default();
+            let mut state = AttentionState::new(message.role, message.content.clone(), embedding);
+            if let Some(ref name) = message.name {
+                state = state.with_metadata("name", name);
+            }
+            batches.last_mut().unwrap().add(state);
+        }
+
+        Ok(batches)
+    }
+
+    /// Ingest transcripts into an index
+    ///
+    /// Each transcript starts a new session. Returns the stored states as
+    /// batches (one per document) tagged with their session and document IDs.
+    pub fn import(&self, index: &mut HatIndex, transcripts: &[Transcript]) -> Result<Vec<AttentionBatch>, ImportError> {
+        self.import_with(index, transcripts, &JobHandle::new())
+    }
+
+    /// `import`, reporting one unit of progress per transcript to `job`
+    ///
+    /// Stops between transcripts once `job` is cancelled.
+    pub fn import_with(
+        &self,
+        index: &mut HatIndex,
+        transcripts: &[Transcript],
+        job: &JobHandle,
+    ) -> Result<Vec<AttentionBatch>, ImportError> {
+        if self.embedder.is_none() {
+            return Err(ImportError::NoEmbedder);
+        }
+
+        job.set_total(transcripts.len());
+        let mut imported = Vec::new();
+        for (i, transcript) in transcripts.iter().enumerate() {
+            if job.is_cancelled() {
+                return Err(ImportError::Cancelled(i));
+            }
+            index.new_session();
+
+            for batch in self.to_batches(transcript)? {
+                index.new_document();
+                for state in &batch.states {
+                    index.add(state.id, &Point::new(state.embedding.clone()))?;
+                }
+
+                let mut batch = batch;
+                batch.session_id = index.active_session();
+                batch.document_id = index.active_document();
+                imported.push(batch);
+            }
+            job.advance(1);
+        }
+
+        Ok(imported)
+    }
+
+    fn starts_document(&self, transcript: &Transcript, i: usize) -> bool {
+        match self.split {
+            DocumentSplit::PerTranscript => false,
+            DocumentSplit::PerUserTurn => {
+                // Leading system messages stay with the first user turn
+                transcript.messages[i].role == Role::User
+                    && transcript.messages[..i].iter().any(|m| m.role == Role::User)
+            }
+        }
+    }
+
+    fn embed(&self, transcript: &Transcript) -> Result<Option<Vec<Point>>, ImportError> {
+        let Some(embedder) = self.embedder else {
+            return Ok(None);
+        };
+
+        let texts: Vec<&str> = transcript.messages.iter().map(|m| m.content.as_str()).collect();
+        let points = embedder.embed_batch(&texts)?;
+        if points.len() != texts.len() {
+            return Err(ImportError::InvalidFormat(format!(
+                "Embedder returned {} points for {} texts",
+                points.len(),
+                texts.len()
+            )));
+        }
+        for point in &points {
+            if point.dimensionality() != embedder.dimensionality() {
+                return Err(EmbedError::DimensionalityMismatch {
+                    expected: embedder.dimensionality(),
+                    got: point.dimensionality(),
+                }
+                .into());
+            }
+        }
+
+        Ok(Some(points))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ports::EmbedResult;
+
+    /// Deterministic embedder: character histogram over 4 buckets
+    struct HistogramEmbedder;
+
+    impl Embedder for HistogramEmbedder {
+        fn dimensionality(&self) -> usize {
+            4
+        }
+
+        fn embed(&self, text: &str) -> EmbedResult<Point> {
+            let mut dims = vec![1e-3; 4];
+            for b in text.bytes() {
+                dims[(b % 4) as usize] += 1.0;
+            }
+            Ok(Point::new(dims).normalize())
+        }
+    }
+
+    #[test]
+    fn test_parse_openai_variants() {
+        let array = r#"[{"role": "system", "content": "Be brief."},
+                        {"role": "user", "content": "Hi"}]"#;
+        let request = r#"{"model": "gpt-4o", "messages": [{"role": "user", "content": "Hi"}]}"#;
+        let jsonl = format!("{}\n{}\n", request, request);
+
+        assert_eq!(parse_openai_json(array).unwrap()[0].messages.len(), 2);
+        assert_eq!(parse_openai_json(request).unwrap().len(), 1);
+        assert_eq!(parse_openai_json(&jsonl).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_openai_content_parts() {
+        let input = r#"[
+            {"role": "developer", "content": "Rules"},
+            {"role": "user", "name": "ana", "content": [
+                {"type": "text", "text": "What is this?"},
+                {"type": "image_url", "image_url": {"url": "http://x"}}
+            ]},
+            {"role": "assistant", "content": null, "tool_calls": []},
+            {"role": "tool", "content": "42"}
+        ]"#;
+
+        let t = &parse_openai_json(input).unwrap()[0];
+        assert_eq!(t.messages.len(), 3);
+        assert_eq!(t.messages[0].role, Role::System);
+        assert_eq!(t.messages[1].content, "What is this?");
+        assert_eq!(t.messages[1].name.as_deref(), Some("ana"));
+        assert_eq!(t.messages[2].role, Role::Tool);
+    }
+
+    #[test]
+    fn test_parse_chatml() {
+        let input = "<|im_start|>system\nBe brief.<|im_end|>\n\
+                     <|im_start|>user name=bob\nHi\nthere<|im_end|>\n\
+                     <|im_start|>assistant\n";
+
+        let t = parse_transcripts(input).unwrap().remove(0);
+        assert_eq!(t.messages.len(), 2);
+        assert_eq!(t.messages[1].content, "Hi\nthere");
+        assert_eq!(t.messages[1].name.as_deref(), Some("bob"));
+    }
+
+    #[test]
+    fn test_unknown_role() {
+        let result = parse_openai_json(r#"[{"role": "narrator", "content": "x"}]"#);
+        assert!(matches!(result, Err(ImportError::UnknownRole(_))));
+    }
+
+    #[test]
+    fn test_batches_without_embedder() {
+        let t = &parse_openai_json(r#"[{"role": "user", "content": "a"}, {"role": "assistant", "content": "b"}]"#)
+            .unwrap()[0];
+
+        let batches = TranscriptImporter::new().to_batches(t).unwrap();
+        assert_eq!(batches.len(), 1);
+        assert!(batches[0].states[0].embedding.is_empty());
+
+        let mut index = HatIndex::cosine(4);
+        let result = TranscriptImporter::new().import(&mut index, std::slice::from_ref(t));
+        assert!(matches!(result, Err(ImportError::NoEmbedder)));
+    }
+
+    #[test]
+    fn test_import_sessions_and_documents() {
+        let input = r#"[
+            {"messages": [
+                {"role": "system", "content": "sys"},
+                {"role": "user", "content": "first question"},
+                {"role": "assistant", "content": "first answer"},
+                {"role": "user", "content": "second question"},
+                {"role": "assistant", "content": "second answer"}
+            ]},
+            {"messages": [{"role": "user", "content": "other chat"}]}
+        ]"#;
+        let transcripts = parse_openai_json(input).unwrap();
+
+        let embedder = HistogramEmbedder;
+        let mut index = HatIndex::cosine(4);
+        let batches = TranscriptImporter::new()
+            .with_embedder(&embedder)
+            .with_document_split(DocumentSplit::PerUserTurn)
+            .import(&mut index, &transcripts)
+            .unwrap();
+
+        // Two documents in the first session, one in the second
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[0].states.len(), 3);
+        assert_eq!(batches[0].session_id, batches[1].session_id);
+        assert_ne!(batches[0].document_id, batches[1].document_id);
+        assert_ne!(batches[1].session_id, batches[2].session_id);
+        assert_eq!(index.len(), 6);
+
+        // Imported states are retrievable
+        let query = embedder.embed("second answer").unwrap();
+        let results = index.near(&query, 1).unwrap();
+        assert_eq!(results[0].id, batches[1].states[1].id);
+
+        let job = JobHandle::new();
+        let importer = TranscriptImporter::new().with_embedder(&embedder);
+        importer.import_with(&mut HatIndex::cosine(4), &transcripts, &job).unwrap();
+        assert_eq!((job.progress().done, job.progress().total), (2, 2));
+
+        job.cancel();
+        let result = importer.import_with(&mut HatIndex::cosine(4), &transcripts, &job);
+        assert!(matches!(result, Err(ImportError::Cancelled(0))));
+    }
+}
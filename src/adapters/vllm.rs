@@ -0,0 +1,341 @@
+//! # vLLM Prefix-Cache Interop
+//!
+//! Maps stored KV segments onto vLLM's automatic prefix caching model,
+//! so retrieved attention states can warm vLLM's cache instead of
+//! recomputing prefills.
+//!
+//! ## How vLLM Prefix Caching Works
+//!
+//! vLLM splits a sequence into fixed-size blocks of `block_size` tokens.
+//! Each *full* block is identified by a hash chained over its prefix:
+//!
+//! ```text
+//! hash(block_i) = H(hash(block_{i-1}), tokens[i*B..(i+1)*B])
+//! ```
+//!
+//! Two sequences sharing a prefix share the hashes of its blocks, so a
+//! cached block can be reused for any request that starts the same way.
+//! A block table maps each logical block of a sequence to a physical block.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let mut cache = PrefixCacheMap::new(16);
+//!
+//! // Register a retrieved attention state's KV under its token IDs
+//! cache.insert(&token_ids, &kv)?;
+//!
+//! // At request time: find the cached prefix and build a block table
+//! let table = cache.block_table(&request_tokens, |hash| allocator.block_for(hash));
+//! ```
+//!
+//! The hash function is pluggable via `PrefixHasher` so deployments can
+//! match the exact hash their vLLM version uses.
+
+use std::collections::HashMap;
+
+use super::attention::CompressedKV;
+
+/// Hash function for chaining prefix blocks
+pub trait PrefixHasher: Send + Sync {
+    /// Hash a block given its parent's hash (None for the first block)
+    fn hash_block(&self, parent: Option<u64>, tokens: &[u32]) -> u64;
+}
+
+/// FNV-1a over the parent hash and token IDs
+///
+/// Stable across processes and platforms (unlike std's SipHash with
+/// random keys), so hashes can be persisted alongside KV segments.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Fnv1aHasher;
+
+impl PrefixHasher for Fnv1aHasher {
+    fn hash_block(&self, parent: Option<u64>, tokens: &[u32]) -> u64 {
+        const OFFSET: u64 = 0xcbf29ce484222325;
+        const PRIME: u64 = 0x100000001b3;
+
+        let mut hash = OFFSET;
+        let mut feed = |bytes: &[u8]| {
+            for &b in bytes {
+                hash ^= b as u64;
+                hash = hash.wrapping_mul(PRIME);
+            }
+        };
+
+        match parent {
+            Some(p) => {
+                feed(&[1]);
+                feed(&p.to_le_bytes());
+            }
+            None => feed(&[0]),
+        }
+        for &t in tokens {
+            feed(&t.to_le_bytes());
+        }
+
+        hash
+    }
+}
+
+/// Compute the chained hashes of all full blocks in a token sequence
+///
+/// Trailing tokens that don't fill a block are not hashed (vLLM never
+/// caches partial blocks).
+pub fn prefix_block_hashes(hasher: &dyn PrefixHasher, token_ids: &[u32], block_size: usize) -> Vec<u64> {
+    assert!(block_size > 0, "Block size must be positive");
+
+    let mut hashes = Vec::with_capacity(token_ids.len() / block_size);
+    let mut parent = None;
+    for block in token_ids.chunks_exact(block_size) {
+        let hash = hasher.hash_block(parent, block);
+        hashes.push(hash);
+        parent = Some(hash);
+    }
+    hashes
+}
+
+/// A cached block of KV states
+#[derive(Debug, Clone)]
+pub struct KvBlock {
+    /// Chained prefix hash identifying this block
+    pub hash: u64,
+
+    /// Hash of the preceding block (None for the first block)
+    pub parent_hash: Option<u64>,
+
+    /// KV states for exactly `block_size` tokens
+    pub kv: CompressedKV,
+}
+
+/// Block table for a single sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockTable {
+    /// Physical block IDs for the cached prefix, in logical order
+    pub block_ids: Vec<u32>,
+
+    /// Hashes of the cached prefix blocks, in logical order
+    pub block_hashes: Vec<u64>,
+
+    /// Number of prefix tokens covered by cached blocks
+    pub num_cached_tokens: usize,
+}
+
+/// Errors for prefix-cache operations
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum PrefixCacheError {
+    /// KV cache covers a different number of tokens than provided
+    #[error("Sequence length mismatch: {tokens} tokens, KV covers {kv_seq_len}")]
+    SeqLenMismatch { tokens: usize, kv_seq_len: usize },
+
+    /// KV data can't be split into blocks (packed quantization or bad shape)
+    #[error("KV cache cannot be split into blocks")]
+    UnsplittableKv,
+}
+
+/// Map from prefix-block hashes to stored KV segments
+pub struct PrefixCacheMap {
+    /// Tokens per block (must match vLLM's `block_size`)
+    block_size: usize,
+
+    /// Hash function for prefix chaining
+    hasher: Box<dyn PrefixHasher>,
+
+    /// Cached blocks by hash
+    blocks: HashMap<u64, KvBlock>,
+}
+
+impl PrefixCacheMap {
+    /// Create a map with the default FNV-1a hasher
+    pub fn new(block_size: usize) -> Self {
+        Self::with_hasher(block_size, Box::new(Fnv1aHasher))
+    }
+
+    /// Create a map with a custom hasher
+    pub fn with_hasher(block_size: usize, hasher: Box<dyn PrefixHasher>) -> Self {
+        assert!(block_size > 0, "Block size must be positive");
+        Self {
+            block_size,
+            hasher,
+            blocks: HashMap::new(),
+        }
+    }
+
+    /// Tokens per block
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Compute block hashes for a token sequence
+    pub fn hashes(&self, token_ids: &[u32]) -> Vec<u64> {
+        prefix_block_hashes(self.hasher.as_ref(), token_ids, self.block_size)
+    }
+
+    /// Split a sequence's KV cache into blocks and register them
+    ///
+    /// `kv` must cover exactly `token_ids`. Returns the hashes of the
+    /// registered (full) blocks; the trailing partial block is dropped.
+    pub fn insert(&mut self, token_ids: &[u32], kv: &CompressedKV) -> Result<Vec<u64>, PrefixCacheError> {
+        if kv.seq_len as usize != token_ids.len() {
+            return Err(PrefixCacheError::SeqLenMismatch {
+                tokens: token_ids.len(),
+                kv_seq_len: kv.seq_len as usize,
+            });
+        }
+
+        let hashes = self.hashes(token_ids);
+        let mut parent = None;
+        for (i, &hash) in hashes.iter().enumerate() {
+            if !self.blocks.contains_key(&hash) {
+                let start = i * self.block_size;
+                let segment = kv
+                    .slice_seq(start, start + self.block_size)
+                    .ok_or(PrefixCacheError::UnsplittableKv)?;
+                self.blocks.insert(hash, KvBlock {
+                    hash,
+                    parent_hash: parent,
+                    kv: segment,
+                });
+            }
+            parent = Some(hash);
+        }
+
+        Ok(hashes)
+    }
+
+    /// Get a cached block by hash
+    pub fn get(&self, hash: u64) -> Option<&KvBlock> {
+        self.blocks.get(&hash)
+    }
+
+    /// Find the longest cached prefix of a token sequence
+    ///
+    /// Returns the cached blocks in logical order.
+    pub fn longest_prefix(&self, token_ids: &[u32]) -> Vec<&KvBlock> {
+        self.hashes(token_ids)
+            .into_iter()
+            .map_while(|hash| self.blocks.get(&hash))
+            .collect()
+    }
+
+    /// Build a block table for the cached prefix of a sequence
+    ///
+    /// `assign` maps each cached block hash to a physical block ID in the
+    /// serving engine (e.g., after uploading the block's KV to GPU memory).
+    pub fn block_table<F>(&self, token_ids: &[u32], mut assign: F) -> BlockTable
+    where
+        F: FnMut(&KvBlock) -> u32,
+    {
+        let prefix = self.longest_prefix(token_ids);
+
+        BlockTable {
+            block_ids: prefix.iter().map(|b| assign(b)).collect(),
+            block_hashes: prefix.iter().map(|b| b.hash).collect(),
+            num_cached_tokens: prefix.len() * self.block_size,
+        }
+    }
+
+    /// Remove a block
+    pub fn remove(&mut self, hash: u64) -> Option<KvBlock> {
+        self.blocks.remove(&hash)
+    }
+
+    /// Number of cached blocks
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Check if no blocks are cached
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Total KV bytes held
+    pub fn size_bytes(&self) -> usize {
+        self.blocks.values().map(|b| b.kv.size_bytes()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// KV with 1 layer, 1 head, head_dim 1, int8: one (k, v) byte pair per token
+    fn make_kv(seq_len: usize) -> CompressedKV {
+        CompressedKV {
+            model_id: "test".to_string(),
+            num_layers: 1,
+            num_heads: 1,
+            head_dim: 1,
+            seq_len: seq_len as u32,
+            quantization: "int8".to_string(),
+            data: (0..seq_len * 2).map(|i| i as u8).collect(),
+        }
+    }
+
+    #[test]
+    fn test_block_hashes_chain() {
+        let a = prefix_block_hashes(&Fnv1aHasher, &[1, 2, 3, 4, 5], 2);
+        let b = prefix_block_hashes(&Fnv1aHasher, &[1, 2, 9, 9], 2);
+
+        // Partial trailing block is not hashed
+        assert_eq!(a.len(), 2);
+        // Shared first block, diverging second block
+        assert_eq!(a[0], b[0]);
+        assert_ne!(a[1], b[1]);
+    }
+
+    #[test]
+    fn test_same_tokens_different_prefix() {
+        let a = prefix_block_hashes(&Fnv1aHasher, &[1, 2, 3, 4], 2);
+        let b = prefix_block_hashes(&Fnv1aHasher, &[5, 6, 3, 4], 2);
+
+        // Identical block contents hash differently under different prefixes
+        assert_ne!(a[1], b[1]);
+    }
+
+    #[test]
+    fn test_insert_and_lookup() {
+        let mut cache = PrefixCacheMap::new(2);
+        let tokens = [1, 2, 3, 4, 5];
+
+        let hashes = cache.insert(&tokens, &make_kv(5)).unwrap();
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(cache.len(), 2);
+
+        let block = cache.get(hashes[1]).unwrap();
+        assert_eq!(block.parent_hash, Some(hashes[0]));
+        assert_eq!(block.kv.seq_len, 2);
+        assert_eq!(block.kv.data, vec![4, 5, 6, 7]);
+
+        // Request sharing only the first block
+        let prefix = cache.longest_prefix(&[1, 2, 7, 7, 7]);
+        assert_eq!(prefix.len(), 1);
+    }
+
+    #[test]
+    fn test_block_table() {
+        let mut cache = PrefixCacheMap::new(2);
+        cache.insert(&[1, 2, 3, 4], &make_kv(4)).unwrap();
+
+        let mut next = 100;
+        let table = cache.block_table(&[1, 2, 3, 4, 5, 6], |_| {
+            next += 1;
+            next
+        });
+
+        assert_eq!(table.block_ids, vec![101, 102]);
+        assert_eq!(table.num_cached_tokens, 4);
+    }
+
+    #[test]
+    fn test_seq_len_mismatch() {
+        let mut cache = PrefixCacheMap::new(2);
+        let result = cache.insert(&[1, 2, 3], &make_kv(4));
+
+        assert_eq!(
+            result,
+            Err(PrefixCacheError::SeqLenMismatch { tokens: 3, kv_seq_len: 4 })
+        );
+    }
+}
@@ -9,71 +9,162 @@
 //! - Point = WHERE (position in space)
 //! - Blob = WHAT (the actual data)
 
+use std::fmt;
+use std::io;
+use std::sync::{Arc, OnceLock};
+
 /// Raw data attached to a point
 ///
 /// ARMS stores this opaquely. You define what it means.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// A blob is either held inline or deferred: a deferred blob knows its size
+/// and a loader for its bytes (a spill file, an object store), and calls it
+/// the first time the bytes are read.
+#[derive(Clone)]
 pub struct Blob {
-    data: Vec<u8>,
+    repr: Repr,
 }
 
+#[derive(Clone)]
+enum Repr {
+    Inline(Vec<u8>),
+    Deferred {
+        size: usize,
+        loader: BlobLoader,
+        loaded: Arc<OnceLock<Vec<u8>>>,
+    },
+}
+
+/// Fetches the bytes of a deferred blob
+pub type BlobLoader = Arc<dyn Fn() -> io::Result<Vec<u8>> + Send + Sync>;
+
 impl Blob {
     /// Create a new blob from bytes
     ///
     /// # Example
     /// ```
-    /// use arms::Blob;
+    /// use arms_hat::Blob;
     /// let blob = Blob::new(vec![1, 2, 3, 4]);
     /// assert_eq!(blob.size(), 4);
     /// ```
     pub fn new(data: Vec<u8>) -> Self {
-        Self { data }
+        Self { repr: Repr::Inline(data) }
+    }
+
+    /// Create a blob of `size` bytes that `loader` fetches on first access
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use arms_hat::Blob;
+    /// let blob = Blob::deferred(3, Arc::new(|| Ok(vec![1, 2, 3])));
+    /// assert!(!blob.is_loaded());
+    /// assert_eq!(blob.data(), &[1, 2, 3]);
+    /// ```
+    pub fn deferred(size: usize, loader: BlobLoader) -> Self {
+        Self {
+            repr: Repr::Deferred { size, loader, loaded: Arc::new(OnceLock::new()) },
+        }
     }
 
     /// Create an empty blob
     ///
     /// Useful when you only care about position, not payload.
     pub fn empty() -> Self {
-        Self { data: vec![] }
+        Self::new(vec![])
     }
 
     /// Create a blob from a string (UTF-8 bytes)
     ///
     /// # Example
     /// ```
-    /// use arms::Blob;
+    /// use arms_hat::Blob;
     /// let blob = Blob::from_str("hello");
     /// assert_eq!(blob.as_str(), Some("hello"));
     /// ```
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
-        Self {
-            data: s.as_bytes().to_vec(),
-        }
+        Self::new(s.as_bytes().to_vec())
     }
 
     /// Get the raw bytes
+    ///
+    /// A deferred blob whose loader fails reads as empty; use `try_data`
+    /// to see the error.
     pub fn data(&self) -> &[u8] {
-        &self.data
+        self.try_data().unwrap_or(&[])
     }
 
-    /// Get the size in bytes
+    /// Get the raw bytes, loading a deferred blob if needed
+    pub fn try_data(&self) -> io::Result<&[u8]> {
+        match &self.repr {
+            Repr::Inline(data) => Ok(data),
+            Repr::Deferred { loader, loaded, .. } => {
+                if let Some(data) = loaded.get() {
+                    return Ok(data);
+                }
+                let data = loader()?;
+                Ok(loaded.get_or_init(|| data))
+            }
+        }
+    }
+
+    /// Get the size in bytes (without loading a deferred blob)
     pub fn size(&self) -> usize {
-        self.data.len()
+        match &self.repr {
+            Repr::Inline(data) => data.len(),
+            Repr::Deferred { size, .. } => *size,
+        }
     }
 
     /// Check if the blob is empty
     pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+        self.size() == 0
+    }
+
+    /// Whether the bytes come from a loader rather than being held inline
+    pub fn is_deferred(&self) -> bool {
+        matches!(self.repr, Repr::Deferred { .. })
+    }
+
+    /// Whether the bytes are in memory
+    pub fn is_loaded(&self) -> bool {
+        match &self.repr {
+            Repr::Inline(_) => true,
+            Repr::Deferred { loaded, .. } => loaded.get().is_some(),
+        }
     }
 
     /// Try to interpret as UTF-8 string
     pub fn as_str(&self) -> Option<&str> {
-        std::str::from_utf8(&self.data).ok()
+        std::str::from_utf8(self.data()).ok()
     }
 
     /// Consume and return the inner data
     pub fn into_inner(self) -> Vec<u8> {
-        self.data
+        match self.repr {
+            Repr::Inline(data) => data,
+            Repr::Deferred { .. } => self.data().to_vec(),
+        }
+    }
+}
+
+impl PartialEq for Blob {
+    fn eq(&self, other: &Self) -> bool {
+        self.size() == other.size() && self.data() == other.data()
+    }
+}
+
+impl fmt::Debug for Blob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            Repr::Inline(data) => f.debug_struct("Blob").field("data", data).finish(),
+            Repr::Deferred { size, loaded, .. } => f
+                .debug_struct("Blob")
+                .field("size", size)
+                .field("loaded", &loaded.get().is_some())
+                .finish(),
+        }
     }
 }
 
@@ -143,6 +234,33 @@ mod tests {
         assert_eq!(blob3.as_str(), Some("test"));
     }
 
+    #[test]
+    fn test_blob_deferred_loads_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let blob = Blob::deferred(
+            5,
+            Arc::new(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(b"hello".to_vec())
+            }),
+        );
+        assert_eq!(blob.size(), 5);
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let copy = blob.clone();
+        assert_eq!(blob.as_str(), Some("hello"));
+        assert_eq!(copy.data(), b"hello");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(blob, Blob::from_str("hello"));
+
+        let missing = Blob::deferred(3, Arc::new(|| Err(io::ErrorKind::NotFound.into())));
+        assert!(missing.try_data().is_err());
+        assert!(missing.data().is_empty());
+    }
+
     #[test]
     fn test_blob_into_inner() {
         let blob = Blob::new(vec![1, 2, 3]);
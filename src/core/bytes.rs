@@ -0,0 +1,213 @@
+//! # Bytes
+//!
+//! Bounds-checked decoding shared by the on-disk formats that are parsed
+//! from a buffer: HAT snapshots and backups, attention states and framed
+//! batches, the WAL and journal, cluster metadata and HDF5 datasets.
+//! (Formats read from a stream go through `io::Read`.)
+//!
+//! All formats are little-endian regardless of the host, and fields are read
+//! by copying out of the buffer, so parsing never depends on alignment.
+//! Counts read from the data are checked against the bytes that remain
+//! before anything is allocated, so a corrupt or hostile header can't ask
+//! for gigabytes:
+//!
+//! ```
+//! use arms_hat::core::bytes::ByteReader;
+//!
+//! let data = [3, 0, 0, 0, 0, 0, 128, 63];
+//! let mut r = ByteReader::new(&data);
+//! let n = r.u32("count").unwrap();
+//! assert!(r.f32s(n as usize, "values").is_err()); // 3 floats need 12 bytes
+//! ```
+
+/// Decoding failure, with the field being read
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum ByteError {
+    /// Fewer bytes remain than the field needs
+    #[error("{what} truncated at byte {offset}: needs {needed} bytes, {remaining} left")]
+    Truncated { what: String, offset: usize, needed: u64, remaining: usize },
+
+    /// A string field isn't UTF-8
+    #[error("Invalid UTF-8 in {what} at byte {offset}")]
+    InvalidUtf8 { what: String, offset: usize },
+}
+
+/// Little-endian cursor over a byte slice
+#[derive(Debug, Clone)]
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    /// Bytes consumed so far
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    /// Bytes not yet consumed
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// The unconsumed bytes, without consuming them
+    pub fn rest(&self) -> &'a [u8] {
+        &self.data[self.offset..]
+    }
+
+    fn truncated(&self, what: &str, needed: u64) -> ByteError {
+        ByteError::Truncated {
+            what: what.to_string(),
+            offset: self.offset,
+            needed,
+            remaining: self.remaining(),
+        }
+    }
+
+    /// Take the next `len` bytes
+    pub fn bytes(&mut self, len: usize, what: &str) -> Result<&'a [u8], ByteError> {
+        if len > self.remaining() {
+            return Err(self.truncated(what, len as u64));
+        }
+        let slice = &self.data[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(slice)
+    }
+
+    /// Skip `len` bytes
+    pub fn skip(&mut self, len: usize, what: &str) -> Result<(), ByteError> {
+        self.bytes(len, what).map(|_| ())
+    }
+
+    pub fn array<const N: usize>(&mut self, what: &str) -> Result<[u8; N], ByteError> {
+        Ok(self.bytes(N, what)?.try_into().expect("slice of length N"))
+    }
+
+    pub fn u8(&mut self, what: &str) -> Result<u8, ByteError> {
+        Ok(self.array::<1>(what)?[0])
+    }
+
+    pub fn u16(&mut self, what: &str) -> Result<u16, ByteError> {
+        self.array(what).map(u16::from_le_bytes)
+    }
+
+    pub fn u32(&mut self, what: &str) -> Result<u32, ByteError> {
+        self.array(what).map(u32::from_le_bytes)
+    }
+
+    pub fn u64(&mut self, what: &str) -> Result<u64, ByteError> {
+        self.array(what).map(u64::from_le_bytes)
+    }
+
+    pub fn i64(&mut self, what: &str) -> Result<i64, ByteError> {
+        self.array(what).map(i64::from_le_bytes)
+    }
+
+    pub fn f32(&mut self, what: &str) -> Result<f32, ByteError> {
+        self.array(what).map(f32::from_le_bytes)
+    }
+
+    pub fn f64(&mut self, what: &str) -> Result<f64, ByteError> {
+        self.array(what).map(f64::from_le_bytes)
+    }
+
+    /// Take an unsigned integer `len` bytes wide (at most 8)
+    pub fn uint(&mut self, len: usize, what: &str) -> Result<u64, ByteError> {
+        assert!(len <= 8, "uint wider than 8 bytes");
+        let mut buf = [0u8; 8];
+        buf[..len].copy_from_slice(self.bytes(len, what)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    /// Check that `count` items of at least `min_size` bytes each could
+    /// still follow, so the count is safe to allocate for
+    pub fn count(&self, count: u64, min_size: usize, what: &str) -> Result<usize, ByteError> {
+        let needed = count.saturating_mul(min_size as u64);
+        if needed > self.remaining() as u64 {
+            return Err(self.truncated(what, needed));
+        }
+        Ok(count as usize)
+    }
+
+    /// Take `len` bytes given as a u64 (lengths written as u64 may not fit usize)
+    pub fn bytes_u64(&mut self, len: u64, what: &str) -> Result<&'a [u8], ByteError> {
+        let len = self.count(len, 1, what)?;
+        self.bytes(len, what)
+    }
+
+    /// Take `count` little-endian f32s
+    pub fn f32s(&mut self, count: usize, what: &str) -> Result<Vec<f32>, ByteError> {
+        let len = self.count(count as u64, 4, what)? * 4;
+        Ok(self
+            .bytes(len, what)?
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect())
+    }
+
+    /// Take `len` bytes as UTF-8
+    pub fn utf8(&mut self, len: usize, what: &str) -> Result<&'a str, ByteError> {
+        let offset = self.offset;
+        let bytes = self.bytes(len, what)?;
+        std::str::from_utf8(bytes).map_err(|_| ByteError::InvalidUtf8 { what: what.to_string(), offset })
+    }
+
+    /// Take a u32-length-prefixed UTF-8 string
+    pub fn str(&mut self, what: &str) -> Result<&'a str, ByteError> {
+        let len = self.u32(what)? as usize;
+        self.utf8(len, what)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_little_endian_at_any_offset() {
+        let mut data = vec![0xAA];
+        data.extend_from_slice(&0x0102_0304u32.to_le_bytes());
+        data.extend_from_slice(&1.5f32.to_le_bytes());
+        data.extend_from_slice(&(-7i64).to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(b"hi");
+
+        let mut r = ByteReader::new(&data);
+        assert_eq!(r.u8("tag").unwrap(), 0xAA);
+        assert_eq!(r.u32("word").unwrap(), 0x0102_0304);
+        assert_eq!(r.f32("float").unwrap(), 1.5);
+        assert_eq!(r.i64("int").unwrap(), -7);
+        assert_eq!(r.str("name").unwrap(), "hi");
+        assert!(r.is_empty());
+
+        // HDF5 offsets are 2, 4 or 8 bytes wide
+        let mut r = ByteReader::new(&[0x34, 0x12, 0xFF]);
+        assert_eq!(r.uint(2, "offset").unwrap(), 0x1234);
+        assert!(r.uint(2, "offset").is_err());
+    }
+
+    #[test]
+    fn test_rejects_truncation_and_huge_counts() {
+        let mut r = ByteReader::new(&[1, 2, 3]);
+        let err = r.u32("length").unwrap_err();
+        assert_eq!(err, ByteError::Truncated { what: "length".into(), offset: 0, needed: 4, remaining: 3 });
+        assert_eq!(r.position(), 0);
+
+        assert!(r.count(u64::MAX, 16, "ids").is_err());
+        assert!(r.bytes_u64(u64::MAX, "payload").is_err());
+        assert!(r.f32s(usize::MAX / 2, "centroid").is_err());
+        assert_eq!(r.count(3, 1, "bytes").unwrap(), 3);
+
+        let mut bad = ByteReader::new(&[1, 0, 0, 0, 0xFF]);
+        assert!(matches!(bad.str("text"), Err(ByteError::InvalidUtf8 { offset: 4, .. })));
+    }
+}
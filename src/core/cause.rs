@@ -0,0 +1,112 @@
+//! # Error Causes
+//!
+//! Error variants such as `PlaceError::StorageError` or
+//! `PersistError::Corrupted` wrap whatever went wrong underneath: an
+//! `io::Error`, a backend's own error, a `ByteError`, or just a message.
+//! `Cause` holds it behind an `Arc` so the enums stay `Clone`, and keeps
+//! the original type reachable through `downcast_ref` and `source()`.
+//!
+//! ```rust,ignore
+//! let e = PlaceError::StorageError(Cause::new(io_error));
+//! if let PlaceError::StorageError(cause) = &e {
+//!     let kind = cause.downcast_ref::<io::Error>().map(|e| e.kind());
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+/// The underlying error of a variant (or a plain message)
+#[derive(Clone)]
+pub struct Cause(Arc<dyn Error + Send + Sync + 'static>);
+
+/// A cause that is only a message
+#[derive(Debug)]
+struct Message(String);
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for Message {}
+
+impl Cause {
+    /// Wrap an error
+    pub fn new(error: impl Error + Send + Sync + 'static) -> Self {
+        Self(Arc::new(error))
+    }
+
+    /// A cause with only a message
+    pub fn msg(message: impl Into<String>) -> Self {
+        Self(Arc::new(Message(message.into())))
+    }
+
+    /// The wrapped error as `E`, if that is its type
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.0.downcast_ref::<E>()
+    }
+
+    /// True if the cause is only a message
+    pub fn is_message(&self) -> bool {
+        self.0.is::<Message>()
+    }
+}
+
+impl fmt::Debug for Cause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for Cause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Transparent: the chain continues with the wrapped error's own source
+impl Error for Cause {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+/// Causes compare by message (the wrapped types needn't be `PartialEq`)
+impl PartialEq for Cause {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.to_string() == other.to_string()
+    }
+}
+
+impl From<String> for Cause {
+    fn from(message: String) -> Self {
+        Self::msg(message)
+    }
+}
+
+impl From<&str> for Cause {
+    fn from(message: &str) -> Self {
+        Self::msg(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_cause_keeps_type() {
+        let cause = Cause::new(io::Error::new(io::ErrorKind::PermissionDenied, "denied"));
+        assert_eq!(cause.to_string(), "denied");
+        assert_eq!(cause.downcast_ref::<io::Error>().map(|e| e.kind()), Some(io::ErrorKind::PermissionDenied));
+        assert!(!cause.is_message());
+
+        let message = Cause::from("truncated header");
+        assert!(message.is_message() && message.downcast_ref::<io::Error>().is_none());
+        assert_eq!(message, Cause::msg("truncated header".to_string()));
+    }
+}
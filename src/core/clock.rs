@@ -0,0 +1,60 @@
+//! # Clock
+//!
+//! Wall-clock time for timestamps and temporal scoring.
+//!
+//! `std::time::SystemTime::now()` panics on `wasm32-unknown-unknown`, which
+//! has no clock of its own. There the time comes from the host: the JS glue
+//! must provide `now_ms` in the `arms` import module (typically `Date.now`).
+
+/// Milliseconds since the Unix epoch
+pub fn now_ms() -> u64 {
+    imp::now_micros() / 1000
+}
+
+/// Microseconds since the Unix epoch
+pub fn now_micros() -> u64 {
+    imp::now_micros()
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+mod imp {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    pub fn now_micros() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros() as u64
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod imp {
+    #[link(wasm_import_module = "arms")]
+    extern "C" {
+        /// Host clock in (fractional) milliseconds since the Unix epoch
+        fn now_ms() -> f64;
+    }
+
+    pub fn now_micros() -> u64 {
+        // SAFETY: host import with no arguments and no memory access
+        (unsafe { now_ms() } * 1000.0) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn test_now_matches_system_time() {
+        let system = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        assert!(now_ms().abs_diff(system) < 1000);
+        assert!(now_micros() / 1000 >= now_ms() - 1);
+    }
+}
@@ -0,0 +1,321 @@
+//! # Clustering
+//!
+//! Mini-batch k-means for discovering recurring topics among points.
+//!
+//! Centers start from k-means++ seeding over a sample, then move toward
+//! random mini-batches with a per-center learning rate that shrinks as the
+//! center absorbs points (Sculley, "Web-Scale K-Means Clustering"). A final
+//! pass assigns every point to its nearest center:
+//!
+//! ```text
+//! sample ──k-means++──▶ k centers ──mini-batches──▶ centers ──assign all──▶ clusters
+//! ```
+//!
+//! Distances come from the space's `Proximity` (via `ScoreOrder::to_distance`),
+//! so clusters follow the same notion of relatedness as queries.
+//!
+//! ```rust,ignore
+//! let clustering = KMeans::new(8).with_seed(7).fit(index.iter(), proximity);
+//! for cluster in clustering.clusters() {
+//!     println!("topic {}: {} points, e.g. {}", cluster.label, cluster.size, cluster.representative);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::gen::Rng;
+use super::proximity::Proximity;
+use super::{Id, Point};
+
+/// Points sampled for k-means++ seeding, at most
+const SEED_SAMPLE: usize = 4096;
+
+/// Mini-batch k-means settings
+#[derive(Debug, Clone)]
+pub struct KMeans {
+    /// Number of clusters (fewer if there are fewer points)
+    pub k: usize,
+
+    /// Points drawn per iteration
+    pub batch_size: usize,
+
+    /// Mini-batch iterations
+    pub iterations: usize,
+
+    /// Seed for sampling and initialization
+    pub seed: u64,
+}
+
+impl KMeans {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            batch_size: 256,
+            iterations: 100,
+            seed: 0,
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Cluster `points`, measuring distance with `proximity`
+    pub fn fit<'a>(
+        &self,
+        points: impl IntoIterator<Item = (Id, &'a Point)>,
+        proximity: Arc<dyn Proximity>,
+    ) -> Clustering {
+        let points: Vec<(Id, &Point)> = points.into_iter().collect();
+        let k = self.k.min(points.len());
+        if k == 0 {
+            return Clustering::new(Vec::new(), HashMap::new(), proximity);
+        }
+
+        let distance = |a: &Point, b: &Point| proximity.order().to_distance(proximity.proximity(a, b));
+        let nearest = |centers: &[Point], p: &Point| {
+            centers
+                .iter()
+                .enumerate()
+                .map(|(i, c)| (i, distance(p, c)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i)
+                .unwrap_or(0)
+        };
+
+        let mut rng = Rng::new(self.seed);
+        let mut centers = self.seed_centers(&points, k, &mut rng, &distance);
+
+        // Mini-batches: move each center toward its points, slower as it grows
+        let mut counts = vec![0usize; k];
+        for _ in 0..self.iterations {
+            for _ in 0..self.batch_size {
+                let point = points[rng.below(points.len())].1;
+                let i = nearest(&centers, point);
+                counts[i] += 1;
+                let rate = 1.0 / counts[i] as f32;
+                let moved = centers[i]
+                    .dims()
+                    .iter()
+                    .zip(point.dims())
+                    .map(|(c, x)| c + rate * (x - c))
+                    .collect();
+                centers[i] = Point::new(moved);
+            }
+        }
+
+        // Assign everything, then keep non-empty clusters, largest first
+        let mut members: Vec<Vec<(Id, f32)>> = vec![Vec::new(); k];
+        for (id, point) in &points {
+            let i = nearest(&centers, point);
+            members[i].push((*id, distance(point, &centers[i])));
+        }
+        let mut found: Vec<(Point, Vec<(Id, f32)>)> = centers
+            .into_iter()
+            .zip(members)
+            .filter(|(_, m)| !m.is_empty())
+            .collect();
+        found.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.1[0].0.cmp(&b.1[0].0)));
+
+        let mut clusters = Vec::with_capacity(found.len());
+        let mut assignments = HashMap::with_capacity(points.len());
+        for (label, (centroid, members)) in found.into_iter().enumerate() {
+            let representative = members
+                .iter()
+                .min_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)))
+                .map(|(id, _)| *id)
+                .expect("clusters are non-empty");
+            assignments.extend(members.iter().map(|(id, _)| (*id, label)));
+            clusters.push(Cluster {
+                label,
+                centroid,
+                size: members.len(),
+                representative,
+            });
+        }
+        Clustering::new(clusters, assignments, proximity)
+    }
+
+    /// k-means++: spread initial centers by sampling far-away points
+    fn seed_centers(
+        &self,
+        points: &[(Id, &Point)],
+        k: usize,
+        rng: &mut Rng,
+        distance: &impl Fn(&Point, &Point) -> f32,
+    ) -> Vec<Point> {
+        let sample: Vec<&Point> = if points.len() <= SEED_SAMPLE {
+            points.iter().map(|(_, p)| *p).collect()
+        } else {
+            (0..SEED_SAMPLE).map(|_| points[rng.below(points.len())].1).collect()
+        };
+
+        let mut centers = vec![sample[rng.below(sample.len())].clone()];
+        let mut nearest: Vec<f32> = sample.iter().map(|p| distance(p, &centers[0]).max(0.0)).collect();
+        while centers.len() < k {
+            let total: f32 = nearest.iter().map(|d| d * d).sum();
+            let next = if total > 0.0 {
+                // Pick proportionally to squared distance from the closest center
+                let mut target = rng.next_f32() * total;
+                nearest
+                    .iter()
+                    .position(|d| {
+                        target -= d * d;
+                        target <= 0.0
+                    })
+                    .unwrap_or(sample.len() - 1)
+            } else {
+                rng.below(sample.len())
+            };
+            let center = sample[next].clone();
+            for (d, p) in nearest.iter_mut().zip(&sample) {
+                *d = d.min(distance(p, &center).max(0.0));
+            }
+            centers.push(center);
+        }
+        centers
+    }
+}
+
+/// A group of related points
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    /// Position in `Clustering::clusters` (0 = largest)
+    pub label: usize,
+
+    /// Center of the cluster
+    pub centroid: Point,
+
+    /// Number of member points
+    pub size: usize,
+
+    /// Member closest to the centroid
+    pub representative: Id,
+}
+
+/// Result of clustering a set of points
+#[derive(Clone)]
+pub struct Clustering {
+    clusters: Vec<Cluster>,
+    assignments: HashMap<Id, usize>,
+    proximity: Arc<dyn Proximity>,
+}
+
+impl Clustering {
+    fn new(clusters: Vec<Cluster>, assignments: HashMap<Id, usize>, proximity: Arc<dyn Proximity>) -> Self {
+        Self {
+            clusters,
+            assignments,
+            proximity,
+        }
+    }
+
+    /// Clusters, largest first
+    pub fn clusters(&self) -> &[Cluster] {
+        &self.clusters
+    }
+
+    pub fn len(&self) -> usize {
+        self.clusters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clusters.is_empty()
+    }
+
+    /// Label of the cluster a clustered point belongs to
+    pub fn label_of(&self, id: Id) -> Option<usize> {
+        self.assignments.get(&id).copied()
+    }
+
+    /// The cluster a clustered point belongs to
+    pub fn cluster_of(&self, id: Id) -> Option<&Cluster> {
+        self.clusters.get(self.label_of(id)?)
+    }
+
+    /// Members of a cluster, in ID order
+    pub fn members(&self, label: usize) -> Vec<Id> {
+        let mut ids: Vec<Id> = self
+            .assignments
+            .iter()
+            .filter(|(_, l)| **l == label)
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// The cluster whose centroid is most related to `point` (for points
+    /// added after clustering)
+    pub fn nearest(&self, point: &Point) -> Option<&Cluster> {
+        let order = self.proximity.order();
+        self.clusters.iter().min_by(|a, b| {
+            let a = order.to_distance(self.proximity.proximity(point, &a.centroid));
+            let b = order.to_distance(self.proximity.proximity(point, &b.centroid));
+            a.total_cmp(&b)
+        })
+    }
+}
+
+impl std::fmt::Debug for Clustering {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Clustering")
+            .field("clusters", &self.clusters)
+            .field("points", &self.assignments.len())
+            .field("proximity", &self.proximity.describe())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::gen::Clusters;
+    use crate::core::proximity::Cosine;
+
+    #[test]
+    fn test_kmeans_recovers_clusters() {
+        let generated = Clusters::new(16, 3).with_seed(5).generate(300);
+        let ids: Vec<Id> = (0..300u128).map(|n| Id::from_bytes(n.to_le_bytes())).collect();
+
+        let clustering = KMeans::new(3)
+            .with_seed(1)
+            .fit(ids.iter().copied().zip(generated.iter().map(|(_, p)| p)), Arc::new(Cosine));
+        assert_eq!(clustering.len(), 3);
+        assert_eq!(clustering.clusters().iter().map(|c| c.size).sum::<usize>(), 300);
+        assert!(clustering.clusters().windows(2).all(|w| w[0].size >= w[1].size));
+
+        // Points generated around the same center land together
+        for (a, (truth, _)) in generated.iter().enumerate() {
+            for (b, (other, _)) in generated.iter().enumerate().take(3) {
+                assert_eq!(truth == other, clustering.label_of(ids[a]) == clustering.label_of(ids[b]));
+            }
+        }
+
+        let first = &clustering.clusters()[0];
+        assert_eq!(clustering.label_of(first.representative), Some(0));
+        assert_eq!(clustering.members(0).len(), first.size);
+        assert_eq!(clustering.nearest(&first.centroid).map(|c| c.label), Some(0));
+    }
+
+    #[test]
+    fn test_kmeans_caps_k() {
+        let points = [Point::new(vec![1.0, 0.0]), Point::new(vec![1.0, 0.0])];
+        let ids = [Id::from_bytes([1; 16]), Id::from_bytes([2; 16])];
+        let clustering = KMeans::new(5).fit(ids.iter().copied().zip(&points), Arc::new(Cosine));
+        assert_eq!(clustering.clusters().iter().map(|c| c.size).sum::<usize>(), 2);
+        assert!(KMeans::new(5).fit(std::iter::empty(), Arc::new(Cosine)).is_empty());
+    }
+}
@@ -10,8 +10,9 @@
 //!
 //! "If we say it's a rock now, in 2 years it can never be carved into a wheel."
 
-use super::proximity::{Cosine, Proximity};
+use super::proximity::{Cosine, Proximity, ScoreOrder, WeightedCosine, WeightedEuclidean};
 use super::merge::{Mean, Merge};
+use super::Point;
 use std::sync::Arc;
 
 /// Main ARMS configuration
@@ -34,8 +35,21 @@ pub struct ArmsConfig {
     /// Whether to normalize points on insertion
     pub normalize_on_insert: bool,
 
+    /// What to do with NaN/infinite coordinates in points and queries
+    pub non_finite: NonFinitePolicy,
+
     /// Tier configuration
     pub tiers: TierConfig,
+
+    /// Index only a prefix of each vector and rescore from storage
+    /// (None = index full vectors)
+    pub matryoshka: Option<MatryoshkaConfig>,
+
+    /// Seed for reproducible runs (None = wall-clock IDs)
+    ///
+    /// When set, placed points get `SeededIds` instead of timestamped IDs,
+    /// so replaying the same inserts yields the same IDs and results.
+    pub deterministic: Option<u64>,
 }
 
 impl ArmsConfig {
@@ -48,7 +62,10 @@ impl ArmsConfig {
             proximity: Arc::new(Cosine),
             merge: Arc::new(Mean),
             normalize_on_insert: true,
+            non_finite: NonFinitePolicy::default(),
             tiers: TierConfig::default(),
+            matryoshka: None,
+            deterministic: None,
         }
     }
 
@@ -58,6 +75,22 @@ impl ArmsConfig {
         self
     }
 
+    /// Weight each dimension in proximity calculations
+    ///
+    /// Replaces the proximity with its weighted counterpart:
+    /// `WeightedEuclidean` for distances, `WeightedCosine` otherwise.
+    ///
+    /// # Panics
+    /// If `weights` doesn't have one entry per dimension.
+    pub fn with_dimension_weights(mut self, weights: Vec<f32>) -> Self {
+        assert_eq!(weights.len(), self.dimensionality, "Weights must match dimensionality");
+        self.proximity = match self.proximity.order() {
+            ScoreOrder::LowerIsBetter => Arc::new(WeightedEuclidean::new(weights)),
+            ScoreOrder::HigherIsBetter => Arc::new(WeightedCosine::new(weights)),
+        };
+        self
+    }
+
     /// Set a custom merge function
     pub fn with_merge<M: Merge + 'static>(mut self, merge: M) -> Self {
         self.merge = Arc::new(merge);
@@ -70,11 +103,106 @@ impl ArmsConfig {
         self
     }
 
+    /// Set how NaN/infinite coordinates are handled
+    pub fn with_non_finite(mut self, policy: NonFinitePolicy) -> Self {
+        self.non_finite = policy;
+        self
+    }
+
     /// Set tier configuration
     pub fn with_tiers(mut self, tiers: TierConfig) -> Self {
         self.tiers = tiers;
         self
     }
+
+    /// Index the first `prefix_dims` dimensions only (Matryoshka embeddings)
+    ///
+    /// Searches fetch `k * oversample` candidates from the prefix index and
+    /// rescore them with the full vectors from storage.
+    ///
+    /// # Panics
+    /// If `prefix_dims` is zero or larger than the dimensionality.
+    pub fn with_matryoshka(mut self, prefix_dims: usize, oversample: usize) -> Self {
+        assert!(
+            prefix_dims > 0 && prefix_dims <= self.dimensionality,
+            "Prefix must be between 1 and the dimensionality"
+        );
+        self.matryoshka = Some(MatryoshkaConfig::new(prefix_dims, oversample));
+        self
+    }
+
+    /// Make runs reproducible: seeded IDs instead of wall-clock ones
+    pub fn with_deterministic(mut self, seed: u64) -> Self {
+        self.deterministic = Some(seed);
+        self
+    }
+
+    /// Whether runs are reproducible (see `with_deterministic`)
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic.is_some()
+    }
+
+    /// Dimensionality of the vectors handed to the index
+    pub fn index_dimensionality(&self) -> usize {
+        self.matryoshka
+            .as_ref()
+            .map_or(self.dimensionality, |m| m.prefix_dims)
+    }
+}
+
+/// Handling of NaN and infinite coordinates
+///
+/// A single NaN makes every cosine score against the point NaN, which then
+/// sorts arbitrarily; checking at the engine boundary keeps it out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinitePolicy {
+    /// Fail with a `NonFinite` error
+    #[default]
+    Reject,
+
+    /// Replace NaN and ±∞ with 0.0
+    Sanitize,
+
+    /// Pass through unchecked
+    Allow,
+}
+
+impl NonFinitePolicy {
+    /// Apply to a point: the point to use, or the first non-finite dimension if rejected
+    pub fn apply(self, point: Point) -> Result<Point, usize> {
+        if self == NonFinitePolicy::Allow {
+            return Ok(point);
+        }
+        match point.dims().iter().position(|x| !x.is_finite()) {
+            None => Ok(point),
+            Some(index) if self == NonFinitePolicy::Reject => Err(index),
+            Some(_) => Ok(Point::new(
+                point.dims().iter().map(|&x| if x.is_finite() { x } else { 0.0 }).collect(),
+            )),
+        }
+    }
+}
+
+/// Prefix indexing for Matryoshka (MRL-trained) embeddings
+///
+/// The index holds `prefix_dims` leading dimensions per point, cutting its
+/// memory and scan cost; full vectors stay in storage for rescoring.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatryoshkaConfig {
+    /// Leading dimensions indexed (e.g., 256 of 1536)
+    pub prefix_dims: usize,
+
+    /// Candidates fetched per requested result before rescoring
+    pub oversample: usize,
+}
+
+impl MatryoshkaConfig {
+    pub fn new(prefix_dims: usize, oversample: usize) -> Self {
+        Self {
+            prefix_dims,
+            oversample: oversample.max(1),
+        }
+    }
 }
 
 impl Default for ArmsConfig {
@@ -174,4 +302,27 @@ mod tests {
         assert_eq!(tiers.hot_capacity, 1024 * 1024);
         assert_eq!(tiers.evict_after_ms, 60 * 1000);
     }
+
+    #[test]
+    fn test_dimension_weights() {
+        let config = ArmsConfig::new(3).with_dimension_weights(vec![1.0, 1.0, 0.0]);
+        assert_eq!(config.proximity.name(), "weighted_cosine");
+
+        let config = ArmsConfig::new(3)
+            .with_proximity(Euclidean)
+            .with_dimension_weights(vec![1.0, 0.5, 0.0]);
+        assert_eq!(config.proximity.name(), "weighted_euclidean");
+    }
+
+    #[test]
+    fn test_non_finite_policy() {
+        let bad = Point::new(vec![1.0, f32::NAN, f32::NEG_INFINITY]);
+
+        assert_eq!(NonFinitePolicy::Reject.apply(bad.clone()), Err(1));
+        assert_eq!(NonFinitePolicy::Sanitize.apply(bad.clone()).unwrap().dims(), &[1.0, 0.0, 0.0]);
+        assert!(NonFinitePolicy::Allow.apply(bad).unwrap().dims()[1].is_nan());
+
+        let good = Point::new(vec![1.0, 2.0]);
+        assert_eq!(NonFinitePolicy::Reject.apply(good.clone()), Ok(good));
+    }
 }
@@ -0,0 +1,73 @@
+//! # Facts
+//!
+//! Structured statements extracted from memory: "user / prefers / tea".
+//!
+//! Vectors remember what was said (episodic memory); facts hold what the
+//! caller concluded from it (semantic memory). A fact is keyed by subject
+//! and predicate, so a newer extraction replaces the old value instead of
+//! piling up beside it, and it can point back at the chunk it came from.
+
+use super::{Id, MetaValue};
+
+/// A (subject, predicate, value) statement with its confidence and source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fact {
+    pub subject: String,
+    pub predicate: String,
+    pub value: MetaValue,
+
+    /// How sure the extractor was, 0.0 - 1.0
+    pub confidence: f32,
+
+    /// Chunk the fact was extracted from (None = not tied to one)
+    pub source: Option<Id>,
+
+    /// When the fact was last written (ms since epoch; set on upsert)
+    pub updated_ms: u64,
+}
+
+impl Fact {
+    /// A fact held with full confidence and no source
+    pub fn new(subject: impl Into<String>, predicate: impl Into<String>, value: impl Into<MetaValue>) -> Self {
+        Self {
+            subject: subject.into(),
+            predicate: predicate.into(),
+            value: value.into(),
+            confidence: 1.0,
+            source: None,
+            updated_ms: 0,
+        }
+    }
+
+    /// Confidence, clamped to 0.0 - 1.0 (NaN counts as 0)
+    pub fn with_confidence(mut self, confidence: f32) -> Self {
+        self.confidence = if confidence.is_nan() { 0.0 } else { confidence.clamp(0.0, 1.0) };
+        self
+    }
+
+    /// Chunk the fact was extracted from
+    pub fn with_source(mut self, source: Id) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Subject and predicate, which identify the fact
+    pub fn key(&self) -> (String, String) {
+        (self.subject.clone(), self.predicate.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fact_builder() {
+        let fact = Fact::new("user", "prefers", "tea").with_confidence(1.7).with_source(Id::seeded(1, 2));
+        assert_eq!(fact.value, MetaValue::Str("tea".into()));
+        assert_eq!(fact.confidence, 1.0);
+        assert_eq!(fact.source, Some(Id::seeded(1, 2)));
+        assert_eq!(fact.key(), ("user".to_string(), "prefers".to_string()));
+        assert_eq!(Fact::new("a", "b", 3i64).with_confidence(f32::NAN).confidence, 0.0);
+    }
+}
@@ -0,0 +1,389 @@
+//! # Synthetic Data
+//!
+//! Seeded generators for tests, benches and demos.
+//!
+//! Generators over assets: instead of checking in embedding dumps, describe
+//! the shape of the data and generate it. Every generator is deterministic
+//! for a given seed, so a failing test reproduces exactly.
+//!
+//! - `Clusters` - points scattered around random centers
+//! - `DriftingSessions` - sessions of documents whose topic drifts chunk by chunk
+//! - `Conversation` - alternating user/assistant turns that stay on a topic
+//!   for a while, then shift (`AttentionBatch::from_turns` turns them into
+//!   attention states)
+//!
+//! ```rust,ignore
+//! let points = Clusters::new(128, 8).with_seed(7).generate(1000);
+//! for session in DriftingSessions::new(128).generate() {
+//!     index.new_session();
+//!     for document in session.documents { /* ... */ }
+//! }
+//! ```
+
+use super::Point;
+
+/// Small deterministic PRNG (SplitMix64)
+///
+/// Not cryptographic; good enough to scatter points.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform in [0, n)
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Uniform sample of up to `n` items in one pass (reservoir sampling)
+    pub fn reservoir<T>(&mut self, items: impl IntoIterator<Item = T>, n: usize) -> Vec<T> {
+        let mut reservoir = Vec::new();
+        for (seen, item) in items.into_iter().enumerate() {
+            if seen < n {
+                reservoir.push(item);
+            } else {
+                // Keep the seen-th item with probability n / (seen + 1)
+                let slot = self.below(seen + 1);
+                if slot < n {
+                    reservoir[slot] = item;
+                }
+            }
+        }
+        reservoir
+    }
+
+    /// Standard normal sample (Box-Muller)
+    pub fn normal(&mut self) -> f32 {
+        let u1 = self.next_f32().max(f32::MIN_POSITIVE);
+        let u2 = self.next_f32();
+        (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos()
+    }
+
+    /// Random direction on the unit sphere
+    pub fn unit_point(&mut self, dimensionality: usize) -> Point {
+        let dims = (0..dimensionality).map(|_| self.normal()).collect();
+        Point::new(dims).normalize()
+    }
+
+    /// `center` plus Gaussian noise of standard deviation `spread`, normalized
+    pub fn around(&mut self, center: &Point, spread: f32) -> Point {
+        let dims = center
+            .dims()
+            .iter()
+            .map(|&x| x + spread * self.normal())
+            .collect();
+        Point::new(dims).normalize()
+    }
+}
+
+/// Points scattered around random centers
+#[derive(Debug, Clone)]
+pub struct Clusters {
+    pub dimensionality: usize,
+    pub clusters: usize,
+
+    /// Noise around each center (standard deviation per dimension)
+    pub spread: f32,
+
+    pub seed: u64,
+}
+
+impl Clusters {
+    pub fn new(dimensionality: usize, clusters: usize) -> Self {
+        Self {
+            dimensionality,
+            clusters: clusters.max(1),
+            spread: 0.1,
+            seed: 0,
+        }
+    }
+
+    pub fn with_spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The cluster centers (unit vectors)
+    pub fn centers(&self) -> Vec<Point> {
+        let mut rng = Rng::new(self.seed);
+        (0..self.clusters)
+            .map(|_| rng.unit_point(self.dimensionality))
+            .collect()
+    }
+
+    /// `n` points with their cluster labels, assigned round-robin
+    pub fn generate(&self, n: usize) -> Vec<(usize, Point)> {
+        let centers = self.centers();
+        let mut rng = Rng::new(self.seed ^ 0xC1u64.rotate_left(56));
+        (0..n)
+            .map(|i| {
+                let label = i % centers.len();
+                (label, rng.around(&centers[label], self.spread))
+            })
+            .collect()
+    }
+}
+
+/// One generated session
+#[derive(Debug, Clone)]
+pub struct SyntheticSession {
+    /// Chunk points of each document, in order
+    pub documents: Vec<Vec<Point>>,
+}
+
+/// Sessions whose topic drifts a little with every chunk
+///
+/// Each session starts from a fresh topic; each document continues where
+/// the previous one left off, so documents of one session stay related.
+#[derive(Debug, Clone)]
+pub struct DriftingSessions {
+    pub dimensionality: usize,
+    pub sessions: usize,
+    pub documents_per_session: usize,
+    pub chunks_per_document: usize,
+
+    /// How far the topic moves per chunk
+    pub drift: f32,
+
+    /// Noise of each chunk around the current topic
+    pub noise: f32,
+
+    pub seed: u64,
+}
+
+impl DriftingSessions {
+    pub fn new(dimensionality: usize) -> Self {
+        Self {
+            dimensionality,
+            sessions: 4,
+            documents_per_session: 4,
+            chunks_per_document: 8,
+            drift: 0.05,
+            noise: 0.05,
+            seed: 0,
+        }
+    }
+
+    pub fn with_shape(mut self, sessions: usize, documents_per_session: usize, chunks_per_document: usize) -> Self {
+        self.sessions = sessions;
+        self.documents_per_session = documents_per_session;
+        self.chunks_per_document = chunks_per_document;
+        self
+    }
+
+    pub fn with_drift(mut self, drift: f32) -> Self {
+        self.drift = drift;
+        self
+    }
+
+    pub fn with_noise(mut self, noise: f32) -> Self {
+        self.noise = noise;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn generate(&self) -> Vec<SyntheticSession> {
+        let mut rng = Rng::new(self.seed);
+        (0..self.sessions)
+            .map(|_| {
+                let mut topic = rng.unit_point(self.dimensionality);
+                let documents = (0..self.documents_per_session)
+                    .map(|_| {
+                        (0..self.chunks_per_document)
+                            .map(|_| {
+                                topic = rng.around(&topic, self.drift);
+                                rng.around(&topic, self.noise)
+                            })
+                            .collect()
+                    })
+                    .collect();
+                SyntheticSession { documents }
+            })
+            .collect()
+    }
+}
+
+/// Who spoke a turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Speaker {
+    User,
+    Assistant,
+}
+
+/// One generated conversation turn
+#[derive(Debug, Clone)]
+pub struct Turn {
+    pub speaker: Speaker,
+
+    /// Index of the topic this turn is about
+    pub topic: usize,
+
+    pub text: String,
+    pub point: Point,
+    pub timestamp_ms: u64,
+}
+
+/// Alternating user/assistant turns that stick to a topic, then shift
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    pub dimensionality: usize,
+    pub turns: usize,
+    pub topics: usize,
+
+    /// Chance a user turn moves to another topic
+    pub topic_shift: f32,
+
+    /// Noise of each turn around its topic
+    pub noise: f32,
+
+    /// Timestamp of the first turn
+    pub start_ms: u64,
+
+    /// Time between turns
+    pub turn_gap_ms: u64,
+
+    pub seed: u64,
+}
+
+impl Conversation {
+    pub fn new(dimensionality: usize, turns: usize) -> Self {
+        Self {
+            dimensionality,
+            turns,
+            topics: 4,
+            topic_shift: 0.2,
+            noise: 0.1,
+            start_ms: 0,
+            turn_gap_ms: 1_000,
+            seed: 0,
+        }
+    }
+
+    pub fn with_topics(mut self, topics: usize, topic_shift: f32) -> Self {
+        self.topics = topics.max(1);
+        self.topic_shift = topic_shift;
+        self
+    }
+
+    pub fn with_noise(mut self, noise: f32) -> Self {
+        self.noise = noise;
+        self
+    }
+
+    pub fn with_timing(mut self, start_ms: u64, turn_gap_ms: u64) -> Self {
+        self.start_ms = start_ms;
+        self.turn_gap_ms = turn_gap_ms;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn generate(&self) -> Vec<Turn> {
+        let mut rng = Rng::new(self.seed);
+        let topics: Vec<Point> = (0..self.topics)
+            .map(|_| rng.unit_point(self.dimensionality))
+            .collect();
+
+        let mut topic = 0;
+        (0..self.turns)
+            .map(|i| {
+                let speaker = if i % 2 == 0 { Speaker::User } else { Speaker::Assistant };
+                if speaker == Speaker::User && i > 0 && rng.next_f32() < self.topic_shift {
+                    topic = (topic + 1 + rng.below(topics.len() - 1)) % topics.len();
+                }
+                let text = match speaker {
+                    Speaker::User => format!("Question {} about topic {}", i / 2, topic),
+                    Speaker::Assistant => format!("Answer {} about topic {}", i / 2, topic),
+                };
+                Turn {
+                    speaker,
+                    topic,
+                    text,
+                    point: rng.around(&topics[topic], self.noise),
+                    timestamp_ms: self.start_ms + i as u64 * self.turn_gap_ms,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::proximity::{Cosine, Proximity};
+
+    #[test]
+    fn test_clusters_are_deterministic_and_tight() {
+        let gen = Clusters::new(16, 3).with_seed(42).with_spread(0.05);
+        let a = gen.generate(30);
+        let b = gen.generate(30);
+        assert_eq!(a[7].1.dims(), b[7].1.dims());
+
+        let centers = gen.centers();
+        for (label, point) in &a {
+            assert!(Cosine.proximity(point, &centers[*label]) > 0.9);
+        }
+    }
+
+    #[test]
+    fn test_drifting_sessions_shape() {
+        let sessions = DriftingSessions::new(8).with_shape(2, 3, 5).generate();
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions.iter().all(|s| s.documents.len() == 3));
+        assert!(sessions[0].documents.iter().all(|d| d.len() == 5));
+
+        // Consecutive chunks stay close
+        let doc = &sessions[0].documents[0];
+        assert!(Cosine.proximity(&doc[0], &doc[1]) > 0.8);
+    }
+
+    #[test]
+    fn test_conversation_alternates_and_shifts() {
+        let turns = Conversation::new(8, 40)
+            .with_topics(3, 0.5)
+            .with_timing(1_000, 500)
+            .with_seed(3)
+            .generate();
+        assert_eq!(turns.len(), 40);
+        assert_eq!(turns[0].speaker, Speaker::User);
+        assert_eq!(turns[1].speaker, Speaker::Assistant);
+        assert_eq!(turns[3].timestamp_ms, 2_500);
+
+        // Answers stay on their question's topic
+        assert!(turns.chunks(2).all(|pair| pair[0].topic == pair[1].topic));
+        assert!(turns.iter().any(|t| t.topic != turns[0].topic));
+    }
+}
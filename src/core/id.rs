@@ -8,9 +8,12 @@
 //! - Random portion adds uniqueness
 //! - Sortable by time when compared
 //! - No external dependencies (not UUID, just bytes)
+//!
+//! Deterministic runs use `SeededIds` instead: a sequence number in place of
+//! timestamp and counter, and seed-derived low bits, identical on every run.
 
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
+use super::clock;
 
 /// Global counter for uniqueness within same millisecond
 static COUNTER: AtomicU64 = AtomicU64::new(0);
@@ -26,10 +29,7 @@ impl Id {
     ///
     /// Uses current timestamp + counter + random bytes for uniqueness.
     pub fn now() -> Self {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        let timestamp = clock::now_ms();
 
         // Atomically increment counter for uniqueness
         let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
@@ -84,6 +84,18 @@ impl Id {
             | (self.0[5] as u64)
     }
 
+    /// Parse the 32-character hex form produced by `Display`
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 32 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let mut bytes = [0u8; 16];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+        }
+        Some(Self(bytes))
+    }
+
     /// Create a nil/zero Id (useful for testing)
     pub fn nil() -> Self {
         Self([0u8; 16])
@@ -93,6 +105,46 @@ impl Id {
     pub fn is_nil(&self) -> bool {
         self.0 == [0u8; 16]
     }
+
+    /// The `sequence`-th ID of a seed (the same on every run)
+    ///
+    /// The sequence number fills the timestamp and counter bits, so seeded
+    /// IDs sort in issue order and `timestamp_ms` reports logical time.
+    pub fn seeded(seed: u64, sequence: u64) -> Self {
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&sequence.to_be_bytes());
+        let low = super::gen::Rng::new(seed ^ sequence.wrapping_mul(0x9E37_79B9_7F4A_7C15)).next_u64();
+        bytes[8..].copy_from_slice(&low.to_be_bytes());
+        Self(bytes)
+    }
+}
+
+/// Reproducible ID source for deterministic runs
+///
+/// Hands out `Id::seeded(seed, 0)`, `Id::seeded(seed, 1)`, ... so the same
+/// sequence of inserts gets the same IDs on every run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeededIds {
+    seed: u64,
+    next: u64,
+}
+
+impl SeededIds {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, next: 0 }
+    }
+
+    /// Issue the next ID
+    pub fn next_id(&mut self) -> Id {
+        let id = Id::seeded(self.seed, self.next);
+        self.next += 1;
+        id
+    }
+
+    /// Number of IDs issued so far
+    pub fn issued(&self) -> u64 {
+        self.next
+    }
 }
 
 impl std::fmt::Display for Id {
@@ -109,7 +161,7 @@ impl std::fmt::Display for Id {
 mod tests {
     use super::*;
     use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
     #[test]
     fn test_id_creation() {
@@ -165,5 +217,22 @@ mod tests {
         let id = Id::from_bytes([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
         let display = format!("{}", id);
         assert_eq!(display, "000102030405060708090a0b0c0d0e0f");
+        assert_eq!(Id::from_hex(&display), Some(id));
+        assert_eq!(Id::from_hex("+00102030405060708090a0b0c0d0e0f"), None);
+        assert_eq!(Id::from_hex("0001"), None);
+    }
+
+    #[test]
+    fn test_seeded_ids_repeat_and_sort() {
+        let mut a = SeededIds::new(7);
+        let mut b = SeededIds::new(7);
+        let first: Vec<Id> = (0..3).map(|_| a.next_id()).collect();
+        let second: Vec<Id> = (0..3).map(|_| b.next_id()).collect();
+        assert_eq!(first, second);
+        assert!(first.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(a.issued(), 3);
+
+        // Another seed, other IDs
+        assert_ne!(SeededIds::new(8).next_id(), first[0]);
     }
 }
@@ -0,0 +1,201 @@
+//! # Metadata
+//!
+//! Typed key/value metadata attached to points.
+//!
+//! Unlike `Blob` (opaque bytes), metadata values are typed so they can be
+//! compared, filtered and round-tripped through language bindings without
+//! guessing (a Python `dict` comes back as a `dict`).
+//!
+//! Keys are ordered (BTreeMap) so the encoding is deterministic.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::bytes::ByteReader;
+
+/// A metadata value
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+/// Metadata for a single point
+pub type Metadata = BTreeMap<String, MetaValue>;
+
+impl MetaValue {
+    /// Get as string slice if this is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            MetaValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get as f64 if numeric
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            MetaValue::Int(i) => Some(*i as f64),
+            MetaValue::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    fn tag(&self) -> u8 {
+        match self {
+            MetaValue::Null => 0,
+            MetaValue::Bool(_) => 1,
+            MetaValue::Int(_) => 2,
+            MetaValue::Float(_) => 3,
+            MetaValue::Str(_) => 4,
+        }
+    }
+}
+
+impl From<&str> for MetaValue {
+    fn from(s: &str) -> Self {
+        MetaValue::Str(s.to_string())
+    }
+}
+
+impl From<String> for MetaValue {
+    fn from(s: String) -> Self {
+        MetaValue::Str(s)
+    }
+}
+
+impl From<i64> for MetaValue {
+    fn from(i: i64) -> Self {
+        MetaValue::Int(i)
+    }
+}
+
+impl From<f64> for MetaValue {
+    fn from(f: f64) -> Self {
+        MetaValue::Float(f)
+    }
+}
+
+impl From<bool> for MetaValue {
+    fn from(b: bool) -> Self {
+        MetaValue::Bool(b)
+    }
+}
+
+/// Encode metadata to bytes
+///
+/// Layout: count u32, then per entry: key (u32 len + UTF-8), tag u8, value.
+pub fn encode_metadata(metadata: &Metadata) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&(metadata.len() as u32).to_le_bytes());
+
+    for (key, value) in metadata {
+        bytes.extend_from_slice(&(key.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(key.as_bytes());
+        bytes.push(value.tag());
+        match value {
+            MetaValue::Null => {}
+            MetaValue::Bool(b) => bytes.push(*b as u8),
+            MetaValue::Int(i) => bytes.extend_from_slice(&i.to_le_bytes()),
+            MetaValue::Float(f) => bytes.extend_from_slice(&f.to_le_bytes()),
+            MetaValue::Str(s) => {
+                bytes.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                bytes.extend_from_slice(s.as_bytes());
+            }
+        }
+    }
+
+    bytes
+}
+
+/// Decode metadata produced by `encode_metadata`
+///
+/// Returns the metadata and the number of bytes consumed.
+pub fn decode_metadata(data: &[u8]) -> Option<(Metadata, usize)> {
+    let mut r = ByteReader::new(data);
+
+    let count = r.u32("entry count").ok()?;
+    let mut metadata = Metadata::new();
+    for _ in 0..count {
+        let key = r.str("key").ok()?.to_string();
+        let value = match r.u8("value tag").ok()? {
+            0 => MetaValue::Null,
+            1 => MetaValue::Bool(r.u8("bool").ok()? != 0),
+            2 => MetaValue::Int(r.i64("int").ok()?),
+            3 => MetaValue::Float(r.f64("float").ok()?),
+            4 => MetaValue::Str(r.str("string").ok()?.to_string()),
+            _ => return None,
+        };
+        metadata.insert(key, value);
+    }
+
+    Some((metadata, r.position()))
+}
+
+/// Append a value as JSON (non-finite floats become `null`)
+pub(crate) fn push_json_value(out: &mut String, value: &MetaValue) {
+    match value {
+        MetaValue::Null => out.push_str("null"),
+        MetaValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        MetaValue::Int(i) => {
+            let _ = write!(out, "{}", i);
+        }
+        MetaValue::Float(f) if f.is_finite() => {
+            let _ = write!(out, "{}", f);
+        }
+        MetaValue::Float(_) => out.push_str("null"),
+        MetaValue::Str(s) => push_json_string(out, s),
+    }
+}
+
+/// Append a string as a quoted, escaped JSON string
+pub(crate) fn push_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let mut metadata = Metadata::new();
+        metadata.insert("user".into(), "ana".into());
+        metadata.insert("turn".into(), 7i64.into());
+        metadata.insert("confidence".into(), 0.25f64.into());
+        metadata.insert("pinned".into(), true.into());
+        metadata.insert("parent".into(), MetaValue::Null);
+
+        let bytes = encode_metadata(&metadata);
+        let (decoded, used) = decode_metadata(&bytes).unwrap();
+
+        assert_eq!(decoded, metadata);
+        assert_eq!(used, bytes.len());
+    }
+
+    #[test]
+    fn test_metadata_truncated() {
+        let mut metadata = Metadata::new();
+        metadata.insert("k".into(), "value".into());
+        let bytes = encode_metadata(&metadata);
+
+        assert!(decode_metadata(&bytes[..bytes.len() - 1]).is_none());
+    }
+}
@@ -6,8 +6,18 @@
 //! - `Point` - A position in dimensional space
 //! - `Id` - Unique identifier for placed points
 //! - `Blob` - Raw payload data
+//! - `Metadata` - Typed key/value metadata
+//! - `Fact` - Subject/predicate/value statements with confidence and source
+//! - `Provenance` - Source, pipeline, model version and confidence of a point
 //! - `Proximity` - Trait for measuring relatedness
 //! - `Merge` - Trait for composing points
+//! - `gen` - Seeded synthetic data generators
+//! - `schema` - Named dimension groups and a `PointBuilder`
+//! - `clustering` - Mini-batch k-means for topic discovery
+//! - `projection` - Approximate 2-D layouts with JSON/CSV export
+//! - `bytes` - Bounds-checked little-endian decoding for file formats
+//! - `cluster` - Replicated cluster metadata as a deterministic state machine
+//! - `Cause` - The underlying error carried by an error variant
 //!
 //! ## Design Principles
 //!
@@ -15,18 +25,33 @@
 //! - No I/O operations
 //! - No external dependencies beyond std
 //! - Fully testable in isolation
+//!
+//! `Point`, `proximity` and `merge` come from the `arms-core` crate, which
+//! builds under `no_std + alloc` for embedded and edge targets.
 
-mod point;
 mod id;
 mod blob;
-pub mod proximity;
-pub mod merge;
 pub mod config;
+pub mod clock;
+pub mod metadata;
+pub mod fact;
+pub mod provenance;
+pub mod gen;
+pub mod schema;
+pub mod clustering;
+pub mod projection;
+pub mod bytes;
+pub mod cluster;
+mod cause;
 
-// Re-exports
-pub use point::Point;
-pub use id::Id;
+// Re-exports (Point, proximity and merge live in the no_std `arms-core` crate)
+pub use arms_core::{merge, proximity, Point};
+pub use id::{Id, SeededIds};
 pub use blob::Blob;
+pub use metadata::{Metadata, MetaValue};
+pub use fact::Fact;
+pub use provenance::Provenance;
+pub use cause::Cause;
 
 /// A point that has been placed in the space
 #[derive(Clone)]
@@ -0,0 +1,387 @@
+//! # 2-D Projection
+//!
+//! Approximate 2-D layouts of points for visualizing a memory space.
+//!
+//! The layout starts from the two principal components (power iteration),
+//! then a UMAP-style refinement pulls each point toward its nearest
+//! neighbors and pushes it away from random others, so local structure
+//! survives even when it isn't linear:
+//!
+//! ```text
+//! points ──PCA──▶ initial layout ──k-NN graph + attract/repel epochs──▶ (x, y)
+//! ```
+//!
+//! Neighbors are found by brute force, O(n²) in the number of points;
+//! project a sample (`Arms::project_2d`) rather than a whole store.
+//!
+//! ```rust,ignore
+//! let projection = arms.project_2d(2000).with_clusters(&clustering);
+//! std::fs::write("memory.csv", projection.to_csv())?;
+//! ```
+
+use std::fmt::Write;
+
+use super::clustering::Clustering;
+use super::gen::Rng;
+use super::proximity::Proximity;
+use super::metadata::{push_json_string, push_json_value};
+use super::{Id, MetaValue, Metadata, Point};
+
+/// Power iterations per principal component
+const PCA_ITERATIONS: usize = 50;
+
+/// Half-width of the initial layout
+const LAYOUT_SCALE: f32 = 10.0;
+
+/// Cap on a single gradient step (as UMAP does)
+const MAX_STEP: f32 = 4.0;
+
+/// Layout settings
+#[derive(Debug, Clone)]
+pub struct Projector {
+    /// Neighbors each point is pulled toward
+    pub neighbors: usize,
+
+    /// Refinement passes over the neighbor graph (0 = plain PCA)
+    pub epochs: usize,
+
+    /// Random points each point is pushed away from per neighbor
+    pub negatives: usize,
+
+    /// Seed for sampling and refinement
+    pub seed: u64,
+}
+
+impl Default for Projector {
+    fn default() -> Self {
+        Self {
+            neighbors: 10,
+            epochs: 200,
+            negatives: 5,
+            seed: 0,
+        }
+    }
+}
+
+impl Projector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_neighbors(mut self, neighbors: usize) -> Self {
+        self.neighbors = neighbors;
+        self
+    }
+
+    pub fn with_epochs(mut self, epochs: usize) -> Self {
+        self.epochs = epochs;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Lay out `points` in 2-D, with neighbors measured by `proximity`
+    ///
+    /// Metadata starts empty; fill it in on the returned points.
+    pub fn project<'a>(
+        &self,
+        points: impl IntoIterator<Item = (Id, &'a Point)>,
+        proximity: &dyn Proximity,
+    ) -> Projection {
+        let (ids, points): (Vec<Id>, Vec<&Point>) = points.into_iter().unzip();
+        let mut layout = pca_layout(&points);
+        if points.len() > 2 && self.epochs > 0 {
+            let graph = neighbor_graph(&points, self.neighbors.min(points.len() - 1), proximity);
+            self.refine(&mut layout, &graph);
+        }
+
+        Projection {
+            points: ids
+                .into_iter()
+                .zip(layout)
+                .map(|(id, [x, y])| ProjectedPoint { id, x, y, metadata: Metadata::new() })
+                .collect(),
+        }
+    }
+
+    /// Attract along neighbor edges, repel from random points
+    fn refine(&self, layout: &mut [[f32; 2]], graph: &[Vec<usize>]) {
+        let mut rng = Rng::new(self.seed);
+        let n = layout.len();
+        for epoch in 0..self.epochs {
+            let rate = 1.0 - epoch as f32 / self.epochs as f32;
+            for (i, neighbors) in graph.iter().enumerate() {
+                for &j in neighbors {
+                    // Attraction: -2 / (1 + d²), moving both ends
+                    let (delta, d2) = offset(layout[i], layout[j]);
+                    let pull = -2.0 / (1.0 + d2);
+                    for axis in 0..2 {
+                        let step = (pull * delta[axis]).clamp(-MAX_STEP, MAX_STEP) * rate;
+                        layout[i][axis] += step;
+                        layout[j][axis] -= step;
+                    }
+
+                    // Repulsion: 2 / ((ε + d²)(1 + d²)), moving only i
+                    for _ in 0..self.negatives {
+                        let l = rng.below(n);
+                        if l == i {
+                            continue;
+                        }
+                        let (delta, d2) = offset(layout[i], layout[l]);
+                        let push = 2.0 / ((0.001 + d2) * (1.0 + d2));
+                        for axis in 0..2 {
+                            layout[i][axis] += (push * delta[axis]).clamp(-MAX_STEP, MAX_STEP) * rate;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `a - b` and its squared length
+fn offset(a: [f32; 2], b: [f32; 2]) -> ([f32; 2], f32) {
+    let delta = [a[0] - b[0], a[1] - b[1]];
+    (delta, delta[0] * delta[0] + delta[1] * delta[1])
+}
+
+/// Coordinates on the top two principal components, scaled to ±`LAYOUT_SCALE`
+fn pca_layout(points: &[&Point]) -> Vec<[f32; 2]> {
+    let Some(first) = points.first() else {
+        return Vec::new();
+    };
+    let d = first.dimensionality();
+    let n = points.len() as f32;
+
+    let mut mean = vec![0.0f32; d];
+    for p in points {
+        for (m, x) in mean.iter_mut().zip(p.dims()) {
+            *m += x / n;
+        }
+    }
+    let centered: Vec<Vec<f32>> = points
+        .iter()
+        .map(|p| p.dims().iter().zip(&mean).map(|(x, m)| x - m).collect())
+        .collect();
+
+    // Power iteration on the covariance, deflating after each component
+    let mut components: Vec<Vec<f32>> = Vec::with_capacity(2);
+    for c in 0..2 {
+        let mut v: Vec<f32> = (0..d).map(|i| if i % 2 == c { 1.0 } else { 0.5 }).collect();
+        for _ in 0..PCA_ITERATIONS {
+            let mut next = vec![0.0f32; d];
+            for row in &centered {
+                let dot: f32 = row.iter().zip(&v).map(|(a, b)| a * b).sum();
+                for (n, x) in next.iter_mut().zip(row) {
+                    *n += dot * x;
+                }
+            }
+            for prev in &components {
+                let dot: f32 = next.iter().zip(prev).map(|(a, b)| a * b).sum();
+                for (n, p) in next.iter_mut().zip(prev) {
+                    *n -= dot * p;
+                }
+            }
+            let norm = next.iter().map(|x| x * x).sum::<f32>().sqrt();
+            if norm <= f32::EPSILON {
+                break;
+            }
+            v = next.into_iter().map(|x| x / norm).collect();
+        }
+        components.push(v);
+    }
+
+    let mut layout: Vec<[f32; 2]> = centered
+        .iter()
+        .map(|row| {
+            let project = |c: &[f32]| row.iter().zip(c).map(|(a, b)| a * b).sum::<f32>();
+            [project(&components[0]), project(&components[1])]
+        })
+        .collect();
+    let extent = layout
+        .iter()
+        .flat_map(|p| p.iter().map(|x| x.abs()))
+        .fold(0.0f32, f32::max);
+    if extent > 0.0 {
+        for p in &mut layout {
+            p[0] *= LAYOUT_SCALE / extent;
+            p[1] *= LAYOUT_SCALE / extent;
+        }
+    }
+    layout
+}
+
+/// The `k` most related other points of each point
+fn neighbor_graph(points: &[&Point], k: usize, proximity: &dyn Proximity) -> Vec<Vec<usize>> {
+    let order = proximity.order();
+    (0..points.len())
+        .map(|i| {
+            let mut others: Vec<(usize, f32)> = (0..points.len())
+                .filter(|&j| j != i)
+                .map(|j| (j, order.to_distance(proximity.proximity(points[i], points[j]))))
+                .collect();
+            others.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+            others.into_iter().take(k).map(|(j, _)| j).collect()
+        })
+        .collect()
+}
+
+/// A point placed on the 2-D layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectedPoint {
+    pub id: Id,
+    pub x: f32,
+    pub y: f32,
+
+    /// Fields exported alongside the coordinates
+    pub metadata: Metadata,
+}
+
+/// A 2-D layout of points, exportable as JSON or CSV
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Projection {
+    pub points: Vec<ProjectedPoint>,
+}
+
+impl Projection {
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Add each point's cluster label as `"cluster"` metadata
+    pub fn with_clusters(mut self, clustering: &Clustering) -> Self {
+        for point in &mut self.points {
+            let label = clustering.label_of(point.id).map_or(MetaValue::Null, |l| MetaValue::Int(l as i64));
+            point.metadata.insert("cluster".to_string(), label);
+        }
+        self
+    }
+
+    /// `[{"id": "...", "x": 1.5, "y": -2.0, "metadata": {...}}, ...]`
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("[");
+        for (i, point) in self.points.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "{{\"id\":\"{}\",\"x\":{},\"y\":{},\"metadata\":{{", point.id, point.x, point.y);
+            for (j, (key, value)) in point.metadata.iter().enumerate() {
+                if j > 0 {
+                    out.push(',');
+                }
+                push_json_string(&mut out, key);
+                out.push(':');
+                push_json_value(&mut out, value);
+            }
+            out.push_str("}}");
+        }
+        out.push(']');
+        out
+    }
+
+    /// Header `id,x,y` plus one column per metadata key (sorted), then a
+    /// row per point; missing fields are empty
+    pub fn to_csv(&self) -> String {
+        let mut keys: Vec<&str> = self
+            .points
+            .iter()
+            .flat_map(|p| p.metadata.keys().map(String::as_str))
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+
+        let mut out = String::from("id,x,y");
+        for key in &keys {
+            out.push(',');
+            push_csv_field(&mut out, key);
+        }
+        out.push('\n');
+        for point in &self.points {
+            let _ = write!(out, "{},{},{}", point.id, point.x, point.y);
+            for key in &keys {
+                out.push(',');
+                match point.metadata.get(*key) {
+                    Some(MetaValue::Str(s)) => push_csv_field(&mut out, s),
+                    Some(MetaValue::Null) | None => {}
+                    Some(value) => push_json_value(&mut out, value),
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Quote a field if it holds a comma, quote or line break
+fn push_csv_field(out: &mut String, s: &str) {
+    if s.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&s.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(s);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::gen::Clusters;
+    use crate::core::proximity::Cosine;
+
+    #[test]
+    fn test_projection_separates_clusters() {
+        let generated = Clusters::new(16, 2).with_seed(4).with_spread(0.05).generate(60);
+        let ids: Vec<Id> = (0..60u128).map(|n| Id::from_bytes(n.to_le_bytes())).collect();
+        let projection = Projector::new()
+            .with_epochs(50)
+            .project(ids.iter().copied().zip(generated.iter().map(|(_, p)| p)), &Cosine);
+        assert_eq!(projection.len(), 60);
+
+        // Each point's nearest neighbor on the layout comes from its own cluster
+        for (i, a) in projection.points.iter().enumerate() {
+            let nearest = projection
+                .points
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .min_by(|(_, p), (_, q)| {
+                    offset([a.x, a.y], [p.x, p.y]).1.total_cmp(&offset([a.x, a.y], [q.x, q.y]).1)
+                })
+                .map(|(j, _)| j)
+                .unwrap();
+            assert_eq!(generated[i].0, generated[nearest].0);
+        }
+    }
+
+    #[test]
+    fn test_projection_exports() {
+        let mut metadata = Metadata::new();
+        metadata.insert("text".to_string(), "say \"hi\", then\nleave".into());
+        metadata.insert("turn".to_string(), 3i64.into());
+        let projection = Projection {
+            points: vec![
+                ProjectedPoint { id: Id::from_bytes([1; 16]), x: 1.5, y: -2.0, metadata },
+                ProjectedPoint { id: Id::from_bytes([2; 16]), x: 0.0, y: 0.25, metadata: Metadata::new() },
+            ],
+        };
+
+        let json = projection.to_json();
+        assert!(json.starts_with(&format!("[{{\"id\":\"{}\",\"x\":1.5,\"y\":-2,", Id::from_bytes([1; 16]))));
+        assert!(json.contains(r#""metadata":{"text":"say \"hi\", then\nleave","turn":3}"#));
+        assert!(json.ends_with(",\"metadata\":{}}]"));
+
+        let csv = projection.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "id,x,y,text,turn");
+        assert!(csv.contains(",1.5,-2,\"say \"\"hi\"\", then\nleave\",3\n"));
+        assert!(lines.last().unwrap().ends_with(",0,0.25,,"));
+    }
+}
@@ -0,0 +1,126 @@
+//! # Provenance
+//!
+//! Where a point came from: the source document or URL, the ingestion
+//! pipeline that chunked it, the embedding model that placed it, and how
+//! much the writer trusted it.
+//!
+//! Provenance travels with search results (see `ReturnFields::provenance`)
+//! so the caller's ranking can discount memories from low-trust sources or
+//! outdated models without a second lookup.
+
+use super::{Metadata, MetaValue};
+
+/// Source, pipeline, model version and confidence of a point
+///
+/// Every field is optional; an empty provenance is the same as none.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Provenance {
+    /// Document, URL or tool the content came from
+    pub source: Option<String>,
+
+    /// Ingestion pipeline that produced the chunk
+    pub pipeline: Option<String>,
+
+    /// Embedding model (and version) that produced the point
+    pub model_version: Option<String>,
+
+    /// How much the writer trusted the content, 0.0 - 1.0
+    pub confidence: Option<f32>,
+}
+
+impl Provenance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn with_pipeline(mut self, pipeline: impl Into<String>) -> Self {
+        self.pipeline = Some(pipeline.into());
+        self
+    }
+
+    pub fn with_model_version(mut self, model_version: impl Into<String>) -> Self {
+        self.model_version = Some(model_version.into());
+        self
+    }
+
+    /// Confidence, clamped to 0.0 - 1.0 (NaN counts as 0)
+    pub fn with_confidence(mut self, confidence: f32) -> Self {
+        self.confidence = Some(if confidence.is_nan() { 0.0 } else { confidence.clamp(0.0, 1.0) });
+        self
+    }
+
+    /// True if no field is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Confidence, or `default` when none was recorded
+    pub fn confidence_or(&self, default: f32) -> f32 {
+        self.confidence.unwrap_or(default)
+    }
+
+    /// The set fields as metadata (`source`, `pipeline`, `model_version`,
+    /// `confidence`)
+    pub fn to_metadata(&self) -> Metadata {
+        let mut metadata = Metadata::new();
+        let strings = [("source", &self.source), ("pipeline", &self.pipeline), ("model_version", &self.model_version)];
+        for (key, value) in strings {
+            if let Some(value) = value {
+                metadata.insert(key.to_string(), MetaValue::Str(value.clone()));
+            }
+        }
+        if let Some(confidence) = self.confidence {
+            metadata.insert("confidence".to_string(), MetaValue::Float(confidence as f64));
+        }
+        metadata
+    }
+
+    /// Read the fields `to_metadata` writes, ignoring other keys
+    ///
+    /// None if a known key holds the wrong type.
+    pub fn from_metadata(metadata: &Metadata) -> Option<Self> {
+        let string = |key: &str| match metadata.get(key) {
+            None => Some(None),
+            Some(MetaValue::Str(s)) => Some(Some(s.clone())),
+            Some(_) => None,
+        };
+        let confidence = match metadata.get("confidence") {
+            None => None,
+            Some(MetaValue::Float(f)) => Some(*f as f32),
+            Some(MetaValue::Int(i)) => Some(*i as f32),
+            Some(_) => return None,
+        };
+        Some(Self {
+            source: string("source")?,
+            pipeline: string("pipeline")?,
+            model_version: string("model_version")?,
+            confidence,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provenance_metadata_roundtrip() {
+        let provenance = Provenance::new()
+            .with_source("wiki/berlin")
+            .with_model_version("e5-large-v2")
+            .with_confidence(1.5);
+        assert_eq!(provenance.confidence, Some(1.0));
+        assert_eq!(provenance.to_metadata().len(), 3);
+        assert_eq!(Provenance::from_metadata(&provenance.to_metadata()), Some(provenance));
+
+        assert!(Provenance::new().is_empty());
+        assert_eq!(Provenance::new().confidence_or(0.5), 0.5);
+        let bad = Metadata::from([("source".to_string(), MetaValue::Int(3))]);
+        assert_eq!(Provenance::from_metadata(&bad), None);
+    }
+}
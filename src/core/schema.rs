@@ -0,0 +1,304 @@
+//! # Dimension Schema
+//!
+//! Names for dimensions and dimension groups of hybrid coordinate spaces.
+//!
+//! A point doesn't have to come from one model. A schema splits it into
+//! named groups that are filled independently and can weigh differently
+//! when comparing points:
+//!
+//! ```text
+//! dims:   0 ............................ 767 768 ... 771
+//!         └──────── semantic ──────────┘ └─ temporal ┘
+//! ```
+//!
+//! ```rust,ignore
+//! let schema = DimensionSchema::new()
+//!     .with_group("semantic", 0..768)
+//!     .with_weighted_group("temporal", 768..772, 0.2);
+//!
+//! let point = schema.builder()
+//!     .set("semantic", &embedding)?
+//!     .set("temporal", &time_features)?
+//!     .build()?;
+//!
+//! // Each group scored on its own, then averaged by weight
+//! let config = ArmsConfig::new(schema.dimensionality())
+//!     .with_proximity(schema.proximity(Arc::new(Cosine)));
+//! ```
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use super::proximity::{Proximity, ScoreOrder};
+use super::Point;
+
+/// Errors from building a point against a schema
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum SchemaError {
+    /// No group has this name
+    #[error("Unknown dimension group: {0}")]
+    UnknownGroup(String),
+
+    /// Values don't fit the group
+    #[error("Group {group} has {expected} dimensions, got {got}")]
+    LengthMismatch { group: String, expected: usize, got: usize },
+
+    /// A group was never set
+    #[error("Dimension group not set: {0}")]
+    Missing(String),
+}
+
+/// A named, contiguous range of dimensions
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionGroup {
+    pub name: String,
+    pub range: Range<usize>,
+
+    /// Relative weight in group-wise proximity (default 1.0)
+    pub weight: f32,
+}
+
+impl DimensionGroup {
+    pub fn len(&self) -> usize {
+        self.range.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
+}
+
+/// Named dimension groups of a space
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DimensionSchema {
+    groups: Vec<DimensionGroup>,
+}
+
+impl DimensionSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name a range of dimensions
+    ///
+    /// # Panics
+    /// If the range is empty, overlaps another group, or the name is taken.
+    pub fn with_group(self, name: impl Into<String>, range: Range<usize>) -> Self {
+        self.with_weighted_group(name, range, 1.0)
+    }
+
+    /// Name a range of dimensions with a proximity weight
+    ///
+    /// # Panics
+    /// As `with_group`, or if the weight is negative.
+    pub fn with_weighted_group(mut self, name: impl Into<String>, range: Range<usize>, weight: f32) -> Self {
+        let name = name.into();
+        assert!(!range.is_empty(), "Dimension group {} is empty", name);
+        assert!(weight >= 0.0, "Dimension group weights must be non-negative");
+        for group in &self.groups {
+            assert!(group.name != name, "Dimension group {} is defined twice", name);
+            assert!(
+                range.end <= group.range.start || range.start >= group.range.end,
+                "Dimension group {} overlaps {}",
+                name,
+                group.name
+            );
+        }
+        self.groups.push(DimensionGroup { name, range, weight });
+        self.groups.sort_by_key(|g| g.range.start);
+        self
+    }
+
+    /// Groups in dimension order
+    pub fn groups(&self) -> &[DimensionGroup] {
+        &self.groups
+    }
+
+    pub fn group(&self, name: &str) -> Option<&DimensionGroup> {
+        self.groups.iter().find(|g| g.name == name)
+    }
+
+    /// Dimensions spanned (gaps between groups included)
+    pub fn dimensionality(&self) -> usize {
+        self.groups.iter().map(|g| g.range.end).max().unwrap_or(0)
+    }
+
+    /// A group's coordinates within a point of this schema
+    pub fn slice<'a>(&self, point: &'a Point, name: &str) -> Option<&'a [f32]> {
+        let group = self.group(name)?;
+        point.dims().get(group.range.clone())
+    }
+
+    /// Start a point of this schema
+    pub fn builder(&self) -> PointBuilder<'_> {
+        PointBuilder {
+            schema: self,
+            dims: vec![0.0; self.dimensionality()],
+            set: vec![false; self.groups.len()],
+        }
+    }
+
+    /// One weight per dimension (0 outside any group)
+    ///
+    /// For `ArmsConfig::with_dimension_weights`, which weighs dimensions
+    /// inside a single score rather than scoring groups separately.
+    pub fn dimension_weights(&self) -> Vec<f32> {
+        let mut weights = vec![0.0; self.dimensionality()];
+        for group in &self.groups {
+            weights[group.range.clone()].fill(group.weight);
+        }
+        weights
+    }
+
+    /// Score each group with `base` and average by group weight
+    pub fn proximity(&self, base: Arc<dyn Proximity>) -> GroupedProximity {
+        GroupedProximity {
+            groups: self.groups.iter().map(|g| (g.range.clone(), g.weight)).collect(),
+            base,
+        }
+    }
+}
+
+/// Composes a point group by group
+pub struct PointBuilder<'a> {
+    schema: &'a DimensionSchema,
+    dims: Vec<f32>,
+    set: Vec<bool>,
+}
+
+impl PointBuilder<'_> {
+    /// Fill a group's dimensions
+    pub fn set(mut self, name: &str, values: &[f32]) -> Result<Self, SchemaError> {
+        let (i, group) = self
+            .schema
+            .groups
+            .iter()
+            .enumerate()
+            .find(|(_, g)| g.name == name)
+            .ok_or_else(|| SchemaError::UnknownGroup(name.to_string()))?;
+        if values.len() != group.len() {
+            return Err(SchemaError::LengthMismatch {
+                group: name.to_string(),
+                expected: group.len(),
+                got: values.len(),
+            });
+        }
+        self.dims[group.range.clone()].copy_from_slice(values);
+        self.set[i] = true;
+        Ok(self)
+    }
+
+    /// Finish, requiring every group to be set (gaps stay zero)
+    pub fn build(self) -> Result<Point, SchemaError> {
+        if let Some(i) = self.set.iter().position(|set| !set) {
+            return Err(SchemaError::Missing(self.schema.groups[i].name.clone()));
+        }
+        Ok(Point::new(self.dims))
+    }
+
+    /// Finish, leaving unset groups zero
+    pub fn build_partial(self) -> Point {
+        Point::new(self.dims)
+    }
+}
+
+/// Weighted average of a base proximity computed per dimension group
+///
+/// Unlike per-dimension weights, each group is scored on its own (a cosine
+/// per group, say), so a small group isn't drowned out by a large one.
+#[derive(Clone)]
+pub struct GroupedProximity {
+    groups: Vec<(Range<usize>, f32)>,
+    base: Arc<dyn Proximity>,
+}
+
+impl Proximity for GroupedProximity {
+    fn proximity(&self, a: &Point, b: &Point) -> f32 {
+        assert_eq!(
+            a.dimensionality(),
+            b.dimensionality(),
+            "Points must have same dimensionality"
+        );
+        self.proximity_slices(a.dims(), b.dims())
+    }
+
+    fn proximity_slices(&self, a: &[f32], b: &[f32]) -> f32 {
+        let (mut total, mut weights) = (0.0, 0.0);
+        for (range, weight) in &self.groups {
+            total += weight * self.base.proximity_slices(&a[range.clone()], &b[range.clone()]);
+            weights += weight;
+        }
+        if weights == 0.0 {
+            0.0
+        } else {
+            total / weights
+        }
+    }
+
+    fn order(&self) -> ScoreOrder {
+        self.base.order()
+    }
+
+    fn normalizes(&self) -> bool {
+        self.base.normalizes()
+    }
+
+    fn name(&self) -> &'static str {
+        "grouped"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::proximity::Cosine;
+
+    fn schema() -> DimensionSchema {
+        DimensionSchema::new()
+            .with_group("semantic", 0..3)
+            .with_weighted_group("temporal", 3..4, 0.5)
+    }
+
+    #[test]
+    fn test_builder_composes_groups() {
+        let schema = schema();
+        assert_eq!(schema.dimensionality(), 4);
+
+        let point = schema
+            .builder()
+            .set("semantic", &[1.0, 2.0, 3.0])
+            .unwrap()
+            .set("temporal", &[9.0])
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(point.dims(), &[1.0, 2.0, 3.0, 9.0]);
+        assert_eq!(schema.slice(&point, "temporal"), Some(&[9.0][..]));
+
+        assert_eq!(
+            schema.builder().set("semantic", &[1.0]).err(),
+            Some(SchemaError::LengthMismatch { group: "semantic".into(), expected: 3, got: 1 })
+        );
+        assert_eq!(schema.builder().set("spatial", &[]).err(), Some(SchemaError::UnknownGroup("spatial".into())));
+        assert_eq!(schema.builder().build().err(), Some(SchemaError::Missing("semantic".into())));
+        assert_eq!(schema.dimension_weights(), vec![1.0, 1.0, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn test_grouped_proximity_weighs_groups() {
+        let proximity = schema().proximity(Arc::new(Cosine));
+        let a = Point::new(vec![1.0, 0.0, 0.0, 1.0]);
+
+        // Same meaning, opposite time: (1.0 * 1 + 0.5 * -1) / 1.5
+        let b = Point::new(vec![2.0, 0.0, 0.0, -1.0]);
+        assert!((proximity.proximity(&a, &b) - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(proximity.order(), ScoreOrder::HigherIsBetter);
+    }
+
+    #[test]
+    #[should_panic(expected = "overlaps")]
+    fn test_overlapping_groups_panic() {
+        DimensionSchema::new().with_group("a", 0..4).with_group("b", 3..6);
+    }
+}
@@ -8,12 +8,78 @@
 //! - Configuration
 //!
 //! And exposes a unified API for storing and retrieving points.
-
-use crate::core::{Blob, Id, PlacedPoint, Point};
+//!
+//! With `ArmsConfig::with_matryoshka`, the index holds only a prefix of each
+//! vector: searches gather extra candidates from it and rescore them with
+//! the full vectors from storage.
+//!
+//! With `Arms::with_query_cache`, repeated `near`/`within` queries are
+//! answered from a `QueryCache` that writes invalidate selectively.
+//!
+//! With `ArmsConfig::with_deterministic`, IDs come from a `SeededIds`
+//! sequence instead of the clock, so a replayed run places the same IDs.
+//!
+//! `Arms::build_index_in_background` swaps in a new index without going
+//! dark: queries run on a flat scan until `poll_index_build` switches over.
+//!
+//! With `Arms::with_salience`, each placed point is scored for importance;
+//! every `near*` query and `within` report the score and rank salient
+//! points slightly ahead (see `with_salience_bias`). `ArmsFork::near`
+//! merges by raw score and doesn't.
+//!
+//! With `Arms::with_interceptor`, each placed point passes through a chain
+//! of `PlaceInterceptor`s that may rewrite its payload or reject it.
+//!
+//! With `Arms::with_query_log`, every `near`/`within` query is recorded
+//! (query or its hash, parameters, latency, returned IDs, and whether a
+//! deadline cut it short) to a `QueryLog`.
+//!
+//! `Arms::near_lazy` returns `LazyResult`s, which read a result's blob only
+//! when asked, so scanning many candidates doesn't fetch every payload.
+//!
+//! `Arms::erase_where` hard-deletes every point a filter matches and
+//! compacts storage, returning an `ErasureReport`.
+//!
+//! `Arms::fork` stages places and removes in an `ArmsFork` that leaves
+//! the store untouched until `commit`.
+//!
+//! `Arms::place_fast` stores a point and queues it for indexing, which a
+//! background worker does in batches once the queue fills (or
+//! `Arms::index_pending` does on demand); until then queries scan the
+//! queued points exactly (see `WriteBehind`).
+
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::core::{clock, Blob, Id, PlacedPoint, Point, SeededIds};
+use crate::core::clustering::{Clustering, KMeans};
+use crate::core::gen::Rng;
+use crate::core::projection::{Projection, Projector};
 use crate::core::config::ArmsConfig;
-use crate::ports::{Near, NearResult, Place, PlaceResult, SearchResult};
+use crate::ports::{Deadline, JobHandle, JobProgress, Near, NearError, NearResult, PartialResults, Place, PlaceResult, ProjectedResult, QueryParams, ReturnFields, SearchResult};
 use crate::adapters::storage::MemoryStorage;
-use crate::adapters::index::FlatIndex;
+use crate::adapters::index::{collapse_duplicates, FlatIndex, TopK};
+use crate::adapters::attention::Role;
+use super::build::IndexBuild;
+use super::cache::QueryCache;
+use super::erasure::{ErasureReport, ErasureTarget};
+use super::fork::ArmsFork;
+use super::intercept::{IngestInput, PlaceInterceptor, Verdict};
+use super::lazy::LazyResult;
+use super::pending::{PendingDrain, PendingQueue, WriteBehind};
+use super::metrics::{self, Gauge, MemoryReport, Metrics, Operation};
+use super::query_log::{QueryLog, QueryRecord};
+use super::salience::{Salience, SalienceInput};
+
+/// Default score bonus for a salience of 1 (see `Arms::with_salience_bias`)
+const DEFAULT_SALIENCE_BIAS: f32 = 0.1;
+
+/// Candidates fetched per result when salience can reorder them
+const SALIENCE_OVERSAMPLE: usize = 2;
+
+/// Neighbors compared by `Arms::novelty`
+const NOVELTY_NEIGHBORS: usize = 5;
 
 /// The main ARMS engine
 ///
@@ -25,8 +91,59 @@ pub struct Arms {
     /// Storage backend (Place port)
     storage: Box<dyn Place>,
 
-    /// Index backend (Near port)
-    index: Box<dyn Near>,
+    /// Index backend (Near port), shared with a running `PendingDrain`
+    index: Arc<RwLock<Box<dyn Near>>>,
+
+    /// Operation metrics (None = not measured)
+    metrics: Option<Arc<Metrics>>,
+
+    /// Search result cache (None = every query hits the index)
+    cache: Option<QueryCache>,
+
+    /// ID source in deterministic mode (None = storage assigns wall-clock IDs)
+    ids: Option<SeededIds>,
+
+    /// Clusters from the last `discover_clusters` (None = never run)
+    clusters: Option<Clustering>,
+
+    /// Importance scoring at place time (None = points aren't scored)
+    salience: Option<Arc<dyn Salience>>,
+
+    /// Score bonus for a salience of 1
+    salience_bias: f32,
+
+    /// Salience of each scored point
+    saliences: HashMap<Id, f32>,
+
+    /// Ingest hooks, run in order on every placed point
+    interceptors: Vec<Arc<dyn PlaceInterceptor>>,
+
+    /// Index being built in the background (None = `index` is final)
+    index_build: Option<IndexBuild>,
+
+    /// Where answered queries are recorded (None = not logged)
+    query_log: Option<Arc<QueryLog>>,
+
+    /// How `place_fast` defers indexing
+    write_behind: WriteBehind,
+
+    /// Stored points `place_fast` hasn't indexed yet
+    pending: PendingQueue,
+
+    /// Pending batch being indexed in the background (None = idle)
+    drain: Option<PendingDrain>,
+
+    /// Copies outside the store that `erase_where` purges
+    erasure_targets: Vec<Arc<dyn ErasureTarget>>,
+}
+
+/// How `place_unmeasured` indexes a point
+#[derive(Clone, Copy)]
+enum Indexing {
+    Now,
+
+    /// Queue for indexing at this priority (None = its salience)
+    Deferred(Option<f32>),
 }
 
 impl Arms {
@@ -36,32 +153,153 @@ impl Arms {
     /// For production, use `Arms::with_adapters` with appropriate backends.
     pub fn new(config: ArmsConfig) -> Self {
         let storage = Box::new(MemoryStorage::new(config.dimensionality));
-        let index = Box::new(FlatIndex::new(
-            config.dimensionality,
+        let index = Box::new(FlatIndex::from_proximity(
+            config.index_dimensionality(),
             config.proximity.clone(),
-            true, // Assuming cosine-like similarity by default
         ));
 
-        Self {
-            config,
-            storage,
-            index,
-        }
+        Self::with_adapters(config, storage, index)
     }
 
     /// Create with custom adapters
+    ///
+    /// With Matryoshka prefix indexing configured, `index` must accept
+    /// `config.index_dimensionality()` dimensions.
     pub fn with_adapters(
         config: ArmsConfig,
         storage: Box<dyn Place>,
         index: Box<dyn Near>,
     ) -> Self {
+        let ids = config.deterministic.map(SeededIds::new);
         Self {
             config,
             storage,
-            index,
+            index: Arc::new(RwLock::new(index)),
+            metrics: None,
+            cache: None,
+            ids,
+            clusters: None,
+            salience: None,
+            salience_bias: DEFAULT_SALIENCE_BIAS,
+            saliences: HashMap::new(),
+            interceptors: Vec::new(),
+            index_build: None,
+            query_log: None,
+            write_behind: WriteBehind::default(),
+            pending: PendingQueue::default(),
+            drain: None,
+            erasure_targets: Vec::new(),
+        }
+    }
+
+    /// Record operation counts and latencies into `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Record every `near`/`within` query to `log`
+    pub fn with_query_log(mut self, log: Arc<QueryLog>) -> Self {
+        self.query_log = Some(log);
+        self
+    }
+
+    /// Answer repeated `near`/`within` queries from `cache`
+    pub fn with_query_cache(mut self, cache: QueryCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Score each placed point's importance with `salience`
+    ///
+    /// Only points placed afterwards are scored.
+    pub fn with_salience(mut self, salience: Arc<dyn Salience>) -> Self {
+        self.salience = Some(salience);
+        self
+    }
+
+    /// How far salience moves rankings: a point with salience 1 ranks as if
+    /// its score were `bias` better (default 0.1; 0 = report only)
+    pub fn with_salience_bias(mut self, bias: f32) -> Self {
+        self.salience_bias = bias;
+        self
+    }
+
+    /// Queue `place_fast` points with these settings (default
+    /// `WriteBehind::new()`)
+    pub fn with_write_behind(mut self, write_behind: WriteBehind) -> Self {
+        self.write_behind = write_behind;
+        self
+    }
+
+    /// Purge erased points from `target` too (see `erase_where`)
+    pub fn with_erasure_target(mut self, target: Arc<dyn ErasureTarget>) -> Self {
+        self.erasure_targets.push(target);
+        self
+    }
+
+    /// Run `interceptor` on every placed point, after those already added
+    pub fn with_interceptor(mut self, interceptor: Arc<dyn PlaceInterceptor>) -> Self {
+        self.interceptors.push(interceptor);
+        self
+    }
+
+    /// Salience a point was given when placed
+    pub fn salience_of(&self, id: Id) -> Option<f32> {
+        self.saliences.get(&id).copied()
+    }
+
+    /// Get the attached query cache
+    pub fn query_cache(&self) -> Option<&QueryCache> {
+        self.cache.as_ref()
+    }
+
+    /// Get the attached metrics
+    pub fn metrics(&self) -> Option<&Arc<Metrics>> {
+        self.metrics.as_ref()
+    }
+
+    /// Record an operation that started at `start` (µs) into the attached metrics
+    fn record(&self, op: Operation, start: u64, ok: bool) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record(op, clock::now_micros().saturating_sub(start), ok);
         }
     }
 
+    /// Log a query that started at `start` (µs) to the attached query log
+    ///
+    /// `results` is None if the query failed; `complete` is false if a
+    /// deadline cut it short.
+    #[allow(clippy::too_many_arguments)]
+    fn log_query(
+        &self,
+        operation: Operation,
+        query: &Point,
+        params: &QueryParams,
+        threshold: Option<f32>,
+        start: u64,
+        results: Option<&[SearchResult]>,
+        complete: bool,
+    ) {
+        let Some(log) = &self.query_log else {
+            return;
+        };
+        log.log(&QueryRecord {
+            at_micros: start,
+            operation,
+            query: log.logged_query(query),
+            k: params.k,
+            threshold,
+            min_score: params.min_score,
+            dedup: params.dedup,
+            filtered: params.filter.is_some(),
+            latency_micros: clock::now_micros().saturating_sub(start),
+            ok: results.is_some(),
+            complete,
+            results: results.into_iter().flatten().map(|r| r.id).collect(),
+        });
+    }
+
     /// Get the configuration
     pub fn config(&self) -> &ArmsConfig {
         &self.config
@@ -81,6 +319,168 @@ impl Arms {
     /// The point will be normalized if configured to do so.
     /// Returns the assigned ID.
     pub fn place(&mut self, point: Point, blob: Blob) -> PlaceResult<Id> {
+        let start = clock::now_micros();
+        let result = self.place_unmeasured(point, blob, None, None, Indexing::Now);
+        self.record(Operation::Place, start, result.is_ok());
+        result
+    }
+
+    /// Store a point now and index it later (see `WriteBehind`)
+    ///
+    /// Interceptors and salience run as for `place`; the point is queued
+    /// for indexing ranked by its salience (0 when unscored). Once the queue
+    /// is full (`WriteBehind::max_pending`), a batch is indexed on a worker
+    /// thread; this call doesn't wait for it.
+    pub fn place_fast(&mut self, point: Point, blob: Blob) -> PlaceResult<Id> {
+        self.place_deferred(point, blob, None)
+    }
+
+    /// `place_fast` with an explicit indexing priority (higher = sooner)
+    pub fn place_fast_with_priority(&mut self, point: Point, blob: Blob, priority: f32) -> PlaceResult<Id> {
+        self.place_deferred(point, blob, Some(priority))
+    }
+
+    fn place_deferred(&mut self, point: Point, blob: Blob, priority: Option<f32>) -> PlaceResult<Id> {
+        let start = clock::now_micros();
+        let result = self
+            .poll_pending_drain()
+            .map_err(crate::ports::PlaceError::Index)
+            .and_then(|_| self.place_unmeasured(point, blob, None, None, Indexing::Deferred(priority)));
+        if self.drain.is_none() && self.pending.queued_len() >= self.write_behind.max_pending {
+            self.start_pending_drain();
+        }
+        self.record(Operation::Place, start, result.is_ok());
+        result
+    }
+
+    /// Hand the highest-priority batch to a worker thread
+    fn start_pending_drain(&mut self) {
+        let mut batch = Vec::new();
+        for id in self.pending.take_batch(self.write_behind.batch_size) {
+            match self.storage.get(id) {
+                Some(placed) => batch.push((id, self.index_point(&placed.point))),
+                None => {
+                    self.pending.settle(id);
+                }
+            }
+        }
+        self.drain = Some(PendingDrain::start(self.index.clone(), batch));
+    }
+
+    /// Collect the background batch if it's done
+    ///
+    /// Returns how many points it indexed (0 = none finished). If indexing
+    /// failed, the error is returned and the batch's remaining points are
+    /// queued again with their priorities.
+    pub fn poll_pending_drain(&mut self) -> NearResult<usize> {
+        match &self.drain {
+            Some(drain) if drain.is_finished() => self.finish_pending_drain(),
+            _ => Ok(0),
+        }
+    }
+
+    /// Wait for the background batch and collect it (see `poll_pending_drain`)
+    pub fn finish_pending_drain(&mut self) -> NearResult<usize> {
+        let Some(drain) = self.drain.take() else {
+            return Ok(0);
+        };
+        let drained = drain.finish();
+        let count = drained.indexed.len();
+        for (id, index_point) in drained.indexed {
+            self.pending.settle(id);
+            if let Some(build) = &mut self.index_build {
+                build.record(id, Some(index_point));
+            }
+            if let Some(placed) = self.storage.get(id) {
+                self.invalidate_cached(id, &placed.point);
+            }
+        }
+        self.pending.requeue();
+        match drained.error {
+            Some(e) => Err(e),
+            None => Ok(count),
+        }
+    }
+
+    /// Handle of the background batch, to track it in `Jobs` or cancel it
+    pub fn pending_drain_handle(&self) -> Option<&JobHandle> {
+        self.drain.as_ref().map(PendingDrain::handle)
+    }
+
+    /// Index up to `max` queued points on this thread, highest priority first
+    ///
+    /// Waits for a background batch first. Returns how many were indexed.
+    /// On an index error the failing point and the rest of the batch stay
+    /// queued with their priorities.
+    pub fn index_pending(&mut self, max: usize) -> NearResult<usize> {
+        self.finish_pending_drain()?;
+        let batch = self.pending.take_batch(max);
+        for &id in &batch {
+            let Some(point) = self.storage.get(id).map(|placed| placed.point.clone()) else {
+                self.pending.settle(id);
+                continue;
+            };
+            let index_point = self.index_point(&point);
+            let added = self.index_mut().add(id, &index_point);
+            if let Err(e) = added {
+                self.pending.requeue();
+                return Err(e);
+            }
+            self.pending.settle(id);
+            if let Some(build) = &mut self.index_build {
+                build.record(id, Some(index_point));
+            }
+            self.invalidate_cached(id, &point);
+        }
+        Ok(batch.len())
+    }
+
+    /// Index every queued point
+    pub fn flush_pending(&mut self) -> NearResult<usize> {
+        let drained = self.finish_pending_drain()?;
+        Ok(drained + self.index_pending(self.pending.len())?)
+    }
+
+    /// Number of stored points waiting to be indexed
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether a stored point is still waiting to be indexed
+    pub fn is_pending(&self, id: Id) -> bool {
+        self.pending.contains(id)
+    }
+
+    /// Place a point under an ID chosen by the caller (a shard router, say)
+    pub fn place_with_id(&mut self, id: Id, point: Point, blob: Blob) -> PlaceResult<()> {
+        let start = clock::now_micros();
+        let result = self.place_unmeasured(point, blob, None, Some(id), Indexing::Now);
+        self.record(Operation::Place, start, result.is_ok());
+        result.map(|_| ())
+    }
+
+    /// Place a point from a conversation turn, for role-aware salience
+    pub fn place_with_role(&mut self, point: Point, blob: Blob, role: Role) -> PlaceResult<Id> {
+        let start = clock::now_micros();
+        let result = self.place_unmeasured(point, blob, Some(role), None, Indexing::Now);
+        self.record(Operation::Place, start, result.is_ok());
+        result
+    }
+
+    fn place_unmeasured(
+        &mut self,
+        point: Point,
+        mut blob: Blob,
+        role: Option<Role>,
+        id: Option<Id>,
+        indexing: Indexing,
+    ) -> PlaceResult<Id> {
+        let point = self
+            .config
+            .non_finite
+            .apply(point)
+            .map_err(|index| crate::ports::PlaceError::NonFinite { index })?;
+
         // Normalize if configured
         let point = if self.config.normalize_on_insert {
             point.normalize()
@@ -88,18 +488,92 @@ impl Arms {
             point
         };
 
-        // Store in storage
-        let id = self.storage.place(point.clone(), blob)?;
+        for interceptor in &self.interceptors {
+            match interceptor.intercept(&IngestInput { point: &point, blob: &blob, role }) {
+                Verdict::Accept => {}
+                Verdict::Replace(replacement) => blob = replacement,
+                Verdict::Reject(reason) => {
+                    return Err(crate::ports::PlaceError::Rejected { by: interceptor.name().to_string(), reason });
+                }
+            }
+        }
+
+        // Score against what's stored before the point joins it
+        let salience = self.salience.as_ref().map(|salience| {
+            let nearest = self
+                .neighbors(&point, 1)
+                .ok()
+                .and_then(|results| results.first().map(|r| r.score));
+            let input = SalienceInput {
+                point: &point,
+                blob: &blob,
+                role,
+                nearest,
+                order: self.config.proximity.order(),
+            };
+            let score = salience.salience(&input);
+            if score.is_nan() { 0.0 } else { score.clamp(0.0, 1.0) }
+        });
+
+        // Store in storage (the clone shares the vector with the index's copy)
+        let id = match (id, &mut self.ids) {
+            (Some(id), _) => {
+                self.storage.place_with_id(id, point.clone(), blob)?;
+                id
+            }
+            (None, Some(ids)) => {
+                // Skip IDs already taken (e.g., by a restored snapshot)
+                let mut id = ids.next_id();
+                while self.storage.get(id).is_some() {
+                    id = ids.next_id();
+                }
+                self.storage.place_with_id(id, point.clone(), blob)?;
+                id
+            }
+            (None, None) => self.storage.place(point.clone(), blob)?,
+        };
+
+        // Keep the index in sync with anything evicted to make room
+        for evicted in self.storage.take_evicted() {
+            if self.pending.is_in_flight(evicted) {
+                // Let the worker's add land before undoing it
+                let _ = self.finish_pending_drain();
+            }
+            let _ = self.index_mut().remove(evicted);
+            self.saliences.remove(&evicted);
+            self.pending.remove(evicted);
+            if let Some(build) = &mut self.index_build {
+                build.record(evicted, None);
+            }
+            if let Some(cache) = &self.cache {
+                cache.invalidate_id(evicted);
+            }
+        }
+
+        // Add to index (or queue it)
+        match indexing {
+            Indexing::Now => {
+                let index_point = self.index_point(&point);
+                let added = self.index_mut().add(id, &index_point);
+                if let Err(e) = added {
+                    // Rollback storage if index fails
+                    self.storage.remove(id);
+                    return Err(crate::ports::PlaceError::Index(e));
+                }
+                if let Some(build) = &mut self.index_build {
+                    build.record(id, Some(index_point));
+                }
+            }
+            Indexing::Deferred(priority) => {
+                self.pending.push(id, priority.or(salience).unwrap_or(0.0));
+            }
+        }
 
-        // Add to index
-        if let Err(e) = self.index.add(id, &point) {
-            // Rollback storage if index fails
-            self.storage.remove(id);
-            return Err(crate::ports::PlaceError::StorageError(format!(
-                "Index error: {:?}",
-                e
-            )));
+        if let Some(salience) = salience {
+            self.saliences.insert(id, salience);
+            self.storage.set_salience(id, salience);
         }
+        self.invalidate_cached(id, &point);
 
         Ok(id)
     }
@@ -114,11 +588,81 @@ impl Arms {
 
     /// Remove a point from the space
     pub fn remove(&mut self, id: Id) -> Option<PlacedPoint> {
-        // Remove from index first
-        let _ = self.index.remove(id);
+        let start = clock::now_micros();
+
+        // Remove from index first (once a worker indexing it is done)
+        if self.pending.is_in_flight(id) {
+            let _ = self.finish_pending_drain();
+        }
+        let _ = self.index_mut().remove(id);
+        if let Some(build) = &mut self.index_build {
+            build.record(id, None);
+        }
 
         // Then from storage
-        self.storage.remove(id)
+        let removed = self.storage.remove(id);
+        self.saliences.remove(&id);
+        self.pending.remove(id);
+        if let Some(cache) = &self.cache {
+            cache.invalidate_id(id);
+        }
+
+        self.record(Operation::Remove, start, removed.is_some());
+        removed
+    }
+
+    /// Hard-delete every point `filter` matches, compact storage and purge
+    /// the erasure targets
+    ///
+    /// The points are gone from the store once this returns; the compaction
+    /// purges them from storage history (write-ahead log entries,
+    /// tombstones). The targets are purged whether or not it succeeds. A
+    /// failed compaction, targets that fail to purge, and a query log
+    /// holding erased IDs are listed in `ErasureReport::retained`: the
+    /// points no longer match, so a second call can't finish the job.
+    pub fn erase_where<F>(&mut self, filter: F) -> PlaceResult<ErasureReport>
+    where
+        F: Fn(&PlacedPoint) -> bool,
+    {
+        let matched: Vec<Id> = self.storage.iter().filter(|placed| filter(placed)).map(|placed| placed.id).collect();
+        let mut report = ErasureReport::default();
+        for id in matched {
+            if let Some(placed) = self.remove(id) {
+                report.bytes += placed.point.dimensionality() * std::mem::size_of::<f32>() + placed.blob.size();
+                report.erased.push(id);
+            }
+        }
+        if report.is_empty() {
+            return Ok(report);
+        }
+        match self.storage.compact() {
+            Ok(compacted) => report.compacted = compacted,
+            Err(e) => report.retained.push(format!("storage history (compaction failed): {}", e)),
+        }
+        for target in &self.erasure_targets {
+            match target.purge(&report.erased) {
+                Ok(()) => report.purged.push(target.describe()),
+                Err(e) => report.retained.push(format!("{}: {}", target.describe(), e)),
+            }
+        }
+        if self.query_log.is_some() {
+            report.retained.push("query log: IDs of erased points in logged results".to_string());
+        }
+        Ok(report)
+    }
+
+    /// Stage changes in a copy-on-write view (see `ArmsFork`)
+    ///
+    /// The fork sees everything stored here; what it places and removes
+    /// reaches this store only on `ArmsFork::commit`.
+    pub fn fork(&mut self) -> ArmsFork<'_> {
+        let ids = self.ids.clone();
+        ArmsFork::new(self, ids)
+    }
+
+    /// Continue the deterministic ID sequence from a committed fork's
+    pub(super) fn resume_ids(&mut self, ids: SeededIds) {
+        self.ids = Some(ids);
     }
 
     /// Get a point by ID
@@ -126,6 +670,72 @@ impl Arms {
         self.storage.get(id)
     }
 
+    /// Iterate over all stored points (in storage order)
+    pub fn iter(&self) -> impl Iterator<Item = &PlacedPoint> + '_ {
+        self.storage.iter()
+    }
+
+    /// Uniformly sample up to `n` stored points that pass `filter`
+    ///
+    /// One pass of reservoir sampling over storage, holding only `n`
+    /// references: for evaluation sets, drift statistics or PCA training
+    /// without a full export. Seeded from the deterministic seed when one
+    /// is configured, else from the clock.
+    pub fn sample<F>(&self, n: usize, filter: F) -> Vec<&PlacedPoint>
+    where
+        F: Fn(&PlacedPoint) -> bool,
+    {
+        let seed = self.config.deterministic.unwrap_or_else(clock::now_micros);
+        self.sample_with_seed(n, seed, filter)
+    }
+
+    /// `sample` with an explicit seed
+    pub fn sample_with_seed<F>(&self, n: usize, seed: u64, filter: F) -> Vec<&PlacedPoint>
+    where
+        F: Fn(&PlacedPoint) -> bool,
+    {
+        Rng::new(seed).reservoir(self.storage.iter().filter(|p| filter(p)), n)
+    }
+
+    /// Cluster the stored points to discover recurring topics
+    ///
+    /// Runs `kmeans` over full stored vectors with the configured proximity
+    /// and keeps the result for `clusters`. Points placed later aren't
+    /// assigned; use `Clustering::nearest` or run discovery again.
+    pub fn discover_clusters(&mut self, kmeans: &KMeans) -> &Clustering {
+        let points = self.storage.iter().map(|p| (p.id, &p.point));
+        let clustering = kmeans.fit(points, self.config.proximity.clone());
+        self.clusters.insert(clustering)
+    }
+
+    /// Clusters from the last `discover_clusters`
+    pub fn clusters(&self) -> Option<&Clustering> {
+        self.clusters.as_ref()
+    }
+
+    /// Lay out a sample of up to `sample_size` stored points in 2-D
+    ///
+    /// Points with a UTF-8 payload carry it as `"text"` metadata. Chain
+    /// `Projection::with_clusters` to color by discovered topic.
+    pub fn project_2d(&self, sample_size: usize) -> Projection {
+        self.project_2d_with(sample_size, &Projector::new())
+    }
+
+    /// `project_2d` with explicit layout settings (the seed also picks the sample)
+    pub fn project_2d_with(&self, sample_size: usize, projector: &Projector) -> Projection {
+        let sample = self.sample_with_seed(sample_size, projector.seed, |_| true);
+        let mut projection = projector.project(
+            sample.iter().map(|p| (p.id, &p.point)),
+            self.config.proximity.as_ref(),
+        );
+        for (projected, placed) in projection.points.iter_mut().zip(&sample) {
+            if let Some(text) = placed.blob.as_str().filter(|t| !t.is_empty()) {
+                projected.metadata.insert("text".to_string(), text.into());
+            }
+        }
+        projection
+    }
+
     /// Check if a point exists
     pub fn contains(&self, id: Id) -> bool {
         self.storage.contains(id)
@@ -143,8 +753,90 @@ impl Arms {
 
     /// Clear all points
     pub fn clear(&mut self) {
+        if let Some(drain) = self.drain.take() {
+            drain.cancel();
+            drain.finish();
+        }
         self.storage.clear();
-        let _ = self.index.rebuild(); // Reset index
+        let _ = self.index_mut().rebuild(); // Reset index
+        self.index_build = None;
+        self.clusters = None;
+        self.saliences.clear();
+        self.pending.clear();
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Replace the index with `index`, filled on a background thread
+    ///
+    /// `index` should be empty. Until the build is done, queries run on a
+    /// provisional `FlatIndex` (exact, but a full scan); places and removes
+    /// keep working and are replayed on `index` before it takes over.
+    /// Starting another build abandons this one.
+    pub fn build_index_in_background(&mut self, index: Box<dyn Near>) -> NearResult<()> {
+        self.index_build = None;
+        let _ = self.finish_pending_drain();
+
+        let points: Vec<(Id, Point)> = self
+            .storage
+            .iter()
+            .map(|p| (p.id, self.index_point(&p.point)))
+            .collect();
+        let mut flat = FlatIndex::from_proximity(self.config.index_dimensionality(), self.config.proximity.clone());
+        for (id, point) in &points {
+            flat.add(*id, point)?;
+        }
+
+        // Every stored point goes in, queued ones included
+        self.pending.clear();
+        *self.index_mut() = Box::new(flat);
+        self.index_build = Some(IndexBuild::start(index, points));
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+        Ok(())
+    }
+
+    /// Progress of the background index build (None = no build running)
+    pub fn build_progress(&self) -> Option<JobProgress> {
+        self.index_build.as_ref().map(IndexBuild::progress)
+    }
+
+    /// Handle of the background index build, to track it in `Jobs` or cancel it
+    ///
+    /// A cancelled build fails at `poll_index_build`; the flat index keeps serving.
+    pub fn index_build_handle(&self) -> Option<&JobHandle> {
+        self.index_build.as_ref().map(IndexBuild::handle)
+    }
+
+    /// Switch to the background-built index if it's done
+    ///
+    /// Returns whether it switched. If the build failed, the error is
+    /// returned and the flat index keeps serving.
+    pub fn poll_index_build(&mut self) -> NearResult<bool> {
+        match &self.index_build {
+            Some(build) if build.is_finished() => self.finish_index_build(),
+            _ => Ok(false),
+        }
+    }
+
+    /// Wait for the background index build and switch to it
+    ///
+    /// Returns false if no build was running.
+    pub fn finish_index_build(&mut self) -> NearResult<bool> {
+        if self.index_build.is_none() {
+            return Ok(false);
+        }
+        let _ = self.finish_pending_drain();
+        let Some(build) = self.index_build.take() else {
+            return Ok(false);
+        };
+        *self.index_mut() = build.finish()?;
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+        Ok(true)
     }
 
     // ========================================================================
@@ -153,28 +845,406 @@ impl Arms {
 
     /// Find k nearest points to query
     pub fn near(&self, query: &Point, k: usize) -> NearResult<Vec<SearchResult>> {
-        // Normalize query if configured
-        let query = if self.config.normalize_on_insert {
-            query.normalize()
-        } else {
-            query.clone()
+        let query = self.prepare_query(query)?;
+
+        let start = clock::now_micros();
+        if let Some(cached) = self.cache.as_ref().and_then(|c| c.get_near(&query, k)) {
+            self.record(Operation::Near, start, true);
+            let results = Ok(cached);
+            self.log_query(Operation::Near, &query, &QueryParams::new(k), None, start, results.as_deref().ok(), true);
+            return results;
+        }
+        let results = self.salient(k, |k| self.near_indexed(&query, k));
+        if let (Some(cache), Ok(results)) = (&self.cache, &results) {
+            cache.put_near(&query, k, results);
+        }
+        self.record(Operation::Near, start, results.is_ok());
+        self.log_query(Operation::Near, &query, &QueryParams::new(k), None, start, results.as_deref().ok(), true);
+        results
+    }
+
+    /// Find k nearest points, returning best-effort results if `deadline` expires
+    ///
+    /// Check `PartialResults::complete` to tell a finished search from one
+    /// that was cut short by the deadline or its cancellation token.
+    pub fn near_with_deadline(&self, query: &Point, k: usize, deadline: &Deadline) -> NearResult<PartialResults> {
+        let query = self.prepare_query(query)?;
+
+        let start = clock::now_micros();
+        let mut complete = true;
+        let results = self
+            .salient(k, |k| {
+                let partial = match self.config.matryoshka {
+                    Some(ref m) => self.check_query(&query).and_then(|()| {
+                        let candidates = self.index().near_with_deadline(
+                            &query.prefix(m.prefix_dims),
+                            k.saturating_mul(m.oversample),
+                            deadline,
+                        )?;
+                        Ok(PartialResults {
+                            results: self.rescore(&query, candidates.results, k),
+                            complete: candidates.complete,
+                        })
+                    }),
+                    None => self.index().near_with_deadline(&query, k, deadline),
+                }?;
+                complete = partial.complete;
+                Ok(self.with_pending(&query, &QueryParams::new(k), partial.results))
+            })
+            .map(|results| PartialResults { results, complete });
+        self.record(Operation::Near, start, results.is_ok());
+        let (logged, complete) = match &results {
+            Ok(partial) => (Some(partial.results.as_slice()), partial.complete),
+            Err(_) => (None, true),
         };
+        self.log_query(Operation::Near, &query, &QueryParams::new(k), None, start, logged, complete);
+        results
+    }
+
+    /// Find nearest points with per-query overrides
+    ///
+    /// `params.rescore` replaces the Matryoshka oversampling: `Some(0)`
+    /// returns index scores as they are, `Some(n)` rescores `k * n`
+    /// candidates on full stored vectors (with or without Matryoshka).
+    /// `min_score` applies to final scores, and `dedup` compares full
+    /// stored vectors. Filtered and overridden queries bypass the query
+    /// cache.
+    pub fn near_with_params(&self, query: &Point, params: &QueryParams) -> NearResult<Vec<SearchResult>> {
+        let query = self.prepare_query(query)?;
+
+        let start = clock::now_micros();
+        let results = self.check_query(&query).and_then(|()| {
+            self.salient(params.k, |k| {
+                let params = QueryParams { k, ..params.clone() };
+                match params.dedup {
+                    Some(threshold) => self.near_distinct(&query, &params, threshold),
+                    None => self.near_params(&query, &params),
+                }
+            })
+        });
+        self.record(Operation::Near, start, results.is_ok());
+        self.log_query(Operation::Near, &query, params, None, start, results.as_deref().ok(), true);
+        results
+    }
 
-        self.index.near(&query, k)
+    /// `near` without the cache or salience: index results (rescored with
+    /// Matryoshka) merged with queued points
+    fn near_indexed(&self, query: &Point, k: usize) -> NearResult<Vec<SearchResult>> {
+        let results = match self.config.matryoshka {
+            Some(ref m) => self.near_rescored(query, k, m.prefix_dims, m.oversample),
+            None => self.index().near(query, k),
+        };
+        results.map(|results| self.with_pending(query, &QueryParams::new(k), results))
+    }
+
+    /// `near_with_params` without deduplication
+    fn near_params(&self, query: &Point, params: &QueryParams) -> NearResult<Vec<SearchResult>> {
+        let oversample = params
+            .rescore
+            .or(self.config.matryoshka.as_ref().map(|m| m.oversample))
+            .unwrap_or(0);
+        let index_query = self.index_point(query);
+        if oversample == 0 {
+            let results = self.index().near_with_params(&index_query, params)?;
+            return Ok(self.with_pending(query, params, results));
+        }
+
+        // Index scores aren't final, so the cutoff waits for rescoring
+        let wide = QueryParams {
+            k: params.k.saturating_mul(oversample),
+            min_score: None,
+            ..params.clone()
+        };
+        let candidates = self.index().near_with_params(&index_query, &wide)?;
+        let mut results = self.rescore(query, candidates, params.k);
+        results.retain(|r| params.passes(r.score, self.config.proximity.order()));
+        Ok(self.with_pending(query, params, results))
+    }
+
+    /// Merge exactly scored pending points into index results (when
+    /// `WriteBehind::scan_pending` is on)
+    fn with_pending(&self, query: &Point, params: &QueryParams, results: Vec<SearchResult>) -> Vec<SearchResult> {
+        if !self.scans_pending() {
+            return results;
+        }
+        // Points a worker is indexing may already be in the results
+        let found: HashSet<Id> = results.iter().map(|r| r.id).collect();
+        let order = self.config.proximity.order();
+        let mut top = TopK::new(params.k, order);
+        for result in results {
+            top.push(result);
+        }
+        for id in self.pending.ids().filter(|&id| params.accepts(id) && !found.contains(&id)) {
+            if let Some(result) = self.full_score(query, id).filter(|r| params.passes(r.score, order)) {
+                top.push(result);
+            }
+        }
+        top.into_sorted_vec()
+    }
+
+    /// Whether queries have queued points to scan
+    fn scans_pending(&self) -> bool {
+        self.write_behind.scan_pending && !self.pending.is_empty()
+    }
+
+    /// `near_params`, widened until `k` results survive deduplication
+    fn near_distinct(&self, query: &Point, params: &QueryParams, threshold: f32) -> NearResult<Vec<SearchResult>> {
+        let mut wide = QueryParams { dedup: None, ..params.clone() };
+        loop {
+            let results = self.near_params(query, &wide)?;
+            let exhausted = results.len() < wide.k || wide.k >= self.len();
+            let kept = collapse_duplicates(results, params.k, threshold, self.config.proximity.as_ref(), |id| {
+                self.storage.get(id).map(|placed| &placed.point)
+            });
+            if kept.len() == params.k || exhausted {
+                return Ok(kept);
+            }
+            wide.k = wide.k.saturating_mul(4).max(1);
+        }
+    }
+
+    /// Visit the k nearest points, most relevant first, without collecting them
+    ///
+    /// Return `ControlFlow::Break(())` from the visitor to stop early.
+    /// Results stream from the index unless Matryoshka rescoring, queued
+    /// points or salience need them all before the first visit.
+    pub fn near_visit<F>(&self, query: &Point, k: usize, mut visitor: F) -> NearResult<()>
+    where
+        F: FnMut(SearchResult) -> ControlFlow<()>,
+    {
+        let query = self.prepare_query(query)?;
+
+        let start = clock::now_micros();
+        // Only the results visited are logged
+        let mut visited = Vec::new();
+        let mut visit = |r: SearchResult| {
+            if self.query_log.is_some() {
+                visited.push(r.clone());
+            }
+            visitor(r)
+        };
+        let streams = self.config.matryoshka.is_none() && !self.scans_pending() && self.salience.is_none();
+        let result = if streams {
+            self.index().near_visit(&query, k, &mut visit)
+        } else {
+            self.salient(k, |k| self.near_indexed(&query, k)).map(|results| {
+                for r in results {
+                    if visit(r).is_break() {
+                        break;
+                    }
+                }
+            })
+        };
+        self.record(Operation::Near, start, result.is_ok());
+        let logged = result.as_ref().ok().map(|()| visited.as_slice());
+        self.log_query(Operation::Near, &query, &QueryParams::new(k), None, start, logged, true);
+        result
     }
 
     /// Find all points within threshold
+    ///
+    /// With Matryoshka prefix indexing, candidates are the points whose
+    /// prefix passes the threshold, filtered again on full vectors; points
+    /// that only pass on full vectors can be missed. Salience is attached
+    /// and, with a bias, reorders the results, but never admits a point
+    /// outside the threshold.
     pub fn within(&self, query: &Point, threshold: f32) -> NearResult<Vec<SearchResult>> {
-        let query = if self.config.normalize_on_insert {
+        let query = self.prepare_query(query)?;
+
+        let start = clock::now_micros();
+        if let Some(cached) = self.cache.as_ref().and_then(|c| c.get_within(&query, threshold)) {
+            self.record(Operation::Within, start, true);
+            let results = Ok(cached);
+            self.log_query(Operation::Within, &query, &QueryParams::new(0), Some(threshold), start, results.as_deref().ok(), true);
+            return results;
+        }
+        // Every result within the threshold is kept, so nothing is truncated
+        let results = self.salient(usize::MAX, |_| {
+            let mut results = match self.config.matryoshka {
+                Some(ref m) => self.within_rescored(&query, threshold, m.prefix_dims),
+                None => self.index().within(&query, threshold),
+            }?;
+            if self.write_behind.scan_pending {
+                let order = self.config.proximity.order();
+                let before = results.len();
+                let found: HashSet<Id> = results.iter().map(|r| r.id).collect();
+                results.extend(
+                    self.pending
+                        .ids()
+                        .filter(|id| !found.contains(id))
+                        .filter_map(|id| self.full_score(&query, id))
+                        .filter(|r| order.passes(r.score, threshold)),
+                );
+                if results.len() > before {
+                    SearchResult::sort(&mut results, order);
+                }
+            }
+            Ok(results)
+        });
+        if let (Some(cache), Ok(results)) = (&self.cache, &results) {
+            cache.put_within(&query, threshold, results);
+        }
+        self.record(Operation::Within, start, results.is_ok());
+        self.log_query(Operation::Within, &query, &QueryParams::new(0), Some(threshold), start, results.as_deref().ok(), true);
+        results
+    }
+
+    /// Top `k` from `fetch`, with salience attached and, with a bias, ranked
+    /// by score plus bias
+    ///
+    /// Fetches extra candidates so salient points just outside the top `k`
+    /// can move in.
+    fn salient<F>(&self, k: usize, fetch: F) -> NearResult<Vec<SearchResult>>
+    where
+        F: FnOnce(usize) -> NearResult<Vec<SearchResult>>,
+    {
+        if self.salience.is_none() {
+            return fetch(k);
+        }
+        let bias = self.salience_bias;
+        let wide = if bias > 0.0 { k.saturating_mul(SALIENCE_OVERSAMPLE) } else { k };
+
+        let mut results: Vec<SearchResult> = fetch(wide)?
+            .into_iter()
+            .map(|r| match self.saliences.get(&r.id) {
+                Some(&salience) => r.with_salience(salience),
+                None => r,
+            })
+            .collect();
+        if bias > 0.0 {
+            let order = self.config.proximity.order();
+            let biased = |r: &SearchResult| order.to_distance(r.score) - bias * r.salience.unwrap_or(0.0);
+            results.sort_by(|a, b| biased(a).total_cmp(&biased(b)).then_with(|| a.id.cmp(&b.id)));
+        }
+        results.truncate(k);
+        Ok(results)
+    }
+
+    /// Drop cached results a newly placed point could change, ranking it
+    /// with its salience as `salient` would
+    fn invalidate_cached(&self, id: Id, point: &Point) {
+        if let Some(cache) = &self.cache {
+            let bias = if self.salience.is_some() { self.salience_bias.max(0.0) } else { 0.0 };
+            let salience = self.saliences.get(&id).copied().unwrap_or(0.0);
+            cache.invalidate_point(point, salience, bias, self.config.proximity.as_ref());
+        }
+    }
+
+    /// Apply the non-finite policy and normalization to a query
+    fn prepare_query(&self, query: &Point) -> NearResult<Point> {
+        let query = self
+            .config
+            .non_finite
+            .apply(query.clone())
+            .map_err(|index| NearError::NonFinite { index })?;
+        Ok(if self.config.normalize_on_insert {
             query.normalize()
         } else {
-            query.clone()
-        };
+            query
+        })
+    }
+
+    /// The part of a point handed to the index
+    fn index_point(&self, point: &Point) -> Point {
+        match self.config.matryoshka {
+            Some(ref m) => point.prefix(m.prefix_dims),
+            None => point.clone(),
+        }
+    }
+
+    /// Reject queries that don't have the full dimensionality
+    fn check_query(&self, query: &Point) -> NearResult<()> {
+        if query.dimensionality() != self.config.dimensionality {
+            return Err(NearError::DimensionalityMismatch {
+                expected: self.config.dimensionality,
+                got: query.dimensionality(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Score a candidate with its full vector from storage
+    fn full_score(&self, query: &Point, id: Id) -> Option<SearchResult> {
+        let placed = self.storage.get(id)?;
+        Some(SearchResult::new(id, self.config.proximity.proximity(query, &placed.point)))
+    }
+
+    /// Top k from the prefix index, rescored on full vectors
+    fn near_rescored(&self, query: &Point, k: usize, prefix_dims: usize, oversample: usize) -> NearResult<Vec<SearchResult>> {
+        self.check_query(query)?;
+        let candidates = self.index().near(&query.prefix(prefix_dims), k.saturating_mul(oversample))?;
+        Ok(self.rescore(query, candidates, k))
+    }
+
+    /// Best k of prefix-index candidates, scored on full vectors
+    fn rescore(&self, query: &Point, candidates: Vec<SearchResult>, k: usize) -> Vec<SearchResult> {
+        let mut top = TopK::new(k, self.config.proximity.order());
+        for candidate in candidates {
+            if let Some(result) = self.full_score(query, candidate.id) {
+                top.push(result);
+            }
+        }
+        top.into_sorted_vec()
+    }
+
+    /// Points within threshold on the prefix index, filtered on full vectors
+    fn within_rescored(&self, query: &Point, threshold: f32, prefix_dims: usize) -> NearResult<Vec<SearchResult>> {
+        self.check_query(query)?;
+        let candidates = self.index().within(&query.prefix(prefix_dims), threshold)?;
+
+        let order = self.config.proximity.order();
+        let mut results: Vec<SearchResult> = candidates
+            .into_iter()
+            .filter_map(|c| self.full_score(query, c.id))
+            .filter(|r| order.passes(r.score, threshold))
+            .collect();
+        SearchResult::sort(&mut results, order);
+        Ok(results)
+    }
 
-        self.index.within(&query, threshold)
+    /// `near_with_params` with the stored fields `params.fields` asks for
+    ///
+    /// Arms keeps no typed metadata or provenance, so `metadata` and
+    /// `provenance` are always `None`.
+    pub fn near_fields(&self, query: &Point, params: &QueryParams) -> NearResult<Vec<ProjectedResult>> {
+        let results = self.near_with_params(query, params)?;
+        Ok(self.project(&results, params.fields))
+    }
+
+    /// Attach stored fields to search results
+    pub fn project(&self, results: &[SearchResult], fields: ReturnFields) -> Vec<ProjectedResult> {
+        results
+            .iter()
+            .map(|result| {
+                let mut projected = ProjectedResult::bare(result, fields);
+                if fields.vector || fields.blob {
+                    if let Some(placed) = self.storage.get(result.id) {
+                        projected.vector = fields.vector.then(|| placed.point.clone());
+                        projected.blob = fields.blob.then(|| placed.blob.clone());
+                    }
+                }
+                projected
+            })
+            .collect()
+    }
+
+    /// Find k nearest points, reading their blobs only when asked
+    ///
+    /// Reads nothing beyond the index and stored vectors until
+    /// `LazyResult::blob`, so a wide candidate scan doesn't fetch every
+    /// spilled payload.
+    pub fn near_lazy(&self, query: &Point, k: usize) -> NearResult<Vec<LazyResult<'_>>> {
+        let results = self.near(query, k)?;
+
+        Ok(results
+            .into_iter()
+            .filter_map(|r| self.storage.get(r.id).map(|p| LazyResult::new(p, r.score)))
+            .collect())
     }
 
     /// Find and retrieve k nearest points (with full data)
+    ///
+    /// See `near_lazy` for results that defer reading spilled blobs.
     pub fn near_with_data(&self, query: &Point, k: usize) -> NearResult<Vec<(&PlacedPoint, f32)>> {
         let results = self.near(query, k)?;
 
@@ -184,6 +1254,103 @@ impl Arms {
             .collect())
     }
 
+    /// How unlike the stored points `point` is, in `[0, 1]`
+    ///
+    /// Compares the mean distance from `point` to its nearest stored points
+    /// with the same spread for each of those neighbors, so the score
+    /// follows local density: near 0 is a duplicate, around 0.5 sits as far
+    /// out as its neighbors do, near 1 is an outlier. 1 when nothing is
+    /// stored. Check it before `place` to skip observations already known.
+    pub fn novelty(&self, point: &Point) -> NearResult<f32> {
+        let point = self.prepare_query(point)?;
+        self.check_query(&point)?;
+
+        let neighbors = self.neighbors(&point, NOVELTY_NEIGHBORS)?;
+        let Some(own) = self.spread(&neighbors) else {
+            return Ok(1.0);
+        };
+
+        let mut local = Vec::with_capacity(neighbors.len());
+        for neighbor in &neighbors {
+            let Some(placed) = self.storage.get(neighbor.id) else { continue };
+            local.extend(self.spread(&self.neighbors_of(placed)?));
+        }
+        Ok(Self::outlier_score(own, &local))
+    }
+
+    /// The `n` stored points in the sparsest surroundings, most isolated first
+    ///
+    /// An approximate local outlier factor: each point's spread to its
+    /// nearest neighbors (found through the index) against theirs, scored
+    /// as in `novelty`. Surfaces garbage embeddings and corrupted ingests;
+    /// costs one index query per stored point.
+    pub fn outliers(&self, n: usize) -> NearResult<Vec<(Id, f32)>> {
+        let mut neighborhoods = HashMap::with_capacity(self.storage.len());
+        for placed in self.storage.iter() {
+            let neighbors = self.neighbors_of(placed)?;
+            let spread = self.spread(&neighbors);
+            neighborhoods.insert(placed.id, (neighbors, spread));
+        }
+
+        let mut scored: Vec<(Id, f32)> = neighborhoods
+            .iter()
+            .filter_map(|(&id, (neighbors, spread))| {
+                let local: Vec<f32> = neighbors
+                    .iter()
+                    .filter_map(|r| neighborhoods.get(&r.id).and_then(|(_, spread)| *spread))
+                    .collect();
+                spread.map(|own| (id, Self::outlier_score(own, &local)))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        scored.truncate(n);
+        Ok(scored)
+    }
+
+    /// Nearest neighbors of a stored point, excluding itself
+    fn neighbors_of(&self, placed: &PlacedPoint) -> NearResult<Vec<SearchResult>> {
+        Ok(self
+            .neighbors(&placed.point, NOVELTY_NEIGHBORS + 1)?
+            .into_iter()
+            .filter(|r| r.id != placed.id)
+            .take(NOVELTY_NEIGHBORS)
+            .collect())
+    }
+
+    /// Mean distance to a set of neighbors (None = no neighbors)
+    fn spread(&self, neighbors: &[SearchResult]) -> Option<f32> {
+        let order = self.config.proximity.order();
+        let total: f32 = neighbors.iter().map(|r| order.to_distance(r.score).max(0.0)).sum();
+        (!neighbors.is_empty()).then(|| total / neighbors.len() as f32)
+    }
+
+    /// Own spread against the neighbors' mean spread, in `[0, 1]`
+    fn outlier_score(own: f32, local: &[f32]) -> f32 {
+        // A lone stored point has no spread to compare with
+        if local.is_empty() {
+            return own / (1.0 + own);
+        }
+        let local = local.iter().sum::<f32>() / local.len() as f32;
+        if own + local > 0.0 { own / (own + local) } else { 0.0 }
+    }
+
+    /// The index, shared with a running `PendingDrain`
+    fn index(&self) -> RwLockReadGuard<'_, Box<dyn Near>> {
+        self.index.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn index_mut(&self) -> RwLockWriteGuard<'_, Box<dyn Near>> {
+        self.index.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Top k on full vectors, bypassing cache, salience and metrics
+    fn neighbors(&self, point: &Point, k: usize) -> NearResult<Vec<SearchResult>> {
+        match self.config.matryoshka {
+            Some(ref m) => self.near_rescored(point, k, m.prefix_dims, m.oversample),
+            None => self.index().near(point, k),
+        }
+    }
+
     // ========================================================================
     // MERGE OPERATIONS
     // ========================================================================
@@ -207,14 +1374,42 @@ impl Arms {
         self.storage.size_bytes()
     }
 
+    /// Approximate heap bytes by component (storage, index, caches)
+    pub fn memory_report(&self) -> MemoryReport {
+        let index = self.index().memory_usage();
+        MemoryReport {
+            storage: self.storage.size_bytes(),
+            index_vectors: index.vectors,
+            index_structure: index.structure,
+            index_metadata: index.metadata,
+            query_cache: self.cache.as_ref().map_or(0, |c| c.size_bytes()),
+        }
+    }
+
     /// Get index stats
     pub fn index_len(&self) -> usize {
-        self.index.len()
+        self.index().len()
     }
 
     /// Check if index is ready
     pub fn is_ready(&self) -> bool {
-        self.index.is_ready()
+        self.index().is_ready()
+    }
+
+    /// Current size gauges (points, index entries, bytes, process memory)
+    pub fn gauges(&self) -> Vec<Gauge> {
+        let memory = self.memory_report();
+        let mut gauges = vec![
+            Gauge::new("arms_points", "Points in storage.", self.len() as f64),
+            Gauge::new("arms_index_points", "Points in the index.", self.index_len() as f64),
+            Gauge::new("arms_storage_bytes", "Approximate storage size in bytes.", self.size_bytes() as f64),
+            Gauge::new("arms_index_bytes", "Approximate index size in bytes.", memory.index() as f64),
+            Gauge::new("arms_query_cache_bytes", "Approximate query cache size in bytes.", memory.query_cache as f64),
+        ];
+        if let Some(rss) = metrics::resident_memory_bytes() {
+            gauges.push(Gauge::new("process_resident_memory_bytes", "Resident memory size in bytes.", rss as f64));
+        }
+        gauges
     }
 }
 
@@ -257,6 +1452,17 @@ mod tests {
         assert!(results[0].score > results[1].score);
     }
 
+    #[test]
+    fn test_arms_iter() {
+        let mut arms = create_test_arms();
+        arms.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::from_str("x")).unwrap();
+        arms.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::from_str("y")).unwrap();
+
+        let mut blobs: Vec<_> = arms.iter().filter_map(|p| p.blob.as_str()).collect();
+        blobs.sort();
+        assert_eq!(blobs, vec!["x", "y"]);
+    }
+
     #[test]
     fn test_arms_near_with_data() {
         let mut arms = create_test_arms();
@@ -271,6 +1477,49 @@ mod tests {
         assert_eq!(results[0].0.blob.as_str(), Some("x"));
     }
 
+    #[test]
+    fn test_arms_near_lazy_defers_spilled_blobs() {
+        use crate::adapters::storage::DirSpillStore;
+
+        let dir = crate::testing::temp_path("arms-lazy");
+        let config = ArmsConfig::new(3);
+        let storage = MemoryStorage::new(3).with_blob_spill(8, DirSpillStore::new(&dir).unwrap());
+        let index = FlatIndex::from_proximity(3, config.proximity.clone());
+        let mut arms = Arms::with_adapters(config, Box::new(storage), Box::new(index));
+
+        let big = arms.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::new(vec![9; 1000])).unwrap();
+        arms.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::from_str("y")).unwrap();
+
+        let results = arms.near_lazy(&Point::new(vec![1.0, 0.1, 0.0]), 2).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, big);
+        assert_eq!((results[0].blob_size(), results[0].is_loaded()), (1000, false));
+        assert!(results[1].is_loaded());
+        assert_eq!(results[1].blob().unwrap(), b"y");
+
+        assert_eq!(results[0].blob().unwrap(), &[9; 1000][..]);
+        assert!(results[0].is_loaded());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_arms_near_fields() {
+        let mut arms = create_test_arms();
+        let id = arms.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::from_str("x")).unwrap();
+        let query = Point::new(vec![1.0, 0.0, 0.0]);
+
+        let full = arms.near_fields(&query, &QueryParams::new(1).with_fields(ReturnFields::ALL)).unwrap();
+        assert_eq!(full[0].id, id);
+        assert_eq!(full[0].vector.as_ref(), Some(&query));
+        assert_eq!(full[0].blob.as_ref().and_then(|b| b.as_str()), Some("x"));
+        assert!(full[0].metadata.is_none());
+
+        let bare = arms.near_fields(&query, &QueryParams::new(1).with_fields(ReturnFields::NONE)).unwrap();
+        assert_eq!((bare[0].id, bare[0].score), (id, full[0].score));
+        assert!(bare[0].vector.is_none() && bare[0].blob.is_none());
+    }
+
     #[test]
     fn test_arms_remove() {
         let mut arms = create_test_arms();
@@ -332,4 +1581,762 @@ mod tests {
         // Should be normalized
         assert!(retrieved.point.is_normalized());
     }
+
+    #[test]
+    fn test_arms_metrics() {
+        let metrics = Arc::new(Metrics::new());
+        let mut arms = create_test_arms().with_metrics(metrics.clone());
+
+        let id = arms.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        assert!(arms.place(Point::new(vec![1.0]), Blob::empty()).is_err());
+        arms.near(&Point::new(vec![1.0, 0.0, 0.0]), 1).unwrap();
+        arms.remove(id);
+
+        assert_eq!(metrics.count(Operation::Place), 2);
+        assert_eq!(metrics.errors(Operation::Place), 1);
+        assert_eq!(metrics.count(Operation::Near), 1);
+        assert_eq!(metrics.count(Operation::Remove), 1);
+
+        let gauges = arms.gauges();
+        assert_eq!(gauges[0], Gauge::new("arms_points", "Points in storage.", 0.0));
+    }
+
+    #[test]
+    fn test_arms_query_log() {
+        use super::super::query_log::LoggedQuery;
+        use std::sync::Mutex;
+
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let log = QueryLog::to_callback({
+            let records = records.clone();
+            move |record: &QueryRecord| records.lock().unwrap().push(record.clone())
+        });
+        let mut arms = create_test_arms().with_query_log(Arc::new(log.with_vectors(true)));
+
+        let id = arms.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        arms.near(&Point::new(vec![1.0, 0.0, 0.0]), 3).unwrap();
+        arms.within(&Point::new(vec![0.0, 1.0, 0.0]), 0.9).unwrap();
+        let params = QueryParams::new(1).with_filter(|_| true).with_min_score(0.5);
+        arms.near_with_params(&Point::new(vec![1.0, 0.0, 0.0]), &params).unwrap();
+        arms.near_visit(&Point::new(vec![1.0, 0.0, 0.0]), 3, |_| ControlFlow::Break(())).unwrap();
+        let token = crate::ports::CancellationToken::new();
+        token.cancel();
+        arms.near_with_deadline(&Point::new(vec![1.0, 0.0, 0.0]), 2, &Deadline::none().with_token(token)).unwrap();
+
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 5);
+        assert_eq!((records[0].operation, records[0].k), (Operation::Near, 3));
+        assert_eq!(records[0].query, LoggedQuery::Vector(vec![1.0, 0.0, 0.0]));
+        assert_eq!(records[0].results, vec![id]);
+        assert_eq!((records[1].operation, records[1].threshold), (Operation::Within, Some(0.9)));
+        assert!(records[1].results.is_empty() && records[1].ok);
+        assert!(records[2].filtered);
+        assert_eq!(records[2].min_score, Some(0.5));
+        assert!(records[..3].iter().all(|r| r.complete));
+
+        // Visits log what was visited; deadline queries whether they finished
+        assert_eq!((records[3].k, records[3].results.clone()), (3, vec![id]));
+        assert_eq!((records[4].k, records[4].complete), (2, false));
+    }
+
+    #[test]
+    fn test_arms_eviction_updates_index() {
+        use crate::adapters::storage::CapacityPolicy;
+
+        let config = ArmsConfig::new(3);
+        let storage = MemoryStorage::with_capacity(3, 152)
+            .with_capacity_policy(CapacityPolicy::EvictOldest);
+        let index = FlatIndex::from_proximity(3, config.proximity.clone());
+        let mut arms = Arms::with_adapters(config, Box::new(storage), Box::new(index));
+
+        let first = arms.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        arms.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::empty()).unwrap();
+        arms.place(Point::new(vec![0.0, 0.0, 1.0]), Blob::empty()).unwrap();
+
+        assert_eq!(arms.len(), 2);
+        assert_eq!(arms.index_len(), 2);
+        let results = arms.near(&Point::new(vec![1.0, 0.0, 0.0]), 3).unwrap();
+        assert!(results.iter().all(|r| r.id != first));
+    }
+
+    #[test]
+    fn test_arms_near_visit() {
+        let mut arms = create_test_arms();
+        let x = arms.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::from_str("x")).unwrap();
+        arms.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::from_str("y")).unwrap();
+
+        let mut ids = Vec::new();
+        arms.near_visit(&Point::new(vec![2.0, 0.0, 0.0]), 2, |r| {
+            ids.push(r.id);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[0], x);
+
+        // A point still queued for indexing is visited in order
+        let queued = arms.place_fast(Point::new(vec![1.0, 0.1, 0.0]), Blob::empty()).unwrap();
+        assert!(arms.is_pending(queued));
+        ids.clear();
+        arms.near_visit(&Point::new(vec![1.0, 0.2, 0.0]), 2, |r| {
+            ids.push(r.id);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(ids, vec![queued, x]);
+    }
+
+    #[test]
+    fn test_arms_matryoshka_rescoring() {
+        let config = ArmsConfig::new(4)
+            .with_proximity(crate::core::proximity::Euclidean)
+            .with_normalize(false)
+            .with_matryoshka(2, 3);
+        let mut arms = Arms::new(config);
+
+        // Identical prefixes; only the full vectors tell them apart
+        let far = arms.place(Point::new(vec![1.0, 0.0, 9.0, 9.0]), Blob::empty()).unwrap();
+        let near = arms.place(Point::new(vec![1.0, 0.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        arms.place(Point::new(vec![5.0, 5.0, 0.0, 0.0]), Blob::empty()).unwrap();
+
+        let results = arms.near(&Point::new(vec![1.0, 0.0, 0.0, 0.0]), 1).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, near);
+        assert_eq!(results[0].score, 0.0);
+
+        // The stored point keeps all dimensions
+        assert_eq!(arms.get(far).unwrap().point.dimensionality(), 4);
+
+        let within = arms.within(&Point::new(vec![1.0, 0.0, 0.0, 0.0]), 1.0).unwrap();
+        assert_eq!(within.len(), 1);
+
+        assert!(arms.near(&Point::new(vec![1.0, 0.0]), 1).is_err());
+    }
+
+    #[test]
+    fn test_arms_near_with_params() {
+        let config = ArmsConfig::new(4)
+            .with_proximity(crate::core::proximity::Euclidean)
+            .with_normalize(false)
+            .with_matryoshka(2, 3);
+        let mut arms = Arms::new(config);
+        let far = arms.place(Point::new(vec![1.0, 0.0, 9.0, 9.0]), Blob::empty()).unwrap();
+        let near = arms.place(Point::new(vec![1.0, 0.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        let query = Point::new(vec![1.0, 0.0, 0.0, 0.0]);
+
+        // Filtered before rescoring, so the best match can be excluded
+        let params = QueryParams::new(1).with_filter(move |id| id != near);
+        let results = arms.near_with_params(&query, &params).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, far);
+
+        // Without rescoring, scores come from the prefix index
+        let results = arms.near_with_params(&query, &QueryParams::new(2).with_rescore(0)).unwrap();
+        assert!(results.iter().all(|r| r.score == 0.0));
+
+        let results = arms.near_with_params(&query, &QueryParams::new(2)).unwrap();
+        assert_eq!(results[0].id, near);
+        assert!(results[1].score > 0.0);
+
+        // Copies compare on full vectors, which the prefix index can't tell apart
+        arms.place(Point::new(vec![1.0, 0.0, 0.0, 0.01]), Blob::empty()).unwrap();
+        let results = arms.near_with_params(&query, &QueryParams::new(2).with_dedup(0.1)).unwrap();
+        let ids: Vec<Id> = results.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![near, far]);
+    }
+
+    #[test]
+    fn test_arms_sample() {
+        let mut arms = create_test_arms();
+        for i in 0..100 {
+            let blob = Blob::from_str(if i % 2 == 0 { "even" } else { "odd" });
+            arms.place(Point::new(vec![1.0, i as f32, 0.0]), blob).unwrap();
+        }
+
+        let sample = arms.sample_with_seed(10, 7, |p| p.blob.as_str() == Some("even"));
+        assert_eq!(sample.len(), 10);
+        assert!(sample.iter().all(|p| p.blob.as_str() == Some("even")));
+        let mut ids: Vec<Id> = sample.iter().map(|p| p.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 10);
+
+        // Fewer matches than asked for: all of them
+        assert_eq!(arms.sample(500, |_| true).len(), 100);
+        assert!(arms.sample(0, |_| true).is_empty());
+
+        // Every point is reachable: over many seeds the sample covers the space
+        let mut seen = std::collections::HashSet::new();
+        for seed in 0..200 {
+            seen.extend(arms.sample_with_seed(5, seed, |_| true).iter().map(|p| p.id));
+        }
+        assert_eq!(seen.len(), 100);
+    }
+
+    #[test]
+    fn test_arms_discover_clusters() {
+        use crate::core::gen::Clusters;
+
+        let mut arms = Arms::new(ArmsConfig::new(8));
+        assert!(arms.clusters().is_none());
+        for (_, point) in Clusters::new(8, 2).with_seed(3).generate(60) {
+            arms.place(point, Blob::empty()).unwrap();
+        }
+
+        let clustering = arms.discover_clusters(&KMeans::new(2).with_seed(9));
+        assert_eq!(clustering.len(), 2);
+        assert_eq!(clustering.clusters()[0].size + clustering.clusters()[1].size, 60);
+
+        let representative = arms.clusters().unwrap().clusters()[1].representative;
+        assert_eq!(arms.clusters().unwrap().label_of(representative), Some(1));
+        assert!(arms.get(representative).is_some());
+
+        arms.clear();
+        assert!(arms.clusters().is_none());
+    }
+
+    #[test]
+    fn test_arms_project_2d() {
+        use crate::core::gen::Clusters;
+
+        let mut arms = Arms::new(ArmsConfig::new(8));
+        for (i, (_, point)) in Clusters::new(8, 2).with_seed(3).generate(40).into_iter().enumerate() {
+            let blob = if i == 0 { Blob::from_str("first") } else { Blob::empty() };
+            arms.place(point, blob).unwrap();
+        }
+        arms.discover_clusters(&KMeans::new(2).with_seed(9));
+
+        let projection = arms.project_2d(25).with_clusters(arms.clusters().unwrap());
+        assert_eq!(projection.len(), 25);
+        assert!(projection.points.iter().all(|p| arms.contains(p.id) && p.x.is_finite() && p.y.is_finite()));
+        assert!(projection.points.iter().all(|p| p.metadata.contains_key("cluster")));
+        assert_eq!(arms.project_2d(100).len(), 40);
+
+        let csv = arms.project_2d(100).with_clusters(arms.clusters().unwrap()).to_csv();
+        assert!(csv.starts_with("id,x,y,cluster,text\n"));
+        assert!(csv.contains(",first\n"));
+    }
+
+    #[test]
+    fn test_arms_near_with_deadline() {
+        use crate::ports::CancellationToken;
+        use std::time::Duration;
+
+        let mut arms = create_test_arms();
+        let x = arms.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        arms.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::empty()).unwrap();
+
+        let query = Point::new(vec![1.0, 0.0, 0.0]);
+        let done = arms.near_with_deadline(&query, 1, &Deadline::after(Duration::from_secs(60))).unwrap();
+        assert!(done.complete);
+        assert_eq!(done.results[0].id, x);
+
+        let token = CancellationToken::new();
+        let deadline = Deadline::none().with_token(token.clone());
+        token.cancel();
+        let cut = arms.near_with_deadline(&query, 1, &deadline).unwrap();
+        assert!(!cut.complete);
+
+        // Queued points are scanned as for `near`
+        let queued = arms.place_fast(Point::new(vec![0.0, 0.0, 1.0]), Blob::empty()).unwrap();
+        assert!(arms.is_pending(queued));
+        let query = Point::new(vec![0.0, 0.1, 1.0]);
+        let done = arms.near_with_deadline(&query, 1, &Deadline::after(Duration::from_secs(60))).unwrap();
+        assert_eq!(done.results[0].id, queued);
+    }
+
+    #[test]
+    fn test_arms_query_cache() {
+        use crate::engine::QueryCache;
+
+        let mut arms = create_test_arms().with_query_cache(QueryCache::new(16, 0.001));
+        let x = arms.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        let query = Point::new(vec![1.0, 0.1, 0.0]);
+
+        arms.near(&query, 1).unwrap();
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, x);
+        assert_eq!(arms.query_cache().unwrap().stats().hits, 1);
+
+        // A far-away point leaves the entry alone; a closer one replaces it
+        arms.place(Point::new(vec![0.0, 0.0, 1.0]), Blob::empty()).unwrap();
+        assert_eq!(arms.query_cache().unwrap().stats().entries, 1);
+        let y = arms.place(Point::new(vec![1.0, 0.1, 0.0]), Blob::empty()).unwrap();
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, y);
+
+        // Removing a cached result invalidates it
+        arms.remove(y);
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, x);
+
+        // A slightly farther point that salience ranks ahead invalidates too
+        let mut arms = create_test_arms()
+            .with_query_cache(QueryCache::new(16, 0.0))
+            .with_salience(Arc::new(crate::engine::RoleSalience::default()));
+        let query = Point::new(vec![1.0, 0.0, 0.0]);
+        arms.place_with_role(Point::new(vec![1.0, 0.05, 0.0]), Blob::empty(), Role::Assistant).unwrap();
+        arms.near(&query, 1).unwrap();
+        let user = arms.place_with_role(Point::new(vec![1.0, 0.1, 0.0]), Blob::empty(), Role::User).unwrap();
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, user);
+    }
+
+    #[test]
+    fn test_arms_memory_report() {
+        use crate::engine::QueryCache;
+
+        let mut arms = create_test_arms().with_query_cache(QueryCache::new(4, 0.0));
+        let empty = arms.memory_report();
+        for i in 0..32 {
+            arms.place(Point::new(vec![1.0, i as f32, 0.0]), Blob::new(vec![0; 100])).unwrap();
+        }
+        arms.near(&Point::new(vec![1.0, 0.0, 0.0]), 5).unwrap();
+
+        let report = arms.memory_report();
+        assert!(report.storage >= 32 * 100);
+        assert!(report.index_vectors >= 32 * 3 * 4);
+        assert!(report.index_vectors > empty.index_vectors);
+        assert!(report.query_cache > 0);
+        assert_eq!(report.total(), report.storage + report.index() + report.query_cache);
+    }
+
+    #[test]
+    fn test_arms_deterministic_ids() {
+        let run = || {
+            let mut arms = Arms::new(ArmsConfig::new(3).with_deterministic(42));
+            let ids: Vec<Id> = (0..4)
+                .map(|i| arms.place(Point::new(vec![1.0, i as f32, 0.0]), Blob::empty()).unwrap())
+                .collect();
+            let near = arms.near(&Point::new(vec![1.0, 1.0, 0.0]), 2).unwrap();
+            (ids, near)
+        };
+
+        let (ids, near) = run();
+        assert_eq!(run(), (ids.clone(), near));
+        assert_eq!(ids[0], Id::seeded(42, 0));
+
+        // A seeded ID already in storage is skipped, not rejected
+        let mut arms = Arms::new(ArmsConfig::new(3).with_deterministic(42));
+        arms.storage.place_with_id(ids[0], Point::new(vec![1.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        assert_eq!(arms.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::empty()).unwrap(), ids[1]);
+    }
+
+    #[test]
+    fn test_deterministic_ids_miss_seeded_containers() {
+        use crate::adapters::index::{HatConfig, HatIndex};
+
+        let hat = HatIndex::from_proximity(
+            3,
+            Arc::new(crate::core::proximity::Cosine),
+            Arc::new(crate::core::merge::Mean),
+            HatConfig::default().with_seed(7),
+        );
+        let config = ArmsConfig::new(3).with_deterministic(7);
+        let storage = Box::new(MemoryStorage::new(3));
+        let mut arms = Arms::with_adapters(config, storage, Box::new(hat));
+
+        let ids: Vec<Id> = (0..5)
+            .map(|i| arms.place(Point::new(vec![1.0, i as f32, 0.0]), Blob::empty()).unwrap())
+            .collect();
+        assert_eq!(ids[0], Id::seeded(7, 0));
+        let near = arms.near(&Point::new(vec![1.0, 0.0, 0.0]), 5).unwrap();
+        assert_eq!(near.len(), 5);
+        assert!(ids.iter().all(|id| near.iter().any(|r| r.id == *id)));
+    }
+
+    #[test]
+    fn test_arms_non_finite_policy() {
+        use crate::core::config::NonFinitePolicy;
+        use crate::ports::PlaceError;
+
+        let mut arms = create_test_arms();
+        let nan = Point::new(vec![1.0, f32::NAN, 0.0]);
+        assert_eq!(arms.place(nan.clone(), Blob::empty()), Err(PlaceError::NonFinite { index: 1 }));
+        assert!(arms.is_empty());
+        assert_eq!(arms.near(&nan, 1), Err(NearError::NonFinite { index: 1 }));
+
+        let mut arms = Arms::new(ArmsConfig::new(3).with_non_finite(NonFinitePolicy::Sanitize));
+        let id = arms.place(nan.clone(), Blob::empty()).unwrap();
+        assert_eq!(arms.get(id).unwrap().point.dims(), &[1.0, 0.0, 0.0]);
+        assert_eq!(arms.near(&nan, 1).unwrap()[0].id, id);
+    }
+
+    #[test]
+    fn test_arms_salience() {
+        use crate::adapters::storage::CapacityPolicy;
+        use crate::engine::RoleSalience;
+
+        let config = ArmsConfig::new(3);
+        let storage = MemoryStorage::with_capacity(3, 152)
+            .with_capacity_policy(CapacityPolicy::EvictLeastSalient);
+        let index = FlatIndex::from_proximity(3, config.proximity.clone());
+        let mut arms = Arms::with_adapters(config, Box::new(storage), Box::new(index))
+            .with_salience(Arc::new(RoleSalience::default()));
+
+        // The assistant turn sits slightly closer, but the user turn matters more
+        let user = arms.place_with_role(Point::new(vec![1.0, 0.1, 0.0]), Blob::empty(), Role::User).unwrap();
+        let assistant = arms
+            .place_with_role(Point::new(vec![1.0, 0.05, 0.0]), Blob::empty(), Role::Assistant)
+            .unwrap();
+        assert_eq!(arms.salience_of(user), Some(0.8));
+
+        let query = Point::new(vec![1.0, 0.0, 0.0]);
+        let results = arms.near(&query, 2).unwrap();
+        assert_eq!(results[0].id, user);
+        assert_eq!(results[0].salience, Some(0.8));
+        assert_eq!(results[1].salience, Some(0.6));
+        assert!(results[0].score < results[1].score);
+
+        // Every retrieval path ranks the same way
+        let deadline = arms.near_with_deadline(&query, 2, &Deadline::none()).unwrap();
+        assert_eq!(deadline.results, results);
+        let mut visited = Vec::new();
+        arms.near_visit(&query, 2, |r| {
+            visited.push(r);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(visited, results);
+        assert_eq!(arms.within(&query, 0.9).unwrap(), results);
+
+        let mut unbiased = create_test_arms()
+            .with_salience(Arc::new(RoleSalience::default()))
+            .with_salience_bias(0.0);
+        let id = unbiased.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        assert_eq!(unbiased.near(&query, 1).unwrap()[0].salience, Some(0.5));
+        assert_eq!(unbiased.salience_of(id), Some(0.5));
+
+        // Forgetting takes the least salient point
+        arms.place_with_role(Point::new(vec![0.0, 0.0, 1.0]), Blob::empty(), Role::Tool).unwrap();
+        assert!(arms.get(user).is_some());
+        assert!(arms.get(assistant).is_none());
+        assert_eq!(arms.salience_of(assistant), None);
+    }
+
+    #[test]
+    fn test_arms_novelty() {
+        use crate::core::proximity::Euclidean;
+
+        let mut arms = Arms::new(ArmsConfig::new(2).with_normalize(false).with_proximity(Euclidean));
+        assert_eq!(arms.novelty(&Point::new(vec![0.0, 0.0])).unwrap(), 1.0);
+
+        // A grid with unit spacing
+        for x in 0..4 {
+            for y in 0..4 {
+                arms.place(Point::new(vec![x as f32, y as f32]), Blob::empty()).unwrap();
+            }
+        }
+
+        let duplicate = arms.novelty(&Point::new(vec![1.0, 1.0])).unwrap();
+        let inside = arms.novelty(&Point::new(vec![1.5, 1.5])).unwrap();
+        let outlier = arms.novelty(&Point::new(vec![20.0, 20.0])).unwrap();
+        assert!(duplicate < inside && inside < outlier, "{duplicate} {inside} {outlier}");
+        assert!((0.3..0.7).contains(&inside));
+        assert!(outlier > 0.9);
+
+        assert!(matches!(arms.novelty(&Point::new(vec![1.0])), Err(NearError::DimensionalityMismatch { .. })));
+    }
+
+    #[test]
+    fn test_arms_outliers() {
+        use crate::core::proximity::Euclidean;
+
+        let mut arms = Arms::new(ArmsConfig::new(2).with_normalize(false).with_proximity(Euclidean));
+        assert!(arms.outliers(3).unwrap().is_empty());
+
+        for x in 0..4 {
+            for y in 0..4 {
+                arms.place(Point::new(vec![x as f32, y as f32]), Blob::empty()).unwrap();
+            }
+        }
+        let garbage = arms.place(Point::new(vec![40.0, -40.0]), Blob::empty()).unwrap();
+        let stray = arms.place(Point::new(vec![8.0, 8.0]), Blob::empty()).unwrap();
+
+        let outliers = arms.outliers(2).unwrap();
+        assert_eq!(outliers.iter().map(|o| o.0).collect::<Vec<_>>(), vec![garbage, stray]);
+        assert!(outliers[0].1 > outliers[1].1 && outliers[1].1 > 0.5);
+        assert_eq!(arms.outliers(100).unwrap().len(), 18);
+    }
+
+    #[test]
+    fn test_arms_background_index_build() {
+        use crate::adapters::index::HatIndex;
+
+        let mut arms = create_test_arms();
+        let mut ids = Vec::new();
+        for i in 0..200 {
+            let angle = i as f32 * 0.03;
+            ids.push(arms.place(Point::new(vec![angle.cos(), angle.sin(), 0.1]), Blob::empty()).unwrap());
+        }
+        assert!(arms.build_progress().is_none());
+
+        arms.build_index_in_background(Box::new(HatIndex::cosine(3))).unwrap();
+        assert_eq!(arms.build_progress().unwrap().total, 200);
+
+        // Served (by the flat scan) and writable during the build
+        let query = Point::new(vec![1.0, 0.0, 0.1]);
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, ids[0]);
+        arms.remove(ids[0]);
+        let late = arms.place(Point::new(vec![0.0, 0.0, 1.0]), Blob::empty()).unwrap();
+
+        assert!(arms.finish_index_build().unwrap());
+        assert!(arms.build_progress().is_none());
+        assert!(!arms.finish_index_build().unwrap());
+        assert_eq!(arms.index_len(), 200);
+        assert!(arms.near(&query, 10).unwrap().iter().all(|r| r.id != ids[0]));
+        assert_eq!(arms.near(&Point::new(vec![0.0, 0.0, 1.0]), 1).unwrap()[0].id, late);
+    }
+
+    #[test]
+    fn test_arms_erase_where() {
+        use crate::adapters::storage::WalStorage;
+
+        let path = crate::testing::temp_path("arms-erase");
+        let storage = WalStorage::open(&path, 2).unwrap();
+        let config = ArmsConfig::new(2);
+        let index = FlatIndex::from_proximity(2, config.proximity.clone());
+        let mut arms = Arms::with_adapters(config, Box::new(storage), Box::new(index))
+            .with_query_cache(QueryCache::new(16, 0.001));
+
+        let query = Point::new(vec![1.0, 0.0]);
+        let mine = arms.place(query.clone(), Blob::from_str("user:7|hello")).unwrap();
+        let theirs = arms.place(Point::new(vec![0.0, 1.0]), Blob::from_str("user:8|hi")).unwrap();
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, mine);
+
+        let report = arms.erase_where(|placed| placed.blob.data().starts_with(b"user:7|")).unwrap();
+        assert_eq!(report.erased, vec![mine]);
+        assert_eq!(report.bytes, 2 * 4 + "user:7|hello".len());
+        assert!(report.compacted);
+
+        // Gone from storage, index, cache and the log on disk
+        assert!(!arms.contains(mine) && arms.contains(theirs));
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, theirs);
+        assert!(!std::fs::read(&path).unwrap().windows(6).any(|w| w == b"user:7"));
+
+        assert!(arms.erase_where(|_| false).unwrap().is_empty());
+        drop(arms);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_arms_erase_survives_failed_compaction() {
+        use crate::engine::ErasureTarget;
+        use crate::ports::PlaceError;
+        use std::sync::Mutex;
+
+        /// Memory storage whose history can't be compacted
+        struct Stuck(MemoryStorage);
+        impl Place for Stuck {
+            fn place(&mut self, point: Point, blob: Blob) -> PlaceResult<Id> {
+                self.0.place(point, blob)
+            }
+            fn place_with_id(&mut self, id: Id, point: Point, blob: Blob) -> PlaceResult<()> {
+                self.0.place_with_id(id, point, blob)
+            }
+            fn remove(&mut self, id: Id) -> Option<PlacedPoint> {
+                self.0.remove(id)
+            }
+            fn get(&self, id: Id) -> Option<&PlacedPoint> {
+                self.0.get(id)
+            }
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+            fn iter(&self) -> Box<dyn Iterator<Item = &PlacedPoint> + '_> {
+                self.0.iter()
+            }
+            fn size_bytes(&self) -> usize {
+                self.0.size_bytes()
+            }
+            fn clear(&mut self) {
+                self.0.clear()
+            }
+            fn compact(&mut self) -> PlaceResult<bool> {
+                Err(PlaceError::StorageError("disk full".into()))
+            }
+        }
+
+        #[derive(Default)]
+        struct Mirror(Mutex<Vec<Id>>);
+        impl ErasureTarget for Mirror {
+            fn describe(&self) -> String {
+                "mirror".to_string()
+            }
+            fn purge(&self, ids: &[Id]) -> Result<(), String> {
+                self.0.lock().unwrap().extend_from_slice(ids);
+                Ok(())
+            }
+        }
+
+        let config = ArmsConfig::new(2);
+        let index = FlatIndex::from_proximity(2, config.proximity.clone());
+        let mirror = Arc::new(Mirror::default());
+        let mut arms = Arms::with_adapters(config, Box::new(Stuck(MemoryStorage::new(2))), Box::new(index))
+            .with_erasure_target(mirror.clone());
+        let mine = arms.place(Point::new(vec![1.0, 0.0]), Blob::from_str("user:7|hello")).unwrap();
+
+        let report = arms.erase_where(|placed| placed.blob.data().starts_with(b"user:7|")).unwrap();
+        assert_eq!(report.erased, vec![mine]);
+        assert!(!report.compacted && !report.is_complete());
+        assert!(report.retained[0].contains("disk full"));
+        assert_eq!(report.purged, ["mirror"]);
+        assert_eq!(*mirror.0.lock().unwrap(), vec![mine]);
+    }
+
+    #[test]
+    fn test_arms_erase_purges_copies() {
+        use crate::adapters::index::{BackupManager, DirBackupStore, HatIndex};
+        use crate::engine::{ErasureTarget, HatFile, QueryLog};
+
+        struct Unreachable;
+        impl ErasureTarget for Unreachable {
+            fn describe(&self) -> String {
+                "offsite tape".to_string()
+            }
+            fn purge(&self, _: &[Id]) -> Result<(), String> {
+                Err("offline".to_string())
+            }
+        }
+
+        let dir = crate::testing::temp_path("arms-erase-copies");
+        let backups = Arc::new(BackupManager::new(DirBackupStore::new(&dir).unwrap()));
+        let snapshot = dir.join("index.hat");
+
+        let mut arms = Arms::new(ArmsConfig::new(2))
+            .with_erasure_target(backups.clone())
+            .with_erasure_target(Arc::new(HatFile::new(&snapshot)));
+        let mine = arms.place(Point::new(vec![1.0, 0.0]), Blob::from_str("user:7|hello")).unwrap();
+        let theirs = arms.place(Point::new(vec![0.0, 1.0]), Blob::from_str("user:8|hi")).unwrap();
+
+        // Copies taken before the erasure
+        let mut hat = HatIndex::cosine(2);
+        for placed in arms.iter() {
+            hat.add(placed.id, &placed.point).unwrap();
+        }
+        backups.backup(&hat).unwrap();
+        hat.save_to_file(&snapshot).unwrap();
+
+        let report = arms.erase_where(|placed| placed.blob.data().starts_with(b"user:7|")).unwrap();
+        assert_eq!(report.purged.len(), 2);
+        assert!(report.is_complete());
+        for copy in [backups.restore_from_backup(u64::MAX).unwrap(), HatIndex::load_from_file(&snapshot).unwrap()] {
+            assert!(!copy.contains(mine) && copy.contains(theirs));
+        }
+
+        // A copy that can't be purged, or a query log, leaves it incomplete
+        let mut arms = arms
+            .with_erasure_target(Arc::new(Unreachable))
+            .with_query_log(Arc::new(QueryLog::to_callback(|_| {})));
+        let report = arms.erase_where(|_| true).unwrap();
+        assert_eq!(report.retained.len(), 2);
+        assert!(report.retained[0].starts_with("offsite tape: offline"));
+        assert!(!report.is_complete());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_arms_write_behind() {
+        use crate::engine::WriteBehind;
+
+        let write_behind = WriteBehind::new().with_batch_size(2).with_max_pending(3);
+        let mut arms = Arms::new(ArmsConfig::new(3)).with_write_behind(write_behind);
+        let low = arms.place_fast_with_priority(Point::new(vec![1.0, 0.0, 0.0]), Blob::from_str("low"), 0.1).unwrap();
+        let high = arms.place_fast_with_priority(Point::new(vec![0.0, 1.0, 0.0]), Blob::from_str("high"), 0.9).unwrap();
+        let mid = arms.place_fast_with_priority(Point::new(vec![0.0, 0.0, 1.0]), Blob::from_str("mid"), 0.5).unwrap();
+        assert_eq!((arms.len(), arms.pending_len()), (3, 3));
+
+        // Queued points are found by an exact scan
+        let query = Point::new(vec![1.0, 0.1, 0.0]);
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, low);
+        assert_eq!(arms.within(&query, 0.5).unwrap().len(), 1);
+        let filtered = QueryParams::new(3).with_filter(move |id| id != low);
+        assert!(arms.near_with_params(&query, &filtered).unwrap().iter().all(|r| r.id != low));
+
+        // The full queue handed its two highest priorities to a worker
+        assert!(arms.pending_drain_handle().is_some());
+        assert_eq!(arms.finish_pending_drain().unwrap(), 2);
+        assert!(!arms.is_pending(high) && !arms.is_pending(mid) && arms.is_pending(low));
+        assert_eq!(arms.near(&query, 3).unwrap().len(), 3);
+
+        // Filling it again starts another batch without indexing inline
+        arms.place_fast(Point::new(vec![1.0, 1.0, 0.0]), Blob::empty()).unwrap();
+        arms.place_fast(Point::new(vec![0.0, 1.0, 1.0]), Blob::empty()).unwrap();
+        assert_eq!(arms.pending_len(), 3);
+        assert_eq!(arms.finish_pending_drain().unwrap(), 2);
+        assert_eq!(arms.pending_len(), 1);
+        assert!(!arms.is_pending(low));
+
+        let queued = arms.place_fast(Point::new(vec![1.0, 0.0, 1.0]), Blob::empty()).unwrap();
+        assert!(arms.pending_drain_handle().is_none());
+        assert!(arms.remove(queued).is_some() && !arms.is_pending(queued));
+        assert_eq!(arms.flush_pending().unwrap(), 1);
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, low);
+
+        // Without the scan, queued points wait for indexing to be found
+        let mut arms = Arms::new(ArmsConfig::new(3)).with_write_behind(WriteBehind::new().with_scan_pending(false));
+        let id = arms.place_fast(Point::new(vec![1.0, 0.0, 0.0]), Blob::empty()).unwrap();
+        assert!(arms.near(&query, 1).unwrap().is_empty());
+        arms.flush_pending().unwrap();
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, id);
+    }
+
+    #[test]
+    fn test_arms_fork() {
+        let mut arms = Arms::new(ArmsConfig::new(3).with_deterministic(7));
+        let x = arms.place(Point::new(vec![1.0, 0.0, 0.0]), Blob::from_str("x")).unwrap();
+        let y = arms.place(Point::new(vec![0.0, 1.0, 0.0]), Blob::from_str("y")).unwrap();
+        let query = Point::new(vec![1.0, 0.1, 0.0]);
+
+        // Discarded changes never reach the parent
+        let mut fork = arms.fork();
+        assert!(fork.remove(x));
+        let draft = fork.place(Point::new(vec![0.9, 0.1, 0.0]), Blob::from_str("draft")).unwrap();
+        assert_eq!(fork.near(&query, 1).unwrap()[0].id, draft);
+        assert_eq!((fork.len(), fork.get(x).is_none()), (2, true));
+        assert!(matches!(fork.place_with_id(y, query.clone(), Blob::empty()), Err(crate::ports::PlaceError::DuplicateId(_))));
+        fork.discard();
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, x);
+        assert_eq!(arms.len(), 2);
+
+        // A committed fork replays its removals and placements, with the
+        // same IDs it handed out
+        let mut fork = arms.fork();
+        fork.remove(x);
+        let kept = fork.place(Point::new(vec![0.9, 0.1, 0.0]), Blob::from_str("kept")).unwrap();
+        assert_eq!(kept, draft);
+        fork.commit().unwrap();
+        assert!(!arms.contains(x));
+        assert_eq!(arms.near(&query, 1).unwrap()[0].id, kept);
+        assert_eq!(arms.get(kept).unwrap().blob.as_str(), Some("kept"));
+        assert_ne!(arms.place(Point::new(vec![0.0, 0.0, 1.0]), Blob::empty()).unwrap(), kept);
+    }
+
+    #[test]
+    fn test_arms_interceptors() {
+        use crate::engine::DenyList;
+
+        let redact = |input: &IngestInput<'_>| match input.blob.as_str() {
+            Some(text) if text.contains('@') => Verdict::Replace(Blob::from_str("[redacted]")),
+            _ => Verdict::Accept,
+        };
+        let mut arms = create_test_arms()
+            .with_interceptor(Arc::new(redact))
+            .with_interceptor(Arc::new(DenyList::new(["redacted"])));
+        let point = Point::new(vec![1.0, 0.0, 0.0]);
+
+        let id = arms.place(point.clone(), Blob::from_str("hello")).unwrap();
+        assert_eq!(arms.get(id).unwrap().blob.as_str(), Some("hello"));
+
+        // The second interceptor sees what the first wrote
+        assert_eq!(
+            arms.place(point.clone(), Blob::from_str("mail a@b.c")),
+            Err(crate::ports::PlaceError::Rejected {
+                by: "deny-list".into(),
+                reason: "contains denied term \"redacted\"".into()
+            })
+        );
+        assert_eq!(arms.len(), 1);
+
+        let mut arms = create_test_arms().with_interceptor(Arc::new(redact));
+        let id = arms.place(point, Blob::from_str("mail a@b.c")).unwrap();
+        assert_eq!(arms.get(id).unwrap().blob.as_str(), Some("[redacted]"));
+    }
 }
@@ -0,0 +1,95 @@
+//! # Background Index Build
+//!
+//! Fills a new index on a worker thread while `Arms` answers queries from
+//! a flat scan, so a bulk load is searchable before a HAT (or any other
+//! index) has finished building.
+//!
+//! ```text
+//! build_index_in_background(hat)
+//!   ├─ Arms serves from a provisional FlatIndex (exact, slower)
+//!   ├─ worker adds the stored points to `hat`      build_progress() → 40%
+//!   ├─ places/removes meanwhile are queued for `hat`
+//!   └─ poll_index_build() once done: queue applied, `hat` swapped in
+//! ```
+
+use crate::core::{Cause, Id, Point};
+use crate::ports::{JobHandle, JobProgress, Near, NearError, NearResult};
+use super::job::Job;
+
+/// An index being filled on a worker thread
+///
+/// Dropping it stops the worker and discards the index.
+pub(crate) struct IndexBuild {
+    /// The worker, returning the filled index (None once finished)
+    job: Option<Job<NearResult<Box<dyn Near>>>>,
+
+    /// Changes since the build started (None = removed), in order
+    changes: Vec<(Id, Option<Point>)>,
+}
+
+impl IndexBuild {
+    /// Start adding `points` to `index` on a new thread
+    pub(crate) fn start(mut index: Box<dyn Near>, points: Vec<(Id, Point)>) -> Self {
+        let total = points.len();
+        let job = Job::spawn("index build", move |handle: &JobHandle| {
+            for (id, point) in points {
+                if handle.is_cancelled() {
+                    return Err(NearError::IndexError("index build cancelled".into()));
+                }
+                index.add(id, &point)?;
+                handle.advance(1);
+            }
+            Ok(index)
+        });
+        // Set here rather than on the worker so it's visible on return
+        job.handle().set_total(total);
+
+        Self { job: Some(job), changes: Vec::new() }
+    }
+
+    fn job(&self) -> &Job<NearResult<Box<dyn Near>>> {
+        self.job.as_ref().expect("build already finished")
+    }
+
+    pub(crate) fn handle(&self) -> &JobHandle {
+        self.job().handle()
+    }
+
+    pub(crate) fn progress(&self) -> JobProgress {
+        self.job().progress()
+    }
+
+    /// Whether the worker is done (and `finish` won't block)
+    pub(crate) fn is_finished(&self) -> bool {
+        self.job().is_finished()
+    }
+
+    /// Queue a change to replay on the new index
+    pub(crate) fn record(&mut self, id: Id, point: Option<Point>) {
+        self.changes.push((id, point));
+    }
+
+    /// Wait for the worker and bring its index up to date
+    pub(crate) fn finish(mut self) -> NearResult<Box<dyn Near>> {
+        let job = self.job.take().expect("build already finished");
+        let mut index = job.wait().map_err(|e| NearError::IndexError(Cause::new(e)))??;
+
+        for (id, point) in std::mem::take(&mut self.changes) {
+            match point {
+                Some(point) => index.add(id, &point)?,
+                None => {
+                    let _ = index.remove(id);
+                }
+            }
+        }
+        Ok(index)
+    }
+}
+
+impl Drop for IndexBuild {
+    fn drop(&mut self) {
+        if let Some(job) = &self.job {
+            job.cancel();
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! # Query Cache
+//!
+//! LRU cache of search results for repeated, near-identical queries.
+//!
+//! Agents often re-issue almost the same query every turn. Queries are
+//! quantized to a grid before lookup, so queries that differ by less than
+//! the grid step share an entry and skip the index entirely.
+//!
+//! Writes only invalidate the entries they can affect:
+//! - a new point drops `near` entries it would rank into (salience bonus
+//!   included) and `within` entries whose threshold it passes (scored
+//!   against the entry's query);
+//! - a removed point drops entries whose results contain it.
+//!
+//! Results served for a quantized neighbor of the original query are
+//! approximate by up to the grid step; use a step of 0 to cache exact
+//! queries only.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use crate::core::proximity::Proximity;
+use crate::core::{Id, Point};
+use crate::ports::SearchResult;
+
+/// What a cached search asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Request {
+    /// `near` with k
+    Near(usize),
+
+    /// `within` with the threshold's bits
+    Within(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Key {
+    request: Request,
+    cells: Vec<i64>,
+}
+
+struct Entry {
+    /// The query that produced the results (for invalidation)
+    query: Point,
+    results: Vec<SearchResult>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<Key, Entry>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// Cache hit/miss counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// LRU cache of `near`/`within` results keyed by quantized query
+pub struct QueryCache {
+    capacity: usize,
+    step: f32,
+    inner: Mutex<Inner>,
+}
+
+impl QueryCache {
+    /// Cache up to `capacity` results, quantizing queries to `step`
+    pub fn new(capacity: usize, step: f32) -> Self {
+        Self {
+            capacity,
+            step,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let inner = self.lock();
+        CacheStats {
+            hits: inner.hits,
+            misses: inner.misses,
+            entries: inner.entries.len(),
+        }
+    }
+
+    /// Approximate heap bytes held by cached queries and results
+    pub fn size_bytes(&self) -> usize {
+        let inner = self.lock();
+        let entries: usize = inner
+            .entries
+            .iter()
+            .map(|(key, entry)| {
+                key.cells.capacity() * std::mem::size_of::<i64>()
+                    + entry.query.dimensionality() * std::mem::size_of::<f32>()
+                    + entry.results.capacity() * std::mem::size_of::<SearchResult>()
+            })
+            .sum();
+        entries + inner.entries.capacity() * (std::mem::size_of::<(Key, Entry)>() + 1)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn key(&self, query: &Point, request: Request) -> Key {
+        let cells = query
+            .dims()
+            .iter()
+            .map(|&x| {
+                if self.step > 0.0 {
+                    (x / self.step).round() as i64
+                } else {
+                    x.to_bits() as i64
+                }
+            })
+            .collect();
+        Key { request, cells }
+    }
+
+    /// Cached `near` results, if any
+    pub(crate) fn get_near(&self, query: &Point, k: usize) -> Option<Vec<SearchResult>> {
+        self.get(self.key(query, Request::Near(k)))
+    }
+
+    /// Cached `within` results, if any
+    pub(crate) fn get_within(&self, query: &Point, threshold: f32) -> Option<Vec<SearchResult>> {
+        self.get(self.key(query, Request::Within(threshold.to_bits())))
+    }
+
+    pub(crate) fn put_near(&self, query: &Point, k: usize, results: &[SearchResult]) {
+        self.put(self.key(query, Request::Near(k)), query, results);
+    }
+
+    pub(crate) fn put_within(&self, query: &Point, threshold: f32, results: &[SearchResult]) {
+        self.put(self.key(query, Request::Within(threshold.to_bits())), query, results);
+    }
+
+    fn get(&self, key: Key) -> Option<Vec<SearchResult>> {
+        let mut inner = self.lock();
+        inner.tick += 1;
+        let tick = inner.tick;
+        match inner.entries.get_mut(&key) {
+            Some(entry) => {
+                entry.last_used = tick;
+                let results = entry.results.clone();
+                inner.hits += 1;
+                Some(results)
+            }
+            None => {
+                inner.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn put(&self, key: Key, query: &Point, results: &[SearchResult]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = self.lock();
+        inner.tick += 1;
+        let tick = inner.tick;
+
+        if inner.entries.len() >= self.capacity && !inner.entries.contains_key(&key) {
+            let oldest = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                inner.entries.remove(&oldest);
+            }
+        }
+
+        inner.entries.insert(
+            key,
+            Entry {
+                query: query.clone(),
+                results: results.to_vec(),
+                last_used: tick,
+            },
+        );
+    }
+
+    /// Drop entries a newly placed point could change
+    ///
+    /// With salience ranking (`Arms::with_salience_bias`), `near` results
+    /// are ordered by distance less `bias` times salience, so the point and
+    /// each entry's last result are compared that way.
+    pub(crate) fn invalidate_point(&self, point: &Point, salience: f32, bias: f32, proximity: &dyn Proximity) {
+        let order = proximity.order();
+        let biased = |score: f32, salience: f32| order.to_distance(score) - bias * salience;
+        self.lock().entries.retain(|key, entry| {
+            if entry.query.dimensionality() != point.dimensionality() {
+                return false;
+            }
+            let score = proximity.proximity(&entry.query, point);
+            match key.request {
+                Request::Near(k) => match entry.results.last() {
+                    Some(worst) if entry.results.len() >= k => {
+                        biased(score, salience) > biased(worst.score, worst.salience.unwrap_or(0.0))
+                    }
+                    _ => false,
+                },
+                Request::Within(bits) => !order.passes(score, f32::from_bits(bits)),
+            }
+        });
+    }
+
+    /// Drop entries whose results include `id`
+    pub(crate) fn invalidate_id(&self, id: Id) {
+        self.lock()
+            .entries
+            .retain(|_, entry| entry.results.iter().all(|r| r.id != id));
+    }
+
+    /// Drop everything
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::proximity::Euclidean;
+
+    fn result(n: u8, score: f32) -> SearchResult {
+        SearchResult::new(Id::from_bytes([n; 16]), score)
+    }
+
+    #[test]
+    fn test_quantized_hits_and_lru() {
+        let cache = QueryCache::new(2, 0.01);
+        let q = Point::new(vec![1.0, 0.0]);
+        cache.put_near(&q, 1, &[result(1, 0.0)]);
+
+        // A near-identical query hits; another k misses
+        assert!(cache.get_near(&Point::new(vec![1.001, 0.0]), 1).is_some());
+        assert!(cache.get_near(&q, 2).is_none());
+
+        cache.put_near(&Point::new(vec![5.0, 0.0]), 1, &[result(2, 0.0)]);
+        cache.get_near(&q, 1);
+        cache.put_near(&Point::new(vec![9.0, 0.0]), 1, &[result(3, 0.0)]);
+
+        // The least recently used entry was evicted
+        assert!(cache.get_near(&q, 1).is_some());
+        assert!(cache.get_near(&Point::new(vec![5.0, 0.0]), 1).is_none());
+        assert_eq!(cache.stats().hits, 3);
+    }
+
+    #[test]
+    fn test_invalidation_is_local() {
+        let cache = QueryCache::new(8, 0.0);
+        let here = Point::new(vec![0.0, 0.0]);
+        let there = Point::new(vec![100.0, 0.0]);
+        cache.put_near(&here, 1, &[result(1, 1.0)]);
+        cache.put_near(&there, 1, &[result(2, 1.0)]);
+        cache.put_within(&there, 2.0, &[result(2, 1.0)]);
+
+        // A point next to `here` only affects `here`
+        cache.invalidate_point(&Point::new(vec![0.5, 0.0]), 0.0, 0.0, &Euclidean);
+        assert!(cache.get_near(&here, 1).is_none());
+        assert!(cache.get_near(&there, 1).is_some());
+        assert!(cache.get_within(&there, 2.0).is_some());
+
+        cache.invalidate_id(Id::from_bytes([2; 16]));
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn test_invalidation_counts_salience() {
+        let cache = QueryCache::new(8, 0.0);
+        let query = Point::new(vec![0.0, 0.0]);
+        cache.put_near(&query, 1, &[result(1, 1.0)]);
+
+        // Farther than the cached result, but its salience bonus outranks it
+        let far = Point::new(vec![1.05, 0.0]);
+        cache.invalidate_point(&far, 0.0, 0.1, &Euclidean);
+        assert!(cache.get_near(&query, 1).is_some());
+        cache.invalidate_point(&far, 1.0, 0.1, &Euclidean);
+        assert!(cache.get_near(&query, 1).is_none());
+
+        // A salient cached result is harder to displace
+        cache.put_near(&query, 1, &[result(1, 1.0).with_salience(1.0)]);
+        cache.invalidate_point(&Point::new(vec![0.95, 0.0]), 0.0, 0.1, &Euclidean);
+        assert!(cache.get_near(&query, 1).is_some());
+    }
+}
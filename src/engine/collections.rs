@@ -0,0 +1,235 @@
+//! # Collections
+//!
+//! Several named vector spaces, each with its own dimension profile.
+//!
+//! One `Arms` holds one dimensionality. Applications running several
+//! embedding models side by side (384-, 768- and 1536-dim, say) keep one
+//! `Arms` per model in a `Collections` and address them by name:
+//!
+//! ```text
+//! Collections
+//! ├── "minilm"   ArmsConfig::new(384)
+//! ├── "bert"     ArmsConfig::new(768)
+//! └── "openai"   ArmsConfig::new(1536)
+//! ```
+//!
+//! Points and queries are validated against the profile of the collection
+//! they go to. Calls without a name are routed by dimensionality, which
+//! works as long as a single collection has that dimensionality.
+//!
+//! ```rust,ignore
+//! let mut spaces = Collections::new();
+//! spaces.create("minilm", ArmsConfig::new(384))?;
+//! spaces.create("openai", ArmsConfig::new(1536))?;
+//!
+//! spaces.place(Point::new(minilm_vec), blob)?;          // routed to "minilm"
+//! spaces.near_in("openai", &Point::new(query_vec), 5)?;
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::core::config::ArmsConfig;
+use crate::core::{Blob, Id, Point};
+use crate::ports::{NearError, PlaceError, SearchResult};
+use super::arms::Arms;
+
+/// Result type for collection operations
+pub type CollectionResult<T> = Result<T, CollectionError>;
+
+/// Errors from addressing or using a collection
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum CollectionError {
+    /// No collection has this name
+    #[error("Unknown collection: {0}")]
+    NotFound(String),
+
+    /// A collection with this name already exists
+    #[error("Collection already exists: {0}")]
+    AlreadyExists(String),
+
+    /// No collection has this dimensionality
+    #[error("No collection with dimensionality {0}")]
+    NoProfile(usize),
+
+    /// Several collections share this dimensionality; name one
+    #[error("Dimensionality {dimensionality} matches several collections: {}", candidates.join(", "))]
+    Ambiguous { dimensionality: usize, candidates: Vec<String> },
+
+    #[error(transparent)]
+    Place(#[from] PlaceError),
+
+    #[error(transparent)]
+    Near(#[from] NearError),
+}
+
+/// Named `Arms` spaces with per-collection dimension profiles
+#[derive(Default)]
+pub struct Collections {
+    spaces: BTreeMap<String, Arms>,
+}
+
+impl Collections {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty collection with default adapters for `config`
+    pub fn create(&mut self, name: impl Into<String>, config: ArmsConfig) -> CollectionResult<&mut Arms> {
+        self.insert(name, Arms::new(config))
+    }
+
+    /// Add an existing space (custom adapters, restored data, ...) under `name`
+    pub fn insert(&mut self, name: impl Into<String>, arms: Arms) -> CollectionResult<&mut Arms> {
+        let name = name.into();
+        if self.spaces.contains_key(&name) {
+            return Err(CollectionError::AlreadyExists(name));
+        }
+        Ok(self.spaces.entry(name).or_insert(arms))
+    }
+
+    /// Remove a collection, handing back its space
+    pub fn remove(&mut self, name: &str) -> Option<Arms> {
+        self.spaces.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arms> {
+        self.spaces.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Arms> {
+        self.spaces.get_mut(name)
+    }
+
+    /// Collection names, sorted
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.spaces.keys().map(String::as_str)
+    }
+
+    /// Number of collections
+    pub fn len(&self) -> usize {
+        self.spaces.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spaces.is_empty()
+    }
+
+    /// Points stored across all collections
+    pub fn points(&self) -> usize {
+        self.spaces.values().map(Arms::len).sum()
+    }
+
+    /// Name of the only collection with this dimensionality
+    pub fn route(&self, dimensionality: usize) -> CollectionResult<&str> {
+        let mut matches = self
+            .spaces
+            .iter()
+            .filter(|(_, arms)| arms.dimensionality() == dimensionality)
+            .map(|(name, _)| name.as_str());
+
+        match (matches.next(), matches.next()) {
+            (Some(name), None) => Ok(name),
+            (None, _) => Err(CollectionError::NoProfile(dimensionality)),
+            (Some(first), Some(second)) => {
+                let mut candidates = vec![first.to_string(), second.to_string()];
+                candidates.extend(matches.map(str::to_string));
+                Err(CollectionError::Ambiguous { dimensionality, candidates })
+            }
+        }
+    }
+
+    fn space(&self, name: &str) -> CollectionResult<&Arms> {
+        self.spaces.get(name).ok_or_else(|| CollectionError::NotFound(name.to_string()))
+    }
+
+    fn space_mut(&mut self, name: &str) -> CollectionResult<&mut Arms> {
+        self.spaces.get_mut(name).ok_or_else(|| CollectionError::NotFound(name.to_string()))
+    }
+
+    /// Place a point in a named collection
+    pub fn place_in(&mut self, name: &str, point: Point, blob: Blob) -> CollectionResult<Id> {
+        Ok(self.space_mut(name)?.place(point, blob)?)
+    }
+
+    /// Place a point in the collection matching its dimensionality
+    pub fn place(&mut self, point: Point, blob: Blob) -> CollectionResult<Id> {
+        let name = self.route(point.dimensionality())?.to_string();
+        self.place_in(&name, point, blob)
+    }
+
+    /// Find the k nearest points in a named collection
+    pub fn near_in(&self, name: &str, query: &Point, k: usize) -> CollectionResult<Vec<SearchResult>> {
+        Ok(self.space(name)?.near(query, k)?)
+    }
+
+    /// Find the k nearest points in the collection matching the query's dimensionality
+    pub fn near(&self, query: &Point, k: usize) -> CollectionResult<Vec<SearchResult>> {
+        self.near_in(self.route(query.dimensionality())?, query, k)
+    }
+
+    /// Find points within a threshold in a named collection
+    pub fn within_in(&self, name: &str, query: &Point, threshold: f32) -> CollectionResult<Vec<SearchResult>> {
+        Ok(self.space(name)?.within(query, threshold)?)
+    }
+
+    /// Find points within a threshold in the collection matching the query's dimensionality
+    pub fn within(&self, query: &Point, threshold: f32) -> CollectionResult<Vec<SearchResult>> {
+        self.within_in(self.route(query.dimensionality())?, query, threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routing_by_dimensionality() {
+        let mut spaces = Collections::new();
+        spaces.create("small", ArmsConfig::new(2)).unwrap();
+        spaces.create("large", ArmsConfig::new(4)).unwrap();
+        assert_eq!(
+            spaces.create("small", ArmsConfig::new(3)).err(),
+            Some(CollectionError::AlreadyExists("small".into()))
+        );
+
+        let a = spaces.place(Point::new(vec![1.0, 0.0]), Blob::empty()).unwrap();
+        let b = spaces.place(Point::new(vec![0.0, 0.0, 1.0, 0.0]), Blob::empty()).unwrap();
+        assert!(spaces.get("small").unwrap().contains(a));
+        assert!(spaces.get("large").unwrap().contains(b));
+        assert_eq!(spaces.near(&Point::new(vec![1.0, 0.1]), 1).unwrap()[0].id, a);
+
+        assert_eq!(
+            spaces.place(Point::new(vec![1.0; 3]), Blob::empty()),
+            Err(CollectionError::NoProfile(3))
+        );
+        assert_eq!(spaces.points(), 2);
+    }
+
+    #[test]
+    fn test_named_collections_validate_profile() {
+        let mut spaces = Collections::new();
+        spaces.create("a", ArmsConfig::new(2)).unwrap();
+        spaces.create("b", ArmsConfig::new(2)).unwrap();
+
+        // Same dimensionality: unnamed calls can't pick one
+        assert!(matches!(
+            spaces.place(Point::new(vec![1.0, 0.0]), Blob::empty()),
+            Err(CollectionError::Ambiguous { dimensionality: 2, .. })
+        ));
+
+        let id = spaces.place_in("b", Point::new(vec![1.0, 0.0]), Blob::empty()).unwrap();
+        assert!(spaces.near_in("a", &Point::new(vec![1.0, 0.0]), 1).unwrap().is_empty());
+        assert_eq!(spaces.near_in("b", &Point::new(vec![1.0, 0.0]), 1).unwrap()[0].id, id);
+
+        assert!(matches!(
+            spaces.place_in("a", Point::new(vec![1.0; 3]), Blob::empty()),
+            Err(CollectionError::Place(PlaceError::DimensionalityMismatch { expected: 2, got: 3 }))
+        ));
+        assert_eq!(
+            spaces.near_in("c", &Point::new(vec![1.0, 0.0]), 1),
+            Err(CollectionError::NotFound("c".into()))
+        );
+        assert_eq!(spaces.names().collect::<Vec<_>>(), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,132 @@
+//! # Local Consensus
+//!
+//! Single-node `Consensus`: commands are committed as soon as they are
+//! proposed. The log is kept so other processes can follow it
+//! (`entries_since`) or bootstrap from a snapshot, until `compact` drops
+//! the entries every follower has seen. For a group of nodes, see
+//! `RaftConsensus`.
+//!
+//! ```rust,ignore
+//! let consensus = LocalConsensus::new();
+//! consensus.propose(MetaCommand::AddNode { name: "a".into(), addr: "10.0.0.1:9090".into() })?;
+//!
+//! // Bring a router's membership in line with the shard map
+//! router.sync_nodes(&consensus.metadata(), |name, addr| Arc::new(RemoteShard::new(name, addr)))?;
+//! ```
+
+use std::sync::{Mutex, PoisonError};
+
+use crate::core::cluster::{ClusterMeta, MetaCommand};
+use crate::ports::{Consensus, ConsensusError, ConsensusResult};
+
+#[derive(Default)]
+struct State {
+    log: Vec<MetaCommand>,
+    meta: ClusterMeta,
+}
+
+/// Consensus of one: every proposal commits immediately
+#[derive(Default)]
+pub struct LocalConsensus {
+    state: Mutex<State>,
+}
+
+impl LocalConsensus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a snapshot (entries up to `meta.applied()` are not kept)
+    pub fn from_snapshot(meta: ClusterMeta) -> Self {
+        Self { state: Mutex::new(State { log: Vec::new(), meta }) }
+    }
+
+    /// Committed entries after `index`, with their log indexes
+    ///
+    /// Entries folded into the starting snapshot or compacted away are not
+    /// available.
+    pub fn entries_since(&self, index: u64) -> Vec<(u64, MetaCommand)> {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let first = state.meta.applied() + 1 - state.log.len() as u64;
+        state
+            .log
+            .iter()
+            .zip(first..)
+            .filter(|(_, i)| *i > index)
+            .map(|(command, i)| (i, command.clone()))
+            .collect()
+    }
+
+    /// Drop log entries up to and including `index`, returning how many
+    /// were dropped
+    ///
+    /// Followers behind `index` have to start over from `metadata()`.
+    pub fn compact(&self, index: u64) -> usize {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let first = state.meta.applied() + 1 - state.log.len() as u64;
+        let dropped = index.saturating_add(1).saturating_sub(first).min(state.log.len() as u64) as usize;
+        state.log.drain(..dropped);
+        dropped
+    }
+
+    /// Entries held in memory
+    pub fn log_len(&self) -> usize {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).log.len()
+    }
+}
+
+impl Consensus for LocalConsensus {
+    fn propose(&self, command: MetaCommand) -> ConsensusResult<u64> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let index = state.meta.applied() + 1;
+        let outcome = state.meta.apply(index, &command);
+        state.log.push(command);
+        outcome.map(|()| index).map_err(|reason| ConsensusError::Rejected { index, reason })
+    }
+
+    fn metadata(&self) -> ClusterMeta {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).meta.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cluster::MetaError;
+
+    fn add(name: &str) -> MetaCommand {
+        MetaCommand::AddNode { name: name.into(), addr: format!("{}:9090", name) }
+    }
+
+    #[test]
+    fn test_proposals_commit_in_order() {
+        let consensus = LocalConsensus::new();
+        assert_eq!(consensus.propose(add("a")), Ok(1));
+        assert_eq!(
+            consensus.propose(add("a")),
+            Err(ConsensusError::Rejected { index: 2, reason: MetaError::NodeExists("a".into()) })
+        );
+        assert_eq!(consensus.propose(add("b")), Ok(3));
+        assert_eq!(consensus.metadata().nodes().len(), 2);
+
+        // A follower replaying the log reaches the same metadata
+        let mut follower = ClusterMeta::new();
+        for (i, command) in consensus.entries_since(0) {
+            let _ = follower.apply(i, &command);
+        }
+        assert_eq!(follower, consensus.metadata());
+
+        let resumed = LocalConsensus::from_snapshot(consensus.metadata());
+        assert_eq!(resumed.propose(add("c")), Ok(4));
+        assert_eq!(resumed.entries_since(0), vec![(4, add("c"))]);
+        assert_eq!(consensus.entries_since(2).len(), 1);
+
+        assert_eq!(consensus.compact(2), 2);
+        assert_eq!(consensus.compact(2), 0);
+        assert_eq!(consensus.log_len(), 1);
+        assert_eq!(consensus.entries_since(0), vec![(3, add("b"))]);
+        assert_eq!(consensus.propose(add("c")), Ok(4));
+        assert_eq!(consensus.compact(u64::MAX), 2);
+        assert!(consensus.entries_since(0).is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+//! # Erasure
+//!
+//! Hard deletion of every point belonging to a subject (a user, a tenant),
+//! for right-to-erasure requests.
+//!
+//! `Arms::erase_where` removes matching points from the index, storage,
+//! query cache and salience scores, then compacts storage so removed
+//! payloads don't linger in a write-ahead log or as database tombstones.
+//! `ErasureReport` is the record of what was done.
+//!
+//! Copies made outside the store are only reached when registered with
+//! `Arms::with_erasure_target`: `BackupManager` rewrites its backups and
+//! `HatFile` rewrites a saved `.hat` snapshot. Anything that may still hold
+//! erased data - a copy that failed to purge, a `QueryLog` with the IDs of
+//! erased points - is listed in `ErasureReport::retained`, and the erasure
+//! isn't complete until that list is empty.
+//!
+//! ```rust,ignore
+//! let arms = arms
+//!     .with_erasure_target(backups.clone())
+//!     .with_erasure_target(Arc::new(HatFile::new("index.hat")));
+//!
+//! // Blobs carry a subject prefix, e.g. "user:42|..."
+//! let report = arms.erase_where(|placed| placed.blob.data().starts_with(b"user:42|"))?;
+//! if !report.is_complete() {
+//!     escalate(&report.retained);
+//! }
+//! ```
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::path::{Path, PathBuf};
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use crate::adapters::index::HatIndex;
+use crate::core::Id;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use crate::ports::Near;
+
+/// A copy of stored points kept outside the store, purged by `erase_where`
+pub trait ErasureTarget: Send + Sync {
+    /// What the copy is, for `ErasureReport`
+    fn describe(&self) -> String;
+
+    /// Delete `ids` from the copy (IDs it doesn't hold are skipped)
+    fn purge(&self, ids: &[Id]) -> Result<(), String>;
+}
+
+/// A `.hat` file written by `HatIndex::save_to_file`
+///
+/// Purging loads it with `HatIndex::load_from_file`, removes the points and
+/// saves it again; a missing file holds nothing. Not on wasm32, which has
+/// no files.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+#[derive(Debug, Clone)]
+pub struct HatFile {
+    path: PathBuf,
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl HatFile {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self { path: path.as_ref().to_path_buf() }
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl ErasureTarget for HatFile {
+    fn describe(&self) -> String {
+        format!("HAT snapshot {}", self.path.display())
+    }
+
+    fn purge(&self, ids: &[Id]) -> Result<(), String> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+        let mut index = HatIndex::load_from_file(&self.path).map_err(|e| e.to_string())?;
+        let before = index.len();
+        for &id in ids {
+            let _ = index.remove(id);
+        }
+        if index.len() == before {
+            return Ok(());
+        }
+        index.save_to_file(&self.path).map_err(|e| e.to_string())
+    }
+}
+
+/// What an erasure removed
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ErasureReport {
+    /// IDs of the erased points
+    pub erased: Vec<Id>,
+
+    /// Vector and payload bytes erased
+    pub bytes: usize,
+
+    /// Whether storage rewrote its history (false for stores that keep none)
+    pub compacted: bool,
+
+    /// Registered copies the points were purged from
+    pub purged: Vec<String>,
+
+    /// What may still hold erased data, and why
+    pub retained: Vec<String>,
+}
+
+impl ErasureReport {
+    pub fn len(&self) -> usize {
+        self.erased.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.erased.is_empty()
+    }
+
+    /// Whether nothing known still holds the erased points
+    pub fn is_complete(&self) -> bool {
+        self.retained.is_empty()
+    }
+}
@@ -0,0 +1,181 @@
+//! # Forked Views
+//!
+//! A copy-on-write view of an `Arms` for what-if experiments: try a
+//! retrieval change or let an agent roll out a plan, then keep or drop
+//! everything it wrote.
+//!
+//! `Arms::fork` costs nothing up front. Points placed in the fork live in
+//! a small overlay, points removed from it are hidden rather than deleted,
+//! and queries merge the overlay (scanned exactly) with the parent's index.
+//! The parent is untouched until `commit` replays the changes on it;
+//! `discard` (or dropping the fork) forgets them.
+//!
+//! ```rust,ignore
+//! let mut fork = arms.fork();
+//! fork.remove(stale_id);
+//! fork.place(plan_embedding, Blob::from_str("draft plan"))?;
+//! if evaluate(&fork.near(&query, 5)?) {
+//!     fork.commit()?;
+//! }
+//! ```
+//!
+//! Storage limits aren't simulated: a fork never evicts, so a commit into a
+//! capped store may evict parent points the fork could still see.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::adapters::index::TopK;
+use crate::core::{Blob, Id, PlacedPoint, Point, SeededIds};
+use crate::ports::{NearError, NearResult, PlaceError, PlaceResult, SearchResult};
+
+use super::Arms;
+
+/// Writes staged over an `Arms` without changing it (see `Arms::fork`)
+pub struct ArmsFork<'a> {
+    parent: &'a mut Arms,
+
+    /// Points placed in the fork
+    placed: HashMap<Id, PlacedPoint>,
+
+    /// Placement order of `placed`, replayed by `commit`
+    order: Vec<Id>,
+
+    /// Parent points removed in the fork
+    hidden: HashSet<Id>,
+
+    /// The parent's ID sequence, advanced by the fork (None = wall-clock IDs)
+    ids: Option<SeededIds>,
+}
+
+impl<'a> ArmsFork<'a> {
+    pub(super) fn new(parent: &'a mut Arms, ids: Option<SeededIds>) -> Self {
+        Self { parent, placed: HashMap::new(), order: Vec::new(), hidden: HashSet::new(), ids }
+    }
+
+    /// Place a point in the fork, returning its ID
+    ///
+    /// The point is normalized and checked as the parent would, but
+    /// interceptors and salience only run when the fork is committed.
+    pub fn place(&mut self, point: Point, blob: Blob) -> PlaceResult<Id> {
+        let id = match &mut self.ids {
+            Some(ids) => {
+                let mut id = ids.next_id();
+                while self.parent.contains(id) || self.placed.contains_key(&id) {
+                    id = ids.next_id();
+                }
+                id
+            }
+            None => Id::now(),
+        };
+        self.place_with_id(id, point, blob)?;
+        Ok(id)
+    }
+
+    /// Place a point in the fork under an ID chosen by the caller
+    pub fn place_with_id(&mut self, id: Id, point: Point, blob: Blob) -> PlaceResult<()> {
+        if self.contains(id) {
+            return Err(PlaceError::DuplicateId(id));
+        }
+        let config = self.parent.config();
+        if point.dimensionality() != config.dimensionality {
+            return Err(PlaceError::DimensionalityMismatch {
+                expected: config.dimensionality,
+                got: point.dimensionality(),
+            });
+        }
+        let point = config.non_finite.apply(point).map_err(|index| PlaceError::NonFinite { index })?;
+        let point = if config.normalize_on_insert { point.normalize() } else { point };
+
+        self.placed.insert(id, PlacedPoint { id, point, blob });
+        self.order.push(id);
+        Ok(())
+    }
+
+    /// Remove a point from the fork's view (the parent keeps it until commit)
+    pub fn remove(&mut self, id: Id) -> bool {
+        if self.placed.remove(&id).is_some() {
+            self.order.retain(|placed| *placed != id);
+            return true;
+        }
+        self.parent.contains(id) && self.hidden.insert(id)
+    }
+
+    /// Get a point as the fork sees it
+    pub fn get(&self, id: Id) -> Option<&PlacedPoint> {
+        match self.placed.get(&id) {
+            Some(placed) => Some(placed),
+            None if self.hidden.contains(&id) => None,
+            None => self.parent.get(id),
+        }
+    }
+
+    /// Check if the fork sees a point
+    pub fn contains(&self, id: Id) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Number of points the fork sees
+    pub fn len(&self) -> usize {
+        self.parent.len() - self.hidden.len() + self.placed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// IDs placed in the fork, in placement order
+    pub fn placed_ids(&self) -> &[Id] {
+        &self.order
+    }
+
+    /// Parent IDs removed in the fork
+    pub fn removed_ids(&self) -> impl Iterator<Item = Id> + '_ {
+        self.hidden.iter().copied()
+    }
+
+    /// Find the k nearest points the fork sees
+    ///
+    /// Parent results (which skip removed points) and the exactly scored
+    /// overlay are merged by score, so the parent's salience bias doesn't
+    /// carry over; overlay points have no salience until committed.
+    pub fn near(&self, query: &Point, k: usize) -> NearResult<Vec<SearchResult>> {
+        let parent = self.parent.near(query, k + self.hidden.len())?;
+
+        let config = self.parent.config();
+        let query = config.non_finite.apply(query.clone()).map_err(|index| NearError::NonFinite { index })?;
+        let query = if config.normalize_on_insert { query.normalize() } else { query };
+
+        let mut top = TopK::new(k, config.proximity.order());
+        for result in parent.into_iter().filter(|r| !self.hidden.contains(&r.id)) {
+            top.push(result);
+        }
+        for placed in self.placed.values() {
+            top.push(SearchResult::new(placed.id, config.proximity.proximity(&query, &placed.point)));
+        }
+        Ok(top.into_sorted_vec())
+    }
+
+    /// Apply the fork's removals, then its placements, to the parent
+    ///
+    /// Placements go through the parent's interceptors and salience. Stops
+    /// at the first one the parent refuses; changes applied before it are
+    /// kept.
+    pub fn commit(self) -> PlaceResult<()> {
+        let Self { parent, mut placed, order, hidden, ids } = self;
+        for id in hidden {
+            parent.remove(id);
+        }
+        if let Some(ids) = ids {
+            parent.resume_ids(ids);
+        }
+        for id in order {
+            if let Some(PlacedPoint { id, point, blob }) = placed.remove(&id) {
+                parent.place_with_id(id, point, blob)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the fork's changes (the same as dropping it)
+    pub fn discard(self) {}
+}
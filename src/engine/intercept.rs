@@ -0,0 +1,111 @@
+//! # Ingest Interceptors
+//!
+//! Hooks that see every point on its way into `Arms` and may pass it,
+//! rewrite its payload or refuse it: PII scrubbers, profanity filters,
+//! classification taggers.
+//!
+//! Interceptors run in the order they were added, after the vector is
+//! validated and normalized and before anything is scored or stored. Each
+//! sees the payload as the previous one left it. A rejection stops the
+//! chain and fails the place with `PlaceError::Rejected`.
+//!
+//! ```rust,ignore
+//! let arms = Arms::new(config)
+//!     .with_interceptor(Arc::new(scrub_emails))   // Fn(&IngestInput) -> Verdict
+//!     .with_interceptor(Arc::new(DenyList::new(["password:"])));
+//! ```
+
+use crate::adapters::attention::Role;
+use crate::core::{Blob, Point};
+
+/// What an interceptor sees of a point being placed
+#[derive(Debug, Clone, Copy)]
+pub struct IngestInput<'a> {
+    pub point: &'a Point,
+    pub blob: &'a Blob,
+
+    /// Conversation role, if the caller gave one
+    pub role: Option<Role>,
+}
+
+/// An interceptor's decision
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    /// Store the payload as it is
+    Accept,
+
+    /// Store this payload instead
+    Replace(Blob),
+
+    /// Don't store the point
+    Reject(String),
+}
+
+/// Inspects, rewrites or refuses points as they are placed
+pub trait PlaceInterceptor: Send + Sync {
+    fn intercept(&self, input: &IngestInput<'_>) -> Verdict;
+
+    /// Name reported with rejections
+    fn name(&self) -> &str {
+        "interceptor"
+    }
+}
+
+impl<F> PlaceInterceptor for F
+where
+    F: Fn(&IngestInput<'_>) -> Verdict + Send + Sync,
+{
+    fn intercept(&self, input: &IngestInput<'_>) -> Verdict {
+        self(input)
+    }
+}
+
+/// Rejects payloads containing any of a set of terms (ASCII case-insensitive)
+#[derive(Debug, Clone)]
+pub struct DenyList {
+    terms: Vec<Vec<u8>>,
+}
+
+impl DenyList {
+    pub fn new<I, S>(terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let terms = terms
+            .into_iter()
+            .map(|term| term.as_ref().to_ascii_lowercase().into_bytes())
+            .filter(|term| !term.is_empty())
+            .collect();
+        Self { terms }
+    }
+}
+
+impl PlaceInterceptor for DenyList {
+    fn intercept(&self, input: &IngestInput<'_>) -> Verdict {
+        let data = input.blob.data().to_ascii_lowercase();
+        match self.terms.iter().find(|term| data.windows(term.len()).any(|w| w == term.as_slice())) {
+            Some(term) => Verdict::Reject(format!("contains denied term \"{}\"", String::from_utf8_lossy(term))),
+            None => Verdict::Accept,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "deny-list"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deny_list() {
+        let deny = DenyList::new(["Secret", ""]);
+        let point = Point::new(vec![1.0]);
+        let verdict = |text: &str| deny.intercept(&IngestInput { point: &point, blob: &Blob::from_str(text), role: None });
+
+        assert_eq!(verdict("nothing to see"), Verdict::Accept);
+        assert_eq!(verdict("my SECRET plan"), Verdict::Reject("contains denied term \"secret\"".into()));
+    }
+}
@@ -0,0 +1,274 @@
+//! # Jobs
+//!
+//! Long-running operations on their own thread, with progress, ETA and
+//! cancellation through a `JobHandle`.
+//!
+//! `wasm32-unknown-unknown` has no threads: there `Job::spawn` runs the
+//! operation to completion before returning, so `wait` never blocks and
+//! cancelling only affects work that checks the handle later.
+//!
+//! ```rust,ignore
+//! let index = Arc::new(RwLock::new(index));
+//! let job = Job::spawn("consolidate", {
+//!     let index = index.clone();
+//!     move |handle| index.write().unwrap().consolidate_with(ConsolidationConfig::full(), handle)
+//! });
+//! jobs.track(&job);                      // listed at the server's /jobs
+//!
+//! println!("{:.0}%, eta {:?}", job.progress().percent(), job.progress().eta());
+//! job.cancel();
+//! let metrics = job.wait()?;             // None: cancelled
+//! ```
+
+use std::sync::{Arc, Mutex, PoisonError};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::thread::{self, JoinHandle};
+
+use crate::core::metadata::push_json_string;
+use crate::ports::{JobHandle, JobProgress};
+
+/// Errors from waiting on a job
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum JobError {
+    /// The operation panicked
+    #[error("Job {0} panicked")]
+    Panicked(String),
+}
+
+/// An operation running on its own thread
+pub struct Job<T> {
+    name: String,
+    handle: JobHandle,
+    worker: Worker<T>,
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+type Worker<T> = JoinHandle<T>;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+type Worker<T> = Inline<T>;
+
+/// The result of an operation run on the calling thread
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+struct Inline<T>(T);
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+impl<T> Inline<T> {
+    fn is_finished(&self) -> bool {
+        true
+    }
+
+    fn join(self) -> std::thread::Result<T> {
+        Ok(self.0)
+    }
+}
+
+impl<T: Send + 'static> Job<T> {
+    /// Run `work` on a new thread, reporting through the handle it's given
+    pub fn spawn<F>(name: impl Into<String>, work: F) -> Self
+    where
+        F: FnOnce(&JobHandle) -> T + Send + 'static,
+    {
+        let handle = JobHandle::new();
+        let reporter = handle.clone();
+        let run = move || {
+            // Marks the job finished even if `work` panics
+            struct Finish(JobHandle);
+            impl Drop for Finish {
+                fn drop(&mut self) {
+                    self.0.finish();
+                }
+            }
+            let finish = Finish(reporter);
+            work(&finish.0)
+        };
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        let worker = thread::spawn(run);
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        let worker = Inline(run());
+
+        Self { name: name.into(), handle, worker }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The job's progress and cancellation handle
+    pub fn handle(&self) -> &JobHandle {
+        &self.handle
+    }
+
+    pub fn progress(&self) -> JobProgress {
+        self.handle.progress()
+    }
+
+    /// Ask the operation to stop; `wait` still returns what it produced
+    pub fn cancel(&self) {
+        self.handle.cancel();
+    }
+
+    /// Whether the operation is over (and `wait` won't block)
+    pub fn is_finished(&self) -> bool {
+        self.worker.is_finished()
+    }
+
+    /// Block until the operation returns
+    pub fn wait(self) -> Result<T, JobError> {
+        self.worker.join().map_err(|_| JobError::Panicked(self.name))
+    }
+}
+
+/// A tracked job as listed by `Jobs`
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobInfo {
+    /// Assigned by `Jobs::track`, for `Jobs::cancel`
+    pub id: u64,
+    pub name: String,
+    pub progress: JobProgress,
+}
+
+impl JobInfo {
+    /// JSON object with progress, ETA (seconds, null if unknown) and state
+    pub fn to_json(&self) -> String {
+        let progress = &self.progress;
+        let mut json = format!("{{\"id\":{},\"name\":", self.id);
+        push_json_string(&mut json, &self.name);
+        json.push_str(&format!(
+            ",\"done\":{},\"total\":{},\"percent\":{},\"elapsed_secs\":{},\"eta_secs\":{},\"cancelled\":{},\"finished\":{}}}",
+            progress.done,
+            progress.total,
+            progress.percent(),
+            progress.elapsed.as_secs_f64(),
+            progress.eta().map_or("null".to_string(), |eta| eta.as_secs_f64().to_string()),
+            progress.cancelled,
+            progress.finished,
+        ));
+        json
+    }
+}
+
+/// Registry of jobs to list and cancel by ID (e.g., from the HTTP server)
+///
+/// Clones share the registry. Finished jobs stay listed until `prune`.
+#[derive(Debug, Clone, Default)]
+pub struct Jobs {
+    inner: Arc<Mutex<Registry>>,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    next_id: u64,
+    jobs: Vec<(u64, String, JobHandle)>,
+}
+
+impl Jobs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track a spawned job; returns its ID
+    pub fn track<T: Send + 'static>(&self, job: &Job<T>) -> u64 {
+        self.track_handle(job.name(), job.handle())
+    }
+
+    /// Track an operation run some other way through `handle`
+    pub fn track_handle(&self, name: impl Into<String>, handle: &JobHandle) -> u64 {
+        let mut registry = self.lock();
+        registry.next_id += 1;
+        let id = registry.next_id;
+        registry.jobs.push((id, name.into(), handle.clone()));
+        id
+    }
+
+    /// Tracked jobs, oldest first
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.lock()
+            .jobs
+            .iter()
+            .map(|(id, name, handle)| JobInfo {
+                id: *id,
+                name: name.clone(),
+                progress: handle.progress(),
+            })
+            .collect()
+    }
+
+    /// Cancel a tracked job; false if the ID is unknown
+    pub fn cancel(&self, id: u64) -> bool {
+        let registry = self.lock();
+        let job = registry.jobs.iter().find(|(job_id, _, _)| *job_id == id);
+        if let Some((_, _, handle)) = job {
+            handle.cancel();
+        }
+        job.is_some()
+    }
+
+    /// Stop tracking finished jobs
+    pub fn prune(&self) {
+        self.lock().jobs.retain(|(_, _, handle)| !handle.is_finished());
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Registry> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_job_progress_and_cancel() {
+        let jobs = Jobs::new();
+        let job = Job::spawn("count", |handle: &JobHandle| {
+            handle.set_total(1_000_000);
+            let mut done = 0;
+            while !handle.is_cancelled() && done < 1_000_000 {
+                handle.advance(1);
+                done += 1;
+                std::thread::sleep(Duration::from_micros(10));
+            }
+            done
+        });
+        let id = jobs.track(&job);
+
+        while job.progress().done == 0 {
+            std::thread::yield_now();
+        }
+        let listed = jobs.list();
+        assert_eq!(listed[0].name, "count");
+        assert!(listed[0].to_json().contains("\"total\":1000000"));
+
+        assert!(jobs.cancel(id));
+        assert!(!jobs.cancel(id + 1));
+        let done = job.wait().unwrap();
+        assert!(done < 1_000_000);
+
+        let progress = jobs.list()[0].progress;
+        assert!(progress.cancelled && progress.finished);
+        assert_eq!(progress.eta(), Some(Duration::ZERO));
+        jobs.prune();
+        assert!(jobs.list().is_empty());
+
+        let failing = Job::spawn("boom", |_: &JobHandle| -> () { panic!("boom") });
+        assert_eq!(failing.wait(), Err(JobError::Panicked("boom".into())));
+    }
+
+    #[test]
+    fn test_job_progress_eta() {
+        let progress = JobProgress {
+            done: 25,
+            total: 100,
+            elapsed: Duration::from_secs(10),
+            cancelled: false,
+            finished: false,
+        };
+        assert_eq!(progress.percent(), 25.0);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(30)));
+        assert_eq!(JobProgress { done: 0, ..progress }.eta(), None);
+        assert_eq!(JobProgress { total: 0, ..progress }.fraction(), 0.0);
+    }
+}
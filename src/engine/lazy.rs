@@ -0,0 +1,78 @@
+//! # Lazy Results
+//!
+//! Search results that carry their stored point but leave its blob unread.
+//!
+//! Ranking needs scores, IDs and vectors; only the few results the caller
+//! keeps need their payload. A `LazyResult` reports the blob's size without
+//! reading it, and `blob()` reads it on demand: free for inline blobs, one
+//! fetch for blobs in a spill store (see `MemoryStorage::with_blob_spill`).
+//!
+//! ```rust,ignore
+//! let candidates = arms.near_lazy(&query, 100)?;
+//! for hit in candidates.iter().filter(|hit| rerank(hit.point())).take(5) {
+//!     use_payload(hit.blob()?);
+//! }
+//! ```
+
+use std::io;
+
+use crate::core::{Blob, Id, PlacedPoint, Point};
+
+/// A search result with its blob loaded on demand
+#[derive(Clone, Copy)]
+pub struct LazyResult<'a> {
+    /// ID of the found point
+    pub id: Id,
+
+    /// Score from the search
+    pub score: f32,
+
+    placed: &'a PlacedPoint,
+}
+
+impl<'a> LazyResult<'a> {
+    pub(crate) fn new(placed: &'a PlacedPoint, score: f32) -> Self {
+        Self { id: placed.id, score, placed }
+    }
+
+    /// The stored vector
+    pub fn point(&self) -> &'a Point {
+        &self.placed.point
+    }
+
+    /// Blob size in bytes, without reading it
+    pub fn blob_size(&self) -> usize {
+        self.placed.blob.size()
+    }
+
+    /// Whether reading the blob is free (inline, or already loaded)
+    pub fn is_loaded(&self) -> bool {
+        self.placed.blob.is_loaded()
+    }
+
+    /// The blob's bytes, reading them if deferred
+    pub fn blob(&self) -> io::Result<&'a [u8]> {
+        self.placed.blob.try_data()
+    }
+
+    /// The blob handle itself
+    pub fn blob_handle(&self) -> &'a Blob {
+        &self.placed.blob
+    }
+
+    /// The whole stored point
+    pub fn placed(&self) -> &'a PlacedPoint {
+        self.placed
+    }
+}
+
+impl std::fmt::Debug for LazyResult<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyResult")
+            .field("id", &self.id)
+            .field("score", &self.score)
+            .field("blob_size", &self.blob_size())
+            .field("loaded", &self.is_loaded())
+            .finish()
+    }
+}
@@ -0,0 +1,250 @@
+//! # Metrics
+//!
+//! Operation counters and latency histograms for the engine.
+//!
+//! Recording only touches atomics, so one `Metrics` can be shared by every
+//! thread using an `Arms` instance. `render_prometheus` produces the text
+//! exposition format served at `/metrics` by the server adapter.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Operations tracked by `Metrics`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Place,
+    Near,
+    Within,
+    Remove,
+}
+
+impl Operation {
+    /// All operations, in rendering order
+    pub const ALL: [Operation; 4] = [
+        Operation::Place,
+        Operation::Near,
+        Operation::Within,
+        Operation::Remove,
+    ];
+
+    /// Label value used in rendered metrics
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Place => "place",
+            Operation::Near => "near",
+            Operation::Within => "within",
+            Operation::Remove => "remove",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Histogram bucket upper bounds in microseconds (100µs to 1s)
+const BUCKETS_MICROS: [u64; 12] = [
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 1_000_000,
+];
+
+/// Latency histogram (per-bucket counts; cumulated when rendered)
+#[derive(Default)]
+struct Histogram {
+    buckets: [AtomicU64; BUCKETS_MICROS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, micros: u64) {
+        if let Some(i) = BUCKETS_MICROS.iter().position(|&le| micros <= le) {
+            self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(micros, Ordering::Relaxed);
+    }
+}
+
+/// Counters for one operation
+#[derive(Default)]
+struct OperationMetrics {
+    total: AtomicU64,
+    errors: AtomicU64,
+    latency: Histogram,
+}
+
+/// A point-in-time value rendered next to the counters
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gauge {
+    /// Metric name (e.g., "arms_points")
+    pub name: &'static str,
+
+    /// One-line description
+    pub help: &'static str,
+
+    /// Current value
+    pub value: f64,
+}
+
+impl Gauge {
+    pub fn new(name: &'static str, help: &'static str, value: f64) -> Self {
+        Self { name, help, value }
+    }
+}
+
+/// Approximate heap bytes held by an `Arms` instance, by component
+///
+/// Estimates from capacities and element sizes, not allocator statistics:
+/// expect them to track real usage, not match it to the byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryReport {
+    /// Stored points and payloads
+    pub storage: usize,
+
+    /// Vectors held by the index
+    pub index_vectors: usize,
+
+    /// Index structure: graph/tree links, centroids, lookup tables
+    pub index_structure: usize,
+
+    /// Payloads and metadata kept by the index
+    pub index_metadata: usize,
+
+    /// Query result cache
+    pub query_cache: usize,
+}
+
+impl MemoryReport {
+    pub fn index(&self) -> usize {
+        self.index_vectors + self.index_structure + self.index_metadata
+    }
+
+    pub fn total(&self) -> usize {
+        self.storage + self.index() + self.query_cache
+    }
+}
+
+/// Operation counters and latency histograms
+#[derive(Default)]
+pub struct Metrics {
+    operations: [OperationMetrics; Operation::ALL.len()],
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one completed operation
+    pub fn record(&self, op: Operation, micros: u64, ok: bool) {
+        let metrics = &self.operations[op.index()];
+        metrics.total.fetch_add(1, Ordering::Relaxed);
+        if !ok {
+            metrics.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        metrics.latency.observe(micros);
+    }
+
+    /// Number of recorded operations of a kind
+    pub fn count(&self, op: Operation) -> u64 {
+        self.operations[op.index()].total.load(Ordering::Relaxed)
+    }
+
+    /// Number of failed operations of a kind
+    pub fn errors(&self, op: Operation) -> u64 {
+        self.operations[op.index()].errors.load(Ordering::Relaxed)
+    }
+
+    /// Render counters, histograms and gauges in Prometheus text format
+    pub fn render_prometheus(&self, gauges: &[Gauge]) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP arms_operations_total Operations performed.\n");
+        out.push_str("# TYPE arms_operations_total counter\n");
+        for op in Operation::ALL {
+            let _ = writeln!(out, "arms_operations_total{{op=\"{}\"}} {}", op.name(), self.count(op));
+        }
+
+        out.push_str("# HELP arms_operation_errors_total Operations that failed.\n");
+        out.push_str("# TYPE arms_operation_errors_total counter\n");
+        for op in Operation::ALL {
+            let _ = writeln!(out, "arms_operation_errors_total{{op=\"{}\"}} {}", op.name(), self.errors(op));
+        }
+
+        out.push_str("# HELP arms_operation_duration_seconds Operation latency.\n");
+        out.push_str("# TYPE arms_operation_duration_seconds histogram\n");
+        for op in Operation::ALL {
+            let latency = &self.operations[op.index()].latency;
+            let count = latency.count.load(Ordering::Relaxed);
+
+            let mut cumulative = 0;
+            for (le, bucket) in BUCKETS_MICROS.iter().zip(&latency.buckets) {
+                cumulative += bucket.load(Ordering::Relaxed);
+                let _ = writeln!(
+                    out,
+                    "arms_operation_duration_seconds_bucket{{op=\"{}\",le=\"{}\"}} {}",
+                    op.name(),
+                    *le as f64 / 1e6,
+                    cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "arms_operation_duration_seconds_bucket{{op=\"{}\",le=\"+Inf\"}} {}",
+                op.name(),
+                count
+            );
+            let _ = writeln!(
+                out,
+                "arms_operation_duration_seconds_sum{{op=\"{}\"}} {}",
+                op.name(),
+                latency.sum_micros.load(Ordering::Relaxed) as f64 / 1e6
+            );
+            let _ = writeln!(out, "arms_operation_duration_seconds_count{{op=\"{}\"}} {}", op.name(), count);
+        }
+
+        for gauge in gauges {
+            let _ = writeln!(out, "# HELP {} {}", gauge.name, gauge.help);
+            let _ = writeln!(out, "# TYPE {} gauge", gauge.name);
+            let _ = writeln!(out, "{} {}", gauge.name, gauge.value);
+        }
+
+        out
+    }
+}
+
+/// Resident memory of this process in bytes, where the OS reports it
+pub(crate) fn resident_memory_bytes() -> Option<u64> {
+    // Linux: "VmRSS:    1234 kB"
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_render() {
+        let metrics = Metrics::new();
+        metrics.record(Operation::Near, 300, true);
+        metrics.record(Operation::Near, 2_000_000, false);
+        metrics.record(Operation::Place, 50, true);
+
+        assert_eq!(metrics.count(Operation::Near), 2);
+        assert_eq!(metrics.errors(Operation::Near), 1);
+
+        let text = metrics.render_prometheus(&[Gauge::new("arms_points", "Stored points.", 3.0)]);
+        assert!(text.contains("arms_operations_total{op=\"near\"} 2"));
+        assert!(text.contains("arms_operation_errors_total{op=\"near\"} 1"));
+        // 300µs falls in the 500µs bucket; 2s only in +Inf
+        assert!(text.contains("arms_operation_duration_seconds_bucket{op=\"near\",le=\"0.00025\"} 0"));
+        assert!(text.contains("arms_operation_duration_seconds_bucket{op=\"near\",le=\"0.0005\"} 1"));
+        assert!(text.contains("arms_operation_duration_seconds_bucket{op=\"near\",le=\"1\"} 1"));
+        assert!(text.contains("arms_operation_duration_seconds_bucket{op=\"near\",le=\"+Inf\"} 2"));
+        assert!(text.contains("arms_operation_duration_seconds_sum{op=\"near\"} 2.0003"));
+        assert!(text.contains("# TYPE arms_points gauge\narms_points 3\n"));
+    }
+}
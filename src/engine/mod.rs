@@ -6,7 +6,56 @@
 //! - Configuration is applied
 //! - Adapters are connected to ports
 //! - The unified ARMS interface is exposed
+//! - Operations are measured (when metrics are attached)
+//! - Several dimension profiles live side by side (`Collections`)
+//! - Points are scored for importance as they are placed (`Salience`)
+//! - Payloads are scrubbed or refused on ingest (`PlaceInterceptor`)
+//! - Indexes are built in the background while a flat scan serves
+//! - Long operations run as `Job`s with progress, ETA and cancellation
+//! - Answered queries are recorded to a `QueryLog` for offline analysis
+//! - A subject's points are hard-deleted on request (`ErasureReport`)
+//! - Experiments stage writes in a copy-on-write view (`ArmsFork`)
+//! - Bursty ingest is stored at once and indexed later (`WriteBehind`)
+//! - Points are partitioned across shard nodes by a `ShardRouter`
+//! - Cluster metadata is agreed through a `Consensus` (`LocalConsensus`,
+//!   or `RaftConsensus` across nodes)
 
 mod arms;
+mod build;
+mod cache;
+mod collections;
+mod consensus;
+mod erasure;
+mod fork;
+mod intercept;
+mod job;
+mod lazy;
+mod metrics;
+mod pending;
+mod query_log;
+mod raft;
+mod router;
+mod salience;
 
 pub use arms::Arms;
+pub use cache::{CacheStats, QueryCache};
+pub use collections::{CollectionError, CollectionResult, Collections};
+pub use consensus::LocalConsensus;
+pub use erasure::{ErasureReport, ErasureTarget};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use erasure::HatFile;
+pub use fork::ArmsFork;
+pub use intercept::{DenyList, IngestInput, PlaceInterceptor, Verdict};
+pub use job::{Job, JobError, JobInfo, Jobs};
+pub use lazy::LazyResult;
+pub use metrics::{Gauge, MemoryReport, Metrics, Operation};
+pub use pending::{WriteBehind, DEFAULT_MAX_PENDING, DEFAULT_PENDING_BATCH};
+pub use query_log::{LoggedQuery, QueryLog, QueryRecord};
+pub use raft::{
+    Entry, LocalTransport, MemoryRaftStore, RaftConsensus, RaftRequest, RaftResponse, RaftRole, RaftState, RaftStore,
+    RaftTransport,
+};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use raft::FileRaftStore;
+pub use router::{HashRing, LocalShard, RebalanceReport, ShardRouter, DEFAULT_REPLICAS};
+pub use salience::{LengthSalience, NoveltySalience, RoleSalience, Salience, SalienceInput, WeightedSalience};
@@ -0,0 +1,308 @@
+//! # Write-Behind Indexing
+//!
+//! `Arms::place_fast` stores a point and queues it for the index instead
+//! of indexing it on the spot, so a burst of writes costs a storage insert
+//! each and nothing more. The queue is drained highest priority first, so
+//! salient points become indexed before the rest:
+//!
+//! - in the background: once the queue reaches `max_pending`, `place_fast`
+//!   hands a batch to a worker thread (a `PendingDrain`) and returns; the
+//!   worker adds it to the index point by point, holding the index's write
+//!   lock only for each add. `Arms::poll_pending_drain` (also run by every
+//!   `place_fast`) collects the finished batch and starts no new one.
+//! - on demand: `Arms::index_pending` and `flush_pending` index on the
+//!   calling thread, after waiting for a running drain.
+//!
+//! On wasm32-unknown-unknown there are no threads: `Job::spawn` runs the
+//! batch inline, so `place_fast` indexes it before returning.
+//!
+//! Until a point is indexed, queries scan it exactly (see
+//! `WriteBehind::scan_pending`); points the worker is indexing stay in the
+//! scan until their batch is collected. A batch that fails goes back in
+//! the queue with its priorities and is retried by the next drain.
+//!
+//! ```text
+//! place_fast ──► storage ──► pending queue ──► PendingDrain(batch) ──► index
+//!                                  │              (worker thread)
+//!                 near/within ─────┘ exact scan merged with index results
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::core::{Cause, Id, Point};
+use crate::ports::{JobHandle, Near, NearError};
+use super::job::Job;
+
+/// Default points indexed per background batch
+pub const DEFAULT_PENDING_BATCH: usize = 256;
+
+/// Default queue length at which `place_fast` starts a background batch
+pub const DEFAULT_MAX_PENDING: usize = 4096;
+
+/// Write-behind settings (see `Arms::with_write_behind`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteBehind {
+    /// Points indexed per background batch
+    pub batch_size: usize,
+
+    /// Queue length at which `place_fast` starts a background batch
+    pub max_pending: usize,
+
+    /// Whether `near`, `near_with_params` and `within` scan pending points
+    /// (false = pending points are invisible until indexed)
+    pub scan_pending: bool,
+}
+
+impl WriteBehind {
+    pub fn new() -> Self {
+        Self { batch_size: DEFAULT_PENDING_BATCH, max_pending: DEFAULT_MAX_PENDING, scan_pending: true }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending.max(1);
+        self
+    }
+
+    pub fn with_scan_pending(mut self, scan_pending: bool) -> Self {
+        self.scan_pending = scan_pending;
+        self
+    }
+}
+
+impl Default for WriteBehind {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A queued point: higher priority first, then first come, first served
+#[derive(Debug, Clone, Copy)]
+struct PendingWrite {
+    priority: f32,
+    seq: u64,
+    id: Id,
+}
+
+impl PartialEq for PendingWrite {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PendingWrite {}
+
+impl PartialOrd for PendingWrite {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PendingWrite {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.total_cmp(&other.priority).then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Stored points waiting to be indexed
+#[derive(Debug, Default)]
+pub(crate) struct PendingQueue {
+    heap: BinaryHeap<PendingWrite>,
+
+    /// Queued IDs and their priorities (removed points linger in `heap`
+    /// until popped)
+    queued: HashMap<Id, f32>,
+
+    /// IDs taken by `take_batch` and not yet settled, with their priorities
+    in_flight: HashMap<Id, f32>,
+
+    seq: u64,
+}
+
+impl PendingQueue {
+    pub(crate) fn push(&mut self, id: Id, priority: f32) {
+        let priority = if priority.is_nan() { 0.0 } else { priority };
+        if !self.in_flight.contains_key(&id) && !self.queued.contains_key(&id) {
+            self.queued.insert(id, priority);
+            self.heap.push(PendingWrite { priority, seq: self.seq, id });
+            self.seq += 1;
+        }
+    }
+
+    /// Take up to `n` queued IDs, highest priority first
+    ///
+    /// They count as pending until `settle`d or `requeue`d.
+    pub(crate) fn take_batch(&mut self, n: usize) -> Vec<Id> {
+        let mut batch = Vec::with_capacity(n.min(self.queued.len()));
+        while batch.len() < n {
+            let Some(write) = self.heap.pop() else {
+                break;
+            };
+            if let Some(priority) = self.queued.remove(&write.id) {
+                self.in_flight.insert(write.id, priority);
+                batch.push(write.id);
+            }
+        }
+        batch
+    }
+
+    /// Mark a taken ID as indexed (returns whether it was still pending)
+    pub(crate) fn settle(&mut self, id: Id) -> bool {
+        self.in_flight.remove(&id).is_some()
+    }
+
+    /// Queue every taken, unsettled ID again at its original priority
+    pub(crate) fn requeue(&mut self) {
+        let mut in_flight: Vec<(Id, f32)> = self.in_flight.drain().collect();
+        in_flight.sort_by_key(|&(id, _)| id);
+        for (id, priority) in in_flight {
+            self.push(id, priority);
+        }
+    }
+
+    /// Whether an ID was taken and not yet settled
+    pub(crate) fn is_in_flight(&self, id: Id) -> bool {
+        self.in_flight.contains_key(&id)
+    }
+
+    /// Forget a pending ID (returns whether it was pending)
+    pub(crate) fn remove(&mut self, id: Id) -> bool {
+        let removed = self.queued.remove(&id).is_some() || self.in_flight.remove(&id).is_some();
+        if self.queued.is_empty() {
+            self.heap.clear();
+        }
+        removed
+    }
+
+    pub(crate) fn contains(&self, id: Id) -> bool {
+        self.queued.contains_key(&id) || self.in_flight.contains_key(&id)
+    }
+
+    /// Pending IDs, queued or in flight
+    pub(crate) fn ids(&self) -> impl Iterator<Item = Id> + '_ {
+        self.queued.keys().chain(self.in_flight.keys()).copied()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.queued.len() + self.in_flight.len()
+    }
+
+    /// Number of IDs waiting to be taken
+    pub(crate) fn queued_len(&self) -> usize {
+        self.queued.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.queued.is_empty() && self.in_flight.is_empty()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.heap.clear();
+        self.queued.clear();
+        self.in_flight.clear();
+    }
+}
+
+/// What a `PendingDrain` worker got through
+pub(crate) struct Drained {
+    /// Points added to the index, in order
+    pub(crate) indexed: Vec<(Id, Point)>,
+
+    /// Why it stopped early (None = whole batch indexed or cancelled)
+    pub(crate) error: Option<NearError>,
+}
+
+/// A batch of pending points being indexed on a worker thread
+///
+/// Dropping it stops the worker after its current add.
+pub(crate) struct PendingDrain {
+    /// The worker (None once finished)
+    job: Option<Job<Drained>>,
+}
+
+impl PendingDrain {
+    /// Start adding `batch` to `index` on a new thread
+    pub(crate) fn start(index: Arc<RwLock<Box<dyn Near>>>, batch: Vec<(Id, Point)>) -> Self {
+        let total = batch.len();
+        let job = Job::spawn("pending drain", move |handle: &JobHandle| {
+            let mut indexed = Vec::with_capacity(batch.len());
+            for (id, point) in batch {
+                if handle.is_cancelled() {
+                    break;
+                }
+                let added = index.write().unwrap_or_else(PoisonError::into_inner).add(id, &point);
+                if let Err(error) = added {
+                    return Drained { indexed, error: Some(error) };
+                }
+                indexed.push((id, point));
+                handle.advance(1);
+            }
+            Drained { indexed, error: None }
+        });
+        job.handle().set_total(total);
+
+        Self { job: Some(job) }
+    }
+
+    fn job(&self) -> &Job<Drained> {
+        self.job.as_ref().expect("drain already finished")
+    }
+
+    pub(crate) fn handle(&self) -> &JobHandle {
+        self.job().handle()
+    }
+
+    /// Whether the worker is done (and `finish` won't block)
+    pub(crate) fn is_finished(&self) -> bool {
+        self.job().is_finished()
+    }
+
+    /// Ask the worker to stop after its current add
+    pub(crate) fn cancel(&self) {
+        self.job().cancel();
+    }
+
+    /// Wait for the worker
+    pub(crate) fn finish(mut self) -> Drained {
+        let job = self.job.take().expect("drain already finished");
+        job.wait().unwrap_or_else(|e| Drained { indexed: Vec::new(), error: Some(NearError::IndexError(Cause::new(e))) })
+    }
+}
+
+impl Drop for PendingDrain {
+    fn drop(&mut self) {
+        if let Some(job) = &self.job {
+            job.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requeue_keeps_priority() {
+        let (a, b, c) = (Id::from_bytes([1; 16]), Id::from_bytes([2; 16]), Id::from_bytes([3; 16]));
+        let mut queue = PendingQueue::default();
+        queue.push(a, 0.9);
+        queue.push(b, 0.1);
+        queue.push(c, 0.5);
+
+        assert_eq!(queue.take_batch(2), vec![a, c]);
+        assert!(queue.contains(c) && queue.is_in_flight(c));
+        assert_eq!((queue.len(), queue.queued_len()), (3, 1));
+
+        // `a` made it in; `c` failed and still outranks `b`
+        assert!(queue.settle(a));
+        queue.requeue();
+        assert_eq!(queue.take_batch(1), vec![c]);
+        assert_eq!(queue.len(), 2);
+    }
+}
@@ -0,0 +1,406 @@
+//! # Query Log
+//!
+//! Records every `near`/`within` query an `Arms` answers, for offline
+//! recall audits and replay benchmarks.
+//!
+//! ```rust,ignore
+//! let log = Arc::new(QueryLog::to_file("queries.log", 64 << 20, 4)?.with_vectors(true));
+//! let arms = Arms::new(config).with_query_log(log);
+//! // ...
+//! let records = QueryLog::read_all("queries.log")?;   // rotated files included, oldest first
+//! ```
+//!
+//! Files hold one record per line, tab-separated:
+//!
+//! ```text
+//! at_micros  op  k  threshold  min_score  dedup  filtered  latency_micros  ok  complete  query  results
+//! ```
+//!
+//! Absent numbers are `-`. Lines written before `complete` was logged (11
+//! fields) read as complete. `query` is `h:` and a 64-bit FNV-1a hash of the
+//! query in hex, or the comma-separated vector with `with_vectors(true)`.
+//! `results` is the comma-separated hex IDs, best first.
+//!
+//! Logging never fails a query: write errors are counted in `dropped`.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use crate::core::{Id, Point};
+use crate::ports::QueryParams;
+use super::metrics::Operation;
+
+/// The query vector as logged
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoggedQuery {
+    /// FNV-1a hash of the vector's bits (enough to group repeats)
+    Hash(u64),
+
+    /// The full vector (enough to replay)
+    Vector(Vec<f32>),
+}
+
+impl LoggedQuery {
+    /// The vector, if it was logged in full
+    pub fn point(&self) -> Option<Point> {
+        match self {
+            LoggedQuery::Vector(dims) => Some(Point::new(dims.clone())),
+            LoggedQuery::Hash(_) => None,
+        }
+    }
+}
+
+/// One answered (or failed) query
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryRecord {
+    /// When the query started (µs since the Unix epoch)
+    pub at_micros: u64,
+
+    /// `Near` or `Within`
+    pub operation: Operation,
+    pub query: LoggedQuery,
+
+    /// Results asked for (0 for `within`)
+    pub k: usize,
+
+    /// `within` threshold
+    pub threshold: Option<f32>,
+
+    pub min_score: Option<f32>,
+    pub dedup: Option<f32>,
+
+    /// Whether an ID filter was applied (filters themselves aren't logged)
+    pub filtered: bool,
+
+    pub latency_micros: u64,
+    pub ok: bool,
+
+    /// False if a deadline cut the search short (`near_with_deadline`)
+    pub complete: bool,
+
+    /// Returned IDs, best first (empty on failure)
+    pub results: Vec<Id>,
+}
+
+impl QueryRecord {
+    /// Parameters to run the query again with (k, min_score, dedup)
+    pub fn params(&self) -> QueryParams {
+        QueryParams { min_score: self.min_score, dedup: self.dedup, ..QueryParams::new(self.k) }
+    }
+
+    /// The record as one log line (without the newline)
+    pub fn to_line(&self) -> String {
+        let number = |value: Option<f32>| value.map_or("-".to_string(), |v| v.to_string());
+        let query = match &self.query {
+            LoggedQuery::Hash(hash) => format!("h:{:016x}", hash),
+            LoggedQuery::Vector(dims) => join(dims),
+        };
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.at_micros,
+            self.operation.name(),
+            self.k,
+            number(self.threshold),
+            number(self.min_score),
+            number(self.dedup),
+            self.filtered as u8,
+            self.latency_micros,
+            self.ok as u8,
+            self.complete as u8,
+            query,
+            join(&self.results),
+        )
+    }
+
+    /// Parse a line written by `to_line` (None if malformed)
+    pub fn from_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.trim_end_matches(['\r', '\n']).split('\t').collect();
+        let [at, op, k, threshold, min_score, dedup, filtered, latency, ok, complete, query, results] = match fields[..] {
+            [at, op, k, threshold, min_score, dedup, filtered, latency, ok, query, results] => {
+                [at, op, k, threshold, min_score, dedup, filtered, latency, ok, "1", query, results]
+            }
+            [at, op, k, threshold, min_score, dedup, filtered, latency, ok, complete, query, results] => {
+                [at, op, k, threshold, min_score, dedup, filtered, latency, ok, complete, query, results]
+            }
+            _ => return None,
+        };
+        let number = |field: &str| -> Option<Option<f32>> {
+            match field {
+                "-" => Some(None),
+                field => field.parse().ok().map(Some),
+            }
+        };
+        let flag = |field: &str| match field {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => None,
+        };
+        let operation = Operation::ALL.into_iter().find(|o| o.name() == op)?;
+        let query = match query.strip_prefix("h:") {
+            Some(hash) => LoggedQuery::Hash(u64::from_str_radix(hash, 16).ok()?),
+            None => LoggedQuery::Vector(split(query, |v| v.parse().ok())?),
+        };
+
+        Some(Self {
+            at_micros: at.parse().ok()?,
+            operation,
+            query,
+            k: k.parse().ok()?,
+            threshold: number(threshold)?,
+            min_score: number(min_score)?,
+            dedup: number(dedup)?,
+            filtered: flag(filtered)?,
+            latency_micros: latency.parse().ok()?,
+            ok: flag(ok)?,
+            complete: flag(complete)?,
+            results: split(results, Id::from_hex)?,
+        })
+    }
+}
+
+fn join<T: std::fmt::Display>(items: &[T]) -> String {
+    items.iter().map(T::to_string).collect::<Vec<_>>().join(",")
+}
+
+fn split<T>(field: &str, parse: impl Fn(&str) -> Option<T>) -> Option<Vec<T>> {
+    if field.is_empty() {
+        return Some(Vec::new());
+    }
+    field.split(',').map(parse).collect()
+}
+
+/// 64-bit FNV-1a over a vector's bits
+fn hash_query(query: &Point) -> u64 {
+    query.dims().iter().flat_map(|v| v.to_bits().to_le_bytes()).fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Where records go
+enum Sink {
+    File(RotatingFile),
+    Callback(Box<dyn Fn(&QueryRecord) + Send + Sync>),
+}
+
+/// A log file renamed to `<path>.1`, `<path>.2`, ... as it fills
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_bytes: u64,
+
+    /// Rotated files kept
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn append(&mut self, line: &str) -> io::Result<()> {
+        if self.len > 0 && self.len + line.len() as u64 + 1 > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.len += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep == 0 {
+            self.file.set_len(0)?;
+        } else {
+            let _ = fs::remove_file(rotated(&self.path, self.keep));
+            for n in (1..self.keep).rev() {
+                let from = rotated(&self.path, n);
+                if from.exists() {
+                    fs::rename(&from, rotated(&self.path, n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated(&self.path, 1))?;
+            self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        }
+        self.len = 0;
+        Ok(())
+    }
+}
+
+fn rotated(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Destination for query records, shared by the `Arms` instances using it
+pub struct QueryLog {
+    sink: Mutex<Sink>,
+
+    /// Log full vectors instead of hashes
+    vectors: bool,
+
+    dropped: AtomicU64,
+}
+
+impl QueryLog {
+    /// Append to `path`, rotating once it would exceed `max_bytes`
+    ///
+    /// Keeps `keep` rotated files (`<path>.1` is the newest); with 0, the
+    /// file is truncated instead.
+    pub fn to_file(path: impl AsRef<Path>, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        let sink = Sink::File(RotatingFile { path, file, len, max_bytes, keep });
+        Ok(Self { sink: Mutex::new(sink), vectors: false, dropped: AtomicU64::new(0) })
+    }
+
+    /// Hand each record to `callback` (called on the querying thread)
+    pub fn to_callback(callback: impl Fn(&QueryRecord) + Send + Sync + 'static) -> Self {
+        let sink = Sink::Callback(Box::new(callback));
+        Self { sink: Mutex::new(sink), vectors: false, dropped: AtomicU64::new(0) }
+    }
+
+    /// Log full query vectors (replayable) instead of hashes
+    pub fn with_vectors(mut self, vectors: bool) -> Self {
+        self.vectors = vectors;
+        self
+    }
+
+    /// The query as this log records it
+    pub fn logged_query(&self, query: &Point) -> LoggedQuery {
+        if self.vectors {
+            LoggedQuery::Vector(query.dims().to_vec())
+        } else {
+            LoggedQuery::Hash(hash_query(query))
+        }
+    }
+
+    /// Write a record
+    pub fn log(&self, record: &QueryRecord) {
+        let mut sink = self.sink.lock().unwrap_or_else(PoisonError::into_inner);
+        match &mut *sink {
+            Sink::File(file) => {
+                if file.append(&record.to_line()).is_err() {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Sink::Callback(callback) => callback(record),
+        }
+    }
+
+    /// Records lost to write errors
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Read the records of one log file, skipping malformed lines
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<QueryRecord>> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut records = Vec::new();
+        for line in reader.lines() {
+            records.extend(QueryRecord::from_line(&line?));
+        }
+        Ok(records)
+    }
+
+    /// Read `path` and its rotated files, oldest first
+    pub fn read_all(path: impl AsRef<Path>) -> io::Result<Vec<QueryRecord>> {
+        let path = path.as_ref();
+        let mut files: Vec<PathBuf> = (1..).map(|n| rotated(path, n)).take_while(|p| p.exists()).collect();
+        files.reverse();
+        files.push(path.to_path_buf());
+
+        let mut records = Vec::new();
+        for file in files.iter().filter(|f| f.exists()) {
+            records.extend(Self::read(file)?);
+        }
+        Ok(records)
+    }
+}
+
+impl std::fmt::Debug for QueryLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryLog")
+            .field("vectors", &self.vectors)
+            .field("dropped", &self.dropped())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn record(results: Vec<Id>) -> QueryRecord {
+        QueryRecord {
+            at_micros: 1_700_000_000_000_000,
+            operation: Operation::Near,
+            query: LoggedQuery::Vector(vec![0.25, -1.5, 1e-7]),
+            k: 2,
+            threshold: None,
+            min_score: Some(0.5),
+            dedup: None,
+            filtered: true,
+            latency_micros: 42,
+            ok: true,
+            complete: true,
+            results,
+        }
+    }
+
+    #[test]
+    fn test_query_record_line_roundtrip() {
+        let original = record(vec![Id::seeded(1, 0), Id::seeded(1, 1)]);
+        assert_eq!(QueryRecord::from_line(&original.to_line()), Some(original.clone()));
+
+        let hashed = QueryRecord {
+            operation: Operation::Within,
+            query: LoggedQuery::Hash(hash_query(&Point::new(vec![1.0]))),
+            threshold: Some(0.8),
+            results: Vec::new(),
+            ..original
+        };
+        assert_eq!(QueryRecord::from_line(&hashed.to_line()), Some(hashed.clone()));
+        assert_eq!(QueryRecord::from_line("garbage"), None);
+
+        // Older lines have no `complete` field
+        let cut = QueryRecord { complete: false, ..hashed };
+        assert_eq!(QueryRecord::from_line(&cut.to_line()), Some(cut.clone()));
+        let old = cut.to_line().replacen("\t1\t0\t", "\t1\t", 1);
+        assert_eq!(QueryRecord::from_line(&old), Some(QueryRecord { complete: true, ..cut }));
+    }
+
+    #[test]
+    fn test_query_log_rotates() {
+        let dir = crate::testing::temp_path("arms-query-log");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("queries.log");
+
+        let line_len = record(vec![Id::seeded(0, 0)]).to_line().len() as u64 + 1;
+        let log = QueryLog::to_file(&path, line_len * 2, 1).unwrap();
+        for i in 0..5 {
+            log.log(&record(vec![Id::seeded(0, i)]));
+        }
+
+        // 5 records, 2 per file, one rotated file kept: the oldest is gone
+        let sequence = |records: Vec<QueryRecord>| records.iter().map(|r| r.results[0]).collect::<Vec<_>>();
+        assert_eq!(sequence(QueryLog::read(&path).unwrap()), vec![Id::seeded(0, 4)]);
+        assert_eq!(
+            sequence(QueryLog::read_all(&path).unwrap()),
+            (2..5).map(|i| Id::seeded(0, i)).collect::<Vec<_>>()
+        );
+        assert_eq!(log.dropped(), 0);
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let callback = QueryLog::to_callback({
+            let seen = seen.clone();
+            move |record: &QueryRecord| seen.lock().unwrap().push(record.k)
+        });
+        callback.log(&record(Vec::new()));
+        assert_eq!(*seen.lock().unwrap(), vec![2]);
+        assert_eq!(callback.logged_query(&Point::new(vec![1.0])), LoggedQuery::Hash(hash_query(&Point::new(vec![1.0]))));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
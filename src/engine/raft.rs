@@ -0,0 +1,1114 @@
+//! # Raft Consensus
+//!
+//! Multi-node `Consensus`: leader election, log replication and commit by
+//! majority (the Raft protocol), with `ClusterMeta` as the state machine,
+//! so shard nodes agree on collections and the shard map without an
+//! external coordinator.
+//!
+//! Each node runs a `RaftConsensus`. Nodes talk through a `RaftTransport` -
+//! an RPC client in a deployment (`MetaCommand` and `ClusterMeta` already
+//! have binary encodings), or a `LocalTransport` for nodes sharing a
+//! process. Time is counted in ticks: call `tick` every few tens of
+//! milliseconds. A follower that hears from no leader for its election
+//! timeout (10 - 19 ticks) stands for election, and the leader sends
+//! appends (or heartbeats) on every tick.
+//!
+//! ```rust,ignore
+//! let transport = LocalTransport::new();
+//! let nodes: Vec<_> = ["a", "b", "c"]
+//!     .iter()
+//!     .map(|name| Arc::new(RaftConsensus::new(*name, ["a", "b", "c"], transport.clone())))
+//!     .collect();
+//! nodes.iter().for_each(|node| transport.register(node));
+//!
+//! // From each node's maintenance loop
+//! node.tick();
+//!
+//! // On the leader (others answer `NotLeader` with the leader's name)
+//! leader.propose(MetaCommand::DefineCollection { name: "docs".into(), dimensionality: 768 })?;
+//! ```
+//!
+//! A node writes its term, vote and log to a `RaftStore` before it
+//! answers a vote or an append, so a restarted node never votes twice in a
+//! term and committed entries survive any number of restarts. `open`
+//! restores a node from its store; `FileRaftStore` keeps the state in one
+//! file, while `new` (and `MemoryRaftStore`) suit nodes that never restart,
+//! such as tests. `compact` folds applied entries into a snapshot, which
+//! followers too far behind receive whole.
+//!
+//! ```rust,ignore
+//! let store = Arc::new(FileRaftStore::open("/var/lib/arms/raft.state")?);
+//! let node = Arc::new(RaftConsensus::open("a", ["a", "b", "c"], transport.clone(), store)?);
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::io;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, Weak};
+
+use crate::core::bytes::ByteReader;
+use crate::core::cluster::{ClusterMeta, MetaCommand, MetaError};
+use crate::ports::{Consensus, ConsensusError, ConsensusResult};
+
+/// Fewest ticks without a leader before a follower stands for election
+const ELECTION_TICKS: u32 = 10;
+
+/// Most entries sent in one append
+const MAX_APPEND: usize = 256;
+
+/// Most append round trips per peer per tick or proposal
+const MAX_ROUNDS: usize = 8;
+
+/// `FileRaftStore` magic
+const STATE_MAGIC: &[u8; 4] = b"ARMR";
+
+/// `FileRaftStore` format version
+const STATE_VERSION: u32 = 1;
+
+/// A log entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Term of the leader that appended it
+    pub term: u64,
+
+    /// The command (None = the blank entry a new leader appends)
+    pub command: Option<MetaCommand>,
+}
+
+/// A message from one node to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaftRequest {
+    /// A candidate asking for a vote
+    Vote { term: u64, candidate: String, last_index: u64, last_term: u64 },
+
+    /// The leader's entries after `prev_index` (none = heartbeat)
+    Append {
+        term: u64,
+        leader: String,
+        prev_index: u64,
+        prev_term: u64,
+        entries: Vec<Entry>,
+        commit: u64,
+    },
+
+    /// The leader's snapshot, for a follower whose next entries were
+    /// compacted away
+    Snapshot { term: u64, leader: String, last_term: u64, meta: ClusterMeta },
+}
+
+impl RaftRequest {
+    /// Name of the node sending the request
+    pub fn sender(&self) -> &str {
+        match self {
+            Self::Vote { candidate, .. } => candidate,
+            Self::Append { leader, .. } | Self::Snapshot { leader, .. } => leader,
+        }
+    }
+}
+
+/// A node's answer to a `RaftRequest`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaftResponse {
+    Vote { term: u64, granted: bool },
+
+    /// Answer to `Append` and `Snapshot`: the follower's log matches the
+    /// leader's up to `matched` (on failure, `matched` is where to retry
+    /// from)
+    Append { term: u64, success: bool, matched: u64 },
+}
+
+/// Carries requests between nodes
+pub trait RaftTransport: Send + Sync {
+    /// Deliver `request` to `peer` and wait for its answer
+    fn call(&self, peer: &str, request: RaftRequest) -> Result<RaftResponse, String>;
+}
+
+/// What a node must remember across restarts
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RaftState {
+    /// Latest term the node has seen
+    pub term: u64,
+
+    /// Candidate it voted for in `term`
+    pub voted_for: Option<String>,
+
+    /// Metadata as of the first entry not in `log`, and that entry's term
+    pub snapshot: ClusterMeta,
+    pub snapshot_term: u64,
+
+    /// Entries after the snapshot
+    pub log: Vec<Entry>,
+}
+
+impl RaftState {
+    /// Replace the entries from index `from` on with `entries`
+    pub fn save_log(&mut self, from: u64, entries: &[Entry]) {
+        let keep = from.saturating_sub(self.snapshot.applied() + 1).min(self.log.len() as u64);
+        self.log.truncate(keep as usize);
+        self.log.extend_from_slice(entries);
+    }
+
+    /// Fold entries up to `meta.applied()` into the snapshot (older
+    /// snapshots are ignored)
+    pub fn save_snapshot(&mut self, meta: &ClusterMeta, term: u64) {
+        let (index, base) = (meta.applied(), self.snapshot.applied());
+        if index <= base {
+            return;
+        }
+        let covered = (index - base).min(self.log.len() as u64);
+        self.log.drain(..covered as usize);
+        self.snapshot = meta.clone();
+        self.snapshot_term = term;
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(STATE_MAGIC);
+        out.extend_from_slice(&STATE_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.term.to_le_bytes());
+        push_bytes(&mut out, self.voted_for.as_deref().unwrap_or("").as_bytes());
+        out.extend_from_slice(&self.snapshot_term.to_le_bytes());
+        push_bytes(&mut out, &self.snapshot.encode());
+        out.extend_from_slice(&(self.log.len() as u32).to_le_bytes());
+        for entry in &self.log {
+            out.extend_from_slice(&entry.term.to_le_bytes());
+            push_bytes(&mut out, &entry.command.as_ref().map(MetaCommand::encode).unwrap_or_default());
+        }
+        out
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, MetaError> {
+        let mut r = ByteReader::new(data);
+        if &r.array::<4>("magic")? != STATE_MAGIC {
+            return Err(MetaError::BadMagic);
+        }
+        let version = r.u32("version")?;
+        if version != STATE_VERSION {
+            return Err(MetaError::UnsupportedVersion(version));
+        }
+        let term = r.u64("term")?;
+        let voted_for = Some(r.str("vote")?).filter(|name| !name.is_empty()).map(str::to_string);
+        let snapshot_term = r.u64("snapshot term")?;
+        let len = r.u32("snapshot length")?;
+        let snapshot = ClusterMeta::decode(r.bytes(len as usize, "snapshot")?)?;
+
+        let count = r.u32("entry count")?;
+        let mut log = Vec::with_capacity(r.count(count as u64, 12, "entries")?);
+        for _ in 0..count {
+            let term = r.u64("entry term")?;
+            let len = r.u32("command length")?;
+            let command = r.bytes(len as usize, "command")?;
+            let command = if command.is_empty() { None } else { Some(MetaCommand::decode(command)?) };
+            log.push(Entry { term, command });
+        }
+        Ok(Self { term, voted_for, snapshot, snapshot_term, log })
+    }
+}
+
+fn push_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Durable storage for a node's `RaftState`
+///
+/// Each save must be durable when it returns: the node answers votes and
+/// appends only after it.
+pub trait RaftStore: Send + Sync {
+    /// The saved state (None = nothing saved yet)
+    fn load(&self) -> io::Result<Option<RaftState>>;
+
+    fn save_vote(&self, term: u64, voted_for: Option<&str>) -> io::Result<()>;
+
+    /// Replace the entries from index `from` on (see `RaftState::save_log`)
+    fn save_log(&self, from: u64, entries: &[Entry]) -> io::Result<()>;
+
+    /// See `RaftState::save_snapshot`
+    fn save_snapshot(&self, meta: &ClusterMeta, term: u64) -> io::Result<()>;
+}
+
+/// A `RaftStore` in memory: it outlives a `RaftConsensus` that holds it,
+/// but not the process
+#[derive(Debug, Default)]
+pub struct MemoryRaftStore {
+    state: Mutex<Option<RaftState>>,
+}
+
+impl MemoryRaftStore {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    fn update(&self, change: impl FnOnce(&mut RaftState)) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        change(state.get_or_insert_with(RaftState::default));
+        Ok(())
+    }
+}
+
+impl RaftStore for MemoryRaftStore {
+    fn load(&self) -> io::Result<Option<RaftState>> {
+        Ok(self.state.lock().unwrap_or_else(PoisonError::into_inner).clone())
+    }
+
+    fn save_vote(&self, term: u64, voted_for: Option<&str>) -> io::Result<()> {
+        self.update(|state| {
+            state.term = term;
+            state.voted_for = voted_for.map(str::to_string);
+        })
+    }
+
+    fn save_log(&self, from: u64, entries: &[Entry]) -> io::Result<()> {
+        self.update(|state| state.save_log(from, entries))
+    }
+
+    fn save_snapshot(&self, meta: &ClusterMeta, term: u64) -> io::Result<()> {
+        self.update(|state| state.save_snapshot(meta, term))
+    }
+}
+
+/// A `RaftStore` in one file, rewritten atomically (write `<path>.tmp`,
+/// sync, rename) on every save
+///
+/// Rewrites cost the whole state, which `RaftConsensus::compact` keeps
+/// small. Not on wasm32, which has no files.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+#[derive(Debug)]
+pub struct FileRaftStore {
+    path: PathBuf,
+    state: Mutex<RaftState>,
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl FileRaftStore {
+    /// Open the state at `path` (a missing file is an empty state)
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let state = match std::fs::read(&path) {
+            Ok(bytes) => RaftState::decode(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => RaftState::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self { path, state: Mutex::new(state) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Apply `change` and write the result; the state in memory only
+    /// changes once the file has
+    fn update(&self, change: impl FnOnce(&mut RaftState)) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut next = state.clone();
+        change(&mut next);
+
+        let mut tmp = self.path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        let file = std::fs::File::create(&tmp)?;
+        io::Write::write_all(&mut &file, &next.encode())?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, &self.path)?;
+
+        *state = next;
+        Ok(())
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl RaftStore for FileRaftStore {
+    fn load(&self) -> io::Result<Option<RaftState>> {
+        Ok(Some(self.state.lock().unwrap_or_else(PoisonError::into_inner).clone()))
+    }
+
+    fn save_vote(&self, term: u64, voted_for: Option<&str>) -> io::Result<()> {
+        self.update(|state| {
+            state.term = term;
+            state.voted_for = voted_for.map(str::to_string);
+        })
+    }
+
+    fn save_log(&self, from: u64, entries: &[Entry]) -> io::Result<()> {
+        self.update(|state| state.save_log(from, entries))
+    }
+
+    fn save_snapshot(&self, meta: &ClusterMeta, term: u64) -> io::Result<()> {
+        self.update(|state| state.save_snapshot(meta, term))
+    }
+}
+
+/// What a node is doing in the current term
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaftRole {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+struct Node {
+    name: String,
+    peers: Vec<String>,
+
+    term: u64,
+    voted_for: Option<String>,
+    role: RaftRole,
+    leader: Option<String>,
+
+    /// Entries after the snapshot
+    log: Vec<Entry>,
+
+    /// Metadata as of the first entry not in `log`, and that entry's term
+    snapshot: ClusterMeta,
+    snapshot_term: u64,
+
+    /// Metadata with every committed entry applied
+    meta: ClusterMeta,
+    commit: u64,
+
+    /// Leader only: next index to send each peer, and the last one known
+    /// to match
+    next: HashMap<String, u64>,
+    matched: HashMap<String, u64>,
+
+    elapsed: u32,
+    timeout: u32,
+
+    /// Outcomes of entries proposed on this node, until `propose` takes them
+    waiting: HashMap<u64, Option<Result<(), MetaError>>>,
+
+    store: Arc<dyn RaftStore>,
+
+    /// Term and vote as last saved
+    saved_vote: (u64, Option<String>),
+
+    /// First log index changed since the last save
+    unsaved_from: Option<u64>,
+}
+
+impl Node {
+    fn base(&self) -> u64 {
+        self.snapshot.applied()
+    }
+
+    fn last_index(&self) -> u64 {
+        self.base() + self.log.len() as u64
+    }
+
+    /// Term of the entry at `index` (None if compacted away or not there)
+    fn term_at(&self, index: u64) -> Option<u64> {
+        let base = self.base();
+        match index {
+            i if i == base => Some(self.snapshot_term),
+            i if i < base => None,
+            i => self.log.get((i - base - 1) as usize).map(|entry| entry.term),
+        }
+    }
+
+    fn last_term(&self) -> u64 {
+        self.term_at(self.last_index()).unwrap_or(self.snapshot_term)
+    }
+
+    /// Last log index the store holds
+    fn saved_index(&self) -> u64 {
+        self.unsaved_from.map_or(self.last_index(), |from| from - 1)
+    }
+
+    /// Note that the log changed from `index` on
+    fn log_changed(&mut self, index: u64) {
+        self.unsaved_from = Some(self.unsaved_from.map_or(index, |from| from.min(index)));
+    }
+
+    /// Save the term, vote and log changes not yet in the store
+    fn persist(&mut self) -> io::Result<()> {
+        if (self.term, &self.voted_for) != (self.saved_vote.0, &self.saved_vote.1) {
+            self.store.save_vote(self.term, self.voted_for.as_deref())?;
+            self.saved_vote = (self.term, self.voted_for.clone());
+        }
+        if let Some(from) = self.unsaved_from {
+            let from = from.max(self.base() + 1);
+            let start = ((from - self.base() - 1) as usize).min(self.log.len());
+            self.store.save_log(from, &self.log[start..])?;
+            self.unsaved_from = None;
+        }
+        Ok(())
+    }
+
+    /// Majority of the group (peers and this node)
+    fn quorum(&self) -> usize {
+        let members = self.peers.len() + 1;
+        members / 2 + 1
+    }
+
+    /// Election timeout for the current term, spread by name so candidates
+    /// rarely collide
+    fn reset_timeout(&mut self) {
+        let mut hasher = DefaultHasher::new();
+        (&self.name, self.term).hash(&mut hasher);
+        self.timeout = ELECTION_TICKS + (hasher.finish() % ELECTION_TICKS as u64) as u32;
+        self.elapsed = 0;
+    }
+
+    fn become_follower(&mut self, term: u64, leader: Option<String>) {
+        if term > self.term {
+            self.term = term;
+            self.voted_for = None;
+        }
+        self.role = RaftRole::Follower;
+        self.leader = leader;
+        self.reset_timeout();
+    }
+
+    fn become_leader(&mut self) {
+        self.role = RaftRole::Leader;
+        self.leader = Some(self.name.clone());
+        let next = self.last_index() + 1;
+        for peer in &self.peers {
+            self.next.insert(peer.clone(), next);
+            self.matched.insert(peer.clone(), 0);
+        }
+        // Entries from earlier terms only commit behind one of this term
+        self.log.push(Entry { term: self.term, command: None });
+        self.log_changed(self.last_index());
+        self.advance_commit();
+    }
+
+    /// Commit the highest index a majority holds from this term (counting
+    /// only what this node has saved)
+    fn advance_commit(&mut self) {
+        let mut matched: Vec<u64> = self.peers.iter().map(|peer| self.matched[peer]).collect();
+        matched.push(self.saved_index());
+        matched.sort_unstable_by(|a, b| b.cmp(a));
+        let majority = matched[self.quorum() - 1];
+        if majority > self.commit && self.term_at(majority) == Some(self.term) {
+            self.commit = majority;
+            self.apply_committed();
+        }
+    }
+
+    fn apply_committed(&mut self) {
+        while self.meta.applied() < self.commit {
+            let index = self.meta.applied() + 1;
+            let entry = &self.log[(index - self.base() - 1) as usize];
+            let outcome = match &entry.command {
+                Some(command) => self.meta.apply(index, command),
+                None => {
+                    self.meta.skip(index);
+                    Ok(())
+                }
+            };
+            if let Some(slot) = self.waiting.get_mut(&index) {
+                *slot = Some(outcome);
+            }
+        }
+    }
+
+    /// Next request for `peer`: its missing entries, or the snapshot when
+    /// they were compacted away
+    fn request_for(&self, peer: &str) -> RaftRequest {
+        let prev_index = self.next[peer] - 1;
+        match self.term_at(prev_index) {
+            Some(prev_term) => {
+                let start = (prev_index - self.base()) as usize;
+                let end = self.log.len().min(start + MAX_APPEND);
+                RaftRequest::Append {
+                    term: self.term,
+                    leader: self.name.clone(),
+                    prev_index,
+                    prev_term,
+                    entries: self.log[start..end].to_vec(),
+                    commit: self.commit,
+                }
+            }
+            None => RaftRequest::Snapshot {
+                term: self.term,
+                leader: self.name.clone(),
+                last_term: self.snapshot_term,
+                meta: self.snapshot.clone(),
+            },
+        }
+    }
+
+    /// Take in `peer`'s answer to a request sent in `term`; true if the
+    /// peer still needs more
+    fn on_append_response(&mut self, peer: &str, term: u64, response: RaftResponse) -> bool {
+        let RaftResponse::Append { term: peer_term, success, matched } = response else {
+            return false;
+        };
+        if peer_term > self.term {
+            self.become_follower(peer_term, None);
+            return false;
+        }
+        if self.role != RaftRole::Leader || self.term != term {
+            return false;
+        }
+        let next = self.next[peer];
+        if success {
+            let matched = self.matched[peer].max(matched);
+            self.matched.insert(peer.to_string(), matched);
+            self.next.insert(peer.to_string(), matched + 1);
+            self.advance_commit();
+            matched < self.last_index()
+        } else {
+            self.next.insert(peer.to_string(), (next - 1).min(matched + 1).max(1));
+            true
+        }
+    }
+
+    /// Answer `request`, after saving what it changed; a node that can't
+    /// save refuses
+    fn handle(&mut self, request: RaftRequest) -> RaftResponse {
+        let response = self.respond(request);
+        match self.persist() {
+            Ok(()) => response,
+            Err(_) => self.refusal(&response),
+        }
+    }
+
+    fn refusal(&self, response: &RaftResponse) -> RaftResponse {
+        match response {
+            RaftResponse::Vote { .. } => RaftResponse::Vote { term: self.term, granted: false },
+            RaftResponse::Append { .. } => RaftResponse::Append { term: self.term, success: false, matched: self.commit },
+        }
+    }
+
+    fn respond(&mut self, request: RaftRequest) -> RaftResponse {
+        match request {
+            RaftRequest::Vote { term, candidate, last_index, last_term } => {
+                if term > self.term {
+                    self.become_follower(term, None);
+                }
+                let up_to_date = (last_term, last_index) >= (self.last_term(), self.last_index());
+                let free = self.voted_for.as_ref().is_none_or(|voted| *voted == candidate);
+                let granted = term == self.term && free && up_to_date;
+                if granted {
+                    self.voted_for = Some(candidate);
+                    self.reset_timeout();
+                }
+                RaftResponse::Vote { term: self.term, granted }
+            }
+            RaftRequest::Append { term, leader, prev_index, prev_term, entries, commit } => {
+                if term < self.term {
+                    return RaftResponse::Append { term: self.term, success: false, matched: self.last_index() };
+                }
+                self.become_follower(term, Some(leader));
+
+                // Entries up to the snapshot are committed here already
+                let base = self.base();
+                let (prev_index, prev_term, entries) = if prev_index < base {
+                    let skip = ((base - prev_index) as usize).min(entries.len());
+                    (base, self.snapshot_term, &entries[skip..])
+                } else {
+                    (prev_index, prev_term, &entries[..])
+                };
+                if self.term_at(prev_index) != Some(prev_term) {
+                    let matched = self.last_index().min(prev_index.saturating_sub(1));
+                    return RaftResponse::Append { term: self.term, success: false, matched };
+                }
+
+                for (index, entry) in (prev_index + 1..).zip(entries) {
+                    match self.term_at(index) {
+                        Some(term) if term == entry.term => continue,
+                        Some(_) => self.log.truncate((index - base - 1) as usize),
+                        None => {}
+                    }
+                    self.log.push(entry.clone());
+                    self.log_changed(index);
+                }
+                let matched = prev_index + entries.len() as u64;
+                if commit > self.commit {
+                    self.commit = commit.min(matched).max(self.commit);
+                    self.apply_committed();
+                }
+                RaftResponse::Append { term: self.term, success: true, matched }
+            }
+            RaftRequest::Snapshot { term, leader, last_term, meta } => {
+                if term < self.term {
+                    return RaftResponse::Append { term: self.term, success: false, matched: self.last_index() };
+                }
+                self.become_follower(term, Some(leader));
+
+                let index = meta.applied();
+                if index > self.commit {
+                    if self.store.save_snapshot(&meta, last_term).is_err() {
+                        return RaftResponse::Append { term: self.term, success: false, matched: self.commit };
+                    }
+                    // Keep entries past the snapshot if the logs agree on it
+                    if self.term_at(index) == Some(last_term) {
+                        self.log.drain(..(index - self.base()) as usize);
+                    } else {
+                        self.log.clear();
+                        self.log_changed(index + 1);
+                    }
+                    self.snapshot = meta.clone();
+                    self.snapshot_term = last_term;
+                    self.meta = meta;
+                    self.commit = index;
+                }
+                RaftResponse::Append { term: self.term, success: true, matched: index }
+            }
+        }
+    }
+}
+
+/// One node of a Raft group agreeing on `ClusterMeta`
+pub struct RaftConsensus {
+    node: Mutex<Node>,
+    transport: Arc<dyn RaftTransport>,
+}
+
+impl RaftConsensus {
+    /// A follower named `name` in a group of `members` (which may list it),
+    /// keeping its state in memory only
+    ///
+    /// For nodes that never restart; a node that may restart must `open`
+    /// its state from a durable store.
+    pub fn new<I>(name: impl Into<String>, members: I, transport: Arc<dyn RaftTransport>) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Self::open(name, members, transport, MemoryRaftStore::new()).expect("memory store can't fail")
+    }
+
+    /// A follower named `name` in a group of `members`, restored from
+    /// `store` (which it keeps saving to)
+    ///
+    /// Committed entries past the snapshot are applied again once the
+    /// leader confirms the commit index.
+    pub fn open<I>(
+        name: impl Into<String>,
+        members: I,
+        transport: Arc<dyn RaftTransport>,
+        store: Arc<dyn RaftStore>,
+    ) -> io::Result<Self>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let state = store.load()?.unwrap_or_default();
+        let name = name.into();
+        let mut peers: Vec<String> = members.into_iter().map(Into::into).filter(|member| *member != name).collect();
+        peers.sort();
+        peers.dedup();
+
+        let mut node = Node {
+            name,
+            peers,
+            term: state.term,
+            voted_for: state.voted_for.clone(),
+            role: RaftRole::Follower,
+            leader: None,
+            log: state.log,
+            commit: state.snapshot.applied(),
+            meta: state.snapshot.clone(),
+            snapshot: state.snapshot,
+            snapshot_term: state.snapshot_term,
+            next: HashMap::new(),
+            matched: HashMap::new(),
+            elapsed: 0,
+            timeout: 0,
+            waiting: HashMap::new(),
+            store,
+            saved_vote: (state.term, state.voted_for),
+            unsaved_from: None,
+        };
+        node.reset_timeout();
+        Ok(Self { node: Mutex::new(node), transport })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Node> {
+        self.node.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn name(&self) -> String {
+        self.lock().name.clone()
+    }
+
+    pub fn role(&self) -> RaftRole {
+        self.lock().role
+    }
+
+    pub fn term(&self) -> u64 {
+        self.lock().term
+    }
+
+    /// The leader this node last heard from (itself, when leading)
+    pub fn leader(&self) -> Option<String> {
+        self.lock().leader.clone()
+    }
+
+    /// Index of the last committed entry
+    pub fn commit_index(&self) -> u64 {
+        self.lock().commit
+    }
+
+    /// Entries held in memory (not yet folded into the snapshot)
+    pub fn log_len(&self) -> usize {
+        self.lock().log.len()
+    }
+
+    /// Fold applied entries into the snapshot, returning how many were
+    /// dropped from the log
+    pub fn compact(&self) -> io::Result<usize> {
+        let mut node = self.lock();
+        let (applied, base) = (node.meta.applied(), node.base());
+        if applied <= base {
+            return Ok(0);
+        }
+        let term = node.term_at(applied).unwrap_or(node.snapshot_term);
+        node.store.save_snapshot(&node.meta, term)?;
+        node.snapshot_term = term;
+        node.log.drain(..(applied - base) as usize);
+        node.snapshot = node.meta.clone();
+        Ok((applied - base) as usize)
+    }
+
+    /// Answer a request from another node (what a transport delivers)
+    pub fn handle(&self, request: RaftRequest) -> RaftResponse {
+        self.lock().handle(request)
+    }
+
+    /// Advance time by one tick: heartbeat as leader, or stand for
+    /// election once the timeout passes without a leader
+    pub fn tick(&self) {
+        let mut node = self.lock();
+        node.elapsed += 1;
+        match node.role {
+            RaftRole::Leader => {
+                drop(node);
+                self.broadcast();
+            }
+            _ if node.elapsed >= node.timeout => {
+                drop(node);
+                self.campaign();
+            }
+            _ => {}
+        }
+    }
+
+    fn campaign(&self) {
+        let (request, term, peers) = {
+            let mut node = self.lock();
+            node.term += 1;
+            node.role = RaftRole::Candidate;
+            node.voted_for = Some(node.name.clone());
+            node.leader = None;
+            node.reset_timeout();
+            // Asking for votes before the own vote is saved could vote
+            // twice after a restart; the next timeout retries
+            if node.persist().is_err() {
+                return;
+            }
+            let request = RaftRequest::Vote {
+                term: node.term,
+                candidate: node.name.clone(),
+                last_index: node.last_index(),
+                last_term: node.last_term(),
+            };
+            (request, node.term, node.peers.clone())
+        };
+
+        // Calls are made without holding the lock, so two candidates
+        // asking each other can't deadlock
+        let mut votes = 1;
+        for peer in &peers {
+            match self.transport.call(peer, request.clone()) {
+                Ok(RaftResponse::Vote { term: peer_term, .. }) if peer_term > term => {
+                    self.lock().become_follower(peer_term, None);
+                    return;
+                }
+                Ok(RaftResponse::Vote { granted: true, .. }) => votes += 1,
+                _ => {}
+            }
+        }
+
+        let mut node = self.lock();
+        if node.role == RaftRole::Candidate && node.term == term && votes >= node.quorum() {
+            node.become_leader();
+            drop(node);
+            self.broadcast();
+        }
+    }
+
+    /// Bring every reachable peer up to date, then commit what a majority
+    /// holds
+    fn broadcast(&self) {
+        let peers = {
+            let mut node = self.lock();
+            // Unsaved entries are retried here; until then `advance_commit`
+            // doesn't count them for this node
+            let _ = node.persist();
+            node.peers.clone()
+        };
+        for peer in &peers {
+            self.replicate(peer);
+        }
+        let mut node = self.lock();
+        if node.role == RaftRole::Leader {
+            node.advance_commit();
+        }
+    }
+
+    fn replicate(&self, peer: &str) {
+        for _ in 0..MAX_ROUNDS {
+            let (request, term) = {
+                let node = self.lock();
+                if node.role != RaftRole::Leader {
+                    return;
+                }
+                (node.request_for(peer), node.term)
+            };
+            let Ok(response) = self.transport.call(peer, request) else {
+                return;
+            };
+            if !self.lock().on_append_response(peer, term, response) {
+                return;
+            }
+        }
+    }
+}
+
+impl Consensus for RaftConsensus {
+    /// Append on the leader and replicate; `Unavailable` if no majority
+    /// acknowledged the entry (it may still commit later)
+    fn propose(&self, command: MetaCommand) -> ConsensusResult<u64> {
+        let index = {
+            let mut node = self.lock();
+            if node.role != RaftRole::Leader {
+                return Err(ConsensusError::NotLeader { leader: node.leader.clone() });
+            }
+            let term = node.term;
+            node.log.push(Entry { term, command: Some(command) });
+            let index = node.last_index();
+            node.log_changed(index);
+            node.waiting.insert(index, None);
+            if let Err(e) = node.persist() {
+                node.waiting.remove(&index);
+                return Err(ConsensusError::Unavailable(format!("entry {} not saved: {}", index, e)));
+            }
+            index
+        };
+
+        self.broadcast();
+
+        match self.lock().waiting.remove(&index) {
+            Some(Some(outcome)) => outcome.map(|()| index).map_err(|reason| ConsensusError::Rejected { index, reason }),
+            _ => Err(ConsensusError::Unavailable(format!("entry {} not acknowledged by a majority", index))),
+        }
+    }
+
+    fn metadata(&self) -> ClusterMeta {
+        self.lock().meta.clone()
+    }
+}
+
+/// Transport between `RaftConsensus` nodes in one process
+///
+/// `disconnect` cuts a node off to simulate a network partition.
+#[derive(Default)]
+pub struct LocalTransport {
+    nodes: RwLock<HashMap<String, Weak<RaftConsensus>>>,
+    cut: RwLock<HashSet<String>>,
+}
+
+impl LocalTransport {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Deliver requests for `node`'s name to it
+    pub fn register(&self, node: &Arc<RaftConsensus>) {
+        let mut nodes = self.nodes.write().unwrap_or_else(PoisonError::into_inner);
+        nodes.insert(node.name(), Arc::downgrade(node));
+    }
+
+    /// Drop every request to or from `name`
+    pub fn disconnect(&self, name: &str) {
+        self.cut.write().unwrap_or_else(PoisonError::into_inner).insert(name.to_string());
+    }
+
+    pub fn reconnect(&self, name: &str) {
+        self.cut.write().unwrap_or_else(PoisonError::into_inner).remove(name);
+    }
+}
+
+impl RaftTransport for LocalTransport {
+    fn call(&self, peer: &str, request: RaftRequest) -> Result<RaftResponse, String> {
+        {
+            let cut = self.cut.read().unwrap_or_else(PoisonError::into_inner);
+            if cut.contains(peer) || cut.contains(request.sender()) {
+                return Err(format!("{} unreachable", peer));
+            }
+        }
+        let node = {
+            let nodes = self.nodes.read().unwrap_or_else(PoisonError::into_inner);
+            nodes.get(peer).and_then(Weak::upgrade)
+        };
+        node.map(|node| node.handle(request)).ok_or_else(|| format!("unknown node {}", peer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(names: &[&str]) -> (Arc<LocalTransport>, Vec<Arc<RaftConsensus>>) {
+        let transport = LocalTransport::new();
+        let nodes: Vec<_> = names
+            .iter()
+            .map(|name| Arc::new(RaftConsensus::new(*name, names.iter().copied(), transport.clone())))
+            .collect();
+        nodes.iter().for_each(|node| transport.register(node));
+        (transport, nodes)
+    }
+
+    /// Tick every node until `done` holds (panics after 500 rounds)
+    fn tick_until(nodes: &[Arc<RaftConsensus>], done: impl Fn() -> bool) {
+        for _ in 0..500 {
+            if done() {
+                return;
+            }
+            nodes.iter().for_each(|node| node.tick());
+        }
+        panic!("cluster did not settle");
+    }
+
+    fn leader_among<'a>(nodes: &'a [Arc<RaftConsensus>], transport: &LocalTransport) -> Option<&'a Arc<RaftConsensus>> {
+        let cut = transport.cut.read().unwrap();
+        let mut leaders = nodes.iter().filter(|node| node.role() == RaftRole::Leader && !cut.contains(&node.name()));
+        leaders.next().filter(|_| leaders.next().is_none())
+    }
+
+    fn add(name: &str) -> MetaCommand {
+        MetaCommand::AddNode { name: name.into(), addr: format!("{}:9090", name) }
+    }
+
+    fn agree(nodes: &[Arc<RaftConsensus>]) -> bool {
+        nodes.windows(2).all(|pair| pair[0].metadata() == pair[1].metadata())
+    }
+
+    #[test]
+    fn test_single_node_elects_itself() {
+        let (_, nodes) = group(&["solo"]);
+        tick_until(&nodes, || nodes[0].role() == RaftRole::Leader);
+        assert_eq!(nodes[0].propose(add("a")), Ok(2));
+        assert_eq!(nodes[0].metadata().nodes().len(), 1);
+    }
+
+    #[test]
+    fn test_raft_replicates_and_fails_over() {
+        let (transport, nodes) = group(&["a", "b", "c"]);
+        tick_until(&nodes, || leader_among(&nodes, &transport).is_some());
+        let leader = leader_among(&nodes, &transport).unwrap().clone();
+
+        let index = leader.propose(add("shard-1")).unwrap();
+        assert!(matches!(
+            leader.propose(add("shard-1")),
+            Err(ConsensusError::Rejected { reason: MetaError::NodeExists(_), .. })
+        ));
+        let follower = nodes.iter().find(|node| node.name() != leader.name()).unwrap();
+        assert_eq!(
+            follower.propose(add("shard-2")),
+            Err(ConsensusError::NotLeader { leader: Some(leader.name()) })
+        );
+        tick_until(&nodes, || agree(&nodes) && follower.commit_index() > index);
+
+        // Cut the leader off: the other two elect a new one and keep
+        // committing, while the old leader can't reach a majority
+        transport.disconnect(&leader.name());
+        assert!(matches!(leader.propose(add("lost")), Err(ConsensusError::Unavailable(_))));
+        tick_until(&nodes, || leader_among(&nodes, &transport).is_some_and(|l| l.name() != leader.name()));
+        let successor = leader_among(&nodes, &transport).unwrap().clone();
+        assert!(successor.term() > leader.term());
+        successor.propose(add("shard-2")).unwrap();
+
+        // Healed, the old leader steps down and drops its uncommitted entry
+        transport.reconnect(&leader.name());
+        tick_until(&nodes, || leader.role() == RaftRole::Follower && agree(&nodes));
+        let nodes_known = leader.metadata().nodes().keys().cloned().collect::<Vec<_>>();
+        assert_eq!(nodes_known, ["shard-1", "shard-2"]);
+    }
+
+    #[test]
+    fn test_lagging_follower_catches_up_from_snapshot() {
+        let (transport, nodes) = group(&["a", "b", "c"]);
+        tick_until(&nodes, || leader_among(&nodes, &transport).is_some());
+        let leader = leader_among(&nodes, &transport).unwrap().clone();
+        let lagging = nodes.iter().find(|node| node.name() != leader.name()).unwrap().clone();
+
+        transport.disconnect(&lagging.name());
+        for i in 0..5 {
+            leader.propose(add(&format!("shard-{}", i))).unwrap();
+        }
+        assert!(leader.compact().unwrap() >= 5);
+        assert_eq!(leader.log_len(), 0);
+
+        transport.reconnect(&lagging.name());
+        tick_until(&nodes, || agree(&nodes));
+        assert_eq!(lagging.metadata().nodes().len(), 5);
+    }
+
+    #[test]
+    fn test_restarted_node_keeps_its_vote() {
+        let transport = LocalTransport::new();
+        let store = MemoryRaftStore::new();
+        let vote = |candidate: &str| RaftRequest::Vote { term: 5, candidate: candidate.into(), last_index: 0, last_term: 0 };
+
+        let node = RaftConsensus::open("b", ["a", "b", "c"], transport.clone(), store.clone()).unwrap();
+        assert_eq!(node.handle(vote("a")), RaftResponse::Vote { term: 5, granted: true });
+        drop(node);
+
+        // Back from a restart, it still voted for `a` in term 5
+        let node = RaftConsensus::open("b", ["a", "b", "c"], transport, store).unwrap();
+        assert_eq!(node.term(), 5);
+        assert_eq!(node.handle(vote("c")), RaftResponse::Vote { term: 5, granted: false });
+        assert_eq!(node.handle(vote("a")), RaftResponse::Vote { term: 5, granted: true });
+    }
+
+    #[test]
+    fn test_committed_entries_survive_full_restart() {
+        let names = ["a", "b", "c"];
+        let path = |name: &str| crate::testing::temp_path(&format!("arms-raft-{}", name));
+        let paths: Vec<_> = names.iter().map(|name| path(name)).collect();
+        let start = |transport: &Arc<LocalTransport>| -> Vec<Arc<RaftConsensus>> {
+            let nodes: Vec<_> = names
+                .iter()
+                .zip(&paths)
+                .map(|(name, path)| {
+                    let store = Arc::new(FileRaftStore::open(path).unwrap());
+                    Arc::new(RaftConsensus::open(*name, names, transport.clone(), store).unwrap())
+                })
+                .collect();
+            nodes.iter().for_each(|node| transport.register(node));
+            nodes
+        };
+
+        let transport = LocalTransport::new();
+        let nodes = start(&transport);
+        tick_until(&nodes, || leader_among(&nodes, &transport).is_some());
+        let leader = leader_among(&nodes, &transport).unwrap().clone();
+        leader.propose(add("shard-1")).unwrap();
+        leader.compact().unwrap();
+        leader.propose(add("shard-2")).unwrap();
+        let term = leader.term();
+        drop((leader, nodes));
+
+        // Every node restarts at once: terms, and the entries a majority
+        // held, come back from the files
+        let transport = LocalTransport::new();
+        let nodes = start(&transport);
+        assert!(nodes.iter().all(|node| node.term() == term));
+        tick_until(&nodes, || leader_among(&nodes, &transport).is_some());
+        let leader = leader_among(&nodes, &transport).unwrap().clone();
+        assert!(leader.term() > term);
+        tick_until(&nodes, || agree(&nodes) && leader.metadata().nodes().len() == 2);
+
+        for path in paths {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
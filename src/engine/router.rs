@@ -0,0 +1,422 @@
+//! # Shard Router
+//!
+//! Client-side partitioning of points across several ARMS nodes.
+//!
+//! IDs map to nodes through a consistent-hash ring: every node owns many
+//! virtual points on the ring, and an ID belongs to the first node point at
+//! or after its hash. Adding or removing a node only moves the IDs between
+//! it and its ring neighbors (about `1 / nodes` of them), and
+//! `add_node`/`remove_node` migrate exactly those points.
+//!
+//! Writes go to the owning node; `near` asks every node in parallel and
+//! merges their results.
+//!
+//! ```rust,ignore
+//! let mut router = ShardRouter::new(ScoreOrder::HigherIsBetter);
+//! router.add_node(Arc::new(RemoteShard::new("a", "10.0.0.1:9090")))?;
+//! router.add_node(Arc::new(RemoteShard::new("b", "10.0.0.2:9090")))?;
+//!
+//! let id = router.place(embedding, Blob::from_str("hello"))?;
+//! let results = router.near(&query, 10)?;
+//!
+//! // Scale out: points now owned by "c" move there
+//! let report = router.add_node(Arc::new(RemoteShard::new("c", "10.0.0.3:9090")))?;
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::core::cluster::ClusterMeta;
+use crate::core::proximity::ScoreOrder;
+use crate::core::{Blob, Id, Point};
+use crate::ports::{SearchResult, ShardError, ShardNode, ShardResult};
+
+use super::Arms;
+
+/// Virtual points per node (more spreads IDs more evenly)
+pub const DEFAULT_REPLICAS: usize = 64;
+
+/// 64-bit FNV-1a, finished with a splitmix64 round
+///
+/// FNV alone leaves short, similar keys ("a#0", "a#1") clustered on the
+/// ring; the final mix spreads them out.
+fn ring_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+    hash ^ (hash >> 31)
+}
+
+/// Consistent-hash ring of node names
+///
+/// Placement depends only on node names and IDs, so every client with the
+/// same membership routes the same way.
+#[derive(Debug, Clone)]
+pub struct HashRing {
+    ring: BTreeMap<u64, String>,
+    replicas: usize,
+}
+
+impl HashRing {
+    pub fn new(replicas: usize) -> Self {
+        Self { ring: BTreeMap::new(), replicas: replicas.max(1) }
+    }
+
+    fn points(&self, node: &str) -> impl Iterator<Item = u64> + '_ {
+        let node = node.to_string();
+        (0..self.replicas).map(move |i| ring_hash(format!("{}#{}", node, i).as_bytes()))
+    }
+
+    pub fn add(&mut self, node: &str) {
+        for point in self.points(node).collect::<Vec<_>>() {
+            self.ring.insert(point, node.to_string());
+        }
+    }
+
+    pub fn remove(&mut self, node: &str) {
+        self.ring.retain(|_, owner| owner != node);
+    }
+
+    /// Node owning `id` (None if the ring is empty)
+    pub fn owner(&self, id: Id) -> Option<&str> {
+        let hash = ring_hash(id.as_bytes());
+        self.ring
+            .range(hash..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, node)| node.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+}
+
+/// Points moved by a membership change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RebalanceReport {
+    /// IDs checked
+    pub scanned: usize,
+
+    /// Points moved to their new owner
+    pub moved: usize,
+}
+
+/// Routes points to shard nodes and fans queries out to all of them
+pub struct ShardRouter {
+    nodes: HashMap<String, Arc<dyn ShardNode>>,
+    ring: HashRing,
+    order: ScoreOrder,
+}
+
+impl ShardRouter {
+    /// Empty router; `order` ranks merged results (the nodes' proximity's)
+    pub fn new(order: ScoreOrder) -> Self {
+        Self { nodes: HashMap::new(), ring: HashRing::new(DEFAULT_REPLICAS), order }
+    }
+
+    /// Virtual points per node (set before adding nodes)
+    pub fn with_replicas(mut self, replicas: usize) -> Self {
+        self.ring = HashRing::new(replicas);
+        for name in self.nodes.keys() {
+            self.ring.add(name);
+        }
+        self
+    }
+
+    /// Node names, sorted
+    pub fn nodes(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.nodes.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    /// Node that owns `id`
+    pub fn owner(&self, id: Id) -> ShardResult<&Arc<dyn ShardNode>> {
+        let name = self.ring.owner(id).ok_or(ShardError::NoNodes)?;
+        Ok(&self.nodes[name])
+    }
+
+    /// Add a node and move the points it now owns onto it
+    pub fn add_node(&mut self, node: Arc<dyn ShardNode>) -> ShardResult<RebalanceReport> {
+        let name = node.name().to_string();
+        if self.nodes.contains_key(&name) {
+            return Err(ShardError::DuplicateNode(name));
+        }
+        let sources: Vec<Arc<dyn ShardNode>> = self.nodes.values().cloned().collect();
+        self.ring.add(&name);
+        self.nodes.insert(name, node);
+        self.migrate(&sources)
+    }
+
+    /// Move a node's points to their new owners, then drop it
+    ///
+    /// The node must still be reachable. If migration fails it stays out
+    /// of the ring, and `rebalance` (after re-adding it) finishes the job.
+    pub fn remove_node(&mut self, name: &str) -> ShardResult<RebalanceReport> {
+        let node = self
+            .nodes
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ShardError::UnknownNode(name.to_string()))?;
+        self.ring.remove(name);
+        if self.ring.is_empty() {
+            self.ring.add(name);
+            return Err(ShardError::NoNodes);
+        }
+        let report = self.migrate(std::slice::from_ref(&node))?;
+        self.nodes.remove(name);
+        Ok(report)
+    }
+
+    /// Match membership to an agreed shard map
+    ///
+    /// Nodes missing from `meta` are removed (draining their points), and
+    /// nodes only in `meta` are connected with `connect(name, addr)` and
+    /// added. Every client syncing to the same metadata routes the same way.
+    pub fn sync_nodes<F>(&mut self, meta: &ClusterMeta, connect: F) -> ShardResult<RebalanceReport>
+    where
+        F: Fn(&str, &str) -> Arc<dyn ShardNode>,
+    {
+        let mut report = RebalanceReport::default();
+        let mut tally = |step: RebalanceReport| {
+            report.scanned += step.scanned;
+            report.moved += step.moved;
+        };
+        // Add first, so removed nodes have somewhere to drain to
+        for (name, addr) in meta.nodes() {
+            if !self.nodes.contains_key(name) {
+                tally(self.add_node(connect(name, addr))?);
+            }
+        }
+        let stale: Vec<String> = self.nodes.keys().filter(|name| !meta.nodes().contains_key(*name)).cloned().collect();
+        for name in stale {
+            tally(self.remove_node(&name)?);
+        }
+        Ok(report)
+    }
+
+    /// Move every point not on its owner (after an interrupted migration)
+    pub fn rebalance(&self) -> ShardResult<RebalanceReport> {
+        let sources: Vec<Arc<dyn ShardNode>> = self.nodes.values().cloned().collect();
+        self.migrate(&sources)
+    }
+
+    /// Move points on `sources` that belong elsewhere
+    fn migrate(&self, sources: &[Arc<dyn ShardNode>]) -> ShardResult<RebalanceReport> {
+        let mut report = RebalanceReport::default();
+        for source in sources {
+            for id in source.ids()? {
+                report.scanned += 1;
+                let owner = self.owner(id)?;
+                if owner.name() == source.name() {
+                    continue;
+                }
+                let Some((point, blob)) = source.get(id)? else { continue };
+                // A move interrupted after the copy left it on the owner already
+                if owner.get(id)?.is_none() {
+                    owner.place(id, point, blob)?;
+                }
+                source.remove(id)?;
+                report.moved += 1;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Place a point on its owner under a new ID
+    pub fn place(&self, point: Point, blob: Blob) -> ShardResult<Id> {
+        let id = Id::now();
+        self.place_with_id(id, point, blob)?;
+        Ok(id)
+    }
+
+    pub fn place_with_id(&self, id: Id, point: Point, blob: Blob) -> ShardResult<()> {
+        self.owner(id)?.place(id, point, blob)
+    }
+
+    pub fn remove(&self, id: Id) -> ShardResult<bool> {
+        self.owner(id)?.remove(id)
+    }
+
+    pub fn get(&self, id: Id) -> ShardResult<Option<(Point, Blob)>> {
+        self.owner(id)?.get(id)
+    }
+
+    /// The k nearest points across all nodes
+    ///
+    /// Nodes are queried in parallel (in turn on `wasm32-unknown-unknown`,
+    /// which has no threads); any node failing fails the query.
+    pub fn near(&self, query: &Point, k: usize) -> ShardResult<Vec<SearchResult>> {
+        if self.nodes.is_empty() {
+            return Err(ShardError::NoNodes);
+        }
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        let answers: Vec<ShardResult<Vec<SearchResult>>> = self.nodes.values().map(|node| node.near(query, k)).collect();
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        let answers: Vec<ShardResult<Vec<SearchResult>>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .nodes
+                .values()
+                .map(|node| scope.spawn(move || node.near(query, k)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("shard query panicked"))
+                .collect()
+        });
+
+        let mut results = Vec::new();
+        for answer in answers {
+            results.extend(answer?);
+        }
+        SearchResult::sort(&mut results, self.order);
+        results.truncate(k);
+        Ok(results)
+    }
+}
+
+/// An in-process node wrapping a shared `Arms`
+pub struct LocalShard {
+    name: String,
+    arms: Arc<RwLock<Arms>>,
+}
+
+impl LocalShard {
+    pub fn new(name: impl Into<String>, arms: Arc<RwLock<Arms>>) -> Self {
+        Self { name: name.into(), arms }
+    }
+
+    pub fn arms(&self) -> &Arc<RwLock<Arms>> {
+        &self.arms
+    }
+
+    fn failed(&self, e: impl std::fmt::Display) -> ShardError {
+        ShardError::Node { node: self.name.clone(), message: e.to_string() }
+    }
+}
+
+impl ShardNode for LocalShard {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn place(&self, id: Id, point: Point, blob: Blob) -> ShardResult<()> {
+        let mut arms = self.arms.write().unwrap_or_else(PoisonError::into_inner);
+        arms.place_with_id(id, point, blob).map_err(|e| self.failed(e))
+    }
+
+    fn remove(&self, id: Id) -> ShardResult<bool> {
+        let mut arms = self.arms.write().unwrap_or_else(PoisonError::into_inner);
+        Ok(arms.remove(id).is_some())
+    }
+
+    fn get(&self, id: Id) -> ShardResult<Option<(Point, Blob)>> {
+        let arms = self.arms.read().unwrap_or_else(PoisonError::into_inner);
+        Ok(arms.get(id).map(|placed| (placed.point.clone(), placed.blob.clone())))
+    }
+
+    fn ids(&self) -> ShardResult<Vec<Id>> {
+        let arms = self.arms.read().unwrap_or_else(PoisonError::into_inner);
+        Ok(arms.iter().map(|placed| placed.id).collect())
+    }
+
+    fn near(&self, query: &Point, k: usize) -> ShardResult<Vec<SearchResult>> {
+        let arms = self.arms.read().unwrap_or_else(PoisonError::into_inner);
+        arms.near(query, k).map_err(|e| self.failed(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cluster::MetaCommand;
+    use crate::core::config::ArmsConfig;
+
+    fn shard(name: &str) -> (Arc<LocalShard>, Arc<RwLock<Arms>>) {
+        let arms = Arc::new(RwLock::new(Arms::new(ArmsConfig::new(2))));
+        (Arc::new(LocalShard::new(name, arms.clone())), arms)
+    }
+
+    fn len(arms: &Arc<RwLock<Arms>>) -> usize {
+        arms.read().unwrap().len()
+    }
+
+    #[test]
+    fn test_ring_is_stable_and_spread() {
+        let mut ring = HashRing::new(DEFAULT_REPLICAS);
+        assert_eq!(ring.owner(Id::seeded(0, 1)), None);
+        for name in ["a", "b", "c"] {
+            ring.add(name);
+        }
+        let ids: Vec<Id> = (0..3000).map(|i| Id::seeded(7, i)).collect();
+        let before: Vec<String> = ids.iter().map(|&id| ring.owner(id).unwrap().to_string()).collect();
+        for name in ["a", "b", "c"] {
+            let share = before.iter().filter(|owner| *owner == name).count();
+            assert!((500..1500).contains(&share), "{} owns {}", name, share);
+        }
+
+        // Adding a node only takes IDs over; nothing moves between old nodes
+        ring.add("d");
+        for (id, old) in ids.iter().zip(&before) {
+            let new = ring.owner(*id).unwrap();
+            assert!(new == old || new == "d");
+        }
+    }
+
+    #[test]
+    fn test_router_places_queries_and_rebalances() {
+        let mut router = ShardRouter::new(ScoreOrder::HigherIsBetter);
+        let point = Point::new(vec![1.0, 0.0]);
+        assert_eq!(router.place(point.clone(), Blob::empty()), Err(ShardError::NoNodes));
+
+        let (a, arms_a) = shard("a");
+        let (b, arms_b) = shard("b");
+        router.add_node(a.clone()).unwrap();
+        router.add_node(b).unwrap();
+        assert_eq!(router.add_node(a), Err(ShardError::DuplicateNode("a".into())));
+
+        let ids: Vec<Id> = (0..40)
+            .map(|i| {
+                let angle = i as f32 * 0.03;
+                router.place(Point::new(vec![angle.cos(), angle.sin()]), Blob::from_str("x")).unwrap()
+            })
+            .collect();
+        assert_eq!(len(&arms_a) + len(&arms_b), 40);
+        assert!(len(&arms_a) > 0 && len(&arms_b) > 0);
+
+        // Results merged across shards, best first
+        let results = router.near(&point, 3).unwrap();
+        assert_eq!(results.iter().map(|r| r.id).collect::<Vec<_>>(), ids[..3].to_vec());
+
+        let (c, arms_c) = shard("c");
+        let report = router.add_node(c).unwrap();
+        assert_eq!(report.scanned, 40);
+        assert_eq!(report.moved, len(&arms_c));
+        assert!(ids.iter().all(|&id| router.get(id).unwrap().is_some()));
+
+        let moved_back = len(&arms_a);
+        let report = router.remove_node("a").unwrap();
+        assert_eq!((report.scanned, report.moved), (moved_back, moved_back));
+        assert_eq!(len(&arms_a), 0);
+        assert_eq!(len(&arms_b) + len(&arms_c), 40);
+        assert_eq!(router.nodes(), vec!["b", "c"]);
+        assert_eq!(router.rebalance().unwrap().moved, 0);
+
+        let mut meta = ClusterMeta::new();
+        meta.apply(1, &MetaCommand::AddNode { name: "c".into(), addr: String::new() }).unwrap();
+        meta.apply(2, &MetaCommand::AddNode { name: "d".into(), addr: String::new() }).unwrap();
+        let (d, arms_d) = shard("d");
+        router.sync_nodes(&meta, |_, _| d.clone()).unwrap();
+        assert_eq!(router.nodes(), vec!["c", "d"]);
+        assert_eq!(len(&arms_b), 0);
+        assert_eq!(len(&arms_c) + len(&arms_d), 40);
+
+        assert!(router.remove(ids[0]).unwrap());
+        assert_eq!(router.get(ids[0]).unwrap(), None);
+    }
+}
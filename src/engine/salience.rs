@@ -0,0 +1,212 @@
+//! # Salience
+//!
+//! Importance scores assigned when a point is placed.
+//!
+//! A `Salience` looks at the incoming point, its payload, its conversation
+//! role and how far it lies from what is already stored, and returns a score
+//! in `[0, 1]`. `Arms` keeps the score with the point and uses it to:
+//! - nudge retrieval toward important memories (the salience bias);
+//! - pick what to forget first under `CapacityPolicy::EvictLeastSalient`;
+//! - report it in `SearchResult::salience`.
+//!
+//! ```rust,ignore
+//! let salience = WeightedSalience::default()
+//!     .with(LengthSalience::default(), 0.3)
+//!     .with(NoveltySalience, 0.5)
+//!     .with(RoleSalience::default(), 0.2);
+//! let arms = Arms::new(config).with_salience(Arc::new(salience));
+//! ```
+
+use std::sync::Arc;
+
+use crate::adapters::attention::Role;
+use crate::core::proximity::ScoreOrder;
+use crate::core::{Blob, Point};
+
+/// What a `Salience` sees of a point being placed
+#[derive(Debug, Clone, Copy)]
+pub struct SalienceInput<'a> {
+    pub point: &'a Point,
+    pub blob: &'a Blob,
+
+    /// Conversation role, if the caller gave one
+    pub role: Option<Role>,
+
+    /// Score of the most related point already stored (None = space empty)
+    pub nearest: Option<f32>,
+
+    /// How `nearest` is ordered
+    pub order: ScoreOrder,
+}
+
+impl SalienceInput<'_> {
+    /// How unlike the stored points this one is, in `[0, 1)`
+    ///
+    /// Distance to the nearest stored point mapped through `d / (1 + d)`
+    /// (orthogonal under cosine = 0.5); 1 if nothing is stored yet.
+    pub fn novelty(&self) -> f32 {
+        match self.nearest {
+            Some(score) => {
+                let distance = self.order.to_distance(score).max(0.0);
+                distance / (1.0 + distance)
+            }
+            None => 1.0,
+        }
+    }
+}
+
+/// Scores the importance of points as they are placed
+pub trait Salience: Send + Sync {
+    /// Importance in `[0, 1]` (higher = keep and surface more readily)
+    fn salience(&self, input: &SalienceInput<'_>) -> f32;
+}
+
+impl<F> Salience for F
+where
+    F: Fn(&SalienceInput<'_>) -> f32 + Send + Sync,
+{
+    fn salience(&self, input: &SalienceInput<'_>) -> f32 {
+        self(input)
+    }
+}
+
+/// Longer payloads matter more, up to a saturation length
+#[derive(Debug, Clone, Copy)]
+pub struct LengthSalience {
+    /// Payload bytes at which salience reaches 1
+    pub saturate_at: usize,
+}
+
+impl Default for LengthSalience {
+    fn default() -> Self {
+        Self { saturate_at: 1024 }
+    }
+}
+
+impl Salience for LengthSalience {
+    fn salience(&self, input: &SalienceInput<'_>) -> f32 {
+        (input.blob.size() as f32 / self.saturate_at.max(1) as f32).min(1.0)
+    }
+}
+
+/// Points unlike anything stored matter more (see `SalienceInput::novelty`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoveltySalience;
+
+impl Salience for NoveltySalience {
+    fn salience(&self, input: &SalienceInput<'_>) -> f32 {
+        input.novelty()
+    }
+}
+
+/// Fixed salience per conversation role
+#[derive(Debug, Clone, Copy)]
+pub struct RoleSalience {
+    pub system: f32,
+    pub user: f32,
+    pub assistant: f32,
+    pub tool: f32,
+    pub context: f32,
+
+    /// Points placed without a role
+    pub untagged: f32,
+}
+
+impl Default for RoleSalience {
+    /// What the user said ranks above answers; system prompts and
+    /// retrieved context are restated elsewhere and rank lowest
+    fn default() -> Self {
+        Self {
+            system: 0.2,
+            user: 0.8,
+            assistant: 0.6,
+            tool: 0.4,
+            context: 0.2,
+            untagged: 0.5,
+        }
+    }
+}
+
+impl Salience for RoleSalience {
+    fn salience(&self, input: &SalienceInput<'_>) -> f32 {
+        match input.role {
+            Some(Role::System) => self.system,
+            Some(Role::User) => self.user,
+            Some(Role::Assistant) => self.assistant,
+            Some(Role::Tool) => self.tool,
+            Some(Role::Context) => self.context,
+            None => self.untagged,
+        }
+    }
+}
+
+/// Weighted average of several saliences
+#[derive(Clone, Default)]
+pub struct WeightedSalience {
+    parts: Vec<(Arc<dyn Salience>, f32)>,
+}
+
+impl WeightedSalience {
+    /// Length, novelty and role, weighted 0.3 / 0.5 / 0.2
+    pub fn heuristics() -> Self {
+        Self::default()
+            .with(LengthSalience::default(), 0.3)
+            .with(NoveltySalience, 0.5)
+            .with(RoleSalience::default(), 0.2)
+    }
+
+    /// Add a part with a (non-negative) weight
+    pub fn with<S: Salience + 'static>(mut self, salience: S, weight: f32) -> Self {
+        self.parts.push((Arc::new(salience), weight));
+        self
+    }
+}
+
+impl Salience for WeightedSalience {
+    fn salience(&self, input: &SalienceInput<'_>) -> f32 {
+        let (mut total, mut weights) = (0.0, 0.0);
+        for (part, weight) in &self.parts {
+            total += weight * part.salience(input);
+            weights += weight;
+        }
+        if weights == 0.0 {
+            0.0
+        } else {
+            total / weights
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_salience_heuristics() {
+        let point = Point::new(vec![1.0, 0.0]);
+        let blob = Blob::from_str("hello");
+        let input = |role, nearest| SalienceInput {
+            point: &point,
+            blob: &blob,
+            role,
+            nearest,
+            order: ScoreOrder::HigherIsBetter,
+        };
+
+        assert_eq!(LengthSalience { saturate_at: 10 }.salience(&input(None, None)), 0.5);
+        assert_eq!(LengthSalience { saturate_at: 2 }.salience(&input(None, None)), 1.0);
+
+        assert_eq!(NoveltySalience.salience(&input(None, None)), 1.0);
+        assert_eq!(NoveltySalience.salience(&input(None, Some(1.0))), 0.0);
+        assert_eq!(NoveltySalience.salience(&input(None, Some(0.0))), 0.5);
+
+        let roles = RoleSalience::default();
+        assert!(roles.salience(&input(Some(Role::User), None)) > roles.salience(&input(Some(Role::System), None)));
+
+        let weighted = WeightedSalience::default()
+            .with(NoveltySalience, 3.0)
+            .with(|_: &SalienceInput<'_>| 0.0, 1.0);
+        assert_eq!(weighted.salience(&input(None, None)), 0.75);
+        assert_eq!(WeightedSalience::default().salience(&input(None, None)), 0.0);
+    }
+}
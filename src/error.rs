@@ -0,0 +1,516 @@
+//! # Errors
+//!
+//! Crate-wide error type.
+//!
+//! Each layer keeps its own precise error enum (`PlaceError`, `NearError`,
+//! `PersistError`, ...). `ArmsError` wraps all of them so callers that mix
+//! layers can use a single `?`, and `ErrorCode` gives every error a stable,
+//! machine-readable category for bindings and logs.
+//!
+//! All error enums are `#[non_exhaustive]`: new variants may be added without
+//! a breaking change, so match with a wildcard arm.
+//!
+//! Variants that wrap a lower-level failure (`StorageError`, `IndexError`,
+//! `Corrupted`, `InvalidFormat`) carry it as a `Cause`, so the original
+//! `io::Error`, `ByteError` or backend error stays reachable through
+//! `source()` and `Cause::downcast_ref`.
+//!
+//! `Arms` and the port traits still return their layer's result
+//! (`PlaceResult`, `NearResult`); `?` converts them into `ArmsResult`.
+//!
+//! ```rust,ignore
+//! fn restore(arms: &mut Arms, bytes: &[u8]) -> ArmsResult<()> {
+//!     let index = HatIndex::from_bytes(bytes)?;   // PersistError
+//!     arms.place(point, blob)?;                     // PlaceError
+//!     Ok(())
+//! }
+//! ```
+
+use std::io;
+
+use crate::adapters::attention::AttentionError;
+use crate::adapters::index::{BackupError, PersistError};
+use crate::adapters::migrations::MigrationError;
+use crate::adapters::vllm::PrefixCacheError;
+use crate::core::bytes::ByteError;
+use crate::core::cluster::MetaError;
+use crate::core::Cause;
+use crate::core::schema::SchemaError;
+use crate::engine::{CollectionError, JobError};
+use crate::eval::DatasetError;
+use crate::ports::{ConsensusError, EmbedError, NearError, PlaceError, ShardError};
+
+#[cfg(feature = "import")]
+use crate::adapters::transcript::ImportError;
+
+#[cfg(feature = "cold-tier")]
+use crate::adapters::index::ColdTierError;
+
+#[cfg(feature = "pgvector")]
+use crate::adapters::pgvector::PgvectorError;
+
+/// Result type for operations that can fail in any layer
+pub type ArmsResult<T> = Result<T, ArmsError>;
+
+/// Any error produced by the crate
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ArmsError {
+    #[error(transparent)]
+    Place(#[from] PlaceError),
+
+    #[error(transparent)]
+    Near(#[from] NearError),
+
+    #[error(transparent)]
+    Embed(#[from] EmbedError),
+
+    #[error(transparent)]
+    Attention(#[from] AttentionError),
+
+    #[error(transparent)]
+    Persist(#[from] PersistError),
+
+    #[error(transparent)]
+    PrefixCache(#[from] PrefixCacheError),
+
+    #[error(transparent)]
+    Collection(#[from] CollectionError),
+
+    #[error(transparent)]
+    Schema(#[from] SchemaError),
+
+    #[error(transparent)]
+    Job(#[from] JobError),
+
+    #[error(transparent)]
+    Dataset(#[from] DatasetError),
+
+    #[error(transparent)]
+    Migration(#[from] MigrationError),
+
+    #[error(transparent)]
+    Backup(#[from] BackupError),
+
+    #[error(transparent)]
+    Shard(#[from] ShardError),
+
+    #[error(transparent)]
+    Consensus(#[from] ConsensusError),
+
+    #[error(transparent)]
+    Meta(#[from] MetaError),
+
+    #[error(transparent)]
+    Bytes(#[from] ByteError),
+
+    #[cfg(feature = "import")]
+    #[error(transparent)]
+    Import(#[from] ImportError),
+
+    #[cfg(feature = "cold-tier")]
+    #[error(transparent)]
+    ColdTier(#[from] ColdTierError),
+
+    #[cfg(feature = "pgvector")]
+    #[error(transparent)]
+    Pgvector(#[from] PgvectorError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Stable category of an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// A point or query has the wrong number of dimensions
+    DimensionalityMismatch,
+
+    /// Storage is full
+    CapacityExceeded,
+
+    /// An ID is already in use
+    DuplicateId,
+
+    /// An ID is unknown
+    NotFound,
+
+    /// The index can't answer queries yet
+    IndexNotReady,
+
+    /// Caller-supplied data is malformed or inconsistent
+    InvalidInput,
+
+    /// Stored or serialized data can't be decoded
+    Corrupted,
+
+    /// Serialized data uses a format version this build doesn't read
+    UnsupportedVersion,
+
+    /// Operating system I/O failure
+    Io,
+
+    /// A backend (model, storage, index) failed
+    Backend,
+
+    /// A write reached a store opened read-only
+    ReadOnly,
+
+    /// Another process holds the resource's lock
+    Locked,
+
+    /// The operation was cancelled through its job handle
+    Cancelled,
+}
+
+impl ErrorCode {
+    /// Snake-case name (e.g., "dimensionality_mismatch")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::DimensionalityMismatch => "dimensionality_mismatch",
+            ErrorCode::CapacityExceeded => "capacity_exceeded",
+            ErrorCode::DuplicateId => "duplicate_id",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::IndexNotReady => "index_not_ready",
+            ErrorCode::InvalidInput => "invalid_input",
+            ErrorCode::Corrupted => "corrupted",
+            ErrorCode::UnsupportedVersion => "unsupported_version",
+            ErrorCode::Io => "io",
+            ErrorCode::Backend => "backend",
+            ErrorCode::ReadOnly => "read_only",
+            ErrorCode::Locked => "locked",
+            ErrorCode::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<io::Error> for PlaceError {
+    fn from(e: io::Error) -> Self {
+        PlaceError::StorageError(Cause::new(e))
+    }
+}
+
+impl From<ByteError> for PlaceError {
+    fn from(e: ByteError) -> Self {
+        PlaceError::Corrupted(Cause::new(e))
+    }
+}
+
+impl From<NearError> for PlaceError {
+    fn from(e: NearError) -> Self {
+        PlaceError::Index(e)
+    }
+}
+
+impl PlaceError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PlaceError::DimensionalityMismatch { .. } => ErrorCode::DimensionalityMismatch,
+            PlaceError::NonFinite { .. } => ErrorCode::InvalidInput,
+            PlaceError::CapacityExceeded => ErrorCode::CapacityExceeded,
+            PlaceError::DuplicateId(_) => ErrorCode::DuplicateId,
+            PlaceError::Corrupted(_) => ErrorCode::Corrupted,
+            PlaceError::Index(e) => e.code(),
+            PlaceError::StorageError(_) => ErrorCode::Backend,
+            PlaceError::ReadOnly => ErrorCode::ReadOnly,
+            PlaceError::StoreLocked { .. } => ErrorCode::Locked,
+            PlaceError::Rejected { .. } => ErrorCode::InvalidInput,
+        }
+    }
+}
+
+impl From<JobError> for NearError {
+    fn from(e: JobError) -> Self {
+        NearError::IndexError(Cause::new(e))
+    }
+}
+
+impl NearError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            NearError::DimensionalityMismatch { .. } => ErrorCode::DimensionalityMismatch,
+            NearError::NonFinite { .. } => ErrorCode::InvalidInput,
+            NearError::IndexNotReady => ErrorCode::IndexNotReady,
+            NearError::NotFound(_) => ErrorCode::NotFound,
+            NearError::IndexError(_) => ErrorCode::Backend,
+        }
+    }
+}
+
+impl EmbedError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            EmbedError::DimensionalityMismatch { .. } => ErrorCode::DimensionalityMismatch,
+            EmbedError::BackendError(_) => ErrorCode::Backend,
+        }
+    }
+}
+
+impl AttentionError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AttentionError::InvalidMagic | AttentionError::InvalidFormat(_) => ErrorCode::Corrupted,
+            AttentionError::UnsupportedVersion(_) => ErrorCode::UnsupportedVersion,
+            AttentionError::Io(_) => ErrorCode::Io,
+        }
+    }
+}
+
+impl PersistError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PersistError::InvalidMagic | PersistError::Corrupted(_) => ErrorCode::Corrupted,
+            PersistError::UnsupportedVersion(_) => ErrorCode::UnsupportedVersion,
+            PersistError::Io(_) => ErrorCode::Io,
+            PersistError::DimensionMismatch { .. } => ErrorCode::DimensionalityMismatch,
+            PersistError::SessionNotFound(_) => ErrorCode::NotFound,
+            PersistError::DuplicateId(_) => ErrorCode::DuplicateId,
+            PersistError::UnknownConfig { .. } | PersistError::ConfigMismatch { .. } => {
+                ErrorCode::InvalidInput
+            }
+        }
+    }
+}
+
+impl PrefixCacheError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PrefixCacheError::SeqLenMismatch { .. } | PrefixCacheError::UnsplittableKv => {
+                ErrorCode::InvalidInput
+            }
+        }
+    }
+}
+
+impl CollectionError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            CollectionError::NotFound(_) => ErrorCode::NotFound,
+            CollectionError::AlreadyExists(_) => ErrorCode::DuplicateId,
+            CollectionError::NoProfile(_) => ErrorCode::DimensionalityMismatch,
+            CollectionError::Ambiguous { .. } => ErrorCode::InvalidInput,
+            CollectionError::Place(e) => e.code(),
+            CollectionError::Near(e) => e.code(),
+        }
+    }
+}
+
+impl SchemaError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            SchemaError::UnknownGroup(_) | SchemaError::Missing(_) => ErrorCode::InvalidInput,
+            SchemaError::LengthMismatch { .. } => ErrorCode::DimensionalityMismatch,
+        }
+    }
+}
+
+#[cfg(feature = "import")]
+impl ImportError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ImportError::Json(_) | ImportError::InvalidFormat(_) | ImportError::UnknownRole(_) => {
+                ErrorCode::InvalidInput
+            }
+            ImportError::NoEmbedder => ErrorCode::InvalidInput,
+            ImportError::Embed(e) => e.code(),
+            ImportError::Index(e) => e.code(),
+            ImportError::Cancelled(_) => ErrorCode::Cancelled,
+        }
+    }
+}
+
+#[cfg(feature = "cold-tier")]
+impl ColdTierError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ColdTierError::Store(object_store::Error::NotFound { .. }) => ErrorCode::NotFound,
+            ColdTierError::Store(_) => ErrorCode::Backend,
+            ColdTierError::Persist(e) => e.code(),
+            ColdTierError::Index(e) => e.code(),
+            ColdTierError::Io(_) => ErrorCode::Io,
+        }
+    }
+}
+
+#[cfg(feature = "pgvector")]
+impl PgvectorError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PgvectorError::Postgres(_) => ErrorCode::Backend,
+            PgvectorError::InvalidTable(_) => ErrorCode::InvalidInput,
+            PgvectorError::DimensionalityMismatch { .. } => ErrorCode::DimensionalityMismatch,
+            PgvectorError::Corrupted(_) => ErrorCode::Corrupted,
+            PgvectorError::Place(e) => e.code(),
+        }
+    }
+}
+
+impl JobError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            JobError::Panicked(_) => ErrorCode::Backend,
+        }
+    }
+}
+
+impl DatasetError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            DatasetError::Io(_) => ErrorCode::Io,
+            DatasetError::NotHdf5 | DatasetError::Corrupted(_) => ErrorCode::Corrupted,
+            DatasetError::Unsupported(_) => ErrorCode::UnsupportedVersion,
+            DatasetError::Missing(_) | DatasetError::Invalid(_) => ErrorCode::InvalidInput,
+        }
+    }
+}
+
+impl MigrationError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            MigrationError::Io(_) => ErrorCode::Io,
+            MigrationError::UnknownFormat => ErrorCode::Corrupted,
+            MigrationError::TooNew { .. } | MigrationError::NoPath { .. } => ErrorCode::UnsupportedVersion,
+            MigrationError::Attention(e) => e.code(),
+        }
+    }
+}
+
+impl BackupError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            BackupError::NotFound(_) => ErrorCode::NotFound,
+            BackupError::Corrupt { .. } => ErrorCode::Corrupted,
+            BackupError::Persist(e) => e.code(),
+            BackupError::Io(_) => ErrorCode::Io,
+        }
+    }
+}
+
+impl ShardError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ShardError::NoNodes | ShardError::Unreachable { .. } | ShardError::Node { .. } => ErrorCode::Backend,
+            ShardError::DuplicateNode(_) => ErrorCode::DuplicateId,
+            ShardError::UnknownNode(_) => ErrorCode::NotFound,
+        }
+    }
+}
+
+impl MetaError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            MetaError::CollectionExists(_) | MetaError::NodeExists(_) => ErrorCode::DuplicateId,
+            MetaError::UnknownCollection(_) | MetaError::UnknownNode(_) => ErrorCode::NotFound,
+            MetaError::InvalidDimensionality(_) => ErrorCode::InvalidInput,
+            MetaError::UnknownCommand(_) | MetaError::BadMagic | MetaError::Bytes(_) => ErrorCode::Corrupted,
+            MetaError::UnsupportedVersion(_) => ErrorCode::UnsupportedVersion,
+        }
+    }
+}
+
+impl ConsensusError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ConsensusError::NotLeader { .. } | ConsensusError::Unavailable(_) => ErrorCode::Backend,
+            ConsensusError::Rejected { reason, .. } => reason.code(),
+        }
+    }
+}
+
+impl ArmsError {
+    /// Stable category of this error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ArmsError::Place(e) => e.code(),
+            ArmsError::Near(e) => e.code(),
+            ArmsError::Embed(e) => e.code(),
+            ArmsError::Attention(e) => e.code(),
+            ArmsError::Persist(e) => e.code(),
+            ArmsError::PrefixCache(e) => e.code(),
+            ArmsError::Collection(e) => e.code(),
+            ArmsError::Schema(e) => e.code(),
+            ArmsError::Job(e) => e.code(),
+            ArmsError::Dataset(e) => e.code(),
+            ArmsError::Migration(e) => e.code(),
+            ArmsError::Backup(e) => e.code(),
+            ArmsError::Shard(e) => e.code(),
+            ArmsError::Consensus(e) => e.code(),
+            ArmsError::Meta(e) => e.code(),
+            ArmsError::Bytes(_) => ErrorCode::Corrupted,
+            #[cfg(feature = "import")]
+            ArmsError::Import(e) => e.code(),
+            #[cfg(feature = "cold-tier")]
+            ArmsError::ColdTier(e) => e.code(),
+            #[cfg(feature = "pgvector")]
+            ArmsError::Pgvector(e) => e.code(),
+            ArmsError::Io(_) => ErrorCode::Io,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Id;
+    use std::error::Error;
+
+    #[test]
+    fn test_codes() {
+        let e: ArmsError = PlaceError::DuplicateId(Id::now()).into();
+        assert_eq!(e.code(), ErrorCode::DuplicateId);
+
+        let e: ArmsError = NearError::NotFound(Id::now()).into();
+        assert_eq!(e.code(), ErrorCode::NotFound);
+        assert_eq!(e.code().as_str(), "not_found");
+
+        let e: ArmsError = PersistError::UnsupportedVersion(9).into();
+        assert_eq!(e.code(), ErrorCode::UnsupportedVersion);
+
+        let e: ArmsError = io::Error::other("disk").into();
+        assert_eq!(e.code(), ErrorCode::Io);
+    }
+
+    #[test]
+    fn test_source_chain() {
+        let inner = NearError::DimensionalityMismatch { expected: 3, got: 2 };
+        let e: ArmsError = PlaceError::Index(inner.clone()).into();
+
+        // Wrapped index errors keep their own code and remain reachable
+        assert_eq!(e.code(), ErrorCode::DimensionalityMismatch);
+        assert_eq!(e.to_string(), "Index rejected point");
+        let source = e.source().unwrap();
+        assert_eq!(source.downcast_ref::<NearError>(), Some(&inner));
+    }
+
+    #[test]
+    fn test_typed_sources() {
+        let e: PlaceError = io::Error::new(io::ErrorKind::PermissionDenied, "denied").into();
+        assert_eq!(e.code(), ErrorCode::Backend);
+        assert_eq!(e.to_string(), "Storage error: denied");
+        let PlaceError::StorageError(cause) = &e else { panic!("{:?}", e) };
+        assert_eq!(cause.downcast_ref::<io::Error>().map(|e| e.kind()), Some(io::ErrorKind::PermissionDenied));
+
+        let truncated = crate::core::bytes::ByteReader::new(&[1]).u32("count").unwrap_err();
+        let e = ArmsError::from(PlaceError::from(truncated.clone()));
+        assert_eq!(e.code(), ErrorCode::Corrupted);
+        let source = e.source().unwrap();
+        assert_eq!(source.downcast_ref::<Cause>().and_then(|c| c.downcast_ref::<ByteError>()), Some(&truncated));
+
+        assert_eq!(ArmsError::from(truncated).code(), ErrorCode::Corrupted);
+        assert_eq!(ArmsError::from(MetaError::UnknownCollection("docs".into())).code(), ErrorCode::NotFound);
+    }
+
+    #[test]
+    fn test_display_is_preserved() {
+        let e = PlaceError::DimensionalityMismatch { expected: 3, got: 2 };
+        assert_eq!(e.to_string(), "Dimensionality mismatch: expected 3, got 2");
+        assert_eq!(ArmsError::from(e).to_string(), "Dimensionality mismatch: expected 3, got 2");
+    }
+}
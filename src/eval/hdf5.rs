@@ -0,0 +1,567 @@
+//! Minimal HDF5 reader
+//!
+//! Reads the subset of HDF5 that ann-benchmarks publishes: h5py's default
+//! (`libver="earliest"`) layout, i.e. a version 0/1 superblock, version 1
+//! object headers, a symbol-table root group, contiguous or compact
+//! numeric datasets, and string attributes on the root group.
+//!
+//! Chunked (compressed) datasets, nested groups and the newer object
+//! header format are reported as `DatasetError::Unsupported`.
+//!
+//! ```rust,ignore
+//! let file = Hdf5File::open("glove-100-angular.hdf5")?;
+//! let train = file.dataset("train")?;          // shape [1183514, 100]
+//! let vectors = train.to_f32()?;
+//! let distance = file.string_attribute("distance")?;
+//! ```
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::DatasetError;
+use crate::core::bytes::ByteReader;
+
+const SIGNATURE: &[u8; 8] = b"\x89HDF\r\n\x1a\n";
+
+/// "Not present" address (all bits set)
+const UNDEFINED: u64 = u64::MAX;
+
+// Object header message types
+const MSG_DATASPACE: u16 = 0x0001;
+const MSG_DATATYPE: u16 = 0x0003;
+const MSG_LAYOUT: u16 = 0x0008;
+const MSG_ATTRIBUTE: u16 = 0x000C;
+const MSG_CONTINUATION: u16 = 0x0010;
+const MSG_SYMBOL_TABLE: u16 = 0x0011;
+
+/// An open HDF5 file
+#[derive(Debug)]
+pub struct Hdf5File {
+    file: File,
+
+    /// Size of addresses and of lengths, in bytes
+    offset_size: usize,
+    length_size: usize,
+
+    /// Address all others are relative to
+    base: u64,
+
+    /// Object header of the root group
+    root: u64,
+}
+
+/// Element type of a dataset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dtype {
+    Int { size: usize, signed: bool, big_endian: bool },
+    Float { size: usize, big_endian: bool },
+}
+
+/// A numeric dataset read into memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Array {
+    /// Dimensions, outermost first (e.g., [rows, columns])
+    pub shape: Vec<usize>,
+    pub dtype: Dtype,
+
+    /// Elements in row-major order, as stored
+    raw: Vec<u8>,
+}
+
+impl Array {
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Elements converted to f32
+    pub fn to_f32(&self) -> Result<Vec<f32>, DatasetError> {
+        self.elements(|bytes| match self.dtype {
+            Dtype::Float { size: 4, big_endian } => Ok(f32::from_bits(uint(bytes, big_endian) as u32)),
+            Dtype::Float { size: 8, big_endian } => Ok(f64::from_bits(uint(bytes, big_endian)) as f32),
+            Dtype::Float { size, .. } => Err(DatasetError::Unsupported(format!("{}-byte floats", size))),
+            Dtype::Int { .. } => Ok(int(bytes, self.dtype) as f32),
+        })
+    }
+
+    /// Elements as indices (integer datasets only, none negative)
+    pub fn to_usize(&self) -> Result<Vec<usize>, DatasetError> {
+        self.elements(|bytes| match self.dtype {
+            Dtype::Int { .. } => usize::try_from(int(bytes, self.dtype))
+                .map_err(|_| DatasetError::Invalid("negative index".into())),
+            Dtype::Float { .. } => Err(DatasetError::Invalid("expected integers, found floats".into())),
+        })
+    }
+
+    fn elements<T>(&self, convert: impl Fn(&[u8]) -> Result<T, DatasetError>) -> Result<Vec<T>, DatasetError> {
+        let size = match self.dtype {
+            Dtype::Int { size, .. } | Dtype::Float { size, .. } => size,
+        };
+        self.raw.chunks_exact(size).map(convert).collect()
+    }
+}
+
+/// Unsigned integer of up to 8 bytes
+fn uint(bytes: &[u8], big_endian: bool) -> u64 {
+    let mut buf = [0u8; 8];
+    if big_endian {
+        buf[8 - bytes.len()..].copy_from_slice(bytes);
+        u64::from_be_bytes(buf)
+    } else {
+        buf[..bytes.len()].copy_from_slice(bytes);
+        u64::from_le_bytes(buf)
+    }
+}
+
+/// Integer element, sign-extended if the type is signed
+fn int(bytes: &[u8], dtype: Dtype) -> i64 {
+    let Dtype::Int { size, signed, big_endian } = dtype else {
+        unreachable!("int() called on a float type")
+    };
+    let value = uint(bytes, big_endian);
+    if signed && size < 8 {
+        let shift = 64 - 8 * size as u32;
+        ((value << shift) as i64) >> shift
+    } else {
+        value as i64
+    }
+}
+
+/// Datatype message, decoded as far as the reader needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Datatype {
+    Numeric(Dtype),
+    FixedString(usize),
+    VariableString,
+    Other(u8),
+}
+
+/// Check a block's 4-byte signature
+fn signature(cursor: &mut ByteReader<'_>, expected: &[u8; 4]) -> Result<(), DatasetError> {
+    if cursor.bytes(4, "signature")? != expected {
+        return Err(DatasetError::Corrupted(format!(
+            "missing {} signature",
+            String::from_utf8_lossy(expected)
+        )));
+    }
+    Ok(())
+}
+
+fn pad8(len: usize) -> usize {
+    len.div_ceil(8) * 8
+}
+
+fn overflow() -> DatasetError {
+    DatasetError::Corrupted("size or address overflows".into())
+}
+
+/// Bytes taken by `dims` elements of `element` bytes each
+fn byte_len(dims: &[usize], element: usize) -> Result<usize, DatasetError> {
+    dims.iter().try_fold(element, |len, &dim| len.checked_mul(dim)).ok_or_else(overflow)
+}
+
+fn size(value: u64) -> Result<usize, DatasetError> {
+    usize::try_from(value).map_err(|_| DatasetError::Corrupted("size out of range".into()))
+}
+
+impl Hdf5File {
+    /// Open a file and locate its root group
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, DatasetError> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+
+        // The superblock sits at 0, 512, 1024, 2048, ...
+        let mut at = 0u64;
+        loop {
+            if at + 8 > file_len {
+                return Err(DatasetError::NotHdf5);
+            }
+            let mut signature = [0u8; 8];
+            (&file).seek(SeekFrom::Start(at))?;
+            (&file).read_exact(&mut signature)?;
+            if &signature == SIGNATURE {
+                break;
+            }
+            at = if at == 0 { 512 } else { at * 2 };
+        }
+
+        let mut hdf5 = Self { file, offset_size: 8, length_size: 8, base: 0, root: 0 };
+        let header = hdf5.read_raw(at + 8, 16)?;
+        let version = header[0];
+        if version > 1 {
+            return Err(DatasetError::Unsupported(format!(
+                "superblock version {} (write with h5py's libver=\"earliest\")",
+                version
+            )));
+        }
+        hdf5.offset_size = header[5] as usize;
+        hdf5.length_size = header[6] as usize;
+        if !matches!(hdf5.offset_size, 2 | 4 | 8) || !matches!(hdf5.length_size, 2 | 4 | 8) {
+            return Err(DatasetError::Corrupted("invalid offset/length size".into()));
+        }
+
+        // Version 1 adds indexed storage K and 2 reserved bytes
+        let fixed = if version == 0 { 16 } else { 20 };
+        let addresses = hdf5.read_raw(at + 8 + fixed, 4 * hdf5.offset_size + hdf5.symbol_entry_size())?;
+        let mut cursor = ByteReader::new(&addresses);
+        hdf5.base = cursor.uint(hdf5.offset_size, "superblock")?;
+        cursor.skip(3 * hdf5.offset_size, "superblock")?;
+        // Root symbol table entry: link name offset, then object header address
+        cursor.skip(hdf5.offset_size, "superblock")?;
+        hdf5.root = cursor.uint(hdf5.offset_size, "superblock")?;
+        Ok(hdf5)
+    }
+
+    /// Names of the root group's members
+    pub fn names(&self) -> Result<Vec<String>, DatasetError> {
+        Ok(self.members()?.into_iter().map(|(name, _)| name).collect())
+    }
+
+    /// Read a dataset of the root group
+    pub fn dataset(&self, name: &str) -> Result<Array, DatasetError> {
+        let address = self
+            .members()?
+            .into_iter()
+            .find(|(member, _)| member == name)
+            .map(|(_, address)| address)
+            .ok_or_else(|| DatasetError::Missing(name.to_string()))?;
+
+        let mut shape = None;
+        let mut datatype = None;
+        let mut raw = None;
+        for (kind, data) in self.messages(address)? {
+            match kind {
+                MSG_DATASPACE => shape = Some(self.dataspace(&data)?),
+                MSG_DATATYPE => datatype = Some(datatype_of(&data)?.0),
+                MSG_LAYOUT => raw = Some(self.layout(&data)?),
+                _ => {}
+            }
+        }
+
+        let missing = |what: &str| DatasetError::Corrupted(format!("dataset '{}' has no {}", name, what));
+        let shape = shape.ok_or_else(|| missing("dataspace"))?;
+        let dtype = match datatype.ok_or_else(|| missing("datatype"))? {
+            Datatype::Numeric(dtype) => dtype,
+            other => return Err(DatasetError::Unsupported(format!("dataset '{}' of type {:?}", name, other))),
+        };
+        let mut raw = raw.ok_or_else(|| missing("layout"))?;
+
+        let element = match dtype {
+            Dtype::Int { size, .. } | Dtype::Float { size, .. } => size,
+        };
+        let len = byte_len(&shape, element)?;
+        if raw.len() < len {
+            return Err(DatasetError::Corrupted(format!("dataset '{}' is shorter than its shape", name)));
+        }
+        raw.truncate(len);
+        Ok(Array { shape, dtype, raw })
+    }
+
+    /// A string attribute of the root group (None if absent)
+    pub fn string_attribute(&self, name: &str) -> Result<Option<String>, DatasetError> {
+        for (kind, data) in self.messages(self.root)? {
+            if kind != MSG_ATTRIBUTE {
+                continue;
+            }
+            let (attribute, datatype, value) = self.attribute(&data)?;
+            if attribute != name {
+                continue;
+            }
+            let bytes = match datatype {
+                Datatype::FixedString(len) => value.get(..len).unwrap_or(&value).to_vec(),
+                Datatype::VariableString => self.variable_string(&value)?,
+                other => {
+                    return Err(DatasetError::Invalid(format!("attribute '{}' is not a string ({:?})", name, other)))
+                }
+            };
+            let text = String::from_utf8_lossy(&bytes);
+            return Ok(Some(text.trim_end_matches(['\0', ' ']).to_string()));
+        }
+        Ok(None)
+    }
+
+    fn read_raw(&self, at: u64, len: usize) -> Result<Vec<u8>, DatasetError> {
+        let mut buf = vec![0u8; len];
+        (&self.file).seek(SeekFrom::Start(at))?;
+        (&self.file).read_exact(&mut buf).map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => DatasetError::Corrupted("address past end of file".into()),
+            _ => DatasetError::Io(e),
+        })?;
+        Ok(buf)
+    }
+
+    /// Read `len` bytes at a (base-relative) address
+    fn read(&self, address: u64, len: usize) -> Result<Vec<u8>, DatasetError> {
+        if address == UNDEFINED {
+            return Err(DatasetError::Corrupted("undefined address".into()));
+        }
+        self.read_raw(self.base.checked_add(address).ok_or_else(overflow)?, len)
+    }
+
+    fn symbol_entry_size(&self) -> usize {
+        2 * self.offset_size + 24
+    }
+
+    /// (message type, message data) of an object header
+    fn messages(&self, address: u64) -> Result<Vec<(u16, Vec<u8>)>, DatasetError> {
+        let prefix = self.read(address, 16)?;
+        let mut cursor = ByteReader::new(&prefix);
+        let version = cursor.u8("object header")?;
+        if version != 1 {
+            return Err(DatasetError::Unsupported(format!("object header version {}", version)));
+        }
+        cursor.skip(1, "object header")?;
+        let count = cursor.u16("object header")? as usize;
+        cursor.skip(4, "object header")?;
+        let header_size = cursor.u32("object header")? as usize;
+
+        let mut messages = Vec::with_capacity(count);
+        // Blocks of messages: the first follows the (padded) 12-byte prefix
+        let mut blocks = vec![(address + 16, header_size)];
+        while let Some((at, len)) = blocks.pop() {
+            let block = self.read(at, len)?;
+            let mut cursor = ByteReader::new(&block);
+            while messages.len() < count && cursor.remaining() >= 8 {
+                let kind = cursor.u16("header message")?;
+                let len = cursor.u16("header message")? as usize;
+                let flags = cursor.u8("header message")?;
+                cursor.skip(3, "header message")?;
+                let data = cursor.bytes(len, "header message")?;
+                if flags & 0x02 != 0 && matches!(kind, MSG_DATATYPE | MSG_DATASPACE) {
+                    return Err(DatasetError::Unsupported("shared (committed) datatypes".into()));
+                }
+                if kind == MSG_CONTINUATION {
+                    let mut continuation = ByteReader::new(data);
+                    let at = continuation.uint(self.offset_size, "continuation")?;
+                    let len = size(continuation.uint(self.length_size, "continuation")?)?;
+                    blocks.push((at, len));
+                }
+                messages.push((kind, data.to_vec()));
+            }
+        }
+        Ok(messages)
+    }
+
+    /// (name, object header address) of each root group member
+    fn members(&self) -> Result<Vec<(String, u64)>, DatasetError> {
+        let table = self
+            .messages(self.root)?
+            .into_iter()
+            .find(|(kind, _)| *kind == MSG_SYMBOL_TABLE)
+            .ok_or_else(|| DatasetError::Unsupported("root group without a symbol table".into()))?;
+        let mut cursor = ByteReader::new(&table.1);
+        let btree = cursor.uint(self.offset_size, "symbol table")?;
+        let heap = cursor.uint(self.offset_size, "symbol table")?;
+
+        let names = self.local_heap(heap)?;
+        let mut members = Vec::new();
+        self.visit_group_node(btree, &names, &mut members, 0)?;
+        Ok(members)
+    }
+
+    /// Data segment of a local heap (where member names live)
+    fn local_heap(&self, address: u64) -> Result<Vec<u8>, DatasetError> {
+        let header = self.read(address, 8 + 2 * self.length_size + self.offset_size)?;
+        let mut cursor = ByteReader::new(&header);
+        signature(&mut cursor, b"HEAP")?;
+        cursor.skip(4, "local heap")?;
+        let len = size(cursor.uint(self.length_size, "local heap")?)?;
+        cursor.skip(self.length_size, "local heap")?;
+        let data = cursor.uint(self.offset_size, "local heap")?;
+        self.read(data, len)
+    }
+
+    /// Collect the members under a group B-tree node
+    fn visit_group_node(
+        &self,
+        address: u64,
+        names: &[u8],
+        members: &mut Vec<(String, u64)>,
+        depth: usize,
+    ) -> Result<(), DatasetError> {
+        if depth > 32 {
+            return Err(DatasetError::Corrupted("group B-tree too deep".into()));
+        }
+        let header = self.read(address, 8 + 2 * self.offset_size)?;
+        let mut cursor = ByteReader::new(&header);
+        signature(&mut cursor, b"TREE")?;
+        if cursor.u8("B-tree node")? != 0 {
+            return Err(DatasetError::Corrupted("group B-tree holds chunk nodes".into()));
+        }
+        let level = cursor.u8("B-tree node")?;
+        let entries = cursor.u16("B-tree node")? as usize;
+
+        // Keys and children alternate: key0 child0 key1 child1 ... keyN
+        let stride = self.length_size + self.offset_size;
+        let body = self.read(address + header.len() as u64, entries * stride + self.length_size)?;
+        let mut cursor = ByteReader::new(&body);
+        for _ in 0..entries {
+            cursor.skip(self.length_size, "B-tree node")?;
+            let child = cursor.uint(self.offset_size, "B-tree node")?;
+            if level > 0 {
+                self.visit_group_node(child, names, members, depth + 1)?;
+            } else {
+                self.read_symbol_node(child, names, members)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_symbol_node(&self, address: u64, names: &[u8], members: &mut Vec<(String, u64)>) -> Result<(), DatasetError> {
+        let header = self.read(address, 8)?;
+        let mut cursor = ByteReader::new(&header);
+        signature(&mut cursor, b"SNOD")?;
+        cursor.skip(2, "symbol table node")?;
+        let count = cursor.u16("symbol table node")? as usize;
+
+        let entries = self.read(address + 8, count * self.symbol_entry_size())?;
+        let mut cursor = ByteReader::new(&entries);
+        for _ in 0..count {
+            let name_offset = size(cursor.uint(self.offset_size, "symbol table node")?)?;
+            let object = cursor.uint(self.offset_size, "symbol table node")?;
+            cursor.skip(24, "symbol table node")?;
+
+            let name = names
+                .get(name_offset..)
+                .and_then(|rest| rest.split(|&b| b == 0).next())
+                .ok_or_else(|| DatasetError::Corrupted("member name outside its heap".into()))?;
+            members.push((String::from_utf8_lossy(name).into_owned(), object));
+        }
+        Ok(())
+    }
+
+    /// Dimensions from a dataspace message (empty for a scalar)
+    fn dataspace(&self, data: &[u8]) -> Result<Vec<usize>, DatasetError> {
+        let mut cursor = ByteReader::new(data);
+        let version = cursor.u8("dataspace")?;
+        let rank = cursor.u8("dataspace")? as usize;
+        cursor.skip(1, "dataspace")?;
+        match version {
+            1 => cursor.skip(5, "dataspace")?,
+            2 => cursor.skip(1, "dataspace")?,
+            other => return Err(DatasetError::Unsupported(format!("dataspace version {}", other))),
+        }
+        (0..rank).map(|_| size(cursor.uint(self.length_size, "dataspace")?)).collect()
+    }
+
+    /// Raw bytes of a contiguous or compact dataset
+    fn layout(&self, data: &[u8]) -> Result<Vec<u8>, DatasetError> {
+        let mut cursor = ByteReader::new(data);
+        let version = cursor.u8("data layout")?;
+        let (class, address, len) = match version {
+            1 | 2 => {
+                let rank = cursor.u8("data layout")? as usize;
+                let class = cursor.u8("data layout")?;
+                cursor.skip(5, "data layout")?;
+                let address = if class == 0 { UNDEFINED } else { cursor.uint(self.offset_size, "data layout")? };
+                let dims: Vec<u64> = (0..rank).map(|_| cursor.uint(4, "data layout")).collect::<Result<_, _>>()?;
+                if class == 0 {
+                    let len = cursor.u32("data layout")? as usize;
+                    return Ok(cursor.bytes(len, "data layout")?.to_vec());
+                }
+                // Contiguous size is the product of the dimensions (last one is the element size)
+                let len = dims.iter().try_fold(1u64, |len, &dim| len.checked_mul(dim));
+                (class, address, size(len.ok_or_else(overflow)?)?)
+            }
+            3 | 4 => {
+                let class = cursor.u8("data layout")?;
+                match class {
+                    0 => {
+                        let len = cursor.u16("data layout")? as usize;
+                        return Ok(cursor.bytes(len, "data layout")?.to_vec());
+                    }
+                    1 => (class, cursor.uint(self.offset_size, "data layout")?, size(cursor.uint(self.length_size, "data layout")?)?),
+                    _ => (class, UNDEFINED, 0),
+                }
+            }
+            other => return Err(DatasetError::Unsupported(format!("layout version {}", other))),
+        };
+        match class {
+            1 if address == UNDEFINED => Ok(Vec::new()),
+            1 => self.read(address, len),
+            2 => Err(DatasetError::Unsupported("chunked (compressed) datasets".into())),
+            other => Err(DatasetError::Unsupported(format!("layout class {}", other))),
+        }
+    }
+
+    /// (name, datatype, raw value) of an attribute message
+    fn attribute(&self, data: &[u8]) -> Result<(String, Datatype, Vec<u8>), DatasetError> {
+        let mut cursor = ByteReader::new(data);
+        let version = cursor.u8("attribute")?;
+        cursor.skip(1, "attribute")?;
+        let name_len = cursor.u16("attribute")? as usize;
+        let type_len = cursor.u16("attribute")? as usize;
+        let space_len = cursor.u16("attribute")? as usize;
+        // Version 1 pads each part to 8 bytes; version 3 adds an encoding byte
+        let padded = |len: usize| if version == 1 { pad8(len) } else { len };
+        match version {
+            1 | 2 => {}
+            3 => cursor.skip(1, "attribute")?,
+            other => return Err(DatasetError::Unsupported(format!("attribute version {}", other))),
+        }
+
+        let name = cursor.bytes(padded(name_len), "attribute")?;
+        let name = String::from_utf8_lossy(name.split(|&b| b == 0).next().unwrap_or_default()).into_owned();
+        let (datatype, element) = datatype_of(cursor.bytes(padded(type_len), "attribute")?)?;
+        let space = cursor.bytes(padded(space_len), "attribute")?;
+        let len = byte_len(&self.dataspace(space)?, element)?;
+
+        let value = cursor.bytes(len.min(cursor.remaining()), "attribute")?;
+        Ok((name, datatype, value.to_vec()))
+    }
+
+    /// Bytes of a variable-length string stored in a global heap
+    fn variable_string(&self, value: &[u8]) -> Result<Vec<u8>, DatasetError> {
+        let mut cursor = ByteReader::new(value);
+        let len = cursor.u32("variable-length string")? as usize;
+        let collection = cursor.uint(self.offset_size, "variable-length string")?;
+        let index = cursor.u32("variable-length string")? as u16;
+
+        let header = self.read(collection, 8 + self.length_size)?;
+        let mut header_cursor = ByteReader::new(&header);
+        signature(&mut header_cursor, b"GCOL")?;
+        header_cursor.skip(4, "global heap")?;
+        let collection_len = size(header_cursor.uint(self.length_size, "global heap")?)?;
+
+        let heap = self.read(collection, collection_len)?;
+        let mut cursor = ByteReader::new(&heap);
+        cursor.skip(header.len(), "global heap")?;
+        while cursor.remaining() >= 8 + self.length_size {
+            let object = cursor.u16("global heap")?;
+            cursor.skip(6, "global heap")?;
+            let object_len = size(cursor.uint(self.length_size, "global heap")?)?;
+            if object == 0 {
+                break;
+            }
+            let data = cursor.bytes(pad8(object_len).min(cursor.remaining()), "global heap")?;
+            if object == index {
+                return Ok(data[..len.min(object_len).min(data.len())].to_vec());
+            }
+        }
+        Err(DatasetError::Corrupted("string missing from global heap".into()))
+    }
+}
+
+/// A datatype message and its element size in bytes
+fn datatype_of(data: &[u8]) -> Result<(Datatype, usize), DatasetError> {
+    let mut cursor = ByteReader::new(data);
+    let class_version = cursor.u8("datatype")?;
+    let bits = cursor.bytes(3, "datatype")?;
+    let size = cursor.u32("datatype")? as usize;
+    let big_endian = bits[0] & 0x01 != 0;
+    let datatype = match class_version & 0x0F {
+        0 if matches!(size, 1 | 2 | 4 | 8) => {
+            Datatype::Numeric(Dtype::Int { size, signed: bits[0] & 0x08 != 0, big_endian })
+        }
+        1 if matches!(size, 4 | 8) => Datatype::Numeric(Dtype::Float { size, big_endian }),
+        3 => Datatype::FixedString(size),
+        9 if bits[0] & 0x0F == 1 => Datatype::VariableString,
+        class => Datatype::Other(class),
+    };
+    Ok((datatype, size))
+}
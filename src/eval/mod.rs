@@ -0,0 +1,379 @@
+//! # Eval
+//!
+//! Recall and throughput of any `Near` index on ann-benchmarks datasets
+//! (sift-128-euclidean, glove-100-angular, dbpedia-openai-1000k-angular,
+//! ...), so recall/QPS curves come straight from this crate.
+//!
+//! ```rust,ignore
+//! let dataset = Dataset::load_hdf5("glove-100-angular.hdf5")?;
+//! let mut hat = HatIndex::with_proximity_name(dataset.dimensionality(), dataset.proximity().unwrap()).unwrap();
+//! dataset.index_into(&mut hat)?;
+//!
+//! for beam in [1, 2, 4, 8, 16] {
+//!     let report = evaluate(&hat, &dataset, &QueryParams::new(10).with_beam_width(beam))?;
+//!     println!("beam {beam}: recall {:.3} at {:.0} QPS", report.recall, report.qps);
+//! }
+//! ```
+//!
+//! Train row `i` is placed under `Dataset::id(i)`, so results map back to
+//! rows with `Dataset::row`.
+//!
+//! `replay` checks an index against a baseline recorded in a `QueryLog`.
+
+pub mod hdf5;
+pub mod replay;
+
+pub use replay::{replay_eval, replay_with, QueryDiff, ReplayReport};
+
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::core::bytes::ByteError;
+use crate::core::{clock, Id, Point};
+use crate::ports::{Near, NearResult, QueryParams};
+use hdf5::Hdf5File;
+
+/// Errors from loading a dataset
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum DatasetError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not an HDF5 file")]
+    NotHdf5,
+
+    #[error("Corrupted HDF5 file: {0}")]
+    Corrupted(String),
+
+    #[error("Unsupported HDF5 feature: {0}")]
+    Unsupported(String),
+
+    /// A required dataset or attribute is absent
+    #[error("Dataset has no '{0}'")]
+    Missing(String),
+
+    /// The contents don't fit together (e.g., neighbor rows out of range)
+    #[error("Invalid dataset: {0}")]
+    Invalid(String),
+}
+
+impl From<ByteError> for DatasetError {
+    fn from(e: ByteError) -> Self {
+        DatasetError::Corrupted(e.to_string())
+    }
+}
+
+/// An ann-benchmarks dataset: base vectors, queries and ground truth
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset {
+    /// File stem (e.g., "sift-128-euclidean")
+    pub name: String,
+
+    /// The "distance" attribute: "euclidean", "angular", ...
+    pub distance: String,
+
+    /// Vectors to index
+    pub train: Vec<Point>,
+
+    /// Query vectors
+    pub test: Vec<Point>,
+
+    /// For each query, train rows nearest first
+    pub neighbors: Vec<Vec<usize>>,
+}
+
+impl Dataset {
+    /// Load an ann-benchmarks HDF5 file
+    pub fn load_hdf5(path: impl AsRef<Path>) -> Result<Self, DatasetError> {
+        let path = path.as_ref();
+        let file = Hdf5File::open(path)?;
+
+        let train = points(&file, "train")?;
+        let test = points(&file, "test")?;
+        let neighbors = file.dataset("neighbors")?;
+        let [queries, depth] = neighbors.shape[..] else {
+            return Err(DatasetError::Invalid("neighbors must be 2-D".into()));
+        };
+        if queries != test.len() {
+            return Err(DatasetError::Invalid(format!(
+                "{} neighbor rows for {} queries",
+                queries,
+                test.len()
+            )));
+        }
+        let neighbors: Vec<Vec<usize>> = neighbors
+            .to_usize()?
+            .chunks(depth.max(1))
+            .map(<[usize]>::to_vec)
+            .collect();
+        if let Some(&row) = neighbors.iter().flatten().find(|&&row| row >= train.len()) {
+            return Err(DatasetError::Invalid(format!("neighbor {} out of {} train rows", row, train.len())));
+        }
+
+        let distance = file
+            .string_attribute("distance")?
+            .ok_or_else(|| DatasetError::Missing("distance".into()))?;
+        let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+
+        Ok(Self { name, distance, train, test, neighbors })
+    }
+
+    pub fn dimensionality(&self) -> usize {
+        self.train.first().map_or(0, Point::dimensionality)
+    }
+
+    /// Proximity name matching the dataset's distance (None if unsupported)
+    ///
+    /// "angular" maps to "cosine"; pass the result to
+    /// `HatIndex::with_proximity_name`.
+    pub fn proximity(&self) -> Option<&'static str> {
+        match self.distance.as_str() {
+            "angular" | "cosine" => Some("cosine"),
+            "euclidean" => Some("euclidean"),
+            "dot" | "ip" => Some("dot_product"),
+            _ => None,
+        }
+    }
+
+    /// ID train row `row` is indexed under
+    pub fn id(row: usize) -> Id {
+        Id::seeded(0, row as u64)
+    }
+
+    /// Train row of an ID handed out by `id`
+    pub fn row(id: Id) -> usize {
+        let mut sequence = [0u8; 8];
+        sequence.copy_from_slice(&id.as_bytes()[..8]);
+        u64::from_be_bytes(sequence) as usize
+    }
+
+    /// Add every train vector to an index
+    pub fn index_into(&self, index: &mut dyn Near) -> NearResult<()> {
+        for (row, point) in self.train.iter().enumerate() {
+            index.add(Self::id(row), point)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rows of a 2-D dataset as points
+fn points(file: &Hdf5File, name: &str) -> Result<Vec<Point>, DatasetError> {
+    let array = file.dataset(name)?;
+    let [_, dims] = array.shape[..] else {
+        return Err(DatasetError::Invalid(format!("{} must be 2-D", name)));
+    };
+    Ok(array.to_f32()?.chunks(dims.max(1)).map(|row| Point::new(row.to_vec())).collect())
+}
+
+/// Recall and speed of one run over a dataset's queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvalReport {
+    /// Neighbors asked for per query
+    pub k: usize,
+
+    /// Queries run
+    pub queries: usize,
+
+    /// Mean share of each query's true k nearest that were returned
+    pub recall: f32,
+
+    /// Queries per second (single thread)
+    pub qps: f64,
+
+    pub mean_latency: Duration,
+}
+
+/// Run every test query through `index` and score against the ground truth
+///
+/// The index must hold the train vectors under `Dataset::id` (see
+/// `Dataset::index_into`). Recall is exact ID overlap with the first k
+/// ground-truth rows; ties at the k-th distance count as misses.
+pub fn evaluate(index: &dyn Near, dataset: &Dataset, params: &QueryParams) -> NearResult<EvalReport> {
+    let k = params.k;
+    let mut found = 0usize;
+    let mut expected = 0usize;
+
+    // The crate clock rather than `Instant`, which panics on wasm32
+    let started = clock::now_micros();
+    for (query, truth) in dataset.test.iter().zip(&dataset.neighbors) {
+        let results = index.near_with_params(query, params)?;
+        let truth = &truth[..k.min(truth.len())];
+        found += results.iter().filter(|r| truth.contains(&Dataset::row(r.id))).count();
+        expected += truth.len();
+    }
+    let elapsed = Duration::from_micros(clock::now_micros().saturating_sub(started));
+
+    let queries = dataset.test.len();
+    Ok(EvalReport {
+        k,
+        queries,
+        recall: if expected == 0 { 1.0 } else { found as f32 / expected as f32 },
+        qps: queries as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        mean_latency: elapsed.checked_div(queries.max(1) as u32).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::index::FlatIndex;
+
+    const UNDEFINED: [u8; 8] = [0xFF; 8];
+
+    /// Builds a file the way h5py's "earliest" layout does
+    #[derive(Default)]
+    struct Writer {
+        buf: Vec<u8>,
+    }
+
+    impl Writer {
+        /// Append 8-byte aligned; returns the address
+        fn put(&mut self, bytes: &[u8]) -> u64 {
+            let at = self.buf.len() as u64;
+            self.buf.extend_from_slice(bytes);
+            self.buf.resize(self.buf.len().div_ceil(8) * 8, 0);
+            at
+        }
+    }
+
+    fn cat(parts: &[&[u8]]) -> Vec<u8> {
+        parts.concat()
+    }
+
+    fn padded(bytes: &[u8]) -> Vec<u8> {
+        let mut bytes = bytes.to_vec();
+        bytes.resize(bytes.len().div_ceil(8) * 8, 0);
+        bytes
+    }
+
+    fn message(kind: u16, data: &[u8]) -> Vec<u8> {
+        let data = padded(data);
+        cat(&[&kind.to_le_bytes(), &(data.len() as u16).to_le_bytes(), &[0; 4], &data])
+    }
+
+    fn object_header(count: u16, messages: &[u8]) -> Vec<u8> {
+        cat(&[&[1, 0], &count.to_le_bytes(), &1u32.to_le_bytes(), &(messages.len() as u32).to_le_bytes(), &[0; 4], messages])
+    }
+
+    fn dataset(w: &mut Writer, class: u8, bits: u8, size: u32, shape: [u64; 2], raw: &[u8]) -> u64 {
+        let address = w.put(raw);
+        let space = cat(&[&[1, 2, 0, 0, 0, 0, 0, 0], &shape[0].to_le_bytes(), &shape[1].to_le_bytes()]);
+        let datatype = cat(&[&[0x10 | class, bits, 0, 0], &size.to_le_bytes(), &[0, 0], &(size as u16 * 8).to_le_bytes()]);
+        let layout = cat(&[&[3, 1], &address.to_le_bytes(), &(raw.len() as u64).to_le_bytes()]);
+        let messages = cat(&[&message(1, &space), &message(3, &datatype), &message(8, &layout)]);
+        w.put(&object_header(3, &messages))
+    }
+
+    /// A tiny ann-benchmarks file whose train dataset claims `train_rows` rows
+    fn write_dataset(path: &Path, train_rows: u64) {
+        let mut w = Writer::default();
+        w.put(&[0; 96]);
+
+        let train: Vec<u8> = [0.0f32, 0.0, 1.0, 0.0, 0.0, 1.0, 5.0, 5.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let test: Vec<u8> = [0.9f64, 0.1, 4.0, 5.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let neighbors: Vec<u8> = [1i32, 0, 3, 2].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let train = dataset(&mut w, 1, 0x20, 4, [train_rows, 2], &train);
+        let test = dataset(&mut w, 1, 0x20, 8, [2, 2], &test);
+        let neighbors = dataset(&mut w, 0, 0x08, 4, [2, 2], &neighbors);
+
+        // Member names at 8, 16 and 24 of the local heap
+        let names = cat(&[&[0; 8], &padded(b"train\0"), &padded(b"test\0"), &padded(b"neighbors\0")]);
+        let names_at = w.put(&names);
+        let heap = w.put(&cat(&[b"HEAP", &[0; 4], &(names.len() as u64).to_le_bytes(), &UNDEFINED, &names_at.to_le_bytes()]));
+
+        let entry = |name: u64, header: u64| cat(&[&name.to_le_bytes(), &header.to_le_bytes(), &[0; 24]]);
+        let snod = w.put(&cat(&[b"SNOD", &[1, 0], &3u16.to_le_bytes(), &entry(24, neighbors), &entry(16, test), &entry(8, train)]));
+        let btree = w.put(&cat(&[b"TREE", &[0, 0], &1u16.to_le_bytes(), &UNDEFINED, &UNDEFINED, &0u64.to_le_bytes(), &snod.to_le_bytes(), &24u64.to_le_bytes()]));
+
+        // "distance" is a variable-length string, as h5py writes a str attribute
+        let object = cat(&[&1u16.to_le_bytes(), &[0; 6], &9u64.to_le_bytes(), &padded(b"euclidean")]);
+        let collection_len = 16 + object.len() as u64 + 16;
+        let gcol = w.put(&cat(&[b"GCOL", &[1, 0, 0, 0], &collection_len.to_le_bytes(), &object, &[0; 16]]));
+        let vlen_string = cat(&[&[0x19, 0x01, 0x01, 0x00], &16u32.to_le_bytes(), &[0x10, 0, 0, 0], &1u32.to_le_bytes(), &[0, 0, 8, 0]]);
+        let attribute = cat(&[
+            &[1, 0],
+            &9u16.to_le_bytes(),
+            &(vlen_string.len() as u16).to_le_bytes(),
+            &8u16.to_le_bytes(),
+            &padded(b"distance\0"),
+            &padded(&vlen_string),
+            &[1, 0, 0, 0, 0, 0, 0, 0],
+            &9u32.to_le_bytes(),
+            &gcol.to_le_bytes(),
+            &1u32.to_le_bytes(),
+        ]);
+
+        // The attribute sits in a continuation block
+        let continued = message(0x0C, &attribute);
+        let continued_at = w.put(&continued);
+        let symbol_table = cat(&[&btree.to_le_bytes(), &heap.to_le_bytes()]);
+        let continuation = cat(&[&continued_at.to_le_bytes(), &(continued.len() as u64).to_le_bytes()]);
+        let root = w.put(&object_header(3, &cat(&[&message(0x11, &symbol_table), &message(0x10, &continuation)])));
+
+        let eof = w.buf.len() as u64;
+        let superblock = cat(&[
+            b"\x89HDF\r\n\x1a\n",
+            &[0, 0, 0, 0, 0, 8, 8, 0],
+            &4u16.to_le_bytes(),
+            &16u16.to_le_bytes(),
+            &[0; 4],
+            &0u64.to_le_bytes(),
+            &UNDEFINED,
+            &eof.to_le_bytes(),
+            &UNDEFINED,
+            &entry(0, root)[..16],
+            &1u32.to_le_bytes(),
+            &[0; 4],
+            &btree.to_le_bytes(),
+            &heap.to_le_bytes(),
+        ]);
+        w.buf[..96].copy_from_slice(&superblock);
+        std::fs::write(path, w.buf).unwrap();
+    }
+
+    #[test]
+    fn test_load_hdf5_and_evaluate() {
+        let dir = crate::testing::temp_path("arms-eval");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tiny-2-euclidean.hdf5");
+        write_dataset(&path, 4);
+
+        let file = Hdf5File::open(&path).unwrap();
+        assert_eq!(file.names().unwrap(), vec!["neighbors", "test", "train"]);
+        assert!(matches!(file.dataset("missing"), Err(DatasetError::Missing(_))));
+        assert_eq!(file.string_attribute("point_type").unwrap(), None);
+
+        let dataset = Dataset::load_hdf5(&path).unwrap();
+        assert_eq!(dataset.name, "tiny-2-euclidean");
+        assert_eq!(dataset.distance, "euclidean");
+        assert_eq!(dataset.proximity(), Some("euclidean"));
+        assert_eq!(dataset.dimensionality(), 2);
+        assert_eq!(dataset.train[3], Point::new(vec![5.0, 5.0]));
+        assert_eq!(dataset.test[0], Point::new(vec![0.9, 0.1]));
+        assert_eq!(dataset.neighbors, vec![vec![1, 0], vec![3, 2]]);
+        assert_eq!(Dataset::row(Dataset::id(3)), 3);
+
+        let mut index = FlatIndex::euclidean(2);
+        dataset.index_into(&mut index).unwrap();
+        let report = evaluate(&index, &dataset, &QueryParams::new(2)).unwrap();
+        assert_eq!((report.k, report.queries, report.recall), (2, 2, 1.0));
+        assert!(report.qps > 0.0);
+
+        // A lone nearest neighbor is half the truth at k=2
+        let mut partial = FlatIndex::euclidean(2);
+        partial.add(Dataset::id(1), &dataset.train[1]).unwrap();
+        partial.add(Dataset::id(3), &dataset.train[3]).unwrap();
+        assert_eq!(evaluate(&partial, &dataset, &QueryParams::new(2)).unwrap().recall, 0.5);
+
+        // Shapes whose byte size overflows are corrupt, not a panic
+        write_dataset(&path, u64::MAX / 4);
+        let file = Hdf5File::open(&path).unwrap();
+        assert!(matches!(file.dataset("train"), Err(DatasetError::Corrupted(_))));
+
+        std::fs::write(&path, b"not hdf5").unwrap();
+        assert!(matches!(Dataset::load_hdf5(&path), Err(DatasetError::NotHdf5)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
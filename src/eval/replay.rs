@@ -0,0 +1,234 @@
+//! Replay regression checks
+//!
+//! Re-runs the queries of a `QueryLog` recorded against a baseline index
+//! and compares today's results with the recorded ones, so an index change
+//! (new config, rebuilt tree, consolidation) can be validated before
+//! rollout.
+//!
+//! ```rust,ignore
+//! let baseline = QueryLog::read_all("queries.log")?;        // logged with_vectors(true)
+//! let report = replay_eval(&baseline, &new_index)?;
+//! assert!(report.mean_overlap > 0.95, "{:?}", report.worst(5));
+//!
+//! // Or through an Arms instance, honoring min_score/dedup
+//! let report = replay_with(&baseline, |record, query| arms.near_with_params(query, &record.params()))?;
+//! ```
+//!
+//! Only records with full vectors can be replayed. Filtered queries (the
+//! filter isn't logged), failed ones and ones a deadline cut short are
+//! skipped.
+
+use crate::core::{Id, Point};
+use crate::engine::{Operation, QueryRecord};
+use crate::ports::{Near, NearResult, SearchResult};
+
+/// How one replayed query's results moved
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryDiff {
+    /// Position of the record in the baseline
+    pub record: usize,
+
+    /// Shared results over the larger result count (1 if both empty)
+    pub overlap: f32,
+
+    /// Kendall's tau over the shared results' order (1 if under two shared)
+    pub rank_correlation: f32,
+
+    /// In the new results only
+    pub added: Vec<Id>,
+
+    /// In the baseline results only
+    pub removed: Vec<Id>,
+}
+
+impl QueryDiff {
+    fn between(record: usize, baseline: &[Id], current: &[Id]) -> Self {
+        let shared: Vec<Id> = baseline.iter().copied().filter(|id| current.contains(id)).collect();
+        let larger = baseline.len().max(current.len());
+        let overlap = if larger == 0 { 1.0 } else { shared.len() as f32 / larger as f32 };
+
+        // Pairs of shared results ordered the same way in both lists
+        let position = |id: &Id| current.iter().position(|c| c == id).unwrap_or(usize::MAX);
+        let positions: Vec<usize> = shared.iter().map(position).collect();
+        let (mut concordant, mut discordant) = (0usize, 0usize);
+        for i in 0..positions.len() {
+            for j in i + 1..positions.len() {
+                if positions[i] < positions[j] {
+                    concordant += 1;
+                } else {
+                    discordant += 1;
+                }
+            }
+        }
+        let pairs = concordant + discordant;
+        let rank_correlation = if pairs == 0 {
+            1.0
+        } else {
+            (concordant as f32 - discordant as f32) / pairs as f32
+        };
+
+        Self {
+            record,
+            overlap,
+            rank_correlation,
+            added: current.iter().copied().filter(|id| !baseline.contains(id)).collect(),
+            removed: baseline.iter().copied().filter(|id| !current.contains(id)).collect(),
+        }
+    }
+
+    /// Whether the results are the same, in the same order
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.rank_correlation == 1.0
+    }
+}
+
+/// Summary of a replay against a baseline
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReplayReport {
+    /// Queries run again
+    pub replayed: usize,
+
+    /// Records that couldn't be replayed (hash only, filtered, failed, or
+    /// incomplete)
+    pub skipped: usize,
+
+    /// Mean `QueryDiff::overlap`
+    pub mean_overlap: f32,
+
+    /// Mean `QueryDiff::rank_correlation`
+    pub mean_rank_correlation: f32,
+
+    /// Share of baseline results no longer returned
+    pub churn: f32,
+
+    /// Queries whose best result changed
+    pub top1_changed: usize,
+
+    /// Queries whose results changed at all, in baseline order
+    pub diffs: Vec<QueryDiff>,
+}
+
+impl ReplayReport {
+    /// The `n` changed queries with the least overlap
+    pub fn worst(&self, n: usize) -> Vec<&QueryDiff> {
+        let mut diffs: Vec<&QueryDiff> = self.diffs.iter().collect();
+        diffs.sort_by(|a, b| {
+            a.overlap
+                .total_cmp(&b.overlap)
+                .then(a.rank_correlation.total_cmp(&b.rank_correlation))
+        });
+        diffs.truncate(n);
+        diffs
+    }
+}
+
+/// Replay `baseline` on `index` with each record's k (or threshold)
+///
+/// `min_score` and `dedup` are applied by the index's `near_with_params`.
+pub fn replay_eval(baseline: &[QueryRecord], index: &dyn Near) -> NearResult<ReplayReport> {
+    replay_with(baseline, |record, query| match (record.operation, record.threshold) {
+        (Operation::Within, Some(threshold)) => index.within(query, threshold),
+        _ => index.near_with_params(query, &record.params()),
+    })
+}
+
+/// Replay `baseline` through `search`, given each record and its query
+pub fn replay_with<F>(baseline: &[QueryRecord], mut search: F) -> NearResult<ReplayReport>
+where
+    F: FnMut(&QueryRecord, &Point) -> NearResult<Vec<SearchResult>>,
+{
+    let mut report = ReplayReport::default();
+    let (mut overlap, mut correlation) = (0.0f32, 0.0f32);
+    let (mut baseline_results, mut lost) = (0usize, 0usize);
+
+    for (position, record) in baseline.iter().enumerate() {
+        let query = match record.query.point() {
+            Some(query) if record.ok && record.complete && !record.filtered => query,
+            _ => {
+                report.skipped += 1;
+                continue;
+            }
+        };
+        let current: Vec<Id> = search(record, &query)?.into_iter().map(|r| r.id).collect();
+        let diff = QueryDiff::between(position, &record.results, &current);
+
+        report.replayed += 1;
+        overlap += diff.overlap;
+        correlation += diff.rank_correlation;
+        baseline_results += record.results.len();
+        lost += diff.removed.len();
+        if record.results.first() != current.first() {
+            report.top1_changed += 1;
+        }
+        if !diff.is_unchanged() {
+            report.diffs.push(diff);
+        }
+    }
+
+    if report.replayed > 0 {
+        report.mean_overlap = overlap / report.replayed as f32;
+        report.mean_rank_correlation = correlation / report.replayed as f32;
+    }
+    if baseline_results > 0 {
+        report.churn = lost as f32 / baseline_results as f32;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::index::FlatIndex;
+    use crate::engine::{LoggedQuery, QueryLog};
+
+    fn record(index: &FlatIndex, query: Vec<f32>, k: usize) -> QueryRecord {
+        let results = index.near(&Point::new(query.clone()), k).unwrap();
+        QueryRecord {
+            at_micros: 0,
+            operation: Operation::Near,
+            query: LoggedQuery::Vector(query),
+            k,
+            threshold: None,
+            min_score: None,
+            dedup: None,
+            filtered: false,
+            latency_micros: 0,
+            ok: true,
+            complete: true,
+            results: results.into_iter().map(|r| r.id).collect(),
+        }
+    }
+
+    #[test]
+    fn test_replay_eval_reports_churn() {
+        let mut index = FlatIndex::euclidean(1);
+        let ids: Vec<Id> = (0..5).map(|i| Id::seeded(0, i)).collect();
+        for (i, id) in ids.iter().enumerate() {
+            index.add(*id, &Point::new(vec![i as f32 * 10.0])).unwrap();
+        }
+
+        let log = QueryLog::to_callback(|_| {});
+        let mut baseline = vec![record(&index, vec![0.0], 2), record(&index, vec![41.0], 2)];
+        baseline.push(QueryRecord { query: log.logged_query(&Point::new(vec![5.0])), ..baseline[0].clone() });
+        baseline.push(QueryRecord { complete: false, ..baseline[1].clone() });
+
+        let unchanged = replay_eval(&baseline, &index).unwrap();
+        assert_eq!((unchanged.replayed, unchanged.skipped), (2, 2));
+        assert_eq!((unchanged.mean_overlap, unchanged.churn), (1.0, 0.0));
+        assert!(unchanged.diffs.is_empty());
+
+        // Losing the nearest point to the first query
+        index.remove(ids[0]).unwrap();
+        let report = replay_eval(&baseline, &index).unwrap();
+        assert_eq!(report.top1_changed, 1);
+        assert_eq!(report.churn, 0.25);
+        assert_eq!(report.mean_overlap, 0.75);
+        assert_eq!(report.diffs.len(), 1);
+        assert_eq!(report.diffs[0].removed, vec![ids[0]]);
+        assert_eq!(report.diffs[0].added, vec![ids[2]]);
+        assert_eq!(report.worst(1)[0].record, 0);
+
+        let swapped = QueryDiff::between(0, &[ids[0], ids[1], ids[2]], &[ids[2], ids[1], ids[0]]);
+        assert_eq!((swapped.overlap, swapped.rank_correlation), (1.0, -1.0));
+    }
+}
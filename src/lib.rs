@@ -74,6 +74,18 @@ pub mod adapters;
 /// Contains: Arms main struct
 pub mod engine;
 
+/// Crate-wide error type
+/// Contains: ArmsError, ErrorCode
+pub mod error;
+
+/// Helpers for testing adapters against the port contracts
+/// Contains: port conformance checks, temp_path, proptest strategies (feature `arbitrary`)
+pub mod testing;
+
+/// Recall/QPS evaluation on ann-benchmarks datasets, replay regression checks
+/// Contains: Dataset, evaluate, replay_eval, minimal HDF5 reader
+pub mod eval;
+
 // ============================================================================
 // PYTHON BINDINGS (when enabled)
 // ============================================================================
@@ -87,15 +99,23 @@ pub use adapters::python::*;
 
 // Core types
 pub use crate::core::{Point, Id, Blob, PlacedPoint};
-pub use crate::core::proximity::{Proximity, Cosine, Euclidean, DotProduct};
-pub use crate::core::merge::{Merge, Mean, WeightedMean, MaxPool};
-pub use crate::core::config::ArmsConfig;
+pub use crate::core::proximity::{
+    Proximity, ScoreOrder, Cosine, Euclidean, DotProduct, WeightedCosine, WeightedEuclidean,
+};
+pub use crate::core::merge::{Merge, Mean, WeightedMean, MaxPool, AttentionMerge, GeometricMedian, OnlineMerge};
+pub use crate::core::config::{ArmsConfig, MatryoshkaConfig, NonFinitePolicy};
+pub use crate::core::schema::{DimensionSchema, GroupedProximity, PointBuilder, SchemaError};
+pub use crate::core::clustering::{Cluster, Clustering, KMeans};
+pub use crate::core::projection::{ProjectedPoint, Projection, Projector};
 
 // Port traits
-pub use crate::ports::{Place, Near, Latency};
+pub use crate::ports::{Place, Near, Latency, Embedder, QueryParams};
 
 // Engine
-pub use crate::engine::Arms;
+pub use crate::engine::{Arms, Collections, MemoryReport, Metrics, QueryCache, QueryLog, Salience, WeightedSalience};
+
+// Errors
+pub use crate::error::{ArmsError, ArmsResult, ErrorCode};
 
 // ============================================================================
 // CRATE-LEVEL DOCUMENTATION
@@ -0,0 +1,40 @@
+//! # Consensus Port
+//!
+//! Trait for agreeing on cluster metadata (`core::cluster::ClusterMeta`).
+//!
+//! An implementation orders proposed `MetaCommand`s into one log and
+//! applies it on every replica, so nodes share collection definitions and
+//! the shard map without an external coordinator. `LocalConsensus` is the
+//! single-node case; `RaftConsensus` replicates the log across a group of
+//! nodes with Raft, with `ClusterMeta` as its state machine.
+
+use crate::core::cluster::{ClusterMeta, MetaCommand, MetaError};
+
+/// Result type for consensus operations
+pub type ConsensusResult<T> = Result<T, ConsensusError>;
+
+/// Errors from proposing or reading metadata
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum ConsensusError {
+    /// Only the leader accepts proposals; retry there
+    #[error("Not the leader (leader: {})", leader.as_deref().unwrap_or("unknown"))]
+    NotLeader { leader: Option<String> },
+
+    /// The command was committed but rejected by the state machine
+    #[error("Rejected at log index {index}: {reason}")]
+    Rejected { index: u64, reason: MetaError },
+
+    /// No quorum, or the log couldn't be written
+    #[error("Consensus unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Replicated cluster metadata
+pub trait Consensus: Send + Sync {
+    /// Commit a command; returns its log index once applied
+    fn propose(&self, command: MetaCommand) -> ConsensusResult<u64>;
+
+    /// Metadata as of the latest applied entry on this replica
+    fn metadata(&self) -> ClusterMeta;
+}
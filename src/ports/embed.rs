@@ -0,0 +1,42 @@
+//! # Embed Port
+//!
+//! Trait for turning text into points.
+//!
+//! ARMS stores and indexes points; it doesn't compute them. Anything that
+//! ingests raw text (importers, pipelines) takes an `Embedder` so the
+//! embedding model stays swappable (local model, remote API, test stub).
+
+use crate::core::Point;
+
+/// Result type for embed operations
+pub type EmbedResult<T> = Result<T, EmbedError>;
+
+/// Errors that can occur while embedding
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum EmbedError {
+    /// The model returned a vector of unexpected size
+    #[error("Dimensionality mismatch: expected {expected}, got {got}")]
+    DimensionalityMismatch { expected: usize, got: usize },
+
+    /// Embedding backend error (model, network, rate limit)
+    #[error("Embedding backend error: {0}")]
+    BackendError(String),
+}
+
+/// Trait for embedding text
+pub trait Embedder: Send + Sync {
+    /// Dimensionality of produced points
+    fn dimensionality(&self) -> usize;
+
+    /// Embed a single text
+    fn embed(&self, text: &str) -> EmbedResult<Point>;
+
+    /// Embed several texts
+    ///
+    /// Default implementation calls `embed` per text; backends with native
+    /// batching should override it.
+    fn embed_batch(&self, texts: &[&str]) -> EmbedResult<Vec<Point>> {
+        texts.iter().map(|t| self.embed(t)).collect()
+    }
+}
@@ -0,0 +1,139 @@
+//! # Job Port
+//!
+//! Progress and cancellation for long-running operations.
+//!
+//! Operations that can take minutes (consolidation, index builds, imports)
+//! take a `JobHandle`: they report units of work as they go and stop early
+//! once it's cancelled. The caller keeps a clone to watch progress, derive
+//! an ETA, or cancel from another thread. `engine::Job` runs such an
+//! operation on its own thread.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::core::clock;
+use super::near::CancellationToken;
+
+/// Shared progress counters and cancellation flag of one job
+///
+/// Clones share state. Times come from `core::clock`, so handles work on
+/// wasm32 too.
+#[derive(Debug, Clone, Default)]
+pub struct JobHandle {
+    state: Arc<JobState>,
+}
+
+#[derive(Debug, Default)]
+struct JobState {
+    done: AtomicUsize,
+    total: AtomicUsize,
+
+    /// Microseconds since the Unix epoch (0 = not started)
+    started_micros: AtomicU64,
+
+    token: CancellationToken,
+    finished: AtomicBool,
+}
+
+impl JobHandle {
+    /// A handle with its clock started now
+    pub fn new() -> Self {
+        let handle = Self::default();
+        handle.state.started_micros.store(clock::now_micros(), Ordering::Relaxed);
+        handle
+    }
+
+    /// Set the units of work expected in total
+    pub fn set_total(&self, total: usize) {
+        self.state.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Record `units` more units of work done
+    pub fn advance(&self, units: usize) {
+        self.state.done.fetch_add(units, Ordering::Relaxed);
+    }
+
+    /// Set the units of work done so far
+    pub fn set_done(&self, done: usize) {
+        self.state.done.store(done, Ordering::Relaxed);
+    }
+
+    /// Ask the operation to stop at its next check
+    pub fn cancel(&self) {
+        self.state.token.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.token.is_cancelled()
+    }
+
+    /// The cancellation flag, e.g. for `Deadline::with_token`
+    pub fn token(&self) -> CancellationToken {
+        self.state.token.clone()
+    }
+
+    /// Mark the operation as over (done, failed or cancelled)
+    pub fn finish(&self) {
+        self.state.finished.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.finished.load(Ordering::Relaxed)
+    }
+
+    /// Snapshot of the counters
+    pub fn progress(&self) -> JobProgress {
+        let started = self.state.started_micros.load(Ordering::Relaxed);
+        JobProgress {
+            done: self.state.done.load(Ordering::Relaxed),
+            total: self.state.total.load(Ordering::Relaxed),
+            elapsed: Duration::from_micros(clock::now_micros().saturating_sub(started)),
+            cancelled: self.is_cancelled(),
+            finished: self.is_finished(),
+        }
+    }
+}
+
+/// Progress of a job at one moment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobProgress {
+    /// Units of work done
+    pub done: usize,
+
+    /// Units of work expected (0 = not known yet)
+    pub total: usize,
+
+    /// Time since the job started
+    pub elapsed: Duration,
+
+    pub cancelled: bool,
+    pub finished: bool,
+}
+
+impl JobProgress {
+    /// Share of the work done, in `[0, 1]` (0 while the total is unknown)
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            return if self.finished { 1.0 } else { 0.0 };
+        }
+        (self.done as f32 / self.total as f32).min(1.0)
+    }
+
+    /// `fraction` as a percentage
+    pub fn percent(&self) -> f32 {
+        self.fraction() * 100.0
+    }
+
+    /// Time left at the rate so far (None until some work is done)
+    pub fn eta(&self) -> Option<Duration> {
+        if self.finished {
+            return Some(Duration::ZERO);
+        }
+        if self.done == 0 || self.total == 0 {
+            return None;
+        }
+        let remaining = self.total.saturating_sub(self.done) as f64;
+        Some(self.elapsed.mul_f64(remaining / self.done as f64))
+    }
+}
@@ -12,17 +12,34 @@
 mod place;
 mod near;
 mod latency;
+mod embed;
+mod job;
+mod shard;
+mod consensus;
 
 // Re-export traits
 pub use place::Place;
 pub use near::Near;
 pub use latency::Latency;
+pub use embed::Embedder;
+pub use job::{JobHandle, JobProgress};
+pub use shard::ShardNode;
+pub use consensus::Consensus;
 
 // Re-export types from place
 pub use place::{PlaceError, PlaceResult};
 
 // Re-export types from near
-pub use near::{NearError, NearResult, SearchResult};
+pub use near::{NearError, NearResult, SearchResult, Deadline, CancellationToken, PartialResults, IndexMemory, QueryParams, IdFilter, ReturnFields, ProjectedResult, ContextChunk};
 
 // Re-export types from latency
 pub use latency::{Tier, LatencyBudget, LatencyMeasurement, TierStats};
+
+// Re-export types from embed
+pub use embed::{EmbedError, EmbedResult};
+
+// Re-export types from shard
+pub use shard::{ShardError, ShardResult};
+
+// Re-export types from consensus
+pub use consensus::{ConsensusError, ConsensusResult};
@@ -7,7 +7,13 @@
 //!
 //! Implemented by index adapters (Flat, HNSW, etc.)
 
-use crate::core::{Id, Point};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::core::{clock, Blob, Cause, Id, Metadata, Point, Provenance};
+use crate::core::proximity::ScoreOrder;
 
 /// Result type for near operations
 pub type NearResult<T> = Result<T, NearError>;
@@ -21,40 +27,389 @@ pub struct SearchResult {
     /// Distance or similarity score
     /// Interpretation depends on the proximity function used.
     pub score: f32,
+
+    /// Importance assigned when the point was placed (None = not scored)
+    pub salience: Option<f32>,
 }
 
 impl SearchResult {
     pub fn new(id: Id, score: f32) -> Self {
-        Self { id, score }
+        Self { id, score, salience: None }
+    }
+
+    pub fn with_salience(mut self, salience: f32) -> Self {
+        self.salience = Some(salience);
+        self
+    }
+
+    /// Total result order: more relevant first, equal scores by ascending ID
+    ///
+    /// IDs are timestamp-prefixed, so ties resolve oldest first and never
+    /// depend on hash map or thread scheduling order.
+    pub fn rank(&self, other: &Self, order: ScoreOrder) -> std::cmp::Ordering {
+        order
+            .compare(self.score, other.score)
+            .then_with(|| self.id.cmp(&other.id))
+    }
+
+    /// Sort results by `rank`
+    pub fn sort(results: &mut [SearchResult], order: ScoreOrder) {
+        results.sort_by(|a, b| a.rank(b, order));
     }
 }
 
-/// Errors that can occur during near operations
-#[derive(Debug, Clone, PartialEq)]
-pub enum NearError {
-    /// The query point has wrong dimensionality
-    DimensionalityMismatch { expected: usize, got: usize },
+/// Cooperative cancellation flag shared between a caller and running queries
+///
+/// Clones share the flag: hand one to the query (via `Deadline`) and keep
+/// one to call `cancel` from another thread or task.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
 
-    /// Index is not built/ready
-    IndexNotReady,
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    /// Index backend error
-    IndexError(String),
+    /// Ask every query holding this token to stop
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Budget for a query: a wall-clock deadline, a cancellation token, or both
+///
+/// Times come from `core::clock`, so deadlines also work on wasm32.
+#[derive(Debug, Clone, Default)]
+pub struct Deadline {
+    /// Microseconds since the Unix epoch
+    at_micros: Option<u64>,
+    token: Option<CancellationToken>,
+}
+
+impl Deadline {
+    /// No time limit (cancellable once a token is attached)
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Expire `budget` from now
+    pub fn after(budget: Duration) -> Self {
+        Self {
+            at_micros: Some(clock::now_micros().saturating_add(budget.as_micros() as u64)),
+            token: None,
+        }
+    }
+
+    /// Also stop when `token` is cancelled
+    pub fn with_token(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Whether the query should stop now
+    pub fn is_expired(&self) -> bool {
+        self.token.as_ref().is_some_and(|t| t.is_cancelled())
+            || self.at_micros.is_some_and(|at| clock::now_micros() >= at)
+    }
+}
+
+/// Results of a query that may have been cut short by its deadline
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PartialResults {
+    /// Best results found, most relevant first
+    pub results: Vec<SearchResult>,
+
+    /// False if the deadline expired before the search finished
+    pub complete: bool,
+}
+
+/// Predicate deciding which IDs a query may return
+pub type IdFilter = Arc<dyn Fn(Id) -> bool + Send + Sync>;
+
+/// Per-query overrides of index and engine settings
+///
+/// Lets latency-sensitive and recall-sensitive callers tune one call
+/// instead of the global config. Unset fields use the configured values.
+///
+/// ```rust,ignore
+/// let params = QueryParams::new(10)
+///     .with_beam_width(32)                    // wider search, better recall
+///     .with_filter(move |id| allowed.contains(&id));
+/// let results = arms.near_with_params(&query, &params)?;
+/// ```
+#[derive(Clone, Default)]
+pub struct QueryParams {
+    /// Number of results
+    pub k: usize,
+
+    /// Beam (ef) width for tree and graph indexes
+    pub beam_width: Option<usize>,
+
+    /// Weight of recency against semantic distance (0.0 - 1.0)
+    pub temporal_weight: Option<f32>,
+
+    /// Only return IDs the filter accepts
+    pub filter: Option<IdFilter>,
+
+    /// Rescore `k * n` candidates on full stored vectors (0 disables)
+    pub rescore: Option<usize>,
+
+    /// Drop results less related than this score (for distances: farther)
+    ///
+    /// `FlatIndex` stops distance computations at it; `HatIndex` only filters
+    /// the leaves it reaches, without pruning the tree.
+    pub min_score: Option<f32>,
+
+    /// Collapse results whose proximity to a better result passes this
+    /// threshold, keeping the best-scoring copy
+    pub dedup: Option<f32>,
+
+    /// Stored fields to attach to results (see `ReturnFields`)
+    pub fields: ReturnFields,
+
+    /// Attach up to this many chunks either side of each hit from the same
+    /// document, as `ProjectedResult::context` (indexes that keep documents)
+    pub expand_context: Option<usize>,
+}
+
+impl QueryParams {
+    pub fn new(k: usize) -> Self {
+        Self { k, ..Self::default() }
+    }
+
+    pub fn with_beam_width(mut self, width: usize) -> Self {
+        self.beam_width = Some(width);
+        self
+    }
+
+    pub fn with_temporal_weight(mut self, weight: f32) -> Self {
+        self.temporal_weight = Some(weight);
+        self
+    }
+
+    pub fn with_filter(mut self, filter: impl Fn(Id) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    pub fn with_rescore(mut self, oversample: usize) -> Self {
+        self.rescore = Some(oversample);
+        self
+    }
+
+    pub fn with_min_score(mut self, score: f32) -> Self {
+        self.min_score = Some(score);
+        self
+    }
+
+    pub fn with_dedup(mut self, threshold: f32) -> Self {
+        self.dedup = Some(threshold);
+        self
+    }
+
+    pub fn with_fields(mut self, fields: ReturnFields) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    pub fn with_expand_context(mut self, n: usize) -> Self {
+        self.expand_context = Some(n);
+        self
+    }
+
+    /// Whether a score clears `min_score` (if set) under `order`
+    pub fn passes(&self, score: f32, order: ScoreOrder) -> bool {
+        self.min_score.is_none_or(|min| order.passes(score, min))
+    }
+
+    /// Whether the filter (if any) accepts `id`
+    pub fn accepts(&self, id: Id) -> bool {
+        self.filter.as_ref().is_none_or(|f| f(id))
+    }
 }
 
-impl std::fmt::Display for NearError {
+impl std::fmt::Debug for QueryParams {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            NearError::DimensionalityMismatch { expected, got } => {
-                write!(f, "Dimensionality mismatch: expected {}, got {}", expected, got)
+        f.debug_struct("QueryParams")
+            .field("k", &self.k)
+            .field("beam_width", &self.beam_width)
+            .field("temporal_weight", &self.temporal_weight)
+            .field("filter", &self.filter.as_ref().map(|_| ".."))
+            .field("rescore", &self.rescore)
+            .field("min_score", &self.min_score)
+            .field("dedup", &self.dedup)
+            .field("fields", &self.fields)
+            .field("expand_context", &self.expand_context)
+            .finish()
+    }
+}
+
+/// Which stored fields a query attaches to its results
+///
+/// IDs and scores always come back; everything else costs a lookup and a
+/// copy (or, for spilled blobs, a read), so high-QPS callers can ask for
+/// only what they use. The default matches what searches have always
+/// returned - blob, metadata and salience - plus provenance, but not the
+/// vector.
+///
+/// ```
+/// use arms_hat::ports::ReturnFields;
+/// let fields = ReturnFields::parse("vector,metadata").unwrap();
+/// assert!(fields.vector && fields.metadata && !fields.blob);
+/// assert_eq!(ReturnFields::parse("none").unwrap(), ReturnFields::NONE);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReturnFields {
+    /// The stored vector
+    pub vector: bool,
+
+    /// The raw payload
+    pub blob: bool,
+
+    /// Typed metadata (indexes that keep it)
+    pub metadata: bool,
+
+    /// Importance assigned at placement
+    pub salience: bool,
+
+    /// Source, pipeline, model version and confidence (indexes that keep it)
+    pub provenance: bool,
+}
+
+impl ReturnFields {
+    /// IDs and scores only
+    pub const NONE: Self = Self { vector: false, blob: false, metadata: false, salience: false, provenance: false };
+
+    /// Every field
+    pub const ALL: Self = Self { vector: true, blob: true, metadata: true, salience: true, provenance: true };
+
+    /// Field names `parse` accepts
+    pub const NAMES: [&'static str; 5] = ["vector", "blob", "metadata", "salience", "provenance"];
+
+    /// Parse a comma-separated list of field names, or `all` / `none`
+    pub fn parse(list: &str) -> Result<Self, String> {
+        let mut fields = Self::NONE;
+        for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            match name {
+                "all" => fields = Self::ALL,
+                "none" => {}
+                "vector" => fields.vector = true,
+                "blob" => fields.blob = true,
+                "metadata" => fields.metadata = true,
+                "salience" => fields.salience = true,
+                "provenance" => fields.provenance = true,
+                other => {
+                    return Err(format!(
+                        "unknown field '{}', expected one of {} (or all, none)",
+                        other,
+                        Self::NAMES.join(", ")
+                    ))
+                }
             }
-            NearError::IndexNotReady => write!(f, "Index not ready"),
-            NearError::IndexError(msg) => write!(f, "Index error: {}", msg),
+        }
+        Ok(fields)
+    }
+}
+
+impl Default for ReturnFields {
+    fn default() -> Self {
+        Self { vector: false, blob: true, metadata: true, salience: true, provenance: true }
+    }
+}
+
+/// A search result with the fields a query asked for
+///
+/// Fields not requested (or not stored) are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectedResult {
+    pub id: Id,
+    pub score: f32,
+    pub salience: Option<f32>,
+    pub vector: Option<Point>,
+    pub blob: Option<Blob>,
+    pub metadata: Option<Metadata>,
+    pub provenance: Option<Provenance>,
+
+    /// Neighbouring chunks of the same document, in document order (see
+    /// `QueryParams::expand_context`)
+    pub context: Vec<ContextChunk>,
+}
+
+/// A chunk returned beside a hit for context
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextChunk {
+    pub id: Id,
+
+    /// Position relative to the hit (-1 = just before, 1 = just after)
+    pub offset: isize,
+
+    /// Payload, if the query asked for blobs
+    pub blob: Option<Blob>,
+}
+
+impl ProjectedResult {
+    /// A result with no fields attached yet
+    pub fn bare(result: &SearchResult, fields: ReturnFields) -> Self {
+        Self {
+            id: result.id,
+            score: result.score,
+            salience: result.salience.filter(|_| fields.salience),
+            vector: None,
+            blob: None,
+            metadata: None,
+            provenance: None,
+            context: Vec::new(),
         }
     }
 }
 
-impl std::error::Error for NearError {}
+/// Approximate heap bytes held by an index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IndexMemory {
+    /// Indexed vectors (or the prefixes the index holds)
+    pub vectors: usize,
+
+    /// Search structure: graph/tree links, centroids, lookup tables
+    pub structure: usize,
+
+    /// Payloads and metadata kept by the index itself
+    pub metadata: usize,
+}
+
+impl IndexMemory {
+    pub fn total(&self) -> usize {
+        self.vectors + self.structure + self.metadata
+    }
+}
+
+/// Errors that can occur during near operations
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum NearError {
+    /// The query point has wrong dimensionality
+    #[error("Dimensionality mismatch: expected {expected}, got {got}")]
+    DimensionalityMismatch { expected: usize, got: usize },
+
+    /// A query coordinate is NaN or infinite (see `NonFinitePolicy`)
+    #[error("Non-finite coordinate at dimension {index}")]
+    NonFinite { index: usize },
+
+    /// Index is not built/ready
+    #[error("Index not ready")]
+    IndexNotReady,
+
+    /// No point with this ID is indexed
+    #[error("Unknown id: {0}")]
+    NotFound(Id),
+
+    /// Index backend error
+    #[error("Index error: {0}")]
+    IndexError(#[source] Cause),
+}
 
 /// Trait for finding related points
 ///
@@ -62,13 +417,93 @@ impl std::error::Error for NearError {}
 pub trait Near: Send + Sync {
     /// Find k nearest points to query
     ///
-    /// Returns results sorted by relevance (most relevant first).
+    /// Returns results sorted by relevance (most relevant first), equal
+    /// scores by ascending ID (see `SearchResult::rank`).
     fn near(&self, query: &Point, k: usize) -> NearResult<Vec<SearchResult>>;
 
+    /// Visit the k nearest points, most relevant first, without collecting them
+    ///
+    /// The visitor can stop early by returning `ControlFlow::Break(())`.
+    /// The default collects `near`; adapters override it to stream from
+    /// their own buffers.
+    fn near_visit(
+        &self,
+        query: &Point,
+        k: usize,
+        visitor: &mut dyn FnMut(SearchResult) -> ControlFlow<()>,
+    ) -> NearResult<()> {
+        for result in self.near(query, k)? {
+            if visitor(result).is_break() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Find k nearest points, giving up when `deadline` expires
+    ///
+    /// An expired search returns the best results found so far with
+    /// `complete: false` rather than an error. The default only checks the
+    /// deadline before starting; adapters override it to check as they go.
+    fn near_with_deadline(
+        &self,
+        query: &Point,
+        k: usize,
+        deadline: &Deadline,
+    ) -> NearResult<PartialResults> {
+        if deadline.is_expired() {
+            return Ok(PartialResults::default());
+        }
+        Ok(PartialResults {
+            results: self.near(query, k)?,
+            complete: true,
+        })
+    }
+
+    /// Find nearest points with per-query overrides
+    ///
+    /// The default ignores beam width, temporal weight and `dedup` (it
+    /// can't see the vectors). It applies the filter and `min_score` by
+    /// fetching `k` results, then four times as many each round, until `k`
+    /// pass, a result falls below `min_score`, or the index is exhausted.
+    /// Adapters override it to honour the overrides and cut results off
+    /// during the search.
+    fn near_with_params(&self, query: &Point, params: &QueryParams) -> NearResult<Vec<SearchResult>> {
+        if params.filter.is_none() && params.min_score.is_none() {
+            return self.near(query, params.k);
+        }
+
+        let order = self.order();
+        let mut fetch = params.k;
+        loop {
+            let candidates = self.near(query, fetch)?;
+            let mut exhausted = candidates.len() < fetch || fetch >= self.len();
+            let mut results = Vec::with_capacity(params.k);
+            for result in candidates {
+                if !params.passes(result.score, order) {
+                    // Sorted, so nothing further can pass either
+                    exhausted = true;
+                    break;
+                }
+                if params.accepts(result.id) {
+                    results.push(result);
+                    if results.len() == params.k {
+                        break;
+                    }
+                }
+            }
+            if results.len() == params.k || exhausted {
+                return Ok(results);
+            }
+            fetch = fetch.saturating_mul(4).max(1);
+        }
+    }
+
     /// Find all points within a distance/similarity threshold
     ///
     /// For distance metrics (Euclidean), finds points with distance < threshold.
     /// For similarity metrics (Cosine), finds points with similarity > threshold.
+    /// Sorted like `near`.
     fn within(&self, query: &Point, threshold: f32) -> NearResult<Vec<SearchResult>>;
 
     /// Add a point to the index
@@ -85,6 +520,11 @@ pub trait Near: Send + Sync {
     /// Check if the index is ready for queries
     fn is_ready(&self) -> bool;
 
+    /// How this index's scores rank (similarity by default)
+    fn order(&self) -> ScoreOrder {
+        ScoreOrder::HigherIsBetter
+    }
+
     /// Get the number of indexed points
     fn len(&self) -> usize;
 
@@ -92,4 +532,11 @@ pub trait Near: Send + Sync {
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Approximate heap bytes held, for capacity planning
+    ///
+    /// The default reports nothing; adapters override it.
+    fn memory_usage(&self) -> IndexMemory {
+        IndexMemory::default()
+    }
 }
@@ -7,41 +7,56 @@
 //!
 //! Implemented by storage adapters (Memory, NVMe, etc.)
 
-use crate::core::{Blob, Id, PlacedPoint, Point};
+use crate::core::{Blob, Cause, Id, PlacedPoint, Point};
+use crate::ports::NearError;
 
 /// Result type for place operations
 pub type PlaceResult<T> = Result<T, PlaceError>;
 
 /// Errors that can occur during place operations
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
 pub enum PlaceError {
     /// The point has wrong dimensionality for this space
+    #[error("Dimensionality mismatch: expected {expected}, got {got}")]
     DimensionalityMismatch { expected: usize, got: usize },
 
+    /// A coordinate is NaN or infinite (see `NonFinitePolicy`)
+    #[error("Non-finite coordinate at dimension {index}")]
+    NonFinite { index: usize },
+
     /// Storage capacity exceeded
+    #[error("Storage capacity exceeded")]
     CapacityExceeded,
 
     /// Point with this ID already exists
+    #[error("Duplicate ID: {0}")]
     DuplicateId(Id),
 
+    /// Stored data can't be decoded
+    #[error("Corrupt data: {0}")]
+    Corrupted(#[source] Cause),
+
+    /// The index rejected the point (storage was rolled back)
+    #[error("Index rejected point")]
+    Index(#[source] NearError),
+
     /// Storage backend error
-    StorageError(String),
-}
+    #[error("Storage error: {0}")]
+    StorageError(#[source] Cause),
 
-impl std::fmt::Display for PlaceError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            PlaceError::DimensionalityMismatch { expected, got } => {
-                write!(f, "Dimensionality mismatch: expected {}, got {}", expected, got)
-            }
-            PlaceError::CapacityExceeded => write!(f, "Storage capacity exceeded"),
-            PlaceError::DuplicateId(id) => write!(f, "Duplicate ID: {}", id),
-            PlaceError::StorageError(msg) => write!(f, "Storage error: {}", msg),
-        }
-    }
-}
+    /// The store was opened read-only
+    #[error("Store is open read-only")]
+    ReadOnly,
 
-impl std::error::Error for PlaceError {}
+    /// An ingest interceptor refused the point
+    #[error("Rejected by {by}: {reason}")]
+    Rejected { by: String, reason: String },
+
+    /// Another writer (this process or `pid`) holds the store's lock
+    #[error("Store {path} is locked by {}", pid.map_or("another process".to_string(), |pid| format!("process {}", pid)))]
+    StoreLocked { path: String, pid: Option<u32> },
+}
 
 /// Trait for placing points in the space
 ///
@@ -88,4 +103,26 @@ pub trait Place: Send + Sync {
 
     /// Clear all points
     fn clear(&mut self);
+
+    /// Drain the IDs evicted to make room since the last call
+    ///
+    /// Callers that index this storage remove these IDs from the index.
+    /// Stores that never evict return nothing.
+    fn take_evicted(&mut self) -> Vec<Id> {
+        Vec::new()
+    }
+
+    /// Record the importance of a stored point, for stores that evict by it
+    ///
+    /// Stores without salience-aware eviction ignore it.
+    fn set_salience(&mut self, _id: Id, _salience: f32) {}
+
+    /// Drop what the store still keeps of removed points (log entries,
+    /// tombstones, freed pages)
+    ///
+    /// Returns whether anything was rewritten. Stores that keep nothing
+    /// after a remove return `Ok(false)`.
+    fn compact(&mut self) -> PlaceResult<bool> {
+        Ok(false)
+    }
 }
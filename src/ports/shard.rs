@@ -0,0 +1,61 @@
+//! # Shard Port
+//!
+//! Trait for one node of a sharded deployment.
+//!
+//! A `ShardRouter` spreads IDs over nodes and fans queries out to all of
+//! them; each node only needs to store, forget, list and search its own
+//! points. Nodes may live in-process (`LocalShard`) or behind the HTTP
+//! server (`RemoteShard`).
+
+use crate::core::{Blob, Id, Point};
+
+use super::near::SearchResult;
+
+/// Result type for shard operations
+pub type ShardResult<T> = Result<T, ShardError>;
+
+/// Errors from shard nodes and the router
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum ShardError {
+    /// The router has no nodes to send to
+    #[error("No shard nodes")]
+    NoNodes,
+
+    /// A node with this name is already routed to
+    #[error("Shard node already present: {0}")]
+    DuplicateNode(String),
+
+    /// No node with this name
+    #[error("Unknown shard node: {0}")]
+    UnknownNode(String),
+
+    /// The node couldn't be reached
+    #[error("Shard node {node} unreachable: {reason}")]
+    Unreachable { node: String, reason: String },
+
+    /// The node refused or failed the request
+    #[error("Shard node {node} failed: {message}")]
+    Node { node: String, message: String },
+}
+
+/// A node holding one shard of the points
+pub trait ShardNode: Send + Sync {
+    /// Stable name; placement on the hash ring derives from it
+    fn name(&self) -> &str;
+
+    /// Store a point under a router-chosen ID
+    fn place(&self, id: Id, point: Point, blob: Blob) -> ShardResult<()>;
+
+    /// Forget a point (false if it wasn't here)
+    fn remove(&self, id: Id) -> ShardResult<bool>;
+
+    /// A stored point and its blob
+    fn get(&self, id: Id) -> ShardResult<Option<(Point, Blob)>>;
+
+    /// IDs of every stored point
+    fn ids(&self) -> ShardResult<Vec<Id>>;
+
+    /// This node's k nearest points to `query`
+    fn near(&self, query: &Point, k: usize) -> ShardResult<Vec<SearchResult>>;
+}
@@ -0,0 +1,172 @@
+//! proptest `Arbitrary` implementations
+//!
+//! Generated values are always valid inputs: finite coordinates, non-zero
+//! points, and HAT configs whose `min_children` fits `max_children`.
+//!
+//! `Point`'s impl lives in `arms-core` (feature `arbitrary` turns it on).
+//!
+//! Points and attention states take their dimensionality as the strategy
+//! parameter (`any_with::<Point>(Some(128))`); `None` picks one in
+//! `1..=MAX_DIMENSIONALITY`.
+
+use std::collections::HashMap;
+
+use proptest::collection::{hash_map, vec};
+use proptest::prelude::*;
+
+use crate::adapters::attention::{AttentionState, Role};
+use crate::adapters::index::{CentroidMethod, HatConfig};
+use crate::core::{Blob, Id, Point};
+
+/// Largest dimensionality picked when none is given
+pub use arms_core::arbitrary::MAX_DIMENSIONALITY;
+
+use arms_core::arbitrary::coordinates;
+
+/// Largest generated blob, in bytes
+pub const MAX_BLOB_BYTES: usize = 256;
+
+/// Points of one dimensionality
+pub fn points(dimensionality: usize) -> BoxedStrategy<Point> {
+    any_with::<Point>(Some(dimensionality))
+}
+
+impl Arbitrary for Id {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Id>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any::<[u8; 16]>().prop_map(Id::from_bytes).boxed()
+    }
+}
+
+impl Arbitrary for Blob {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Blob>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        vec(any::<u8>(), 0..=MAX_BLOB_BYTES).prop_map(Blob::new).boxed()
+    }
+}
+
+impl Arbitrary for Role {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Role>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(Role::System),
+            Just(Role::User),
+            Just(Role::Assistant),
+            Just(Role::Tool),
+            Just(Role::Context),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for AttentionState {
+    type Parameters = Option<usize>;
+    type Strategy = BoxedStrategy<AttentionState>;
+
+    /// States without a KV cache
+    fn arbitrary_with(dimensionality: Self::Parameters) -> Self::Strategy {
+        (
+            any::<Id>(),
+            any::<u64>(),
+            any::<Role>(),
+            ".{0,64}",
+            coordinates(dimensionality),
+            hash_map("[a-z_]{1,8}", ".{0,16}", 0..4),
+        )
+            .prop_map(|(id, timestamp_ms, role, text, embedding, metadata)| {
+                let mut state = AttentionState::new(role, text, embedding);
+                state.id = id;
+                state.timestamp_ms = timestamp_ms;
+                state.metadata = metadata.into_iter().collect::<HashMap<_, _>>();
+                state
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for CentroidMethod {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<CentroidMethod>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        prop_oneof![
+            Just(CentroidMethod::Euclidean),
+            Just(CentroidMethod::Frechet),
+            Just(CentroidMethod::GeometricMedian),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for HatConfig {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<HatConfig>;
+
+    /// Tunable search and centroid parameters; optional subsystems
+    /// (subspaces, learnable routing, segmentation) keep their defaults
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            (2usize..=64).prop_flat_map(|max| (Just(max), 1..=max / 2)),
+            1usize..=8,
+            0.0f32..=1.0,
+            0.0f32..=0.01,
+            0.0f32..=0.5,
+            any::<CentroidMethod>(),
+            1usize..=8,
+        )
+            .prop_map(
+                |((max_children, min_children), beam_width, temporal_weight, time_decay, propagation_threshold, centroid_method, frechet_iterations)| {
+                    HatConfig {
+                        max_children,
+                        min_children,
+                        beam_width,
+                        temporal_weight,
+                        time_decay,
+                        propagation_threshold,
+                        centroid_method,
+                        frechet_iterations,
+                        ..HatConfig::default()
+                    }
+                },
+            )
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::index::HatIndex;
+    use crate::ports::Near;
+
+    proptest! {
+        #[test]
+        fn test_points_are_valid(point in points(8)) {
+            prop_assert_eq!(point.dimensionality(), 8);
+            prop_assert!(point.magnitude() > 0.0);
+        }
+
+        #[test]
+        fn test_attention_state_roundtrip(state in any_with::<AttentionState>(Some(4))) {
+            let back = AttentionState::from_bytes(&state.to_bytes()).unwrap();
+            prop_assert_eq!(back.id, state.id);
+            prop_assert_eq!(back.text, state.text);
+            prop_assert_eq!(back.metadata, state.metadata);
+        }
+
+        #[test]
+        fn test_any_hat_config_indexes(config in any::<HatConfig>(), points in vec(points(4), 1..20)) {
+            let mut index = HatIndex::cosine(4).with_config(config);
+            for point in &points {
+                index.add(Id::now(), point).unwrap();
+            }
+            prop_assert_eq!(index.near(&points[0], 1).unwrap().len(), 1);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Port conformance checks
+//!
+//! Contract tests any `Place` or `Near` implementation can run. Each check
+//! builds fresh instances through a factory taking the dimensionality, and
+//! panics with a description of the broken rule.
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn my_storage_conforms() {
+//!     arms_hat::testing::conformance::check_place(MyStorage::new);
+//! }
+//!
+//! #[test]
+//! fn my_index_conforms() {
+//!     arms_hat::testing::conformance::check_near(MyIndex::cosine, ScoreOrder::HigherIsBetter);
+//! }
+//! ```
+
+use crate::core::proximity::ScoreOrder;
+use crate::core::{Blob, Id, Point};
+use crate::ports::{Near, NearError, Place, PlaceError, SearchResult};
+
+/// Dimensionality the checks run at
+pub const DIMENSIONALITY: usize = 4;
+
+/// Four distinct, normalized points, the first one being `axis(0)`
+fn sample_points() -> Vec<Point> {
+    vec![
+        axis(0),
+        Point::new(vec![0.8, 0.6, 0.0, 0.0]),
+        Point::new(vec![0.0, 0.0, 0.6, 0.8]),
+        axis(3),
+    ]
+}
+
+/// Unit vector along one dimension
+fn axis(dim: usize) -> Point {
+    let mut dims = vec![0.0; DIMENSIONALITY];
+    dims[dim] = 1.0;
+    Point::new(dims)
+}
+
+/// Run every `Place` check
+pub fn check_place<P: Place>(mut make: impl FnMut(usize) -> P) {
+    place_then_get(make(DIMENSIONALITY));
+    place_rejects_duplicate_id(make(DIMENSIONALITY));
+    place_rejects_wrong_dimensionality(make(DIMENSIONALITY));
+    place_remove_then_get(make(DIMENSIONALITY));
+    place_clear(make(DIMENSIONALITY));
+}
+
+/// Placed points come back unchanged, under distinct IDs
+pub fn place_then_get<P: Place>(mut storage: P) {
+    assert!(storage.is_empty(), "new storage must be empty");
+
+    let mut ids = Vec::new();
+    for (i, point) in sample_points().into_iter().enumerate() {
+        let blob = Blob::new(vec![i as u8; i + 1]);
+        let id = storage.place(point.clone(), blob.clone()).expect("place must accept a valid point");
+        assert!(!ids.contains(&id), "place must assign distinct IDs");
+        ids.push(id);
+
+        let placed = storage.get(id).expect("get must find a placed point");
+        assert_eq!(placed.id, id, "get must return the requested ID");
+        assert_eq!(placed.point, point, "get must return the placed point");
+        assert_eq!(placed.blob, blob, "get must return the placed blob");
+        assert!(storage.contains(id), "contains must see a placed point");
+    }
+
+    assert_eq!(storage.len(), ids.len(), "len must count placed points");
+    assert_eq!(storage.iter().count(), ids.len(), "iter must visit every placed point");
+}
+
+/// `place_with_id` refuses an ID already in use and keeps the original
+pub fn place_rejects_duplicate_id<P: Place>(mut storage: P) {
+    let id = Id::now();
+    storage
+        .place_with_id(id, axis(0), Blob::from_str("first"))
+        .expect("place_with_id must accept a new ID");
+
+    match storage.place_with_id(id, axis(1), Blob::from_str("second")) {
+        Err(PlaceError::DuplicateId(dup)) => assert_eq!(dup, id, "DuplicateId must name the ID"),
+        other => panic!("duplicate ID must fail with DuplicateId, got {:?}", other),
+    }
+    assert_eq!(storage.get(id).map(|p| p.point.clone()), Some(axis(0)), "a rejected place must not overwrite");
+    assert_eq!(storage.len(), 1);
+}
+
+/// Points of the wrong dimensionality are rejected and not stored
+pub fn place_rejects_wrong_dimensionality<P: Place>(mut storage: P) {
+    let wrong = Point::new(vec![1.0; DIMENSIONALITY + 1]);
+    match storage.place(wrong, Blob::empty()) {
+        Err(PlaceError::DimensionalityMismatch { expected, got }) => {
+            assert_eq!((expected, got), (DIMENSIONALITY, DIMENSIONALITY + 1));
+        }
+        other => panic!("wrong dimensionality must fail with DimensionalityMismatch, got {:?}", other),
+    }
+    assert!(storage.is_empty(), "a rejected point must not be stored");
+}
+
+/// Removed points are gone; removing twice is harmless
+pub fn place_remove_then_get<P: Place>(mut storage: P) {
+    let keep = storage.place(axis(0), Blob::empty()).unwrap();
+    let id = storage.place(axis(1), Blob::from_str("gone")).unwrap();
+
+    let removed = storage.remove(id).expect("remove must return the removed point");
+    assert_eq!(removed.id, id);
+    assert_eq!(removed.point, axis(1));
+
+    assert!(storage.get(id).is_none(), "get must not find a removed point");
+    assert!(!storage.contains(id), "contains must not see a removed point");
+    assert!(storage.remove(id).is_none(), "removing twice must return None");
+    assert!(storage.get(keep).is_some(), "remove must not touch other points");
+    assert_eq!(storage.len(), 1);
+}
+
+/// `clear` removes everything
+pub fn place_clear<P: Place>(mut storage: P) {
+    let id = storage.place(axis(0), Blob::empty()).unwrap();
+    storage.place(axis(1), Blob::empty()).unwrap();
+
+    storage.clear();
+    assert!(storage.is_empty(), "clear must remove every point");
+    assert!(storage.get(id).is_none());
+    assert_eq!(storage.iter().count(), 0);
+}
+
+/// Run every `Near` check; `order` is how the index ranks scores
+pub fn check_near<N: Near>(mut make: impl FnMut(usize) -> N, order: ScoreOrder) {
+    near_rejects_wrong_dimensionality(make(DIMENSIONALITY));
+    near_orders_results(make(DIMENSIONALITY), order);
+    near_within_threshold(make(DIMENSIONALITY), order);
+    near_remove_then_search(make(DIMENSIONALITY));
+    near_breaks_ties_by_id(make(DIMENSIONALITY));
+}
+
+/// Points and queries of the wrong dimensionality are rejected
+pub fn near_rejects_wrong_dimensionality<N: Near>(mut index: N) {
+    let wrong = Point::new(vec![1.0; DIMENSIONALITY + 1]);
+    assert!(
+        matches!(index.add(Id::now(), &wrong), Err(NearError::DimensionalityMismatch { .. })),
+        "adding the wrong dimensionality must fail with DimensionalityMismatch"
+    );
+    assert_eq!(index.len(), 0, "a rejected point must not be indexed");
+
+    index.add(Id::now(), &axis(0)).unwrap();
+    assert!(
+        matches!(index.near(&wrong, 1), Err(NearError::DimensionalityMismatch { .. })),
+        "near with the wrong dimensionality must fail with DimensionalityMismatch"
+    );
+    assert!(
+        matches!(index.within(&wrong, 0.0), Err(NearError::DimensionalityMismatch { .. })),
+        "within with the wrong dimensionality must fail with DimensionalityMismatch"
+    );
+}
+
+/// `near` returns at most k results, best first, exact match on top
+pub fn near_orders_results<N: Near>(mut index: N, order: ScoreOrder) {
+    let ids = add_samples(&mut index);
+    assert_eq!(index.len(), ids.len(), "len must count indexed points");
+
+    let results = index.near(&axis(0), 3).expect("near must succeed");
+    assert_eq!(results.len(), 3, "near must return k results when it has them");
+    assert_eq!(results[0].id, ids[0], "an exact match must rank first");
+    assert_eq!(results[1].id, ids[1], "the second-closest point must rank second");
+    assert_ordered(&results, order);
+
+    let all = index.near(&axis(0), 10).unwrap();
+    assert_eq!(all.len(), ids.len(), "near must return every point when k exceeds len");
+    assert_ordered(&all, order);
+
+    assert!(index.near(&axis(0), 0).unwrap().is_empty(), "k = 0 must return nothing");
+}
+
+/// `within` returns exactly the points passing the threshold
+pub fn near_within_threshold<N: Near>(mut index: N, order: ScoreOrder) {
+    let ids = add_samples(&mut index);
+    let all = index.near(&axis(0), ids.len()).unwrap();
+
+    // Threshold between the second and third best scores
+    let threshold = (all[1].score + all[2].score) / 2.0;
+    let within = index.within(&axis(0), threshold).expect("within must succeed");
+
+    let mut got: Vec<Id> = within.iter().map(|r| r.id).collect();
+    got.sort();
+    let mut expected = vec![ids[0], ids[1]];
+    expected.sort();
+    assert_eq!(got, expected, "within must return exactly the points passing the threshold");
+    assert!(within.iter().all(|r| order.passes(r.score, threshold)));
+}
+
+/// Removed points no longer appear in results
+pub fn near_remove_then_search<N: Near>(mut index: N) {
+    let ids = add_samples(&mut index);
+    index.remove(ids[0]).expect("remove must succeed");
+
+    assert_eq!(index.len(), ids.len() - 1, "len must drop after remove");
+    let results = index.near(&axis(0), ids.len()).unwrap();
+    assert!(results.iter().all(|r| r.id != ids[0]), "near must not return a removed point");
+    assert_eq!(results[0].id, ids[1], "the next closest point must take its place");
+}
+
+/// Equal scores come back by ascending ID, whatever the insertion order
+pub fn near_breaks_ties_by_id<N: Near>(mut index: N) {
+    let mut ids: Vec<Id> = (1..=5u8).map(|n| Id::from_bytes([n; 16])).collect();
+    for &id in ids.iter().rev() {
+        index.add(id, &axis(1)).unwrap();
+    }
+    ids.sort();
+
+    let near: Vec<Id> = index.near(&axis(1), ids.len()).unwrap().iter().map(|r| r.id).collect();
+    assert_eq!(near, ids, "near must order equal scores by ascending ID");
+
+    let within: Vec<Id> = index.within(&axis(1), 0.5).unwrap().iter().map(|r| r.id).collect();
+    assert_eq!(within, ids, "within must order equal scores by ascending ID");
+}
+
+fn add_samples<N: Near>(index: &mut N) -> Vec<Id> {
+    sample_points()
+        .iter()
+        .map(|point| {
+            let id = Id::now();
+            index.add(id, point).expect("add must accept a valid point");
+            id
+        })
+        .collect()
+}
+
+fn assert_ordered(results: &[SearchResult], order: ScoreOrder) {
+    for pair in results.windows(2) {
+        assert!(
+            !order.is_better(pair[1].score, pair[0].score),
+            "results must be ordered best first: {} before {}",
+            pair[0].score,
+            pair[1].score
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::index::{FlatIndex, HatIndex};
+    use crate::adapters::storage::{JournaledStorage, MemoryStorage};
+
+    #[test]
+    fn test_storage_adapters_conform() {
+        check_place(MemoryStorage::new);
+        check_place(JournaledStorage::new);
+    }
+
+    #[test]
+    fn test_index_adapters_conform() {
+        check_near(FlatIndex::cosine, ScoreOrder::HigherIsBetter);
+        check_near(FlatIndex::euclidean, ScoreOrder::LowerIsBetter);
+        check_near(HatIndex::cosine, ScoreOrder::HigherIsBetter);
+    }
+}
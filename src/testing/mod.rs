@@ -0,0 +1,53 @@
+//! # Testing
+//!
+//! Helpers for downstream crates testing their own adapters.
+//!
+//! `conformance` checks that a `Place` or `Near` implementation keeps the
+//! port contracts (duplicate IDs, dimensionality errors, remove-then-get,
+//! result ordering).
+//!
+//! With the `arbitrary` feature, core types implement proptest's
+//! `Arbitrary`, so adapter tests can generate points, ids, blobs,
+//! attention states and HAT configs:
+//!
+//! ```rust,ignore
+//! use proptest::prelude::*;
+//! use arms_hat::Point;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn place_then_get(point in any_with::<Point>(Some(64)), blob in any::<Blob>()) {
+//!         let mut storage = MyStorage::new(64);
+//!         let id = storage.place(point.clone(), blob)?;
+//!         prop_assert_eq!(&storage.get(id).unwrap().point, &point);
+//!     }
+//! }
+//! ```
+//!
+//! `temp_path` names a fresh scratch file or directory for adapters that
+//! persist to disk.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub mod conformance;
+
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+
+/// A path under the system temp directory, unique within the process
+///
+/// `<prefix>-<pid>-<n>`, with any file or directory left there by an
+/// earlier run removed.
+pub fn temp_path(prefix: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "{}-{}-{}",
+        prefix,
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}